@@ -0,0 +1,217 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::scope::ScopeMatcher;
+use super::storage::CheckpointStorage;
+use super::{DiffHunk, FileSnapshot, WorktreeDiff, WorktreeFileDiff, WorktreeFileStatus};
+
+/// Unchanged lines shown around each change
+const CONTEXT_LINES: usize = 3;
+
+/// Above this many line pairs the LCS table gets too large; the changed region
+/// is reported as a single replacement hunk instead
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+enum Op<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Line-level edit script between `old` and `new`
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Op<'a>> {
+    // Common prefix and suffix don't need the LCS table
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<Op> = old[..prefix].iter().map(|l| Op::Equal(l)).collect();
+
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_DIFF_CELLS {
+        ops.extend(old_mid.iter().map(|l| Op::Delete(l)));
+        ops.extend(new_mid.iter().map(|l| Op::Insert(l)));
+    } else {
+        // lcs[i][j] = LCS length of old_mid[i..] and new_mid[j..]
+        let (n, m) = (old_mid.len(), new_mid.len());
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old_mid[i] == new_mid[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if old_mid[i] == new_mid[j] {
+                ops.push(Op::Equal(old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+                ops.push(Op::Delete(old_mid[i]));
+                i += 1;
+            } else {
+                ops.push(Op::Insert(new_mid[j]));
+                j += 1;
+            }
+        }
+        ops.extend(old_mid[i..].iter().map(|l| Op::Delete(l)));
+        ops.extend(new_mid[j..].iter().map(|l| Op::Insert(l)));
+    }
+
+    ops.extend(old[old.len() - suffix..].iter().map(|l| Op::Equal(l)));
+    ops
+}
+
+/// Unified-diff hunks between two texts, plus the number of added and deleted lines
+pub fn line_hunks(old: &str, new: &str) -> (Vec<DiffHunk>, usize, usize) {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    let additions = ops.iter().filter(|op| matches!(op, Op::Insert(_))).count();
+    let deletions = ops.iter().filter(|op| matches!(op, Op::Delete(_))).count();
+
+    // Group changes that are within 2 * CONTEXT_LINES of each other into one hunk
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(_)))
+        .map(|(idx, _)| idx)
+        .collect();
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for idx in changed {
+        let start = idx.saturating_sub(CONTEXT_LINES);
+        let end = (idx + CONTEXT_LINES + 1).min(ops.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    // Line numbers (1-based) at each op index
+    let mut old_line = 1;
+    let mut new_line = 1;
+    let mut positions = Vec::with_capacity(ops.len());
+    for op in &ops {
+        positions.push((old_line, new_line));
+        match op {
+            Op::Equal(_) => {
+                old_line += 1;
+                new_line += 1;
+            }
+            Op::Delete(_) => old_line += 1,
+            Op::Insert(_) => new_line += 1,
+        }
+    }
+
+    let hunks = ranges
+        .into_iter()
+        .map(|(start, end)| {
+            let mut hunk = DiffHunk {
+                old_start: positions[start].0,
+                old_lines: 0,
+                new_start: positions[start].1,
+                new_lines: 0,
+                lines: Vec::with_capacity(end - start),
+            };
+            for op in &ops[start..end] {
+                match op {
+                    Op::Equal(line) => {
+                        hunk.old_lines += 1;
+                        hunk.new_lines += 1;
+                        hunk.lines.push(format!(" {}", line));
+                    }
+                    Op::Delete(line) => {
+                        hunk.old_lines += 1;
+                        hunk.lines.push(format!("-{}", line));
+                    }
+                    Op::Insert(line) => {
+                        hunk.new_lines += 1;
+                        hunk.lines.push(format!("+{}", line));
+                    }
+                }
+            }
+            hunk
+        })
+        .collect();
+
+    (hunks, additions, deletions)
+}
+
+fn file_diff(path: PathBuf, status: WorktreeFileStatus, old: &str, new: &str) -> WorktreeFileDiff {
+    let (hunks, additions, deletions) = line_hunks(old, new);
+    WorktreeFileDiff {
+        path,
+        status,
+        additions,
+        deletions,
+        hunks,
+    }
+}
+
+/// Compare a checkpoint's file snapshots with the files currently in `project_path`.
+///
+/// Like a code restore, the snapshots are taken as the complete file set of the
+/// checkpoint: in-scope project files without a snapshot are reported as added.
+pub fn diff_snapshots_to_worktree(
+    checkpoint_id: &str,
+    snapshots: &[FileSnapshot],
+    project_path: &Path,
+    scope: &ScopeMatcher,
+) -> WorktreeDiff {
+    let snapshot_map: HashMap<&PathBuf, &FileSnapshot> = snapshots
+        .iter()
+        .filter(|s| !s.is_deleted)
+        .map(|s| (&s.file_path, s))
+        .collect();
+
+    let current_files = scope.collect_files();
+    let current_set: HashSet<&PathBuf> = current_files.iter().collect();
+
+    let mut files = Vec::new();
+    let mut unchanged = 0;
+
+    for (path, snapshot) in &snapshot_map {
+        if !current_set.contains(path) {
+            files.push(file_diff((*path).clone(), WorktreeFileStatus::Deleted, &snapshot.content, ""));
+            continue;
+        }
+
+        let current = fs::read_to_string(project_path.join(path)).unwrap_or_default();
+        if CheckpointStorage::calculate_file_hash(&current) == snapshot.hash {
+            unchanged += 1;
+        } else {
+            files.push(file_diff(
+                (*path).clone(),
+                WorktreeFileStatus::Modified,
+                &snapshot.content,
+                &current,
+            ));
+        }
+    }
+
+    for path in &current_files {
+        if !snapshot_map.contains_key(path) {
+            let current = fs::read_to_string(project_path.join(path)).unwrap_or_default();
+            files.push(file_diff(path.clone(), WorktreeFileStatus::Added, "", &current));
+        }
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    WorktreeDiff {
+        checkpoint_id: checkpoint_id.to_string(),
+        files,
+        unchanged_files: unchanged,
+    }
+}
@@ -0,0 +1,162 @@
+use anyhow::{bail, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+/// Minimum time between two progress reports of the same job
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Cancellation flags of running jobs, by job id
+static JOBS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Stage a checkpoint creation job is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum CheckpointPhase {
+    /// Hashing project files to find what changed
+    Scanning,
+    /// Reading changed files into snapshots
+    Snapshotting,
+    /// Writing the checkpoint to disk
+    Saving,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Payload of `checkpoint-progress:<session_id>`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CheckpointProgress {
+    pub job_id: String,
+    pub session_id: String,
+    pub phase: CheckpointPhase,
+    /// Files processed in the current phase
+    #[ts(type = "number")]
+    pub files_processed: usize,
+    #[ts(type = "number")]
+    pub total_files: usize,
+    #[ts(type = "number")]
+    pub bytes_processed: u64,
+    /// Set once the checkpoint is saved
+    pub checkpoint_id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// One checkpoint creation, reporting progress and checking for cancellation.
+///
+/// Registered while it exists so `cancel` can reach it by id.
+pub struct CheckpointJob {
+    pub id: String,
+    session_id: String,
+    cancelled: Arc<AtomicBool>,
+    on_progress: Box<dyn Fn(CheckpointProgress) + Send + Sync>,
+    last_report: Mutex<Option<Instant>>,
+}
+
+impl CheckpointJob {
+    pub fn new(
+        session_id: String,
+        on_progress: impl Fn(CheckpointProgress) + Send + Sync + 'static,
+    ) -> Self {
+        let id = uuid::Uuid::new_v4().to_string();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        if let Ok(mut jobs) = JOBS.lock() {
+            jobs.insert(id.clone(), Arc::clone(&cancelled));
+        }
+        Self {
+            id,
+            session_id,
+            cancelled,
+            on_progress: Box::new(on_progress),
+            last_report: Mutex::new(None),
+        }
+    }
+
+    fn progress(
+        &self,
+        phase: CheckpointPhase,
+        files_processed: usize,
+        total_files: usize,
+        bytes_processed: u64,
+    ) -> CheckpointProgress {
+        CheckpointProgress {
+            job_id: self.id.clone(),
+            session_id: self.session_id.clone(),
+            phase,
+            files_processed,
+            total_files,
+            bytes_processed,
+            checkpoint_id: None,
+            error: None,
+        }
+    }
+
+    /// Report progress within a phase; throttled, except for the last file of the phase
+    pub fn report(
+        &self,
+        phase: CheckpointPhase,
+        files_processed: usize,
+        total_files: usize,
+        bytes_processed: u64,
+    ) {
+        if let Ok(mut last) = self.last_report.lock() {
+            let due = last.map_or(true, |at| at.elapsed() >= PROGRESS_INTERVAL);
+            if !due && files_processed < total_files {
+                return;
+            }
+            *last = Some(Instant::now());
+        }
+        (self.on_progress)(self.progress(phase, files_processed, total_files, bytes_processed));
+    }
+
+    /// Report how the job ended
+    pub fn finish(&self, result: &Result<String, String>) {
+        let mut progress = match result {
+            Ok(_) => self.progress(CheckpointPhase::Completed, 0, 0, 0),
+            Err(_) if self.is_cancelled() => self.progress(CheckpointPhase::Cancelled, 0, 0, 0),
+            Err(_) => self.progress(CheckpointPhase::Failed, 0, 0, 0),
+        };
+        match result {
+            Ok(checkpoint_id) => progress.checkpoint_id = Some(checkpoint_id.clone()),
+            Err(e) => progress.error = Some(e.clone()),
+        }
+        (self.on_progress)(progress);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Bail out if the job was cancelled
+    pub fn check_cancelled(&self) -> Result<()> {
+        if self.is_cancelled() {
+            bail!("Checkpoint creation cancelled");
+        }
+        Ok(())
+    }
+}
+
+impl Drop for CheckpointJob {
+    fn drop(&mut self) {
+        if let Ok(mut jobs) = JOBS.lock() {
+            jobs.remove(&self.id);
+        }
+    }
+}
+
+/// Ask a running job to stop; returns false if no such job is running
+pub fn cancel(job_id: &str) -> bool {
+    match JOBS.lock().ok().and_then(|jobs| jobs.get(job_id).cloned()) {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
@@ -3,16 +3,22 @@ use chrono::{DateTime, TimeZone, Utc};
 use log;
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 
 use super::{
+    jobs::{CheckpointJob, CheckpointPhase},
+    scope::{CheckpointScope, ScopeMatcher},
     storage::{self, CheckpointStorage},
     Checkpoint, CheckpointMetadata, CheckpointPaths, CheckpointResult, CheckpointStrategy,
-    FileSnapshot, FileState, FileTracker, RestoreMode, SessionTimeline,
+    ConflictResolution, FileSnapshot, FileState, FileTracker, RestoreConflict, RestoreMode,
+    SessionTimeline,
 };
 
+/// Checkpoints walked back from the current one to find a file's base version
+const MAX_BASE_DEPTH: usize = 100;
+
 /// Manages checkpoint operations for a session
 pub struct CheckpointManager {
     project_id: String,
@@ -22,6 +28,11 @@ pub struct CheckpointManager {
     pub storage: Arc<CheckpointStorage>,
     timeline: Arc<RwLock<SessionTimeline>>,
     current_messages: Arc<RwLock<Vec<String>>>, // JSONL messages
+    scope: Arc<RwLock<Arc<ScopeMatcher>>>,
+    /// Held while a checkpoint is being created, so concurrent creations don't interleave
+    create_lock: Mutex<()>,
+    /// Conflicts of the last restore that were not resolved yet
+    pending_conflicts: RwLock<HashMap<PathBuf, RestoreConflict>>,
 }
 
 impl CheckpointManager {
@@ -48,6 +59,7 @@ impl CheckpointManager {
         let file_tracker = FileTracker {
             tracked_files: HashMap::new(),
         };
+        let scope = ScopeMatcher::new(&CheckpointScope::load(&claude_dir, &project_id), &project_path);
 
         Ok(Self {
             project_id,
@@ -57,9 +69,22 @@ impl CheckpointManager {
             storage,
             timeline: Arc::new(RwLock::new(timeline)),
             current_messages: Arc::new(RwLock::new(Vec::new())),
+            scope: Arc::new(RwLock::new(Arc::new(scope))),
+            create_lock: Mutex::new(()),
+            pending_conflicts: RwLock::new(HashMap::new()),
         })
     }
 
+    /// Reload the project's checkpoint scope (settings and .gitignore) from disk
+    async fn refresh_scope(&self) -> Arc<ScopeMatcher> {
+        let scope = Arc::new(ScopeMatcher::new(
+            &CheckpointScope::load(&self.storage.claude_dir, &self.project_id),
+            &self.project_path,
+        ));
+        *self.scope.write().await = Arc::clone(&scope);
+        scope
+    }
+
     /// Track a new message in the session
     pub async fn track_message(&self, jsonl_message: String) -> Result<()> {
         let mut messages = self.current_messages.write().await;
@@ -121,8 +146,15 @@ impl CheckpointManager {
 
     /// Track a file modification
     pub async fn track_file_modification(&self, file_path: &str) -> Result<()> {
+        // Files outside the checkpoint scope are never tracked or snapshotted
+        let scope = Arc::clone(&*self.scope.read().await);
+        if !scope.includes_file(std::path::Path::new(file_path)) {
+            log::debug!("Skipping file outside checkpoint scope: {}", file_path);
+            return Ok(());
+        }
+
         let mut tracker = self.file_tracker.write().await;
-        let full_path = self.project_path.join(file_path);
+        let full_path = self.project_file(file_path);
 
         // Read current file state
         let (hash, exists, _size, modified) = if full_path.exists() {
@@ -235,6 +267,20 @@ impl CheckpointManager {
         description: Option<String>,
         parent_checkpoint_id: Option<String>,
     ) -> Result<CheckpointResult> {
+        self.create_checkpoint_with_job(description, parent_checkpoint_id, None).await
+    }
+
+    /// Create a checkpoint, reporting progress to `job` and stopping if it is cancelled.
+    ///
+    /// Cancellation is honoured until the checkpoint is written; a cancelled
+    /// creation leaves no checkpoint behind.
+    pub async fn create_checkpoint_with_job(
+        &self,
+        description: Option<String>,
+        parent_checkpoint_id: Option<String>,
+        job: Option<&CheckpointJob>,
+    ) -> Result<CheckpointResult> {
+        let _creating = self.create_lock.lock().await;
         let messages = self.current_messages.read().await;
         let message_index = messages.len().saturating_sub(1);
 
@@ -242,48 +288,36 @@ impl CheckpointManager {
         let (user_prompt, model_used, total_tokens) =
             self.extract_checkpoint_metadata(&messages).await?;
 
-        // Ensure every file in the project is tracked so new checkpoints include all files
-        // Recursively walk the project directory and track each file
-        fn collect_files(
-            dir: &std::path::Path,
-            base: &std::path::Path,
-            files: &mut Vec<std::path::PathBuf>,
-        ) -> Result<(), std::io::Error> {
-            for entry in std::fs::read_dir(dir)? {
-                let entry = entry?;
-                let path = entry.path();
-                if path.is_dir() {
-                    // Skip hidden directories like .git
-                    if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                        if name.starts_with('.') {
-                            continue;
-                        }
-                    }
-                    collect_files(&path, base, files)?;
-                } else if path.is_file() {
-                    // Compute relative path from project root
-                    if let Ok(rel) = path.strip_prefix(base) {
-                        files.push(rel.to_path_buf());
-                    }
-                }
+        // Ensure every in-scope file is tracked so new checkpoints include all files.
+        // The scope is reloaded so edits made with update_checkpoint_scope take effect.
+        let scope = self.refresh_scope().await;
+        let files = scope.collect_files();
+        let mut bytes = 0;
+        for (index, rel) in files.iter().enumerate() {
+            if let Some(job) = job {
+                job.check_cancelled()?;
             }
-            Ok(())
-        }
-        let mut all_files = Vec::new();
-        let project_dir = &self.project_path;
-        let _ = collect_files(project_dir.as_path(), project_dir.as_path(), &mut all_files);
-        for rel in all_files {
             if let Some(p) = rel.to_str() {
                 // Track each file for snapshot
                 let _ = self.track_file_modification(p).await;
             }
+            if let Some(job) = job {
+                bytes += fs::metadata(self.project_file(rel)).map(|m| m.len()).unwrap_or(0);
+                job.report(CheckpointPhase::Scanning, index + 1, files.len(), bytes);
+            }
         }
 
         // Generate checkpoint ID early so snapshots reference it
         let checkpoint_id = storage::CheckpointStorage::generate_checkpoint_id();
 
         // Create file snapshots
-        let file_snapshots = self.create_file_snapshots(&checkpoint_id).await?;
+        let file_snapshots = self.create_file_snapshots(&checkpoint_id, job).await?;
+
+        if let Some(job) = job {
+            // Last chance to cancel: nothing has been written yet
+            job.check_cancelled()?;
+            job.report(CheckpointPhase::Saving, 0, 0, 0);
+        }
 
         // Generate checkpoint struct
         let checkpoint = Checkpoint {
@@ -443,17 +477,26 @@ impl CheckpointManager {
     }
 
     /// Create file snapshots for all tracked modified files
-    async fn create_file_snapshots(&self, checkpoint_id: &str) -> Result<Vec<FileSnapshot>> {
+    async fn create_file_snapshots(
+        &self,
+        checkpoint_id: &str,
+        job: Option<&CheckpointJob>,
+    ) -> Result<Vec<FileSnapshot>> {
         let tracker = self.file_tracker.read().await;
         let mut snapshots = Vec::new();
+        let total = tracker.tracked_files.values().filter(|s| s.is_modified).count();
+        let mut bytes = 0;
 
         for (rel_path, state) in &tracker.tracked_files {
             // Skip files that haven't been modified
             if !state.is_modified {
                 continue;
             }
+            if let Some(job) = job {
+                job.check_cancelled()?;
+            }
 
-            let full_path = self.project_path.join(rel_path);
+            let full_path = self.project_file(rel_path);
 
             let (content, exists, permissions, size, current_hash) = if full_path.exists() {
                 let content = fs::read_to_string(&full_path).unwrap_or_default();
@@ -489,6 +532,11 @@ impl CheckpointManager {
                 permissions,
                 size,
             });
+
+            if let Some(job) = job {
+                bytes += size;
+                job.report(CheckpointPhase::Snapshotting, snapshots.len(), total, bytes);
+            }
         }
 
         Ok(snapshots)
@@ -507,6 +555,7 @@ impl CheckpointManager {
 
         let mut warnings = Vec::new();
         let mut files_processed = 0;
+        let mut conflicts = Vec::new();
 
         // Execute restore based on mode
         match mode {
@@ -521,6 +570,7 @@ impl CheckpointManager {
                 let result = self.restore_files_only(&file_snapshots).await?;
                 files_processed = result.0;
                 warnings = result.1;
+                conflicts = result.2;
             }
             RestoreMode::Both => {
                 log::info!("Restoring both code and conversation for checkpoint: {}", checkpoint_id);
@@ -528,6 +578,7 @@ impl CheckpointManager {
                 let result = self.restore_full(&file_snapshots, &messages).await?;
                 files_processed = result.0;
                 warnings = result.1;
+                conflicts = result.2;
             }
         }
 
@@ -539,6 +590,7 @@ impl CheckpointManager {
             checkpoint: checkpoint.clone(),
             files_processed,
             warnings,
+            conflicts,
         })
     }
 
@@ -555,21 +607,18 @@ impl CheckpointManager {
         Ok(())
     }
 
-    /// Restore only code files
+    /// Restore only code files. Files with changes no checkpoint holds are left alone
+    /// and returned as conflicts, to be settled with `resolve_restore_conflict`.
     async fn restore_files_only(
         &self,
         file_snapshots: &[FileSnapshot],
-    ) -> Result<(usize, Vec<String>)> {
+    ) -> Result<(usize, Vec<String>, Vec<RestoreConflict>)> {
         let mut warnings = Vec::new();
         let mut files_processed = 0;
 
-        // First, collect all files currently in the project
-        let mut current_files = Vec::new();
-        let _ = Self::collect_all_project_files(
-            &self.project_path,
-            &self.project_path,
-            &mut current_files,
-        );
+        // First, collect all in-scope files currently in the project;
+        // files outside the scope are left alone
+        let current_files = self.refresh_scope().await.collect_files();
 
         // Create a set of files that should exist after restore
         let mut checkpoint_files = std::collections::HashSet::new();
@@ -579,10 +628,24 @@ impl CheckpointManager {
             }
         }
 
+        let conflicts = self.detect_restore_conflicts(&current_files, file_snapshots).await;
+        if !conflicts.is_empty() {
+            log::warn!(
+                "Restore left {} file(s) with uncheckpointed changes untouched",
+                conflicts.len()
+            );
+        }
+        *self.pending_conflicts.write().await = conflicts
+            .iter()
+            .map(|conflict| (conflict.file_path.clone(), conflict.clone()))
+            .collect();
+        let conflicted =
+            |path: &PathBuf| conflicts.iter().any(|conflict| &conflict.file_path == path);
+
         // Delete files that exist now but shouldn't exist in the checkpoint
         for current_file in current_files {
-            if !checkpoint_files.contains(&current_file) {
-                let full_path = self.project_path.join(&current_file);
+            if !checkpoint_files.contains(&current_file) && !conflicted(&current_file) {
+                let full_path = self.project_file(&current_file);
                 match fs::remove_file(&full_path) {
                     Ok(_) => {
                         files_processed += 1;
@@ -603,7 +666,7 @@ impl CheckpointManager {
         let _ = Self::remove_empty_dirs(&self.project_path, &self.project_path);
 
         // Restore files from checkpoint
-        for snapshot in file_snapshots {
+        for snapshot in file_snapshots.iter().filter(|snapshot| !conflicted(&snapshot.file_path)) {
             match self.restore_file_snapshot(snapshot).await {
                 Ok(_) => files_processed += 1,
                 Err(e) => warnings.push(format!(
@@ -618,7 +681,7 @@ impl CheckpointManager {
         let mut tracker = self.file_tracker.write().await;
         tracker.tracked_files.clear();
         for snapshot in file_snapshots {
-            if !snapshot.is_deleted {
+            if !snapshot.is_deleted && !conflicted(&snapshot.file_path) {
                 tracker.tracked_files.insert(
                     snapshot.file_path.clone(),
                     FileState {
@@ -630,51 +693,158 @@ impl CheckpointManager {
                 );
             }
         }
+        drop(tracker);
+        for conflict in &conflicts {
+            if let Some(path) = conflict.file_path.to_str() {
+                let _ = self.track_file_modification(path).await;
+            }
+        }
 
         log::info!("Restored {} files", files_processed);
-        Ok((files_processed, warnings))
+        Ok((files_processed, warnings, conflicts))
     }
 
-    /// Restore both code and conversation (full restore)
-    async fn restore_full(
+    /// Each file's latest checkpointed version, from the current checkpoint back
+    /// through its ancestors (checkpoints only store the files that changed).
+    /// A `None` content means the file was deleted at that checkpoint.
+    async fn checkpointed_versions(&self) -> HashMap<PathBuf, Option<String>> {
+        let mut versions = HashMap::new();
+        let mut next = self.timeline.read().await.current_checkpoint_id.clone();
+        let mut depth = 0;
+        while let Some(checkpoint_id) = next.take() {
+            if depth >= MAX_BASE_DEPTH {
+                break;
+            }
+            depth += 1;
+            let Ok((checkpoint, snapshots, _)) =
+                self.storage.load_checkpoint(&self.project_id, &self.session_id, &checkpoint_id)
+            else {
+                break;
+            };
+            for snapshot in snapshots {
+                versions
+                    .entry(snapshot.file_path)
+                    .or_insert((!snapshot.is_deleted).then_some(snapshot.content));
+            }
+            next = checkpoint.parent_checkpoint_id;
+        }
+        versions
+    }
+
+    /// Files whose current content differs from both their base version and the
+    /// version the restore would leave
+    async fn detect_restore_conflicts(
         &self,
+        current_files: &[PathBuf],
         file_snapshots: &[FileSnapshot],
-        messages: &str,
-    ) -> Result<(usize, Vec<String>)> {
-        // Restore files first
-        let (files_processed, warnings) = self.restore_files_only(file_snapshots).await?;
-
-        // Then restore messages
-        self.restore_messages_only(messages).await?;
+    ) -> Vec<RestoreConflict> {
+        let targets: HashMap<&PathBuf, Option<&str>> = file_snapshots
+            .iter()
+            .map(|snapshot| {
+                let content = (!snapshot.is_deleted).then_some(snapshot.content.as_str());
+                (&snapshot.file_path, content)
+            })
+            .collect();
+        let mut candidates: Vec<&PathBuf> =
+            current_files.iter().chain(targets.keys().copied()).collect();
+        candidates.sort();
+        candidates.dedup();
+
+        let bases = self.checkpointed_versions().await;
+        let mut conflicts = Vec::new();
+        for path in candidates {
+            let full_path = self.project_file(path);
+            // Snapshots store unreadable (binary) files as empty text; compare the same way
+            let mine = full_path
+                .is_file()
+                .then(|| fs::read_to_string(&full_path).unwrap_or_default());
+            let target = targets.get(path).copied().flatten();
+            if mine.as_deref() == target {
+                continue;
+            }
+            let base = bases.get(path);
+            let changed_since_base = match base {
+                Some(base) => mine != *base,
+                // Never checkpointed: only an existing file has anything to lose
+                None => mine.is_some(),
+            };
+            if changed_since_base {
+                conflicts.push(RestoreConflict {
+                    file_path: path.clone(),
+                    base_content: base.cloned().flatten(),
+                    mine_content: mine,
+                    checkpoint_content: target.map(str::to_string),
+                });
+            }
+        }
+        conflicts
+    }
 
-        Ok((files_processed, warnings))
+    /// Conflicts of the last restore that are still unresolved
+    pub async fn pending_restore_conflicts(&self) -> Vec<RestoreConflict> {
+        let mut conflicts: Vec<RestoreConflict> =
+            self.pending_conflicts.read().await.values().cloned().collect();
+        conflicts.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        conflicts
     }
 
-    /// Helper: Collect all project files (made static for reuse)
-    fn collect_all_project_files(
-        dir: &std::path::Path,
-        base: &std::path::Path,
-        files: &mut Vec<std::path::PathBuf>,
-    ) -> Result<(), std::io::Error> {
-        for entry in std::fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_dir() {
-                // Skip hidden directories like .git
-                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                    if name.starts_with('.') {
-                        continue;
+    /// Settle a conflict of the last restore; returns the conflicts still unresolved
+    pub async fn resolve_restore_conflict(
+        &self,
+        file_path: &Path,
+        resolution: ConflictResolution,
+    ) -> Result<Vec<RestoreConflict>> {
+        let conflict = self
+            .pending_conflicts
+            .read()
+            .await
+            .get(file_path)
+            .cloned()
+            .with_context(|| format!("No pending restore conflict for {}", file_path.display()))?;
+
+        let content = match resolution {
+            ConflictResolution::KeepMine => None,
+            ConflictResolution::TakeCheckpoint => Some(conflict.checkpoint_content.clone()),
+            ConflictResolution::Merged { content } => Some(Some(content)),
+        };
+        if let Some(content) = content {
+            let full_path = self.project_file(file_path);
+            match content {
+                Some(content) => {
+                    if let Some(parent) = full_path.parent() {
+                        fs::create_dir_all(parent).context("Failed to create parent directories")?;
                     }
+                    fs::write(&full_path, content).context("Failed to write file")?;
                 }
-                Self::collect_all_project_files(&path, base, files)?;
-            } else if path.is_file() {
-                // Compute relative path from project root
-                if let Ok(rel) = path.strip_prefix(base) {
-                    files.push(rel.to_path_buf());
+                None => {
+                    if full_path.exists() {
+                        fs::remove_file(&full_path).context("Failed to delete file")?;
+                    }
                 }
             }
         }
-        Ok(())
+        if let Some(path) = file_path.to_str() {
+            self.track_file_modification(path).await?;
+        }
+
+        self.pending_conflicts.write().await.remove(file_path);
+        log::info!("Resolved restore conflict for {}", file_path.display());
+        Ok(self.pending_restore_conflicts().await)
+    }
+
+    /// Restore both code and conversation (full restore)
+    async fn restore_full(
+        &self,
+        file_snapshots: &[FileSnapshot],
+        messages: &str,
+    ) -> Result<(usize, Vec<String>, Vec<RestoreConflict>)> {
+        // Restore files first
+        let (files_processed, warnings, conflicts) = self.restore_files_only(file_snapshots).await?;
+
+        // Then restore messages
+        self.restore_messages_only(messages).await?;
+
+        Ok((files_processed, warnings, conflicts))
     }
 
     /// Helper: Remove empty directories (made static for reuse)
@@ -713,9 +883,14 @@ impl CheckpointManager {
             .await
     }
 
+    /// Absolute path of a project file, in the spelling filesystem calls need
+    fn project_file(&self, rel: impl AsRef<Path>) -> PathBuf {
+        crate::paths::long_path(&self.project_path.join(rel))
+    }
+
     /// Restore a single file from snapshot
     async fn restore_file_snapshot(&self, snapshot: &FileSnapshot) -> Result<()> {
-        let full_path = self.project_path.join(&snapshot.file_path);
+        let full_path = self.project_file(&snapshot.file_path);
 
         if snapshot.is_deleted {
             // Delete the file if it exists
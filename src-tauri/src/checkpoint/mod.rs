@@ -1,9 +1,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+pub mod diff;
+pub mod jobs;
 pub mod manager;
+pub mod safety;
+pub mod scope;
 pub mod state;
 pub mod storage;
 
@@ -138,6 +142,9 @@ pub struct CheckpointResult {
     pub files_processed: usize,
     /// Any warnings during the operation
     pub warnings: Vec<String>,
+    /// Files a restore left untouched because they had changes no checkpoint holds
+    #[serde(default)]
+    pub conflicts: Vec<RestoreConflict>,
 }
 
 /// Diff between two checkpoints
@@ -170,6 +177,88 @@ pub struct FileDiff {
     pub diff_content: Option<String>,
 }
 
+/// How a file differs between a checkpoint and the working tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorktreeFileStatus {
+    /// Content changed since the checkpoint
+    Modified,
+    /// File exists now but not in the checkpoint
+    Added,
+    /// File was in the checkpoint but no longer exists
+    Deleted,
+}
+
+/// A unified-diff hunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffHunk {
+    /// First line of the hunk in the checkpoint version (1-based)
+    pub old_start: usize,
+    pub old_lines: usize,
+    /// First line of the hunk in the working tree version (1-based)
+    pub new_start: usize,
+    pub new_lines: usize,
+    /// Lines prefixed with ' ', '-' or '+'
+    pub lines: Vec<String>,
+}
+
+/// Diff of a single file between a checkpoint and the working tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeFileDiff {
+    pub path: PathBuf,
+    pub status: WorktreeFileStatus,
+    pub additions: usize,
+    pub deletions: usize,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// Changes in the working tree since a checkpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorktreeDiff {
+    pub checkpoint_id: String,
+    /// Changed files, sorted by path
+    pub files: Vec<WorktreeFileDiff>,
+    /// Files identical to their checkpoint snapshot
+    pub unchanged_files: usize,
+}
+
+/// Disk usage of a project's checkpoints
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CheckpointStorageStats {
+    pub project_id: String,
+    pub sessions: usize,
+    pub checkpoints: usize,
+    /// Blobs in the project's content-addressed store
+    pub blob_count: usize,
+    /// Compressed size of those blobs on disk
+    pub blob_bytes: u64,
+    /// Uncompressed size of the file snapshots and transcripts all checkpoints reference,
+    /// counting content once per checkpoint that references it
+    pub logical_bytes: u64,
+    /// Blobs no checkpoint references any more
+    pub unreferenced_blobs: usize,
+    pub unreferenced_bytes: u64,
+    /// Data still in the per-session layout (content pools and whole transcripts per
+    /// checkpoint), which compaction moves into the shared store
+    pub legacy_bytes: u64,
+    /// Everything under the project's `.timelines` directory
+    pub total_bytes: u64,
+}
+
+/// What `compact_checkpoint_storage` did
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CheckpointCompaction {
+    pub projects: usize,
+    /// Blobs moved from per-session content pools into the shared store
+    pub migrated_blobs: usize,
+    /// Whole checkpoint transcripts split into shared chunks
+    pub migrated_transcripts: usize,
+    pub removed_blobs: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    pub warnings: Vec<String>,
+}
+
 /// Strategy for restoring a checkpoint
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -182,6 +271,33 @@ pub enum RestoreMode {
     Both,
 }
 
+/// A file whose restore would discard changes not captured by any checkpoint: its
+/// current content differs from both its last checkpointed version (the base) and
+/// the checkpoint being restored. Holds the inputs of a three-way merge.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreConflict {
+    /// Relative path from project root
+    pub file_path: PathBuf,
+    /// Last checkpointed version before the restore; None if no checkpoint has the file
+    pub base_content: Option<String>,
+    /// Current content; None if the file was deleted
+    pub mine_content: Option<String>,
+    /// Version in the restored checkpoint; None if the checkpoint doesn't have the file
+    pub checkpoint_content: Option<String>,
+}
+
+/// How to settle a restore conflict
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// Keep the current file as it is
+    KeepMine,
+    /// Apply the restored checkpoint's version
+    TakeCheckpoint,
+    /// Write content merged by the user
+    Merged { content: String },
+}
+
 impl Default for RestoreMode {
     fn default() -> Self {
         RestoreMode::Both
@@ -234,23 +350,35 @@ pub struct CheckpointPaths {
     pub timeline_file: PathBuf,
     pub checkpoints_dir: PathBuf,
     pub files_dir: PathBuf,
+    /// Compressed blobs named by content hash, shared by all sessions of the project
+    pub blobs_dir: PathBuf,
+    /// Safety checkpoints taken before destructive tool calls
+    pub safety_dir: PathBuf,
 }
 
 impl CheckpointPaths {
     pub fn new(claude_dir: &PathBuf, project_id: &str, session_id: &str) -> Self {
-        let base_dir = claude_dir
-            .join("projects")
-            .join(project_id)
-            .join(".timelines")
-            .join(session_id);
+        let base_dir = Self::timelines_dir(claude_dir, project_id).join(session_id);
 
         Self {
             timeline_file: base_dir.join("timeline.json"),
             checkpoints_dir: base_dir.join("checkpoints"),
             files_dir: base_dir.join("files"),
+            blobs_dir: Self::blobs_dir(claude_dir, project_id),
+            safety_dir: base_dir.join("safety"),
         }
     }
 
+    /// Directory holding the timelines of every session of a project
+    pub fn timelines_dir(claude_dir: &Path, project_id: &str) -> PathBuf {
+        claude_dir.join("projects").join(project_id).join(".timelines")
+    }
+
+    /// The project's blob store
+    pub fn blobs_dir(claude_dir: &Path, project_id: &str) -> PathBuf {
+        Self::timelines_dir(claude_dir, project_id).join(".blobs")
+    }
+
     pub fn checkpoint_dir(&self, checkpoint_id: &str) -> PathBuf {
         self.checkpoints_dir.join(checkpoint_id)
     }
@@ -259,14 +387,31 @@ impl CheckpointPaths {
         self.checkpoint_dir(checkpoint_id).join("metadata.json")
     }
 
+    /// Whole compressed transcript, as written before transcripts were chunked
     pub fn checkpoint_messages_file(&self, checkpoint_id: &str) -> PathBuf {
         self.checkpoint_dir(checkpoint_id).join("messages.jsonl")
     }
 
+    /// List of the blobs a checkpoint's transcript is made of
+    pub fn checkpoint_message_chunks_file(&self, checkpoint_id: &str) -> PathBuf {
+        self.checkpoint_dir(checkpoint_id).join("messages.chunks.json")
+    }
+
+    /// Per-session pool blobs were written to before the project-wide store
+    pub fn legacy_content_pool_dir(&self) -> PathBuf {
+        self.files_dir.join("content_pool")
+    }
+
+    /// Blobs are sharded by the first two characters of their hash
+    pub fn blob_path(&self, hash: &str) -> PathBuf {
+        let shard = hash.get(..2).unwrap_or("__");
+        self.blobs_dir.join(shard).join(hash)
+    }
+
     #[allow(dead_code)]
     pub fn file_snapshot_path(&self, _checkpoint_id: &str, file_hash: &str) -> PathBuf {
-        // In content-addressable storage, files are stored by hash in the content pool
-        self.files_dir.join("content_pool").join(file_hash)
+        // In content-addressable storage, files are stored by hash in the blob store
+        self.blob_path(file_hash)
     }
 
     #[allow(dead_code)]
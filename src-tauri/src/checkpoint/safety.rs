@@ -0,0 +1,440 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use ts_rs::TS;
+use walkdir::WalkDir;
+
+use super::CheckpointPaths;
+
+/// Most files one safety checkpoint copies
+const MAX_SAFETY_FILES: usize = 5000;
+
+/// Most bytes one safety checkpoint copies
+const MAX_SAFETY_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Directories not copied when a deleted directory contains them; they can be rebuilt
+const REBUILDABLE_DIRS: &[&str] = &["node_modules", "target", ".git", "__pycache__", ".venv", "dist", "build"];
+
+/// Local database files snapshotted before a migration
+const DB_EXTENSIONS: &[&str] = &["db", "sqlite", "sqlite3"];
+
+/// Command fragments that run or roll back schema migrations or drop data
+const MIGRATION_PATTERNS: &[&str] = &[
+    "prisma migrate",
+    "prisma db push",
+    "diesel migration",
+    "sqlx migrate",
+    "alembic upgrade",
+    "alembic downgrade",
+    "manage.py migrate",
+    "rails db:",
+    "rake db:",
+    "knex migrate",
+    "sequelize db:migrate",
+    "typeorm migration:",
+    "flyway migrate",
+    "drizzle-kit push",
+    "dbmate ",
+    "goose up",
+    "goose down",
+    "dropdb ",
+    "drop table",
+    "drop database",
+    "truncate table",
+];
+
+/// Manifest file inside each safety checkpoint directory
+const MANIFEST_FILE: &str = "safety.json";
+
+/// Kind of destructive action a safety checkpoint guards against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export)]
+pub enum DestructiveKind {
+    /// `rm` of files or directories
+    Delete,
+    /// `git reset --hard`, `git checkout -- …`, `git restore`, `git clean -f`
+    GitDiscard,
+    /// Schema migrations and SQL that drops data
+    DbMigration,
+}
+
+/// A destructive Bash command found in a tool call
+#[derive(Debug, Clone)]
+pub struct DestructiveAction {
+    pub kind: DestructiveKind,
+    /// Path operands of the command, as written (deletes only)
+    pub targets: Vec<String>,
+    /// For `git clean`: whether ignored files are removed too (`-x`)
+    pub include_ignored: bool,
+}
+
+/// Copies of the files a destructive tool call was about to touch, taken when the
+/// call appeared in the stream. Payload of `safety-checkpoint-created:<session_id>`.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SafetyCheckpoint {
+    pub id: String,
+    pub session_id: String,
+    pub project_path: String,
+    /// The `tool_use` block that triggered the snapshot
+    pub tool_use_id: Option<String>,
+    pub command: String,
+    pub kind: DestructiveKind,
+    #[ts(type = "string")]
+    pub created_at: DateTime<Utc>,
+    /// Snapshotted files, relative to the project
+    pub files: Vec<String>,
+    /// Some files were left out because of the size limits
+    pub truncated: bool,
+    #[ts(type = "string | null")]
+    pub restored_at: Option<DateTime<Utc>>,
+}
+
+/// Split a shell command into words, honoring quotes and backslash escapes
+fn shell_words(segment: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = segment.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => current.extend(chars.next()),
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, '\\') => {
+                current.extend(chars.next());
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    words
+}
+
+/// Split a command line into simple commands on `&&`, `||`, `;`, `|` and newlines
+fn command_segments(command: &str) -> Vec<String> {
+    command
+        .replace("&&", "\n")
+        .replace("||", "\n")
+        .split(['\n', ';', '|'])
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Words of a simple command without `sudo` and leading `VAR=value` assignments
+fn command_words(segment: &str) -> Vec<String> {
+    let words = shell_words(segment);
+    let start = words
+        .iter()
+        .position(|word| word != "sudo" && !(word.contains('=') && !word.starts_with('-')))
+        .unwrap_or(words.len());
+    words[start..].to_vec()
+}
+
+fn detect_segment(segment: &str) -> Option<DestructiveAction> {
+    let lowered = segment.to_lowercase();
+    if MIGRATION_PATTERNS.iter().any(|pattern| lowered.contains(pattern)) {
+        return Some(DestructiveAction {
+            kind: DestructiveKind::DbMigration,
+            targets: Vec::new(),
+            include_ignored: false,
+        });
+    }
+
+    let words = command_words(segment);
+    let program = words.first()?.rsplit('/').next().unwrap_or_default();
+    match program {
+        "rm" => {
+            let mut targets = Vec::new();
+            let mut operands_only = false;
+            for word in &words[1..] {
+                if !operands_only && word == "--" {
+                    operands_only = true;
+                } else if operands_only || !word.starts_with('-') {
+                    targets.push(word.clone());
+                }
+            }
+            (!targets.is_empty()).then_some(DestructiveAction {
+                kind: DestructiveKind::Delete,
+                targets,
+                include_ignored: false,
+            })
+        }
+        "git" => {
+            // Skip global options such as `-C <dir>` to find the subcommand
+            let mut args = words[1..].iter();
+            let subcommand = loop {
+                let word = args.next()?;
+                if word == "-C" || word == "-c" {
+                    args.next();
+                } else if !word.starts_with('-') {
+                    break word.as_str();
+                }
+            };
+            let rest: Vec<&str> = args.map(String::as_str).collect();
+            let discards = match subcommand {
+                "reset" => rest.contains(&"--hard"),
+                "checkout" => rest.iter().any(|arg| matches!(*arg, "--" | "." | "-f" | "--force")),
+                "restore" => !rest.contains(&"--staged") || rest.contains(&"--worktree"),
+                "clean" => rest
+                    .iter()
+                    .any(|arg| *arg == "--force" || (arg.starts_with('-') && !arg.starts_with("--") && arg.contains('f'))),
+                _ => false,
+            };
+            let include_ignored = subcommand == "clean"
+                && rest.iter().any(|arg| arg.starts_with('-') && !arg.starts_with("--") && arg.contains('x'));
+            discards.then_some(DestructiveAction {
+                kind: DestructiveKind::GitDiscard,
+                targets: Vec::new(),
+                include_ignored,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Find the first destructive simple command in a Bash command line
+pub fn detect(command: &str) -> Option<DestructiveAction> {
+    command_segments(command).iter().find_map(|segment| detect_segment(segment))
+}
+
+/// Lexically resolve `.` and `..` so paths can be checked against the project root
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Absolute paths of the existing project files and directories `rm` operands name
+fn resolve_targets(project_path: &Path, targets: &[String]) -> Vec<PathBuf> {
+    let mut resolved = Vec::new();
+    for target in targets {
+        let path = project_path.join(target);
+        let pattern = path.to_string_lossy().to_string();
+        if target.contains(['*', '?', '[']) {
+            if let Ok(paths) = glob::glob(&pattern) {
+                resolved.extend(paths.flatten());
+            }
+        } else {
+            resolved.push(path);
+        }
+    }
+    resolved
+        .into_iter()
+        .map(|path| normalize(&path))
+        .filter(|path| path.starts_with(project_path) && path.exists())
+        .collect()
+}
+
+fn git_files(project_path: &Path, args: &[&str]) -> Vec<PathBuf> {
+    let output = Command::new("git").args(args).current_dir(project_path).output();
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| project_path.join(line))
+            .filter(|path| path.is_file())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Local database files of the project, skipping rebuildable and hidden directories
+fn database_files(project_path: &Path) -> Vec<PathBuf> {
+    WalkDir::new(project_path)
+        .max_depth(4)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0 || !(name.starts_with('.') || REBUILDABLE_DIRS.contains(&name.as_ref()))
+        })
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .and_then(|e| e.to_str())
+                .map_or(false, |ext| DB_EXTENSIONS.contains(&ext))
+        })
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Files the action may delete or overwrite
+fn affected_files(project_path: &Path, action: &DestructiveAction) -> Vec<PathBuf> {
+    match action.kind {
+        DestructiveKind::Delete => resolve_targets(project_path, &action.targets)
+            .into_iter()
+            .flat_map(|target| {
+                WalkDir::new(target)
+                    .follow_links(false)
+                    .into_iter()
+                    .filter_entry(|entry| {
+                        entry.depth() == 0
+                            || !REBUILDABLE_DIRS.contains(&entry.file_name().to_string_lossy().as_ref())
+                    })
+                    .flatten()
+                    .filter(|entry| entry.file_type().is_file())
+                    .map(|entry| entry.into_path())
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+        DestructiveKind::GitDiscard => {
+            let mut files = git_files(project_path, &["diff", "--name-only", "HEAD"]);
+            let untracked: &[&str] = if action.include_ignored {
+                &["ls-files", "--others"]
+            } else {
+                &["ls-files", "--others", "--exclude-standard"]
+            };
+            files.extend(git_files(project_path, untracked));
+            files
+        }
+        DestructiveKind::DbMigration => database_files(project_path),
+    }
+}
+
+fn safety_dir(claude_dir: &Path, project_id: &str, session_id: &str) -> PathBuf {
+    CheckpointPaths::new(&claude_dir.to_path_buf(), project_id, session_id).safety_dir
+}
+
+fn save_manifest(dir: &Path, checkpoint: &SafetyCheckpoint) -> Result<()> {
+    fs::write(dir.join(MANIFEST_FILE), serde_json::to_string_pretty(checkpoint)?)
+        .context("Failed to write safety checkpoint manifest")
+}
+
+/// Copy the files `action` is about to touch into a new safety checkpoint.
+///
+/// Returns `None` when none of them exist, e.g. a migration of a remote database.
+pub fn create(
+    claude_dir: &Path,
+    project_id: &str,
+    session_id: &str,
+    project_path: &Path,
+    tool_use_id: Option<String>,
+    command: &str,
+    action: &DestructiveAction,
+) -> Result<Option<SafetyCheckpoint>> {
+    let mut candidates = affected_files(project_path, action);
+    candidates.sort();
+    candidates.dedup();
+    if candidates.is_empty() {
+        return Ok(None);
+    }
+
+    let id = format!("{}-{}", Utc::now().format("%Y%m%d%H%M%S"), &uuid::Uuid::new_v4().to_string()[..8]);
+    let dir = safety_dir(claude_dir, project_id, session_id).join(&id);
+    let files_dir = dir.join("files");
+    fs::create_dir_all(&files_dir).context("Failed to create safety checkpoint directory")?;
+
+    let mut files = Vec::new();
+    let mut bytes = 0u64;
+    let mut truncated = false;
+    for path in candidates {
+        let Ok(rel) = path.strip_prefix(project_path) else {
+            continue;
+        };
+        let source = crate::paths::long_path(&path);
+        let size = fs::metadata(&source).map(|m| m.len()).unwrap_or(0);
+        if files.len() >= MAX_SAFETY_FILES || bytes + size > MAX_SAFETY_BYTES {
+            truncated = true;
+            continue;
+        }
+        let dest = crate::paths::long_path(&files_dir.join(rel));
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match fs::copy(&source, &dest) {
+            Ok(copied) => {
+                bytes += copied;
+                files.push(rel.to_string_lossy().replace('\\', "/"));
+            }
+            Err(e) => log::warn!("Failed to snapshot {:?} before destructive command: {}", path, e),
+        }
+    }
+
+    let checkpoint = SafetyCheckpoint {
+        id,
+        session_id: session_id.to_string(),
+        project_path: project_path.to_string_lossy().to_string(),
+        tool_use_id,
+        command: command.to_string(),
+        kind: action.kind,
+        created_at: Utc::now(),
+        files,
+        truncated,
+        restored_at: None,
+    };
+    save_manifest(&dir, &checkpoint)?;
+    Ok(Some(checkpoint))
+}
+
+/// Safety checkpoints of a session, newest first
+pub fn list(claude_dir: &Path, project_id: &str, session_id: &str) -> Vec<SafetyCheckpoint> {
+    let Ok(entries) = fs::read_dir(safety_dir(claude_dir, project_id, session_id)) else {
+        return Vec::new();
+    };
+    let mut checkpoints: Vec<SafetyCheckpoint> = entries
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path().join(MANIFEST_FILE)).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    checkpoints.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    checkpoints
+}
+
+/// Copy a safety checkpoint's files back into the project. Only those files are
+/// written; everything else the session changed since is left alone.
+pub fn restore(claude_dir: &Path, project_id: &str, session_id: &str, checkpoint_id: &str) -> Result<SafetyCheckpoint> {
+    let dir = safety_dir(claude_dir, project_id, session_id).join(checkpoint_id);
+    let mut checkpoint: SafetyCheckpoint = serde_json::from_str(
+        &fs::read_to_string(dir.join(MANIFEST_FILE)).context("Safety checkpoint not found")?,
+    )?;
+    let project_path = PathBuf::from(&checkpoint.project_path);
+
+    for rel in &checkpoint.files {
+        let dest = crate::paths::long_path(&project_path.join(rel));
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let source = crate::paths::long_path(&dir.join("files").join(rel));
+        fs::copy(source, &dest).with_context(|| format!("Failed to restore {}", rel))?;
+    }
+
+    checkpoint.restored_at = Some(Utc::now());
+    save_manifest(&dir, &checkpoint)?;
+    log::info!("Restored {} files from safety checkpoint {}", checkpoint.files.len(), checkpoint.id);
+    Ok(checkpoint)
+}
@@ -0,0 +1,258 @@
+use anyhow::{Context, Result};
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directories never worth snapshotting, excluded unless the user removes them
+const DEFAULT_EXCLUDES: &[&str] = &[
+    "**/node_modules",
+    "**/target",
+    "**/dist",
+    "**/build",
+    "**/__pycache__",
+];
+
+/// Which project files checkpoints capture.
+///
+/// Stored per project; the file tracker, the snapshotter and code restores
+/// all use it, so files outside the scope are neither captured nor deleted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointScope {
+    /// Globs (relative to the project root) a file must match; empty means every file
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Globs for files and directories to skip; a matching directory skips everything below it
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Also skip whatever the project's root .gitignore ignores
+    #[serde(default = "default_true")]
+    pub respect_gitignore: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for CheckpointScope {
+    fn default() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: DEFAULT_EXCLUDES.iter().map(|s| s.to_string()).collect(),
+            respect_gitignore: true,
+        }
+    }
+}
+
+impl CheckpointScope {
+    fn path(claude_dir: &Path, project_id: &str) -> PathBuf {
+        claude_dir
+            .join("projects")
+            .join(project_id)
+            .join(".timelines")
+            .join("checkpoint_scope.json")
+    }
+
+    /// Load a project's scope, falling back to the defaults
+    pub fn load(claude_dir: &Path, project_id: &str) -> Self {
+        fs::read_to_string(Self::path(claude_dir, project_id))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, claude_dir: &Path, project_id: &str) -> Result<()> {
+        let path = Self::path(claude_dir, project_id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create timelines directory")?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?).context("Failed to write checkpoint scope")
+    }
+
+    /// Check every glob compiles
+    pub fn validate(&self) -> Result<(), String> {
+        for glob in self.include.iter().chain(&self.exclude) {
+            Pattern::new(glob).map_err(|e| format!("Invalid glob '{}': {}", glob, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returned by `get_checkpoint_scope`: the settings plus what they capture right now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointScopeInfo {
+    pub scope: CheckpointScope,
+    /// Number of project files a checkpoint would capture
+    pub file_count: usize,
+    /// The first `SCOPE_FILE_SAMPLE` captured files, sorted
+    pub files: Vec<PathBuf>,
+}
+
+/// How many in-scope files `get_checkpoint_scope` lists
+pub const SCOPE_FILE_SAMPLE: usize = 500;
+
+/// One .gitignore rule
+struct IgnoreRule {
+    pattern: Pattern,
+    negated: bool,
+    dir_only: bool,
+}
+
+/// Compiled `CheckpointScope` for a project directory
+pub struct ScopeMatcher {
+    project_path: PathBuf,
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    gitignore: Vec<IgnoreRule>,
+}
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+fn compile(globs: &[String]) -> Vec<Pattern> {
+    globs
+        .iter()
+        .filter_map(|g| match Pattern::new(g) {
+            Ok(pattern) => Some(pattern),
+            Err(e) => {
+                log::warn!("Ignoring invalid checkpoint glob '{}': {}", g, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Parse the root .gitignore into glob rules (root-level rules only, no nested files)
+fn load_gitignore(project_path: &Path) -> Vec<IgnoreRule> {
+    let Ok(content) = fs::read_to_string(project_path.join(".gitignore")) else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let (dir_only, line) = match line.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            // Patterns without an inner slash match at any depth
+            let glob = match line.strip_prefix('/') {
+                Some(anchored) => anchored.to_string(),
+                None if line.contains('/') => line.to_string(),
+                None => format!("**/{}", line),
+            };
+            Pattern::new(&glob).ok().map(|pattern| IgnoreRule {
+                pattern,
+                negated,
+                dir_only,
+            })
+        })
+        .collect()
+}
+
+impl ScopeMatcher {
+    pub fn new(scope: &CheckpointScope, project_path: &Path) -> Self {
+        Self {
+            project_path: project_path.to_path_buf(),
+            include: compile(&scope.include),
+            exclude: compile(&scope.exclude),
+            gitignore: if scope.respect_gitignore {
+                load_gitignore(project_path)
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    fn is_gitignored(&self, rel: &str, is_dir: bool) -> bool {
+        // Last matching rule wins, as in git
+        let mut ignored = false;
+        for rule in &self.gitignore {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            if rule.pattern.matches_with(rel, MATCH_OPTIONS) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+
+    fn is_excluded(&self, rel: &str, is_dir: bool) -> bool {
+        self.exclude.iter().any(|p| p.matches_with(rel, MATCH_OPTIONS)) || self.is_gitignored(rel, is_dir)
+    }
+
+    /// Whether a file (relative or absolute path) belongs to the checkpoint scope
+    pub fn includes_file(&self, path: &Path) -> bool {
+        let rel = path.strip_prefix(&self.project_path).unwrap_or(path);
+        let rel = rel.to_string_lossy().replace('\\', "/");
+
+        // A file inside an excluded or ignored directory is out of scope
+        let mut prefix = String::new();
+        let components: Vec<&str> = rel.split('/').collect();
+        for dir in &components[..components.len().saturating_sub(1)] {
+            if !prefix.is_empty() {
+                prefix.push('/');
+            }
+            prefix.push_str(dir);
+            if dir.starts_with('.') || self.is_excluded(&prefix, true) {
+                return false;
+            }
+        }
+
+        if self.is_excluded(&rel, false) {
+            return false;
+        }
+        self.include.is_empty() || self.include.iter().any(|p| p.matches_with(&rel, MATCH_OPTIONS))
+    }
+
+    /// Walk the project and collect in-scope files (relative paths).
+    ///
+    /// Hidden directories such as .git are always skipped; excluded directories
+    /// are not descended into.
+    pub fn collect_files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let _ = self.walk(&self.project_path, &mut files);
+        files
+    }
+
+    fn walk(&self, dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let Ok(rel) = path.strip_prefix(&self.project_path) else {
+                continue;
+            };
+            let rel_str = rel.to_string_lossy().replace('\\', "/");
+
+            if path.is_dir() {
+                let hidden = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .map_or(false, |n| n.starts_with('.'));
+                if hidden || self.is_excluded(&rel_str, true) {
+                    continue;
+                }
+                self.walk(&path, files)?;
+            } else if path.is_file() {
+                if !self.is_excluded(&rel_str, false)
+                    && (self.include.is_empty()
+                        || self.include.iter().any(|p| p.matches_with(&rel_str, MATCH_OPTIONS)))
+                {
+                    files.push(rel.to_path_buf());
+                }
+            }
+        }
+        Ok(())
+    }
+}
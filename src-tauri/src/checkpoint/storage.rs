@@ -1,14 +1,54 @@
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use uuid::Uuid;
 use zstd::stream::{decode_all, encode_all};
 
 use super::{
-    Checkpoint, CheckpointPaths, CheckpointResult, FileSnapshot, SessionTimeline, TimelineNode,
+    Checkpoint, CheckpointCompaction, CheckpointPaths, CheckpointResult, CheckpointStorageStats,
+    FileSnapshot, SessionTimeline, TimelineNode,
 };
 
+/// Transcript lines stored per blob. A session's transcript only grows, so every full
+/// chunk of one checkpoint is shared with the checkpoints after it.
+const MESSAGE_CHUNK_LINES: usize = 64;
+
+/// Garbage collection keeps blobs younger than this: a checkpoint being saved writes
+/// its blobs before the references to them
+const BLOB_GRACE_PERIOD: Duration = Duration::from_secs(10 * 60);
+
+/// A checkpoint's transcript as an ordered list of blobs
+#[derive(Debug, Serialize, Deserialize)]
+struct MessageChunks {
+    /// Uncompressed size of the whole transcript
+    size: u64,
+    chunks: Vec<String>,
+}
+
+/// Hashes are lowercase hex; anything else read from a reference is not a blob name
+fn is_blob_hash(hash: &str) -> bool {
+    !hash.is_empty() && hash.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Total size of the files under a directory
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(_) => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
 /// Manages checkpoint storage operations
 pub struct CheckpointStorage {
     pub claude_dir: PathBuf,
@@ -63,12 +103,8 @@ impl CheckpointStorage {
             .context("Failed to serialize checkpoint metadata")?;
         fs::write(&metadata_path, metadata_json).context("Failed to write checkpoint metadata")?;
 
-        // Save messages (compressed)
-        let messages_path = paths.checkpoint_messages_file(&checkpoint.id);
-        let compressed_messages = encode_all(messages.as_bytes(), self.compression_level)
-            .context("Failed to compress messages")?;
-        fs::write(&messages_path, compressed_messages)
-            .context("Failed to write compressed messages")?;
+        // Save messages as shared chunks
+        self.save_messages(&paths, &checkpoint.id, messages)?;
 
         // Save file snapshots
         let mut warnings = Vec::new();
@@ -92,29 +128,116 @@ impl CheckpointStorage {
             checkpoint: checkpoint.clone(),
             files_processed,
             warnings,
+            conflicts: Vec::new(),
         })
     }
 
-    /// Save a single file snapshot
-    fn save_file_snapshot(&self, paths: &CheckpointPaths, snapshot: &FileSnapshot) -> Result<()> {
-        // Use content-addressable storage: store files by their hash
-        // This prevents duplication of identical file content across checkpoints
-        let content_pool_dir = paths.files_dir.join("content_pool");
-        fs::create_dir_all(&content_pool_dir).context("Failed to create content pool directory")?;
+    /// Compress content into the project's blob store under its hash, unless a blob with
+    /// that hash is already there. Returns whether a new blob was written.
+    fn write_blob(&self, paths: &CheckpointPaths, hash: &str, content: &[u8]) -> Result<bool> {
+        if !is_blob_hash(hash) {
+            anyhow::bail!("Invalid blob hash: {}", hash);
+        }
+        let blob_path = paths.blob_path(hash);
+        if blob_path.exists() {
+            return Ok(false);
+        }
+        let shard_dir = blob_path.parent().context("Blob path has no parent")?;
+        fs::create_dir_all(shard_dir).context("Failed to create blob directory")?;
+        crate::commands::fault_injection::on_disk_io();
+
+        // Write under a temporary name first, so a crash never leaves a truncated blob
+        // that later checkpoints would trust
+        let compressed =
+            encode_all(content, self.compression_level).context("Failed to compress blob")?;
+        let temp_path = shard_dir.join(format!(".{}.{}.tmp", hash, Uuid::new_v4()));
+        fs::write(&temp_path, compressed).context("Failed to write blob")?;
+        if let Err(e) = fs::rename(&temp_path, &blob_path) {
+            let _ = fs::remove_file(&temp_path);
+            // Another checkpoint stored the same content first
+            if !blob_path.exists() {
+                return Err(e).context("Failed to move blob into place");
+            }
+        }
+        Ok(true)
+    }
 
-        // Store the actual content in the content pool
-        let content_file = content_pool_dir.join(&snapshot.hash);
+    /// Read and decompress a blob, falling back to the session's legacy content pool
+    fn read_blob(&self, paths: &CheckpointPaths, hash: &str) -> Result<Option<Vec<u8>>> {
+        if !is_blob_hash(hash) {
+            anyhow::bail!("Invalid blob hash: {}", hash);
+        }
+        let candidates = [paths.blob_path(hash), paths.legacy_content_pool_dir().join(hash)];
+        let Some(path) = candidates.iter().find(|path| path.exists()) else {
+            return Ok(None);
+        };
+        crate::commands::fault_injection::on_disk_io();
+        let compressed = fs::read(path).context("Failed to read blob")?;
+        let content = decode_all(&compressed[..]).context("Failed to decompress blob")?;
+        Ok(Some(content))
+    }
+
+    /// Store a transcript as blobs of `MESSAGE_CHUNK_LINES` lines plus a list of them
+    fn save_messages(
+        &self,
+        paths: &CheckpointPaths,
+        checkpoint_id: &str,
+        messages: &str,
+    ) -> Result<()> {
+        let lines: Vec<&str> = messages.split_inclusive('\n').collect();
+        let mut chunks = Vec::new();
+        for group in lines.chunks(MESSAGE_CHUNK_LINES) {
+            let chunk = group.concat();
+            let hash = Self::calculate_file_hash(&chunk);
+            self.write_blob(paths, &hash, chunk.as_bytes())?;
+            chunks.push(hash);
+        }
+        let manifest = MessageChunks {
+            size: messages.len() as u64,
+            chunks,
+        };
+        fs::write(
+            paths.checkpoint_message_chunks_file(checkpoint_id),
+            serde_json::to_string(&manifest)?,
+        )
+        .context("Failed to write message chunk list")?;
+        Ok(())
+    }
+
+    fn load_message_chunks(paths: &CheckpointPaths, checkpoint_id: &str) -> Option<MessageChunks> {
+        let json = fs::read_to_string(paths.checkpoint_message_chunks_file(checkpoint_id)).ok()?;
+        serde_json::from_str(&json).ok()
+    }
 
-        // Only write the content if it doesn't already exist
-        if !content_file.exists() {
-            // Compress and save file content
-            let compressed_content =
-                encode_all(snapshot.content.as_bytes(), self.compression_level)
-                    .context("Failed to compress file content")?;
-            fs::write(&content_file, compressed_content)
-                .context("Failed to write file content to pool")?;
+    /// Load a checkpoint's transcript from its chunks, or from the whole compressed
+    /// transcript older checkpoints have
+    fn load_messages(&self, paths: &CheckpointPaths, checkpoint_id: &str) -> Result<String> {
+        if let Some(manifest) = Self::load_message_chunks(paths, checkpoint_id) {
+            let mut messages = Vec::with_capacity(manifest.size as usize);
+            for hash in &manifest.chunks {
+                let chunk = self
+                    .read_blob(paths, hash)?
+                    .with_context(|| format!("Message chunk {} is missing", hash))?;
+                messages.extend_from_slice(&chunk);
+            }
+            return String::from_utf8(messages).context("Invalid UTF-8 in messages");
         }
 
+        let messages_path = paths.checkpoint_messages_file(checkpoint_id);
+        let compressed_messages =
+            fs::read(&messages_path).context("Failed to read compressed messages")?;
+        String::from_utf8(
+            decode_all(&compressed_messages[..]).context("Failed to decompress messages")?,
+        )
+        .context("Invalid UTF-8 in messages")
+    }
+
+    /// Save a single file snapshot
+    fn save_file_snapshot(&self, paths: &CheckpointPaths, snapshot: &FileSnapshot) -> Result<()> {
+        // Content is stored once per project under its hash; checkpoints only keep
+        // references to it
+        self.write_blob(paths, &snapshot.hash, snapshot.content.as_bytes())?;
+
         // Create a reference in the checkpoint-specific directory
         let checkpoint_refs_dir = paths.files_dir.join("refs").join(&snapshot.checkpoint_id);
         fs::create_dir_all(&checkpoint_refs_dir)
@@ -160,13 +283,7 @@ impl CheckpointStorage {
             serde_json::from_str(&metadata_json).context("Failed to parse checkpoint metadata")?;
 
         // Load messages
-        let messages_path = paths.checkpoint_messages_file(checkpoint_id);
-        let compressed_messages =
-            fs::read(&messages_path).context("Failed to read compressed messages")?;
-        let messages = String::from_utf8(
-            decode_all(&compressed_messages[..]).context("Failed to decompress messages")?,
-        )
-        .context("Invalid UTF-8 in messages")?;
+        let messages = self.load_messages(&paths, checkpoint_id)?;
 
         // Load file snapshots
         let file_snapshots = self.load_file_snapshots(&paths, checkpoint_id)?;
@@ -185,7 +302,6 @@ impl CheckpointStorage {
             return Ok(Vec::new());
         }
 
-        let content_pool_dir = paths.files_dir.join("content_pool");
         let mut snapshots = Vec::new();
 
         // Read all reference files
@@ -207,20 +323,16 @@ impl CheckpointStorage {
                 .as_str()
                 .ok_or_else(|| anyhow::anyhow!("Missing hash in reference"))?;
 
-            // Load content from pool
-            let content_file = content_pool_dir.join(hash);
-            let content = if content_file.exists() {
-                let compressed_content =
-                    fs::read(&content_file).context("Failed to read file content from pool")?;
-                String::from_utf8(
-                    decode_all(&compressed_content[..])
-                        .context("Failed to decompress file content")?,
-                )
-                .context("Invalid UTF-8 in file content")?
-            } else {
-                // Handle missing content gracefully
-                log::warn!("Content file missing for hash: {}", hash);
-                String::new()
+            // Load content from the blob store
+            let content = match self.read_blob(paths, hash)? {
+                Some(content) => {
+                    String::from_utf8(content).context("Invalid UTF-8 in file content")?
+                }
+                None => {
+                    // Handle missing content gracefully
+                    log::warn!("Content file missing for hash: {}", hash);
+                    String::new()
+                }
             };
 
             snapshots.push(FileSnapshot {
@@ -451,56 +563,274 @@ impl CheckpointStorage {
         Ok(())
     }
 
-    /// Garbage collect unreferenced content from the content pool
-    pub fn garbage_collect_content(&self, project_id: &str, session_id: &str) -> Result<usize> {
-        let paths = CheckpointPaths::new(&self.claude_dir, project_id, session_id);
-        let content_pool_dir = paths.files_dir.join("content_pool");
-        let refs_dir = paths.files_dir.join("refs");
-
-        if !content_pool_dir.exists() {
-            return Ok(0);
-        }
+    /// Session ids with a timeline in a project
+    fn session_ids(&self, project_id: &str) -> Vec<String> {
+        let timelines_dir = CheckpointPaths::timelines_dir(&self.claude_dir, project_id);
+        let Ok(entries) = fs::read_dir(&timelines_dir) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter(|entry| entry.path().join("timeline.json").is_file())
+            .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+            .collect()
+    }
 
-        // Collect all referenced hashes
-        let mut referenced_hashes = std::collections::HashSet::new();
+    /// Projects that have checkpoints
+    pub fn checkpoint_projects(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(self.claude_dir.join("projects")) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter(|entry| entry.path().join(".timelines").is_dir())
+            .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+            .collect()
+    }
 
-        if refs_dir.exists() {
-            for checkpoint_entry in fs::read_dir(&refs_dir)? {
-                let checkpoint_dir = checkpoint_entry?.path();
-                if checkpoint_dir.is_dir() {
+    /// Hashes of the blobs every checkpoint of a project references, with the
+    /// uncompressed size of all those references. Fails rather than returning a partial
+    /// set, since garbage collection deletes whatever is missing from it.
+    fn referenced_blobs(&self, project_id: &str) -> Result<(HashSet<String>, u64)> {
+        let mut referenced = HashSet::new();
+        let mut logical_bytes = 0;
+
+        for session_id in self.session_ids(project_id) {
+            let paths = CheckpointPaths::new(&self.claude_dir, project_id, &session_id);
+
+            let refs_dir = paths.files_dir.join("refs");
+            if refs_dir.exists() {
+                for checkpoint_entry in fs::read_dir(&refs_dir)? {
+                    let checkpoint_dir = checkpoint_entry?.path();
+                    if !checkpoint_dir.is_dir() {
+                        continue;
+                    }
                     for ref_entry in fs::read_dir(&checkpoint_dir)? {
                         let ref_path = ref_entry?.path();
-                        if ref_path.extension().and_then(|e| e.to_str()) == Some("json") {
-                            if let Ok(ref_json) = fs::read_to_string(&ref_path) {
-                                if let Ok(ref_metadata) =
-                                    serde_json::from_str::<serde_json::Value>(&ref_json)
-                                {
-                                    if let Some(hash) = ref_metadata["hash"].as_str() {
-                                        referenced_hashes.insert(hash.to_string());
-                                    }
-                                }
-                            }
+                        if ref_path.extension().and_then(|e| e.to_str()) != Some("json") {
+                            continue;
+                        }
+                        let ref_json = fs::read_to_string(&ref_path)
+                            .with_context(|| format!("Failed to read {}", ref_path.display()))?;
+                        let Ok(ref_metadata) = serde_json::from_str::<serde_json::Value>(&ref_json)
+                        else {
+                            log::warn!("Skipping unreadable file reference {}", ref_path.display());
+                            continue;
+                        };
+                        if let Some(hash) = ref_metadata["hash"].as_str() {
+                            referenced.insert(hash.to_string());
+                            logical_bytes += ref_metadata["size"].as_u64().unwrap_or(0);
                         }
                     }
                 }
             }
+
+            if paths.checkpoints_dir.exists() {
+                for checkpoint_entry in fs::read_dir(&paths.checkpoints_dir)? {
+                    let checkpoint_id = checkpoint_entry?.file_name();
+                    let Some(checkpoint_id) = checkpoint_id.to_str() else {
+                        continue;
+                    };
+                    if let Some(manifest) = Self::load_message_chunks(&paths, checkpoint_id) {
+                        logical_bytes += manifest.size;
+                        referenced.extend(manifest.chunks);
+                    }
+                }
+            }
+        }
+
+        Ok((referenced, logical_bytes))
+    }
+
+    /// Every file in a blob store as (name, path, size, age)
+    fn list_blobs(blobs_dir: &Path) -> Vec<(String, PathBuf, u64, Option<Duration>)> {
+        let Ok(shards) = fs::read_dir(blobs_dir) else {
+            return Vec::new();
+        };
+        shards
+            .flatten()
+            .filter_map(|shard| fs::read_dir(shard.path()).ok())
+            .flat_map(|entries| entries.flatten())
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+                let name = entry.file_name().to_str()?.to_string();
+                let age = metadata.modified().ok().and_then(|m| m.elapsed().ok());
+                Some((name, entry.path(), metadata.len(), age))
+            })
+            .collect()
+    }
+
+    /// Delete blobs (and leftover temporary files) nothing references that are older
+    /// than the grace period. Returns how many were removed and their size.
+    fn remove_unreferenced_blobs(blobs_dir: &Path, referenced: &HashSet<String>) -> (usize, u64) {
+        let mut removed = (0, 0);
+        for (name, path, size, age) in Self::list_blobs(blobs_dir) {
+            if referenced.contains(&name) || age.map_or(true, |age| age < BLOB_GRACE_PERIOD) {
+                continue;
+            }
+            if fs::remove_file(&path).is_ok() {
+                removed.0 += 1;
+                removed.1 += size;
+            }
+        }
+        if let Ok(shards) = fs::read_dir(blobs_dir) {
+            for shard in shards.flatten() {
+                // Only succeeds once the shard is empty
+                let _ = fs::remove_dir(shard.path());
+            }
         }
+        removed
+    }
+
+    /// Garbage collect content no checkpoint references any more: files in the
+    /// session's legacy content pool and, since blobs are shared by all sessions of the
+    /// project, unreferenced blobs of the whole project
+    pub fn garbage_collect_content(&self, project_id: &str, session_id: &str) -> Result<usize> {
+        let paths = CheckpointPaths::new(&self.claude_dir, project_id, session_id);
+        let (referenced, _) = self.referenced_blobs(project_id)?;
 
-        // Remove unreferenced content
         let mut removed_count = 0;
-        for entry in fs::read_dir(&content_pool_dir)? {
-            let content_file = entry?.path();
-            if content_file.is_file() {
-                if let Some(hash) = content_file.file_name().and_then(|n| n.to_str()) {
-                    if !referenced_hashes.contains(hash) {
-                        if fs::remove_file(&content_file).is_ok() {
-                            removed_count += 1;
-                        }
-                    }
+        if let Ok(entries) = fs::read_dir(paths.legacy_content_pool_dir()) {
+            for content_file in entries.flatten().map(|e| e.path()) {
+                let Some(hash) = content_file.file_name().and_then(|n| n.to_str()) else {
+                    continue;
+                };
+                if content_file.is_file()
+                    && !referenced.contains(hash)
+                    && fs::remove_file(&content_file).is_ok()
+                {
+                    removed_count += 1;
                 }
             }
         }
 
-        Ok(removed_count)
+        let (removed_blobs, _) = Self::remove_unreferenced_blobs(&paths.blobs_dir, &referenced);
+        Ok(removed_count + removed_blobs)
+    }
+
+    /// Report how much space a project's checkpoints take and how much compaction
+    /// could reclaim
+    pub fn storage_stats(&self, project_id: &str) -> Result<CheckpointStorageStats> {
+        let timelines_dir = CheckpointPaths::timelines_dir(&self.claude_dir, project_id);
+        let sessions = self.session_ids(project_id);
+        let (referenced, logical_bytes) = self.referenced_blobs(project_id)?;
+        let mut stats = CheckpointStorageStats {
+            project_id: project_id.to_string(),
+            sessions: sessions.len(),
+            logical_bytes,
+            total_bytes: dir_size(&timelines_dir),
+            ..Default::default()
+        };
+
+        for session_id in &sessions {
+            let paths = CheckpointPaths::new(&self.claude_dir, project_id, session_id);
+            stats.legacy_bytes += dir_size(&paths.legacy_content_pool_dir());
+            let Ok(entries) = fs::read_dir(&paths.checkpoints_dir) else {
+                continue;
+            };
+            for entry in entries.flatten().filter(|e| e.path().is_dir()) {
+                stats.checkpoints += 1;
+                let legacy_messages = entry.path().join("messages.jsonl");
+                if let Ok(metadata) = fs::metadata(&legacy_messages) {
+                    stats.legacy_bytes += metadata.len();
+                }
+            }
+        }
+
+        let blobs_dir = CheckpointPaths::blobs_dir(&self.claude_dir, project_id);
+        for (name, _, size, _) in Self::list_blobs(&blobs_dir) {
+            stats.blob_count += 1;
+            stats.blob_bytes += size;
+            if !referenced.contains(&name) {
+                stats.unreferenced_blobs += 1;
+                stats.unreferenced_bytes += size;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Move a session's legacy content pool into the project's blob store. Pool files
+    /// are already zstd blobs named by hash, so they move as they are.
+    fn migrate_content_pool(&self, paths: &CheckpointPaths, report: &mut CheckpointCompaction) {
+        let pool_dir = paths.legacy_content_pool_dir();
+        let Ok(entries) = fs::read_dir(&pool_dir) else {
+            return;
+        };
+        for source in entries.flatten().map(|e| e.path()) {
+            let Some(hash) = source.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if !source.is_file() || !is_blob_hash(hash) {
+                continue;
+            }
+            let target = paths.blob_path(hash);
+            let moved = if target.exists() {
+                fs::remove_file(&source)
+            } else {
+                target
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::rename(&source, &target))
+            };
+            match moved {
+                Ok(()) => report.migrated_blobs += 1,
+                Err(e) => {
+                    let warning = format!("Failed to move {}: {}", source.display(), e);
+                    report.warnings.push(warning);
+                }
+            }
+        }
+        let _ = fs::remove_dir(&pool_dir);
+    }
+
+    /// Split the whole transcripts of a session's older checkpoints into shared chunks
+    fn migrate_transcripts(&self, paths: &CheckpointPaths, report: &mut CheckpointCompaction) {
+        let Ok(entries) = fs::read_dir(&paths.checkpoints_dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let Some(checkpoint_id) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            let legacy_path = paths.checkpoint_messages_file(&checkpoint_id);
+            if !legacy_path.exists() {
+                continue;
+            }
+            let migrated = if paths.checkpoint_message_chunks_file(&checkpoint_id).exists() {
+                Ok(())
+            } else {
+                self.load_messages(paths, &checkpoint_id)
+                    .and_then(|messages| self.save_messages(paths, &checkpoint_id, &messages))
+            };
+            match migrated.and_then(|_| fs::remove_file(&legacy_path).map_err(Into::into)) {
+                Ok(()) => report.migrated_transcripts += 1,
+                Err(e) => report
+                    .warnings
+                    .push(format!("Failed to migrate transcript of {}: {}", checkpoint_id, e)),
+            }
+        }
+    }
+
+    /// Move a project's checkpoints to the shared, deduplicated layout and delete
+    /// blobs nothing references
+    pub fn compact_storage(&self, project_id: &str) -> Result<CheckpointCompaction> {
+        let timelines_dir = CheckpointPaths::timelines_dir(&self.claude_dir, project_id);
+        let mut report = CheckpointCompaction {
+            projects: 1,
+            bytes_before: dir_size(&timelines_dir),
+            ..Default::default()
+        };
+
+        for session_id in self.session_ids(project_id) {
+            let paths = CheckpointPaths::new(&self.claude_dir, project_id, &session_id);
+            self.migrate_content_pool(&paths, &mut report);
+            self.migrate_transcripts(&paths, &mut report);
+        }
+
+        let (referenced, _) = self.referenced_blobs(project_id)?;
+        let blobs_dir = CheckpointPaths::blobs_dir(&self.claude_dir, project_id);
+        report.removed_blobs = Self::remove_unreferenced_blobs(&blobs_dir, &referenced).0;
+        report.bytes_after = dir_size(&timelines_dir);
+        Ok(report)
     }
 }
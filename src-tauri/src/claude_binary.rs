@@ -9,6 +9,8 @@ use std::path::PathBuf;
 use std::process::Command;
 use tauri::Manager;
 
+use crate::commands::settings_store;
+
 /// Get user home directory (cross-platform)
 fn get_home_dir() -> Result<String, String> {
     #[cfg(target_os = "windows")]
@@ -54,12 +56,8 @@ pub fn find_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, Strin
     if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
         let db_path = app_data_dir.join("agents.db");
         if db_path.exists() {
-            if let Ok(conn) = rusqlite::Connection::open(&db_path) {
-                if let Ok(stored_path) = conn.query_row(
-                    "SELECT value FROM app_settings WHERE key = 'claude_binary_path'",
-                    [],
-                    |row| row.get::<_, String>(0),
-                ) {
+            if let Ok(conn) = crate::commands::db_pool::open_connection(&db_path) {
+                if let Ok(Some(stored_path)) = settings_store::get::<String>(&conn, settings_store::CLAUDE_BINARY_PATH) {
                     info!("Found stored claude path in database: {}", stored_path);
                     
                     // Verify the stored path still exists and is accessible
@@ -72,18 +70,12 @@ pub fn find_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, Strin
                         } else {
                             warn!("Stored claude path exists but is not executable: {}", stored_path);
                             // Remove invalid cached path
-                            let _ = conn.execute(
-                                "DELETE FROM app_settings WHERE key = 'claude_binary_path'",
-                                [],
-                            );
+                            let _ = settings_store::remove_in(app_handle, &conn, settings_store::CLAUDE_BINARY_PATH);
                         }
                     } else {
                         warn!("Stored claude path no longer exists: {}", stored_path);
                         // Remove invalid cached path
-                        let _ = conn.execute(
-                            "DELETE FROM app_settings WHERE key = 'claude_binary_path'",
-                            [],
-                        );
+                        let _ = settings_store::remove_in(app_handle, &conn, settings_store::CLAUDE_BINARY_PATH);
                     }
                 }
             }
@@ -129,24 +121,15 @@ fn store_claude_path(app_handle: &tauri::AppHandle, path: &str) -> Result<(), St
         }
         
         let db_path = app_data_dir.join("agents.db");
-        match rusqlite::Connection::open(&db_path) {
+        match crate::commands::db_pool::open_connection(&db_path) {
             Ok(conn) => {
-                // Create table if it doesn't exist
-                if let Err(e) = conn.execute(
-                    "CREATE TABLE IF NOT EXISTS app_settings (
-                        key TEXT PRIMARY KEY,
-                        value TEXT NOT NULL
-                    )",
-                    [],
-                ) {
-                    return Err(format!("Failed to create settings table: {}", e));
+                // The app may not have opened the database yet
+                if let Err(e) = crate::commands::db_migrations::migrate(&conn) {
+                    return Err(format!("Failed to migrate database: {}", e));
                 }
                 
                 // Store the path
-                if let Err(e) = conn.execute(
-                    "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
-                    rusqlite::params!["claude_binary_path", path],
-                ) {
+                if let Err(e) = settings_store::set_in(app_handle, &conn, settings_store::CLAUDE_BINARY_PATH, &path) {
                     return Err(format!("Failed to store claude path: {}", e));
                 }
                 
@@ -0,0 +1,372 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use super::agents::AgentDb;
+use super::events::AppEvent;
+use super::settings_store;
+
+/// `app_settings` key holding the accessibility verbosity
+const VERBOSITY_SETTING: &str = "accessibility_verbosity";
+
+/// File names listed in a "files edited" announcement before the rest are counted
+const MAX_LISTED_FILES: usize = 3;
+
+/// Characters of a tool's command or pattern read out at verbose level
+const MAX_TOOL_DETAIL_CHARS: usize = 80;
+
+/// Tools whose `file_path` (or `notebook_path`) input is a file they change
+const EDIT_TOOLS: &[&str] = &["Edit", "MultiEdit", "Write", "NotebookEdit"];
+
+/// How much the `accessibility` channel announces
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum AccessibilityVerbosity {
+    /// Nothing is announced
+    Off,
+    /// Runs starting and finishing
+    Minimal,
+    /// Also the files each turn edited
+    #[default]
+    Normal,
+    /// Also every tool call as it starts
+    Verbose,
+}
+
+impl AccessibilityVerbosity {
+    fn as_str(self) -> &'static str {
+        match self {
+            AccessibilityVerbosity::Off => "off",
+            AccessibilityVerbosity::Minimal => "minimal",
+            AccessibilityVerbosity::Normal => "normal",
+            AccessibilityVerbosity::Verbose => "verbose",
+        }
+    }
+}
+
+/// What an announcement reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum AnnouncementKind {
+    RunStarted,
+    FilesEdited,
+    ToolStarted,
+    RunCompleted,
+    RunFailed,
+    RunCancelled,
+}
+
+impl AnnouncementKind {
+    /// Lowest verbosity the announcement is made at
+    fn verbosity(self) -> AccessibilityVerbosity {
+        match self {
+            AnnouncementKind::ToolStarted => AccessibilityVerbosity::Verbose,
+            AnnouncementKind::FilesEdited => AccessibilityVerbosity::Normal,
+            _ => AccessibilityVerbosity::Minimal,
+        }
+    }
+}
+
+/// How urgently a screen reader should speak an announcement, as in `aria-live`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum AnnouncementPriority {
+    Polite,
+    Assertive,
+}
+
+/// Payload of `accessibility`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AccessibilityAnnouncement {
+    pub kind: AnnouncementKind,
+    /// One sentence to read out
+    pub message: String,
+    pub priority: AnnouncementPriority,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub run_id: Option<i64>,
+}
+
+/// The run an announcement is about
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AnnouncedRun {
+    /// An interactive Claude session
+    Session(String),
+    /// An agent run
+    Agent(i64),
+}
+
+/// What has happened in a run so far
+struct RunState {
+    /// "Claude" or the agent's name
+    label: String,
+    project: String,
+    started: Instant,
+    /// Tool use ids already handled; the stream repeats a message once per content block
+    seen_tools: HashSet<String>,
+    /// Files edited since the last turn ended
+    turn_edits: BTreeSet<String>,
+    edited: BTreeSet<String>,
+}
+
+static VERBOSITY: Lazy<RwLock<AccessibilityVerbosity>> =
+    Lazy::new(|| RwLock::new(AccessibilityVerbosity::default()));
+
+static RUNS: Lazy<Mutex<HashMap<AnnouncedRun, RunState>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn current_verbosity() -> AccessibilityVerbosity {
+    VERBOSITY.read().map(|v| *v).unwrap_or_default()
+}
+
+/// Load the verbosity into the cache, called once at startup
+pub fn init_accessibility(app: &AppHandle) {
+    let db = app.state::<AgentDb>();
+    let Ok(conn) = db.0.get() else {
+        return;
+    };
+    let verbosity: AccessibilityVerbosity = settings_store::get_or_default(&conn, VERBOSITY_SETTING);
+    if let Ok(mut cached) = VERBOSITY.write() {
+        *cached = verbosity;
+    }
+}
+
+fn announce(app: &AppHandle, run: &AnnouncedRun, kind: AnnouncementKind, message: String) {
+    if current_verbosity() < kind.verbosity() {
+        return;
+    }
+    let priority = match kind {
+        AnnouncementKind::RunFailed => AnnouncementPriority::Assertive,
+        _ => AnnouncementPriority::Polite,
+    };
+    let (session_id, run_id) = match run {
+        AnnouncedRun::Session(session_id) => (Some(session_id.clone()), None),
+        AnnouncedRun::Agent(run_id) => (None, Some(*run_id)),
+    };
+    let announcement = AccessibilityAnnouncement {
+        kind,
+        message,
+        priority,
+        session_id,
+        run_id,
+    };
+    if let Err(e) = AppEvent::Accessibility(announcement).emit(app) {
+        log::debug!("Failed to emit accessibility event: {}", e);
+    }
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// "45 seconds", "2 minutes 5 seconds", "1 hour 3 minutes"
+fn spoken_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs % 3600 / 60, secs % 60) {
+        (0, 0, s) => plural(s as usize, "second"),
+        (0, m, 0) => plural(m as usize, "minute"),
+        (0, m, s) => format!("{} {}", plural(m as usize, "minute"), plural(s as usize, "second")),
+        (h, 0, _) => plural(h as usize, "hour"),
+        (h, m, _) => format!("{} {}", plural(h as usize, "hour"), plural(m as usize, "minute")),
+    }
+}
+
+/// "Edited main.rs", "Edited 5 files: a.rs, b.rs, c.rs and 2 more"
+fn files_edited_message(files: &BTreeSet<String>) -> String {
+    let names: Vec<String> = files.iter().take(MAX_LISTED_FILES).map(|f| file_name(f)).collect();
+    let listed = match files.len() - names.len() {
+        0 => names.join(", "),
+        more => format!("{} and {} more", names.join(", "), more),
+    };
+    if files.len() == 1 {
+        format!("Edited {}", listed)
+    } else {
+        format!("Edited {}: {}", plural(files.len(), "file"), listed)
+    }
+}
+
+/// What a tool call is about, short enough to read out
+fn tool_detail(input: &serde_json::Value) -> Option<String> {
+    if let Some(path) = ["file_path", "notebook_path", "path"]
+        .iter()
+        .find_map(|key| input.get(*key).and_then(|v| v.as_str()))
+    {
+        return Some(file_name(path));
+    }
+    let detail = ["description", "command", "pattern", "url", "query"]
+        .iter()
+        .find_map(|key| input.get(*key).and_then(|v| v.as_str()))?
+        .lines()
+        .next()?;
+    if detail.chars().count() > MAX_TOOL_DETAIL_CHARS {
+        let cut: String = detail.chars().take(MAX_TOOL_DETAIL_CHARS).collect();
+        Some(format!("{}…", cut))
+    } else {
+        Some(detail.to_string()).filter(|detail| !detail.trim().is_empty())
+    }
+}
+
+/// Announce that a run started; `label` is "Claude" or the agent's name
+pub fn run_started(app: &AppHandle, run: AnnouncedRun, label: &str, project_path: &str) {
+    let project = file_name(project_path);
+    announce(
+        app,
+        &run,
+        AnnouncementKind::RunStarted,
+        format!("{} started in {}", label, project),
+    );
+    if let Ok(mut runs) = RUNS.lock() {
+        runs.insert(
+            run,
+            RunState {
+                label: label.to_string(),
+                project,
+                started: Instant::now(),
+                seen_tools: HashSet::new(),
+                turn_edits: BTreeSet::new(),
+                edited: BTreeSet::new(),
+            },
+        );
+    }
+}
+
+/// Follow one stream-json message of a run: tool calls as they start, and the files
+/// edited once the turn ends
+pub fn observe_message(app: &AppHandle, run: &AnnouncedRun, msg: &serde_json::Value) {
+    let mut pending = Vec::new();
+    {
+        let Ok(mut runs) = RUNS.lock() else {
+            return;
+        };
+        let Some(state) = runs.get_mut(run) else {
+            return;
+        };
+        match msg["type"].as_str() {
+            Some("assistant") => {
+                let blocks = msg["message"]["content"].as_array().into_iter().flatten();
+                for block in blocks.filter(|b| b["type"] == "tool_use") {
+                    let Some(name) = block["name"].as_str() else {
+                        continue;
+                    };
+                    if let Some(id) = block["id"].as_str() {
+                        if !state.seen_tools.insert(id.to_string()) {
+                            continue;
+                        }
+                    }
+                    let input = &block["input"];
+                    if EDIT_TOOLS.contains(&name) {
+                        let path = input["file_path"].as_str().or(input["notebook_path"].as_str());
+                        if let Some(path) = path {
+                            state.turn_edits.insert(path.to_string());
+                            state.edited.insert(path.to_string());
+                        }
+                    }
+                    let message = match tool_detail(input) {
+                        Some(detail) => format!("Running {}: {}", name, detail),
+                        None => format!("Running {}", name),
+                    };
+                    pending.push((AnnouncementKind::ToolStarted, message));
+                }
+            }
+            Some("result") if !state.turn_edits.is_empty() => {
+                let edits = std::mem::take(&mut state.turn_edits);
+                pending.push((AnnouncementKind::FilesEdited, files_edited_message(&edits)));
+            }
+            _ => {}
+        }
+    }
+    for (kind, message) in pending {
+        announce(app, run, kind, message);
+    }
+}
+
+/// `observe_message` for a raw output line
+pub fn observe_line(app: &AppHandle, run: &AnnouncedRun, line: &str) {
+    if let Ok(msg) = serde_json::from_str::<serde_json::Value>(line) {
+        observe_message(app, run, &msg);
+    }
+}
+
+fn finish(app: &AppHandle, run: &AnnouncedRun, kind: AnnouncementKind) {
+    // Only the first of completion and cancellation is announced
+    let Some(state) = RUNS.lock().ok().and_then(|mut runs| runs.remove(run)) else {
+        return;
+    };
+    let outcome = match kind {
+        AnnouncementKind::RunCompleted => "finished",
+        AnnouncementKind::RunCancelled => "was cancelled",
+        _ => "failed",
+    };
+    let edited = match state.edited.len() {
+        0 => "no files edited".to_string(),
+        count => format!("{} edited", plural(count, "file")),
+    };
+    let message = format!(
+        "{} {} in {} after {}; {}",
+        state.label,
+        outcome,
+        state.project,
+        spoken_duration(state.started.elapsed()),
+        edited
+    );
+    announce(app, run, kind, message);
+}
+
+/// Announce that a run exited, with how long it took and how many files it edited
+pub fn run_finished(app: &AppHandle, run: &AnnouncedRun, success: bool) {
+    let kind = if success {
+        AnnouncementKind::RunCompleted
+    } else {
+        AnnouncementKind::RunFailed
+    };
+    finish(app, run, kind);
+}
+
+/// Announce that a run was cancelled
+pub fn run_cancelled(app: &AppHandle, run: &AnnouncedRun) {
+    finish(app, run, AnnouncementKind::RunCancelled);
+}
+
+/// How much the `accessibility` event channel announces
+#[tauri::command]
+pub async fn get_accessibility_verbosity() -> Result<AccessibilityVerbosity, String> {
+    Ok(current_verbosity())
+}
+
+/// Set how much the `accessibility` event channel announces
+#[tauri::command]
+pub async fn set_accessibility_verbosity(
+    app: AppHandle,
+    verbosity: AccessibilityVerbosity,
+) -> Result<AccessibilityVerbosity, String> {
+    settings_store::set(&app, VERBOSITY_SETTING, &verbosity)
+        .map_err(|e| format!("Failed to save accessibility verbosity: {}", e))?;
+
+    if let Ok(mut cached) = VERBOSITY.write() {
+        *cached = verbosity;
+    }
+    log::info!("Accessibility verbosity set to {}", verbosity.as_str());
+    Ok(verbosity)
+}
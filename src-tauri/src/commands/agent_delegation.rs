@@ -0,0 +1,705 @@
+/// Agent-to-agent delegation
+///
+/// An executing agent can hand a sub-task to a specialised agent by emitting a
+/// delegation block in its assistant output:
+///
+/// ```text
+/// <delegate specialty="test-engineer">Write unit tests for src/parser.rs</delegate>
+/// ```
+///
+/// The backend detects the block, routes it to an agent with that specialty
+/// (falling back to the subagent router's keyword matching), runs the delegate
+/// as a regular agent run and shows its final result in the parent's output stream
+/// as a `tool_result` message. Once the parent's turn has ended and all of its
+/// delegations are done, its session is resumed with their results. Every
+/// delegation is recorded in `agent_delegations` so the full delegation tree of a
+/// run can be traced.
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use ts_rs::TS;
+
+use super::agents::AgentDb;
+use super::events::{AppEvent, EventKind};
+
+/// Maximum nesting of delegations (parent -> delegate -> delegate ...)
+pub const MAX_DELEGATION_DEPTH: i64 = 3;
+
+/// Maximum number of delegations a single run may issue
+const MAX_DELEGATIONS_PER_RUN: i64 = 5;
+
+/// How long a delegate may run before the parent stops waiting for it
+const DELEGATION_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+static DELEGATE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?s)<delegate\s+specialty\s*=\s*"([^"]+)"\s*>(.*?)</delegate>"#)
+        .expect("valid delegation pattern")
+});
+
+/// Final result text of running delegate runs, keyed by the delegate's run ID
+static DELEGATE_RESULTS: Lazy<Mutex<HashMap<i64, Option<String>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static HANDBACK: Lazy<Mutex<Handback>> = Lazy::new(|| Mutex::new(Handback::default()));
+
+/// Runs that resumed a parent's session with its delegation results
+#[derive(Default)]
+struct Handback {
+    /// Resumed run -> the run that issued the delegations
+    origins: HashMap<i64, i64>,
+    /// Delegations whose outcome was handed back
+    delivered: HashSet<i64>,
+}
+
+impl Handback {
+    /// The run that issued the delegations of `run_id`: itself unless it resumed one
+    fn origin(&self, run_id: i64) -> i64 {
+        self.origins.get(&run_id).copied().unwrap_or(run_id)
+    }
+
+    /// The latest run of `origin`'s session
+    fn latest_run(&self, origin: i64) -> i64 {
+        self.origins
+            .iter()
+            .filter(|(_, o)| **o == origin)
+            .map(|(run_id, _)| *run_id)
+            .max()
+            .unwrap_or(origin)
+    }
+}
+
+/// A delegation request parsed from agent output
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DelegationRequest {
+    pub specialty: String,
+    pub task: String,
+}
+
+/// A recorded delegation between two agent runs
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AgentDelegation {
+    #[ts(type = "number")]
+    pub id: i64,
+    #[ts(type = "number")]
+    pub parent_run_id: i64,
+    #[ts(type = "number | null")]
+    pub child_run_id: Option<i64>,
+    #[ts(type = "number")]
+    pub root_run_id: i64,
+    #[ts(type = "number")]
+    pub depth: i64,
+    pub specialty: String,
+    pub task: String,
+    #[ts(type = "number | null")]
+    pub delegate_agent_id: Option<i64>,
+    /// pending, running, completed, failed, rejected
+    pub status: String,
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+}
+
+/// A node in the delegation tree of a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegationNode {
+    pub run_id: i64,
+    pub delegation: Option<AgentDelegation>,
+    pub children: Vec<DelegationNode>,
+}
+
+fn row_to_delegation(row: &rusqlite::Row) -> SqliteResult<AgentDelegation> {
+    Ok(AgentDelegation {
+        id: row.get(0)?,
+        parent_run_id: row.get(1)?,
+        child_run_id: row.get(2)?,
+        root_run_id: row.get(3)?,
+        depth: row.get(4)?,
+        specialty: row.get(5)?,
+        task: row.get(6)?,
+        delegate_agent_id: row.get(7)?,
+        status: row.get(8)?,
+        result: row.get(9)?,
+        error: row.get(10)?,
+        created_at: row.get(11)?,
+        completed_at: row.get(12)?,
+    })
+}
+
+const DELEGATION_COLUMNS: &str = "id, parent_run_id, child_run_id, root_run_id, depth, specialty, task, delegate_agent_id, status, result, error, created_at, completed_at";
+
+/// Extract delegation requests from a stream-json line of an assistant message
+pub fn detect_delegations(line: &str) -> Vec<DelegationRequest> {
+    let json: JsonValue = match serde_json::from_str(line) {
+        Ok(json) => json,
+        Err(_) => return Vec::new(),
+    };
+    if json.get("type").and_then(|t| t.as_str()) != Some("assistant") {
+        return Vec::new();
+    }
+
+    json["message"]["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+        .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+        .flat_map(|text| {
+            DELEGATE_PATTERN
+                .captures_iter(text)
+                .map(|caps| DelegationRequest {
+                    specialty: caps[1].trim().to_string(),
+                    task: caps[2].trim().to_string(),
+                })
+                .filter(|request| !request.task.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Inspect a line of agent output: capture delegate results and start any requested delegations
+pub fn handle_agent_output_line(app: &AppHandle, run_id: i64, project_path: &str, line: &str) {
+    // Remember the final result of delegate runs so it can be handed back to the parent
+    if line.contains("\"result\"") {
+        if let Ok(mut results) = DELEGATE_RESULTS.lock() {
+            if let Some(slot) = results.get_mut(&run_id) {
+                if let Ok(json) = serde_json::from_str::<JsonValue>(line) {
+                    if json.get("type").and_then(|t| t.as_str()) == Some("result") {
+                        *slot = json.get("result").and_then(|r| r.as_str()).map(|s| s.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    if !line.contains("<delegate") {
+        return;
+    }
+
+    // A resumed parent keeps delegating on behalf of the run it continues
+    let run_id = HANDBACK.lock().map_or(run_id, |handback| handback.origin(run_id));
+    for request in detect_delegations(line) {
+        log::info!(
+            "Run {} requested delegation to '{}': {}",
+            run_id,
+            request.specialty,
+            request.task
+        );
+        tauri::async_runtime::spawn(run_delegation(
+            app.clone(),
+            run_id,
+            project_path.to_string(),
+            request,
+        ));
+    }
+}
+
+/// Pick the agent that handles a delegation: exact specialty match first, then the router
+/// unless subagents are disabled
+fn resolve_delegate_agent(conn: &Connection, request: &DelegationRequest) -> Result<Option<i64>, String> {
+    let by_specialty = conn
+        .query_row(
+            "SELECT id FROM agents WHERE specialty = ?1 ORDER BY id LIMIT 1",
+            params![request.specialty],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if by_specialty.is_some() || !super::startup::is_enabled(super::startup::Subsystem::Subagents) {
+        return Ok(by_specialty);
+    }
+
+    let router = super::subagents::SubagentRouter::new(conn);
+    let decision = router.route_request(&format!("{} {}", request.specialty, request.task))?;
+    Ok(decision.agent_id)
+}
+
+fn update_delegation(
+    conn: &Connection,
+    id: i64,
+    status: &str,
+    result: Option<&str>,
+    error: Option<&str>,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE agent_delegations SET status = ?1, result = ?2, error = ?3, completed_at = CURRENT_TIMESTAMP WHERE id = ?4",
+        params![status, result, error, id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn load_delegation(conn: &Connection, id: i64) -> Option<AgentDelegation> {
+    conn.query_row(
+        &format!("SELECT {} FROM agent_delegations WHERE id = ?1", DELEGATION_COLUMNS),
+        params![id],
+        row_to_delegation,
+    )
+    .ok()
+}
+
+fn list_delegations(conn: &Connection, parent_run_id: i64) -> Result<Vec<AgentDelegation>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM agent_delegations WHERE parent_run_id = ?1 ORDER BY id",
+            DELEGATION_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let delegations = stmt
+        .query_map(params![parent_run_id], row_to_delegation)
+        .map_err(|e| e.to_string())?
+        .collect::<SqliteResult<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+    Ok(delegations)
+}
+
+/// The text handed back to the parent for a delegation, and whether it reports a failure
+fn delegation_outcome(delegation: &AgentDelegation) -> (String, bool) {
+    match delegation.status.as_str() {
+        "completed" => (delegation.result.clone().unwrap_or_default(), false),
+        _ => (
+            format!(
+                "Delegation to '{}' {}: {}",
+                delegation.specialty,
+                delegation.status,
+                delegation.error.clone().unwrap_or_default()
+            ),
+            true,
+        ),
+    }
+}
+
+/// Show the outcome of a delegation in the parent run's output as a tool result
+fn inject_delegation_result(app: &AppHandle, delegation: &AgentDelegation) {
+    let (content, is_error) = delegation_outcome(delegation);
+    let message = serde_json::json!({
+        "type": "user",
+        "message": {
+            "role": "user",
+            "content": [{
+                "type": "tool_result",
+                "tool_use_id": format!("delegation-{}", delegation.id),
+                "content": content,
+                "is_error": is_error,
+            }],
+        },
+        "delegation": delegation,
+    });
+    let line = message.to_string();
+
+    if let Some(registry) = app.try_state::<crate::process::ProcessRegistryState>() {
+        let _ = registry.0.append_live_output(delegation.parent_run_id, &line);
+    }
+    let _ = super::event_subscriptions::emit(
+        app,
+        &EventKind::AgentOutput.scoped(delegation.parent_run_id),
+        &line,
+    );
+    let _ = AppEvent::AgentDelegation(delegation.clone()).emit(app);
+}
+
+/// The prompt that resumes a parent's session with the outcome of its delegations
+fn handback_prompt(delegations: &[AgentDelegation]) -> String {
+    let results: Vec<String> = delegations
+        .iter()
+        .map(|delegation| {
+            let (content, _) = delegation_outcome(delegation);
+            format!(
+                "<delegation_result specialty=\"{}\" status=\"{}\">\n{}\n</delegation_result>",
+                delegation.specialty, delegation.status, content
+            )
+        })
+        .collect();
+    format!(
+        "Your delegations have finished. Continue your task using their results.\n\n{}",
+        results.join("\n\n")
+    )
+}
+
+fn run_status(app: &AppHandle, run_id: i64) -> Option<String> {
+    let db = app.state::<AgentDb>();
+    let conn = db.0.get().ok()?;
+    conn.query_row(
+        "SELECT status FROM agent_runs WHERE id = ?1",
+        params![run_id],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+/// Wait for an agent run to finish; its final status, or "timeout"
+async fn wait_for_run(app: &AppHandle, run_id: i64) -> String {
+    let started = Instant::now();
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        match run_status(app, run_id).as_deref() {
+            Some("pending") | Some("running") => {}
+            Some(status) => return status.to_string(),
+            None => return "failed".to_string(),
+        }
+        if started.elapsed() > DELEGATION_TIMEOUT {
+            return "timeout".to_string();
+        }
+    }
+}
+
+/// Record the delegate's run before its process starts, so its result cannot arrive
+/// before `handle_agent_output_line` knows to keep it
+fn register_delegate_run(app: &AppHandle, delegation_id: i64, child_run_id: i64, agent_id: i64) {
+    if let Ok(mut results) = DELEGATE_RESULTS.lock() {
+        results.insert(child_run_id, None);
+    }
+    let db = app.state::<AgentDb>();
+    if let Ok(conn) = db.0.get() {
+        let _ = conn.execute(
+            "UPDATE agent_delegations SET child_run_id = ?1, delegate_agent_id = ?2, status = 'running' WHERE id = ?3",
+            params![child_run_id, agent_id, delegation_id],
+        );
+    };
+}
+
+/// Resume the parent's session with the outcome of its delegations. Waits for the
+/// parent's turn to end; the last of its delegations to finish resumes it, so every
+/// outcome is handed back once.
+async fn hand_back_results(app: AppHandle, origin_run_id: i64, project_path: String) {
+    let Some(parent_run_id) = HANDBACK.lock().ok().map(|handback| handback.latest_run(origin_run_id)) else {
+        return;
+    };
+    let status = wait_for_run(&app, parent_run_id).await;
+    if status != "completed" {
+        log::warn!(
+            "Not resuming run {} with its delegation results: the run {}",
+            parent_run_id,
+            status
+        );
+        return;
+    }
+
+    let resume = {
+        let db = app.state::<AgentDb>();
+        let Ok(conn) = db.0.get() else {
+            return;
+        };
+        let Ok(mut handback) = HANDBACK.lock() else {
+            return;
+        };
+        // Another delegation already resumed the parent
+        if handback.latest_run(origin_run_id) != parent_run_id {
+            return;
+        }
+        let delegations = match list_delegations(&conn, origin_run_id) {
+            Ok(delegations) => delegations,
+            Err(e) => {
+                log::warn!("Failed to load delegations of run {}: {}", origin_run_id, e);
+                return;
+            }
+        };
+        if delegations.iter().any(|d| d.status == "pending" || d.status == "running") {
+            return;
+        }
+        let outcomes: Vec<AgentDelegation> = delegations
+            .into_iter()
+            .filter(|d| !handback.delivered.contains(&d.id))
+            .collect();
+        if outcomes.is_empty() {
+            return;
+        }
+        let parent: Option<(i64, String, String)> = conn
+            .query_row(
+                "SELECT agent_id, model, session_id FROM agent_runs WHERE id = ?1",
+                params![parent_run_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .ok();
+        let Some((agent_id, model, session_id)) = parent.filter(|(_, _, session)| !session.is_empty()) else {
+            log::warn!("Run {} has no session to resume with its delegation results", parent_run_id);
+            return;
+        };
+        handback.delivered.extend(outcomes.iter().map(|d| d.id));
+        (agent_id, model, session_id, handback_prompt(&outcomes))
+    };
+
+    let (agent_id, model, session_id, prompt) = resume;
+    let options = super::agents::AgentRunOptions {
+        resume_session_id: Some(session_id),
+        on_run_created: Some(Box::new(move |run_id| {
+            if let Ok(mut handback) = HANDBACK.lock() {
+                handback.origins.insert(run_id, origin_run_id);
+            }
+        })),
+    };
+    match super::agents::start_agent_run(
+        app.clone(),
+        agent_id,
+        project_path,
+        prompt,
+        Some(model),
+        None,
+        None,
+        options,
+        app.state::<AgentDb>(),
+        app.state::<crate::process::ProcessRegistryState>(),
+    )
+    .await
+    {
+        Ok(run_id) => log::info!(
+            "Resumed run {} with its delegation results as run {}",
+            parent_run_id,
+            run_id
+        ),
+        Err(e) => log::warn!(
+            "Failed to resume run {} with its delegation results: {}",
+            parent_run_id,
+            e
+        ),
+    }
+}
+
+/// Insert a pending delegation and return its ID, with the reason it is rejected if it
+/// exceeds the depth or fan-out limit. Counting and inserting share one write
+/// transaction, so delegations requested together cannot all pass the limit.
+fn record_delegation(
+    conn: &mut Connection,
+    parent_run_id: i64,
+    request: &DelegationRequest,
+) -> Result<(i64, Option<String>), String> {
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let parent: Option<(i64, i64)> = tx
+        .query_row(
+            "SELECT depth, root_run_id FROM agent_delegations WHERE child_run_id = ?1",
+            params![parent_run_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let (parent_depth, root_run_id) = parent.unwrap_or((0, parent_run_id));
+    let depth = parent_depth + 1;
+    let issued: i64 = tx
+        .query_row(
+            "SELECT COUNT(*) FROM agent_delegations WHERE parent_run_id = ?1",
+            params![parent_run_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rejection = if depth > MAX_DELEGATION_DEPTH {
+        Some(format!("maximum delegation depth of {} reached", MAX_DELEGATION_DEPTH))
+    } else if issued >= MAX_DELEGATIONS_PER_RUN {
+        Some(format!("run already issued {} delegations", MAX_DELEGATIONS_PER_RUN))
+    } else {
+        None
+    };
+
+    tx.execute(
+        "INSERT INTO agent_delegations (parent_run_id, root_run_id, depth, specialty, task, status)
+         VALUES (?1, ?2, ?3, ?4, ?5, 'pending')",
+        params![parent_run_id, root_run_id, depth, request.specialty, request.task],
+    )
+    .map_err(|e| e.to_string())?;
+    let id = tx.last_insert_rowid();
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok((id, rejection))
+}
+
+/// Run a delegation end to end. Boxed because delegate runs can delegate again.
+fn run_delegation(
+    app: AppHandle,
+    parent_run_id: i64,
+    project_path: String,
+    request: DelegationRequest,
+) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+    Box::pin(async move {
+        // Record the delegation, enforcing depth and fan-out limits
+        let recorded = {
+            let db = app.state::<AgentDb>();
+            db.0.get().and_then(|mut conn| record_delegation(&mut conn, parent_run_id, &request))
+        };
+        let (delegation_id, rejection) = match recorded {
+            Ok(recorded) => recorded,
+            Err(e) => {
+                log::error!("Failed to record delegation: {}", e);
+                return;
+            }
+        };
+
+        let finish = |status: &str, result: Option<&str>, error: Option<&str>| {
+            let db = app.state::<AgentDb>();
+            let Ok(conn) = db.0.get() else {
+                return;
+            };
+            if let Err(e) = update_delegation(&conn, delegation_id, status, result, error) {
+                log::warn!("Failed to update delegation {}: {}", delegation_id, e);
+            }
+            if let Some(delegation) = load_delegation(&conn, delegation_id) {
+                inject_delegation_result(&app, &delegation);
+            }
+        };
+
+        let outcome = run_delegate(&app, delegation_id, rejection, &project_path, &request).await;
+        match outcome {
+            Ok(result) => finish("completed", Some(&result), None),
+            Err((status, error)) => finish(status, None, Some(&error)),
+        }
+        hand_back_results(app.clone(), parent_run_id, project_path).await;
+    })
+}
+
+/// Start the delegate and wait for its result; on failure the delegation's final status
+/// and the reason
+async fn run_delegate(
+    app: &AppHandle,
+    delegation_id: i64,
+    rejection: Option<String>,
+    project_path: &str,
+    request: &DelegationRequest,
+) -> Result<String, (&'static str, String)> {
+    if let Some(reason) = rejection {
+        log::warn!("Rejected delegation {}: {}", delegation_id, reason);
+        return Err(("rejected", reason));
+    }
+
+    let agent_id = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.get().map_err(|e| ("failed", e))?;
+        resolve_delegate_agent(&conn, request)
+            .map_err(|e| ("failed", e))?
+            .ok_or_else(|| ("failed", format!("no agent available for specialty '{}'", request.specialty)))?
+    };
+
+    let registering_app = app.clone();
+    let options = super::agents::AgentRunOptions {
+        resume_session_id: None,
+        on_run_created: Some(Box::new(move |child_run_id| {
+            register_delegate_run(&registering_app, delegation_id, child_run_id, agent_id)
+        })),
+    };
+    let started = super::agents::start_agent_run(
+        app.clone(),
+        agent_id,
+        project_path.to_string(),
+        request.task.clone(),
+        None,
+        None,
+        None,
+        options,
+        app.state::<AgentDb>(),
+        app.state::<crate::process::ProcessRegistryState>(),
+    )
+    .await;
+    let child_run_id = match started {
+        Ok(run_id) => run_id,
+        Err(e) => {
+            // The run may have been recorded before its process failed to start
+            let db = app.state::<AgentDb>();
+            let child_run_id = db
+                .0
+                .get()
+                .ok()
+                .and_then(|conn| load_delegation(&conn, delegation_id))
+                .and_then(|delegation| delegation.child_run_id);
+            if let (Some(child_run_id), Ok(mut results)) = (child_run_id, DELEGATE_RESULTS.lock()) {
+                results.remove(&child_run_id);
+            }
+            return Err(("failed", format!("failed to start delegate: {}", e)));
+        }
+    };
+    log::info!(
+        "Delegation {} running as agent run {} (agent {})",
+        delegation_id,
+        child_run_id,
+        agent_id
+    );
+
+    let final_status = wait_for_run(app, child_run_id).await;
+    let result = DELEGATE_RESULTS
+        .lock()
+        .ok()
+        .and_then(|mut results| results.remove(&child_run_id))
+        .flatten();
+
+    match (final_status.as_str(), result) {
+        ("completed", Some(result)) => Ok(result),
+        ("completed", None) => Err(("failed", "delegate finished without a result".to_string())),
+        ("timeout", _) => Err(("failed", "delegate timed out".to_string())),
+        (status, _) => Err(("failed", format!("delegate run {}", status))),
+    }
+}
+
+fn build_delegation_tree(conn: &Connection, run_id: i64, delegation: Option<AgentDelegation>, depth: i64) -> Result<DelegationNode, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM agent_delegations WHERE parent_run_id = ?1 ORDER BY id",
+            DELEGATION_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let delegations = stmt
+        .query_map(params![run_id], row_to_delegation)
+        .map_err(|e| e.to_string())?
+        .collect::<SqliteResult<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut children = Vec::new();
+    for child in delegations {
+        match child.child_run_id {
+            // Depth guard protects against cycles in corrupted data
+            Some(child_run_id) if depth <= MAX_DELEGATION_DEPTH => {
+                children.push(build_delegation_tree(conn, child_run_id, Some(child), depth + 1)?);
+            }
+            _ => children.push(DelegationNode {
+                run_id: child.child_run_id.unwrap_or_default(),
+                delegation: Some(child),
+                children: Vec::new(),
+            }),
+        }
+    }
+
+    Ok(DelegationNode {
+        run_id,
+        delegation,
+        children,
+    })
+}
+
+/// Get the full delegation tree that a run belongs to, starting at its root run
+#[tauri::command]
+pub async fn get_delegation_tree(
+    db: State<'_, AgentDb>,
+    run_id: i64,
+) -> Result<DelegationNode, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+
+    let root_run_id: i64 = conn
+        .query_row(
+            "SELECT root_run_id FROM agent_delegations WHERE child_run_id = ?1",
+            params![run_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(run_id);
+
+    build_delegation_tree(&conn, root_run_id, None, 1)
+}
+
+/// List the delegations issued directly by a run
+#[tauri::command]
+pub async fn list_run_delegations(
+    db: State<'_, AgentDb>,
+    run_id: i64,
+) -> Result<Vec<AgentDelegation>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    list_delegations(&conn, run_id)
+}
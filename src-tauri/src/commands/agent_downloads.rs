@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use super::events::AppEvent;
+use super::github_client;
+
+/// Attempts per download before falling back to the offline cache
+const MAX_ATTEMPTS: u32 = 4;
+
+/// Delay before the first retry, doubled for each further attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Payload of `agent-download-progress`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AgentDownloadProgress {
+    pub url: String,
+    #[ts(type = "number")]
+    pub downloaded: u64,
+    /// Total size, when the server reports it
+    #[ts(type = "number | null")]
+    pub total: Option<u64>,
+    pub attempt: u32,
+    pub done: bool,
+    /// Served from the offline cache because the download failed
+    pub from_cache: bool,
+}
+
+/// Cached GitHub agent index with the ETag it was served with
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedIndex<T> {
+    pub etag: Option<String>,
+    pub files: Vec<T>,
+}
+
+/// Directory holding the agent index and downloaded agent files
+fn cache_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("github_agents");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create agent cache dir: {}", e))?;
+    Ok(dir)
+}
+
+fn index_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(cache_dir(app)?.join("index.json"))
+}
+
+/// Cache file for a download URL; the URL itself, made filesystem-safe, keeps the name stable
+fn content_path(app: &AppHandle, url: &str) -> Result<PathBuf, String> {
+    let name: String = url
+        .trim_start_matches("https://")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    Ok(cache_dir(app)?.join(name))
+}
+
+fn emit_progress(app: &AppHandle, progress: AgentDownloadProgress) {
+    if let Err(e) = AppEvent::AgentDownloadProgress(progress).emit(app) {
+        log::debug!("Failed to emit agent download progress: {}", e);
+    }
+}
+
+/// Load the cached agent index, if any
+pub fn load_cached_index<T: for<'de> Deserialize<'de>>(app: &AppHandle) -> Option<CachedIndex<T>> {
+    let content = fs::read_to_string(index_path(app).ok()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Store the agent index together with its ETag
+pub fn save_cached_index<T: Serialize>(app: &AppHandle, index: &CachedIndex<T>) -> Result<(), String> {
+    let path = index_path(app)?;
+    let content = serde_json::to_string(index).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| format!("Failed to write agent index cache: {}", e))
+}
+
+/// Result of a conditional index request
+pub enum IndexResponse {
+    /// The cached index is still current (HTTP 304)
+    NotModified,
+    Modified { etag: Option<String>, body: String },
+}
+
+/// Fetch the agent index, sending the cached ETag so an unchanged index costs no download
+pub async fn fetch_index(url: &str, etag: Option<&str>) -> Result<IndexResponse, String> {
+    let mut last_error = String::new();
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut request = github_client::get(url).header("Accept", "application/vnd.github+json");
+        if let Some(etag) = etag {
+            request = request.header("If-None-Match", etag);
+        }
+
+        match github_client::send(request).await {
+            Ok(response) if response.status() == reqwest::StatusCode::NOT_MODIFIED => {
+                return Ok(IndexResponse::NotModified);
+            }
+            Ok(response) if response.status().is_success() => {
+                let etag = response
+                    .headers()
+                    .get(reqwest::header::ETAG)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+                match response.text().await {
+                    Ok(body) => return Ok(IndexResponse::Modified { etag, body }),
+                    Err(e) => last_error = format!("Failed to read GitHub response: {}", e),
+                }
+            }
+            Ok(response) => {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_default();
+                let error = format!("GitHub API error ({}): {}", status, error_text);
+                // Client errors (other than rate limiting) won't go away by retrying
+                if status.is_client_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    return Err(error);
+                }
+                last_error = error;
+            }
+            Err(e) => last_error = format!("Failed to fetch from GitHub: {}", e),
+        }
+
+        log::warn!("Agent index fetch attempt {}/{} failed: {}", attempt, MAX_ATTEMPTS, last_error);
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+        }
+    }
+
+    Err(last_error)
+}
+
+/// Download into `part`, resuming from its current length with a Range request
+async fn download_attempt(
+    app: &AppHandle,
+    url: &str,
+    part: &PathBuf,
+    attempt: u32,
+) -> Result<(), String> {
+    let resume_from = fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+
+    let mut request = github_client::get(url).header("Accept", "application/json");
+    if resume_from > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", resume_from));
+    }
+    let mut response = github_client::send(request)
+        .await
+        .map_err(|e| format!("Failed to download agent: {}", e))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file doesn't match the server's copy; the next attempt starts over
+        let _ = fs::remove_file(part);
+        return Err("Partial download is stale, restarting".to_string());
+    }
+    if !status.is_success() {
+        return Err(format!("Failed to download agent: HTTP {}", status));
+    }
+
+    // A plain 200 means the server ignored the Range header: start over
+    let resumed = status == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut downloaded = if resumed { resume_from } else { 0 };
+    let total = response.content_length().map(|len| len + downloaded);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(part)
+        .map_err(|e| format!("Failed to open download file: {}", e))?;
+
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Download interrupted: {}", e))?
+    {
+        file.write_all(&chunk)
+            .map_err(|e| format!("Failed to write download file: {}", e))?;
+        downloaded += chunk.len() as u64;
+        emit_progress(
+            app,
+            AgentDownloadProgress {
+                url: url.to_string(),
+                downloaded,
+                total,
+                attempt,
+                done: false,
+                from_cache: false,
+            },
+        );
+    }
+    file.flush().map_err(|e| e.to_string())
+}
+
+/// Download an agent file with retries, resuming partial downloads, and keep it in
+/// the offline cache. Falls back to the cached copy when every attempt fails.
+pub async fn download_agent_file(app: &AppHandle, url: &str) -> Result<String, String> {
+    let cached = content_path(app, url)?;
+    let part = cached.with_extension("json.part");
+    let mut last_error = String::new();
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        match download_attempt(app, url, &part, attempt).await {
+            Ok(()) => {
+                let content = fs::read_to_string(&part)
+                    .map_err(|e| format!("Failed to read response: {}", e))?;
+                fs::rename(&part, &cached)
+                    .map_err(|e| format!("Failed to cache agent file: {}", e))?;
+                emit_progress(
+                    app,
+                    AgentDownloadProgress {
+                        url: url.to_string(),
+                        downloaded: content.len() as u64,
+                        total: Some(content.len() as u64),
+                        attempt,
+                        done: true,
+                        from_cache: false,
+                    },
+                );
+                return Ok(content);
+            }
+            Err(e) => {
+                log::warn!("Agent download attempt {}/{} failed: {}", attempt, MAX_ATTEMPTS, e);
+                last_error = e;
+            }
+        }
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+        }
+    }
+
+    match fs::read_to_string(&cached) {
+        Ok(content) => {
+            log::info!("Using cached copy of {} after failed download", url);
+            emit_progress(
+                app,
+                AgentDownloadProgress {
+                    url: url.to_string(),
+                    downloaded: content.len() as u64,
+                    total: Some(content.len() as u64),
+                    attempt: MAX_ATTEMPTS,
+                    done: true,
+                    from_cache: true,
+                },
+            );
+            Ok(content)
+        }
+        Err(_) => Err(last_error),
+    }
+}
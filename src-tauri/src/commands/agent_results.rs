@@ -0,0 +1,580 @@
+use chrono::Utc;
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+
+use super::agents::AgentDb;
+
+/// Appended to the system prompt of agents with an output schema
+const RESULT_INSTRUCTION: &str = "When you are done, end your final response with a JSON \
+value matching this JSON Schema, in a ```json code block:";
+
+/// Whether the final answer of a run held a result matching the agent's schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentResultStatus {
+    Valid,
+    /// A JSON value was found but does not match the schema
+    Invalid,
+    /// The final answer holds no JSON value
+    Missing,
+}
+
+impl AgentResultStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Valid => "valid",
+            Self::Invalid => "invalid",
+            Self::Missing => "missing",
+        }
+    }
+
+    fn parse(status: &str) -> Self {
+        match status {
+            "valid" => Self::Valid,
+            "invalid" => Self::Invalid,
+            _ => Self::Missing,
+        }
+    }
+}
+
+/// The structured result of an agent run with an output schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentRunResult {
+    pub run_id: i64,
+    pub status: AgentResultStatus,
+    /// The extracted value; also kept when it does not match the schema
+    pub result: Option<Value>,
+    /// Where and why the value does not match the schema
+    pub errors: Vec<String>,
+    pub extracted_at: String,
+}
+
+pub fn init_agent_result_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_output_schemas (
+            agent_id INTEGER PRIMARY KEY,
+            schema TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (agent_id) REFERENCES agents(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_run_results (
+            run_id INTEGER PRIMARY KEY,
+            status TEXT NOT NULL,
+            result TEXT,
+            errors TEXT NOT NULL,
+            extracted_at TEXT NOT NULL,
+            FOREIGN KEY (run_id) REFERENCES agent_runs(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// The output schema of an agent, if it has one
+pub fn output_schema(conn: &Connection, agent_id: i64) -> Result<Option<Value>, String> {
+    let schema: Option<String> = conn
+        .query_row(
+            "SELECT schema FROM agent_output_schemas WHERE agent_id = ?1",
+            params![agent_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    schema
+        .map(|schema| serde_json::from_str(&schema))
+        .transpose()
+        .map_err(|e| format!("Invalid output schema of agent {}: {}", agent_id, e))
+}
+
+/// The system prompt of an agent, with the instruction to end with a result matching
+/// its output schema
+pub fn with_result_instruction(system_prompt: &str, schema: &Value) -> String {
+    let schema = serde_json::to_string_pretty(schema).unwrap_or_default();
+    format!("{}\n\n{}\n\n{}", system_prompt.trim_end(), RESULT_INSTRUCTION, schema)
+}
+
+/// Text of the final answer in a transcript of stream-json output or session JSONL:
+/// the last `result` message, else the text of the last assistant message
+fn final_text(transcript: &str) -> Option<String> {
+    let mut result = None;
+    let mut assistant = None;
+    for line in transcript.lines() {
+        let Ok(msg) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        match msg["type"].as_str() {
+            Some("result") => {
+                if let Some(text) = msg["result"].as_str() {
+                    result = Some(text.to_string());
+                }
+            }
+            Some("assistant") => {
+                let text: Vec<&str> = msg["message"]["content"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|block| block["type"] == "text")
+                    .filter_map(|block| block["text"].as_str())
+                    .collect();
+                if !text.is_empty() {
+                    assistant = Some(text.join("\n"));
+                }
+            }
+            _ => {}
+        }
+    }
+    result.or(assistant)
+}
+
+/// The first JSON value at the start of `text` and its length in bytes
+fn json_prefix(text: &str) -> Option<(Value, usize)> {
+    let mut values = serde_json::Deserializer::from_str(text).into_iter::<Value>();
+    let value = values.next()?.ok()?;
+    Some((value, values.byte_offset()))
+}
+
+/// The structured result in a final answer: the whole text if it is JSON, else the
+/// last fenced code block that is, else the last JSON object or array in the text
+fn extract_json(text: &str) -> Option<Value> {
+    if let Ok(value) = serde_json::from_str(text.trim()) {
+        return Some(value);
+    }
+
+    // Odd segments between ``` fences are code blocks; the first line may name the language
+    let fenced = text.split("```").skip(1).step_by(2).filter_map(|block| {
+        let body = match block.split_once('\n') {
+            Some((lang, body)) if !lang.trim().starts_with(['{', '[']) => body,
+            _ => block,
+        };
+        serde_json::from_str::<Value>(body.trim()).ok()
+    });
+    if let Some(value) = fenced.last() {
+        return Some(value);
+    }
+
+    // Outermost objects and arrays only: parsing resumes after each value found
+    let mut last = None;
+    let mut pos = 0;
+    while let Some(offset) = text[pos..].find(['{', '[']) {
+        let start = pos + offset;
+        match json_prefix(&text[start..]) {
+            Some((value, len)) if value.is_object() || value.is_array() => {
+                last = Some(value);
+                pos = start + len;
+            }
+            _ => pos = start + 1,
+        }
+    }
+    last
+}
+
+fn type_matches(name: &str, value: &Value) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().map_or(false, |n| n.fract() == 0.0)
+        }
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => false,
+    }
+}
+
+/// Check `value` against a JSON Schema, collecting errors with the JSON pointer of
+/// the offending value.
+///
+/// Supports the keywords agent results need: `type`, `enum`, `const`, `properties`,
+/// `required`, `additionalProperties`, `items`, `minItems`, `maxItems`, `minLength`,
+/// `maxLength`, `pattern`, `minimum`, `maximum`, `allOf`, `anyOf` and `oneOf`.
+/// Other keywords are ignored.
+fn validate(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let at = if path.is_empty() { "/" } else { path };
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => return errors.push(format!("{}: no value is allowed here", at)),
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+
+    if let Some(types) = schema.get("type") {
+        let names: Vec<&str> = match types {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !names.is_empty() && !names.iter().any(|name| type_matches(name, value)) {
+            errors.push(format!("{}: expected {}", at, names.join(" or ")));
+            return;
+        }
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            errors.push(format!("{}: {} is not one of the allowed values", at, value));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{}: expected {}", at, expected));
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !map.contains_key(name) {
+                        errors.push(format!("{}: missing required property {}", at, name));
+                    }
+                }
+            }
+            for (name, item) in map {
+                let item_path = format!("{}/{}", path, name);
+                match properties.and_then(|properties| properties.get(name)) {
+                    Some(item_schema) => validate(item_schema, item, &item_path, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            errors.push(format!("{}: unexpected property {}", at, name))
+                        }
+                        Some(extra) => validate(extra, item, &item_path, errors),
+                        None => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            let count = items.len() as u64;
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if count < min {
+                    errors.push(format!("{}: expected at least {} items", at, min));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if count > max {
+                    errors.push(format!("{}: expected at most {} items", at, max));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate(item_schema, item, &format!("{}/{}", path, index), errors);
+                }
+            }
+        }
+        Value::String(text) => {
+            let length = text.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if length < min {
+                    errors.push(format!("{}: expected at least {} characters", at, min));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if length > max {
+                    errors.push(format!("{}: expected at most {} characters", at, max));
+                }
+            }
+            if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+                match Regex::new(pattern) {
+                    Ok(re) if !re.is_match(text) => {
+                        errors.push(format!("{}: does not match {}", at, pattern))
+                    }
+                    Ok(_) => {}
+                    Err(e) => errors.push(format!("{}: invalid pattern {}: {}", at, pattern, e)),
+                }
+            }
+        }
+        Value::Number(number) => {
+            let n = number.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                if n < min {
+                    errors.push(format!("{}: {} is less than {}", at, n, min));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                if n > max {
+                    errors.push(format!("{}: {} is greater than {}", at, n, max));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    let subschemas = |keyword: &str| -> Vec<&Value> {
+        let list = schema.get(keyword).and_then(Value::as_array);
+        list.map(|s| s.iter().collect()).unwrap_or_default()
+    };
+    for subschema in subschemas("allOf") {
+        validate(subschema, value, path, errors);
+    }
+    let matches = |subschemas: &[&Value]| {
+        subschemas
+            .iter()
+            .filter(|subschema| {
+                let mut sub_errors = Vec::new();
+                validate(subschema, value, path, &mut sub_errors);
+                sub_errors.is_empty()
+            })
+            .count()
+    };
+    let any_of = subschemas("anyOf");
+    if !any_of.is_empty() && matches(&any_of) == 0 {
+        errors.push(format!("{}: matches none of the anyOf schemas", at));
+    }
+    let one_of = subschemas("oneOf");
+    if !one_of.is_empty() && matches(&one_of) != 1 {
+        errors.push(format!("{}: must match exactly one of the oneOf schemas", at));
+    }
+}
+
+/// Extract the result from a run's transcript and check it against `schema`
+fn evaluate(run_id: i64, transcript: &str, schema: &Value) -> AgentRunResult {
+    let result = final_text(transcript).and_then(|text| extract_json(&text));
+    let mut errors = Vec::new();
+    let status = match &result {
+        Some(value) => {
+            validate(schema, value, "", &mut errors);
+            if errors.is_empty() {
+                AgentResultStatus::Valid
+            } else {
+                AgentResultStatus::Invalid
+            }
+        }
+        None => {
+            errors.push("The final response holds no JSON value".to_string());
+            AgentResultStatus::Missing
+        }
+    };
+    AgentRunResult {
+        run_id,
+        status,
+        result,
+        errors,
+        extracted_at: Utc::now().to_rfc3339(),
+    }
+}
+
+fn store(conn: &Connection, result: &AgentRunResult) -> Result<(), String> {
+    let value = result.result.as_ref().map(Value::to_string);
+    let errors = serde_json::to_string(&result.errors).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO agent_run_results (run_id, status, result, errors, extracted_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![result.run_id, result.status.as_str(), value, errors, result.extracted_at],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Extract and store the result of a completed run whose agent has an output schema
+pub async fn agent_run_finished(app: &AppHandle, run_id: i64) {
+    let run = app.state::<AgentDb>().0.get().ok().and_then(|conn| {
+        let (agent_id, session_id, project_path) = conn
+            .query_row(
+                "SELECT agent_id, session_id, project_path FROM agent_runs
+                 WHERE id = ?1 AND status = 'completed'",
+                params![run_id],
+                |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                },
+            )
+            .ok()?;
+        let schema = output_schema(&conn, agent_id).ok().flatten()?;
+        Some((schema, session_id.unwrap_or_default(), project_path))
+    });
+    let Some((schema, session_id, project_path)) = run else {
+        return;
+    };
+
+    // The stream-json output ends with the result message; the session file is
+    // the fallback once the output is gone
+    let registry = app.state::<crate::process::ProcessRegistryState>();
+    let mut transcript = registry.0.get_live_output(run_id).unwrap_or_default();
+    if transcript.trim().is_empty() && !session_id.is_empty() {
+        transcript = super::agents::read_session_jsonl(&session_id, &project_path)
+            .await
+            .unwrap_or_default();
+    }
+
+    let result = evaluate(run_id, &transcript, &schema);
+    if result.status != AgentResultStatus::Valid {
+        log::warn!("Agent run {} has no valid result: {:?}", run_id, result.errors);
+    }
+    let stored = app.state::<AgentDb>().0.get().and_then(|conn| store(&conn, &result));
+    if let Err(e) = stored {
+        log::warn!("Failed to store the result of agent run {}: {}", run_id, e);
+    }
+}
+
+/// Set the JSON Schema an agent's final result must match, or remove it with `None`.
+///
+/// Agents with a schema are told to end their runs with a matching JSON value, which
+/// is extracted and validated when a run completes (see `get_agent_run_result`).
+#[tauri::command]
+pub async fn set_agent_output_schema(
+    db: State<'_, AgentDb>,
+    agent_id: i64,
+    schema: Option<Value>,
+) -> Result<(), String> {
+    let conn = db.0.get()?;
+    match schema {
+        Some(schema) => {
+            if !schema.is_object() {
+                return Err("An output schema must be a JSON object".to_string());
+            }
+            conn.execute(
+                "INSERT INTO agent_output_schemas (agent_id, schema, updated_at)
+                 VALUES (?1, ?2, CURRENT_TIMESTAMP)
+                 ON CONFLICT(agent_id) DO UPDATE SET
+                     schema = excluded.schema,
+                     updated_at = excluded.updated_at",
+                params![agent_id, schema.to_string()],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        None => {
+            conn.execute("DELETE FROM agent_output_schemas WHERE agent_id = ?1", params![agent_id])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Get the output schema of an agent; None when it has none
+#[tauri::command]
+pub async fn get_agent_output_schema(
+    db: State<'_, AgentDb>,
+    agent_id: i64,
+) -> Result<Option<Value>, String> {
+    let conn = db.0.get()?;
+    output_schema(&conn, agent_id)
+}
+
+/// Get the structured result of an agent run; None while the run is going and for
+/// runs of agents without an output schema
+#[tauri::command]
+pub async fn get_agent_run_result(
+    db: State<'_, AgentDb>,
+    run_id: i64,
+) -> Result<Option<AgentRunResult>, String> {
+    let conn = db.0.get()?;
+    let row = conn
+        .query_row(
+            "SELECT status, result, errors, extracted_at FROM agent_run_results WHERE run_id = ?1",
+            params![run_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let Some((status, result, errors, extracted_at)) = row else {
+        return Ok(None);
+    };
+    Ok(Some(AgentRunResult {
+        run_id,
+        status: AgentResultStatus::parse(&status),
+        result: result.and_then(|result| serde_json::from_str(&result).ok()),
+        errors: serde_json::from_str(&errors).unwrap_or_default(),
+        extracted_at,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn errors(schema: &Value, value: &Value) -> Vec<String> {
+        let mut errors = Vec::new();
+        validate(schema, value, "", &mut errors);
+        errors
+    }
+
+    #[test]
+    fn test_final_text_prefers_the_result_message() {
+        let transcript = [
+            json!({"type": "assistant", "message": {"content": [{"type": "text", "text": "a"}]}}),
+            json!({"type": "result", "subtype": "success", "result": "final"}),
+        ]
+        .map(|msg| msg.to_string())
+        .join("\n");
+        assert_eq!(final_text(&transcript).as_deref(), Some("final"));
+
+        let session = json!({"type": "assistant", "message": {"content": [
+            {"type": "tool_use", "name": "Read"},
+            {"type": "text", "text": "done"}
+        ]}});
+        assert_eq!(final_text(&session.to_string()).as_deref(), Some("done"));
+    }
+
+    #[test]
+    fn test_extract_json_from_fenced_block_and_prose() {
+        let fenced = "Summary below.\n```json\n{\"ok\": true}\n```\nThanks";
+        assert_eq!(extract_json(fenced), Some(json!({"ok": true})));
+
+        let prose = "First {\"a\": 1} then the answer: {\"b\": {\"c\": [1, 2]}}.";
+        assert_eq!(extract_json(prose), Some(json!({"b": {"c": [1, 2]}})));
+
+        assert_eq!(extract_json("no structure {here"), None);
+    }
+
+    #[test]
+    fn test_validate_reports_paths() {
+        let schema = json!({
+            "type": "object",
+            "required": ["status", "files"],
+            "additionalProperties": false,
+            "properties": {
+                "status": {"enum": ["pass", "fail"]},
+                "files": {"type": "array", "items": {"type": "string", "minLength": 1}},
+                "score": {"type": "integer", "minimum": 0, "maximum": 10}
+            }
+        });
+        let valid = json!({"status": "pass", "files": ["a.rs"], "score": 3});
+        assert!(errors(&schema, &valid).is_empty());
+
+        let invalid = json!({"status": "maybe", "files": [""], "score": 11, "x": 1});
+        let found = errors(&schema, &invalid);
+        assert_eq!(found.len(), 4, "{:?}", found);
+        assert!(found.iter().any(|e| e.starts_with("/status:")));
+        assert!(found.iter().any(|e| e.starts_with("/files/0:")));
+        assert!(found.iter().any(|e| e.starts_with("/score:")));
+        assert!(found.iter().any(|e| e.contains("unexpected property x")));
+
+        assert_eq!(errors(&schema, &json!([])), vec!["/: expected object".to_string()]);
+    }
+
+    #[test]
+    fn test_validate_combinators() {
+        let schema = json!({"oneOf": [{"type": "string"}, {"type": "integer"}]});
+        assert!(errors(&schema, &json!(3)).is_empty());
+        assert_eq!(errors(&schema, &json!(1.5)).len(), 1);
+        let schema = json!({"anyOf": [{"type": "number"}, {"type": "integer"}]});
+        assert!(errors(&schema, &json!(3)).is_empty());
+    }
+
+    #[test]
+    fn test_evaluate_without_json_is_missing() {
+        let transcript = json!({"type": "result", "result": "All done, no issues."}).to_string();
+        let result = evaluate(1, &transcript, &json!({"type": "object"}));
+        assert_eq!(result.status, AgentResultStatus::Missing);
+        assert!(result.result.is_none());
+    }
+}
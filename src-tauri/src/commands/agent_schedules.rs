@@ -0,0 +1,597 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeZone, Timelike, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use super::agents::AgentDb;
+
+/// How often the scheduler looks for due schedules
+const SCHEDULER_TICK: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Shortest allowed interval between runs
+const MIN_INTERVAL_SECS: u64 = 60;
+
+/// How far ahead a cron expression is searched for its next time
+const MAX_LOOKAHEAD_DAYS: i64 = 366 * 5;
+
+/// Run history kept per schedule
+const MAX_SCHEDULE_RUNS: i64 = 50;
+
+/// A recurring agent run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentSchedule {
+    pub id: i64,
+    pub agent_id: i64,
+    pub project_path: String,
+    pub task: String,
+    /// Model override; the agent's model when None
+    pub model: Option<String>,
+    /// Five-field cron expression in local time, e.g. `0 2 * * *`
+    pub cron: Option<String>,
+    /// Seconds between runs, used instead of `cron`
+    pub interval_secs: Option<u64>,
+    pub enabled: bool,
+    /// RFC 3339; None while disabled
+    pub next_run_at: Option<String>,
+    pub last_run_at: Option<String>,
+    pub last_run_id: Option<i64>,
+    /// Why the last launch failed or was skipped
+    pub last_error: Option<String>,
+    pub created_at: String,
+}
+
+/// What `create_agent_schedule` and `update_agent_schedule` take
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentScheduleInput {
+    pub agent_id: i64,
+    pub project_path: String,
+    pub task: String,
+    pub model: Option<String>,
+    /// Exactly one of `cron` and `interval_secs` must be set
+    pub cron: Option<String>,
+    pub interval_secs: Option<u64>,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// One launch of a schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentScheduleRun {
+    pub schedule_id: i64,
+    pub run_id: Option<i64>,
+    pub started_at: String,
+    pub error: Option<String>,
+}
+
+/// A parsed cron expression; each field is a bit set of the values it matches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    /// Sunday is 0
+    days_of_week: u64,
+    /// Day of month and day of week were both restricted; either may match
+    either_day: bool,
+}
+
+const MONTH_NAMES: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const DAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+fn parse_value(value: &str, min: u32, names: &[&str]) -> Option<u32> {
+    let lower = value.to_ascii_lowercase();
+    names
+        .iter()
+        .position(|name| *name == lower)
+        .map(|index| index as u32 + min)
+        .or_else(|| value.parse().ok())
+}
+
+/// Parse one field (`*`, `5`, `1-5`, `*/15`, `10-40/10`, `mon,wed`) into a bit set
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let invalid = || format!("Invalid cron field '{}'", field);
+    let mut bits = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step = step.parse::<u32>().ok().filter(|s| *s > 0).ok_or_else(invalid)?;
+                (range, step)
+            }
+            None => (item, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, min, names).ok_or_else(invalid)?,
+                parse_value(end, min, names).ok_or_else(invalid)?,
+            )
+        } else {
+            let start = parse_value(range, min, names).ok_or_else(invalid)?;
+            // `5/15` runs from 5 to the end of the range
+            (start, if item.contains('/') { max } else { start })
+        };
+        if start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+impl CronSchedule {
+    /// Parse a five-field expression (minute hour day-of-month month day-of-week)
+    /// or one of `@hourly`, `@daily`, `@weekly`, `@monthly`, `@yearly`
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields[..] else {
+            return Err(format!(
+                "Cron expression '{}' must have 5 fields: minute hour day-of-month month day-of-week",
+                expression
+            ));
+        };
+        let mut days_of_week = parse_field(dow, 0, 7, DAY_NAMES)?;
+        // 7 is another name for Sunday
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[])?,
+            hours: parse_field(hour, 0, 23, &[])?,
+            days_of_month: parse_field(dom, 1, 31, &[])?,
+            months: parse_field(month, 1, 12, MONTH_NAMES)?,
+            days_of_week,
+            either_day: dom != "*" && dow != "*",
+        })
+    }
+
+    fn matches_day(&self, time: &NaiveDateTime) -> bool {
+        let dom = self.days_of_month & (1 << time.day()) != 0;
+        let dow = self.days_of_week & (1 << time.weekday().num_days_from_sunday()) != 0;
+        if self.either_day {
+            dom || dow
+        } else {
+            dom && dow
+        }
+    }
+
+    /// First matching minute after `after`, in local time
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = start + Duration::days(MAX_LOOKAHEAD_DAYS);
+        let mut time = start;
+        while time < limit {
+            if self.months & (1 << time.month()) == 0 {
+                let (year, month) = if time.month() == 12 {
+                    (time.year() + 1, 1)
+                } else {
+                    (time.year(), time.month() + 1)
+                };
+                time = chrono::NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.matches_day(&time) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << time.hour()) == 0 {
+                time = time.with_minute(0)? + Duration::hours(1);
+            } else if self.minutes & (1 << time.minute()) == 0 {
+                time += Duration::minutes(1);
+            } else {
+                // Times skipped by a DST change don't exist; try the next minute
+                match Local.from_local_datetime(&time).earliest() {
+                    Some(local) if local > after => return Some(local),
+                    _ => time += Duration::minutes(1),
+                }
+            }
+        }
+        None
+    }
+}
+
+pub fn init_agent_schedule_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_schedules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            agent_id INTEGER NOT NULL,
+            project_path TEXT NOT NULL,
+            task TEXT NOT NULL,
+            model TEXT,
+            cron TEXT,
+            interval_secs INTEGER,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            next_run_at TEXT,
+            last_run_at TEXT,
+            last_run_id INTEGER,
+            last_error TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (agent_id) REFERENCES agents(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_schedule_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            schedule_id INTEGER NOT NULL,
+            run_id INTEGER,
+            started_at TEXT NOT NULL,
+            error TEXT,
+            FOREIGN KEY (schedule_id) REFERENCES agent_schedules(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_agent_schedule_runs_schedule
+         ON agent_schedule_runs(schedule_id)",
+        [],
+    )?;
+    Ok(())
+}
+
+const SCHEDULE_COLUMNS: &str = "id, agent_id, project_path, task, model, cron, interval_secs, \
+    enabled, next_run_at, last_run_at, last_run_id, last_error, created_at";
+
+fn row_to_schedule(row: &rusqlite::Row) -> rusqlite::Result<AgentSchedule> {
+    Ok(AgentSchedule {
+        id: row.get(0)?,
+        agent_id: row.get(1)?,
+        project_path: row.get(2)?,
+        task: row.get(3)?,
+        model: row.get(4)?,
+        cron: row.get(5)?,
+        interval_secs: row.get::<_, Option<i64>>(6)?.map(|secs| secs as u64),
+        enabled: row.get(7)?,
+        next_run_at: row.get(8)?,
+        last_run_at: row.get(9)?,
+        last_run_id: row.get(10)?,
+        last_error: row.get(11)?,
+        created_at: row.get(12)?,
+    })
+}
+
+fn load_schedule(conn: &Connection, id: i64) -> Result<AgentSchedule, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM agent_schedules WHERE id = ?1", SCHEDULE_COLUMNS),
+        params![id],
+        row_to_schedule,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Agent schedule {} not found", id))
+}
+
+fn validate(conn: &Connection, input: &AgentScheduleInput) -> Result<(), String> {
+    if input.task.trim().is_empty() {
+        return Err("A scheduled run needs a task".to_string());
+    }
+    if !std::path::Path::new(&input.project_path).is_dir() {
+        return Err(format!("Project directory {} does not exist", input.project_path));
+    }
+    let agent_exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM agents WHERE id = ?1)",
+            params![input.agent_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if !agent_exists {
+        return Err(format!("Agent {} not found", input.agent_id));
+    }
+    match (&input.cron, input.interval_secs) {
+        (Some(cron), None) => match CronSchedule::parse(cron)?.next_after(Local::now()) {
+            Some(_) => Ok(()),
+            None => Err(format!("Cron expression '{}' never matches", cron)),
+        },
+        (None, Some(secs)) if secs < MIN_INTERVAL_SECS => {
+            Err(format!("The interval must be at least {} seconds", MIN_INTERVAL_SECS))
+        }
+        (None, Some(_)) => Ok(()),
+        _ => Err("Set either a cron expression or an interval".to_string()),
+    }
+}
+
+/// When a schedule runs next after `after`; None if disabled or the expression never matches
+fn next_run(
+    cron: Option<&str>,
+    interval_secs: Option<u64>,
+    enabled: bool,
+    after: DateTime<Local>,
+) -> Option<String> {
+    if !enabled {
+        return None;
+    }
+    let next = match (cron, interval_secs) {
+        (Some(cron), _) => CronSchedule::parse(cron).ok()?.next_after(after)?,
+        (None, Some(secs)) => after + Duration::seconds(secs as i64),
+        (None, None) => return None,
+    };
+    Some(next.with_timezone(&Utc).to_rfc3339())
+}
+
+fn record_launch(
+    conn: &Connection,
+    schedule: &AgentSchedule,
+    run_id: Option<i64>,
+    error: Option<&str>,
+) {
+    let now = Utc::now().to_rfc3339();
+    let _ = conn.execute(
+        "UPDATE agent_schedules
+         SET last_run_at = ?2, last_run_id = COALESCE(?3, last_run_id), last_error = ?4
+         WHERE id = ?1",
+        params![schedule.id, now, run_id, error],
+    );
+    let _ = conn.execute(
+        "INSERT INTO agent_schedule_runs (schedule_id, run_id, started_at, error)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![schedule.id, run_id, now, error],
+    );
+    let _ = conn.execute(
+        "DELETE FROM agent_schedule_runs WHERE schedule_id = ?1 AND id NOT IN (
+             SELECT id FROM agent_schedule_runs WHERE schedule_id = ?1 ORDER BY id DESC LIMIT ?2
+         )",
+        params![schedule.id, MAX_SCHEDULE_RUNS],
+    );
+}
+
+/// Whether the schedule's previous run is still going
+fn previous_run_active(conn: &Connection, schedule: &AgentSchedule) -> bool {
+    schedule.last_run_id.map_or(false, |run_id| {
+        conn.query_row(
+            "SELECT status IN ('pending', 'running') FROM agent_runs WHERE id = ?1",
+            params![run_id],
+            |row| row.get::<_, bool>(0),
+        )
+        .unwrap_or(false)
+    })
+}
+
+/// Launch one due schedule and move its next run forward
+async fn fire(app: &AppHandle, schedule: AgentSchedule) {
+    let skip_reason = {
+        let db = app.state::<AgentDb>();
+        let Ok(conn) = db.0.get() else {
+            return;
+        };
+        // Advance first, so a failing launch isn't retried every tick
+        let next = next_run(schedule.cron.as_deref(), schedule.interval_secs, true, Local::now());
+        let _ = conn.execute(
+            "UPDATE agent_schedules SET next_run_at = ?2 WHERE id = ?1",
+            params![schedule.id, next],
+        );
+        if previous_run_active(&conn, &schedule) {
+            let reason = "Skipped: the previous run is still going";
+            record_launch(&conn, &schedule, None, Some(reason));
+            Some(reason)
+        } else {
+            None
+        }
+    };
+    if let Some(reason) = skip_reason {
+        log::info!("Agent schedule {}: {}", schedule.id, reason);
+        return;
+    }
+
+    log::info!(
+        "Starting scheduled run of agent {} (schedule {}) in {}",
+        schedule.agent_id,
+        schedule.id,
+        schedule.project_path
+    );
+    let result = super::agents::execute_agent(
+        app.clone(),
+        schedule.agent_id,
+        schedule.project_path.clone(),
+        schedule.task.clone(),
+        schedule.model.clone(),
+        None,
+        None,
+        app.state::<AgentDb>(),
+        app.state::<crate::process::ProcessRegistryState>(),
+    )
+    .await;
+
+    let db = app.state::<AgentDb>();
+    if let Ok(conn) = db.0.get() {
+        match &result {
+            Ok(run_id) => record_launch(&conn, &schedule, Some(*run_id), None),
+            Err(e) => {
+                log::warn!("Agent schedule {} failed to start its run: {}", schedule.id, e);
+                record_launch(&conn, &schedule, None, Some(e));
+            }
+        }
+    };
+}
+
+fn due_schedules(app: &AppHandle) -> Vec<AgentSchedule> {
+    let db = app.state::<AgentDb>();
+    let Ok(conn) = db.0.get() else {
+        return Vec::new();
+    };
+    let now = Utc::now();
+    let Ok(mut stmt) = conn.prepare(&format!(
+        "SELECT {} FROM agent_schedules WHERE enabled = 1 AND next_run_at IS NOT NULL",
+        SCHEDULE_COLUMNS
+    )) else {
+        return Vec::new();
+    };
+    let schedules = stmt
+        .query_map([], row_to_schedule)
+        .map(|rows| rows.filter_map(Result::ok).collect::<Vec<_>>())
+        .unwrap_or_default();
+    schedules
+        .into_iter()
+        .filter(|schedule| {
+            schedule
+                .next_run_at
+                .as_deref()
+                .and_then(|next| DateTime::parse_from_rfc3339(next).ok())
+                .map_or(false, |next| next <= now)
+        })
+        .collect()
+}
+
+/// Start the background task that launches scheduled agent runs and one-off scheduled
+/// prompts. Schedules only run while the app is open; one missed while it was closed
+/// runs once at the next start.
+pub fn start_agent_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            for schedule in due_schedules(&app) {
+                fire(&app, schedule).await;
+            }
+            super::scheduled_prompts::dispatch_scheduled_prompts(&app).await;
+            tokio::time::sleep(SCHEDULER_TICK).await;
+        }
+    });
+}
+
+/// Schedule recurring runs of an agent, by cron expression (local time) or interval
+#[tauri::command]
+pub async fn create_agent_schedule(
+    db: State<'_, AgentDb>,
+    schedule: AgentScheduleInput,
+) -> Result<AgentSchedule, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    validate(&conn, &schedule)?;
+    let next = next_run(
+        schedule.cron.as_deref(),
+        schedule.interval_secs,
+        schedule.enabled,
+        Local::now(),
+    );
+    conn.execute(
+        "INSERT INTO agent_schedules
+             (agent_id, project_path, task, model, cron, interval_secs, enabled, next_run_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            schedule.agent_id,
+            schedule.project_path,
+            schedule.task,
+            schedule.model,
+            schedule.cron.as_deref().map(str::trim),
+            schedule.interval_secs.map(|secs| secs as i64),
+            schedule.enabled,
+            next
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    let id = conn.last_insert_rowid();
+    log::info!(
+        "Created agent schedule {} for agent {}, next run {:?}",
+        id,
+        schedule.agent_id,
+        next
+    );
+    load_schedule(&conn, id)
+}
+
+/// Replace a schedule's settings; its next run is recomputed from now
+#[tauri::command]
+pub async fn update_agent_schedule(
+    db: State<'_, AgentDb>,
+    id: i64,
+    schedule: AgentScheduleInput,
+) -> Result<AgentSchedule, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    load_schedule(&conn, id)?;
+    validate(&conn, &schedule)?;
+    let next = next_run(
+        schedule.cron.as_deref(),
+        schedule.interval_secs,
+        schedule.enabled,
+        Local::now(),
+    );
+    conn.execute(
+        "UPDATE agent_schedules
+         SET agent_id = ?2, project_path = ?3, task = ?4, model = ?5, cron = ?6,
+             interval_secs = ?7, enabled = ?8, next_run_at = ?9
+         WHERE id = ?1",
+        params![
+            id,
+            schedule.agent_id,
+            schedule.project_path,
+            schedule.task,
+            schedule.model,
+            schedule.cron.as_deref().map(str::trim),
+            schedule.interval_secs.map(|secs| secs as i64),
+            schedule.enabled,
+            next
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    load_schedule(&conn, id)
+}
+
+#[tauri::command]
+pub async fn delete_agent_schedule(db: State<'_, AgentDb>, id: i64) -> Result<(), String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM agent_schedule_runs WHERE schedule_id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    let deleted = conn
+        .execute("DELETE FROM agent_schedules WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Agent schedule {} not found", id));
+    }
+    Ok(())
+}
+
+/// List schedules, optionally of one agent
+#[tauri::command]
+pub async fn list_agent_schedules(
+    db: State<'_, AgentDb>,
+    agent_id: Option<i64>,
+) -> Result<Vec<AgentSchedule>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM agent_schedules WHERE ?1 IS NULL OR agent_id = ?1 ORDER BY id",
+            SCHEDULE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![agent_id], row_to_schedule)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Recent launches of a schedule, newest first
+#[tauri::command]
+pub async fn list_agent_schedule_runs(
+    db: State<'_, AgentDb>,
+    schedule_id: i64,
+) -> Result<Vec<AgentScheduleRun>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT schedule_id, run_id, started_at, error FROM agent_schedule_runs
+             WHERE schedule_id = ?1 ORDER BY id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![schedule_id], |row| {
+            Ok(AgentScheduleRun {
+                schedule_id: row.get(0)?,
+                run_id: row.get(1)?,
+                started_at: row.get(2)?,
+                error: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
@@ -3,18 +3,23 @@ use chrono;
 use dirs;
 use log::{debug, error, info, warn};
 use regex;
-use reqwest;
 use rusqlite::{params, Connection, Result as SqliteResult};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use std::io::{BufRead, BufReader};
 use std::process::Stdio;
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Emitter, Manager, State};
+use tauri::{AppHandle, Manager, State};
 use tauri_plugin_shell::ShellExt;
 use tokio::io::{AsyncBufReadExt, BufReader as TokioBufReader};
 use tokio::process::Command;
 
+use super::agent_downloads;
+use super::accessibility::AnnouncedRun;
+use super::events::{AppEvent, EventKind};
+use super::execution_profiles::RunBudget;
+use super::provider_env::ProviderEnv;
+
 /// Finds the full path to the claude binary
 /// This is necessary because Windows apps may have a limited PATH environment
 fn find_claude_binary(app_handle: &AppHandle) -> Result<String, String> {
@@ -93,8 +98,8 @@ pub struct AgentData {
     pub hooks: Option<String>,
 }
 
-/// Database connection state
-pub struct AgentDb(pub Mutex<Connection>);
+/// Database connection state: a small pool of WAL-mode connections to agents.db
+pub struct AgentDb(pub super::db_pool::DbPool);
 
 /// Real-time JSONL reading and processing functions
 impl AgentRunMetrics {
@@ -232,7 +237,7 @@ pub async fn get_agent_run_with_metrics(run: AgentRun) -> AgentRunWithMetrics {
     }
 }
 
-/// Initialize the agents database
+/// Initialize the agents database, bringing its schema up to date
 pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
     let app_dir = app
         .path()
@@ -241,8 +246,23 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
     std::fs::create_dir_all(&app_dir).expect("Failed to create app data dir");
 
     let db_path = app_dir.join("agents.db");
-    let conn = Connection::open(db_path)?;
+    let conn = super::db_pool::open_connection(db_path)?;
+    super::db_migrations::migrate(&conn)?;
+
+    // Move hidden projects, execution config etc. from JSON files into app_settings
+    super::settings_store::migrate_json_files(&conn);
+
+    // Built-in rows are refreshed on every start so new versions ship their changes
+    super::model_aliases::seed_builtin_aliases(&conn)?;
+    super::execution_profiles::seed_builtin_profiles(&conn)?;
+
+    Ok(conn)
+}
 
+/// The schema of the agents database as it was before versioned migrations, the
+/// first migration. Every statement is idempotent, so databases created by any
+/// earlier version are brought up to it. Do not change it; add a migration instead.
+pub(crate) fn create_baseline_schema(conn: &Connection) -> SqliteResult<()> {
     // Create agents table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS agents (
@@ -340,6 +360,51 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
     )?;
 
 
+    // Record which session transcripts belong to agent runs
+    super::session_kind::init_session_kinds(conn)?;
+
+    // Project memory shared with sessions through the memory MCP server
+    super::memory::init_memory_tables(conn)?;
+
+    // Chunked and embedded project docs for knowledge queries
+    super::knowledge_base::init_knowledge_tables(conn)?;
+
+    // Tokens for the local API and the audit trail of remote invocations
+    super::capability_tokens::init_capability_tables(conn)?;
+
+    // Tasks grouping the sessions and agent runs of one piece of work
+    super::tasks::init_task_tables(conn)?;
+
+    // Enabled state and health of installed plugins
+    super::plugins::init_plugin_tables(conn)?;
+
+    // Git worktrees sessions run in to keep parallel sessions apart
+    super::worktree::init_worktree_tables(conn)?;
+
+    // Messages left out of the context when a session is resumed
+    super::context_exclusion::init_context_exclusion_tables(conn)?;
+
+    // Response language per project, with per-session overrides
+    super::response_language::init_response_language_tables(conn)?;
+
+    // Recurring agent runs and their launch history
+    super::agent_schedules::init_agent_schedule_tables(conn)?;
+
+    // One-off prompts to run at a set time
+    super::scheduled_prompts::init_scheduled_prompt_tables(conn)?;
+
+    // Runs that finished while the window was hidden or on another project
+    super::unseen_completions::init_unseen_completion_tables(conn)?;
+
+    // File edits parsed from the tool calls of running sessions
+    super::file_edits::init_file_edit_tables(conn)?;
+
+    // Extra API keys per provider and how they rotate
+    super::provider_keys::init_provider_key_tables(conn)?;
+
+    // Output schemas of agents and the structured results of their runs
+    super::agent_results::init_agent_result_tables(conn)?;
+
     // Create settings table for app-wide settings
     conn.execute(
         "CREATE TABLE IF NOT EXISTS app_settings (
@@ -365,10 +430,199 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
             total_tokens INTEGER DEFAULT 0,
             cost REAL DEFAULT 0.0,
             project_path TEXT,
+            provider_source TEXT NOT NULL DEFAULT 'direct',
+            operator_id TEXT,
             created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
         )",
         [],
     )?;
+    let _ = conn.execute(
+        "ALTER TABLE usage_entries ADD COLUMN provider_source TEXT NOT NULL DEFAULT 'direct'",
+        [],
+    );
+    let _ = conn.execute("ALTER TABLE usage_entries ADD COLUMN operator_id TEXT", []);
+    let _ = conn.execute("ALTER TABLE usage_entries ADD COLUMN provider_key TEXT", []);
+
+    // Create recent_sessions table for the per-window "reopen closed session" stack
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS recent_sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            window_label TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            project_id TEXT NOT NULL,
+            project_path TEXT NOT NULL,
+            scroll_position REAL,
+            event TEXT NOT NULL DEFAULT 'closed',
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_recent_sessions_window ON recent_sessions(window_label, id)",
+        [],
+    )?;
+
+    // Create project_trust table for workspace trust decisions
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS project_trust (
+            normalized_path TEXT PRIMARY KEY,
+            project_path TEXT NOT NULL,
+            trusted BOOLEAN NOT NULL DEFAULT 0,
+            first_seen_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            decided_at TEXT
+        )",
+        [],
+    )?;
+
+    // Create dangerous_skip_audit table for runs that skip permission checks
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS dangerous_skip_audit (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source TEXT NOT NULL,
+            project_path TEXT NOT NULL,
+            session_id TEXT,
+            expires_at TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Create hook_execution_log table for the hooks audit log
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS hook_execution_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            event TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            project_path TEXT NOT NULL,
+            command TEXT NOT NULL,
+            duration_ms INTEGER NOT NULL DEFAULT 0,
+            exit_code INTEGER,
+            success BOOLEAN NOT NULL,
+            output_size INTEGER NOT NULL DEFAULT 0,
+            error TEXT,
+            executed_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Create agent_delegations table to trace agent-to-agent delegation trees
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_delegations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            parent_run_id INTEGER NOT NULL,
+            child_run_id INTEGER,
+            root_run_id INTEGER NOT NULL,
+            depth INTEGER NOT NULL,
+            specialty TEXT NOT NULL,
+            task TEXT NOT NULL,
+            delegate_agent_id INTEGER,
+            status TEXT NOT NULL DEFAULT 'pending',
+            result TEXT,
+            error TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            completed_at TEXT
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_agent_delegations_parent ON agent_delegations(parent_run_id)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_agent_delegations_child ON agent_delegations(child_run_id)",
+        [],
+    )?;
+
+    // Create full-text search index for global search (sessions, agents, checkpoints, ...)
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+            kind UNINDEXED,
+            ref_id UNINDEXED,
+            title,
+            body,
+            target UNINDEXED,
+            source UNINDEXED,
+            tokenize = 'unicode61'
+        )",
+        [],
+    )?;
+
+    // Track indexed sources (files) and their modification time for incremental refresh
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS search_index_sources (
+            source TEXT PRIMARY KEY,
+            modified INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    // Create model_aliases table mapping frontend model IDs to Claude CLI models
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS model_aliases (
+            alias TEXT PRIMARY KEY,
+            target TEXT NOT NULL,
+            description TEXT,
+            builtin BOOLEAN NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Create session_provider_bindings table so each session resolves its own provider
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_provider_bindings (
+            session_id TEXT PRIMARY KEY,
+            provider_id TEXT NOT NULL,
+            bound_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Create session_env_overrides table for variables set on every turn of a session
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_env_overrides (
+            session_id TEXT PRIMARY KEY,
+            vars TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Create session_templates table for reusable session setups
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_templates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            config TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Create execution_profiles table for named run setups (local-dev, ci, demo, ...)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS execution_profiles (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            description TEXT NOT NULL DEFAULT '',
+            config TEXT NOT NULL,
+            builtin INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Record which execution profile each session ran with
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_run_profiles (
+            session_id TEXT PRIMARY KEY,
+            profile_name TEXT NOT NULL,
+            recorded_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
 
     // Create trigger to update the updated_at timestamp
     conn.execute(
@@ -381,13 +635,13 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
         [],
     )?;
 
-    Ok(conn)
+    Ok(())
 }
 
 /// List all agents
 #[tauri::command]
 pub async fn list_agents(db: State<'_, AgentDb>) -> Result<Vec<Agent>, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
 
     let mut stmt = conn
         .prepare("SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, created_at, updated_at FROM agents ORDER BY created_at DESC")
@@ -433,7 +687,7 @@ pub async fn create_agent(
     enable_network: Option<bool>,
     hooks: Option<String>,
 ) -> Result<Agent, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
     let model = model.unwrap_or_else(|| "sonnet".to_string());
     let enable_file_read = enable_file_read.unwrap_or(true);
     let enable_file_write = enable_file_write.unwrap_or(true);
@@ -471,6 +725,7 @@ pub async fn create_agent(
         )
         .map_err(|e| e.to_string())?;
 
+    super::global_search::invalidate_search_index();
     Ok(agent)
 }
 
@@ -489,7 +744,7 @@ pub async fn update_agent(
     enable_network: Option<bool>,
     hooks: Option<String>,
 ) -> Result<Agent, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
     let model = model.unwrap_or_else(|| "sonnet".to_string());
 
     // Build dynamic query based on provided parameters
@@ -556,24 +811,26 @@ pub async fn update_agent(
         )
         .map_err(|e| e.to_string())?;
 
+    super::global_search::invalidate_search_index();
     Ok(agent)
 }
 
 /// Delete an agent
 #[tauri::command]
 pub async fn delete_agent(db: State<'_, AgentDb>, id: i64) -> Result<(), String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
 
     conn.execute("DELETE FROM agents WHERE id = ?1", params![id])
         .map_err(|e| e.to_string())?;
 
+    super::global_search::invalidate_search_index();
     Ok(())
 }
 
 /// Get a single agent by ID
 #[tauri::command]
 pub async fn get_agent(db: State<'_, AgentDb>, id: i64) -> Result<Agent, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
 
     let agent = conn
         .query_row(
@@ -607,7 +864,7 @@ pub async fn list_agent_runs(
     db: State<'_, AgentDb>,
     agent_id: Option<i64>,
 ) -> Result<Vec<AgentRun>, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
 
     let query = if agent_id.is_some() {
         "SELECT id, agent_id, agent_name, agent_icon, task, model, project_path, session_id, status, pid, process_started_at, created_at, completed_at 
@@ -658,7 +915,7 @@ pub async fn list_agent_runs(
 /// Get a single agent run by ID
 #[tauri::command]
 pub async fn get_agent_run(db: State<'_, AgentDb>, id: i64) -> Result<AgentRun, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
 
     let run = conn
         .query_row(
@@ -715,7 +972,19 @@ pub async fn list_agent_runs_with_metrics(
     Ok(runs_with_metrics)
 }
 
+/// Settings of agent runs the backend starts on its own, e.g. for delegations
+#[derive(Default)]
+pub struct AgentRunOptions {
+    /// Session of an earlier run to continue instead of starting a new one
+    pub resume_session_id: Option<String>,
+    /// Called with the run ID once the run is recorded, before its process starts
+    pub on_run_created: Option<Box<dyn FnOnce(i64) + Send>>,
+}
+
 /// Execute a CC agent with streaming output
+///
+/// `env_profile` names an environment profile whose variables are set on this run's
+/// process only (see `save_env_profile`).
 #[tauri::command]
 pub async fn execute_agent(
     app: AppHandle,
@@ -723,17 +992,60 @@ pub async fn execute_agent(
     project_path: String,
     task: String,
     model: Option<String>,
+    max_cost_usd: Option<f64>,
+    env_profile: Option<String>,
+    db: State<'_, AgentDb>,
+    registry: State<'_, crate::process::ProcessRegistryState>,
+) -> Result<i64, String> {
+    start_agent_run(
+        app,
+        agent_id,
+        project_path,
+        task,
+        model,
+        max_cost_usd,
+        env_profile,
+        AgentRunOptions::default(),
+        db,
+        registry,
+    )
+    .await
+}
+
+/// `execute_agent` with the settings only backend-started runs use
+pub async fn start_agent_run(
+    app: AppHandle,
+    agent_id: i64,
+    project_path: String,
+    task: String,
+    model: Option<String>,
+    max_cost_usd: Option<f64>,
+    env_profile: Option<String>,
+    options: AgentRunOptions,
     db: State<'_, AgentDb>,
     registry: State<'_, crate::process::ProcessRegistryState>,
 ) -> Result<i64, String> {
     info!("Executing agent {} with task: {}", agent_id, task);
+    super::cost_cap::validate(max_cost_usd)?;
 
     // Get the agent from database
     let agent = get_agent(db.clone(), agent_id).await?;
     let execution_model = model.unwrap_or(agent.model.clone());
+    let mapped_model = super::model_aliases::resolve_model_alias(&app, &execution_model);
+    let mut env = super::provider_env::resolve_execution_env(&app, None, None, &mapped_model)?;
+    if let Some(name) = &env_profile {
+        env.extend(super::env_profiles::resolve_env_profile(&app, name)?);
+        info!("Applied environment profile {}: {:?}", name, env.redacted());
+    }
+
+    // Untrusted projects get read-only permissions and no agent hooks
+    let project_trusted = super::workspace_trust::is_project_trusted(&app, &project_path);
+    if !project_trusted {
+        warn!("Project {} is not trusted, running agent read-only without hooks", project_path);
+    }
     
     // Create .claude/settings.json with agent hooks if it doesn't exist
-    if let Some(hooks_json) = &agent.hooks {
+    if let Some(hooks_json) = agent.hooks.as_ref().filter(|_| project_trusted) {
         let claude_dir = std::path::Path::new(&project_path).join(".claude");
         let settings_path = claude_dir.join("settings.json");
         
@@ -759,7 +1071,7 @@ pub async fn execute_agent(
             let settings_content = serde_json::to_string_pretty(&settings)
                 .map_err(|e| format!("Failed to serialize settings: {}", e))?;
             
-            std::fs::write(&settings_path, settings_content)
+            crate::config_file::write(&settings_path, settings_content)
                 .map_err(|e| format!("Failed to write settings.json: {}", e))?;
             
             info!("Created settings.json with agent hooks at: {:?}", settings_path);
@@ -770,7 +1082,7 @@ pub async fn execute_agent(
 
     // Create a new run record
     let run_id = {
-        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let conn = db.0.get().map_err(|e| e.to_string())?;
         conn.execute(
             "INSERT INTO agent_runs (agent_id, agent_name, agent_icon, task, model, project_path, session_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![agent_id, agent.name, agent.icon, task, execution_model, project_path, ""],
@@ -778,6 +1090,9 @@ pub async fn execute_agent(
         .map_err(|e| e.to_string())?;
         conn.last_insert_rowid()
     };
+    if let Some(on_run_created) = options.on_run_created {
+        on_run_created(run_id);
+    }
 
     // Find Claude binary
     info!("Running agent '{}'", agent.name);
@@ -789,25 +1104,51 @@ pub async fn execute_agent(
         }
     };
 
+    // Agents with an output schema are told to end with a matching result
+    let output_schema = {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        super::agent_results::output_schema(&conn, agent_id)?
+    };
+    let system_prompt = match &output_schema {
+        Some(schema) => super::agent_results::with_result_instruction(&agent.system_prompt, schema),
+        None => agent.system_prompt.clone(),
+    };
+
     // Build arguments
-    let args = vec![
+    let mut args = vec![
         "-p".to_string(),
         task.clone(),
         "--system-prompt".to_string(),
-        agent.system_prompt.clone(),
+        system_prompt,
         "--model".to_string(),
-        execution_model.clone(),
+        mapped_model,
         "--output-format".to_string(),
         "stream-json".to_string(),
         "--verbose".to_string(),
-        "--dangerously-skip-permissions".to_string(),
     ];
+    if let Some(session_id) = &options.resume_session_id {
+        args.push("--resume".to_string());
+        args.push(session_id.clone());
+    }
+    if project_trusted {
+        // Permission checks are skipped only where sessions skip them: dangerous-skip
+        // is on, unexpired and scoped to this project (audited when it applies)
+        let mut execution_config = super::claude::get_claude_execution_config(app.clone()).await?;
+        super::safety::apply_dangerous_skip_policy(&app, "agent", &project_path, None, &mut execution_config);
+        args.extend(super::permission_config::build_permission_args(&execution_config.permissions));
+    } else {
+        args.extend(super::workspace_trust::untrusted_permission_args());
+    }
+    if !project_trusted || super::enhanced_hooks::hooks_disabled() {
+        args.extend(super::workspace_trust::disable_hooks_args());
+    }
 
     // Execute based on whether we should use sidecar or system binary
+    let budget = max_cost_usd.map(RunBudget::with_limit);
     if should_use_sidecar(&claude_path) {
-        spawn_agent_sidecar(app, run_id, agent_id, agent.name.clone(), args, project_path, task, execution_model, db, registry).await
+        spawn_agent_sidecar(app, run_id, agent_id, agent.name.clone(), args, env, project_path, task, execution_model, budget, db, registry).await
     } else {
-        spawn_agent_system(app, run_id, agent_id, agent.name.clone(), claude_path, args, project_path, task, execution_model, db, registry).await
+        spawn_agent_system(app, run_id, agent_id, agent.name.clone(), claude_path, args, env, project_path, task, execution_model, budget, db, registry).await
     }
 }
 
@@ -820,6 +1161,7 @@ fn should_use_sidecar(claude_path: &str) -> bool {
 fn create_agent_sidecar_command(
     app: &AppHandle,
     args: Vec<String>,
+    env: &ProviderEnv,
     project_path: &str,
 ) -> Result<tauri_plugin_shell::process::Command, String> {
     let mut sidecar_cmd = app
@@ -827,8 +1169,8 @@ fn create_agent_sidecar_command(
         .sidecar("claude-code")
         .map_err(|e| format!("Failed to create sidecar command: {}", e))?;
     
-    // Add all arguments
-    sidecar_cmd = sidecar_cmd.args(args);
+    // Add all arguments and the run's provider and profile variables
+    sidecar_cmd = sidecar_cmd.args(args).envs(env.process_vars());
     
     // Set working directory
     sidecar_cmd = sidecar_cmd.current_dir(project_path);
@@ -840,9 +1182,11 @@ fn create_agent_sidecar_command(
 fn create_agent_system_command(
     claude_path: &str,
     args: Vec<String>,
+    env: &ProviderEnv,
     project_path: &str,
 ) -> Command {
     let mut cmd = create_command_with_env(claude_path);
+    env.apply(&mut cmd);
     
     // Add all arguments
     for arg in args {
@@ -862,18 +1206,20 @@ async fn spawn_agent_sidecar(
     app: AppHandle,
     run_id: i64,
     _agent_id: i64,
-    _agent_name: String,
+    agent_name: String,
     args: Vec<String>,
+    env: ProviderEnv,
     project_path: String,
     _task: String,
-    _execution_model: String,
+    execution_model: String,
+    mut budget: Option<RunBudget>,
     db: State<'_, AgentDb>,
     registry: State<'_, crate::process::ProcessRegistryState>,
 ) -> Result<i64, String> {
     use std::sync::Mutex;
 
     // Create the sidecar command
-    let sidecar_cmd = create_agent_sidecar_command(&app, args, &project_path)?;
+    let sidecar_cmd = create_agent_sidecar_command(&app, args, &env, &project_path)?;
     
     // Spawn the sidecar process
     let (mut rx, child) = sidecar_cmd
@@ -883,11 +1229,13 @@ async fn spawn_agent_sidecar(
     // Get the child PID for logging
     let pid = child.pid();
     info!("✅ Spawned Claude sidecar process with PID: {:?}", pid);
+    super::accessibility::run_started(&app, AnnouncedRun::Agent(run_id), &agent_name, &project_path);
+    super::power::run_started(&app, AnnouncedRun::Agent(run_id), &project_path);
 
     // Update the database with PID and status
     let now = chrono::Utc::now().to_rfc3339();
     {
-        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let conn = db.0.get().map_err(|e| e.to_string())?;
         conn.execute(
             "UPDATE agent_runs SET status = 'running', pid = ?1, process_started_at = ?2 WHERE id = ?3",
             params![pid as i64, now, run_id],
@@ -909,11 +1257,11 @@ async fn spawn_agent_sidecar(
     // Spawn task to read events from sidecar
     let app_handle = app.clone();
     let session_id_holder_clone = session_id_holder.clone();
-    let live_output = std::sync::Arc::new(Mutex::new(String::new()));
-    let live_output_clone = live_output.clone();
     let registry_clone = registry.0.clone();
     let first_output = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
     let first_output_clone = first_output.clone();
+    let project_path_for_stream = project_path.clone();
+    let batcher = super::output_batching::OutputBatcher::spawn(app.clone(), Some(format!("agent-run-{}", run_id)));
 
     let sidecar_task = tokio::spawn(async move {
         info!("📖 Starting to read Claude sidecar events...");
@@ -938,13 +1286,7 @@ async fn spawn_agent_sidecar(
                             debug!("sidecar stdout[{}]: {}", line_count, line);
                         }
 
-                        // Store live output
-                        if let Ok(mut output) = live_output_clone.lock() {
-                            output.push_str(&line);
-                            output.push('\n');
-                        }
-
-                        // Also store in process registry for cross-session access
+                        // Store live output in process registry for cross-session access
                         let _ = registry_clone.append_live_output(run_id, &line);
 
                         // Extract session ID from JSONL output
@@ -959,7 +1301,7 @@ async fn spawn_agent_sidecar(
                                             info!("🔑 Extracted session ID: {}", sid);
                                             
                                             // Update database immediately with session ID
-                                            if let Ok(conn) = Connection::open(&db_path_for_stream) {
+                                            if let Ok(conn) = super::db_pool::open_connection(&db_path_for_stream) {
                                                 match conn.execute(
                                                     "UPDATE agent_runs SET session_id = ?1 WHERE id = ?2",
                                                     params![sid, run_id],
@@ -980,20 +1322,36 @@ async fn spawn_agent_sidecar(
                             }
                         }
 
+                        // Pick up delegation requests and delegate results
+                        super::agent_delegation::handle_agent_output_line(&app_handle, run_id, &project_path_for_stream, &line);
+                        super::accessibility::observe_line(&app_handle, &AnnouncedRun::Agent(run_id), &line);
+                        let session_id = session_id_holder_clone.lock().ok().and_then(|s| s.clone());
+                        if let Some(session_id) = &session_id {
+                            super::file_edits::observe_line(&app_handle, session_id, &line);
+                        }
+                        super::cost_cap::observe_agent_line(
+                            &app_handle,
+                            run_id,
+                            session_id,
+                            &project_path_for_stream,
+                            &execution_model,
+                            &mut budget,
+                            &line,
+                        );
+
                         // Emit the line to the frontend with run_id for isolation
-                        let _ = app_handle.emit(&format!("agent-output:{}", run_id), &line);
+                        batcher.emit(EventKind::AgentOutput.scoped(run_id), &line);
                         // Also emit to the generic event for backward compatibility
-                        let _ = app_handle.emit("agent-output", &line);
+                        batcher.emit(EventKind::AgentOutput.as_str(), &line);
                     }
                 }
                 tauri_plugin_shell::process::CommandEvent::Stderr(data) => {
                     let line = String::from_utf8_lossy(&data).trim().to_string();
                     if !line.is_empty() {
                         error!("sidecar stderr: {}", line);
-                        // Emit error lines to the frontend with run_id for isolation
-                        let _ = app_handle.emit(&format!("agent-error:{}", run_id), &line);
-                        // Also emit to the generic event for backward compatibility
-                        let _ = app_handle.emit("agent-error", &line);
+                        // Emit error lines to the frontend with run_id for isolation,
+                        // and to the generic event for backward compatibility
+                        let _ = AppEvent::AgentError { run_id, line }.emit_with_global(&app_handle);
                     }
                 }
                 tauri_plugin_shell::process::CommandEvent::Terminated { .. } => {
@@ -1011,6 +1369,7 @@ async fn spawn_agent_sidecar(
             }
         }
 
+        batcher.finish().await;
         info!("📖 Finished reading Claude sidecar events. Total lines: {}", line_count);
     });
 
@@ -1035,15 +1394,19 @@ async fn spawn_agent_sidecar(
                 warn!("   4. Claude failed to initialize but didn't report an error");
 
                 // Update database with failed status
-                if let Ok(conn) = Connection::open(&db_path) {
+                if let Ok(conn) = super::db_pool::open_connection(&db_path) {
                     let _ = conn.execute(
                         "UPDATE agent_runs SET status = 'failed', completed_at = CURRENT_TIMESTAMP WHERE id = ?1",
                         params![run_id],
                     );
                 }
 
-                let _ = app.emit("agent-complete", false);
-                let _ = app.emit(&format!("agent-complete:{}", run_id), false);
+                super::accessibility::run_finished(&app, &AnnouncedRun::Agent(run_id), false);
+                super::unseen_completions::agent_run_finished(&app, run_id, false);
+                super::power::run_finished(&app, &AnnouncedRun::Agent(run_id));
+                let complete = AppEvent::AgentComplete { run_id, success: false };
+                let _ = complete.emit_global(&app);
+                let _ = complete.emit(&app);
                 return;
             }
 
@@ -1062,10 +1425,13 @@ async fn spawn_agent_sidecar(
         };
 
         // Update the run record with session ID and mark as completed
-        if let Ok(conn) = Connection::open(&db_path) {
+        if let Ok(conn) = super::db_pool::open_connection(&db_path) {
             info!("🔄 Updating database with extracted session ID: {}", extracted_session_id);
             match conn.execute(
-                "UPDATE agent_runs SET session_id = ?1, status = 'completed', completed_at = CURRENT_TIMESTAMP WHERE id = ?2",
+                "UPDATE agent_runs SET session_id = ?1,
+                     status = CASE WHEN status = 'running' THEN 'completed' ELSE status END,
+                     completed_at = COALESCE(completed_at, CURRENT_TIMESTAMP)
+                 WHERE id = ?2",
                 params![extracted_session_id, run_id],
             ) {
                 Ok(rows_affected) => {
@@ -1085,8 +1451,14 @@ async fn spawn_agent_sidecar(
 
         info!("✅ Claude sidecar execution monitoring complete");
 
-        let _ = app.emit("agent-complete", true);
-        let _ = app.emit(&format!("agent-complete:{}", run_id), true);
+        super::agent_results::agent_run_finished(&app, run_id).await;
+        super::accessibility::run_finished(&app, &AnnouncedRun::Agent(run_id), true);
+        super::unseen_completions::agent_run_finished(&app, run_id, true);
+        super::power::run_finished(&app, &AnnouncedRun::Agent(run_id));
+        super::global_search::invalidate_search_index();
+        let complete = AppEvent::AgentComplete { run_id, success: true };
+        let _ = complete.emit_global(&app);
+        let _ = complete.emit(&app);
     });
 
     Ok(run_id)
@@ -1100,14 +1472,16 @@ async fn spawn_agent_system(
     agent_name: String,
     claude_path: String,
     args: Vec<String>,
+    env: ProviderEnv,
     project_path: String,
     task: String,
     execution_model: String,
+    mut budget: Option<RunBudget>,
     db: State<'_, AgentDb>,
     registry: State<'_, crate::process::ProcessRegistryState>,
 ) -> Result<i64, String> {
     // Build the command
-    let mut cmd = create_agent_system_command(&claude_path, args, &project_path);
+    let mut cmd = create_agent_system_command(&claude_path, args, &env, &project_path);
 
     // Spawn the process
     info!("🚀 Spawning Claude system process...");
@@ -1122,10 +1496,12 @@ async fn spawn_agent_system(
     let pid = child.id().unwrap_or(0);
     let now = chrono::Utc::now().to_rfc3339();
     info!("✅ Claude process spawned successfully with PID: {}", pid);
+    super::accessibility::run_started(&app, AnnouncedRun::Agent(run_id), &agent_name, &project_path);
+    super::power::run_started(&app, AnnouncedRun::Agent(run_id), &project_path);
 
     // Update the database with PID and status
     {
-        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let conn = db.0.get().map_err(|e| e.to_string())?;
         conn.execute(
             "UPDATE agent_runs SET status = 'running', pid = ?1, process_started_at = ?2 WHERE id = ?3",
             params![pid as i64, now, run_id],
@@ -1151,17 +1527,18 @@ async fn spawn_agent_system(
 
     // Shared state for collecting session ID and live output
     let session_id = std::sync::Arc::new(Mutex::new(String::new()));
-    let live_output = std::sync::Arc::new(Mutex::new(String::new()));
     let start_time = std::time::Instant::now();
 
     // Spawn tasks to read stdout and stderr
     let app_handle = app.clone();
     let session_id_clone = session_id.clone();
-    let live_output_clone = live_output.clone();
     let registry_clone = registry.0.clone();
     let first_output = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
     let first_output_clone = first_output.clone();
     let db_path_for_stdout = db_path.clone(); // Clone the db_path for the stdout task
+    let project_path_for_stdout = project_path.clone();
+    let model_for_stdout = execution_model.clone();
+    let batcher = super::output_batching::OutputBatcher::spawn(app.clone(), Some(format!("agent-run-{}", run_id)));
 
     let stdout_task = tokio::spawn(async move {
         info!("📖 Starting to read Claude stdout...");
@@ -1186,13 +1563,7 @@ async fn spawn_agent_system(
                 debug!("stdout[{}]: {}", line_count, line);
             }
 
-            // Store live output in both local buffer and registry
-            if let Ok(mut output) = live_output_clone.lock() {
-                output.push_str(&line);
-                output.push('\n');
-            }
-
-            // Also store in process registry for cross-session access
+            // Store live output in process registry for cross-session access
             let _ = registry_clone.append_live_output(run_id, &line);
 
             // Extract session ID from JSONL output
@@ -1207,7 +1578,7 @@ async fn spawn_agent_system(
                                 info!("🔑 Extracted session ID: {}", sid);
                                 
                                 // Update database immediately with session ID
-                                if let Ok(conn) = Connection::open(&db_path_for_stdout) {
+                                if let Ok(conn) = super::db_pool::open_connection(&db_path_for_stdout) {
                                     match conn.execute(
                                         "UPDATE agent_runs SET session_id = ?1 WHERE id = ?2",
                                         params![sid, run_id],
@@ -1228,11 +1599,29 @@ async fn spawn_agent_system(
                 }
             }
 
+            // Pick up delegation requests and delegate results
+            super::agent_delegation::handle_agent_output_line(&app_handle, run_id, &project_path_for_stdout, &line);
+            super::accessibility::observe_line(&app_handle, &AnnouncedRun::Agent(run_id), &line);
+            let session_id = session_id_clone.lock().map(|s| s.clone()).unwrap_or_default();
+            if !session_id.is_empty() {
+                super::file_edits::observe_line(&app_handle, &session_id, &line);
+            }
+            super::cost_cap::observe_agent_line(
+                &app_handle,
+                run_id,
+                Some(session_id).filter(|s| !s.is_empty()),
+                &project_path_for_stdout,
+                &model_for_stdout,
+                &mut budget,
+                &line,
+            );
+
             // Emit the line to the frontend with run_id for isolation
-            let _ = app_handle.emit(&format!("agent-output:{}", run_id), &line);
+            batcher.emit(EventKind::AgentOutput.scoped(run_id), &line);
             // Also emit to the generic event for backward compatibility
-            let _ = app_handle.emit("agent-output", &line);
+            batcher.emit(EventKind::AgentOutput.as_str(), &line);
         }
+        batcher.finish().await;
 
         info!(
             "📖 Finished reading Claude stdout. Total lines: {}",
@@ -1260,9 +1649,9 @@ async fn spawn_agent_system(
 
             error!("stderr[{}]: {}", error_count, line);
             // Emit error lines to the frontend with run_id for isolation
-            let _ = app_handle_stderr.emit(&format!("agent-error:{}", run_id), &line);
+            let _ = AppEvent::AgentError { run_id, line: line.clone() }.emit(&app_handle_stderr);
             // Also emit to the generic event for backward compatibility
-            let _ = app_handle_stderr.emit("agent-error", &line);
+            let _ = AppEvent::AgentError { run_id, line }.emit_global(&app_handle_stderr);
         }
 
         if error_count > 0 {
@@ -1363,15 +1752,19 @@ async fn spawn_agent_system(
                 }
 
                 // Update database
-                if let Ok(conn) = Connection::open(&db_path_for_monitor) {
+                if let Ok(conn) = super::db_pool::open_connection(&db_path_for_monitor) {
                     let _ = conn.execute(
                         "UPDATE agent_runs SET status = 'failed', completed_at = CURRENT_TIMESTAMP WHERE id = ?1",
                         params![run_id],
                     );
                 }
 
-                let _ = app.emit("agent-complete", false);
-                let _ = app.emit(&format!("agent-complete:{}", run_id), false);
+                super::accessibility::run_finished(&app, &AnnouncedRun::Agent(run_id), false);
+                super::unseen_completions::agent_run_finished(&app, run_id, false);
+                super::power::run_finished(&app, &AnnouncedRun::Agent(run_id));
+                let complete = AppEvent::AgentComplete { run_id, success: false };
+                let _ = complete.emit_global(&app);
+                let _ = complete.emit(&app);
                 return;
             }
 
@@ -1397,10 +1790,13 @@ async fn spawn_agent_system(
         info!("✅ Claude process execution monitoring complete");
 
         // Update the run record with session ID and mark as completed - open a new connection
-        if let Ok(conn) = Connection::open(&db_path_for_monitor) {
+        if let Ok(conn) = super::db_pool::open_connection(&db_path_for_monitor) {
             info!("🔄 Updating database with extracted session ID: {}", extracted_session_id);
             match conn.execute(
-                "UPDATE agent_runs SET session_id = ?1, status = 'completed', completed_at = CURRENT_TIMESTAMP WHERE id = ?2",
+                "UPDATE agent_runs SET session_id = ?1,
+                     status = CASE WHEN status = 'running' THEN 'completed' ELSE status END,
+                     completed_at = COALESCE(completed_at, CURRENT_TIMESTAMP)
+                 WHERE id = ?2",
                 params![extracted_session_id, run_id],
             ) {
                 Ok(rows_affected) => {
@@ -1420,8 +1816,14 @@ async fn spawn_agent_system(
 
         // Cleanup will be handled by the cleanup_finished_processes function
 
-        let _ = app.emit("agent-complete", true);
-        let _ = app.emit(&format!("agent-complete:{}", run_id), true);
+        super::agent_results::agent_run_finished(&app, run_id).await;
+        super::accessibility::run_finished(&app, &AnnouncedRun::Agent(run_id), true);
+        super::unseen_completions::agent_run_finished(&app, run_id, true);
+        super::power::run_finished(&app, &AnnouncedRun::Agent(run_id));
+        super::global_search::invalidate_search_index();
+        let complete = AppEvent::AgentComplete { run_id, success: true };
+        let _ = complete.emit_global(&app);
+        let _ = complete.emit(&app);
     });
 
     Ok(run_id)
@@ -1433,7 +1835,7 @@ pub async fn list_running_sessions(
     db: State<'_, AgentDb>,
     registry: State<'_, crate::process::ProcessRegistryState>,
 ) -> Result<Vec<AgentRun>, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
 
     // First get all running sessions from the database
     let mut stmt = conn.prepare(
@@ -1523,7 +1925,7 @@ pub async fn kill_agent_session(
     // If registry kill didn't work, try fallback with PID from database
     if !killed_via_registry {
         let pid_result = {
-            let conn = db.0.lock().map_err(|e| e.to_string())?;
+            let conn = db.0.get().map_err(|e| e.to_string())?;
             conn.query_row(
                 "SELECT pid FROM agent_runs WHERE id = ?1 AND status = 'running'",
                 params![run_id],
@@ -1539,14 +1941,16 @@ pub async fn kill_agent_session(
     }
 
     // Update the database to mark as cancelled
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
     let updated = conn.execute(
         "UPDATE agent_runs SET status = 'cancelled', completed_at = CURRENT_TIMESTAMP WHERE id = ?1 AND status = 'running'",
         params![run_id],
     ).map_err(|e| e.to_string())?;
 
     // Emit cancellation event with run_id for proper isolation
-    let _ = app.emit(&format!("agent-cancelled:{}", run_id), true);
+    super::accessibility::run_cancelled(&app, &AnnouncedRun::Agent(run_id));
+    super::power::run_finished(&app, &AnnouncedRun::Agent(run_id));
+    let _ = AppEvent::AgentCancelled { run_id }.emit(&app);
 
     Ok(updated > 0 || killed_via_registry)
 }
@@ -1557,7 +1961,7 @@ pub async fn get_session_status(
     db: State<'_, AgentDb>,
     run_id: i64,
 ) -> Result<Option<String>, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
 
     match conn.query_row(
         "SELECT status FROM agent_runs WHERE id = ?1",
@@ -1573,7 +1977,7 @@ pub async fn get_session_status(
 /// Cleanup finished processes and update their status
 #[tauri::command]
 pub async fn cleanup_finished_processes(db: State<'_, AgentDb>) -> Result<Vec<i64>, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
 
     // Get all running processes
     let mut stmt = conn
@@ -1647,7 +2051,8 @@ pub async fn cleanup_finished_processes(db: State<'_, AgentDb>) -> Result<Vec<i6
     Ok(cleaned_up)
 }
 
-/// Get live output from a running process
+/// Get live output from a running process, or from its journal once it is no longer
+/// in the registry, e.g. after a restart
 #[tauri::command]
 pub async fn get_live_session_output(
     registry: State<'_, crate::process::ProcessRegistryState>,
@@ -1656,6 +2061,41 @@ pub async fn get_live_session_output(
     registry.0.get_live_output(run_id)
 }
 
+/// Read a byte window of a process' live output, including output spilled or journaled to disk.
+/// Lets the UI page through huge outputs instead of transferring them whole.
+#[tauri::command]
+pub async fn get_live_output_range(
+    registry: State<'_, crate::process::ProcessRegistryState>,
+    run_id: i64,
+    offset: u64,
+    len: usize,
+) -> Result<Option<crate::process::LiveOutputRange>, String> {
+    registry.0.get_live_output_range(run_id, offset, len)
+}
+
+/// Get the per-process in-memory live output cap in bytes
+#[tauri::command]
+pub async fn get_live_output_limit(
+    registry: State<'_, crate::process::ProcessRegistryState>,
+) -> Result<usize, String> {
+    registry.0.live_output_max_bytes()
+}
+
+/// Set the per-process in-memory live output cap in bytes; older output spills to disk
+#[tauri::command]
+pub async fn set_live_output_limit(
+    registry: State<'_, crate::process::ProcessRegistryState>,
+    max_bytes: usize,
+) -> Result<(), String> {
+    if max_bytes < crate::process::MIN_LIVE_OUTPUT_MAX_BYTES {
+        return Err(format!(
+            "Live output limit must be at least {} bytes",
+            crate::process::MIN_LIVE_OUTPUT_MAX_BYTES
+        ));
+    }
+    registry.0.set_live_output_max_bytes(max_bytes)
+}
+
 /// Get real-time output for a running session by reading its JSONL file with live output fallback
 #[tauri::command]
 pub async fn get_session_output(
@@ -1739,18 +2179,32 @@ pub async fn get_session_output(
     }
 }
 
-/// Stream real-time session output by watching the JSONL file
+/// Stream real-time session output by watching the JSONL file. After a restart the
+/// output journaled while the app was running is replayed first.
 #[tauri::command]
 pub async fn stream_session_output(
     app: AppHandle,
     db: State<'_, AgentDb>,
+    registry: State<'_, crate::process::ProcessRegistryState>,
     run_id: i64,
 ) -> Result<(), String> {
     // Get the session information
     let run = get_agent_run(db, run_id).await?;
 
+    // The registry forgets runs on restart; their output is still on disk
+    let mut replayed = false;
+    if registry.0.get_process(run_id)?.is_none() {
+        if let Some(content) = registry.0.persisted_live_output(run_id)? {
+            let _ = AppEvent::SessionOutputUpdate { run_id, content }.emit(&app);
+            replayed = true;
+        }
+    }
+
     // If no session ID yet, can't stream
     if run.session_id.is_empty() {
+        if replayed {
+            return Ok(());
+        }
         return Err("Session not started yet".to_string());
     }
 
@@ -1779,8 +2233,7 @@ pub async fn stream_session_output(
                     if current_size > last_size {
                         // File has grown, read new content
                         if let Ok(content) = tokio::fs::read_to_string(&session_file).await {
-                            let _ = app
-                                .emit("session-output-update", &format!("{}:{}", run_id, content));
+                            let _ = AppEvent::SessionOutputUpdate { run_id, content }.emit(&app);
                         }
                         last_size = current_size;
                     }
@@ -1793,7 +2246,7 @@ pub async fn stream_session_output(
 
             // Check if the session is still running by querying the database
             // If the session is no longer running, stop streaming
-            if let Ok(conn) = rusqlite::Connection::open(
+            if let Ok(conn) = super::db_pool::open_connection(
                 app.path()
                     .app_data_dir()
                     .expect("Failed to get app data dir")
@@ -1829,7 +2282,7 @@ pub async fn stream_session_output(
 /// Export a single agent to JSON format
 #[tauri::command]
 pub async fn export_agent(db: State<'_, AgentDb>, id: i64) -> Result<String, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
 
     // Fetch the agent
     let agent = conn
@@ -1880,34 +2333,20 @@ pub async fn export_agent_to_file(
 /// Get the stored Claude binary path from settings
 #[tauri::command]
 pub async fn get_claude_binary_path(db: State<'_, AgentDb>) -> Result<Option<String>, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
-
-    match conn.query_row(
-        "SELECT value FROM app_settings WHERE key = 'claude_binary_path'",
-        [],
-        |row| row.get::<_, String>(0),
-    ) {
-        Ok(path) => Ok(Some(path)),
-        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(format!("Failed to get Claude binary path: {}", e)),
-    }
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    super::settings_store::get(&conn, super::settings_store::CLAUDE_BINARY_PATH)
+        .map_err(|e| format!("Failed to get Claude binary path: {}", e))
 }
 
 /// Set the Claude binary path in settings
 #[tauri::command]
-pub async fn set_claude_binary_path(db: State<'_, AgentDb>, path: String) -> Result<(), String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
-
+pub async fn set_claude_binary_path(app: AppHandle, path: String) -> Result<(), String> {
     // Special handling for bundled sidecar reference
     if path == "claude-code" {
         // For bundled sidecar, we don't need to validate file existence
         // as it's handled by Tauri's sidecar system
-        conn.execute(
-            "INSERT INTO app_settings (key, value) VALUES ('claude_binary_path', ?1)
-             ON CONFLICT(key) DO UPDATE SET value = ?1",
-            params![path],
-        )
-        .map_err(|e| format!("Failed to save Claude binary path: {}", e))?;
+        super::settings_store::set(&app, super::settings_store::CLAUDE_BINARY_PATH, &path)
+            .map_err(|e| format!("Failed to save Claude binary path: {}", e))?;
         return Ok(());
     }
 
@@ -1932,12 +2371,8 @@ pub async fn set_claude_binary_path(db: State<'_, AgentDb>, path: String) -> Res
     }
 
     // Insert or update the setting
-    conn.execute(
-        "INSERT INTO app_settings (key, value) VALUES ('claude_binary_path', ?1)
-         ON CONFLICT(key) DO UPDATE SET value = ?1",
-        params![path],
-    )
-    .map_err(|e| format!("Failed to save Claude binary path: {}", e))?;
+    super::settings_store::set(&app, super::settings_store::CLAUDE_BINARY_PATH, &path)
+        .map_err(|e| format!("Failed to save Claude binary path: {}", e))?;
 
     Ok(())
 }
@@ -2156,7 +2591,7 @@ pub async fn import_agent(db: State<'_, AgentDb>, json_data: String) -> Result<A
     }
 
     let agent_data = export_data.agent;
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
 
     // Check if an agent with the same name already exists
     let existing_count: i64 = conn
@@ -2214,6 +2649,7 @@ pub async fn import_agent(db: State<'_, AgentDb>, json_data: String) -> Result<A
         )
         .map_err(|e| format!("Failed to fetch created agent: {}", e))?;
 
+    super::global_search::invalidate_search_index();
     Ok(agent)
 }
 
@@ -2255,31 +2691,37 @@ struct GitHubApiResponse {
     file_type: String,
 }
 
-/// Fetch list of agents from GitHub repository
+/// Fetch list of agents from GitHub repository.
+///
+/// The index is cached with its ETag, so an unchanged index is not downloaded again
+/// and the last known index is returned when GitHub can't be reached.
 #[tauri::command]
-pub async fn fetch_github_agents() -> Result<Vec<GitHubAgentFile>, String> {
+pub async fn fetch_github_agents(app: AppHandle) -> Result<Vec<GitHubAgentFile>, String> {
     info!("Fetching agents from GitHub repository...");
 
-    let client = reqwest::Client::new();
     let url = "https://api.github.com/repos/getAsterisk/claudia/contents/cc_agents";
+    let cached = agent_downloads::load_cached_index::<GitHubAgentFile>(&app);
+    let etag = cached.as_ref().and_then(|c| c.etag.as_deref());
+
+    let (etag, body) = match agent_downloads::fetch_index(url, etag).await {
+        Ok(agent_downloads::IndexResponse::Modified { etag, body }) => (etag, body),
+        Ok(agent_downloads::IndexResponse::NotModified) => {
+            let files = cached.map(|c| c.files).unwrap_or_default();
+            info!("GitHub agent index unchanged, {} agents cached", files.len());
+            return Ok(files);
+        }
+        Err(e) => {
+            return match cached {
+                Some(cached) => {
+                    warn!("Failed to fetch GitHub agents, using cached index: {}", e);
+                    Ok(cached.files)
+                }
+                None => Err(e),
+            };
+        }
+    };
 
-    let response = client
-        .get(url)
-        .header("Accept", "application/vnd.github+json")
-        .header("User-Agent", "Claude-Workbench-App")
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch from GitHub: {}", e))?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("GitHub API error ({}): {}", status, error_text));
-    }
-
-    let api_files: Vec<GitHubApiResponse> = response
-        .json()
-        .await
+    let api_files: Vec<GitHubApiResponse> = serde_json::from_str(&body)
         .map_err(|e| format!("Failed to parse GitHub response: {}", e))?;
 
     // Filter only .claudia.json files
@@ -2297,35 +2739,24 @@ pub async fn fetch_github_agents() -> Result<Vec<GitHubAgentFile>, String> {
         })
         .collect();
 
-    info!("Found {} agents on GitHub", agent_files.len());
-    Ok(agent_files)
+    let index = agent_downloads::CachedIndex { etag, files: agent_files };
+    if let Err(e) = agent_downloads::save_cached_index(&app, &index) {
+        warn!("{}", e);
+    }
+
+    info!("Found {} agents on GitHub", index.files.len());
+    Ok(index.files)
 }
 
-/// Fetch and preview a specific agent from GitHub
+/// Fetch and preview a specific agent from GitHub.
+///
+/// Downloads are retried and resumed, reported as `agent-download-progress` events,
+/// and cached so previously browsed agents can be imported offline.
 #[tauri::command]
-pub async fn fetch_github_agent_content(download_url: String) -> Result<AgentExport, String> {
+pub async fn fetch_github_agent_content(app: AppHandle, download_url: String) -> Result<AgentExport, String> {
     info!("Fetching agent content from: {}", download_url);
 
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&download_url)
-        .header("Accept", "application/json")
-        .header("User-Agent", "Claude-Workbench-App")
-        .send()
-        .await
-        .map_err(|e| format!("Failed to download agent: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!(
-            "Failed to download agent: HTTP {}",
-            response.status()
-        ));
-    }
-
-    let json_text = response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read response: {}", e))?;
+    let json_text = agent_downloads::download_agent_file(&app, &download_url).await?;
 
     // Parse and validate the agent data
     let export_data: AgentExport = serde_json::from_str(&json_text)
@@ -2345,13 +2776,14 @@ pub async fn fetch_github_agent_content(download_url: String) -> Result<AgentExp
 /// Import an agent directly from GitHub
 #[tauri::command]
 pub async fn import_agent_from_github(
+    app: AppHandle,
     db: State<'_, AgentDb>,
     download_url: String,
 ) -> Result<Agent, String> {
     info!("Importing agent from GitHub: {}", download_url);
 
     // First, fetch the agent content
-    let export_data = fetch_github_agent_content(download_url).await?;
+    let export_data = fetch_github_agent_content(app, download_url).await?;
 
     // Convert to JSON string and use existing import logic
     let json_data = serde_json::to_string(&export_data)
@@ -2361,11 +2793,12 @@ pub async fn import_agent_from_github(
     import_agent(db, json_data).await
 }
 
-/// Load agent session history from JSONL file
+/// Load agent session history from JSONL file, trimmed by `filter` if given
 /// Similar to Claude Code's load_session_history, but searches across all project directories
 #[tauri::command]
 pub async fn load_agent_session_history(
     session_id: String,
+    filter: Option<super::history_filter::HistoryFilter>,
 ) -> Result<Vec<serde_json::Value>, String> {
     log::info!("Loading agent session history for session: {}", session_id);
 
@@ -2420,56 +2853,67 @@ pub async fn load_agent_session_history(
             }
         }
 
-        Ok(messages)
+        Ok(super::history_filter::apply(messages, 0, &filter.unwrap_or_default()))
     } else {
         Err(format!("Session file not found: {}", session_id))
     }
 }
 
-/// Insert real-time usage data into the database
-pub fn insert_usage_entry(
-    db: &AgentDb,
-    session_id: &str,
-    timestamp: &str,
-    model: &str,
-    input_tokens: u64,
-    output_tokens: u64,
-    cache_creation_tokens: Option<u64>,
-    cache_read_tokens: Option<u64>,
-    project_path: Option<&str>,
+/// Insert a batch of real-time usage rows in a single transaction. Rows whose
+/// `row_id` is already stored are skipped, so a replayed batch is not counted twice.
+pub fn insert_usage_rows(
+    conn: &mut Connection,
+    rows: &[super::usage_writer::UsageRow],
 ) -> Result<(), String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
-
-    let cache_creation = cache_creation_tokens.unwrap_or(0);
-    let cache_read = cache_read_tokens.unwrap_or(0);
-    let total_tokens = input_tokens + output_tokens + cache_creation + cache_read;
-
-    // Calculate cost based on model (simplified version)
-    let cost = calculate_usage_cost(model, input_tokens, output_tokens, cache_creation, cache_read);
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    {
+        let mut stmt = tx
+            .prepare_cached(
+                "INSERT OR IGNORE INTO usage_entries (
+                    session_id, timestamp, model, input_tokens, output_tokens,
+                    cache_creation_tokens, cache_read_tokens, total_tokens, cost, project_path,
+                    provider_source, operator_id, provider_key, row_id
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            )
+            .map_err(|e| e.to_string())?;
+
+        for row in rows {
+            let total_tokens = row.input_tokens
+                + row.output_tokens
+                + row.cache_creation_tokens
+                + row.cache_read_tokens;
+
+            // Calculate cost based on model (simplified version)
+            let cost = calculate_usage_cost(
+                &row.model,
+                row.input_tokens,
+                row.output_tokens,
+                row.cache_creation_tokens,
+                row.cache_read_tokens,
+            );
 
-    conn.execute(
-        "INSERT INTO usage_entries (
-            session_id, timestamp, model, input_tokens, output_tokens,
-            cache_creation_tokens, cache_read_tokens, total_tokens, cost, project_path
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-        rusqlite::params![
-            session_id,
-            timestamp,
-            model,
-            input_tokens as i64,
-            output_tokens as i64,
-            cache_creation as i64,
-            cache_read as i64,
-            total_tokens as i64,
-            cost,
-            project_path.unwrap_or("")
-        ],
-    ).map_err(|e| e.to_string())?;
+            stmt.execute(rusqlite::params![
+                row.session_id,
+                row.timestamp,
+                row.model,
+                row.input_tokens as i64,
+                row.output_tokens as i64,
+                row.cache_creation_tokens as i64,
+                row.cache_read_tokens as i64,
+                total_tokens as i64,
+                cost,
+                row.project_path,
+                row.provider_source,
+                row.operator_id,
+                row.provider_key,
+                row.row_id
+            ])
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
 
-    log::debug!(
-        "Inserted usage entry: session={}, tokens={}+{}={}, cost={}",
-        session_id, input_tokens, output_tokens, total_tokens, cost
-    );
+    log::debug!("Inserted {} usage entries", rows.len());
 
     Ok(())
 }
@@ -0,0 +1,401 @@
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::events::EVENT_SCHEMA_VERSION;
+
+/// Major version of the command surface; bumped when a command is removed or its
+/// arguments or result change incompatibly
+pub const API_VERSION_MAJOR: u32 = 1;
+
+/// Minor version of the command surface; bumped when commands or optional fields are added
+pub const API_VERSION_MINOR: u32 = 64;
+
+/// Renamed commands as `(old, new)`.
+///
+/// The old name stays registered as a thin wrapper around the new command until the
+/// next major version, and is listed here so the manifest marks it as replaced.
+const RENAMED_COMMANDS: &[(&str, &str)] = &[];
+
+/// Feature flags, each enabled when all of its commands are registered
+const FEATURES: &[(&str, &[&str])] = &[
+    ("checkpoints", &["create_checkpoint", "restore_checkpoint", "list_checkpoints"]),
+    ("checkpoint-jobs", &["create_checkpoint_async", "cancel_checkpoint_job"]),
+    ("checkpoint-scope", &["get_checkpoint_scope", "update_checkpoint_scope"]),
+    (
+        "safety-checkpoints",
+        &["list_safety_checkpoints", "restore_safety_checkpoint", "undo_last_destructive_action"],
+    ),
+    ("event-catalog", &["get_event_catalog"]),
+    ("output-batching", &["set_output_batching", "get_output_batching"]),
+    ("global-search", &["global_search", "rebuild_search_index"]),
+    ("session-providers", &["bind_session_provider", "get_session_provider"]),
+    ("session-locks", &["get_session_lock", "force_unlock_session"]),
+    ("currency", &["get_currency_config", "update_currency_config"]),
+    ("team-usage", &["get_operator_id", "set_operator_id", "export_team_usage"]),
+    ("safety-status", &["get_safety_status", "disable_dangerous_skip"]),
+    ("prompt-queue", &["queue_followup_prompt", "list_queued_prompts", "remove_queued_prompt", "reorder_queued_prompts"]),
+    ("prompt-suggestions", &["suggest_prompts"]),
+    ("event-subscriptions", &["subscribe_events", "unsubscribe_events"]),
+    ("session-templates", &["create_session_template", "list_session_templates", "start_session_from_template"]),
+    ("session-export", &["export_session", "get_export_preview"]),
+    ("claude-md-lint", &["analyze_claude_md", "apply_claude_md_fixes"]),
+    ("hook-packs", &["list_hook_packs", "preview_hook_pack", "install_hook_pack"]),
+    (
+        "project-memory",
+        &[
+            "list_memory_entries",
+            "set_memory_value",
+            "add_memory_note",
+            "update_memory_entry",
+            "delete_memory_entry",
+            "mcp_register_memory_server",
+        ],
+    ),
+    ("project-knowledge", &["ingest_project_knowledge", "query_project_knowledge"]),
+    (
+        "execution-profiles",
+        &[
+            "list_execution_profiles",
+            "save_execution_profile",
+            "delete_execution_profile",
+            "apply_execution_profile",
+            "get_session_profile",
+        ],
+    ),
+    ("spawn-diagnostics", &["diagnose_spawn_failure"]),
+    (
+        "path-case-sensitivity",
+        &["get_path_case_sensitivity", "set_path_case_sensitivity", "repair_merged_projects"],
+    ),
+    (
+        "project-merge-exclusions",
+        &[
+            "list_project_merge_exclusions",
+            "exclude_from_project_merge",
+            "remove_project_merge_exclusion",
+            "split_merged_project",
+        ],
+    ),
+    ("session-import", &["import_session_file"]),
+    ("full-backup", &["create_full_backup", "restore_full_backup"]),
+    (
+        "capability-tokens",
+        &[
+            "create_capability_token",
+            "list_capability_tokens",
+            "revoke_capability_token",
+            "list_remote_invocation_audit",
+        ],
+    ),
+    ("local-api", &["get_local_api_status", "set_local_api_enabled"]),
+    ("metrics-exporter", &["set_metrics_exporter_enabled"]),
+    ("burn-rate", &["get_active_burn_rates"]),
+    ("run-idempotency-keys", &["get_run_dedupe_window", "set_run_dedupe_window"]),
+    ("run-handles", &["get_run_status"]),
+    (
+        "tasks",
+        &[
+            "create_task",
+            "list_tasks",
+            "delete_task",
+            "assign_to_task",
+            "remove_from_task",
+            "get_task_overview",
+        ],
+    ),
+    ("crash-reports", &["list_crash_reports", "export_crash_report"]),
+    ("startup-config", &["get_startup_timings", "get_startup_config", "set_startup_config"]),
+    (
+        "plugins",
+        &[
+            "list_plugins",
+            "get_plugins_dir",
+            "set_plugin_enabled",
+            "invoke_plugin_command",
+            "run_plugin_analyzer",
+        ],
+    ),
+    (
+        "session-worktrees",
+        &[
+            "create_session_worktree",
+            "list_session_worktrees",
+            "merge_session_worktree",
+            "remove_session_worktree",
+        ],
+    ),
+    ("restore-conflicts", &["list_restore_conflicts", "resolve_restore_conflict"]),
+    ("session-export-bundle", &["export_session"]),
+    ("stale-state-cleanup", &["clean_stale_claude_state"]),
+    ("context-exclusion", &["set_message_context_inclusion", "list_context_exclusions"]),
+    (
+        "response-language",
+        &[
+            "get_response_language",
+            "set_project_response_language",
+            "set_session_response_language",
+            "suggest_response_language",
+        ],
+    ),
+    (
+        "agent-schedules",
+        &[
+            "create_agent_schedule",
+            "update_agent_schedule",
+            "delete_agent_schedule",
+            "list_agent_schedules",
+            "list_agent_schedule_runs",
+        ],
+    ),
+    ("accessibility-events", &["get_accessibility_verbosity", "set_accessibility_verbosity"]),
+    (
+        "checkpoint-storage-compaction",
+        &["get_checkpoint_storage_stats", "compact_checkpoint_storage"],
+    ),
+    ("power-policy", &["get_power_policy", "update_power_policy"]),
+    (
+        "project-execution-config",
+        &["get_project_execution_config", "update_project_execution_config"],
+    ),
+    ("execution-pause", &["pause_execution", "resume_execution"]),
+    ("session-history-pages", &["load_session_history_page", "get_session_message_count"]),
+    ("fault-injection", &["get_fault_injection", "set_fault_injection"]),
+    ("file-edit-events", &["list_session_file_edits"]),
+    (
+        "project-gitignore-audit",
+        &["audit_project_gitignore", "ignore_local_files", "set_auto_gitignore"],
+    ),
+    (
+        "provider-key-rotation",
+        &["get_provider_keys", "add_provider_key", "remove_provider_key", "set_rotation_strategy"],
+    ),
+    (
+        "run-cost-cap",
+        &["execute_claude_code", "continue_claude_code", "resume_claude_code", "execute_agent"],
+    ),
+    ("workbench-settings-transfer", &["export_workbench_settings", "import_workbench_settings"]),
+    ("prompt-cost-estimate", &["estimate_prompt_cost"]),
+    ("model-comparison-report", &["get_model_comparison_report"]),
+    (
+        "scheduled-prompts",
+        &[
+            "schedule_prompt",
+            "update_scheduled_prompt",
+            "cancel_scheduled_prompt",
+            "list_scheduled_prompts",
+        ],
+    ),
+    (
+        "live-output-journal",
+        &["get_live_session_output", "get_live_output_range", "stream_session_output"],
+    ),
+    (
+        "unseen-completions",
+        &["list_unseen_completions", "mark_session_seen", "set_active_project"],
+    ),
+    ("hook-templates", &["list_hook_templates", "apply_hook_template"]),
+    ("hook-shells", &["trigger_hook_event", "validate_hook_command"]),
+    (
+        "env-profiles",
+        &[
+            "list_env_profiles",
+            "save_env_profile",
+            "delete_env_profile",
+            "execute_claude_code",
+            "execute_agent",
+        ],
+    ),
+    ("session-env", &["set_session_env", "get_session_env", "resume_claude_code"]),
+    (
+        "agent-results",
+        &["set_agent_output_schema", "get_agent_output_schema", "get_agent_run_result"],
+    ),
+    ("project-bootstrap", &["analyze_project_stack", "apply_project_bootstrap"]),
+    ("db-migrations", &["get_db_schema_info", "rollback_db_schema"]),
+    (
+        "history-filters",
+        &["load_session_history", "load_session_history_page", "load_agent_session_history"],
+    ),
+    ("history-delta", &["get_session_history_delta", "load_session_history_page"]),
+    (
+        "prompt-templates",
+        &[
+            "list_prompt_templates",
+            "create_prompt_template",
+            "update_prompt_template",
+            "delete_prompt_template",
+            "expand_prompt_template",
+            "execute_claude_code",
+        ],
+    ),
+    ("service-ports", &["get_service_ports", "get_local_api_status"]),
+    (
+        "file-watch",
+        &["watch_project_path", "unwatch_project_path", "list_file_change_watchers"],
+    ),
+    (
+        "router-validation",
+        &["router_validate_providers", "switch_provider_config"],
+    ),
+    ("checkpoint-workdir-diff", &["diff_checkpoint_against_workdir"]),
+];
+
+/// How a command behaves beyond a plain request/response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export)]
+pub enum CommandCapability {
+    /// Output arrives as events while the command runs
+    Streaming,
+    /// Returns a job or scan id at once; progress arrives as events
+    Job,
+    /// Can be stopped through a companion cancel command
+    Cancellable,
+    /// Talks to a remote service
+    Network,
+}
+
+/// Capability flags of commands that have any
+const COMMAND_CAPABILITIES: &[(&str, &[CommandCapability])] = {
+    use CommandCapability::*;
+    &[
+        ("execute_claude_code", &[Streaming, Cancellable]),
+        ("continue_claude_code", &[Streaming, Cancellable]),
+        ("resume_claude_code", &[Streaming, Cancellable]),
+        ("start_session_from_template", &[Streaming, Cancellable]),
+        ("execute_agent", &[Streaming, Cancellable]),
+        ("stream_session_output", &[Streaming]),
+        ("create_checkpoint_async", &[Job, Cancellable]),
+        ("start_project_scan", &[Job]),
+        ("enhance_prompt", &[Network]),
+        ("enhance_prompt_with_gemini", &[Network]),
+        ("fetch_github_agents", &[Network]),
+        ("fetch_github_agent_content", &[Network]),
+        ("import_agent_from_github", &[Network]),
+        ("test_provider_connection", &[Network]),
+        ("mcp_test_connection", &[Network]),
+        ("translate", &[Network]),
+        ("translate_batch", &[Network]),
+        ("update_currency_config", &[Network]),
+        ("suggest_prompts", &[Network]),
+    ]
+};
+
+/// Result of `get_api_version`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ApiVersionInfo {
+    /// `major.minor` of the command surface
+    pub api_version: String,
+    pub app_version: String,
+    pub event_schema_version: u32,
+    /// Whether a client built against the requested version can use this backend
+    pub compatible: bool,
+    /// Enabled feature flags
+    pub features: Vec<String>,
+}
+
+/// Manifest entry describing one command
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CommandDescriptor {
+    pub name: String,
+    pub capabilities: Vec<CommandCapability>,
+    /// Set on compatibility shims: the command this name was renamed to
+    pub replaced_by: Option<String>,
+}
+
+/// Result of `get_command_manifest`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CommandManifest {
+    pub api_version: String,
+    pub app_version: String,
+    pub commands: Vec<CommandDescriptor>,
+}
+
+/// Names of the registered commands, recorded when the invoke handler is built
+static REGISTERED_COMMANDS: OnceCell<Vec<String>> = OnceCell::new();
+
+/// Record the registered commands from the stringified `generate_handler!` input,
+/// where commands may be given as paths (`commands::x::command_name`)
+pub fn register_commands(handler_input: &str) {
+    let names = handler_input
+        .split(',')
+        .filter_map(|path| path.rsplit("::").next())
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect();
+    let _ = REGISTERED_COMMANDS.set(names);
+}
+
+fn registered_commands() -> &'static [String] {
+    REGISTERED_COMMANDS.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+fn api_version() -> String {
+    format!("{}.{}", API_VERSION_MAJOR, API_VERSION_MINOR)
+}
+
+fn enabled_features() -> Vec<String> {
+    let registered = registered_commands();
+    FEATURES
+        .iter()
+        .filter(|(_, commands)| commands.iter().all(|c| registered.iter().any(|r| r == c)))
+        .map(|(feature, _)| feature.to_string())
+        .collect()
+}
+
+/// Whether a client built against `major.minor` can use this backend
+fn is_compatible(client_version: &str) -> Result<bool, String> {
+    let (major, minor) = client_version
+        .trim()
+        .split_once('.')
+        .and_then(|(major, minor)| Some((major.parse::<u32>().ok()?, minor.parse::<u32>().ok()?)))
+        .ok_or_else(|| format!("Invalid API version '{}', expected major.minor", client_version))?;
+    Ok(major == API_VERSION_MAJOR && minor <= API_VERSION_MINOR)
+}
+
+/// Negotiate the API version: reports this backend's version and features, and
+/// whether the client's `major.minor` is supported
+#[tauri::command]
+pub async fn get_api_version(client_version: Option<String>) -> Result<ApiVersionInfo, String> {
+    let compatible = match client_version {
+        Some(version) => is_compatible(&version)?,
+        None => true,
+    };
+    Ok(ApiVersionInfo {
+        api_version: api_version(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        event_schema_version: EVENT_SCHEMA_VERSION,
+        compatible,
+        features: enabled_features(),
+    })
+}
+
+/// List every registered command with its capability flags
+#[tauri::command]
+pub async fn get_command_manifest() -> Result<CommandManifest, String> {
+    let commands = registered_commands()
+        .iter()
+        .map(|name| CommandDescriptor {
+            name: name.clone(),
+            capabilities: COMMAND_CAPABILITIES
+                .iter()
+                .find(|(command, _)| command == name)
+                .map(|(_, capabilities)| capabilities.to_vec())
+                .unwrap_or_default(),
+            replaced_by: RENAMED_COMMANDS
+                .iter()
+                .find(|(old, _)| old == name)
+                .map(|(_, new)| new.to_string()),
+        })
+        .collect();
+    Ok(CommandManifest {
+        api_version: api_version(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        commands,
+    })
+}
@@ -0,0 +1,861 @@
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::stream::{DecryptorBE32, EncryptorBE32};
+use aes_gcm::aead::{KeyInit, OsRng};
+use aes_gcm::Aes256Gcm;
+use rusqlite::DatabaseName;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use super::agents::{init_database, AgentDb};
+use super::api_manifest::{API_VERSION_MAJOR, API_VERSION_MINOR};
+use super::claude::get_claude_dir;
+use super::events::AppEvent;
+
+const BACKUP_MAGIC: &[u8; 8] = b"CWBACKUP";
+
+/// Bumped when the container or archive layout changes
+const BACKUP_FORMAT_VERSION: u32 = 1;
+
+const FLAG_ENCRYPTED: u8 = 1;
+
+/// Plaintext bytes per encrypted frame
+const CHUNK_SIZE: usize = 64 * 1024;
+
+const SALT_LEN: usize = 16;
+
+/// AES-GCM nonce minus the 32-bit counter and last-frame flag of the STREAM construction
+const NONCE_PREFIX_LEN: usize = 7;
+
+/// First archive entry, read before anything is extracted
+const INFO_ENTRY: &str = "backup.json";
+
+/// Last archive entry, with the size and hash of every file
+const MANIFEST_ENTRY: &str = "manifest.json";
+
+/// Archive directory for files from ~/.claude
+const CLAUDE_PREFIX: &str = "claude";
+
+/// Archive directory for files from the app data dir
+const APP_PREFIX: &str = "app";
+
+const DATABASE_FILE: &str = "agents.db";
+const SECRETS_FILE: &str = "secrets.json";
+
+/// Parts of ~/.claude that make up the workbench state; sessions and checkpoints live in `projects`
+const CLAUDE_ITEMS: &[&str] = &[
+    "settings.json",
+    "settings.local.json",
+    "CLAUDE.md",
+    "agents",
+    "commands",
+    "projects",
+];
+
+/// Minimum time between two `backup-progress` events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum BackupOperation {
+    Backup,
+    Restore,
+}
+
+/// Payload of `backup-progress`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BackupProgress {
+    pub operation: BackupOperation,
+    #[ts(type = "number")]
+    pub files_done: usize,
+    #[ts(type = "number")]
+    pub files_total: usize,
+    #[ts(type = "number")]
+    pub bytes_done: u64,
+    #[ts(type = "number")]
+    pub bytes_total: u64,
+    /// Archive path of the file being processed
+    pub current: Option<String>,
+    pub done: bool,
+}
+
+/// `backup.json`: what created the backup and how much it holds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub format_version: u32,
+    pub app_version: String,
+    /// Backend API version, `major.minor`
+    pub api_version: String,
+    pub created_at: String,
+    pub encrypted: bool,
+    /// Stored secrets are only included in encrypted backups
+    pub includes_secrets: bool,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// `manifest.json`: archive path to size and hash
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub files: BTreeMap<String, ManifestEntry>,
+}
+
+/// Returned by `create_full_backup`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSummary {
+    pub path: String,
+    pub files: usize,
+    /// Size of the backed up files
+    pub bytes: u64,
+    /// Size of the backup file
+    pub archive_bytes: u64,
+    pub encrypted: bool,
+    pub includes_secrets: bool,
+    pub created_at: String,
+}
+
+/// Returned by `restore_full_backup`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreSummary {
+    /// Version of the app that created the backup
+    pub app_version: String,
+    pub created_at: String,
+    pub files_restored: usize,
+    pub database_restored: bool,
+    pub secrets_restored: bool,
+    /// Settings cached at startup still hold their old values until the app restarts
+    pub restart_recommended: bool,
+}
+
+/// Throttles progress reports; the first and the final one always go out
+struct ProgressReporter<'a> {
+    progress: BackupProgress,
+    last_report: Option<Instant>,
+    report: &'a mut dyn FnMut(&BackupProgress),
+}
+
+impl<'a> ProgressReporter<'a> {
+    fn new(operation: BackupOperation, report: &'a mut dyn FnMut(&BackupProgress)) -> Self {
+        Self {
+            progress: BackupProgress {
+                operation,
+                files_done: 0,
+                files_total: 0,
+                bytes_done: 0,
+                bytes_total: 0,
+                current: None,
+                done: false,
+            },
+            last_report: None,
+            report,
+        }
+    }
+
+    fn update(&mut self, update: impl FnOnce(&mut BackupProgress)) {
+        update(&mut self.progress);
+        if self.progress.done || self.last_report.map_or(true, |last| last.elapsed() >= PROGRESS_INTERVAL) {
+            (self.report)(&self.progress);
+            self.last_report = Some(Instant::now());
+        }
+    }
+
+    fn finish(&mut self) {
+        self.update(|progress| {
+            progress.current = None;
+            progress.done = true;
+        });
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Aes256Gcm, String> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive backup key: {}", e))?;
+    Aes256Gcm::new_from_slice(&key).map_err(|e| e.to_string())
+}
+
+/// Frames of `[last: u8][length: u32 BE][ciphertext]`, each frame one STREAM segment
+struct EncryptingWriter<W: Write> {
+    inner: W,
+    encryptor: Option<EncryptorBE32<Aes256Gcm>>,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> EncryptingWriter<W> {
+    fn new(inner: W, cipher: Aes256Gcm, nonce_prefix: &[u8]) -> Self {
+        Self {
+            inner,
+            encryptor: Some(EncryptorBE32::from_aead(cipher, GenericArray::from_slice(nonce_prefix))),
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+        }
+    }
+
+    fn write_frame(&mut self, last: bool, ciphertext: &[u8]) -> io::Result<()> {
+        self.inner.write_all(&[last as u8])?;
+        self.inner.write_all(&(ciphertext.len() as u32).to_be_bytes())?;
+        self.inner.write_all(ciphertext)
+    }
+
+    /// Encrypt the remaining bytes as the last frame
+    fn finish(mut self) -> io::Result<W> {
+        let encryptor = self
+            .encryptor
+            .take()
+            .ok_or_else(|| io::Error::other("backup stream already finished"))?;
+        let ciphertext = encryptor
+            .encrypt_last(self.buffer.as_slice())
+            .map_err(|_| io::Error::other("failed to encrypt backup"))?;
+        self.write_frame(true, &ciphertext)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncryptingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        // Keep a partial or full chunk back so `finish` always has a last frame to write
+        while self.buffer.len() > CHUNK_SIZE {
+            let chunk: Vec<u8> = self.buffer.drain(..CHUNK_SIZE).collect();
+            let encryptor = self
+                .encryptor
+                .as_mut()
+                .ok_or_else(|| io::Error::other("backup stream already finished"))?;
+            let ciphertext = encryptor
+                .encrypt_next(chunk.as_slice())
+                .map_err(|_| io::Error::other("failed to encrypt backup"))?;
+            self.write_frame(false, &ciphertext)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+struct DecryptingReader<R: Read> {
+    inner: R,
+    decryptor: Option<DecryptorBE32<Aes256Gcm>>,
+    plaintext: Vec<u8>,
+    position: usize,
+}
+
+fn bad_backup(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+impl<R: Read> DecryptingReader<R> {
+    fn new(inner: R, cipher: Aes256Gcm, nonce_prefix: &[u8]) -> Self {
+        Self {
+            inner,
+            decryptor: Some(DecryptorBE32::from_aead(cipher, GenericArray::from_slice(nonce_prefix))),
+            plaintext: Vec::new(),
+            position: 0,
+        }
+    }
+
+    fn read_frame(&mut self) -> io::Result<()> {
+        let mut header = [0u8; 5];
+        self.inner
+            .read_exact(&mut header)
+            .map_err(|_| bad_backup("Backup file is truncated"))?;
+        let last = header[0] == 1;
+        let length = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        if length > CHUNK_SIZE + 16 {
+            return Err(bad_backup("Backup file is corrupted"));
+        }
+        let mut ciphertext = vec![0u8; length];
+        self.inner
+            .read_exact(&mut ciphertext)
+            .map_err(|_| bad_backup("Backup file is truncated"))?;
+
+        let wrong_key = |_| bad_backup("Wrong passphrase, or the backup file is corrupted");
+        self.plaintext = if last {
+            let decryptor = self.decryptor.take().ok_or_else(|| bad_backup("Backup file is corrupted"))?;
+            decryptor.decrypt_last(ciphertext.as_slice()).map_err(wrong_key)?
+        } else {
+            let decryptor = self.decryptor.as_mut().ok_or_else(|| bad_backup("Backup file is corrupted"))?;
+            decryptor.decrypt_next(ciphertext.as_slice()).map_err(wrong_key)?
+        };
+        self.position = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecryptingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.plaintext.len() {
+            if self.decryptor.is_none() {
+                return Ok(0);
+            }
+            self.read_frame()?;
+        }
+        let n = buf.len().min(self.plaintext.len() - self.position);
+        buf[..n].copy_from_slice(&self.plaintext[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+/// Where the compressed archive goes: the file itself or the encryption layer in front of it
+enum Sink {
+    Plain(BufWriter<File>),
+    Encrypted(Box<EncryptingWriter<BufWriter<File>>>),
+}
+
+impl Sink {
+    fn finish(self) -> io::Result<()> {
+        let mut file = match self {
+            Sink::Plain(file) => file,
+            Sink::Encrypted(writer) => writer.finish()?,
+        };
+        file.flush()?;
+        file.get_ref().sync_all()
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Sink::Plain(file) => file.write(buf),
+            Sink::Encrypted(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::Plain(file) => file.flush(),
+            Sink::Encrypted(writer) => writer.flush(),
+        }
+    }
+}
+
+/// Hashes and counts what passes through
+struct HashingReader<R: Read> {
+    inner: R,
+    hasher: Sha256,
+    bytes: u64,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.bytes += n as u64;
+        Ok(n)
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A file going into the backup
+struct BackupFile {
+    archive_path: String,
+    source: PathBuf,
+    size: u64,
+}
+
+/// What a backup is made of
+pub struct BackupSources {
+    pub claude_dir: PathBuf,
+    /// Consistent copy of agents.db
+    pub database: Option<PathBuf>,
+    pub secrets: Option<PathBuf>,
+}
+
+fn collect_files(sources: &BackupSources) -> Result<Vec<BackupFile>, String> {
+    let mut files = Vec::new();
+    let mut add = |archive_path: String, source: &Path| -> Result<(), String> {
+        let metadata = fs::metadata(source).map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        files.push(BackupFile {
+            archive_path,
+            source: source.to_path_buf(),
+            size: metadata.len(),
+        });
+        Ok(())
+    };
+
+    if let Some(database) = &sources.database {
+        add(format!("{}/{}", APP_PREFIX, DATABASE_FILE), database)?;
+    }
+    if let Some(secrets) = &sources.secrets {
+        add(format!("{}/{}", APP_PREFIX, SECRETS_FILE), secrets)?;
+    }
+
+    for item in CLAUDE_ITEMS {
+        let root = sources.claude_dir.join(item);
+        if !root.exists() {
+            continue;
+        }
+        for entry in walkdir::WalkDir::new(&root).sort_by_file_name() {
+            let entry = entry.map_err(|e| format!("Failed to read {}: {}", root.display(), e))?;
+            if !entry.file_type().is_file() || entry.path().extension().map_or(false, |ext| ext == "tmp") {
+                continue;
+            }
+            let Ok(relative) = entry.path().strip_prefix(&sources.claude_dir) else {
+                continue;
+            };
+            let parts: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            add(format!("{}/{}", CLAUDE_PREFIX, parts.join("/")), entry.path())?;
+        }
+    }
+    Ok(files)
+}
+
+fn append_json<W: Write>(builder: &mut tar::Builder<W>, name: &str, value: &impl Serialize) -> Result<(), String> {
+    let data = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    builder
+        .append_data(&mut header, name, data.as_slice())
+        .map_err(|e| format!("Failed to write {}: {}", name, e))
+}
+
+/// Write an archive of `sources` to `dest`, encrypted when a passphrase is given.
+/// The file is written next to `dest` and only moved into place once complete.
+pub fn write_backup(
+    dest: &Path,
+    sources: &BackupSources,
+    passphrase: Option<&str>,
+    report: &mut dyn FnMut(&BackupProgress),
+) -> Result<BackupSummary, String> {
+    let mut progress = ProgressReporter::new(BackupOperation::Backup, report);
+    let files = collect_files(sources)?;
+    let info = BackupInfo {
+        format_version: BACKUP_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        api_version: format!("{}.{}", API_VERSION_MAJOR, API_VERSION_MINOR),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        encrypted: passphrase.is_some(),
+        includes_secrets: sources.secrets.is_some(),
+        files: files.len(),
+        bytes: files.iter().map(|file| file.size).sum(),
+    };
+    progress.update(|p| {
+        p.files_total = info.files;
+        p.bytes_total = info.bytes;
+    });
+
+    if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    }
+    let partial = dest.with_extension("partial");
+    let result = (|| -> Result<(), String> {
+        let mut file = BufWriter::new(File::create(&partial).map_err(|e| format!("Failed to create backup: {}", e))?);
+        file.write_all(BACKUP_MAGIC).map_err(|e| e.to_string())?;
+        file.write_all(&BACKUP_FORMAT_VERSION.to_be_bytes()).map_err(|e| e.to_string())?;
+        let sink = match passphrase {
+            Some(passphrase) => {
+                let mut salt = [0u8; SALT_LEN];
+                let mut nonce_prefix = [0u8; NONCE_PREFIX_LEN];
+                OsRng.fill_bytes(&mut salt);
+                OsRng.fill_bytes(&mut nonce_prefix);
+                file.write_all(&[FLAG_ENCRYPTED]).map_err(|e| e.to_string())?;
+                file.write_all(&salt).map_err(|e| e.to_string())?;
+                file.write_all(&nonce_prefix).map_err(|e| e.to_string())?;
+                Sink::Encrypted(Box::new(EncryptingWriter::new(file, derive_key(passphrase, &salt)?, &nonce_prefix)))
+            }
+            None => {
+                file.write_all(&[0]).map_err(|e| e.to_string())?;
+                Sink::Plain(file)
+            }
+        };
+
+        let encoder = zstd::Encoder::new(sink, 3).map_err(|e| e.to_string())?;
+        let mut builder = tar::Builder::new(encoder);
+        append_json(&mut builder, INFO_ENTRY, &info)?;
+
+        let mut manifest = BackupManifest::default();
+        for backup_file in &files {
+            progress.update(|p| p.current = Some(backup_file.archive_path.clone()));
+            let source = File::open(&backup_file.source)
+                .map_err(|e| format!("Failed to read {}: {}", backup_file.source.display(), e))?;
+            let mut header = tar::Header::new_gnu();
+            header.set_size(backup_file.size);
+            header.set_mode(0o644);
+            if let Ok(modified) = source.metadata().and_then(|m| m.modified()) {
+                let secs = modified.duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs());
+                header.set_mtime(secs.unwrap_or(0));
+            }
+            // Sessions may be appended to while the backup runs: store exactly the size in
+            // the header, padding with zeros if the file shrank, and hash what was stored
+            let mut reader = HashingReader {
+                inner: source.take(backup_file.size).chain(io::repeat(0)).take(backup_file.size),
+                hasher: Sha256::new(),
+                bytes: 0,
+            };
+            builder
+                .append_data(&mut header, &backup_file.archive_path, &mut reader)
+                .map_err(|e| format!("Failed to archive {}: {}", backup_file.archive_path, e))?;
+            manifest.files.insert(
+                backup_file.archive_path.clone(),
+                ManifestEntry {
+                    size: reader.bytes,
+                    sha256: to_hex(&reader.hasher.finalize()),
+                },
+            );
+            progress.update(|p| {
+                p.files_done += 1;
+                p.bytes_done += backup_file.size;
+            });
+        }
+
+        append_json(&mut builder, MANIFEST_ENTRY, &manifest)?;
+        let encoder = builder.into_inner().map_err(|e| e.to_string())?;
+        let sink = encoder.finish().map_err(|e| e.to_string())?;
+        sink.finish().map_err(|e| format!("Failed to write backup: {}", e))
+    })();
+
+    if let Err(e) = result {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::rename(&partial, dest).map_err(|e| format!("Failed to write backup: {}", e))?;
+    progress.finish();
+
+    Ok(BackupSummary {
+        path: dest.to_string_lossy().to_string(),
+        files: info.files,
+        bytes: info.bytes,
+        archive_bytes: fs::metadata(dest).map(|m| m.len()).unwrap_or(0),
+        encrypted: info.encrypted,
+        includes_secrets: info.includes_secrets,
+        created_at: info.created_at,
+    })
+}
+
+/// Refuse backups this version can't read or that come from a newer backend
+fn check_compatible(info: &BackupInfo) -> Result<(), String> {
+    if info.format_version > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Backup format {} was created by a newer app version ({}); update the app to restore it",
+            info.format_version, info.app_version
+        ));
+    }
+    let (major, minor) = info
+        .api_version
+        .split_once('.')
+        .and_then(|(major, minor)| Some((major.parse::<u32>().ok()?, minor.parse::<u32>().ok()?)))
+        .ok_or_else(|| format!("Backup has an invalid API version '{}'", info.api_version))?;
+    if major != API_VERSION_MAJOR || minor > API_VERSION_MINOR {
+        return Err(format!(
+            "Backup was created by app version {} (API {}), which this version (API {}.{}) can't restore",
+            info.app_version, info.api_version, API_VERSION_MAJOR, API_VERSION_MINOR
+        ));
+    }
+    Ok(())
+}
+
+/// Archive paths must stay inside `claude/` or `app/`
+fn safe_archive_path(path: &Path) -> Option<PathBuf> {
+    let mut components = path.components();
+    match components.next()? {
+        Component::Normal(first) if first == CLAUDE_PREFIX || first == APP_PREFIX => {}
+        _ => return None,
+    }
+    if components.clone().next().is_none() || !components.all(|c| matches!(c, Component::Normal(_))) {
+        return None;
+    }
+    Some(path.to_path_buf())
+}
+
+/// A verified backup extracted to a staging directory
+#[derive(Debug)]
+pub struct StagedBackup {
+    pub info: BackupInfo,
+    pub manifest: BackupManifest,
+    /// Holds `claude/` and `app/` as in the archive
+    pub dir: PathBuf,
+}
+
+/// Decrypt, extract and verify a backup into `staging`. Nothing outside `staging`
+/// is touched, so a wrong passphrase, a corrupted file or an incompatible version
+/// fails before any state is overwritten.
+pub fn stage_backup(
+    path: &Path,
+    passphrase: Option<&str>,
+    staging: &Path,
+    report: &mut dyn FnMut(&BackupProgress),
+) -> Result<StagedBackup, String> {
+    let mut progress = ProgressReporter::new(BackupOperation::Restore, report);
+    let mut file = BufReader::new(File::open(path).map_err(|e| format!("Failed to open backup: {}", e))?);
+
+    let mut magic = [0u8; 8];
+    let mut version = [0u8; 4];
+    let mut flags = [0u8; 1];
+    file.read_exact(&mut magic)
+        .and_then(|_| file.read_exact(&mut version))
+        .and_then(|_| file.read_exact(&mut flags))
+        .map_err(|_| "Not a workbench backup file".to_string())?;
+    if &magic != BACKUP_MAGIC {
+        return Err("Not a workbench backup file".to_string());
+    }
+    let container_version = u32::from_be_bytes(version);
+    if container_version > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Backup format {} was created by a newer app version; update the app to restore it",
+            container_version
+        ));
+    }
+
+    let source: Box<dyn Read> = if flags[0] & FLAG_ENCRYPTED != 0 {
+        let passphrase = passphrase.ok_or("This backup is encrypted; enter its passphrase to restore it")?;
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce_prefix = [0u8; NONCE_PREFIX_LEN];
+        file.read_exact(&mut salt)
+            .and_then(|_| file.read_exact(&mut nonce_prefix))
+            .map_err(|_| "Backup file is truncated".to_string())?;
+        Box::new(DecryptingReader::new(file, derive_key(passphrase, &salt)?, &nonce_prefix))
+    } else {
+        Box::new(file)
+    };
+    let decoder = zstd::Decoder::new(source).map_err(|e| e.to_string())?;
+    let mut archive = tar::Archive::new(decoder);
+
+    let mut info: Option<BackupInfo> = None;
+    let mut manifest: Option<BackupManifest> = None;
+    let mut extracted: BTreeMap<String, ManifestEntry> = BTreeMap::new();
+
+    for entry in archive.entries().map_err(|e| format!("Failed to read backup: {}", e))? {
+        let mut entry = entry.map_err(|e| format!("Failed to read backup: {}", e))?;
+        let entry_path = entry.path().map_err(|e| e.to_string())?.to_path_buf();
+        let name = entry_path.to_string_lossy().replace('\\', "/");
+
+        if info.is_none() {
+            if name != INFO_ENTRY {
+                return Err("Backup file is corrupted: it does not start with backup.json".to_string());
+            }
+            let parsed: BackupInfo = serde_json::from_reader(&mut entry).map_err(|e| format!("Invalid backup.json: {}", e))?;
+            check_compatible(&parsed)?;
+            progress.update(|p| {
+                p.files_total = parsed.files;
+                p.bytes_total = parsed.bytes;
+            });
+            info = Some(parsed);
+            continue;
+        }
+        if name == MANIFEST_ENTRY {
+            manifest = Some(serde_json::from_reader(&mut entry).map_err(|e| format!("Invalid manifest.json: {}", e))?);
+            continue;
+        }
+
+        let relative = safe_archive_path(&entry_path).ok_or_else(|| format!("Backup contains an invalid path: {}", name))?;
+        progress.update(|p| p.current = Some(name.clone()));
+        let target = staging.join(&relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to stage backup: {}", e))?;
+        }
+        let mut reader = HashingReader {
+            inner: &mut entry,
+            hasher: Sha256::new(),
+            bytes: 0,
+        };
+        let mut output = File::create(&target).map_err(|e| format!("Failed to stage backup: {}", e))?;
+        io::copy(&mut reader, &mut output).map_err(|e| format!("Failed to extract {}: {}", name, e))?;
+        let size = reader.bytes;
+        extracted.insert(
+            name,
+            ManifestEntry {
+                size,
+                sha256: to_hex(&reader.hasher.finalize()),
+            },
+        );
+        progress.update(|p| {
+            p.files_done += 1;
+            p.bytes_done += size;
+        });
+    }
+
+    let info = info.ok_or("Backup file is empty")?;
+    let manifest = manifest.ok_or("Backup file is truncated: the manifest is missing")?;
+    if let Some(bad) = manifest
+        .files
+        .iter()
+        .find(|(path, expected)| extracted.get(*path) != Some(expected))
+        .map(|(path, _)| path)
+        .or_else(|| extracted.keys().find(|path| !manifest.files.contains_key(*path)))
+    {
+        return Err(format!("Backup failed verification: {} does not match the manifest", bad));
+    }
+    progress.finish();
+
+    Ok(StagedBackup {
+        info,
+        manifest,
+        dir: staging.to_path_buf(),
+    })
+}
+
+fn copy_staged(staged: &Path, target: &Path) -> Result<(), String> {
+    let target = crate::paths::long_path(target);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::copy(staged, &target)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to restore {}: {}", target.display(), e))
+}
+
+fn emit_progress(app: &AppHandle, progress: &BackupProgress) {
+    if let Err(e) = AppEvent::BackupProgress(progress.clone()).emit(app) {
+        log::debug!("Failed to emit backup progress: {}", e);
+    }
+}
+
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))
+}
+
+/// Back up agents.db, Claude settings, agents and commands, and all sessions with
+/// their checkpoints to `dest`.
+///
+/// With a passphrase the backup is encrypted and also holds the stored secrets.
+/// Progress arrives as `backup-progress` events.
+#[tauri::command]
+pub async fn create_full_backup(app: AppHandle, dest: String, passphrase: Option<String>) -> Result<BackupSummary, String> {
+    let dest = PathBuf::from(dest.trim());
+    if dest.as_os_str().is_empty() {
+        return Err("Backup destination is empty".to_string());
+    }
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    log::info!("Creating full backup at {} (encrypted: {})", dest.display(), passphrase.is_some());
+
+    // Usage rows still buffered belong in the database snapshot
+    if let Err(e) = super::usage_writer::flush_usage_queue(&app).await {
+        log::warn!("Failed to flush usage rows before backup: {}", e);
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let staging = tempfile::tempdir().map_err(|e| format!("Failed to create staging directory: {}", e))?;
+        let snapshot = staging.path().join(DATABASE_FILE);
+        {
+            let db = app.state::<AgentDb>();
+            let conn = db.0.get()?;
+            conn.backup(DatabaseName::Main, &snapshot, None)
+                .map_err(|e| format!("Failed to snapshot the database: {}", e))?;
+        }
+
+        let secrets = app_data_dir(&app)?.join(SECRETS_FILE);
+        let sources = BackupSources {
+            claude_dir: get_claude_dir().map_err(|e| e.to_string())?,
+            database: Some(snapshot),
+            secrets: (passphrase.is_some() && secrets.exists()).then_some(secrets),
+        };
+        let summary = write_backup(&dest, &sources, passphrase.as_deref(), &mut |progress| {
+            emit_progress(&app, progress)
+        })?;
+        log::info!(
+            "Full backup written to {}: {} files, {} bytes",
+            summary.path,
+            summary.files,
+            summary.archive_bytes
+        );
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Restore a backup made with `create_full_backup`.
+///
+/// The backup is decrypted, checked against its manifest and its versions before
+/// anything is overwritten. Files from the backup replace their current versions;
+/// files created since are kept. The database is replaced as a whole and migrated
+/// to the current schema.
+#[tauri::command]
+pub async fn restore_full_backup(app: AppHandle, path: String, passphrase: Option<String>) -> Result<RestoreSummary, String> {
+    let path = PathBuf::from(path.trim());
+    if !path.is_file() {
+        return Err(format!("Backup not found: {}", path.display()));
+    }
+    log::info!("Restoring full backup from {}", path.display());
+
+    // Rows still buffered would otherwise be written on top of the restored database
+    if let Err(e) = super::usage_writer::flush_usage_queue(&app).await {
+        log::warn!("Failed to flush usage rows before restore: {}", e);
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let staging = tempfile::tempdir().map_err(|e| format!("Failed to create staging directory: {}", e))?;
+        let staged = stage_backup(&path, passphrase.as_deref().filter(|p| !p.is_empty()), staging.path(), &mut |progress| {
+            emit_progress(&app, progress)
+        })?;
+
+        let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+        let app_dir = app_data_dir(&app)?;
+        let mut files_restored = 0;
+        let mut database_restored = false;
+        let mut secrets_restored = false;
+
+        for archive_path in staged.manifest.files.keys() {
+            let source = staged.dir.join(archive_path);
+            if let Some(relative) = archive_path.strip_prefix(&format!("{}/", CLAUDE_PREFIX)) {
+                copy_staged(&source, &claude_dir.join(relative))?;
+                files_restored += 1;
+            } else if archive_path == &format!("{}/{}", APP_PREFIX, SECRETS_FILE) {
+                copy_staged(&source, &app_dir.join(SECRETS_FILE))?;
+                secrets_restored = true;
+                files_restored += 1;
+            }
+        }
+
+        let database = staged.dir.join(APP_PREFIX).join(DATABASE_FILE);
+        if database.is_file() {
+            let db = app.state::<AgentDb>();
+            {
+                let mut conn = db.0.get()?;
+                conn.restore(DatabaseName::Main, &database, None::<fn(rusqlite::backup::Progress)>)
+                    .map_err(|e| format!("Failed to restore the database: {}", e))?;
+            }
+            // Bring a database from an older version up to the current schema
+            let conn = init_database(&app).map_err(|e| format!("Failed to migrate the restored database: {}", e))?;
+            db.0.reset(conn)?;
+            database_restored = true;
+            files_restored += 1;
+        }
+
+        log::info!(
+            "Restored backup from {} (app {}, created {}): {} files",
+            path.display(),
+            staged.info.app_version,
+            staged.info.created_at,
+            files_restored
+        );
+        Ok(RestoreSummary {
+            app_version: staged.info.app_version,
+            created_at: staged.info.created_at,
+            files_restored,
+            database_restored,
+            secrets_restored,
+            restart_recommended: database_restored || secrets_restored,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
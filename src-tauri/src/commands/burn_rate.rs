@@ -0,0 +1,162 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use super::events::AppEvent;
+use super::usage::estimate_message_cost;
+use crate::process::ProcessRegistryState;
+
+/// Usage older than this no longer counts towards the rate
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Shortest span a rate is computed over, so the first message doesn't read as a spike
+const MIN_RATE_SPAN: Duration = Duration::from_secs(10);
+
+/// How often `burn-rate:<session_id>` is emitted for running sessions
+const TICK_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Payload of `burn-rate:<session_id>`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BurnRate {
+    pub session_id: String,
+    /// Model of the most recent message
+    pub model: String,
+    /// Input and output tokens per minute over the last minute
+    pub tokens_per_minute: f64,
+    /// Estimated USD per minute over the last minute
+    pub cost_per_minute_usd: f64,
+    /// Input and output tokens since the session started streaming
+    #[ts(type = "number")]
+    pub total_tokens: u64,
+    pub total_cost_usd: f64,
+}
+
+struct SessionBurn {
+    model: String,
+    started: Instant,
+    /// `(when, tokens, cost)` of messages inside `RATE_WINDOW`
+    recent: VecDeque<(Instant, u64, f64)>,
+    total_tokens: u64,
+    total_cost_usd: f64,
+    /// Message ids already counted; the stream repeats a message once per content block
+    counted: HashSet<String>,
+}
+
+impl SessionBurn {
+    fn new(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            started: Instant::now(),
+            recent: VecDeque::new(),
+            total_tokens: 0,
+            total_cost_usd: 0.0,
+            counted: HashSet::new(),
+        }
+    }
+
+    fn rate(&mut self, session_id: &str, now: Instant) -> BurnRate {
+        while self
+            .recent
+            .front()
+            .map_or(false, |(at, _, _)| now.duration_since(*at) > RATE_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        let span = now.duration_since(self.started).clamp(MIN_RATE_SPAN, RATE_WINDOW);
+        let minutes = span.as_secs_f64() / 60.0;
+        let (tokens, cost) = self
+            .recent
+            .iter()
+            .fold((0u64, 0.0), |(tokens, cost), (_, t, c)| (tokens + t, cost + c));
+        BurnRate {
+            session_id: session_id.to_string(),
+            model: self.model.clone(),
+            tokens_per_minute: tokens as f64 / minutes,
+            cost_per_minute_usd: cost / minutes,
+            total_tokens: self.total_tokens,
+            total_cost_usd: self.total_cost_usd,
+        }
+    }
+}
+
+static SESSIONS: Lazy<Mutex<HashMap<String, SessionBurn>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Count an assistant stream message towards its session's burn rate
+pub fn record_message(session_id: &str, msg: &serde_json::Value, default_model: &str) {
+    let message = &msg["message"];
+    let Some(usage) = message.get("usage") else {
+        return;
+    };
+    let model = message["model"].as_str().unwrap_or(default_model);
+    let Ok(mut sessions) = SESSIONS.lock() else {
+        return;
+    };
+    let burn = sessions
+        .entry(session_id.to_string())
+        .or_insert_with(|| SessionBurn::new(model));
+    if let Some(id) = message["id"].as_str() {
+        if !burn.counted.insert(id.to_string()) {
+            return;
+        }
+    }
+
+    let tokens = usage["input_tokens"].as_u64().unwrap_or(0) + usage["output_tokens"].as_u64().unwrap_or(0);
+    let cost = estimate_message_cost(model, usage);
+    burn.model = model.to_string();
+    burn.recent.push_back((Instant::now(), tokens, cost));
+    burn.total_tokens += tokens;
+    burn.total_cost_usd += cost;
+}
+
+/// Current rates of sessions that are still running; forgets the ones that stopped
+fn current_rates(app: &AppHandle) -> Vec<BurnRate> {
+    let registry = app.state::<ProcessRegistryState>();
+    let running: HashSet<String> = registry
+        .0
+        .get_running_claude_sessions()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|info| match info.process_type {
+            crate::process::ProcessType::ClaudeSession { session_id } => Some(session_id),
+            _ => None,
+        })
+        .collect();
+
+    let Ok(mut sessions) = SESSIONS.lock() else {
+        return Vec::new();
+    };
+    sessions.retain(|session_id, _| running.contains(session_id));
+    let now = Instant::now();
+    let mut rates: Vec<BurnRate> = sessions
+        .iter_mut()
+        .map(|(session_id, burn)| burn.rate(session_id, now))
+        .collect();
+    rates.sort_by(|a, b| b.cost_per_minute_usd.total_cmp(&a.cost_per_minute_usd));
+    rates
+}
+
+/// Emit `burn-rate:<session_id>` for every running session with usage, every few seconds
+pub fn start_burn_rate_ticker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        loop {
+            interval.tick().await;
+            for rate in current_rates(&app) {
+                if let Err(e) = AppEvent::BurnRate(rate).emit(&app) {
+                    log::debug!("Failed to emit burn-rate event: {}", e);
+                }
+            }
+        }
+    });
+}
+
+/// Token and cost burn rates of running sessions, fastest-spending first
+#[tauri::command]
+pub async fn get_active_burn_rates(app: AppHandle) -> Result<Vec<BurnRate>, String> {
+    Ok(current_rates(&app))
+}
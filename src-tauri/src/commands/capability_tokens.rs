@@ -0,0 +1,360 @@
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::State;
+
+use super::agents::AgentDb;
+
+/// Prefix of token secrets, so leaked tokens are easy to recognize
+const TOKEN_PREFIX: &str = "cwb_";
+
+/// Audit entries returned when no limit is given
+const DEFAULT_AUDIT_LIMIT: i64 = 100;
+
+/// What a capability token allows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CapabilityScope {
+    /// Usage statistics and other read-only analytics
+    #[serde(rename = "analytics:read")]
+    AnalyticsRead,
+    /// Project and session listings
+    #[serde(rename = "projects:read")]
+    ProjectsRead,
+    /// Starting Claude sessions
+    #[serde(rename = "sessions:execute")]
+    SessionsExecute,
+    /// Scraping the Prometheus metrics endpoint
+    #[serde(rename = "metrics:read")]
+    MetricsRead,
+}
+
+/// Commands that may be invoked from outside the app, with the scope each needs
+const REMOTE_COMMANDS: &[(&str, CapabilityScope)] = &[
+    ("get_usage_stats", CapabilityScope::AnalyticsRead),
+    ("list_projects", CapabilityScope::ProjectsRead),
+    ("execute_claude_code", CapabilityScope::SessionsExecute),
+    ("apply_execution_profile", CapabilityScope::SessionsExecute),
+    ("get_metrics", CapabilityScope::MetricsRead),
+];
+
+/// Scope a remotely invoked command requires; None if it can't be invoked remotely
+pub fn required_scope(command: &str) -> Option<CapabilityScope> {
+    REMOTE_COMMANDS
+        .iter()
+        .find(|(name, _)| *name == command)
+        .map(|(_, scope)| *scope)
+}
+
+/// A capability token; the secret itself is only returned when the token is created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityToken {
+    pub id: i64,
+    pub name: String,
+    pub scopes: Vec<CapabilityScope>,
+    pub created_at: String,
+    /// RFC 3339; None for tokens that don't expire
+    pub expires_at: Option<String>,
+    pub revoked_at: Option<String>,
+    pub last_used_at: Option<String>,
+}
+
+/// Returned by `create_capability_token`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedCapabilityToken {
+    pub token: CapabilityToken,
+    /// Shown once; only its hash is stored
+    pub secret: String,
+}
+
+/// One remote invocation, allowed or not
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteInvocationAudit {
+    pub id: i64,
+    pub token_id: Option<i64>,
+    pub token_name: Option<String>,
+    /// Where the invocation came from, e.g. `http`
+    pub source: String,
+    pub command: String,
+    pub allowed: bool,
+    /// Why the invocation was refused
+    pub reason: Option<String>,
+    pub created_at: String,
+}
+
+/// Why a remote invocation was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Denial {
+    MissingToken,
+    UnknownToken,
+    Revoked,
+    Expired,
+    MissingScope(CapabilityScope),
+    NotRemote,
+}
+
+impl Denial {
+    /// The caller did not prove who it is, as opposed to lacking permission
+    pub fn is_unauthenticated(&self) -> bool {
+        matches!(self, Denial::MissingToken | Denial::UnknownToken)
+    }
+
+    pub fn reason(&self) -> String {
+        match self {
+            Denial::MissingToken => "No capability token given".to_string(),
+            Denial::UnknownToken => "Unknown capability token".to_string(),
+            Denial::Revoked => "Capability token was revoked".to_string(),
+            Denial::Expired => "Capability token has expired".to_string(),
+            Denial::MissingScope(scope) => format!(
+                "Capability token lacks the {} scope",
+                serde_json::to_value(scope)
+                    .ok()
+                    .and_then(|v| v.as_str().map(str::to_string))
+                    .unwrap_or_default()
+            ),
+            Denial::NotRemote => "Command can't be invoked remotely".to_string(),
+        }
+    }
+}
+
+pub fn init_capability_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS capability_tokens (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            token_hash TEXT NOT NULL UNIQUE,
+            scopes TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            expires_at TEXT,
+            revoked_at TEXT,
+            last_used_at TEXT
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS remote_invocation_audit (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            token_id INTEGER,
+            token_name TEXT,
+            source TEXT NOT NULL,
+            command TEXT NOT NULL,
+            allowed BOOLEAN NOT NULL,
+            reason TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn hash_secret(secret: &str) -> String {
+    Sha256::digest(secret.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+const TOKEN_COLUMNS: &str = "id, name, scopes, created_at, expires_at, revoked_at, last_used_at";
+
+fn row_to_token(row: &rusqlite::Row) -> rusqlite::Result<CapabilityToken> {
+    let scopes: String = row.get(2)?;
+    Ok(CapabilityToken {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        scopes: serde_json::from_str(&scopes).unwrap_or_default(),
+        created_at: row.get(3)?,
+        expires_at: row.get(4)?,
+        revoked_at: row.get(5)?,
+        last_used_at: row.get(6)?,
+    })
+}
+
+fn is_expired(token: &CapabilityToken, now: DateTime<Utc>) -> bool {
+    token
+        .expires_at
+        .as_deref()
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .map_or(false, |at| at <= now)
+}
+
+fn record_invocation(
+    conn: &Connection,
+    token: Option<&CapabilityToken>,
+    source: &str,
+    command: &str,
+    denial: Option<&Denial>,
+) {
+    let result = conn.execute(
+        "INSERT INTO remote_invocation_audit (token_id, token_name, source, command, allowed, reason)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            token.map(|t| t.id),
+            token.map(|t| t.name.as_str()),
+            source,
+            command,
+            denial.is_none(),
+            denial.map(Denial::reason)
+        ],
+    );
+    if let Err(e) = result {
+        log::error!("Failed to audit remote invocation of {}: {}", command, e);
+    }
+}
+
+/// Check a remote invocation of `command` against the presented token and record
+/// it in the audit trail, whether it is allowed or not
+pub fn authorize(conn: &Connection, secret: Option<&str>, command: &str, source: &str) -> Result<CapabilityToken, Denial> {
+    let token = match secret.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(secret) => conn
+            .query_row(
+                &format!("SELECT {} FROM capability_tokens WHERE token_hash = ?1", TOKEN_COLUMNS),
+                params![hash_secret(secret)],
+                row_to_token,
+            )
+            .optional()
+            .unwrap_or_else(|e| {
+                log::error!("Failed to look up capability token: {}", e);
+                None
+            }),
+        None => None,
+    };
+
+    let denial = match (&token, required_scope(command)) {
+        (None, _) if secret.map_or(true, |s| s.trim().is_empty()) => Some(Denial::MissingToken),
+        (None, _) => Some(Denial::UnknownToken),
+        (Some(_), None) => Some(Denial::NotRemote),
+        (Some(token), Some(scope)) => {
+            if token.revoked_at.is_some() {
+                Some(Denial::Revoked)
+            } else if is_expired(token, Utc::now()) {
+                Some(Denial::Expired)
+            } else if !token.scopes.contains(&scope) {
+                Some(Denial::MissingScope(scope))
+            } else {
+                None
+            }
+        }
+    };
+
+    record_invocation(conn, token.as_ref(), source, command, denial.as_ref());
+    if let Some(denial) = denial {
+        log::warn!("Refused remote {} invocation from {}: {}", command, source, denial.reason());
+        return Err(denial);
+    }
+
+    let token = token.expect("allowed invocations have a token");
+    if let Err(e) = conn.execute(
+        "UPDATE capability_tokens SET last_used_at = ?1 WHERE id = ?2",
+        params![Utc::now().to_rfc3339(), token.id],
+    ) {
+        log::debug!("Failed to update capability token last use: {}", e);
+    }
+    Ok(token)
+}
+
+/// Create a token for the local API. The secret is returned once and can't be recovered.
+#[tauri::command]
+pub async fn create_capability_token(
+    db: State<'_, AgentDb>,
+    name: String,
+    scopes: Vec<CapabilityScope>,
+    expires_in_days: Option<u32>,
+) -> Result<CreatedCapabilityToken, String> {
+    if name.trim().is_empty() {
+        return Err("Token name is empty".to_string());
+    }
+    if scopes.is_empty() {
+        return Err("A token needs at least one scope".to_string());
+    }
+    let mut scopes = scopes;
+    scopes.dedup();
+
+    let secret = format!(
+        "{}{}{}",
+        TOKEN_PREFIX,
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    let expires_at = expires_in_days.map(|days| (Utc::now() + Duration::days(days as i64)).to_rfc3339());
+    let scopes_json = serde_json::to_string(&scopes).map_err(|e| e.to_string())?;
+
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO capability_tokens (name, token_hash, scopes, expires_at) VALUES (?1, ?2, ?3, ?4)",
+        params![name.trim(), hash_secret(&secret), scopes_json, expires_at],
+    )
+    .map_err(|e| e.to_string())?;
+    let token = conn
+        .query_row(
+            &format!("SELECT {} FROM capability_tokens WHERE id = ?1", TOKEN_COLUMNS),
+            params![conn.last_insert_rowid()],
+            row_to_token,
+        )
+        .map_err(|e| e.to_string())?;
+    log::info!("Created capability token '{}' with scopes {:?}", token.name, token.scopes);
+    Ok(CreatedCapabilityToken { token, secret })
+}
+
+/// List capability tokens, newest first
+#[tauri::command]
+pub async fn list_capability_tokens(db: State<'_, AgentDb>) -> Result<Vec<CapabilityToken>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM capability_tokens ORDER BY id DESC", TOKEN_COLUMNS))
+        .map_err(|e| e.to_string())?;
+    let tokens = stmt
+        .query_map([], row_to_token)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(tokens)
+}
+
+/// Revoke a token; it stays listed so its audit entries keep their context
+#[tauri::command]
+pub async fn revoke_capability_token(db: State<'_, AgentDb>, token_id: i64) -> Result<(), String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let updated = conn
+        .execute(
+            "UPDATE capability_tokens SET revoked_at = ?1 WHERE id = ?2 AND revoked_at IS NULL",
+            params![Utc::now().to_rfc3339(), token_id],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Capability token {} not found or already revoked", token_id));
+    }
+    log::info!("Revoked capability token {}", token_id);
+    Ok(())
+}
+
+/// Recent remote invocations, newest first
+#[tauri::command]
+pub async fn list_remote_invocation_audit(
+    db: State<'_, AgentDb>,
+    limit: Option<i64>,
+) -> Result<Vec<RemoteInvocationAudit>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, token_id, token_name, source, command, allowed, reason, created_at
+             FROM remote_invocation_audit ORDER BY id DESC LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let entries = stmt
+        .query_map([limit.unwrap_or(DEFAULT_AUDIT_LIMIT)], |row| {
+            Ok(RemoteInvocationAudit {
+                id: row.get(0)?,
+                token_id: row.get(1)?,
+                token_name: row.get(2)?,
+                source: row.get(3)?,
+                command: row.get(4)?,
+                allowed: row.get(5)?,
+                reason: row.get(6)?,
+                created_at: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(entries)
+}
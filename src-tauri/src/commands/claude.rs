@@ -4,14 +4,23 @@ use super::permission_config::{
     ClaudePermissionConfig, ClaudeExecutionConfig, PermissionMode,
     build_execution_args, DEVELOPMENT_TOOLS, SAFE_TOOLS, ALL_TOOLS
 };
-use super::agents::{AgentDb, insert_usage_entry};
+use super::events::{AppEvent, EventKind, SessionStateEvent, SessionStatus};
+use super::accessibility::AnnouncedRun;
+use super::model_aliases::resolve_model_alias;
+use super::provider_env::{bind_provider, resolve_execution_env, set_session_env_vars, ProviderEnv};
+use super::session_kind::{agent_session_ids, kind_of, SessionKind};
+use super::execution_profiles::{notify_run_finished, record_session_profile, ExecutionProfile, RunBudget};
+use super::project_matching::{read_merge_exclusions, MergeExclusions};
+use super::run_dedupe::{RunHandle, RunRequest};
+use super::settings_store;
 use std::fs;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
 use std::time::SystemTime;
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
 use tokio::process::{Child, Command};
 use tokio::sync::Mutex;
 use tauri_plugin_shell::ShellExt;
@@ -34,22 +43,9 @@ impl Default for ClaudeProcessState {
     }
 }
 
-/// Maps frontend model IDs to Claude CLI model aliases
-/// Converts frontend-friendly model names to official Claude Code model identifiers
-/// Updated to use Claude 4.1 Opus (released August 2025) as the latest Opus model
-fn map_model_to_claude_alias(model: &str) -> String {
-    match model {
-        "sonnet1m" => "sonnet[1m]".to_string(),
-        "sonnet" => "sonnet".to_string(),
-        // Use 'opus' alias which automatically resolves to latest Opus (Claude 4.1)
-        "opus" => "opus".to_string(),
-        // Pass through any other model names unchanged (for future compatibility)
-        _ => model.to_string(),
-    }
-}
-
 /// Represents a project in the ~/.claude/projects directory
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct Project {
     /// The project ID (derived from the directory name)
     pub id: String,
@@ -58,7 +54,11 @@ pub struct Project {
     /// List of session IDs (JSONL file names without extension)
     pub sessions: Vec<String>,
     /// Unix timestamp of the latest activity (session modification or project creation)
+    #[ts(type = "number")]
     pub created_at: u64,
+    /// IDs of other directories for the same path whose sessions were merged into this project
+    #[serde(default)]
+    pub merged_from: Vec<String>,
 }
 
 /// Represents a session with its metadata
@@ -78,6 +78,17 @@ pub struct Session {
     pub first_message: Option<String>,
     /// Timestamp of the first user message (if available)
     pub message_timestamp: Option<String>,
+    /// Resumability from the transcript ("resumable" / "view_only"), None if its
+    /// metadata could not be read
+    pub resume_status: Option<String>,
+    /// Short title derived from the first message (if available)
+    pub title: Option<String>,
+    /// Number of message entries in the session
+    pub message_count: Option<u64>,
+    /// Timestamp of the latest entry (if available)
+    pub last_activity: Option<String>,
+    /// Whether the session is the transcript of an agent run
+    pub kind: SessionKind,
 }
 
 /// Represents a message entry in the JSONL file
@@ -175,7 +186,7 @@ pub fn get_claude_dir() -> Result<PathBuf> {
 /// Gets the actual project path by reading the cwd from the first JSONL entry
 fn get_project_path_from_sessions(project_dir: &PathBuf) -> Result<String, String> {
     // Try to read any JSONL file in the directory
-    let entries = fs::read_dir(project_dir)
+    let entries = fs::read_dir(crate::paths::long_path(project_dir))
         .map_err(|e| format!("Failed to read project directory: {}", e))?;
 
     for entry in entries {
@@ -190,18 +201,8 @@ fn get_project_path_from_sessions(project_dir: &PathBuf) -> Result<String, Strin
                         if let Ok(json) = serde_json::from_str::<serde_json::Value>(&first_line) {
                             if let Some(cwd) = json.get("cwd").and_then(|v| v.as_str()) {
                                 // Apply consistent path normalization to ensure project paths are unified
-                                let normalized_cwd = std::path::Path::new(cwd)
-                                    .canonicalize()
-                                    .map(|p| {
-                                        let path_str = p.to_string_lossy().to_string();
-                                        // Remove Windows long path prefix for cleaner display
-                                        if path_str.starts_with("\\\\?\\") {
-                                            path_str[4..].to_string()
-                                        } else {
-                                            path_str
-                                        }
-                                    })
-                                    .unwrap_or_else(|_| cwd.to_string());
+                                let normalized_cwd = crate::paths::canonical_display(std::path::Path::new(cwd))
+                                    .unwrap_or_else(|| cwd.to_string());
                                 return Ok(normalized_cwd);
                             }
                         }
@@ -216,7 +217,7 @@ fn get_project_path_from_sessions(project_dir: &PathBuf) -> Result<String, Strin
 
 /// Encodes a project path to match Claude CLI's encoding scheme
 /// Uses single hyphens to separate path components
-fn encode_project_path(path: &str) -> String {
+pub fn encode_project_path(path: &str) -> String {
     path.replace("\\", "-")
         .replace("/", "-")
         .replace(":", "")
@@ -234,12 +235,7 @@ fn decode_project_path(encoded: &str) -> String {
     // On Windows, ensure we use backslashes for consistency
     #[cfg(target_os = "windows")]
     {
-        let mut windows_path = decoded.replace('/', "\\");
-        // Remove Windows long path prefix if present
-        if windows_path.starts_with("\\\\?\\") {
-            windows_path = windows_path[4..].to_string();
-        }
-        windows_path
+        crate::paths::strip_verbatim(&decoded.replace('/', "\\"))
     }
     
     #[cfg(not(target_os = "windows"))]
@@ -249,46 +245,10 @@ fn decode_project_path(encoded: &str) -> String {
 }
 
 /// Normalize a path for comparison to detect duplicates
-/// This handles case sensitivity, path separators, and trailing slashes
-fn normalize_path_for_comparison(path: &str) -> String {
-    let mut normalized = path.to_lowercase();
-    
-    // Remove Windows long path prefix if present (\\?\ or \\?\UNC\)
-    if normalized.starts_with("\\\\?\\") {
-        normalized = normalized[4..].to_string();
-    } else if normalized.starts_with("\\\\?\\unc\\") {
-        normalized = format!("\\\\{}", &normalized[8..]);
-    }
-    
-    // Normalize path separators - convert all to forward slashes for comparison
-    normalized = normalized.replace('\\', "/");
-    
-    // Remove trailing slash if present
-    if normalized.ends_with('/') && normalized.len() > 1 {
-        normalized.pop();
-    }
-    
-    // Remove leading slash for relative path comparison
-    if normalized.starts_with('/') {
-        normalized = normalized[1..].to_string();
-    }
-    
-    // Handle Windows drive letters - convert C:/ to c
-    if normalized.len() >= 2 && normalized.chars().nth(1) == Some(':') {
-        if normalized.len() == 2 {
-            normalized = normalized.chars().take(1).collect();
-        } else if normalized.chars().nth(2) == Some('/') {
-            let drive = normalized.chars().take(1).collect::<String>();
-            let rest = &normalized[3..];
-            normalized = if rest.is_empty() { 
-                drive 
-            } else { 
-                format!("{}/{}", drive, rest)
-            };
-        }
-    }
-    
-    normalized
+/// This handles case sensitivity, path separators, trailing slashes, verbatim
+/// prefixes and UNC shares
+pub fn normalize_path_for_comparison(path: &str) -> String {
+    crate::paths::comparison_key(path)
 }
 
 /// Extracts the first valid user message from a JSONL file
@@ -460,9 +420,9 @@ fn create_system_command(
     claude_path: &str,
     args: Vec<String>,
     project_path: &str,
-    model: Option<&str>,
+    env: &ProviderEnv,
 ) -> Result<Command, String> {
-    create_windows_command(claude_path, args, project_path, model)
+    create_windows_command(claude_path, args, project_path, env)
 }
 
 /// Create a Windows command
@@ -470,15 +430,12 @@ fn create_windows_command(
     claude_path: &str,
     args: Vec<String>,
     project_path: &str,
-    model: Option<&str>,
+    env: &ProviderEnv,
 ) -> Result<Command, String> {
     let mut cmd = create_command_with_env(claude_path);
 
-    // 🔥 修复：设置ANTHROPIC_MODEL环境变量以确保模型选择生效
-    if let Some(model_name) = model {
-        log::info!("Setting ANTHROPIC_MODEL environment variable to: {}", model_name);
-        cmd.env("ANTHROPIC_MODEL", model_name);
-    }
+    // 🔥 修复：设置ANTHROPIC_MODEL等环境变量，仅作用于本次进程
+    env.apply(&mut cmd);
 
     // Add all arguments
     cmd.args(&args);
@@ -498,119 +455,120 @@ fn create_windows_command(
 }
 
 
-#[tauri::command]
-pub async fn list_projects() -> Result<Vec<Project>, String> {
-    log::info!("Listing projects from ~/.claude/projects");
-
-    let mut all_projects = Vec::new();
-
-    // Get Windows projects
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
-    let projects_dir = claude_dir.join("projects");
-    let hidden_projects_file = claude_dir.join("hidden_projects.json");
-
-    // Read hidden projects list
-    let hidden_projects: Vec<String> = if hidden_projects_file.exists() {
-        let content = fs::read_to_string(&hidden_projects_file)
-            .map_err(|e| format!("Failed to read hidden projects file: {}", e))?;
-        serde_json::from_str(&content).unwrap_or_else(|_| Vec::new())
-    } else {
-        Vec::new()
-    };
-
-    if projects_dir.exists() {
-        // Read all directories in the Windows projects folder
-        let entries = fs::read_dir(&projects_dir)
-            .map_err(|e| format!("Failed to read projects directory: {}", e))?;
-
-        for entry in entries {
-            let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-            let path = entry.path();
+/// Reads the list of project IDs hidden from the project list
+pub(crate) fn read_hidden_projects(conn: &rusqlite::Connection) -> Vec<String> {
+    settings_store::get_or_default(conn, settings_store::HIDDEN_PROJECTS)
+}
 
-            if path.is_dir() {
-                let dir_name = path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .ok_or_else(|| "Invalid directory name".to_string())?;
+/// Lists project directories under ~/.claude/projects, skipping hidden projects
+pub(crate) fn list_project_dirs(projects_dir: &PathBuf, hidden_projects: &[String]) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(projects_dir)
+        .map_err(|e| format!("Failed to read projects directory: {}", e))?;
 
-                // Skip hidden projects
-                if hidden_projects.contains(&dir_name.to_string()) {
-                    log::debug!("Skipping hidden project: {}", dir_name);
-                    continue;
-                }
+    let mut dirs = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+        if !crate::paths::long_path(&path).is_dir() {
+            continue;
+        }
+        let Some(dir_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
 
-                // Get directory creation time
-                let metadata = fs::metadata(&path)
-                    .map_err(|e| format!("Failed to read directory metadata: {}", e))?;
+        // Skip hidden projects
+        if hidden_projects.iter().any(|hidden| hidden == dir_name) {
+            log::debug!("Skipping hidden project: {}", dir_name);
+            continue;
+        }
+        dirs.push(path);
+    }
+    Ok(dirs)
+}
 
-                let created_at = metadata
-                    .created()
-                    .or_else(|_| metadata.modified())
-                    .unwrap_or(SystemTime::UNIX_EPOCH)
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs();
+/// Scans a single project directory for its path, sessions and latest activity
+pub(crate) fn scan_project_dir(path: &PathBuf) -> Result<Project, String> {
+    let dir_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| "Invalid directory name".to_string())?;
+
+    // Get directory creation time
+    let metadata = fs::metadata(crate::paths::long_path(path))
+        .map_err(|e| format!("Failed to read directory metadata: {}", e))?;
+
+    let created_at = metadata
+        .created()
+        .or_else(|_| metadata.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
 
-                // Get the actual project path from JSONL files
-                let project_path = match get_project_path_from_sessions(&path) {
-                    Ok(path) => path,
-                    Err(e) => {
-                        log::warn!("Failed to get project path from sessions for {}: {}, falling back to decode", dir_name, e);
-                        decode_project_path(dir_name)
-                    }
-                };
+    // Get the actual project path from JSONL files
+    let project_path = match get_project_path_from_sessions(path) {
+        Ok(path) => path,
+        Err(e) => {
+            log::warn!("Failed to get project path from sessions for {}: {}, falling back to decode", dir_name, e);
+            decode_project_path(dir_name)
+        }
+    };
 
-                // List all JSONL files (sessions) in this project directory and find latest activity
-                let mut sessions = Vec::new();
-                let mut latest_activity = created_at; // Default to project creation time
-                
-                if let Ok(session_entries) = fs::read_dir(&path) {
-                    for session_entry in session_entries.flatten() {
-                        let session_path = session_entry.path();
-                        if session_path.is_file()
-                            && session_path.extension().and_then(|s| s.to_str()) == Some("jsonl")
-                        {
-                            if let Some(session_id) = session_path.file_stem().and_then(|s| s.to_str())
-                            {
-                                sessions.push(session_id.to_string());
-                                
-                                // Check the modification time of this session file
-                                if let Ok(session_metadata) = fs::metadata(&session_path) {
-                                    let session_modified = session_metadata
-                                        .modified()
-                                        .unwrap_or(SystemTime::UNIX_EPOCH)
-                                        .duration_since(SystemTime::UNIX_EPOCH)
-                                        .unwrap_or_default()
-                                        .as_secs();
-                                    
-                                    // Update latest activity if this session is newer
-                                    if session_modified > latest_activity {
-                                        latest_activity = session_modified;
-                                    }
-                                }
-                            }
+    // List all JSONL files (sessions) in this project directory and find latest activity
+    let mut sessions = Vec::new();
+    let mut latest_activity = created_at; // Default to project creation time
+    
+    if let Ok(session_entries) = fs::read_dir(crate::paths::long_path(path)) {
+        for session_entry in session_entries.flatten() {
+            let session_path = session_entry.path();
+            if session_path.is_file()
+                && session_path.extension().and_then(|s| s.to_str()) == Some("jsonl")
+            {
+                if let Some(session_id) = session_path.file_stem().and_then(|s| s.to_str())
+                {
+                    sessions.push(session_id.to_string());
+                    
+                    // Check the modification time of this session file
+                    if let Ok(session_metadata) = fs::metadata(&session_path) {
+                        let session_modified = session_metadata
+                            .modified()
+                            .unwrap_or(SystemTime::UNIX_EPOCH)
+                            .duration_since(SystemTime::UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_secs();
+                        
+                        // Update latest activity if this session is newer
+                        if session_modified > latest_activity {
+                            latest_activity = session_modified;
                         }
                     }
                 }
-
-                all_projects.push(Project {
-                    id: dir_name.to_string(),
-                    path: project_path,
-                    sessions,
-                    created_at: latest_activity, // Use latest activity time instead of creation time
-                });
             }
         }
-    } else {
-        log::warn!("Windows projects directory does not exist: {:?}", projects_dir);
     }
 
+    Ok(Project {
+        id: dir_name.to_string(),
+        path: project_path,
+        sessions,
+        created_at: latest_activity, // Use latest activity time instead of creation time
+        merged_from: Vec::new(),
+    })
+}
 
+/// Merges projects that resolve to the same path and sorts by latest activity.
+/// Projects matching `exclusions` are always listed on their own.
+pub(crate) fn merge_duplicate_projects(all_projects: Vec<Project>, exclusions: &MergeExclusions) -> Vec<Project> {
     // Remove duplicate projects based on normalized paths and merge sessions
     let mut unique_projects_map: std::collections::HashMap<String, Project> = std::collections::HashMap::new();
-    let original_count = all_projects.len();
+    let mut excluded_projects = Vec::new();
     
     for project in all_projects {
+        if exclusions.excludes(&project) {
+            excluded_projects.push(project);
+            continue;
+        }
+
         // Normalize the path for comparison (convert to lowercase, normalize separators)
         let normalized_path = normalize_path_for_comparison(&project.path);
         
@@ -646,9 +604,13 @@ pub async fn list_projects() -> Result<Vec<Project>, String> {
                      project.id.chars().any(|c| c.is_uppercase()) && 
                      existing_project.id.chars().all(|c| !c.is_uppercase()));
                 
+                existing_project.merged_from.extend(project.merged_from);
                 if should_update_id {
                     log::debug!("Updating project ID from '{}' to '{}'", existing_project.id, project.id);
-                    existing_project.id = project.id;
+                    let previous_id = std::mem::replace(&mut existing_project.id, project.id);
+                    existing_project.merged_from.push(previous_id);
+                } else {
+                    existing_project.merged_from.push(project.id);
                 }
             }
             None => {
@@ -664,12 +626,62 @@ pub async fn list_projects() -> Result<Vec<Project>, String> {
             // Remove duplicate sessions within the project
             let mut unique_sessions = std::collections::HashSet::new();
             project.sessions.retain(|session| unique_sessions.insert(session.clone()));
+            project.merged_from.sort();
             project
         })
+        .chain(excluded_projects)
         .collect();
 
     // Sort projects by latest activity time (most recently active first)
     unique_projects.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    unique_projects
+}
+
+/// Lists projects; with `kind`, only projects and sessions of that kind
+#[tauri::command]
+pub async fn list_projects(
+    db: tauri::State<'_, super::agents::AgentDb>,
+    kind: Option<SessionKind>,
+) -> Result<Vec<Project>, String> {
+    use rayon::prelude::*;
+
+    log::info!("Listing projects from ~/.claude/projects");
+
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let projects_dir = claude_dir.join("projects");
+
+    // Read hidden projects list
+    let hidden_projects = read_hidden_projects(&*db.0.get()?);
+
+    let all_projects: Vec<Project> = if projects_dir.exists() {
+        let dirs = list_project_dirs(&projects_dir, &hidden_projects)?;
+
+        // Scan project directories in parallel; each one reads a JSONL head and stats its sessions
+        tokio::task::spawn_blocking(move || {
+            dirs.par_iter()
+                .map(scan_project_dir)
+                .collect::<Result<Vec<_>, String>>()
+        })
+        .await
+        .map_err(|e| e.to_string())??
+    } else {
+        log::warn!("Projects directory does not exist: {:?}", projects_dir);
+        Vec::new()
+    };
+
+    let original_count = all_projects.len();
+    let exclusions = read_merge_exclusions(&*db.0.get()?);
+    let mut unique_projects = merge_duplicate_projects(all_projects, &exclusions);
+
+    // Keep only sessions of the requested kind, dropping projects left without any
+    if let Some(kind) = kind {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        let agent_sessions = agent_session_ids(&conn)?;
+        for project in &mut unique_projects {
+            project.sessions.retain(|id| kind_of(&agent_sessions, id) == kind);
+        }
+        unique_projects.retain(|project| !project.sessions.is_empty());
+    }
 
     log::info!("Found {} unique projects (filtered {} hidden, {} duplicates)", 
         unique_projects.len(), 
@@ -679,16 +691,25 @@ pub async fn list_projects() -> Result<Vec<Project>, String> {
     Ok(unique_projects)
 }
 
-/// Gets sessions for a specific project
+/// Gets sessions for a specific project, optionally only interactive or agent sessions
 #[tauri::command]
-pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, String> {
+pub async fn get_project_sessions(
+    db: tauri::State<'_, super::agents::AgentDb>,
+    project_id: String,
+    kind: Option<SessionKind>,
+) -> Result<Vec<Session>, String> {
     log::info!("Getting sessions for project: {}", project_id);
 
+    let agent_sessions = {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        agent_session_ids(&conn)?
+    };
+
     let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
     let project_dir = claude_dir.join("projects").join(&project_id);
     let todos_dir = claude_dir.join("todos");
 
-    if !project_dir.exists() {
+    if !crate::paths::long_path(&project_dir).exists() {
         return Err(format!("Project directory not found: {}", project_id));
     }
 
@@ -708,7 +729,7 @@ pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, St
     let mut sessions = Vec::new();
 
     // Read all JSONL files in the project directory
-    let entries = fs::read_dir(&project_dir)
+    let entries = fs::read_dir(crate::paths::long_path(&project_dir))
         .map_err(|e| format!("Failed to read project directory: {}", e))?;
 
     for entry in entries {
@@ -717,6 +738,11 @@ pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, St
 
         if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
             if let Some(session_id) = path.file_stem().and_then(|s| s.to_str()) {
+                let session_kind = kind_of(&agent_sessions, session_id);
+                if kind.map_or(false, |kind| kind != session_kind) {
+                    continue;
+                }
+
                 // Get file creation time
                 let metadata = fs::metadata(&path)
                     .map_err(|e| format!("Failed to read file metadata: {}", e))?;
@@ -729,8 +755,24 @@ pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, St
                     .unwrap_or_default()
                     .as_secs();
 
-                // Extract first user message and timestamp
-                let (first_message, message_timestamp) = extract_first_user_message(&path);
+                // Read first message, counts and last activity from the sidecar,
+                // falling back to scanning the JSONL if it can't be used
+                let (first_message, message_timestamp, title, message_count, last_activity, resume_status) =
+                    match super::session_meta::load_session_meta(&path) {
+                        Ok(meta) => (
+                            meta.first_message.clone(),
+                            meta.message_timestamp.clone(),
+                            meta.title.clone(),
+                            Some(meta.message_count),
+                            meta.last_activity.clone(),
+                            Some(super::session_health::listed_resume_status(&path, &meta)),
+                        ),
+                        Err(e) => {
+                            log::debug!("Session sidecar unavailable for {}: {}", session_id, e);
+                            let (first_message, message_timestamp) = extract_first_user_message(&path);
+                            (first_message, message_timestamp, None, None, None, None)
+                        }
+                    };
 
                 // Try to load associated todo data
                 let todo_path = todos_dir.join(format!("{}.json", session_id));
@@ -750,6 +792,11 @@ pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, St
                     created_at,
                     first_message,
                     message_timestamp,
+                    resume_status,
+                    title,
+                    message_count,
+                    last_activity,
+                    kind: session_kind,
                 });
             }
         }
@@ -768,31 +815,17 @@ pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, St
 
 /// Removes a project from the project list (without deleting files)
 #[tauri::command]
-pub async fn delete_project(project_id: String) -> Result<String, String> {
+pub async fn delete_project(app: AppHandle, project_id: String) -> Result<String, String> {
     log::info!("Removing project from list: {}", project_id);
 
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
-    let hidden_projects_file = claude_dir.join("hidden_projects.json");
-    
-    // Read existing hidden projects or create empty list
-    let mut hidden_projects: Vec<String> = if hidden_projects_file.exists() {
-        let content = fs::read_to_string(&hidden_projects_file)
-            .map_err(|e| format!("Failed to read hidden projects file: {}", e))?;
-        serde_json::from_str(&content).unwrap_or_else(|_| Vec::new())
-    } else {
-        Vec::new()
-    };
-
     // Add project to hidden list if not already present
-    if !hidden_projects.contains(&project_id) {
-        hidden_projects.push(project_id.clone());
-        
-        // Save updated hidden projects list
-        let content = serde_json::to_string_pretty(&hidden_projects)
-            .map_err(|e| format!("Failed to serialize hidden projects: {}", e))?;
-        fs::write(&hidden_projects_file, content)
-            .map_err(|e| format!("Failed to write hidden projects file: {}", e))?;
-    }
+    settings_store::update(&app, settings_store::HIDDEN_PROJECTS, |hidden: &mut Vec<String>| {
+        if hidden.contains(&project_id) {
+            return Ok(false);
+        }
+        hidden.push(project_id.clone());
+        Ok(true)
+    })?;
 
     let result_msg = format!("Project '{}' has been removed from the list (files are preserved)", project_id);
     log::info!("{}", result_msg);
@@ -802,31 +835,20 @@ pub async fn delete_project(project_id: String) -> Result<String, String> {
 
 /// Restores a project to the project list
 #[tauri::command]
-pub async fn restore_project(project_id: String) -> Result<String, String> {
+pub async fn restore_project(app: AppHandle, project_id: String) -> Result<String, String> {
     log::info!("Restoring project to list: {}", project_id);
 
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
-    let hidden_projects_file = claude_dir.join("hidden_projects.json");
-    
-    // Read existing hidden projects
-    let mut hidden_projects: Vec<String> = if hidden_projects_file.exists() {
-        let content = fs::read_to_string(&hidden_projects_file)
-            .map_err(|e| format!("Failed to read hidden projects file: {}", e))?;
-        serde_json::from_str(&content).unwrap_or_else(|_| Vec::new())
-    } else {
-        return Err("No hidden projects found".to_string());
-    };
-
     // Remove project from hidden list
-    if let Some(pos) = hidden_projects.iter().position(|x| x == &project_id) {
-        hidden_projects.remove(pos);
-        
-        // Save updated hidden projects list
-        let content = serde_json::to_string_pretty(&hidden_projects)
-            .map_err(|e| format!("Failed to serialize hidden projects: {}", e))?;
-        fs::write(&hidden_projects_file, content)
-            .map_err(|e| format!("Failed to write hidden projects file: {}", e))?;
+    let mut restored = false;
+    settings_store::update(&app, settings_store::HIDDEN_PROJECTS, |hidden: &mut Vec<String>| {
+        if let Some(pos) = hidden.iter().position(|x| x == &project_id) {
+            hidden.remove(pos);
+            restored = true;
+        }
+        Ok(restored)
+    })?;
 
+    if restored {
         let result_msg = format!("Project '{}' has been restored to the list", project_id);
         log::info!("{}", result_msg);
         Ok(result_msg)
@@ -837,7 +859,7 @@ pub async fn restore_project(project_id: String) -> Result<String, String> {
 
 /// Permanently delete a project from the file system with intelligent directory detection
 #[tauri::command]
-pub async fn delete_project_permanently(project_id: String) -> Result<String, String> {
+pub async fn delete_project_permanently(app: AppHandle, project_id: String) -> Result<String, String> {
     log::info!("Permanently deleting project: {}", project_id);
 
     let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
@@ -848,7 +870,7 @@ pub async fn delete_project_permanently(project_id: String) -> Result<String, St
     let mut actual_project_id = project_id.clone();
     
     // Check if the project directory exists directly
-    if project_dir.exists() {
+    if crate::paths::long_path(&project_dir).exists() {
         actual_project_dir = Some(project_dir);
     } else {
         // Try to find the actual directory with intelligent matching
@@ -885,31 +907,19 @@ pub async fn delete_project_permanently(project_id: String) -> Result<String, St
     })?;
     
     // Remove the project directory and all its contents
-    fs::remove_dir_all(&dir_to_delete)
+    fs::remove_dir_all(crate::paths::long_path(&dir_to_delete))
         .map_err(|e| format!("Failed to delete project directory: {}", e))?;
     
     // Remove all variants from hidden projects list (both original and actual IDs)
-    let hidden_projects_file = claude_dir.join("hidden_projects.json");
-    if hidden_projects_file.exists() {
-        let mut hidden_projects: Vec<String> = {
-            let content = fs::read_to_string(&hidden_projects_file)
-                .map_err(|e| format!("Failed to read hidden projects file: {}", e))?;
-            serde_json::from_str(&content).unwrap_or_else(|_| Vec::new())
-        };
-        
-        // Remove both original and actual project IDs from hidden list
-        let original_len = hidden_projects.len();
-        hidden_projects.retain(|id| id != &project_id && id != &actual_project_id);
-        
-        if hidden_projects.len() != original_len {
-            // Save updated list
-            let content = serde_json::to_string_pretty(&hidden_projects)
-                .map_err(|e| format!("Failed to serialize hidden projects: {}", e))?;
-            fs::write(&hidden_projects_file, content)
-                .map_err(|e| format!("Failed to write hidden projects file: {}", e))?;
-            
-            log::info!("Removed project from hidden list: {} (and variants)", project_id);
-        }
+    let mut unhidden = false;
+    settings_store::update(&app, settings_store::HIDDEN_PROJECTS, |hidden: &mut Vec<String>| {
+        let original_len = hidden.len();
+        hidden.retain(|id| id != &project_id && id != &actual_project_id);
+        unhidden = hidden.len() != original_len;
+        Ok(unhidden)
+    })?;
+    if unhidden {
+        log::info!("Removed project from hidden list: {} (and variants)", project_id);
     }
     
     let result_msg = if actual_project_id != project_id {
@@ -925,20 +935,13 @@ pub async fn delete_project_permanently(project_id: String) -> Result<String, St
 
 /// Lists all hidden projects with intelligent directory existence check
 #[tauri::command]
-pub async fn list_hidden_projects() -> Result<Vec<String>, String> {
+pub async fn list_hidden_projects(app: AppHandle) -> Result<Vec<String>, String> {
     log::info!("Listing hidden projects with directory validation");
 
     let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
-    let hidden_projects_file = claude_dir.join("hidden_projects.json");
     let projects_dir = claude_dir.join("projects");
     
-    let mut hidden_projects: Vec<String> = if hidden_projects_file.exists() {
-        let content = fs::read_to_string(&hidden_projects_file)
-            .map_err(|e| format!("Failed to read hidden projects file: {}", e))?;
-        serde_json::from_str(&content).unwrap_or_else(|_| Vec::new())
-    } else {
-        Vec::new()
-    };
+    let hidden_projects = read_hidden_projects(&*app.state::<super::agents::AgentDb>().0.get()?);
 
     // Filter out hidden projects whose directories no longer exist
     // and find actual existing project directories for each hidden project
@@ -991,15 +994,13 @@ pub async fn list_hidden_projects() -> Result<Vec<String>, String> {
         }
     }
     
-    // Clean up hidden_projects.json if any projects were manually deleted
+    // Clean up the hidden list if any projects were manually deleted
     if !projects_to_remove.is_empty() {
-        hidden_projects.retain(|id| !projects_to_remove.contains(id));
-        
-        let content = serde_json::to_string_pretty(&hidden_projects)
-            .map_err(|e| format!("Failed to serialize hidden projects: {}", e))?;
-        fs::write(&hidden_projects_file, content)
-            .map_err(|e| format!("Failed to write updated hidden projects file: {}", e))?;
-            
+        settings_store::update(&app, settings_store::HIDDEN_PROJECTS, |hidden: &mut Vec<String>| {
+            hidden.retain(|id| !projects_to_remove.contains(id));
+            Ok(true)
+        })?;
+
         log::info!("Cleaned up {} non-existent hidden projects from list", projects_to_remove.len());
     }
 
@@ -1023,7 +1024,7 @@ pub async fn get_claude_settings() -> Result<ClaudeSettings, String> {
         });
     }
 
-    let content = fs::read_to_string(&settings_path)
+    let content = crate::config_file::read_to_string(&settings_path)
         .map_err(|e| format!("Failed to read settings file: {}", e))?;
 
     let data: serde_json::Value = serde_json::from_str(&content)
@@ -1257,7 +1258,8 @@ pub async fn save_system_prompt(content: String) -> Result<String, String> {
     let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
     let claude_md_path = claude_dir.join("CLAUDE.md");
 
-    fs::write(&claude_md_path, content).map_err(|e| format!("Failed to write CLAUDE.md: {}", e))?;
+    crate::config_file::write(&claude_md_path, content)
+        .map_err(|e| format!("Failed to write CLAUDE.md: {}", e))?;
 
     Ok("System prompt saved successfully".to_string())
 }
@@ -1277,52 +1279,32 @@ pub async fn save_claude_settings(settings: serde_json::Value) -> Result<String,
     let settings_path = claude_dir.join("settings.json");
     log::info!("Settings path: {:?}", settings_path);
 
-    // Read existing settings to preserve unknown fields
-    let mut existing_settings = if settings_path.exists() {
-        let content = fs::read_to_string(&settings_path).ok();
-        if let Some(content) = content {
-            serde_json::from_str::<serde_json::Value>(&content).ok()
-        } else {
-            None
-        }
-    } else {
-        None
-    }.unwrap_or(serde_json::json!({}));
-
-    log::info!("Existing settings: {}", existing_settings);
-
     // Use settings directly - no wrapper expected from frontend
     let actual_settings = &settings;
     log::info!("Using settings directly: {}", actual_settings);
 
-    // Merge the new settings with existing settings
+    // Merge the new settings into the existing ones under the file's lock
     // This preserves unknown fields that the app doesn't manage
-    if let (Some(existing_obj), Some(new_obj)) = (existing_settings.as_object_mut(), actual_settings.as_object()) {
-        for (key, value) in new_obj {
-            existing_obj.insert(key.clone(), value.clone());
+    let merged = crate::config_file::update_json(&settings_path, |existing_settings| {
+        log::info!("Existing settings: {}", existing_settings);
+        if let (Some(existing_obj), Some(new_obj)) =
+            (existing_settings.as_object_mut(), actual_settings.as_object())
+        {
+            for (key, value) in new_obj {
+                existing_obj.insert(key.clone(), value.clone());
+            }
+        } else {
+            // If either is not an object, just use the new settings
+            *existing_settings = actual_settings.clone();
         }
-        log::info!("Merged settings: {}", existing_settings);
-    } else {
-        // If either is not an object, just use the new settings
-        existing_settings = actual_settings.clone();
-    }
-
-    // Pretty print the JSON with 2-space indentation
-    let json_string = serde_json::to_string_pretty(&existing_settings)
-        .map_err(|e| {
-            let error_msg = format!("Failed to serialize settings: {}", e);
-            log::error!("{}", error_msg);
-            error_msg
-        })?;
-
-    log::info!("Serialized JSON length: {} characters", json_string.len());
-
-    fs::write(&settings_path, &json_string)
-        .map_err(|e| {
-            let error_msg = format!("Failed to write settings file: {}", e);
-            log::error!("{}", error_msg);
-            error_msg
-        })?;
+        Ok(())
+    })
+    .map_err(|e| {
+        let error_msg = format!("Failed to save settings: {}", e);
+        log::error!("{}", error_msg);
+        error_msg
+    })?;
+    log::info!("Merged settings: {}", merged);
 
     log::info!("Settings saved successfully to: {:?}", settings_path);
     Ok("Settings saved successfully".to_string())
@@ -1354,7 +1336,7 @@ fn find_claude_md_recursive(
     project_root: &PathBuf,
     claude_files: &mut Vec<ClaudeMdFile>,
 ) -> Result<(), String> {
-    let entries = fs::read_dir(current_path)
+    let entries = fs::read_dir(crate::paths::long_path(current_path))
         .map_err(|e| format!("Failed to read directory {:?}: {}", current_path, e))?;
 
     for entry in entries {
@@ -1445,11 +1427,12 @@ pub async fn save_claude_md_file(file_path: String, content: String) -> Result<S
     Ok("File saved successfully".to_string())
 }
 
-/// Loads the JSONL history for a specific session
+/// Loads the JSONL history for a specific session, trimmed by `filter` if given
 #[tauri::command]
 pub async fn load_session_history(
     session_id: String,
     project_id: String,
+    filter: Option<super::history_filter::HistoryFilter>,
 ) -> Result<Vec<serde_json::Value>, String> {
     log::info!(
         "Loading session history for session: {} in project: {}",
@@ -1490,35 +1473,10 @@ pub async fn load_session_history(
     // Add timestamps to historical messages that don't have them
     let messages_count = messages.len();
     for (i, message) in messages.iter_mut().enumerate() {
-        let message_type = message.get("type").and_then(|t| t.as_str()).unwrap_or("");
-
-        // Calculate timestamp for this message (5 second intervals, older messages get earlier timestamps)
-        let time_offset = (messages_count - i - 1) as u64 * 5; // 5 seconds between messages
-        let message_time = base_time - std::time::Duration::from_secs(time_offset);
-        let timestamp_iso = chrono::DateTime::<chrono::Utc>::from(message_time).to_rfc3339();
-
-        // Set appropriate timestamp fields based on message type, only if they don't exist
-        match message_type {
-            "user" => {
-                if !message.get("sentAt").is_some() {
-                    message["sentAt"] = serde_json::Value::String(timestamp_iso);
-                }
-            }
-            "assistant" | "system" | "result" => {
-                if !message.get("receivedAt").is_some() {
-                    message["receivedAt"] = serde_json::Value::String(timestamp_iso);
-                }
-            }
-            _ => {
-                // For unknown types, add receivedAt
-                if !message.get("receivedAt").is_some() {
-                    message["receivedAt"] = serde_json::Value::String(timestamp_iso);
-                }
-            }
-        }
+        super::session_history::add_display_timestamp(message, i, messages_count, base_time);
     }
 
-    Ok(messages)
+    Ok(super::history_filter::apply(messages, 0, &filter.unwrap_or_default()))
 }
 
 
@@ -1526,27 +1484,115 @@ pub async fn load_session_history(
 /// Execute Claude Code session with project context resume and streaming output
 /// Always tries to resume project context first for better continuity
 /// Enhanced for Windows with better error handling
+///
+/// A retry carrying the same `idempotency_key` within the dedupe window gets the
+/// first request's run instead of a second process, unless `force_new_run` is set.
+///
+/// With `use_worktree` the session runs in a new git worktree of the project (see
+/// `create_session_worktree`), to be merged back with `merge_session_worktree`.
+///
+/// `env_profile` names an environment profile whose variables are set on this
+/// session only (see `save_env_profile`); they are kept as its overrides for later
+/// turns (see `set_session_env`).
+///
+/// With `prompt_template` the prompt is the template with its variables filled in,
+/// followed by `prompt` when that is not empty.
 #[tauri::command]
 pub async fn execute_claude_code(
     app: AppHandle,
     project_path: String,
     prompt: String,
     model: String,
-) -> Result<(), String> {
+    provider_id: Option<String>,
+    idempotency_key: Option<String>,
+    force_new_run: Option<bool>,
+    use_worktree: Option<bool>,
+    max_cost_usd: Option<f64>,
+    env_profile: Option<String>,
+    prompt_template: Option<super::prompt_templates::PromptTemplateUse>,
+) -> Result<RunHandle, String> {
+    super::cost_cap::validate(max_cost_usd)?;
+    let prompt = match &prompt_template {
+        Some(template) => {
+            let db = app.state::<super::agents::AgentDb>();
+            let conn = db.0.get()?;
+            let expanded = super::prompt_templates::expand_template(&conn, template)?;
+            if prompt.trim().is_empty() {
+                expanded
+            } else {
+                format!("{}\n\n{}", expanded, prompt)
+            }
+        }
+        None => prompt,
+    };
+    let overrides = RunOverrides { max_cost_usd, env_profile, ..RunOverrides::default() };
+    let request = RunRequest {
+        operation: "execute",
+        project_path: project_path.clone(),
+        session_id: None,
+    };
+    let start = async {
+        if !use_worktree.unwrap_or(false) {
+            return execute_claude_code_with(app.clone(), project_path, prompt, model, provider_id, overrides)
+                .await;
+        }
+        let worktree = super::worktree::prepare_session_worktree(&app, &project_path)?;
+        let overrides = RunOverrides { working_dir: Some(worktree.session_path.clone()), ..overrides };
+        let result =
+            execute_claude_code_with(app.clone(), project_path, prompt, model, provider_id, overrides).await;
+        super::worktree::record_worktree_run(&app, &worktree, result.as_ref().ok().copied());
+        result
+    };
+    super::run_dedupe::run_once(idempotency_key, force_new_run.unwrap_or(false), request, start).await
+}
+
+/// What a new session changes relative to the saved execution config
+#[derive(Debug, Clone, Default)]
+pub struct RunOverrides {
+    /// Replaces the configured permissions
+    pub permissions: Option<ClaudePermissionConfig>,
+    /// Extra CLI arguments, appended after the regular ones
+    pub extra_args: Vec<String>,
+    /// The run is recorded against the profile, stopped when its budget runs out and
+    /// reported on completion
+    pub profile: Option<ExecutionProfile>,
+    /// Checkpoint and stop the run once its estimated cost exceeds this many dollars
+    pub max_cost_usd: Option<f64>,
+    /// Environment profile whose variables are set on top of the provider's
+    pub env_profile: Option<String>,
+    /// Run the CLI here instead of in the project, e.g. in a session worktree. Trust,
+    /// config and policies still come from the project.
+    pub working_dir: Option<String>,
+}
+
+/// Start a new session with overrides (used by session templates and execution
+/// profiles). Workspace trust and the dangerous-skip policy still apply on top.
+pub async fn execute_claude_code_with(
+    app: AppHandle,
+    project_path: String,
+    prompt: String,
+    model: String,
+    provider_id: Option<String>,
+    overrides: RunOverrides,
+) -> Result<i64, String> {
     log::info!(
         "Starting Claude Code session with project context resume in: {} with model: {}",
         project_path,
         model
     );
 
-    let claude_path = find_claude_binary(&app)?;
+    let claude_path = find_claude_binary(&app).map_err(|e| super::spawn_diagnostics::report(&app, e, None))?;
     
     // 获取当前执行配置
-    let execution_config = get_claude_execution_config(app.clone()).await
-        .unwrap_or_else(|e| {
-            log::warn!("Failed to load execution config, using default: {}", e);
-            ClaudeExecutionConfig::default()
-        });
+    let mut execution_config =
+        super::project_execution_config::resolve_execution_config(&app, &project_path).await;
+    if let Some(permissions) = overrides.permissions {
+        execution_config.permissions = permissions;
+    }
+
+    // Untrusted projects run with read-only permissions and without hooks
+    let trust_args = super::workspace_trust::enforce_workspace_trust(&app, &project_path, &mut execution_config);
+    super::safety::apply_dangerous_skip_policy(&app, "execute", &project_path, None, &mut execution_config);
     
     log::info!("Using execution config: permissions_mode={:?}, dangerous_skip={}", 
         execution_config.permissions.permission_mode,
@@ -1554,37 +1600,74 @@ pub async fn execute_claude_code(
     );
     
     // 使用新的参数构建函数（先映射模型名称）
-    let mapped_model = map_model_to_claude_alias(&model);
-    let args = build_execution_args(&execution_config, &prompt, &mapped_model, escape_prompt_for_cli);
+    let mapped_model = resolve_model_alias(&app, &model);
+    let mut args = build_execution_args(&execution_config, &prompt, &mapped_model, escape_prompt_for_cli);
+    args.extend(overrides.extra_args);
+    super::response_language::apply_response_language(&app, &project_path, None, &mut args);
+    args.extend(trust_args);
 
     // Create command
-    let cmd = create_system_command(&claude_path, args, &project_path, Some(&mapped_model))?;
-    spawn_claude_process(app, cmd, prompt, model, project_path).await
+    let mut env = resolve_execution_env(&app, None, provider_id.as_deref(), &mapped_model)?;
+    if let Some(name) = &overrides.env_profile {
+        env.extend(super::env_profiles::resolve_env_profile(&app, name)?);
+        log::info!("Applied environment profile {}: {:?}", name, env.redacted());
+    }
+    let working_dir = overrides.working_dir.unwrap_or(project_path);
+    let cmd = create_system_command(&claude_path, args, &working_dir, &env)?;
+    spawn_claude_process(app.clone(), cmd, prompt, model, working_dir, env, None, overrides.profile, overrides.max_cost_usd)
+        .await
+        .map_err(|e| super::spawn_diagnostics::report(&app, e, Some(&claude_path)))
 }
 
 /// Continue an existing Claude Code conversation with streaming output
 /// Enhanced for Windows with better error handling
+///
+/// Retries are deduplicated by `idempotency_key` like `execute_claude_code`.
 #[tauri::command]
 pub async fn continue_claude_code(
     app: AppHandle,
     project_path: String,
     prompt: String,
     model: String,
-) -> Result<(), String> {
+    provider_id: Option<String>,
+    idempotency_key: Option<String>,
+    force_new_run: Option<bool>,
+    max_cost_usd: Option<f64>,
+) -> Result<RunHandle, String> {
+    super::cost_cap::validate(max_cost_usd)?;
+    let request = RunRequest {
+        operation: "continue",
+        project_path: project_path.clone(),
+        session_id: None,
+    };
+    let start = continue_run(app.clone(), project_path, prompt, model, provider_id, max_cost_usd);
+    super::run_dedupe::run_once(idempotency_key, force_new_run.unwrap_or(false), request, start).await
+}
+
+/// Spawn `claude -c`; returns the run ID
+async fn continue_run(
+    app: AppHandle,
+    project_path: String,
+    prompt: String,
+    model: String,
+    provider_id: Option<String>,
+    max_cost_usd: Option<f64>,
+) -> Result<i64, String> {
     log::info!(
         "Continuing Claude Code conversation in: {} with model: {}",
         project_path,
         model
     );
 
-    let claude_path = find_claude_binary(&app)?;
+    let claude_path = find_claude_binary(&app).map_err(|e| super::spawn_diagnostics::report(&app, e, None))?;
     
     // 获取当前执行配置
-    let execution_config = get_claude_execution_config(app.clone()).await
-        .unwrap_or_else(|e| {
-            log::warn!("Failed to load execution config, using default: {}", e);
-            ClaudeExecutionConfig::default()
-        });
+    let mut execution_config =
+        super::project_execution_config::resolve_execution_config(&app, &project_path).await;
+
+    // Untrusted projects run with read-only permissions and without hooks
+    let trust_args = super::workspace_trust::enforce_workspace_trust(&app, &project_path, &mut execution_config);
+    super::safety::apply_dangerous_skip_policy(&app, "continue", &project_path, None, &mut execution_config);
     
     log::info!("Continuing with execution config: permissions_mode={:?}, dangerous_skip={}", 
         execution_config.permissions.permission_mode,
@@ -1592,19 +1675,26 @@ pub async fn continue_claude_code(
     );
     
     // 使用新的参数构建函数，添加 -c 标志用于继续对话（先映射模型名称）
-    let mapped_model = map_model_to_claude_alias(&model);
+    let mapped_model = resolve_model_alias(&app, &model);
     let mut args = build_execution_args(&execution_config, &prompt, &mapped_model, escape_prompt_for_cli);
 
     // 在开头插入 -c 标志
     args.insert(0, "-c".to_string());
+    super::response_language::apply_response_language(&app, &project_path, None, &mut args);
+    args.extend(trust_args);
 
     // Create command
-    let cmd = create_system_command(&claude_path, args, &project_path, Some(&mapped_model))?;
-    spawn_claude_process(app, cmd, prompt, model, project_path).await
+    let env = resolve_execution_env(&app, None, provider_id.as_deref(), &mapped_model)?;
+    let cmd = create_system_command(&claude_path, args, &project_path, &env)?;
+    spawn_claude_process(app.clone(), cmd, prompt, model, project_path, env, None, None, max_cost_usd)
+        .await
+        .map_err(|e| super::spawn_diagnostics::report(&app, e, Some(&claude_path)))
 }
 
 /// Resume an existing Claude Code session by ID with streaming output
 /// Enhanced for Windows with better error handling
+///
+/// Retries are deduplicated by `idempotency_key` like `execute_claude_code`.
 #[tauri::command]
 pub async fn resume_claude_code(
     app: AppHandle,
@@ -1612,7 +1702,31 @@ pub async fn resume_claude_code(
     session_id: String,
     prompt: String,
     model: String,
-) -> Result<(), String> {
+    provider_id: Option<String>,
+    idempotency_key: Option<String>,
+    force_new_run: Option<bool>,
+    max_cost_usd: Option<f64>,
+) -> Result<RunHandle, String> {
+    super::cost_cap::validate(max_cost_usd)?;
+    let request = RunRequest {
+        operation: "resume",
+        project_path: project_path.clone(),
+        session_id: Some(session_id.clone()),
+    };
+    let start = resume_run(app.clone(), project_path, session_id, prompt, model, provider_id, max_cost_usd);
+    super::run_dedupe::run_once(idempotency_key, force_new_run.unwrap_or(false), request, start).await
+}
+
+/// Spawn `claude --resume`, falling back to `claude -c`; returns the run ID
+async fn resume_run(
+    app: AppHandle,
+    project_path: String,
+    session_id: String,
+    prompt: String,
+    model: String,
+    provider_id: Option<String>,
+    max_cost_usd: Option<f64>,
+) -> Result<i64, String> {
     log::info!(
         "Resuming Claude Code session: {} in: {} with model: {}",
         session_id,
@@ -1629,14 +1743,15 @@ pub async fn resume_claude_code(
     log::info!("Expected session file directory: {}", session_dir);
     log::info!("Session ID to resume: {}", session_id);
 
-    let claude_path = find_claude_binary(&app)?;
+    let claude_path = find_claude_binary(&app).map_err(|e| super::spawn_diagnostics::report(&app, e, None))?;
     
     // 获取当前执行配置
-    let execution_config = get_claude_execution_config(app.clone()).await
-        .unwrap_or_else(|e| {
-            log::warn!("Failed to load execution config, using default: {}", e);
-            ClaudeExecutionConfig::default()
-        });
+    let mut execution_config =
+        super::project_execution_config::resolve_execution_config(&app, &project_path).await;
+
+    // Untrusted projects run with read-only permissions and without hooks
+    let trust_args = super::workspace_trust::enforce_workspace_trust(&app, &project_path, &mut execution_config);
+    super::safety::apply_dangerous_skip_policy(&app, "resume", &project_path, Some(&session_id), &mut execution_config);
     
     log::info!("Resuming with execution config: permissions_mode={:?}, dangerous_skip={}", 
         execution_config.permissions.permission_mode,
@@ -1644,25 +1759,38 @@ pub async fn resume_claude_code(
     );
     
     // 使用新的参数构建函数，添加 --resume 和 session_id（先映射模型名称）
-    let mapped_model = map_model_to_claude_alias(&model);
+    let mapped_model = resolve_model_alias(&app, &model);
     let mut args = build_execution_args(&execution_config, &prompt, &mapped_model, escape_prompt_for_cli);
     
+    // Messages excluded from context are left out of a filtered copy of the transcript,
+    // which is resumed instead; the original stays as it is
+    let resume_id = super::context_exclusion::materialize_filtered_transcript(&app, &session_id)?
+        .unwrap_or_else(|| session_id.clone());
+
     // 为resume模式重新组织参数：--resume session_id 应该在最前面
     args.insert(0, "--resume".to_string());
-    args.insert(1, session_id.clone());
+    args.insert(1, resume_id);
+    super::response_language::apply_response_language(&app, &project_path, Some(&session_id), &mut args);
+    args.extend(trust_args);
 
     log::info!("Resume command: claude {}", args.join(" "));
 
-    // Create command
-    let cmd = create_system_command(&claude_path, args, &project_path, Some(&mapped_model))?;
+    // Create command; without an explicit provider the session keeps the one it is bound to
+    let env = resolve_execution_env(&app, Some(&session_id), provider_id.as_deref(), &mapped_model)?;
+    let fallback_provider = env.provider_id.clone();
+    let cmd = create_system_command(&claude_path, args, &project_path, &env)?;
+
+    // Refuse to interleave writes with another run of the same session
+    super::session_lock::acquire(&session_id, std::process::id())?;
     
     // Try to spawn the process - if it fails, fall back to continue mode
-    match spawn_claude_process(app.clone(), cmd, prompt.clone(), model.clone(), project_path.clone()).await {
-        Ok(_) => Ok(()),
+    match spawn_claude_process(app.clone(), cmd, prompt.clone(), model.clone(), project_path.clone(), env, Some(session_id.clone()), None, max_cost_usd).await {
+        Ok(run_id) => Ok(run_id),
         Err(resume_error) => {
+            super::session_lock::release(&session_id);
             log::warn!("Resume failed: {}, trying continue mode as fallback", resume_error);
             // Fallback to continue mode
-            continue_claude_code(app, project_path, prompt, model).await
+            continue_run(app, project_path, prompt, model, fallback_provider, max_cost_usd).await
         }
     }
 }
@@ -1678,6 +1806,8 @@ pub async fn cancel_claude_execution(
         session_id
     );
 
+    super::run_status::record_cancel_request(session_id.as_deref());
+
     let mut killed = false;
     let mut attempted_methods = Vec::new();
 
@@ -1783,17 +1913,24 @@ pub async fn cancel_claude_execution(
         log::warn!("No active Claude process found to cancel");
     }
 
+    if let Some(session_id) = &session_id {
+        super::accessibility::run_cancelled(&app, &AnnouncedRun::Session(session_id.clone()));
+        super::power::run_finished(&app, &AnnouncedRun::Session(session_id.clone()));
+    }
+
     // Always emit cancellation events for UI consistency
-    if let Some(sid) = session_id {
-        let _ = app.emit(&format!("claude-cancelled:{}", sid), true);
+    let cancelled = AppEvent::ClaudeCancelled { session_id: session_id.clone() };
+    let complete = AppEvent::ClaudeComplete { session_id: session_id.clone(), success: false };
+    if session_id.is_some() {
+        let _ = cancelled.emit(&app);
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-        let _ = app.emit(&format!("claude-complete:{}", sid), false);
+        let _ = complete.emit(&app);
     }
     
     // Also emit generic events for backward compatibility
-    let _ = app.emit("claude-cancelled", true);
+    let _ = cancelled.emit_global(&app);
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-    let _ = app.emit("claude-complete", false);
+    let _ = complete.emit_global(&app);
     
     if killed {
         log::info!("Claude process cancellation completed successfully");
@@ -1804,6 +1941,61 @@ pub async fn cancel_claude_execution(
     Ok(())
 }
 
+/// Suspend or resume the process tree of a running session and report it on
+/// `claude-session-state`
+fn set_execution_paused(
+    app: &AppHandle,
+    session_id: String,
+    paused: bool,
+) -> Result<crate::process::ProcessInfo, String> {
+    let registry = app.state::<crate::process::ProcessRegistryState>();
+    let process = registry
+        .0
+        .get_claude_session_by_id(&session_id)?
+        .ok_or_else(|| format!("No running process for session {}", session_id))?;
+    let info = if paused {
+        registry.0.pause_process(process.run_id)?
+    } else {
+        registry.0.resume_process(process.run_id)?
+    };
+
+    let event = AppEvent::ClaudeSessionState(SessionStateEvent {
+        session_id,
+        status: if paused { SessionStatus::Paused } else { SessionStatus::Resumed },
+        project_path: Some(info.project_path.clone()),
+        model: Some(info.model.clone()),
+        pid: Some(info.pid),
+        run_id: Some(info.run_id),
+        success: None,
+        error: None,
+    });
+    if let Err(e) = event.emit(app) {
+        log::warn!("Failed to emit claude-session-state event: {}", e);
+    }
+    Ok(info)
+}
+
+/// Suspend a running Claude session (and the tools it started) without ending it.
+/// Output, tool calls and token use stop until `resume_execution` is called.
+#[tauri::command]
+pub async fn pause_execution(
+    app: AppHandle,
+    session_id: String,
+) -> Result<crate::process::ProcessInfo, String> {
+    log::info!("Pausing Claude Code execution for session: {}", session_id);
+    set_execution_paused(&app, session_id, true)
+}
+
+/// Continue a session suspended with `pause_execution`
+#[tauri::command]
+pub async fn resume_execution(
+    app: AppHandle,
+    session_id: String,
+) -> Result<crate::process::ProcessInfo, String> {
+    log::info!("Resuming Claude Code execution for session: {}", session_id);
+    set_execution_paused(&app, session_id, false)
+}
+
 /// Get all running Claude sessions
 #[tauri::command]
 pub async fn list_running_claude_sessions(
@@ -1826,8 +2018,11 @@ pub async fn get_claude_session_output(
     }
 }
 
-/// Helper function to spawn Claude process and handle streaming
-async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String, model: String, project_path: String) -> Result<(), String> {
+/// Spawn the CLI and stream its output; returns the run ID, assigned before the CLI
+/// reports its session.
+/// `locked_session` is a session the caller already locked, released when the process exits
+#[allow(clippy::too_many_arguments)]
+async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String, model: String, project_path: String, env: ProviderEnv, locked_session: Option<String>, profile: Option<ExecutionProfile>, max_cost_usd: Option<f64>) -> Result<i64, String> {
     use tokio::io::{AsyncBufReadExt, BufReader};
     use std::sync::Mutex;
 
@@ -1855,6 +2050,10 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
     let session_id_holder: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
     let run_id_holder: Arc<Mutex<Option<i64>>> = Arc::new(Mutex::new(None));
 
+    // The run ID is handed out now so callers can correlate events before the session is known
+    let run_id = app.state::<crate::process::ProcessRegistryState>().0.generate_id()?;
+    super::run_status::record_spawn(run_id, pid, &project_path, &model, locked_session.as_deref());
+
     // Store the child process in the global state (for backward compatibility)
     let claude_state = app.state::<ClaudeProcessState>();
     {
@@ -1879,6 +2078,15 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
     let project_path_clone = project_path.clone();
     let prompt_clone = prompt.clone();
     let model_clone = model.clone();
+    let provider_source = super::router_usage::provider_source_for(
+        &env.base_url().map(str::to_string).unwrap_or_else(super::usage::get_api_base_url),
+    );
+    // Provider and alias of the rotated key the run uses, if any
+    let rotated_key = env.provider_id.clone().zip(env.key_alias.clone());
+    let rotated_key_stderr = rotated_key.clone();
+    let batcher = super::output_batching::OutputBatcher::spawn(app.clone(), None);
+    let profile_name = profile.as_ref().map(|profile| profile.name.clone());
+    let mut budget = RunBudget::for_run(profile.as_ref(), max_cost_usd);
     let stdout_task = tokio::spawn(async move {
         let mut lines = stdout_reader.lines();
         while let Ok(Some(line)) = lines.next_line().await {
@@ -1892,6 +2100,40 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                         if session_id_guard.is_none() {
                             *session_id_guard = Some(claude_session_id.to_string());
                             log::info!("Extracted Claude session ID: {}", claude_session_id);
+                            batcher.set_config_key(claude_session_id.to_string());
+
+                            // Hold the session's lock for the lifetime of the process
+                            if let Err(e) = super::session_lock::acquire(claude_session_id, pid) {
+                                log::warn!("Running without a session lock: {}", e);
+                            }
+
+                            if let Some(profile_name) = &profile_name {
+                                let db = app_handle.state::<super::agents::AgentDb>();
+                                let recorded = db.0.get().map_err(|e| e.to_string())
+                                    .and_then(|conn| record_session_profile(&conn, claude_session_id, profile_name));
+                                if let Err(e) = recorded {
+                                    log::warn!("Failed to record execution profile for {}: {}", claude_session_id, e);
+                                }
+                            }
+
+                            // Later turns of this session resolve the same provider
+                            if let Some(provider_id) = &env.provider_id {
+                                let db = app_handle.state::<super::agents::AgentDb>();
+                                if let Err(e) = db.0.get().and_then(|conn| bind_provider(&conn, claude_session_id, provider_id)) {
+                                    log::warn!("Failed to bind session {} to provider {}: {}", claude_session_id, provider_id, e);
+                                }
+                            }
+
+                            // ... and get the same environment overrides
+                            if !env.overrides().is_empty() {
+                                let db = app_handle.state::<super::agents::AgentDb>();
+                                let stored = db.0.get().and_then(|conn| {
+                                    set_session_env_vars(&conn, claude_session_id, env.overrides())
+                                });
+                                if let Err(e) = stored {
+                                    log::warn!("Failed to store environment overrides of {}: {}", claude_session_id, e);
+                                }
+                            }
 
                             // Register with auto-compact manager
                             if auto_compact_available {
@@ -1907,32 +2149,50 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                             }
 
                             // Now register with ProcessRegistry using Claude's session ID
-                            match registry_clone.register_claude_session(
+                            super::run_status::record_session(run_id, claude_session_id);
+                            match registry_clone.register_claude_session_as(
+                                run_id,
                                 claude_session_id.to_string(),
                                 pid,
                                 project_path_clone.clone(),
                                 prompt_clone.clone(),
                                 model_clone.clone(),
                             ) {
-                                Ok(run_id) => {
+                                Ok(()) => {
                                     log::info!("Registered Claude session with run_id: {}", run_id);
                                     let mut run_id_guard = run_id_holder_clone.lock().unwrap();
                                     *run_id_guard = Some(run_id);
+                                    if let Err(e) = registry_clone.set_process_env(run_id, env.redacted()) {
+                                        log::warn!("Failed to record run environment: {}", e);
+                                    }
 
                                     // ✨ Phase 2: Emit event for real-time session tracking
-                                    let event_payload = serde_json::json!({
-                                        "session_id": claude_session_id,
-                                        "project_path": project_path_clone,
-                                        "model": model_clone,
-                                        "status": "started",
-                                        "pid": pid,
-                                        "run_id": run_id,
+                                    let event = AppEvent::ClaudeSessionState(SessionStateEvent {
+                                        session_id: claude_session_id.to_string(),
+                                        status: SessionStatus::Started,
+                                        project_path: Some(project_path_clone.clone()),
+                                        model: Some(model_clone.clone()),
+                                        pid: Some(pid),
+                                        run_id: Some(run_id),
+                                        success: None,
+                                        error: None,
                                     });
-                                    if let Err(e) = app_handle.emit("claude-session-state", &event_payload) {
+                                    if let Err(e) = event.emit(&app_handle) {
                                         log::warn!("Failed to emit claude-session-state event: {}", e);
                                     } else {
                                         log::info!("Emitted claude-session-started event for session: {}", claude_session_id);
                                     }
+                                    super::accessibility::run_started(
+                                        &app_handle,
+                                        AnnouncedRun::Session(claude_session_id.to_string()),
+                                        "Claude",
+                                        &project_path_clone,
+                                    );
+                                    super::power::run_started(
+                                        &app_handle,
+                                        AnnouncedRun::Session(claude_session_id.to_string()),
+                                        &project_path_clone,
+                                    );
 
                                     log::info!("Claude CLI will handle project creation for session: {}", claude_session_id);
                                 }
@@ -1944,10 +2204,53 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                     }
                 }
 
-                // Check for usage information and update context tracking
-                if let Some(usage) = msg.get("usage") {
-                    if let (Some(input_tokens), Some(output_tokens)) =
-                        (usage.get("input_tokens").and_then(|t| t.as_u64()),
+                // Snapshot what destructive Bash calls are about to touch
+                if msg["type"] == "assistant" {
+                    let session_id = session_id_holder_clone.lock().unwrap().clone();
+                    if let Some(session_id) = session_id {
+                        super::burn_rate::record_message(&session_id, &msg, &model_clone);
+                        protect_destructive_tool_uses(&app_handle, &session_id, &project_path_clone, &msg).await;
+                    }
+
+                    // Checkpoint and stop the run once it outspends its cost cap or profile budget
+                    let over_budget = budget.as_mut().map_or(false, |budget| budget.record(&msg, &model_clone));
+                    if over_budget {
+                        if let Some(budget) = budget.take() {
+                            let session_id = session_id_holder_clone.lock().unwrap().clone();
+                            tokio::spawn(super::cost_cap::stop_session_run(
+                                app_handle.clone(),
+                                run_id,
+                                pid,
+                                session_id,
+                                project_path_clone.clone(),
+                                budget,
+                            ));
+                        }
+                    }
+                }
+
+                // Describe tool calls and edited files on the accessibility channel
+                if let Some(session_id) = session_id_holder_clone.lock().unwrap().clone() {
+                    super::file_edits::observe_message(&app_handle, &session_id, &msg);
+                    super::accessibility::observe_message(&app_handle, &AnnouncedRun::Session(session_id), &msg);
+                }
+
+                // Put the rotated key aside when the provider rate limits the run
+                if let Some((provider_id, alias)) = &rotated_key {
+                    super::provider_keys::report_message(&app_handle, provider_id, alias, &msg);
+                }
+
+                // A turn finished: fold the new JSONL entries into the session's listing sidecar
+                if msg["type"] == "result" {
+                    if let Some(session_id) = session_id_holder_clone.lock().unwrap().clone() {
+                        super::session_meta::spawn_refresh(session_id);
+                    }
+                }
+
+                // Check for usage information and update context tracking
+                if let Some(usage) = msg.get("usage") {
+                    if let (Some(input_tokens), Some(output_tokens)) =
+                        (usage.get("input_tokens").and_then(|t| t.as_u64()),
                          usage.get("output_tokens").and_then(|t| t.as_u64())) {
 
                         let total_tokens = (input_tokens + output_tokens) as usize;
@@ -1962,27 +2265,25 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                         };
 
                         if let Some(session_id_str) = &session_id_for_update {
-                            // Store real-time usage data in database
-                            if let Some(agent_db) = app_handle.try_state::<AgentDb>() {
-                                let timestamp = chrono::Utc::now().to_rfc3339();
-                                let model = msg.get("model")
-                                    .and_then(|m| m.as_str())
-                                    .unwrap_or(&model_clone);
-
-                                if let Err(e) = insert_usage_entry(
-                                    &agent_db,
-                                    session_id_str,
-                                    &timestamp,
-                                    model,
-                                    input_tokens,
-                                    output_tokens,
-                                    cache_creation_tokens,
-                                    cache_read_tokens,
-                                    Some(&project_path_clone),
-                                ) {
-                                    log::warn!("Failed to store usage data in database: {}", e);
-                                }
-                            }
+                            // Queue real-time usage data for the batched database writer
+                            let model = msg.get("model")
+                                .and_then(|m| m.as_str())
+                                .unwrap_or(&model_clone);
+                            super::usage_writer::queue_usage_entry(super::usage_writer::UsageRow {
+                                session_id: session_id_str.clone(),
+                                timestamp: chrono::Utc::now().to_rfc3339(),
+                                model: model.to_string(),
+                                input_tokens,
+                                output_tokens,
+                                cache_creation_tokens: cache_creation_tokens.unwrap_or(0),
+                                cache_read_tokens: cache_read_tokens.unwrap_or(0),
+                                project_path: project_path_clone.clone(),
+                                provider_source: provider_source.to_string(),
+                                operator_id: super::team_usage::current_operator_id(),
+                                provider_key: rotated_key.as_ref().map(|(_, alias)| alias.clone()),
+                                row_id: None,
+                                attempts: 0,
+                            });
 
                             // Update auto-compact manager with token count
                             if auto_compact_available {
@@ -2018,25 +2319,37 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
             
             // Emit the line to the frontend with session isolation if we have session ID
             if let Some(ref session_id) = *session_id_holder_clone.lock().unwrap() {
-                let _ = app_handle.emit(&format!("claude-output:{}", session_id), &line);
+                batcher.emit(EventKind::ClaudeOutput.scoped(session_id), &line);
             }
             // Also emit to the generic event for backward compatibility and early messages
-            let _ = app_handle.emit("claude-output", &line);
+            batcher.emit(EventKind::ClaudeOutput.as_str(), &line);
         }
+        batcher.finish().await;
     });
 
+    // Stderr written before Claude reports a session, to explain a failed startup
+    let startup_stderr: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
     let app_handle_stderr = app.clone();
     let session_id_holder_clone2 = session_id_holder.clone();
+    let startup_stderr_clone = startup_stderr.clone();
     let stderr_task = tokio::spawn(async move {
         let mut lines = stderr_reader.lines();
         while let Ok(Some(line)) = lines.next_line().await {
             log::error!("Claude stderr: {}", line);
-            // Emit error lines to the frontend with session isolation if we have session ID
-            if let Some(ref session_id) = *session_id_holder_clone2.lock().unwrap() {
-                let _ = app_handle_stderr.emit(&format!("claude-error:{}", session_id), &line);
+            if let Some((provider_id, alias)) = &rotated_key_stderr {
+                super::provider_keys::report_output(&app_handle_stderr, provider_id, alias, &line);
             }
-            // Also emit to the generic event for backward compatibility
-            let _ = app_handle_stderr.emit("claude-error", &line);
+            // Emit error lines to the frontend with session isolation if we have session ID,
+            // and to the generic event for backward compatibility
+            let session_id = session_id_holder_clone2.lock().unwrap().clone();
+            if session_id.is_none() {
+                let mut startup_lines = startup_stderr_clone.lock().unwrap();
+                if startup_lines.len() < 50 {
+                    startup_lines.push(line.clone());
+                }
+            }
+            let _ = AppEvent::ClaudeError { session_id, line }.emit_with_global(&app_handle_stderr);
         }
     });
 
@@ -2046,34 +2359,69 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
     let session_id_holder_clone3 = session_id_holder.clone();
     let run_id_holder_clone2 = run_id_holder.clone();
     let registry_clone2 = registry.0.clone();
+    let project_path_wait = project_path.clone();
     tokio::spawn(async move {
         let _ = stdout_task.await;
         let _ = stderr_task.await;
 
         // Get the child from the state to wait on it
         let mut current_process = claude_state_wait.lock().await;
+        let mut succeeded = false;
         if let Some(mut child) = current_process.take() {
             match child.wait().await {
                 Ok(status) => {
                     log::info!("Claude process exited with status: {}", status);
+                    succeeded = status.success();
+                    if !succeeded && session_id_holder_clone3.lock().unwrap().is_none() {
+                        let stderr = startup_stderr.lock().unwrap().join("\n");
+                        super::spawn_diagnostics::report_early_exit(&app_handle_wait, &stderr);
+                    }
                     // Add a small delay to ensure all messages are processed
                     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
                     if let Some(ref session_id) = *session_id_holder_clone3.lock().unwrap() {
                         // ✨ Phase 2: Emit state change event
-                        let event_payload = serde_json::json!({
-                            "session_id": session_id,
-                            "status": "stopped",
-                            "success": status.success(),
-                        });
-                        let _ = app_handle_wait.emit("claude-session-state", &event_payload);
-                        
-                        let _ = app_handle_wait.emit(
-                            &format!("claude-complete:{}", session_id),
+                        let _ = AppEvent::ClaudeSessionState(SessionStateEvent {
+                            session_id: session_id.clone(),
+                            status: SessionStatus::Stopped,
+                            project_path: None,
+                            model: None,
+                            pid: None,
+                            run_id: None,
+                            success: Some(status.success()),
+                            error: None,
+                        })
+                        .emit(&app_handle_wait);
+                    }
+                    // Scoped completion plus the generic event for backward compatibility
+                    super::global_search::invalidate_search_index();
+                    let session_id = session_id_holder_clone3.lock().unwrap().clone();
+                    let _ = AppEvent::ClaudeComplete { session_id, success: status.success() }
+                        .emit_with_global(&app_handle_wait);
+
+                    let finished_session = session_id_holder_clone3.lock().unwrap().clone();
+                    if let Some(session_id) = finished_session {
+                        super::accessibility::run_finished(
+                            &app_handle_wait,
+                            &AnnouncedRun::Session(session_id.clone()),
+                            status.success(),
+                        );
+                        super::unseen_completions::session_finished(
+                            &app_handle_wait,
+                            &session_id,
+                            &project_path_wait,
+                            status.success(),
+                        );
+                        super::power::run_finished(
+                            &app_handle_wait,
+                            &AnnouncedRun::Session(session_id.clone()),
+                        );
+                        super::session_handoff::fire_session_end(
+                            &app_handle_wait,
+                            session_id,
+                            project_path_wait.clone(),
                             status.success(),
                         );
                     }
-                    // Also emit to the generic event for backward compatibility
-                    let _ = app_handle_wait.emit("claude-complete", status.success());
                 }
                 Err(e) => {
                     log::error!("Failed to wait for Claude process: {}", e);
@@ -2081,19 +2429,48 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
                     if let Some(ref session_id) = *session_id_holder_clone3.lock().unwrap() {
                         // ✨ Phase 2: Emit state change event for error case
-                        let event_payload = serde_json::json!({
-                            "session_id": session_id,
-                            "status": "stopped",
-                            "success": false,
-                            "error": e.to_string(),
-                        });
-                        let _ = app_handle_wait.emit("claude-session-state", &event_payload);
-                        
-                        let _ = app_handle_wait
-                            .emit(&format!("claude-complete:{}", session_id), false);
+                        let _ = AppEvent::ClaudeSessionState(SessionStateEvent {
+                            session_id: session_id.clone(),
+                            status: SessionStatus::Stopped,
+                            project_path: None,
+                            model: None,
+                            pid: None,
+                            run_id: None,
+                            success: Some(false),
+                            error: Some(e.to_string()),
+                        })
+                        .emit(&app_handle_wait);
+                    }
+                    // Scoped completion plus the generic event for backward compatibility
+                    super::global_search::invalidate_search_index();
+                    let session_id = session_id_holder_clone3.lock().unwrap().clone();
+                    let _ = AppEvent::ClaudeComplete { session_id, success: false }
+                        .emit_with_global(&app_handle_wait);
+
+                    let finished_session = session_id_holder_clone3.lock().unwrap().clone();
+                    if let Some(session_id) = finished_session {
+                        super::accessibility::run_finished(
+                            &app_handle_wait,
+                            &AnnouncedRun::Session(session_id.clone()),
+                            false,
+                        );
+                        super::unseen_completions::session_finished(
+                            &app_handle_wait,
+                            &session_id,
+                            &project_path_wait,
+                            false,
+                        );
+                        super::power::run_finished(
+                            &app_handle_wait,
+                            &AnnouncedRun::Session(session_id.clone()),
+                        );
+                        super::session_handoff::fire_session_end(
+                            &app_handle_wait,
+                            session_id,
+                            project_path_wait.clone(),
+                            false,
+                        );
                     }
-                    // Also emit to the generic event for backward compatibility
-                    let _ = app_handle_wait.emit("claude-complete", false);
                 }
             }
         }
@@ -2102,12 +2479,29 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
         if let Some(run_id) = *run_id_holder_clone2.lock().unwrap() {
             let _ = registry_clone2.unregister_process(run_id);
         }
+        super::run_status::record_exit(run_id, succeeded);
+
+        let started_session = session_id_holder_clone3.lock().unwrap().clone();
+        for session_id in started_session.iter().chain(locked_session.iter()) {
+            super::session_lock::release(session_id);
+        }
 
         // Clear the process from state
         *current_process = None;
+        drop(current_process);
+
+        if let Some(profile) = &profile {
+            notify_run_finished(profile, started_session.as_deref(), &project_path_wait, succeeded).await;
+        }
+
+        // Start the next follow-up prompt queued for this session (under the resumed or the reported id)
+        if succeeded {
+            let _ = locked_session.iter().chain(started_session.iter())
+                .any(|session_id| super::prompt_queue::dispatch_next(&app_handle_wait, session_id));
+        }
     });
 
-    Ok(())
+    Ok(run_id)
 }
 
 /// Lists files and directories in a given path
@@ -2121,7 +2515,7 @@ pub async fn list_directory_contents(directory_path: String) -> Result<Vec<FileE
         return Err("Directory path cannot be empty".to_string());
     }
 
-    let path = PathBuf::from(&directory_path);
+    let path = crate::paths::long_path(std::path::Path::new(&directory_path));
     log::debug!("Resolved path: {:?}", path);
 
     if !path.exists() {
@@ -2170,7 +2564,7 @@ pub async fn list_directory_contents(directory_path: String) -> Result<Vec<FileE
 
         entries.push(FileEntry {
             name,
-            path: entry_path.to_string_lossy().to_string(),
+            path: crate::paths::display(&entry_path),
             is_directory: metadata.is_dir(),
             size: metadata.len(),
             extension,
@@ -2204,7 +2598,7 @@ pub async fn search_files(base_path: String, query: String) -> Result<Vec<FileEn
         return Ok(Vec::new());
     }
 
-    let path = PathBuf::from(&base_path);
+    let path = crate::paths::long_path(std::path::Path::new(&base_path));
     log::debug!("Resolved search base path: {:?}", path);
 
     if !path.exists() {
@@ -2247,7 +2641,7 @@ fn search_files_recursive(
         return Ok(());
     }
 
-    let entries = fs::read_dir(current_path)
+    let entries = fs::read_dir(crate::paths::long_path(current_path))
         .map_err(|e| format!("Failed to read directory {:?}: {}", current_path, e))?;
 
     for entry in entries {
@@ -2277,7 +2671,7 @@ fn search_files_recursive(
 
                 results.push(FileEntry {
                     name: name.to_string(),
-                    path: entry_path.to_string_lossy().to_string(),
+                    path: crate::paths::display(&entry_path),
                     is_directory: metadata.is_dir(),
                     size: metadata.len(),
                     extension,
@@ -2304,27 +2698,51 @@ fn search_files_recursive(
     Ok(())
 }
 
-/// Creates a checkpoint for the current session state
-#[tauri::command]
-pub async fn create_checkpoint(
-    app: tauri::State<'_, crate::checkpoint::state::CheckpointState>,
-    session_id: String,
-    project_id: String,
-    project_path: String,
-    message_index: Option<usize>,
-    description: Option<String>,
-) -> Result<crate::checkpoint::CheckpointResult, String> {
-    log::info!(
-        "Creating checkpoint for session: {} in project: {}",
-        session_id,
-        project_id
-    );
+/// Fire OnCheckpointCreate / OnCheckpointRestore hooks with the checkpoint metadata
+fn fire_checkpoint_hooks(
+    app_handle: &AppHandle,
+    event: super::enhanced_hooks::HookEvent,
+    project_path: &str,
+    result: &crate::checkpoint::CheckpointResult,
+    extra: serde_json::Value,
+) {
+    let checkpoint = &result.checkpoint;
+    let mut data = serde_json::json!({
+        "checkpoint_id": checkpoint.id,
+        "description": checkpoint.description,
+        "files_count": result.files_processed,
+        "message_index": checkpoint.message_index,
+        "parent_checkpoint_id": checkpoint.parent_checkpoint_id,
+        "project_id": checkpoint.project_id,
+        "timestamp": checkpoint.timestamp.to_rfc3339(),
+    });
+    if let (Some(data), serde_json::Value::Object(extra)) = (data.as_object_mut(), extra) {
+        data.extend(extra);
+    }
 
+    let context = super::enhanced_hooks::HookContext {
+        event: event.as_str().to_string(),
+        session_id: checkpoint.session_id.clone(),
+        project_path: project_path.to_string(),
+        data,
+    };
+    super::enhanced_hooks::spawn_hook_event(app_handle, event, context);
+}
+
+/// Gets the session's checkpoint manager, loading messages from the session
+/// JSONL file when the manager is new
+async fn prepare_checkpoint_manager(
+    app: &crate::checkpoint::state::CheckpointState,
+    session_id: &str,
+    project_id: &str,
+    project_path: &str,
+    message_index: Option<usize>,
+) -> Result<Arc<crate::checkpoint::manager::CheckpointManager>, String> {
     let manager = app
         .get_or_create_manager(
-            session_id.clone(),
-            project_id.clone(),
-            PathBuf::from(&project_path),
+            session_id.to_string(),
+            project_id.to_string(),
+            PathBuf::from(project_path),
         )
         .await
         .map_err(|e| format!("Failed to get checkpoint manager: {}", e))?;
@@ -2338,7 +2756,7 @@ pub async fn create_checkpoint(
         let session_path = get_claude_dir()
             .map_err(|e| e.to_string())?
             .join("projects")
-            .join(&project_id)
+            .join(project_id)
             .join(format!("{}.jsonl", session_id));
 
         if session_path.exists() {
@@ -2367,16 +2785,114 @@ pub async fn create_checkpoint(
         log::info!("Using {} already-tracked messages", current_message_count);
     }
 
-    manager
-        .create_checkpoint(description, None)
+    Ok(manager)
+}
+
+/// Progress reporter emitting `checkpoint-progress:<session_id>`
+fn checkpoint_job(app_handle: &AppHandle, session_id: &str) -> crate::checkpoint::jobs::CheckpointJob {
+    let app_handle = app_handle.clone();
+    crate::checkpoint::jobs::CheckpointJob::new(session_id.to_string(), move |progress| {
+        if let Err(e) = AppEvent::CheckpointProgress(progress).emit(&app_handle) {
+            log::debug!("Failed to emit checkpoint progress: {}", e);
+        }
+    })
+}
+
+/// Creates a checkpoint, reporting progress and ending with a final progress event
+async fn run_checkpoint_job(
+    app_handle: &AppHandle,
+    manager: &crate::checkpoint::manager::CheckpointManager,
+    job: &crate::checkpoint::jobs::CheckpointJob,
+    project_path: &str,
+    description: Option<String>,
+) -> Result<crate::checkpoint::CheckpointResult, String> {
+    let result = manager
+        .create_checkpoint_with_job(description, None, Some(job))
         .await
-        .map_err(|e| format!("Failed to create checkpoint: {}", e))
+        .map_err(|e| format!("Failed to create checkpoint: {}", e));
+    job.finish(&result.as_ref().map(|r| r.checkpoint.id.clone()).map_err(Clone::clone));
+
+    if let Ok(result) = &result {
+        fire_checkpoint_hooks(
+            app_handle,
+            super::enhanced_hooks::HookEvent::OnCheckpointCreate,
+            project_path,
+            result,
+            serde_json::json!({}),
+        );
+    }
+    result
+}
+
+/// Creates a checkpoint for the current session state
+#[tauri::command]
+pub async fn create_checkpoint(
+    app: tauri::State<'_, crate::checkpoint::state::CheckpointState>,
+    app_handle: AppHandle,
+    session_id: String,
+    project_id: String,
+    project_path: String,
+    message_index: Option<usize>,
+    description: Option<String>,
+) -> Result<crate::checkpoint::CheckpointResult, String> {
+    log::info!(
+        "Creating checkpoint for session: {} in project: {}",
+        session_id,
+        project_id
+    );
+
+    let manager =
+        prepare_checkpoint_manager(&app, &session_id, &project_id, &project_path, message_index).await?;
+    let job = checkpoint_job(&app_handle, &session_id);
+    run_checkpoint_job(&app_handle, &manager, &job, &project_path, description).await
+}
+
+/// Starts creating a checkpoint in the background and returns its job id.
+///
+/// Progress and the outcome are reported through `checkpoint-progress:<session_id>`;
+/// the job can be stopped with `cancel_checkpoint_job`.
+#[tauri::command]
+pub async fn create_checkpoint_async(
+    app: tauri::State<'_, crate::checkpoint::state::CheckpointState>,
+    app_handle: AppHandle,
+    session_id: String,
+    project_id: String,
+    project_path: String,
+    message_index: Option<usize>,
+    description: Option<String>,
+) -> Result<String, String> {
+    log::info!(
+        "Starting background checkpoint for session: {} in project: {}",
+        session_id,
+        project_id
+    );
+
+    let manager =
+        prepare_checkpoint_manager(&app, &session_id, &project_id, &project_path, message_index).await?;
+    let job = checkpoint_job(&app_handle, &session_id);
+    let job_id = job.id.clone();
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = run_checkpoint_job(&app_handle, &manager, &job, &project_path, description).await {
+            log::warn!("Background checkpoint {} ended: {}", job.id, e);
+        }
+    });
+
+    Ok(job_id)
+}
+
+/// Cancels a checkpoint being created; returns false if the job already finished
+#[tauri::command]
+pub async fn cancel_checkpoint_job(job_id: String) -> Result<bool, String> {
+    log::info!("Cancelling checkpoint job: {}", job_id);
+    Ok(crate::checkpoint::jobs::cancel(&job_id))
 }
 
 /// Restores a session to a specific checkpoint
 #[tauri::command]
 pub async fn restore_checkpoint(
     app: tauri::State<'_, crate::checkpoint::state::CheckpointState>,
+    app_handle: AppHandle,
     checkpoint_id: String,
     session_id: String,
     project_id: String,
@@ -2436,9 +2952,58 @@ pub async fn restore_checkpoint(
             .map_err(|e| format!("Failed to update session file: {}", e))?;
     }
 
+    fire_checkpoint_hooks(
+        &app_handle,
+        super::enhanced_hooks::HookEvent::OnCheckpointRestore,
+        &project_path,
+        &result,
+        serde_json::json!({ "restore_mode": restore_mode.as_deref().unwrap_or("both") }),
+    );
+
     Ok(result)
 }
 
+/// Lists the conflicts left by the last restore of a session: files whose uncheckpointed
+/// changes the restore did not overwrite
+#[tauri::command]
+pub async fn list_restore_conflicts(
+    app: tauri::State<'_, crate::checkpoint::state::CheckpointState>,
+    session_id: String,
+    project_id: String,
+    project_path: String,
+) -> Result<Vec<crate::checkpoint::RestoreConflict>, String> {
+    let manager = app
+        .get_or_create_manager(session_id, project_id, PathBuf::from(&project_path))
+        .await
+        .map_err(|e| format!("Failed to get checkpoint manager: {}", e))?;
+
+    Ok(manager.pending_restore_conflicts().await)
+}
+
+/// Settles one restore conflict by keeping the current file, taking the checkpoint's
+/// version, or writing merged content; returns the conflicts still unresolved
+#[tauri::command]
+pub async fn resolve_restore_conflict(
+    app: tauri::State<'_, crate::checkpoint::state::CheckpointState>,
+    session_id: String,
+    project_id: String,
+    project_path: String,
+    file_path: String,
+    resolution: crate::checkpoint::ConflictResolution,
+) -> Result<Vec<crate::checkpoint::RestoreConflict>, String> {
+    log::info!("Resolving restore conflict for {} in session {}", file_path, session_id);
+
+    let manager = app
+        .get_or_create_manager(session_id, project_id, PathBuf::from(&project_path))
+        .await
+        .map_err(|e| format!("Failed to get checkpoint manager: {}", e))?;
+
+    manager
+        .resolve_restore_conflict(std::path::Path::new(&file_path), resolution)
+        .await
+        .map_err(|e| format!("Failed to resolve restore conflict: {}", e))
+}
+
 /// Lists all checkpoints for a session
 #[tauri::command]
 pub async fn list_checkpoints(
@@ -2465,6 +3030,7 @@ pub async fn list_checkpoints(
 #[tauri::command]
 pub async fn fork_from_checkpoint(
     app: tauri::State<'_, crate::checkpoint::state::CheckpointState>,
+    app_handle: AppHandle,
     checkpoint_id: String,
     session_id: String,
     project_id: String,
@@ -2505,10 +3071,20 @@ pub async fn fork_from_checkpoint(
         .await
         .map_err(|e| format!("Failed to get checkpoint manager: {}", e))?;
 
-    manager
+    let result = manager
         .fork_from_checkpoint(&checkpoint_id, description)
         .await
-        .map_err(|e| format!("Failed to fork checkpoint: {}", e))
+        .map_err(|e| format!("Failed to fork checkpoint: {}", e))?;
+
+    fire_checkpoint_hooks(
+        &app_handle,
+        super::enhanced_hooks::HookEvent::OnCheckpointCreate,
+        &project_path,
+        &result,
+        serde_json::json!({ "forked_from": checkpoint_id, "source_session_id": session_id }),
+    );
+
+    Ok(result)
 }
 
 /// Gets the timeline for a session
@@ -2658,6 +3234,189 @@ pub async fn get_checkpoint_diff(
     })
 }
 
+/// Diffs a checkpoint against the files currently on disk, without creating a checkpoint.
+///
+/// This previews a code restore of the checkpoint: `modified` files would be
+/// overwritten, `added` ones deleted and `deleted` ones recreated. Files holding
+/// changes no checkpoint has are left alone by the restore and reported as conflicts.
+#[tauri::command]
+pub async fn diff_checkpoint_to_worktree(
+    checkpoint_id: String,
+    session_id: String,
+    project_id: String,
+    project_path: String,
+) -> Result<crate::checkpoint::WorktreeDiff, String> {
+    use crate::checkpoint::storage::CheckpointStorage;
+
+    log::info!("Diffing checkpoint {} against working tree", checkpoint_id);
+
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let scope = crate::checkpoint::scope::CheckpointScope::load(&claude_dir, &project_id);
+    let storage = CheckpointStorage::new(claude_dir);
+    let (_, snapshots, _) = storage
+        .load_checkpoint(&project_id, &session_id, &checkpoint_id)
+        .map_err(|e| format!("Failed to load checkpoint: {}", e))?;
+
+    tokio::task::spawn_blocking(move || {
+        let project_path = std::path::Path::new(&project_path);
+        let matcher = crate::checkpoint::scope::ScopeMatcher::new(&scope, project_path);
+        crate::checkpoint::diff::diff_snapshots_to_worktree(&checkpoint_id, &snapshots, project_path, &matcher)
+    })
+    .await
+    .map_err(|e| format!("Failed to diff checkpoint: {}", e))
+}
+
+/// Shows what `restore_checkpoint` would change in the working directory: files it
+/// would overwrite (`modified`), delete because they exist now but not in the
+/// checkpoint (`added`) or recreate (`deleted`), each with its diff
+#[tauri::command]
+pub async fn diff_checkpoint_against_workdir(
+    checkpoint_id: String,
+    session_id: String,
+    project_id: String,
+    project_path: String,
+) -> Result<crate::checkpoint::WorktreeDiff, String> {
+    diff_checkpoint_to_worktree(checkpoint_id, session_id, project_id, project_path).await
+}
+
+/// Gets a project's checkpoint scope and the files it currently captures
+#[tauri::command]
+pub async fn get_checkpoint_scope(
+    project_id: String,
+    project_path: String,
+) -> Result<crate::checkpoint::scope::CheckpointScopeInfo, String> {
+    use crate::checkpoint::scope::{CheckpointScope, CheckpointScopeInfo, ScopeMatcher, SCOPE_FILE_SAMPLE};
+
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let scope = CheckpointScope::load(&claude_dir, &project_id);
+
+    tokio::task::spawn_blocking(move || {
+        let mut files = ScopeMatcher::new(&scope, std::path::Path::new(&project_path)).collect_files();
+        files.sort();
+        let file_count = files.len();
+        files.truncate(SCOPE_FILE_SAMPLE);
+        CheckpointScopeInfo {
+            scope,
+            file_count,
+            files,
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to collect checkpoint scope: {}", e))
+}
+
+/// Updates which files a project's checkpoints capture
+#[tauri::command]
+pub async fn update_checkpoint_scope(
+    project_id: String,
+    project_path: String,
+    scope: crate::checkpoint::scope::CheckpointScope,
+) -> Result<crate::checkpoint::scope::CheckpointScopeInfo, String> {
+    log::info!("Updating checkpoint scope for project {}", project_id);
+
+    scope.validate()?;
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    scope
+        .save(&claude_dir, &project_id)
+        .map_err(|e| format!("Failed to save checkpoint scope: {}", e))?;
+
+    // Active checkpoint managers reload the scope on their next checkpoint
+    get_checkpoint_scope(project_id, project_path).await
+}
+
+/// Snapshots the files destructive Bash calls of an assistant message are about to
+/// touch. Runs as soon as the message is read from the stream, before the tool result
+/// arrives; the copy races the tool itself, so this is a best effort.
+async fn protect_destructive_tool_uses(app: &AppHandle, session_id: &str, project_path: &str, msg: &serde_json::Value) {
+    use crate::checkpoint::safety;
+
+    let tool_uses = msg["message"]["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|block| block["type"] == "tool_use" && block["name"] == "Bash");
+    for block in tool_uses {
+        let Some(command) = block["input"]["command"].as_str() else {
+            continue;
+        };
+        let Some(action) = safety::detect(command) else {
+            continue;
+        };
+        let Ok(claude_dir) = get_claude_dir() else {
+            return;
+        };
+
+        let project_id = encode_project_path(project_path);
+        let session = session_id.to_string();
+        let project = PathBuf::from(project_path);
+        let tool_use_id = block["id"].as_str().map(str::to_string);
+        let command_line = command.to_string();
+        let created = tokio::task::spawn_blocking(move || {
+            safety::create(&claude_dir, &project_id, &session, &project, tool_use_id, &command_line, &action)
+        })
+        .await;
+
+        match created {
+            Ok(Ok(Some(checkpoint))) => {
+                log::info!(
+                    "Safety checkpoint {} saved {} files before: {}",
+                    checkpoint.id,
+                    checkpoint.files.len(),
+                    checkpoint.command
+                );
+                if let Err(e) = AppEvent::SafetyCheckpointCreated(checkpoint).emit(app) {
+                    log::warn!("Failed to emit safety-checkpoint-created event: {}", e);
+                }
+            }
+            Ok(Ok(None)) => log::debug!("Destructive command touches no local files: {}", command),
+            Ok(Err(e)) => log::warn!("Failed to create safety checkpoint: {}", e),
+            Err(e) => log::warn!("Safety checkpoint task failed: {}", e),
+        }
+    }
+}
+
+/// Lists the safety checkpoints taken before destructive tool calls of a session, newest first
+#[tauri::command]
+pub async fn list_safety_checkpoints(
+    session_id: String,
+    project_id: String,
+) -> Result<Vec<crate::checkpoint::safety::SafetyCheckpoint>, String> {
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    Ok(crate::checkpoint::safety::list(&claude_dir, &project_id, &session_id))
+}
+
+/// Restores the files of one safety checkpoint, leaving all other files as they are
+#[tauri::command]
+pub async fn restore_safety_checkpoint(
+    session_id: String,
+    project_id: String,
+    checkpoint_id: String,
+) -> Result<crate::checkpoint::safety::SafetyCheckpoint, String> {
+    log::info!("Restoring safety checkpoint {} for session {}", checkpoint_id, session_id);
+
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    tokio::task::spawn_blocking(move || {
+        crate::checkpoint::safety::restore(&claude_dir, &project_id, &session_id, &checkpoint_id)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| format!("Failed to restore safety checkpoint: {}", e))
+}
+
+/// Undoes the session's most recent destructive action that has not been undone yet
+#[tauri::command]
+pub async fn undo_last_destructive_action(
+    session_id: String,
+    project_id: String,
+) -> Result<crate::checkpoint::safety::SafetyCheckpoint, String> {
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let latest = crate::checkpoint::safety::list(&claude_dir, &project_id, &session_id)
+        .into_iter()
+        .find(|checkpoint| checkpoint.restored_at.is_none())
+        .ok_or_else(|| "No destructive action to undo".to_string())?;
+    restore_safety_checkpoint(session_id, project_id, latest.id).await
+}
+
 /// Tracks a message for checkpointing
 #[tauri::command]
 pub async fn track_checkpoint_message(
@@ -2760,6 +3519,82 @@ pub async fn cleanup_old_checkpoints_by_age(
         .map_err(|e| format!("Failed to cleanup checkpoints by age: {}", e))
 }
 
+/// Projects to work on: the given one, or every project with checkpoints
+fn checkpoint_storage_projects(
+    storage: &crate::checkpoint::storage::CheckpointStorage,
+    project_id: Option<String>,
+) -> Result<Vec<String>, String> {
+    match project_id {
+        Some(project_id) if project_id.contains(['/', '\\']) || project_id.contains("..") => {
+            Err(format!("Invalid project id: {}", project_id))
+        }
+        Some(project_id) => Ok(vec![project_id]),
+        None => Ok(storage.checkpoint_projects()),
+    }
+}
+
+/// Reports how much disk space checkpoints take and how much compaction would reclaim,
+/// for one project or every project with checkpoints
+#[tauri::command]
+pub async fn get_checkpoint_storage_stats(
+    project_id: Option<String>,
+) -> Result<Vec<crate::checkpoint::CheckpointStorageStats>, String> {
+    use crate::checkpoint::storage::CheckpointStorage;
+
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    tokio::task::spawn_blocking(move || {
+        let storage = CheckpointStorage::new(claude_dir);
+        checkpoint_storage_projects(&storage, project_id)?
+            .iter()
+            .map(|project_id| {
+                storage.storage_stats(project_id).map_err(|e| {
+                    format!("Failed to read checkpoint storage of {}: {}", project_id, e)
+                })
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to read checkpoint storage: {}", e))?
+}
+
+/// Moves checkpoints into the deduplicated blob store and deletes blobs no checkpoint
+/// references, for one project or every project with checkpoints
+#[tauri::command]
+pub async fn compact_checkpoint_storage(
+    project_id: Option<String>,
+) -> Result<crate::checkpoint::CheckpointCompaction, String> {
+    use crate::checkpoint::{storage::CheckpointStorage, CheckpointCompaction};
+
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let report = tokio::task::spawn_blocking(move || {
+        let storage = CheckpointStorage::new(claude_dir);
+        let mut total = CheckpointCompaction::default();
+        for project_id in checkpoint_storage_projects(&storage, project_id)? {
+            let report = storage
+                .compact_storage(&project_id)
+                .map_err(|e| format!("Failed to compact checkpoints of {}: {}", project_id, e))?;
+            total.projects += report.projects;
+            total.migrated_blobs += report.migrated_blobs;
+            total.migrated_transcripts += report.migrated_transcripts;
+            total.removed_blobs += report.removed_blobs;
+            total.bytes_before += report.bytes_before;
+            total.bytes_after += report.bytes_after;
+            total.warnings.extend(report.warnings);
+        }
+        Ok::<_, String>(total)
+    })
+    .await
+    .map_err(|e| format!("Failed to compact checkpoint storage: {}", e))??;
+
+    log::info!(
+        "Compacted checkpoints of {} project(s): {} -> {} bytes",
+        report.projects,
+        report.bytes_before,
+        report.bytes_after
+    );
+    Ok(report)
+}
+
 /// Gets checkpoint settings for a session
 #[tauri::command]
 pub async fn get_checkpoint_settings(
@@ -2908,7 +3743,7 @@ pub async fn get_hooks_config(scope: String, project_path: Option<String>) -> Re
         return Ok(serde_json::json!({}));
     }
 
-    let content = fs::read_to_string(&settings_path)
+    let content = crate::config_file::read_to_string(&settings_path)
         .map_err(|e| format!("Failed to read settings: {}", e))?;
     
     let settings: serde_json::Value = serde_json::from_str(&content)
@@ -2933,14 +3768,14 @@ pub async fn update_hooks_config(
                 .join("settings.json")
         },
         "project" => {
-            let path = project_path.ok_or("Project path required for project scope")?;
+            let path = project_path.as_deref().ok_or("Project path required for project scope")?;
             let claude_dir = PathBuf::from(path).join(".claude");
             fs::create_dir_all(&claude_dir)
                 .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
             claude_dir.join("settings.json")
         },
         "local" => {
-            let path = project_path.ok_or("Project path required for local scope")?;
+            let path = project_path.as_deref().ok_or("Project path required for local scope")?;
             let claude_dir = PathBuf::from(path).join(".claude");
             fs::create_dir_all(&claude_dir)
                 .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
@@ -2949,39 +3784,36 @@ pub async fn update_hooks_config(
         _ => return Err("Invalid scope".to_string())
     };
 
-    // Read existing settings or create new
-    let mut settings = if settings_path.exists() {
-        let content = fs::read_to_string(&settings_path)
-            .map_err(|e| format!("Failed to read settings: {}", e))?;
-        serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse settings: {}", e))?
-    } else {
-        serde_json::json!({})
-    };
-
-    // Update hooks section
-    settings["hooks"] = hooks;
+    // Update the hooks section, keeping the rest of the file
+    crate::config_file::update_json(&settings_path, |settings| {
+        settings["hooks"] = hooks;
+        Ok(())
+    })?;
 
-    // Write back with pretty formatting
-    let json_string = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    
-    fs::write(&settings_path, json_string)
-        .map_err(|e| format!("Failed to write settings: {}", e))?;
+    // Local settings stay out of the project's repository if the user agreed to that
+    if let (Some(path), "local") = (&project_path, scope.as_str()) {
+        super::project_gitignore::local_file_written(path, ".claude/settings.local.json");
+    }
 
     Ok("Hooks configuration updated successfully".to_string())
 }
 
-/// Validates a hook command by dry-running it
+/// Validates a hook command's syntax for the shell it runs in (the platform default
+/// when not given) without running it
 #[tauri::command]
-pub async fn validate_hook_command(command: String) -> Result<serde_json::Value, String> {
+pub async fn validate_hook_command(
+    command: String,
+    shell: Option<super::enhanced_hooks::HookShell>,
+) -> Result<serde_json::Value, String> {
     log::info!("Validating hook command syntax");
 
-    // Validate syntax without executing
-    let mut cmd = std::process::Command::new("bash");
-    cmd.arg("-n") // Syntax check only
-       .arg("-c")
-       .arg(&command);
+    let shell = super::enhanced_hooks::HookShell::resolve(shell);
+    let Some(mut cmd) = shell.syntax_check(&command) else {
+        return Ok(serde_json::json!({
+            "valid": true,
+            "message": "cmd commands cannot be syntax-checked"
+        }));
+    };
     
     // Add CREATE_NO_WINDOW flag on Windows to prevent terminal window popup
     #[cfg(target_os = "windows")]
@@ -3051,23 +3883,19 @@ pub async fn set_custom_claude_path(app: AppHandle, custom_path: String) -> Resu
         }
         
         let db_path = app_data_dir.join("agents.db");
-        match rusqlite::Connection::open(&db_path) {
+        match super::db_pool::open_connection(&db_path) {
             Ok(conn) => {
-                // Create table if it doesn't exist
-                if let Err(e) = conn.execute(
-                    "CREATE TABLE IF NOT EXISTS app_settings (
-                        key TEXT PRIMARY KEY,
-                        value TEXT NOT NULL
-                    )",
-                    [],
-                ) {
-                    return Err(format!("Failed to create settings table: {}", e));
+                // The app may not have opened the database yet
+                if let Err(e) = super::db_migrations::migrate(&conn) {
+                    return Err(format!("Failed to migrate database: {}", e));
                 }
                 
                 // Store the custom path
-                if let Err(e) = conn.execute(
-                    "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
-                    rusqlite::params!["claude_binary_path", custom_path],
+                if let Err(e) = super::settings_store::set_in(
+                    &app,
+                    &conn,
+                    super::settings_store::CLAUDE_BINARY_PATH,
+                    &custom_path,
                 ) {
                     return Err(format!("Failed to store custom Claude path: {}", e));
                 }
@@ -3091,12 +3919,10 @@ pub async fn get_claude_path(app: AppHandle) -> Result<String, String> {
     if let Ok(app_data_dir) = app.path().app_data_dir() {
         let db_path = app_data_dir.join("agents.db");
         if db_path.exists() {
-            if let Ok(conn) = rusqlite::Connection::open(&db_path) {
-                if let Ok(stored_path) = conn.query_row(
-                    "SELECT value FROM app_settings WHERE key = 'claude_binary_path'",
-                    [],
-                    |row| row.get::<_, String>(0),
-                ) {
+            if let Ok(conn) = super::db_pool::open_connection(&db_path) {
+                if let Ok(Some(stored_path)) =
+                    super::settings_store::get::<String>(&conn, super::settings_store::CLAUDE_BINARY_PATH)
+                {
                     log::info!("Found stored Claude path: {}", stored_path);
                     return Ok(stored_path);
                 }
@@ -3122,12 +3948,11 @@ pub async fn clear_custom_claude_path(app: AppHandle) -> Result<(), String> {
     if let Ok(app_data_dir) = app.path().app_data_dir() {
         let db_path = app_data_dir.join("agents.db");
         if db_path.exists() {
-            match rusqlite::Connection::open(&db_path) {
+            match super::db_pool::open_connection(&db_path) {
                 Ok(conn) => {
-                    if let Err(e) = conn.execute(
-                        "DELETE FROM app_settings WHERE key = 'claude_binary_path'",
-                        [],
-                    ) {
+                    if let Err(e) =
+                        super::settings_store::remove_in(&app, &conn, super::settings_store::CLAUDE_BINARY_PATH)
+                    {
                         return Err(format!("Failed to clear custom Claude path: {}", e));
                     }
                     
@@ -3152,7 +3977,7 @@ pub async fn enhance_prompt(
     prompt: String, 
     model: String, 
     context: Option<Vec<String>>, 
-    _app: AppHandle
+    app: AppHandle
 ) -> Result<String, String> {
     log::info!("Enhancing prompt using local Claude Code CLI with context");
     
@@ -3200,7 +4025,7 @@ pub async fn enhance_prompt(
     let mut command = tokio::process::Command::new(&claude_path);
     command.args(&[
         "--print",
-        "--model", &map_model_to_claude_alias(&model)
+        "--model", &resolve_model_alias(&app, &model)
     ]);
 
     // 设置stdin
@@ -3629,52 +4454,44 @@ async fn find_claude_executable() -> Result<String, String> {
 
 /// 获取当前Claude执行配置
 #[tauri::command]
-pub async fn get_claude_execution_config(_app: AppHandle) -> Result<ClaudeExecutionConfig, String> {
-    let claude_dir = get_claude_dir()
-        .map_err(|e| format!("Failed to get Claude directory: {}", e))?;
-    let config_file = claude_dir.join("execution_config.json");
-    
-    if config_file.exists() {
-        match fs::read_to_string(&config_file) {
-            Ok(content) => {
-                match serde_json::from_str::<ClaudeExecutionConfig>(&content) {
-                    Ok(config) => {
-                        log::info!("Loaded Claude execution config");
-                        Ok(config)
-                    }
-                    Err(e) => {
-                        log::warn!("Failed to parse execution config: {}, using default", e);
-                        Ok(ClaudeExecutionConfig::default())
-                    }
-                }
-            }
-            Err(e) => {
-                log::warn!("Failed to read execution config: {}, using default", e);
-                Ok(ClaudeExecutionConfig::default())
+pub async fn get_claude_execution_config(app: AppHandle) -> Result<ClaudeExecutionConfig, String> {
+    let stored = {
+        let db = app.state::<super::agents::AgentDb>();
+        let conn = db.0.get()?;
+        settings_store::get::<ClaudeExecutionConfig>(&conn, settings_store::EXECUTION_CONFIG)
+    };
+    match stored {
+        Ok(Some(mut config)) => {
+            log::info!("Loaded Claude execution config");
+            // 危险跳过模式到期后自动关闭并保存
+            if config.permissions.revert_expired_dangerous_skip() {
+                log::warn!("Dangerous-skip permission mode expired, reverting to regular permissions");
+                update_claude_execution_config(app, config.clone()).await?;
             }
+            Ok(config)
+        }
+        Ok(None) => {
+            log::info!("No execution config stored, using default");
+            Ok(ClaudeExecutionConfig::default())
+        }
+        Err(e) => {
+            log::warn!("{}, using default", e);
+            Ok(ClaudeExecutionConfig::default())
         }
-    } else {
-        log::info!("No execution config file found, using default");
-        Ok(ClaudeExecutionConfig::default())
     }
 }
 
 /// 更新Claude执行配置
 #[tauri::command]
 pub async fn update_claude_execution_config(
-    _app: AppHandle,
-    config: ClaudeExecutionConfig,
+    app: AppHandle,
+    mut config: ClaudeExecutionConfig,
 ) -> Result<(), String> {
-    let claude_dir = get_claude_dir()
-        .map_err(|e| format!("Failed to get Claude directory: {}", e))?;
-    let config_file = claude_dir.join("execution_config.json");
-    
-    let json_string = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-        
-    fs::write(&config_file, json_string)
-        .map_err(|e| format!("Failed to write config file: {}", e))?;
-        
+    // 危险跳过模式必须限定项目并设置过期时间
+    config.permissions.prepare_dangerous_skip()?;
+
+    settings_store::set(&app, settings_store::EXECUTION_CONFIG, &config)?;
+
     log::info!("Updated Claude execution config");
     Ok(())
 }
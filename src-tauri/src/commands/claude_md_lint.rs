@@ -0,0 +1,445 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use super::claude::{encode_project_path, get_claude_dir};
+use super::session_handoff::{content_text, is_user_prompt};
+
+/// Above this many estimated tokens the file is reported as oversized
+const OVERSIZED_TOKENS: usize = 2500;
+
+/// Sections above this many estimated tokens are named in the oversized report
+const LARGE_SECTION_TOKENS: usize = 600;
+
+/// Most recent sessions checked for section relevance
+const RECENT_SESSIONS: usize = 20;
+
+/// Fewer sessions than this are too little history to call a section unused
+const MIN_SESSIONS_FOR_RELEVANCE: usize = 3;
+
+/// Instructions shorter than this (normalized) are not checked for duplicates
+const MIN_INSTRUCTION_CHARS: usize = 20;
+
+/// Phrasings of "don't do X", longest first
+const NEGATIVE_PREFIXES: &[&str] = &["never use ", "don't use ", "do not use ", "avoid using ", "avoid ", "never ", "don't ", "do not "];
+
+/// Phrasings of "do X", longest first
+const POSITIVE_PREFIXES: &[&str] = &["always use ", "prefer ", "always ", "use "];
+
+/// Heading words too generic to tell whether a section was relevant
+const GENERIC_WORDS: &[&str] = &[
+    "about", "general", "guide", "guidelines", "information", "instructions", "notes", "overview", "project",
+    "rules", "important", "these", "this", "that", "with", "when", "your", "from", "using",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaudeMdIssueKind {
+    Oversized,
+    Duplicate,
+    Conflict,
+    StaleReference,
+    UnusedSection,
+}
+
+/// Edit that resolves an issue, applied with `apply_claude_md_fixes`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeMdFix {
+    /// 1-based line numbers to delete
+    pub remove_lines: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeMdIssue {
+    pub kind: ClaudeMdIssueKind,
+    pub message: String,
+    /// 1-based line numbers the issue refers to
+    pub lines: Vec<usize>,
+    pub suggestion: String,
+    /// Set when the suggestion can be applied automatically
+    pub fix: Option<ClaudeMdFix>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeMdSection {
+    pub heading: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub estimated_tokens: usize,
+}
+
+/// Returned by `analyze_claude_md`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeMdAnalysis {
+    pub path: String,
+    pub estimated_tokens: usize,
+    pub line_count: usize,
+    pub sections: Vec<ClaudeMdSection>,
+    pub issues: Vec<ClaudeMdIssue>,
+    /// Recent sessions the relevance check looked at
+    pub sessions_checked: usize,
+}
+
+/// Rough token estimate: about four ASCII characters per token, one per other character
+pub fn estimate_tokens(text: &str) -> usize {
+    let (ascii, other) = text
+        .chars()
+        .fold((0usize, 0usize), |(ascii, other), c| if c.is_ascii() { (ascii + 1, other) } else { (ascii, other + 1) });
+    ascii.div_ceil(4) + other
+}
+
+/// Lowercase an instruction line without list markers, emphasis and trailing punctuation
+fn normalize_instruction(line: &str) -> String {
+    let trimmed = line.trim().trim_start_matches(|c: char| c == '-' || c == '*' || c == '+').trim_start();
+    let trimmed = match trimmed.split_once(". ") {
+        Some((number, rest)) if number.chars().all(|c| c.is_ascii_digit()) => rest,
+        _ => trimmed,
+    };
+    trimmed
+        .replace("**", "")
+        .replace('`', "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .trim_end_matches(|c: char| c == '.' || c == '!' || c == ';' || c == ',')
+        .to_string()
+}
+
+/// Split an instruction into its polarity and subject, e.g. `never use tabs` -> (false, `tabs`)
+fn instruction_polarity(normalized: &str) -> Option<(bool, &str)> {
+    let subject = |prefixes: &[&str]| prefixes.iter().find_map(|prefix| normalized.strip_prefix(prefix));
+    subject(NEGATIVE_PREFIXES)
+        .map(|s| (false, s))
+        .or_else(|| subject(POSITIVE_PREFIXES).map(|s| (true, s)))
+        .filter(|(_, s)| s.len() >= 3)
+}
+
+/// Markdown sections; text before the first heading forms an untitled section
+fn split_sections(lines: &[&str]) -> Vec<ClaudeMdSection> {
+    let mut sections: Vec<ClaudeMdSection> = Vec::new();
+    let mut in_code = false;
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        }
+        let is_heading = !in_code && line.starts_with('#');
+        if is_heading || sections.is_empty() {
+            sections.push(ClaudeMdSection {
+                heading: if is_heading { line.trim_start_matches('#').trim().to_string() } else { String::new() },
+                start_line: index + 1,
+                end_line: index + 1,
+                estimated_tokens: 0,
+            });
+        }
+        if let Some(section) = sections.last_mut() {
+            section.end_line = index + 1;
+            section.estimated_tokens += estimate_tokens(line) + 1;
+        }
+    }
+    sections
+}
+
+/// Lines outside code blocks, as `(line number, text)`
+fn prose_lines<'a>(lines: &[&'a str]) -> Vec<(usize, &'a str)> {
+    let mut in_code = false;
+    let mut prose = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if !in_code && !line.starts_with('#') {
+            prose.push((index + 1, *line));
+        }
+    }
+    prose
+}
+
+fn duplicate_issues(prose: &[(usize, &str)]) -> Vec<ClaudeMdIssue> {
+    let mut seen: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut order: Vec<String> = Vec::new();
+    for (line_number, line) in prose {
+        let normalized = normalize_instruction(line);
+        if normalized.len() < MIN_INSTRUCTION_CHARS {
+            continue;
+        }
+        let entry = seen.entry(normalized.clone()).or_default();
+        if entry.is_empty() {
+            order.push(normalized);
+        }
+        entry.push(*line_number);
+    }
+
+    order
+        .into_iter()
+        .filter_map(|instruction| {
+            let lines = seen.remove(&instruction)?;
+            (lines.len() > 1).then(|| ClaudeMdIssue {
+                kind: ClaudeMdIssueKind::Duplicate,
+                message: format!("Instruction repeated {} times: \"{}\"", lines.len(), instruction),
+                suggestion: format!("Keep line {} and remove the repeats", lines[0]),
+                fix: Some(ClaudeMdFix {
+                    remove_lines: lines[1..].to_vec(),
+                }),
+                lines,
+            })
+        })
+        .collect()
+}
+
+fn conflict_issues(prose: &[(usize, &str)]) -> Vec<ClaudeMdIssue> {
+    let normalized: Vec<(usize, String)> = prose
+        .iter()
+        .map(|(line_number, line)| (*line_number, normalize_instruction(line)))
+        .collect();
+    // Subject -> (lines saying do, lines saying don't)
+    let mut subjects: Vec<(&str, Vec<usize>, Vec<usize>)> = Vec::new();
+    for (line_number, instruction) in &normalized {
+        let Some((positive, subject)) = instruction_polarity(instruction) else {
+            continue;
+        };
+        let index = match subjects.iter().position(|(s, _, _)| *s == subject) {
+            Some(index) => index,
+            None => {
+                subjects.push((subject, Vec::new(), Vec::new()));
+                subjects.len() - 1
+            }
+        };
+        let (_, dos, donts) = &mut subjects[index];
+        if positive {
+            dos.push(*line_number);
+        } else {
+            donts.push(*line_number);
+        }
+    }
+
+    subjects
+        .into_iter()
+        .filter(|(_, dos, donts)| !dos.is_empty() && !donts.is_empty())
+        .map(|(subject, dos, donts)| {
+            let lines: BTreeSet<usize> = dos.iter().chain(&donts).copied().collect();
+            ClaudeMdIssue {
+                kind: ClaudeMdIssueKind::Conflict,
+                message: format!("Conflicting instructions about \"{}\"", subject),
+                lines: lines.into_iter().collect(),
+                suggestion: "Decide which instruction applies and remove the other".to_string(),
+                fix: None,
+            }
+        })
+        .collect()
+}
+
+/// Relative paths mentioned in the file that no longer exist under `project_root`
+fn stale_reference_issues(lines: &[&str], project_root: &Path) -> Vec<ClaudeMdIssue> {
+    let pattern = Regex::new(r"(?:^|[\s`(\[])@?((?:\./)?[A-Za-z0-9_.\-]+(?:/[A-Za-z0-9_.\-]+)+/?)").expect("valid path pattern");
+    let mut issues = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        for captures in pattern.captures_iter(line) {
+            let reference = captures[1].trim_end_matches(|c: char| c == '.' || c == ',' || c == ':' || c == ';');
+            if reference.contains("://") || reference.starts_with("..") || reference.contains("...") {
+                continue;
+            }
+            // `src/main.rs:42` style references name the file
+            let file = reference.split(':').next().unwrap_or(reference);
+            // Only file names with an extension and directories with a trailing slash,
+            // so prose like "and/or" is not taken for a path
+            let last = file.trim_end_matches('/').rsplit('/').next().unwrap_or_default();
+            if !file.ends_with('/') && !last.contains('.') {
+                continue;
+            }
+            if project_root.join(file.trim_start_matches("./")).exists() {
+                continue;
+            }
+            issues.push(ClaudeMdIssue {
+                kind: ClaudeMdIssueKind::StaleReference,
+                message: format!("References missing path {}", file),
+                lines: vec![index + 1],
+                suggestion: format!("Update or remove the reference to {}", file),
+                fix: None,
+            });
+        }
+    }
+    issues
+}
+
+/// Lowercased prompts and tool inputs of the project's most recent sessions
+fn recent_session_corpus(project_root: &Path) -> (String, usize) {
+    let Ok(claude_dir) = get_claude_dir() else {
+        return (String::new(), 0);
+    };
+    let sessions_dir = claude_dir
+        .join("projects")
+        .join(encode_project_path(&project_root.to_string_lossy()));
+    let Ok(entries) = fs::read_dir(&sessions_dir) else {
+        return (String::new(), 0);
+    };
+
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("jsonl"))
+        .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+        .collect();
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    files.truncate(RECENT_SESSIONS);
+
+    let mut corpus = String::new();
+    for (_, path) in &files {
+        let Ok(file) = fs::File::open(path) else {
+            continue;
+        };
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) else {
+                continue;
+            };
+            let content = &entry["message"]["content"];
+            match entry.get("type").and_then(|t| t.as_str()) {
+                Some("user") if is_user_prompt(content) => corpus.push_str(&content_text(content)),
+                Some("assistant") => {
+                    for block in content.as_array().into_iter().flatten() {
+                        if block.get("type").and_then(|t| t.as_str()) == Some("tool_use") {
+                            corpus.push_str(&block["input"].to_string());
+                        }
+                    }
+                }
+                _ => continue,
+            }
+            corpus.push('\n');
+        }
+    }
+    (corpus.to_lowercase(), files.len())
+}
+
+fn unused_section_issues(sections: &[ClaudeMdSection], corpus: &str) -> Vec<ClaudeMdIssue> {
+    sections
+        .iter()
+        .filter(|section| !section.heading.is_empty())
+        .filter_map(|section| {
+            let keywords: Vec<String> = section
+                .heading
+                .split(|c: char| !c.is_alphanumeric())
+                .map(str::to_lowercase)
+                .filter(|word| word.chars().count() >= 4 && !GENERIC_WORDS.contains(&word.as_str()))
+                .collect();
+            if keywords.is_empty() || keywords.iter().any(|word| corpus.contains(word.as_str())) {
+                return None;
+            }
+            Some(ClaudeMdIssue {
+                kind: ClaudeMdIssueKind::UnusedSection,
+                message: format!(
+                    "Section \"{}\" (~{} tokens) was not relevant to any recent session",
+                    section.heading, section.estimated_tokens
+                ),
+                lines: vec![section.start_line],
+                suggestion: "Remove the section or move it into a file imported only where needed".to_string(),
+                fix: Some(ClaudeMdFix {
+                    remove_lines: (section.start_line..=section.end_line).collect(),
+                }),
+            })
+        })
+        .collect()
+}
+
+fn analyze(path: &str, content: &str, project_root: Option<&Path>) -> ClaudeMdAnalysis {
+    let lines: Vec<&str> = content.lines().collect();
+    let sections = split_sections(&lines);
+    let prose = prose_lines(&lines);
+    let estimated_tokens = estimate_tokens(content);
+    let mut issues = Vec::new();
+
+    if estimated_tokens > OVERSIZED_TOKENS {
+        let large: Vec<String> = sections
+            .iter()
+            .filter(|s| s.estimated_tokens > LARGE_SECTION_TOKENS)
+            .map(|s| format!("\"{}\" (~{})", if s.heading.is_empty() { "(top)" } else { s.heading.as_str() }, s.estimated_tokens))
+            .collect();
+        issues.push(ClaudeMdIssue {
+            kind: ClaudeMdIssueKind::Oversized,
+            message: format!(
+                "~{} tokens are loaded into every session (recommended: under {})",
+                estimated_tokens, OVERSIZED_TOKENS
+            ),
+            lines: Vec::new(),
+            suggestion: if large.is_empty() {
+                "Trim instructions Claude follows without being told".to_string()
+            } else {
+                format!("Shorten or move out the largest sections: {}", large.join(", "))
+            },
+            fix: None,
+        });
+    }
+
+    issues.extend(duplicate_issues(&prose));
+    issues.extend(conflict_issues(&prose));
+
+    let mut sessions_checked = 0;
+    if let Some(root) = project_root {
+        issues.extend(stale_reference_issues(&lines, root));
+        let (corpus, sessions) = recent_session_corpus(root);
+        sessions_checked = sessions;
+        if sessions >= MIN_SESSIONS_FOR_RELEVANCE {
+            issues.extend(unused_section_issues(&sections, &corpus));
+        }
+    }
+
+    ClaudeMdAnalysis {
+        path: path.to_string(),
+        estimated_tokens,
+        line_count: lines.len(),
+        sections,
+        issues,
+        sessions_checked,
+    }
+}
+
+/// Analyze a CLAUDE.md file: size, duplicate and conflicting instructions, references
+/// to missing files and sections unrelated to recent sessions.
+///
+/// `content` analyzes unsaved editor content instead of the file. The project root
+/// defaults to the file's directory; the global `~/.claude/CLAUDE.md` has none, so
+/// the project checks are skipped for it.
+#[tauri::command]
+pub async fn analyze_claude_md(
+    path: String,
+    project_path: Option<String>,
+    content: Option<String>,
+) -> Result<ClaudeMdAnalysis, String> {
+    let content = match content {
+        Some(content) => content,
+        None => fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?,
+    };
+
+    let claude_dir = get_claude_dir().ok();
+    let project_root = project_path.map(PathBuf::from).or_else(|| {
+        Path::new(&path)
+            .parent()
+            .filter(|parent| Some(*parent) != claude_dir.as_deref())
+            .map(Path::to_path_buf)
+    });
+
+    tokio::task::spawn_blocking(move || analyze(&path, &content, project_root.as_deref()))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Apply the fixes of selected issues to CLAUDE.md content, returning the new content
+/// for the editor to review and save
+#[tauri::command]
+pub async fn apply_claude_md_fixes(content: String, fixes: Vec<ClaudeMdFix>) -> Result<String, String> {
+    let remove: BTreeSet<usize> = fixes.into_iter().flat_map(|fix| fix.remove_lines).collect();
+    let mut result = content
+        .lines()
+        .enumerate()
+        .filter(|(index, _)| !remove.contains(&(index + 1)))
+        .map(|(_, line)| line)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if content.ends_with('\n') {
+        result.push('\n');
+    }
+    Ok(result)
+}
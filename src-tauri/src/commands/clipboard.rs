@@ -102,13 +102,8 @@ pub async fn save_clipboard_image(
     
     println!("Image saved successfully! File size: {} bytes", file_size);
 
-    // 返回清洁的Windows文件路径，移除UNC前缀
-    let mut path_str = file_path.to_string_lossy().to_string();
-    
-    // 移除Windows长路径前缀 \\?\
-    if path_str.starts_with("\\\\?\\") {
-        path_str = path_str[4..].to_string();
-    }
+    // 返回清洁的Windows文件路径，移除长路径前缀（\\?\ 与 \\?\UNC\）
+    let path_str = crate::paths::display(&file_path);
     
     println!("Final cleaned path: {}", path_str);
     
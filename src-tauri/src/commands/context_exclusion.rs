@@ -0,0 +1,227 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, Manager, State};
+
+use super::agents::AgentDb;
+use super::session_meta::find_session_file;
+
+/// Stands in for the content of an excluded message in a filtered transcript
+const EXCLUDED_PLACEHOLDER: &str = "[Excluded from context]";
+
+/// A message left out of the context when its session is resumed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextExclusion {
+    /// Position among the session's entries as returned by `load_session_history`;
+    /// None if the entry is no longer in the transcript
+    pub index: Option<usize>,
+    pub entry_uuid: String,
+    pub excluded_at: String,
+}
+
+pub fn init_context_exclusion_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS message_context_exclusions (
+            session_id TEXT NOT NULL,
+            entry_uuid TEXT NOT NULL,
+            excluded_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (session_id, entry_uuid)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Entries of a session JSONL, skipping lines that are not valid JSON the same way
+/// `load_session_history` does, so indexes match what the UI shows
+fn read_entries(path: &Path) -> Result<Vec<Value>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read session file: {}", e))?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .collect())
+}
+
+fn excluded_uuids(conn: &Connection, session_id: &str) -> Result<Vec<(String, String)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT entry_uuid, excluded_at FROM message_context_exclusions
+             WHERE session_id = ?1 ORDER BY excluded_at",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([session_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn list_exclusions(
+    conn: &Connection,
+    session_id: &str,
+    entries: &[Value],
+) -> Result<Vec<ContextExclusion>, String> {
+    let mut exclusions: Vec<ContextExclusion> = excluded_uuids(conn, session_id)?
+        .into_iter()
+        .map(|(entry_uuid, excluded_at)| ContextExclusion {
+            index: entries
+                .iter()
+                .position(|entry| entry["uuid"].as_str() == Some(entry_uuid.as_str())),
+            entry_uuid,
+            excluded_at,
+        })
+        .collect();
+    exclusions.sort_by_key(|exclusion| exclusion.index.unwrap_or(usize::MAX));
+    Ok(exclusions)
+}
+
+fn placeholder_block() -> Value {
+    serde_json::json!({ "type": "text", "text": EXCLUDED_PLACEHOLDER })
+}
+
+/// Replace what an entry adds to the context with a placeholder, keeping its shape so
+/// tool uses still pair with their results. Thinking blocks are dropped since their
+/// signatures would no longer match.
+fn exclude_entry(entry: &mut Value) {
+    if let Some(object) = entry.as_object_mut() {
+        // The CLI's full copy of a tool result, kept for display only
+        object.remove("toolUseResult");
+    }
+    let Some(content) = entry.get_mut("message").and_then(|m| m.get_mut("content")) else {
+        return;
+    };
+    match content {
+        Value::String(text) => *text = EXCLUDED_PLACEHOLDER.to_string(),
+        Value::Array(blocks) => {
+            blocks.retain(|block| {
+                !matches!(block["type"].as_str(), Some("thinking") | Some("redacted_thinking"))
+            });
+            for block in blocks.iter_mut() {
+                match block["type"].as_str() {
+                    Some("tool_use") => block["input"] = serde_json::json!({}),
+                    Some("tool_result") => block["content"] = EXCLUDED_PLACEHOLDER.into(),
+                    _ => *block = placeholder_block(),
+                }
+            }
+            if blocks.is_empty() {
+                blocks.push(placeholder_block());
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Write a copy of a session with its excluded messages replaced by placeholders, under
+/// a new session id next to the original, which is left untouched. Returns the new
+/// session id to resume, or None if nothing in the session is excluded.
+pub fn materialize_filtered_transcript(
+    app: &AppHandle,
+    session_id: &str,
+) -> Result<Option<String>, String> {
+    let excluded: HashSet<String> = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        excluded_uuids(&conn, session_id)?.into_iter().map(|(uuid, _)| uuid).collect()
+    };
+    if excluded.is_empty() {
+        return Ok(None);
+    }
+    let Some((_, path)) = find_session_file(session_id, None)? else {
+        return Ok(None);
+    };
+
+    let filtered_id = uuid::Uuid::new_v4().to_string();
+    let mut filtered = String::new();
+    let mut excluded_count = 0;
+    for mut entry in read_entries(&path)? {
+        if entry["uuid"].as_str().map_or(false, |uuid| excluded.contains(uuid)) {
+            exclude_entry(&mut entry);
+            excluded_count += 1;
+        }
+        if let Some(object) = entry.as_object_mut() {
+            if object.contains_key("sessionId") {
+                object.insert("sessionId".to_string(), filtered_id.clone().into());
+            }
+        }
+        filtered.push_str(&entry.to_string());
+        filtered.push('\n');
+    }
+    if excluded_count == 0 {
+        return Ok(None);
+    }
+
+    let filtered_path = path.with_file_name(format!("{}.jsonl", filtered_id));
+    fs::write(&filtered_path, filtered)
+        .map_err(|e| format!("Failed to write filtered transcript: {}", e))?;
+    log::info!(
+        "Resuming session {} as {} with {} message(s) excluded from context",
+        session_id,
+        filtered_id,
+        excluded_count
+    );
+    Ok(Some(filtered_id))
+}
+
+/// Exclude a message from, or include it back in, the context of later resumes of its
+/// session. `index` is the message's position as returned by `load_session_history`.
+/// Returns the session's exclusions.
+#[tauri::command]
+pub async fn set_message_context_inclusion(
+    db: State<'_, AgentDb>,
+    session_id: String,
+    index: usize,
+    included: bool,
+) -> Result<Vec<ContextExclusion>, String> {
+    let (_, path) = find_session_file(&session_id, None)?
+        .ok_or_else(|| format!("Session {} not found", session_id))?;
+    let entries = read_entries(&path)?;
+    let entry = entries
+        .get(index)
+        .ok_or_else(|| format!("Session {} has no message at index {}", session_id, index))?;
+    if !matches!(entry["type"].as_str(), Some("user") | Some("assistant")) {
+        return Err(format!("Entry {} is not a user or assistant message", index));
+    }
+    let entry_uuid = entry["uuid"]
+        .as_str()
+        .ok_or_else(|| format!("Message {} has no uuid and can't be excluded", index))?;
+
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    if included {
+        conn.execute(
+            "DELETE FROM message_context_exclusions WHERE session_id = ?1 AND entry_uuid = ?2",
+            params![session_id, entry_uuid],
+        )
+    } else {
+        conn.execute(
+            "INSERT OR IGNORE INTO message_context_exclusions (session_id, entry_uuid)
+             VALUES (?1, ?2)",
+            params![session_id, entry_uuid],
+        )
+    }
+    .map_err(|e| e.to_string())?;
+
+    log::info!(
+        "Message {} of session {} {} context",
+        index,
+        session_id,
+        if included { "included in" } else { "excluded from" }
+    );
+    list_exclusions(&conn, &session_id, &entries)
+}
+
+/// Messages of a session excluded from the context of later resumes
+#[tauri::command]
+pub async fn list_context_exclusions(
+    db: State<'_, AgentDb>,
+    session_id: String,
+) -> Result<Vec<ContextExclusion>, String> {
+    let entries = match find_session_file(&session_id, None)? {
+        Some((_, path)) => read_entries(&path)?,
+        None => Vec::new(),
+    };
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    list_exclusions(&conn, &session_id, &entries)
+}
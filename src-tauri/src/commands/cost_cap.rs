@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use super::agents::AgentDb;
+use super::events::AppEvent;
+use super::execution_profiles::RunBudget;
+
+/// Payload of `cost-cap-reached`, also kept on the run's status
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CostCapReached {
+    /// The Claude run, or the agent run when `agent_run` is set
+    pub run_id: i64,
+    pub agent_run: bool,
+    pub session_id: Option<String>,
+    pub project_path: String,
+    pub limit_usd: f64,
+    /// Estimated spend when the run was stopped
+    pub spent_usd: f64,
+    /// Checkpoint taken before stopping; None if there was no session yet or it failed
+    pub checkpoint_id: Option<String>,
+}
+
+/// Reject caps that would stop a run before it starts
+pub fn validate(max_cost_usd: Option<f64>) -> Result<(), String> {
+    if max_cost_usd.map_or(false, |cap| cap <= 0.0) {
+        return Err("max_cost_usd must be positive".to_string());
+    }
+    Ok(())
+}
+
+async fn checkpoint(app: &AppHandle, session_id: &str, project_path: &str) -> Option<String> {
+    let checkpoint = super::claude::create_checkpoint(
+        app.state::<crate::checkpoint::state::CheckpointState>(),
+        app.clone(),
+        session_id.to_string(),
+        super::claude::encode_project_path(project_path),
+        project_path.to_string(),
+        None,
+        Some("Cost cap reached".to_string()),
+    )
+    .await;
+    match checkpoint {
+        Ok(result) => Some(result.checkpoint.id),
+        Err(e) => {
+            log::warn!("Failed to checkpoint session {} at its cost cap: {}", session_id, e);
+            None
+        }
+    }
+}
+
+/// Suspend the run so it stops spending, checkpoint its session and cancel it. The
+/// run's status ends up as `cost_cap_reached` rather than failed.
+pub async fn stop_session_run(
+    app: AppHandle,
+    run_id: i64,
+    pid: u32,
+    session_id: Option<String>,
+    project_path: String,
+    budget: RunBudget,
+) {
+    let message = format!(
+        "Stopping run: estimated cost ${:.2} exceeds its cap of ${:.2}",
+        budget.spent_usd(),
+        budget.limit_usd()
+    );
+    log::warn!("{}", message);
+    let error = AppEvent::ClaudeError { session_id: session_id.clone(), line: message };
+    let _ = error.emit_with_global(&app);
+
+    let registry = app.state::<crate::process::ProcessRegistryState>();
+    if let Err(e) = registry.0.pause_process(run_id) {
+        log::debug!("Run {} keeps going while it is checkpointed: {}", run_id, e);
+    }
+    let checkpoint_id = match &session_id {
+        Some(session_id) => checkpoint(&app, session_id, &project_path).await,
+        None => None,
+    };
+    let outcome = CostCapReached {
+        run_id,
+        agent_run: false,
+        session_id: session_id.clone(),
+        project_path,
+        limit_usd: budget.limit_usd(),
+        spent_usd: budget.spent_usd(),
+        checkpoint_id,
+    };
+    super::run_status::record_cost_cap(run_id, outcome.clone());
+
+    let stopped = match session_id {
+        Some(session_id) => {
+            super::claude::cancel_claude_execution(app.clone(), Some(session_id)).await
+        }
+        None => registry.0.kill_process_by_pid(run_id, pid).map(|_| ()),
+    };
+    if let Err(e) = stopped {
+        log::error!("Failed to stop run {} at its cost cap: {}", run_id, e);
+    }
+    if let Err(e) = AppEvent::CostCapReached(outcome).emit(&app) {
+        log::debug!("Failed to emit cost-cap-reached event: {}", e);
+    }
+}
+
+/// Agent-run counterpart of `stop_session_run`; the run is recorded with status
+/// `cost_cap_reached`
+async fn stop_agent_run(
+    app: AppHandle,
+    run_id: i64,
+    session_id: Option<String>,
+    project_path: String,
+    budget: RunBudget,
+) {
+    log::warn!(
+        "Stopping agent run {}: estimated cost ${:.2} exceeds its cap of ${:.2}",
+        run_id,
+        budget.spent_usd(),
+        budget.limit_usd()
+    );
+    let registry = app.state::<crate::process::ProcessRegistryState>();
+    if let Err(e) = registry.0.pause_process(run_id) {
+        log::debug!("Agent run {} keeps going while it is checkpointed: {}", run_id, e);
+    }
+    let checkpoint_id = match &session_id {
+        Some(session_id) => checkpoint(&app, session_id, &project_path).await,
+        None => None,
+    };
+
+    let killed = super::agents::kill_agent_session(
+        app.clone(),
+        app.state::<AgentDb>(),
+        app.state::<crate::process::ProcessRegistryState>(),
+        run_id,
+    )
+    .await;
+    if let Err(e) = killed {
+        log::error!("Failed to stop agent run {} at its cost cap: {}", run_id, e);
+    }
+    let recorded = app.state::<AgentDb>().0.get().and_then(|conn| {
+        conn.execute(
+            "UPDATE agent_runs SET status = 'cost_cap_reached',
+                 completed_at = COALESCE(completed_at, CURRENT_TIMESTAMP)
+             WHERE id = ?1 AND status IN ('running', 'cancelled')",
+            [run_id],
+        )
+        .map_err(|e| e.to_string())
+    });
+    if let Err(e) = recorded {
+        log::warn!("Failed to record cost cap of agent run {}: {}", run_id, e);
+    }
+
+    let outcome = CostCapReached {
+        run_id,
+        agent_run: true,
+        session_id,
+        project_path,
+        limit_usd: budget.limit_usd(),
+        spent_usd: budget.spent_usd(),
+        checkpoint_id,
+    };
+    if let Err(e) = AppEvent::CostCapReached(outcome).emit(&app) {
+        log::debug!("Failed to emit cost-cap-reached event: {}", e);
+    }
+}
+
+/// Count an agent run's stream line against its cap, stopping the run once the cap is
+/// exceeded. The budget is taken so the run is only stopped once.
+pub fn observe_agent_line(
+    app: &AppHandle,
+    run_id: i64,
+    session_id: Option<String>,
+    project_path: &str,
+    model: &str,
+    budget: &mut Option<RunBudget>,
+    line: &str,
+) {
+    let Some(tracked) = budget.as_mut() else {
+        return;
+    };
+    let Ok(msg) = serde_json::from_str::<serde_json::Value>(line) else {
+        return;
+    };
+    if msg["type"] != "assistant" || !tracked.record(&msg, model) {
+        return;
+    }
+    if let Some(budget) = budget.take() {
+        tauri::async_runtime::spawn(stop_agent_run(
+            app.clone(),
+            run_id,
+            session_id,
+            project_path.to_string(),
+            budget,
+        ));
+    }
+}
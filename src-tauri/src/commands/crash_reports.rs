@@ -0,0 +1,328 @@
+use chrono::Utc;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+/// Subdirectory of the app data directory holding crash reports
+const CRASH_DIR_NAME: &str = "crash_reports";
+
+/// Used before the app handle is available; matches the identifier in tauri.conf.json
+const APP_IDENTIFIER: &str = "claude.workbench.app";
+
+/// Log lines kept in memory for the next crash report
+const LOG_TAIL_LINES: usize = 200;
+
+/// Longest log line or panic message kept in a report
+const MAX_LINE_CHARS: usize = 500;
+
+/// Reports kept on disk; older ones are deleted
+const MAX_REPORTS: usize = 20;
+
+/// Log messages that carry prompt or transcript content; never kept for reports
+const PROMPT_BEARING_MESSAGES: &[&str] = &[
+    "Resume command:",
+    "Enhanced prompt preview",
+    "Claude stdout:",
+    "Claude stderr:",
+];
+
+/// A crash written to disk by the panic hook or the fatal error handler
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    pub created_at: String,
+    /// `panic` or `fatal_error`
+    pub kind: String,
+    pub message: String,
+    /// `file:line:column` of the panic
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub backtrace: String,
+    /// Recent log lines at info level and above, without prompt content
+    pub log_tail: Vec<String>,
+    pub app_version: String,
+    pub tauri_version: String,
+    pub os: String,
+    pub arch: String,
+    /// Whether the recovery dialog was shown for it
+    #[serde(default)]
+    pub acknowledged: bool,
+}
+
+/// Listing entry of `list_crash_reports`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReportSummary {
+    pub id: String,
+    pub created_at: String,
+    pub kind: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub app_version: String,
+}
+
+static LOG_TAIL: Lazy<Mutex<VecDeque<String>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+static CRASH_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+/// env_logger output plus an in-memory tail of recent lines for crash reports
+struct TailLogger {
+    inner: env_logger::Logger,
+}
+
+impl Log for TailLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Info || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.inner.matches(record) {
+            self.inner.log(record);
+        }
+        if record.level() > Level::Info {
+            return;
+        }
+        let message = record.args().to_string();
+        if PROMPT_BEARING_MESSAGES.iter().any(|marker| message.contains(marker)) {
+            return;
+        }
+        let line = format!(
+            "{} {} {}: {}",
+            Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            record.level(),
+            record.target(),
+            truncate(&message)
+        );
+        if let Ok(mut tail) = LOG_TAIL.lock() {
+            if tail.len() >= LOG_TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_LINE_CHARS {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(MAX_LINE_CHARS).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Install the logger (configured by `RUST_LOG` like before) and the panic hook
+pub fn install() {
+    let inner = env_logger::Builder::from_default_env().build();
+    let max_level = inner.filter().max(LevelFilter::Info);
+    if log::set_boxed_logger(Box::new(TailLogger { inner })).is_ok() {
+        log::set_max_level(max_level);
+    }
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        match write_report("panic", &message, location) {
+            Ok(path) => eprintln!("Crash report written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write crash report: {}", e),
+        }
+        default_hook(info);
+    }));
+}
+
+fn crash_dir() -> Option<PathBuf> {
+    CRASH_DIR
+        .get()
+        .cloned()
+        .or_else(|| dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER).join(CRASH_DIR_NAME)))
+}
+
+fn report_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.json", id))
+}
+
+fn write_report(kind: &str, message: &str, location: Option<String>) -> Result<PathBuf, String> {
+    let dir = crash_dir().ok_or("Could not determine the crash report directory")?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let now = Utc::now();
+    let id = format!(
+        "{}-{}",
+        now.format("%Y%m%dT%H%M%SZ"),
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    );
+    let report = CrashReport {
+        id: id.clone(),
+        created_at: now.to_rfc3339(),
+        kind: kind.to_string(),
+        message: truncate(message),
+        location,
+        thread: std::thread::current().name().map(str::to_string),
+        backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        // A panic while logging leaves the lock poisoned; the lines are still usable
+        log_tail: match LOG_TAIL.lock() {
+            Ok(tail) => tail.iter().cloned().collect(),
+            Err(poisoned) => poisoned.into_inner().iter().cloned().collect(),
+        },
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        acknowledged: false,
+    };
+    let path = report_path(&dir, &id);
+    let content = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())?;
+    prune_reports(&dir);
+    Ok(path)
+}
+
+/// Record a fatal Tauri error, then exit
+pub fn fatal_error(context: &str, error: impl std::fmt::Display) -> ! {
+    let message = format!("{}: {}", context, error);
+    log::error!("{}", message);
+    if let Err(e) = write_report("fatal_error", &message, None) {
+        eprintln!("Failed to write crash report: {}", e);
+    }
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+fn read_reports(dir: &Path) -> Vec<CrashReport> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut reports: Vec<CrashReport> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    // Ids start with the UTC timestamp, so this is newest first
+    reports.sort_by(|a, b| b.id.cmp(&a.id));
+    reports
+}
+
+fn prune_reports(dir: &Path) {
+    for report in read_reports(dir).into_iter().skip(MAX_REPORTS) {
+        let _ = fs::remove_file(report_path(dir, &report.id));
+    }
+}
+
+/// Use the app data directory for reports and offer recovery for crashes since the last start
+pub fn init_crash_reports(app: &AppHandle) {
+    if let Ok(dir) = app.path().app_data_dir() {
+        let _ = CRASH_DIR.set(dir.join(CRASH_DIR_NAME));
+    }
+    let Some(dir) = crash_dir() else {
+        return;
+    };
+
+    let unseen: Vec<CrashReport> = read_reports(&dir).into_iter().filter(|r| !r.acknowledged).collect();
+    let Some(latest) = unseen.first() else {
+        return;
+    };
+    for report in &unseen {
+        let mut report = report.clone();
+        report.acknowledged = true;
+        if let Ok(content) = serde_json::to_string_pretty(&report) {
+            let _ = fs::write(report_path(&dir, &report.id), content);
+        }
+    }
+
+    log::warn!("Found {} crash report(s) since the last start", unseen.len());
+    let text = format!(
+        "Claude Workbench closed unexpectedly on {}.\n\n{}\n\nA crash report without your prompts or \
+         transcripts was saved to:\n{}\n\nYou can export it from Settings to attach to a bug report.",
+        latest.created_at,
+        latest.message,
+        report_path(&dir, &latest.id).display()
+    );
+    app.dialog()
+        .message(text)
+        .title("Claude Workbench recovered from a crash")
+        .kind(MessageDialogKind::Warning)
+        .show(|_| {});
+}
+
+/// Crash reports on disk, newest first
+#[tauri::command]
+pub async fn list_crash_reports() -> Result<Vec<CrashReportSummary>, String> {
+    let Some(dir) = crash_dir() else {
+        return Ok(Vec::new());
+    };
+    Ok(read_reports(&dir)
+        .into_iter()
+        .map(|report| CrashReportSummary {
+            id: report.id,
+            created_at: report.created_at,
+            kind: report.kind,
+            message: report.message,
+            location: report.location,
+            app_version: report.app_version,
+        })
+        .collect())
+}
+
+fn render_markdown(report: &CrashReport) -> String {
+    format!(
+        "## Crash report {}\n\n\
+         - **When:** {}\n\
+         - **Kind:** {}\n\
+         - **App version:** {} (Tauri {})\n\
+         - **Platform:** {} {}\n\
+         - **Location:** {}\n\
+         - **Thread:** {}\n\n\
+         ### Message\n\n```\n{}\n```\n\n\
+         ### Backtrace\n\n```\n{}\n```\n\n\
+         ### Recent log\n\n```\n{}\n```\n",
+        report.id,
+        report.created_at,
+        report.kind,
+        report.app_version,
+        report.tauri_version,
+        report.os,
+        report.arch,
+        report.location.as_deref().unwrap_or("unknown"),
+        report.thread.as_deref().unwrap_or("unnamed"),
+        report.message,
+        report.backtrace.trim_end(),
+        report.log_tail.join("\n")
+    )
+}
+
+/// Render a crash report as Markdown for a bug report, and write it to `dest` if given
+#[tauri::command]
+pub async fn export_crash_report(report_id: String, dest: Option<String>) -> Result<String, String> {
+    let dir = crash_dir().ok_or("Could not determine the crash report directory")?;
+    if report_id.contains(['/', '\\']) || report_id.contains("..") {
+        return Err(format!("Invalid crash report id: {}", report_id));
+    }
+    let content = fs::read_to_string(report_path(&dir, &report_id))
+        .map_err(|_| format!("Crash report {} not found", report_id))?;
+    let report: CrashReport = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    let markdown = render_markdown(&report);
+    if let Some(dest) = dest {
+        fs::write(&dest, &markdown).map_err(|e| format!("Failed to write {}: {}", dest, e))?;
+        log::info!("Exported crash report {} to {}", report_id, dest);
+    }
+    Ok(markdown)
+}
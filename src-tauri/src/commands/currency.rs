@@ -0,0 +1,234 @@
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Costs are computed in USD; every other currency is converted from it
+const BASE_CURRENCY: &str = "USD";
+
+/// Free, keyless USD exchange-rate endpoint
+const RATES_URL: &str = "https://open.er-api.com/v6/latest/USD";
+
+/// Auto-updated rates older than this are refreshed in the background
+const RATE_MAX_AGE_HOURS: i64 = 12;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How costs are shown. Stored in ~/.claude/currency_config.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrencyConfig {
+    /// ISO 4217 code of the display currency
+    pub currency: String,
+    /// Fetch the USD rate automatically; otherwise `manual_rate` is used
+    #[serde(default)]
+    pub auto_update: bool,
+    /// Units of `currency` per USD, set by the user
+    #[serde(default)]
+    pub manual_rate: Option<f64>,
+    /// Last rate fetched for `currency`, used offline
+    #[serde(default)]
+    pub last_known_rate: Option<f64>,
+    #[serde(default)]
+    pub rate_updated_at: Option<DateTime<Utc>>,
+}
+
+impl Default for CurrencyConfig {
+    fn default() -> Self {
+        Self {
+            currency: BASE_CURRENCY.to_string(),
+            auto_update: false,
+            manual_rate: None,
+            last_known_rate: None,
+            rate_updated_at: None,
+        }
+    }
+}
+
+/// Returned by the currency commands: the settings and the rate in effect
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrencyStatus {
+    pub config: CurrencyConfig,
+    /// Currency local amounts are in; USD when no rate is known for the configured one
+    pub display_currency: String,
+    /// Units of `display_currency` per USD
+    pub rate: f64,
+    /// The auto-updated rate is older than the refresh interval (e.g. while offline)
+    pub stale: bool,
+}
+
+static CONFIG: Lazy<RwLock<CurrencyConfig>> = Lazy::new(|| RwLock::new(load_config()));
+
+/// Set while a background rate refresh is running
+static REFRESHING: AtomicBool = AtomicBool::new(false);
+
+fn config_path() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or("Failed to get home directory")?;
+    Ok(home_dir.join(".claude").join("currency_config.json"))
+}
+
+fn load_config() -> CurrencyConfig {
+    config_path()
+        .ok()
+        .and_then(|path| crate::config_file::read_to_string(&path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_config(config: &CurrencyConfig) -> Result<(), String> {
+    let path = config_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    crate::config_file::write(&path, content)
+        .map_err(|e| format!("Failed to write currency config: {}", e))
+}
+
+fn is_stale(config: &CurrencyConfig) -> bool {
+    config.auto_update
+        && config.currency != BASE_CURRENCY
+        && config
+            .rate_updated_at
+            .map_or(true, |at| Utc::now() - at > chrono::Duration::hours(RATE_MAX_AGE_HOURS))
+}
+
+fn status(config: &CurrencyConfig) -> CurrencyStatus {
+    let rate = if config.currency == BASE_CURRENCY {
+        Some(1.0)
+    } else if config.auto_update {
+        config.last_known_rate
+    } else {
+        config.manual_rate.or(config.last_known_rate)
+    };
+
+    let (display_currency, rate) = match rate {
+        Some(rate) => (config.currency.clone(), rate),
+        None => (BASE_CURRENCY.to_string(), 1.0),
+    };
+    CurrencyStatus {
+        config: config.clone(),
+        display_currency,
+        rate,
+        stale: is_stale(config),
+    }
+}
+
+/// Current currency status; starts a background refresh when the auto-updated rate is stale
+pub fn currency_status() -> CurrencyStatus {
+    let current = match CONFIG.read() {
+        Ok(config) => status(&config),
+        Err(_) => status(&CurrencyConfig::default()),
+    };
+    if current.stale && !REFRESHING.swap(true, Ordering::SeqCst) {
+        tauri::async_runtime::spawn(async {
+            if let Err(e) = refresh_rate().await {
+                log::warn!("Failed to refresh exchange rate, keeping last known rate: {}", e);
+            }
+            REFRESHING.store(false, Ordering::SeqCst);
+        });
+    }
+    current
+}
+
+#[derive(Debug, Deserialize)]
+struct RatesResponse {
+    result: String,
+    #[serde(default)]
+    rates: HashMap<String, f64>,
+}
+
+/// Fetch the USD rate for the configured currency and remember it
+async fn refresh_rate() -> Result<(), String> {
+    let currency = CONFIG.read().map_err(|e| e.to_string())?.currency.clone();
+    if currency == BASE_CURRENCY {
+        return Ok(());
+    }
+
+    let response = reqwest::Client::new()
+        .get(RATES_URL)
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch exchange rates: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Exchange rate API error: HTTP {}", response.status()));
+    }
+    let rates: RatesResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse exchange rates: {}", e))?;
+    if rates.result != "success" {
+        return Err(format!("Exchange rate API returned '{}'", rates.result));
+    }
+    let rate = *rates
+        .rates
+        .get(&currency)
+        .ok_or_else(|| format!("No exchange rate available for {}", currency))?;
+
+    let mut config = CONFIG.write().map_err(|e| e.to_string())?;
+    // The user may have switched currency while the request was in flight
+    if config.currency != currency {
+        return Ok(());
+    }
+    config.last_known_rate = Some(rate);
+    config.rate_updated_at = Some(Utc::now());
+    save_config(&config)?;
+    log::info!("Updated exchange rate: 1 USD = {} {}", rate, currency);
+    Ok(())
+}
+
+/// Get the display currency settings and the rate in effect
+#[tauri::command]
+pub async fn get_currency_config() -> Result<CurrencyStatus, String> {
+    Ok(currency_status())
+}
+
+/// Change the display currency and how its exchange rate is obtained.
+///
+/// With auto-update on, the rate is fetched right away; if that fails the last
+/// known rate for the currency stays in use.
+#[tauri::command]
+pub async fn update_currency_config(mut config: CurrencyConfig) -> Result<CurrencyStatus, String> {
+    config.currency = config.currency.trim().to_uppercase();
+    if config.currency.len() != 3 || !config.currency.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(format!("Invalid currency code: {}", config.currency));
+    }
+    if let Some(rate) = config.manual_rate {
+        if !(rate.is_finite() && rate > 0.0) {
+            return Err("Exchange rate must be a positive number".to_string());
+        }
+    }
+    if !config.auto_update && config.currency != BASE_CURRENCY && config.manual_rate.is_none() {
+        return Err("Set a manual exchange rate or enable automatic updates".to_string());
+    }
+
+    {
+        let mut current = CONFIG.write().map_err(|e| e.to_string())?;
+        // Rates fetched for another currency don't apply
+        if current.currency == config.currency {
+            config.last_known_rate = config.last_known_rate.or(current.last_known_rate);
+            config.rate_updated_at = config.rate_updated_at.or(current.rate_updated_at);
+        } else {
+            config.last_known_rate = None;
+            config.rate_updated_at = None;
+        }
+        save_config(&config)?;
+        *current = config;
+    }
+
+    let stale = {
+        let cfg = CONFIG.read().map_err(|e| e.to_string())?;
+        is_stale(&cfg)
+    };
+    if stale {
+        if let Err(e) = refresh_rate().await {
+            log::warn!("Failed to fetch exchange rate: {}", e);
+        }
+    }
+    Ok(currency_status())
+}
@@ -0,0 +1,455 @@
+//! Versioned schema migrations of the agents database.
+//!
+//! Each migration has an `up` script under `migrations/`, numbered in order, and
+//! optionally a `down` script reverting it. Applied versions are recorded in the
+//! `schema_version` table with a checksum of their `up` script; every start applies
+//! the pending ones, each in its own transaction. A released migration must never
+//! change: add a new one instead.
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Transaction, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::State;
+
+use super::agents::AgentDb;
+
+/// One step of the agents database schema
+struct Migration {
+    version: u32,
+    name: &'static str,
+    /// Run before `up`, for changes plain SQL cannot make idempotently, like adding a
+    /// column that databases from before versioning may already have
+    prepare: Option<fn(&Connection) -> rusqlite::Result<()>>,
+    up: &'static str,
+    /// None when the migration cannot be reverted
+    down: Option<&'static str>,
+}
+
+/// The migrations of agents.db, in order
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "baseline",
+        prepare: Some(super::agents::create_baseline_schema),
+        up: "",
+        down: None,
+    },
+    Migration {
+        version: 2,
+        name: "subagents",
+        prepare: Some(super::subagents::add_specialty_columns),
+        up: include_str!("migrations/0002_subagents.up.sql"),
+        down: Some(include_str!("migrations/0002_subagents.down.sql")),
+    },
+    Migration {
+        version: 3,
+        name: "usage_entries_session_index",
+        prepare: None,
+        up: include_str!("migrations/0003_usage_entries_session_index.up.sql"),
+        down: Some(include_str!(
+            "migrations/0003_usage_entries_session_index.down.sql"
+        )),
+    },
+    Migration {
+        version: 4,
+        name: "prompt_templates",
+        prepare: None,
+        up: include_str!("migrations/0004_prompt_templates.up.sql"),
+        down: Some(include_str!("migrations/0004_prompt_templates.down.sql")),
+    },
+    Migration {
+        version: 5,
+        name: "usage_row_ids",
+        prepare: None,
+        up: include_str!("migrations/0005_usage_row_ids.up.sql"),
+        down: Some(include_str!("migrations/0005_usage_row_ids.down.sql")),
+    },
+    Migration {
+        version: 6,
+        name: "json_setting_values",
+        prepare: None,
+        up: include_str!("migrations/0006_json_setting_values.up.sql"),
+        down: Some(include_str!("migrations/0006_json_setting_values.down.sql")),
+    },
+];
+
+/// A migration and whether it has been applied to the database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationStatus {
+    pub version: u32,
+    pub name: String,
+    /// None while pending
+    pub applied_at: Option<String>,
+    pub reversible: bool,
+    /// The `up` script changed after it was applied
+    pub modified: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableInfo {
+    pub name: String,
+    pub rows: i64,
+}
+
+/// Returned by `get_db_schema_info`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbSchemaInfo {
+    pub db_path: String,
+    pub sqlite_version: String,
+    pub current_version: u32,
+    pub latest_version: u32,
+    pub migrations: Vec<MigrationStatus>,
+    /// Versions applied by a newer build of the app, unknown to this one
+    pub unknown_versions: Vec<u32>,
+    pub tables: Vec<TableInfo>,
+}
+
+/// Returned by `rollback_db_schema`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaRollback {
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Copy of the database taken before reverting
+    pub backup_path: String,
+}
+
+fn checksum(sql: &str) -> String {
+    format!("{:x}", Sha256::digest(sql.as_bytes()))
+}
+
+fn ensure_version_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Applied versions with their checksum and time
+fn applied(conn: &Connection) -> rusqlite::Result<HashMap<u32, (String, String)>> {
+    let mut stmt = conn.prepare("SELECT version, checksum, applied_at FROM schema_version")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?;
+    rows.collect()
+}
+
+fn is_applied(conn: &Connection, version: u32) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT 1 FROM schema_version WHERE version = ?1",
+        [version],
+        |_| Ok(()),
+    )
+    .optional()
+    .map(|row| row.is_some())
+}
+
+/// The highest applied version, 0 for a new database
+pub fn current_version(conn: &Connection) -> rusqlite::Result<u32> {
+    ensure_version_table(conn)?;
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )
+}
+
+fn apply(conn: &Connection, migrations: &[Migration]) -> rusqlite::Result<()> {
+    ensure_version_table(conn)?;
+    for migration in migrations {
+        let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+        // Another connection may have applied it while this one waited for the lock
+        if is_applied(&tx, migration.version)? {
+            continue;
+        }
+        if let Some(prepare) = migration.prepare {
+            prepare(&tx)?;
+        }
+        tx.execute_batch(migration.up)?;
+        tx.execute(
+            "INSERT INTO schema_version (version, name, checksum) VALUES (?1, ?2, ?3)",
+            params![migration.version, migration.name, checksum(migration.up)],
+        )?;
+        tx.commit()?;
+        log::info!(
+            "Applied database migration {} ({})",
+            migration.version,
+            migration.name
+        );
+    }
+    Ok(())
+}
+
+/// Revert the applied migrations above `target`, newest first. Stops at the first
+/// one that cannot be reverted.
+fn revert(conn: &Connection, migrations: &[Migration], target: u32) -> Result<(), String> {
+    ensure_version_table(conn).map_err(|e| e.to_string())?;
+    for migration in migrations.iter().rev().filter(|m| m.version > target) {
+        let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+            .map_err(|e| e.to_string())?;
+        if !is_applied(&tx, migration.version).map_err(|e| e.to_string())? {
+            continue;
+        }
+        let Some(down) = migration.down else {
+            return Err(format!(
+                "Migration {} ({}) cannot be reverted",
+                migration.version, migration.name
+            ));
+        };
+        let reverted = (|| {
+            tx.execute_batch(down)?;
+            tx.execute(
+                "DELETE FROM schema_version WHERE version = ?1",
+                [migration.version],
+            )?;
+            tx.commit()
+        })();
+        reverted.map_err(|e| {
+            format!(
+                "Failed to revert migration {} ({}): {}",
+                migration.version, migration.name, e
+            )
+        })?;
+        log::info!(
+            "Reverted database migration {} ({})",
+            migration.version,
+            migration.name
+        );
+    }
+    Ok(())
+}
+
+fn statuses(
+    conn: &Connection,
+    migrations: &[Migration],
+) -> rusqlite::Result<(Vec<MigrationStatus>, Vec<u32>)> {
+    ensure_version_table(conn)?;
+    let mut applied = applied(conn)?;
+    let statuses = migrations
+        .iter()
+        .map(|migration| {
+            let record = applied.remove(&migration.version);
+            MigrationStatus {
+                version: migration.version,
+                name: migration.name.to_string(),
+                modified: record
+                    .as_ref()
+                    .map_or(false, |(sum, _)| *sum != checksum(migration.up)),
+                applied_at: record.map(|(_, at)| at),
+                reversible: migration.down.is_some(),
+            }
+        })
+        .collect();
+    let mut unknown: Vec<u32> = applied.into_keys().collect();
+    unknown.sort_unstable();
+    Ok((statuses, unknown))
+}
+
+/// Bring the agents database schema up to date
+pub fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    apply(conn, MIGRATIONS)?;
+    let (statuses, unknown) = statuses(conn, MIGRATIONS)?;
+    for status in statuses.iter().filter(|status| status.modified) {
+        log::warn!(
+            "Database migration {} ({}) changed after it was applied",
+            status.version,
+            status.name
+        );
+    }
+    if !unknown.is_empty() {
+        log::warn!(
+            "The database has migrations {:?} from a newer version of the app",
+            unknown
+        );
+    }
+    Ok(())
+}
+
+/// Report the schema version, the migrations and the tables of the agents database
+#[tauri::command]
+pub async fn get_db_schema_info(db: State<'_, AgentDb>) -> Result<DbSchemaInfo, String> {
+    let conn = db.0.get()?;
+    let (migrations, unknown_versions) = statuses(&conn, MIGRATIONS).map_err(|e| e.to_string())?;
+    let sqlite_version: String = conn
+        .query_row("SELECT sqlite_version()", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+
+    let names: Vec<String> = conn
+        .prepare(
+            "SELECT name FROM sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+        )
+        .and_then(|mut stmt| stmt.query_map([], |row| row.get(0))?.collect())
+        .map_err(|e| e.to_string())?;
+    let tables = names
+        .into_iter()
+        .map(|name| {
+            let rows = conn
+                .query_row(&format!("SELECT COUNT(*) FROM \"{}\"", name), [], |row| {
+                    row.get(0)
+                })
+                .unwrap_or(-1);
+            TableInfo { name, rows }
+        })
+        .collect();
+
+    Ok(DbSchemaInfo {
+        db_path: conn.path().unwrap_or_default().to_string(),
+        sqlite_version,
+        current_version: current_version(&conn).map_err(|e| e.to_string())?,
+        latest_version: MIGRATIONS.last().map_or(0, |m| m.version),
+        migrations,
+        unknown_versions,
+        tables,
+    })
+}
+
+/// Revert the agents database schema to `target_version`, e.g. before going back to
+/// an older version of the app. The data of the reverted migrations is dropped, so
+/// a copy of the database is written next to it first.
+#[tauri::command]
+pub async fn rollback_db_schema(
+    db: State<'_, AgentDb>,
+    target_version: u32,
+) -> Result<SchemaRollback, String> {
+    let conn = db.0.get()?;
+    let from_version = current_version(&conn).map_err(|e| e.to_string())?;
+    if target_version >= from_version {
+        return Err(format!("The schema is at version {} already", from_version));
+    }
+
+    let db_path = conn
+        .path()
+        .filter(|p| !p.is_empty())
+        .ok_or("Database has no file")?;
+    let backup_path = Path::new(db_path).with_file_name(format!(
+        "agents-v{}-{}.db",
+        from_version,
+        Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    conn.execute("VACUUM INTO ?1", [backup_path.to_string_lossy()])
+        .map_err(|e| format!("Failed to back up the database: {}", e))?;
+
+    revert(&conn, MIGRATIONS, target_version)?;
+    Ok(SchemaRollback {
+        from_version,
+        to_version: current_version(&conn).map_err(|e| e.to_string())?,
+        backup_path: backup_path.to_string_lossy().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_done_column(conn: &Connection) -> rusqlite::Result<()> {
+        let exists = conn
+            .prepare("SELECT 1 FROM pragma_table_info('notes') WHERE name = 'done'")?
+            .exists([])?;
+        if !exists {
+            conn.execute("ALTER TABLE notes ADD COLUMN done BOOLEAN DEFAULT 0", [])?;
+        }
+        Ok(())
+    }
+
+    const TEST_MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: 1,
+            name: "notes",
+            prepare: None,
+            up: "CREATE TABLE IF NOT EXISTS notes (id INTEGER PRIMARY KEY, body TEXT);",
+            down: None,
+        },
+        Migration {
+            version: 2,
+            name: "note_done",
+            prepare: Some(add_done_column),
+            up: "CREATE INDEX idx_notes_done ON notes(done);",
+            down: Some("DROP INDEX idx_notes_done; ALTER TABLE notes DROP COLUMN done;"),
+        },
+        Migration {
+            version: 3,
+            name: "tags",
+            prepare: None,
+            up: "CREATE TABLE tags (note_id INTEGER, tag TEXT);",
+            down: Some("DROP TABLE tags;"),
+        },
+    ];
+
+    fn has_table(conn: &Connection, name: &str) -> bool {
+        conn.prepare("SELECT 1 FROM sqlite_master WHERE name = ?1")
+            .and_then(|mut stmt| stmt.exists([name]))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_apply_is_idempotent() {
+        let conn = Connection::open_in_memory().unwrap();
+        apply(&conn, TEST_MIGRATIONS).unwrap();
+        apply(&conn, TEST_MIGRATIONS).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 3);
+        let (statuses, unknown) = statuses(&conn, TEST_MIGRATIONS).unwrap();
+        assert!(statuses
+            .iter()
+            .all(|s| s.applied_at.is_some() && !s.modified));
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn test_prepare_adopts_schema_from_before_versioning() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT, done BOOLEAN);")
+            .unwrap();
+        apply(&conn, &TEST_MIGRATIONS[..2]).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 2);
+
+        let (statuses, _) = statuses(&conn, TEST_MIGRATIONS).unwrap();
+        assert!(statuses[2].applied_at.is_none());
+    }
+
+    #[test]
+    fn test_revert_runs_down_scripts_until_irreversible() {
+        let conn = Connection::open_in_memory().unwrap();
+        apply(&conn, TEST_MIGRATIONS).unwrap();
+
+        revert(&conn, TEST_MIGRATIONS, 1).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 1);
+        assert!(!has_table(&conn, "tags"));
+        assert!(!has_table(&conn, "idx_notes_done"));
+
+        let err = revert(&conn, TEST_MIGRATIONS, 0).unwrap_err();
+        assert!(err.contains("cannot be reverted"));
+        assert!(has_table(&conn, "notes"));
+
+        // Reverted migrations are applied again on the next start
+        apply(&conn, TEST_MIGRATIONS).unwrap();
+        assert!(has_table(&conn, "tags"));
+    }
+
+    #[test]
+    fn test_statuses_report_modified_and_unknown_versions() {
+        let conn = Connection::open_in_memory().unwrap();
+        apply(&conn, TEST_MIGRATIONS).unwrap();
+        conn.execute(
+            "UPDATE schema_version SET checksum = 'old' WHERE version = 3",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, name, checksum) VALUES (9, 'x', '')",
+            [],
+        )
+        .unwrap();
+
+        let (statuses, unknown) = statuses(&conn, TEST_MIGRATIONS).unwrap();
+        assert!(statuses[2].modified);
+        assert!(!statuses[1].modified);
+        assert_eq!(unknown, vec![9]);
+    }
+}
@@ -0,0 +1,159 @@
+use rusqlite::{Connection, Result as SqliteResult};
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Number of connections kept for the agents database
+pub const DB_POOL_SIZE: usize = 4;
+
+/// How long a statement waits on a locked database before failing with SQLITE_BUSY
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long `DbPool::get` waits for a free connection
+const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Prepared statements cached per connection (used by `prepare_cached`)
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// Apply the settings every agents.db connection should use.
+///
+/// WAL lets readers proceed while a write is in progress, and the busy timeout
+/// makes concurrent writers wait for each other instead of failing immediately.
+pub fn configure_connection(conn: &Connection) -> SqliteResult<()> {
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    // journal_mode returns the resulting mode as a row
+    let _mode: String = conn.query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+    Ok(())
+}
+
+/// Open a configured connection to a database file
+pub fn open_connection<P: AsRef<Path>>(path: P) -> SqliteResult<Connection> {
+    let conn = Connection::open(path)?;
+    configure_connection(&conn)?;
+    Ok(conn)
+}
+
+struct PoolState {
+    idle: Vec<Connection>,
+    /// Connections currently open, idle or checked out
+    open: usize,
+}
+
+/// Small blocking connection pool for a single SQLite database file
+pub struct DbPool {
+    path: PathBuf,
+    max_size: usize,
+    state: Mutex<PoolState>,
+    available: Condvar,
+}
+
+impl DbPool {
+    /// Create a pool seeded with an already configured connection
+    pub fn new(conn: Connection, max_size: usize) -> Result<Self, String> {
+        let path = conn
+            .path()
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+            .ok_or("Connection pool requires a file-backed database")?;
+
+        Ok(Self {
+            path,
+            max_size: max_size.max(1),
+            state: Mutex::new(PoolState {
+                idle: vec![conn],
+                open: 1,
+            }),
+            available: Condvar::new(),
+        })
+    }
+
+    /// Check out a connection, opening a new one while below the pool size
+    /// and otherwise waiting for one to be returned
+    pub fn get(&self) -> Result<PooledConnection<'_>, String> {
+        crate::commands::fault_injection::on_db_acquire()?;
+        let deadline = Instant::now() + ACQUIRE_TIMEOUT;
+        let mut state = self.state.lock().map_err(|e| e.to_string())?;
+
+        loop {
+            if let Some(conn) = state.idle.pop() {
+                return Ok(PooledConnection { pool: self, conn: Some(conn) });
+            }
+
+            if state.open < self.max_size {
+                state.open += 1;
+                drop(state);
+                return match open_connection(&self.path) {
+                    Ok(conn) => Ok(PooledConnection { pool: self, conn: Some(conn) }),
+                    Err(e) => {
+                        if let Ok(mut state) = self.state.lock() {
+                            state.open -= 1;
+                        }
+                        self.available.notify_one();
+                        Err(e.to_string())
+                    }
+                };
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err("Timed out waiting for a database connection".to_string());
+            }
+            state = self
+                .available
+                .wait_timeout(state, deadline - now)
+                .map_err(|e| e.to_string())?
+                .0;
+        }
+    }
+
+    /// Close all idle connections and seed the pool with `conn`,
+    /// e.g. after the database has been recreated
+    pub fn reset(&self, conn: Connection) -> Result<(), String> {
+        let mut state = self.state.lock().map_err(|e| e.to_string())?;
+        let closed = state.idle.len();
+        state.idle.clear();
+        state.open = state.open - closed + 1;
+        state.idle.push(conn);
+        drop(state);
+        self.available.notify_one();
+        Ok(())
+    }
+
+    fn release(&self, conn: Connection) {
+        if let Ok(mut state) = self.state.lock() {
+            state.idle.push(conn);
+        }
+        self.available.notify_one();
+    }
+}
+
+/// A connection checked out of a `DbPool`, returned to it on drop
+pub struct PooledConnection<'a> {
+    pool: &'a DbPool,
+    conn: Option<Connection>,
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection present until drop")
+    }
+}
+
+impl DerefMut for PooledConnection<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn.as_mut().expect("connection present until drop")
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.release(conn);
+        }
+    }
+}
@@ -8,10 +8,70 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::process::Command;
 use log::{info, warn, error, debug};
-use tauri::{AppHandle, Emitter, State};
+use rusqlite::{params, Connection};
+use tauri::{AppHandle, Manager, State};
+use ts_rs::TS;
+
+use super::events::AppEvent;
+
+/// 审计日志保留的最大条数
+const MAX_HOOK_LOG_ENTRIES: i64 = 5000;
+
+/// `app_settings` key of the kill switch
+const HOOKS_DISABLED_SETTING: &str = "hooks_disabled";
+
+/// 全局hooks禁用开关（kill switch），立即生效
+static HOOKS_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// 同时运行的hook进程数上限（全局）
+const MAX_CONCURRENT_HOOK_PROCESSES: usize = 8;
+
+/// 默认的hook输出上限（stdout/stderr各自计算）
+const DEFAULT_MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// 内置hook动作前缀，如 `builtin:write-handoff`
+const BUILTIN_ACTION_PREFIX: &str = "builtin:";
+
+/// 内置动作：在项目根目录写入HANDOFF.md会话交接文档
+const BUILTIN_WRITE_HANDOFF: &str = "write-handoff";
+
+/// PowerShell语法检查脚本，待检查的命令通过`HOOK_SCRIPT`环境变量传入
+const POWERSHELL_SYNTAX_CHECK: &str = "$errors = $null; \
+    [void][System.Management.Automation.Language.Parser]::ParseInput( \
+        $env:HOOK_SCRIPT, [ref]$null, [ref]$errors); \
+    if ($errors) { $errors | ForEach-Object { [Console]::Error.WriteLine($_.Message) }; exit 1 }";
+
+/// 全局hook进程并发限制
+static HOOK_PROCESS_SLOTS: once_cell::sync::Lazy<Arc<tokio::sync::Semaphore>> =
+    once_cell::sync::Lazy::new(|| Arc::new(tokio::sync::Semaphore::new(MAX_CONCURRENT_HOOK_PROCESSES)));
+
+/// 每个hook命令当前运行中的进程数
+static RUNNING_HOOKS_PER_COMMAND: once_cell::sync::Lazy<Mutex<HashMap<String, usize>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 用于通知正在运行的hook进程立即终止
+static HOOKS_KILL_NOTIFY: once_cell::sync::Lazy<tokio::sync::Notify> =
+    once_cell::sync::Lazy::new(tokio::sync::Notify::new);
+
+/// 全局hooks是否已被禁用
+pub fn hooks_disabled() -> bool {
+    HOOKS_DISABLED.load(Ordering::SeqCst)
+}
+
+/// 启动时从app_settings加载kill switch状态
+pub fn load_hooks_kill_switch(conn: &Connection) {
+    let disabled: bool = super::settings_store::get_or_default(conn, HOOKS_DISABLED_SETTING);
+
+    HOOKS_DISABLED.store(disabled, Ordering::SeqCst);
+    if disabled {
+        warn!("All hooks are disabled by the global kill switch");
+    }
+}
 
 /// 扩展的Hook事件类型
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -65,13 +125,17 @@ pub struct HookContext {
 }
 
 /// Hook执行结果
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct HookExecutionResult {
     pub success: bool,
     pub output: String,
     pub error: Option<String>,
+    #[ts(type = "number")]
     pub execution_time_ms: u64,
     pub hook_command: String,
+    #[serde(default)]
+    pub output_truncated: bool, // 输出是否因超出上限被截断
 }
 
 /// Hook链执行结果
@@ -102,6 +166,245 @@ pub struct EnhancedHook {
     pub condition: Option<ConditionalTrigger>,
     pub on_success: Option<Vec<String>>, // 成功后执行的命令
     pub on_failure: Option<Vec<String>>, // 失败后执行的命令
+    #[serde(default)]
+    pub limits: Option<HookResourceLimits>, // 资源限制
+    #[serde(default)]
+    pub matcher: Option<String>, // OnFileChange的glob过滤（逗号分隔，如 "src/**/*.rs,*.toml"）
+    #[serde(default)]
+    pub shell: Option<HookShell>, // 执行命令的shell，未设置时按系统选择
+}
+
+/// 执行hook命令的shell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookShell {
+    Powershell,
+    Cmd,
+    Bash,
+    Sh,
+}
+
+/// PATH中是否存在该程序
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH").map_or(false, |paths| {
+        std::env::split_paths(&paths).any(|dir| dir.join(program).is_file())
+    })
+}
+
+impl HookShell {
+    /// 系统默认shell：Windows上为PowerShell，其他系统优先bash，没有bash时使用sh
+    pub fn platform_default() -> Self {
+        static DEFAULT: once_cell::sync::Lazy<HookShell> = once_cell::sync::Lazy::new(|| {
+            if cfg!(target_os = "windows") {
+                HookShell::Powershell
+            } else if on_path("bash") {
+                HookShell::Bash
+            } else {
+                HookShell::Sh
+            }
+        });
+        *DEFAULT
+    }
+
+    pub fn resolve(shell: Option<HookShell>) -> Self {
+        shell.unwrap_or_else(Self::platform_default)
+    }
+
+    /// bash和sh支持`ulimit`和`-n`语法检查
+    fn is_posix(self) -> bool {
+        matches!(self, HookShell::Bash | HookShell::Sh)
+    }
+
+    fn program(self) -> &'static str {
+        match self {
+            HookShell::Powershell if cfg!(target_os = "windows") => "powershell",
+            HookShell::Powershell => "pwsh",
+            HookShell::Cmd => "cmd",
+            HookShell::Bash => "bash",
+            HookShell::Sh => "sh",
+        }
+    }
+
+    /// 用该shell执行`script`的命令
+    pub fn command(self, script: &str) -> std::process::Command {
+        let mut cmd = std::process::Command::new(self.program());
+        match self {
+            HookShell::Powershell => {
+                cmd.args(["-NoProfile", "-NonInteractive", "-Command", script]);
+            }
+            HookShell::Cmd => {
+                // cmd不按MSVC规则解析引号，脚本需原样传入
+                #[cfg(target_os = "windows")]
+                {
+                    use std::os::windows::process::CommandExt;
+                    cmd.raw_arg(format!("/S /C \"{}\"", script));
+                }
+                #[cfg(not(target_os = "windows"))]
+                cmd.args(["/C", script]);
+            }
+            HookShell::Bash | HookShell::Sh => {
+                cmd.args(["-c", script]);
+            }
+        }
+        cmd
+    }
+
+    /// 只检查语法、不执行`script`的命令；cmd无法检查语法，返回None
+    pub fn syntax_check(self, script: &str) -> Option<std::process::Command> {
+        match self {
+            HookShell::Bash | HookShell::Sh => {
+                let mut cmd = std::process::Command::new(self.program());
+                cmd.args(["-n", "-c", script]);
+                Some(cmd)
+            }
+            HookShell::Powershell => {
+                let mut cmd = self.command(POWERSHELL_SYNTAX_CHECK);
+                cmd.env("HOOK_SCRIPT", script);
+                Some(cmd)
+            }
+            HookShell::Cmd => None,
+        }
+    }
+}
+
+/// Hook资源限制
+///
+/// 内存/CPU限制在Unix上通过`ulimit`实现，只能用于bash/sh执行的hook；Windows上以及
+/// PowerShell/cmd hook设置了这两项时拒绝执行，而不是不受限制地运行。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HookResourceLimits {
+    pub max_output_bytes: Option<usize>, // stdout/stderr各自的最大字节数，超出部分截断
+    pub max_memory_mb: Option<u64>,      // 最大虚拟内存
+    pub max_cpu_seconds: Option<u64>,    // 最大CPU时间
+    pub max_concurrent: Option<usize>,   // 同一命令最多同时运行的进程数
+}
+
+impl HookResourceLimits {
+    /// 为命令添加ulimit前缀（仅Unix上的bash/sh）；其他平台和shell无法强制内存/CPU限制时返回错误
+    fn wrap_command(&self, command: &str, shell: HookShell) -> Result<String, String> {
+        if cfg!(target_os = "windows") || !shell.is_posix() {
+            if self.max_memory_mb.is_some() || self.max_cpu_seconds.is_some() {
+                return Err(format!(
+                    "Hook memory/CPU limits can only be enforced for bash or sh on Unix, not {:?}",
+                    shell
+                ));
+            }
+            return Ok(command.to_string());
+        }
+
+        let mut prefix = String::new();
+        if let Some(memory_mb) = self.max_memory_mb {
+            prefix.push_str(&format!("ulimit -v {} 2>/dev/null; ", memory_mb * 1024));
+        }
+        if let Some(cpu_seconds) = self.max_cpu_seconds {
+            prefix.push_str(&format!("ulimit -t {} 2>/dev/null; ", cpu_seconds));
+        }
+        Ok(format!("{}{}", prefix, command))
+    }
+}
+
+/// 运行中hook进程的占位，释放时归还并发额度
+struct HookProcessSlot {
+    command: String,
+    _permit: tokio::sync::OwnedSemaphorePermit,
+}
+
+impl HookProcessSlot {
+    /// 获取并发额度，超出全局或单命令上限时直接拒绝，避免hook无限fork
+    fn acquire(command: &str, max_concurrent: Option<usize>) -> Result<Self, String> {
+        let permit = HOOK_PROCESS_SLOTS.clone().try_acquire_owned().map_err(|_| {
+            format!(
+                "Too many concurrent hook processes (limit {})",
+                MAX_CONCURRENT_HOOK_PROCESSES
+            )
+        })?;
+
+        let mut running = RUNNING_HOOKS_PER_COMMAND.lock().map_err(|e| e.to_string())?;
+        let count = running.entry(command.to_string()).or_insert(0);
+        if let Some(max) = max_concurrent {
+            if *count >= max {
+                return Err(format!("Hook concurrency limit reached ({} running)", max));
+            }
+        }
+        *count += 1;
+
+        Ok(Self {
+            command: command.to_string(),
+            _permit: permit,
+        })
+    }
+}
+
+impl Drop for HookProcessSlot {
+    fn drop(&mut self) {
+        if let Ok(mut running) = RUNNING_HOOKS_PER_COMMAND.lock() {
+            if let Some(count) = running.get_mut(&self.command) {
+                *count = count.saturating_sub(1);
+                if *count == 0 {
+                    running.remove(&self.command);
+                }
+            }
+        }
+    }
+}
+
+/// 读取输出，最多保留cap字节，其余部分继续读取并丢弃以免阻塞子进程
+pub(crate) async fn read_capped<R: AsyncRead + Unpin>(reader: Option<R>, cap: usize) -> (Vec<u8>, usize) {
+    let mut reader = match reader {
+        Some(reader) => reader,
+        None => return (Vec::new(), 0),
+    };
+
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let mut total = 0;
+
+    loop {
+        match reader.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                total += n;
+                if buffer.len() < cap {
+                    let take = n.min(cap - buffer.len());
+                    buffer.extend_from_slice(&chunk[..take]);
+                }
+            }
+        }
+    }
+
+    (buffer, total)
+}
+
+/// 受限执行的输出
+struct LimitedOutput {
+    status: std::process::ExitStatus,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    total_bytes: usize,
+    truncated: bool,
+}
+
+/// Hook审计日志条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookExecutionLogEntry {
+    pub id: i64,
+    pub event: String,
+    pub session_id: String,
+    pub project_path: String,
+    pub command: String,
+    pub duration_ms: i64,
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub output_size: i64,
+    pub error: Option<String>,
+    pub executed_at: String,
+}
+
+/// Hooks kill switch状态
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct HooksKillSwitchStatus {
+    pub disabled: bool,
 }
 
 /// Hook执行器
@@ -114,6 +417,59 @@ impl HookExecutor {
         Self { app }
     }
 
+    /// 执行内置hook动作
+    fn execute_builtin_action(
+        &self,
+        action: &str,
+        hook: &EnhancedHook,
+        context: &HookContext,
+        start_time: std::time::Instant,
+    ) -> Result<HookExecutionResult, String> {
+        if hooks_disabled() {
+            debug!("Hooks are globally disabled, skipping: {}", hook.command);
+            return Ok(HookExecutionResult {
+                success: true,
+                output: "Skipped: hooks are globally disabled".to_string(),
+                error: None,
+                execution_time_ms: 0,
+                hook_command: hook.command.clone(),
+                output_truncated: false,
+            });
+        }
+
+        let result = match action {
+            BUILTIN_WRITE_HANDOFF => crate::commands::session_handoff::write_handoff_from_context(context)
+                .map(|path| format!("Wrote {}", path.display())),
+            other => Err(format!("Unknown built-in hook action: {}", other)),
+        };
+
+        let execution_time_ms = start_time.elapsed().as_millis() as u64;
+        match result {
+            Ok(output) => {
+                self.record_execution(context, &hook.command, execution_time_ms as i64, Some(0), output.len() as i64, None);
+                Ok(HookExecutionResult {
+                    success: true,
+                    output,
+                    error: None,
+                    execution_time_ms,
+                    hook_command: hook.command.clone(),
+                    output_truncated: false,
+                })
+            }
+            Err(error) => {
+                self.record_execution(context, &hook.command, execution_time_ms as i64, Some(1), 0, Some(&error));
+                Ok(HookExecutionResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(error),
+                    execution_time_ms,
+                    hook_command: hook.command.clone(),
+                    output_truncated: false,
+                })
+            }
+        }
+    }
+
     /// 执行单个hook
     pub async fn execute_hook(
         &self,
@@ -132,41 +488,133 @@ impl HookExecutor {
                     error: None,
                     execution_time_ms: 0,
                     hook_command: hook.command.clone(),
+                    output_truncated: false,
                 });
             }
         }
 
+        // 内置动作不启动外部进程
+        if let Some(action) = hook.command.strip_prefix(BUILTIN_ACTION_PREFIX) {
+            return self.execute_builtin_action(action.trim(), hook, context, start_time);
+        }
+
         // 准备执行环境
         let context_json = serde_json::to_string(context).map_err(|e| e.to_string())?;
+        let shell = HookShell::resolve(hook.shell);
 
         // 执行命令
         let mut retry_count = 0;
         let max_retries = hook.retry.unwrap_or(0);
 
         loop {
-            let mut cmd = Command::new("bash");
-            cmd.arg("-c")
-                .arg(&hook.command)
-                .stdin(std::process::Stdio::piped())
+            // 全局kill switch：禁用后不再启动任何hook
+            if hooks_disabled() {
+                debug!("Hooks are globally disabled, skipping: {}", hook.command);
+                return Ok(HookExecutionResult {
+                    success: true,
+                    output: "Skipped: hooks are globally disabled".to_string(),
+                    error: None,
+                    execution_time_ms: 0,
+                    hook_command: hook.command.clone(),
+                    output_truncated: false,
+                });
+            }
+
+            let limits = hook.limits.clone().unwrap_or_default();
+            let wrapped = match limits.wrap_command(&hook.command, shell) {
+                Ok(wrapped) => wrapped,
+                Err(error) => {
+                    warn!("Refusing to start hook '{}': {}", hook.command, error);
+                    self.record_execution(context, &hook.command, 0, None, 0, Some(&error));
+                    return Err(error);
+                }
+            };
+            let slot = match HookProcessSlot::acquire(&hook.command, limits.max_concurrent) {
+                Ok(slot) => slot,
+                Err(error) => {
+                    warn!("Refusing to start hook '{}': {}", hook.command, error);
+                    self.record_execution(context, &hook.command, 0, None, 0, Some(&error));
+                    return Err(error);
+                }
+            };
+
+            let mut cmd = Command::from(shell.command(&wrapped));
+            cmd.stdin(std::process::Stdio::null())
                 .stdout(std::process::Stdio::piped())
                 .stderr(std::process::Stdio::piped())
                 .env("HOOK_CONTEXT", &context_json)
                 .env("HOOK_EVENT", &context.event)
                 .env("SESSION_ID", &context.session_id)
-                .env("PROJECT_PATH", &context.project_path);
+                .env("PROJECT_PATH", &context.project_path)
+                .kill_on_drop(true);
 
             // 设置超时
             let timeout_duration = tokio::time::Duration::from_secs(hook.timeout.unwrap_or(30));
 
             // 生成进程并设置超时
-            let child = cmd.spawn().map_err(|e| format!("Failed to spawn hook process: {}", e))?;
+            let attempt_start = std::time::Instant::now();
+            let mut child = cmd.spawn().map_err(|e| format!("Failed to spawn hook process: {}", e))?;
+            let max_output = limits.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES);
+            let stdout = child.stdout.take();
+            let stderr = child.stderr.take();
+
+            let run = async {
+                let (status, (stdout, stdout_total), (stderr, stderr_total)) = tokio::join!(
+                    child.wait(),
+                    read_capped(stdout, max_output),
+                    read_capped(stderr, max_output),
+                );
+                status.map(|status| LimitedOutput {
+                    status,
+                    truncated: stdout_total > stdout.len() || stderr_total > stderr.len(),
+                    total_bytes: stdout_total + stderr_total,
+                    stdout,
+                    stderr,
+                })
+            };
+
+            // kill switch触发或超时时丢弃future，kill_on_drop会终止子进程
+            let wait_result = tokio::select! {
+                result = tokio::time::timeout(timeout_duration, run) => result,
+                _ = HOOKS_KILL_NOTIFY.notified() => {
+                    let error = "Hook killed by global kill switch".to_string();
+                    self.record_execution(context, &hook.command, attempt_start.elapsed().as_millis() as i64, None, 0, Some(&error));
+                    return Err(error);
+                }
+            };
+
+            let result = match wait_result {
+                Ok(Ok(output)) => output,
+                Ok(Err(e)) => {
+                    let error = format!("Hook execution failed: {}", e);
+                    self.record_execution(context, &hook.command, attempt_start.elapsed().as_millis() as i64, None, 0, Some(&error));
+                    return Err(error);
+                }
+                Err(_) => {
+                    let error = "Hook execution timeout".to_string();
+                    self.record_execution(context, &hook.command, attempt_start.elapsed().as_millis() as i64, None, 0, Some(&error));
+                    return Err(error);
+                }
+            };
 
-            let result = tokio::time::timeout(timeout_duration, child.wait_with_output())
-                .await
-                .map_err(|_| "Hook execution timeout".to_string())?
-                .map_err(|e| format!("Hook execution failed: {}", e))?;
+            drop(child);
+            drop(slot);
 
             let execution_time = start_time.elapsed().as_millis() as u64;
+            self.record_execution(
+                context,
+                &hook.command,
+                attempt_start.elapsed().as_millis() as i64,
+                result.status.code(),
+                result.total_bytes as i64,
+                if result.status.success() { None } else { Some("Non-zero exit status") },
+            );
+            if result.truncated {
+                warn!(
+                    "Hook output exceeded {} bytes and was truncated: {}",
+                    max_output, hook.command
+                );
+            }
 
             if result.status.success() {
                 let output = String::from_utf8_lossy(&result.stdout).to_string();
@@ -174,7 +622,7 @@ impl HookExecutor {
                 // 执行成功后的钩子
                 if let Some(on_success_commands) = &hook.on_success {
                     for cmd in on_success_commands {
-                        let _ = self.execute_simple_command(cmd, context).await;
+                        let _ = self.execute_simple_command(cmd, shell, context).await;
                     }
                 }
 
@@ -184,6 +632,7 @@ impl HookExecutor {
                     error: None,
                     execution_time_ms: execution_time,
                     hook_command: hook.command.clone(),
+                    output_truncated: result.truncated,
                 });
             } else {
                 // 失败处理
@@ -199,7 +648,7 @@ impl HookExecutor {
                 // 执行失败后的钩子
                 if let Some(on_failure_commands) = &hook.on_failure {
                     for cmd in on_failure_commands {
-                        let _ = self.execute_simple_command(cmd, context).await;
+                        let _ = self.execute_simple_command(cmd, shell, context).await;
                     }
                 }
 
@@ -209,6 +658,7 @@ impl HookExecutor {
                     error: Some(error_output),
                     execution_time_ms: execution_time,
                     hook_command: hook.command.clone(),
+                    output_truncated: result.truncated,
                 });
             }
         }
@@ -254,13 +704,18 @@ impl HookExecutor {
                         error: Some(e),
                         execution_time_ms: 0,
                         hook_command: hook.command.clone(),
+                        output_truncated: false,
                     });
                 }
             }
         }
 
         // 发送执行结果事件
-        let _ = self.app.emit(&format!("hook-chain-complete:{}", context.session_id), &results);
+        let _ = AppEvent::HookChainComplete {
+            session_id: context.session_id.clone(),
+            results: results.clone(),
+        }
+        .emit(&self.app);
 
         Ok(HookChainResult {
             event: event.as_str().to_string(),
@@ -276,22 +731,95 @@ impl HookExecutor {
     async fn execute_simple_command(
         &self,
         command: &str,
+        shell: HookShell,
         context: &HookContext,
     ) -> Result<(), String> {
-        let mut cmd = Command::new("bash");
-        cmd.arg("-c")
-            .arg(command)
+        if hooks_disabled() {
+            return Ok(());
+        }
+
+        let _slot = HookProcessSlot::acquire(command, None)?;
+
+        let mut cmd = Command::from(shell.command(command));
+        cmd.stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
             .env("SESSION_ID", &context.session_id)
-            .env("PROJECT_PATH", &context.project_path);
+            .env("PROJECT_PATH", &context.project_path)
+            .kill_on_drop(true);
+
+        let start = std::time::Instant::now();
+        let mut child = cmd.spawn()
+            .map_err(|e| format!("Failed to spawn command: {}", e))?;
+
+        let status = tokio::select! {
+            status = child.wait() => status.ok(),
+            _ = HOOKS_KILL_NOTIFY.notified() => None,
+        };
 
-        let _ = cmd.spawn()
-            .map_err(|e| format!("Failed to spawn command: {}", e))?
-            .wait()
-            .await;
+        self.record_execution(
+            context,
+            command,
+            start.elapsed().as_millis() as i64,
+            status.and_then(|s| s.code()),
+            0,
+            match status {
+                Some(s) if s.success() => None,
+                Some(_) => Some("Non-zero exit status"),
+                None => Some("Hook killed by global kill switch"),
+            },
+        );
 
         Ok(())
     }
 
+    /// 写入hook审计日志
+    fn record_execution(
+        &self,
+        context: &HookContext,
+        command: &str,
+        duration_ms: i64,
+        exit_code: Option<i32>,
+        output_size: i64,
+        error: Option<&str>,
+    ) {
+        let db = match self.app.try_state::<crate::commands::agents::AgentDb>() {
+            Some(db) => db,
+            None => return,
+        };
+        let conn = match db.0.get() {
+            Ok(conn) => conn,
+            Err(e) => {
+                warn!("Failed to lock database for hook audit log: {}", e);
+                return;
+            }
+        };
+
+        let insert = conn.execute(
+            "INSERT INTO hook_execution_log (event, session_id, project_path, command, duration_ms, exit_code, success, output_size, error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                context.event,
+                context.session_id,
+                context.project_path,
+                command,
+                duration_ms,
+                exit_code,
+                error.is_none(),
+                output_size,
+                error,
+            ],
+        );
+        if let Err(e) = insert {
+            warn!("Failed to write hook audit log: {}", e);
+            return;
+        }
+
+        let _ = conn.execute(
+            "DELETE FROM hook_execution_log WHERE id <= (SELECT MAX(id) FROM hook_execution_log) - ?1",
+            params![MAX_HOOK_LOG_ENTRIES],
+        );
+    }
+
     /// 评估条件表达式
     fn evaluate_condition(
         &self,
@@ -377,14 +905,94 @@ impl HookManager {
     }
 }
 
+/// 加载项目配置中某个事件的增强型hooks
+pub async fn load_enhanced_hooks(event: &HookEvent, project_path: &str) -> Result<Vec<EnhancedHook>, String> {
+    let hooks_config = crate::commands::claude::get_hooks_config(
+        "project".to_string(),
+        Some(project_path.to_string())
+    ).await?;
+
+    Ok(hooks_config
+        .get(event.as_str())
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| serde_json::from_value::<EnhancedHook>(v.clone()).ok())
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// 从后端触发Hook事件（检查项目信任状态后执行配置的hooks）
+pub async fn fire_hook_event(
+    app: &AppHandle,
+    event: HookEvent,
+    context: HookContext,
+) -> Result<HookChainResult, String> {
+    fire_hook_event_with_shell(app, event, context, None).await
+}
+
+/// 同`fire_hook_event`，未指定shell的hooks使用`default_shell`
+async fn fire_hook_event_with_shell(
+    app: &AppHandle,
+    event: HookEvent,
+    context: HookContext,
+    default_shell: Option<HookShell>,
+) -> Result<HookChainResult, String> {
+    let empty_result = |event: &HookEvent| HookChainResult {
+        event: event.as_str().to_string(),
+        total_hooks: 0,
+        successful: 0,
+        failed: 0,
+        results: vec![],
+        should_continue: true,
+    };
+
+    // 未受信任的项目不执行任何hooks
+    if !crate::commands::workspace_trust::is_project_trusted(app, &context.project_path) {
+        warn!("项目未受信任，跳过hooks执行: {}", context.project_path);
+        return Ok(empty_result(&event));
+    }
+
+    // 通知订阅该事件的插件（后台执行，插件故障不影响hooks）
+    if !hooks_disabled() {
+        crate::commands::plugins::dispatch_hook_event(app, &event, &context);
+    }
+
+    // 从配置中加载hooks
+    let mut hooks = load_enhanced_hooks(&event, &context.project_path).await?;
+    if hooks.is_empty() {
+        debug!("No hooks configured for event: {:?}", event);
+        return Ok(empty_result(&event));
+    }
+    for hook in &mut hooks {
+        hook.shell = hook.shell.or(default_shell);
+    }
+
+    let executor = HookExecutor::new(app.clone());
+    executor.execute_hook_chain(event, context, hooks).await
+}
+
+/// 在后台触发Hook事件，不阻塞调用方
+pub fn spawn_hook_event(app: &AppHandle, event: HookEvent, context: HookContext) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let event_name = event.as_str().to_string();
+        if let Err(e) = fire_hook_event(&app, event, context).await {
+            warn!("Failed to run {} hooks: {}", event_name, e);
+        }
+    });
+}
+
 // ============ Tauri Commands ============
 
-/// 触发Hook事件
+/// 触发Hook事件；`shell`为未单独配置shell的hooks指定shell
 #[tauri::command]
 pub async fn trigger_hook_event(
     app: AppHandle,
     event: String,
     context: HookContext,
+    shell: Option<HookShell>,
 ) -> Result<HookChainResult, String> {
     let event_enum = match event.as_str() {
         "OnContextCompact" => HookEvent::OnContextCompact,
@@ -398,24 +1006,89 @@ pub async fn trigger_hook_event(
         _ => return Err(format!("Unknown hook event: {}", event)),
     };
 
-    // 从配置中加载hooks
-    let hooks_config = crate::commands::claude::get_hooks_config(
-        "project".to_string(),
-        Some(context.project_path.clone())
-    ).await?;
+    fire_hook_event_with_shell(&app, event_enum, context, shell).await
+}
 
-    let hooks_array = hooks_config
-        .get(&event)
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| serde_json::from_value::<EnhancedHook>(v.clone()).ok())
-                .collect()
+/// 查询hook审计日志
+#[tauri::command]
+pub async fn get_hook_execution_log(
+    db: State<'_, crate::commands::agents::AgentDb>,
+    limit: Option<i64>,
+    event: Option<String>,
+    session_id: Option<String>,
+) -> Result<Vec<HookExecutionLogEntry>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let limit = limit.unwrap_or(200).clamp(1, MAX_HOOK_LOG_ENTRIES);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, event, session_id, project_path, command, duration_ms, exit_code, success, output_size, error, executed_at
+             FROM hook_execution_log
+             WHERE (?1 IS NULL OR event = ?1) AND (?2 IS NULL OR session_id = ?2)
+             ORDER BY id DESC LIMIT ?3",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let entries = stmt
+        .query_map(params![event, session_id, limit], |row| {
+            Ok(HookExecutionLogEntry {
+                id: row.get(0)?,
+                event: row.get(1)?,
+                session_id: row.get(2)?,
+                project_path: row.get(3)?,
+                command: row.get(4)?,
+                duration_ms: row.get(5)?,
+                exit_code: row.get(6)?,
+                success: row.get(7)?,
+                output_size: row.get(8)?,
+                error: row.get(9)?,
+                executed_at: row.get(10)?,
+            })
         })
-        .unwrap_or_default();
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
 
-    let executor = HookExecutor::new(app);
-    executor.execute_hook_chain(event_enum, context, hooks_array).await
+    Ok(entries)
+}
+
+/// 清空hook审计日志
+#[tauri::command]
+pub async fn clear_hook_execution_log(
+    db: State<'_, crate::commands::agents::AgentDb>,
+) -> Result<usize, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM hook_execution_log", [])
+        .map_err(|e| e.to_string())
+}
+
+/// 全局禁用/启用所有hooks（kill switch）
+///
+/// 禁用时立即终止所有正在运行的hook进程，并持久化到app_settings。
+#[tauri::command]
+pub async fn set_hooks_disabled(app: AppHandle, disabled: bool) -> Result<HooksKillSwitchStatus, String> {
+    HOOKS_DISABLED.store(disabled, Ordering::SeqCst);
+    if disabled {
+        HOOKS_KILL_NOTIFY.notify_waiters();
+        warn!("🛑 All hooks disabled by kill switch");
+    } else {
+        info!("Hooks re-enabled");
+    }
+
+    super::settings_store::set(&app, HOOKS_DISABLED_SETTING, &disabled)
+        .map_err(|e| format!("Failed to persist hooks kill switch: {}", e))?;
+
+    let status = HooksKillSwitchStatus { disabled };
+    let _ = AppEvent::HooksKillSwitch(status.clone()).emit(&app);
+    Ok(status)
+}
+
+/// 获取hooks kill switch状态
+#[tauri::command]
+pub async fn get_hooks_kill_switch() -> Result<HooksKillSwitchStatus, String> {
+    Ok(HooksKillSwitchStatus {
+        disabled: hooks_disabled(),
+    })
 }
 
 /// 测试Hook条件
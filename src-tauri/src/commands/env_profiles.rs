@@ -0,0 +1,95 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Manager, State};
+
+use super::agents::AgentDb;
+use super::settings_store::{self, ENV_PROFILES};
+
+/// Named environment variables a single session or agent run can be started with,
+/// e.g. `ANTHROPIC_BASE_URL` pointing at a staging API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub vars: BTreeMap<String, String>,
+    pub updated_at: String,
+}
+
+/// Profiles by name, as stored in `app_settings`
+type EnvProfiles = BTreeMap<String, EnvProfile>;
+
+pub(crate) fn check_var_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = chars.next().map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid environment variable name: {:?}", name))
+    }
+}
+
+/// The variables of the profile `name`, to be set on the spawned process
+pub fn resolve_env_profile(
+    app: &AppHandle,
+    name: &str,
+) -> Result<BTreeMap<String, String>, String> {
+    let db = app.state::<AgentDb>();
+    let conn = db.0.get()?;
+    let mut profiles: EnvProfiles = settings_store::get_or_default(&conn, ENV_PROFILES);
+    profiles
+        .remove(name)
+        .map(|profile| profile.vars)
+        .ok_or_else(|| format!("Environment profile {} not found", name))
+}
+
+/// List the environment profiles, by name
+#[tauri::command]
+pub async fn list_env_profiles(db: State<'_, AgentDb>) -> Result<Vec<EnvProfile>, String> {
+    let conn = db.0.get()?;
+    let profiles: EnvProfiles = settings_store::get_or_default(&conn, ENV_PROFILES);
+    Ok(profiles.into_values().collect())
+}
+
+/// Create an environment profile, or replace the one with the same name
+#[tauri::command]
+pub async fn save_env_profile(
+    app: AppHandle,
+    name: String,
+    description: Option<String>,
+    vars: BTreeMap<String, String>,
+) -> Result<EnvProfile, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Environment profile name cannot be empty".to_string());
+    }
+    for key in vars.keys() {
+        check_var_name(key)?;
+    }
+    let profile = EnvProfile {
+        name: name.clone(),
+        description: description.filter(|d| !d.trim().is_empty()),
+        vars,
+        updated_at: Utc::now().to_rfc3339(),
+    };
+    let saved = profile.clone();
+    settings_store::update(&app, ENV_PROFILES, |profiles: &mut EnvProfiles| {
+        profiles.insert(name, saved);
+        Ok(true)
+    })?;
+    Ok(profile)
+}
+
+/// Delete an environment profile
+#[tauri::command]
+pub async fn delete_env_profile(app: AppHandle, name: String) -> Result<(), String> {
+    settings_store::update(&app, ENV_PROFILES, |profiles: &mut EnvProfiles| {
+        profiles
+            .remove(&name)
+            .map(|_| true)
+            .ok_or_else(|| format!("Environment profile {} not found", name))
+    })?;
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::events::EventKind;
+
+/// Event patterns each window listens to, by window label.
+///
+/// Windows without an entry receive every event, so nothing changes until a window
+/// subscribes. An entry with no patterns receives nothing.
+static SUBSCRIPTIONS: Lazy<RwLock<HashMap<String, Vec<String>>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Whether `pattern` matches the event `name`.
+///
+/// `*` matches everything, a trailing `*` matches by prefix (`agent-*`), and a plain
+/// event name matches the event and all of its scoped forms (`claude-output` matches
+/// `claude-output:<session_id>`).
+fn matches(pattern: &str, name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => name == pattern || name.strip_prefix(pattern).map_or(false, |rest| rest.starts_with(':')),
+    }
+}
+
+/// Reject patterns that cannot match any backend event
+fn validate_pattern(pattern: &str) -> Result<(), String> {
+    let base = pattern.split(':').next().unwrap_or_default();
+    let known = match base.strip_suffix('*') {
+        Some(prefix) => EventKind::ALL.iter().any(|kind| kind.as_str().starts_with(prefix)),
+        None => EventKind::ALL.iter().any(|kind| kind.as_str() == base),
+    };
+    if known {
+        Ok(())
+    } else {
+        Err(format!("Event pattern '{}' matches no backend event", pattern))
+    }
+}
+
+/// Whether any open window listens to `event`
+pub fn is_wanted(app: &AppHandle, event: &str) -> bool {
+    let Ok(subscriptions) = SUBSCRIPTIONS.read() else {
+        return true;
+    };
+    if subscriptions.is_empty() {
+        return true;
+    }
+    app.webview_windows().keys().any(|label| {
+        subscriptions
+            .get(label)
+            .map_or(true, |patterns| patterns.iter().any(|pattern| matches(pattern, event)))
+    })
+}
+
+/// Emit an event unless no window is subscribed to it
+pub fn emit<S: Serialize + Clone>(app: &AppHandle, event: &str, payload: S) -> tauri::Result<()> {
+    if !is_wanted(app, event) {
+        return Ok(());
+    }
+    app.emit(event, payload)
+}
+
+/// Subscribe a window to event patterns; once subscribed, the backend skips events
+/// that match no pattern of any open window. Returns the window's patterns.
+#[tauri::command]
+pub async fn subscribe_events(window_label: String, patterns: Vec<String>) -> Result<Vec<String>, String> {
+    for pattern in &patterns {
+        validate_pattern(pattern)?;
+    }
+    let mut subscriptions = SUBSCRIPTIONS.write().map_err(|e| e.to_string())?;
+    let current = subscriptions.entry(window_label).or_default();
+    for pattern in patterns {
+        if !current.contains(&pattern) {
+            current.push(pattern);
+        }
+    }
+    Ok(current.clone())
+}
+
+/// Remove event patterns from a window. Without `patterns` the window's subscription
+/// is dropped entirely and it receives every event again. Returns the remaining patterns.
+#[tauri::command]
+pub async fn unsubscribe_events(
+    window_label: String,
+    patterns: Option<Vec<String>>,
+) -> Result<Vec<String>, String> {
+    let mut subscriptions = SUBSCRIPTIONS.write().map_err(|e| e.to_string())?;
+    let Some(patterns) = patterns else {
+        subscriptions.remove(&window_label);
+        return Ok(Vec::new());
+    };
+    Ok(match subscriptions.get_mut(&window_label) {
+        Some(current) => {
+            current.retain(|pattern| !patterns.contains(pattern));
+            current.clone()
+        }
+        None => Vec::new(),
+    })
+}
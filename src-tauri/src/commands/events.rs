@@ -0,0 +1,519 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use tauri::AppHandle;
+use ts_rs::TS;
+
+use super::accessibility::AccessibilityAnnouncement;
+use super::agent_delegation::AgentDelegation;
+use super::agent_downloads::AgentDownloadProgress;
+use super::backup::BackupProgress;
+use super::burn_rate::BurnRate;
+use super::cost_cap::CostCapReached;
+use super::event_subscriptions::emit;
+use super::file_edits::FileEdit;
+use super::file_watcher::FilesChanged;
+use super::enhanced_hooks::{HookExecutionResult, HooksKillSwitchStatus};
+use super::power::PausedRun;
+use super::project_scan::ProjectScanProgress;
+use super::prompt_queue::QueuedPromptStarted;
+use super::scheduled_prompts::ScheduledPrompt;
+use super::settings_store::SettingChanged;
+use super::spawn_diagnostics::SpawnFailure;
+use super::unseen_completions::UnseenCompletionsChanged;
+use super::weekly_digest::WeeklyDigest;
+use crate::checkpoint::jobs::CheckpointProgress;
+use crate::checkpoint::safety::SafetyCheckpoint;
+
+/// Bumped whenever an event payload changes shape
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// Every event the backend emits to the frontend.
+///
+/// Event names are the kebab-case variant names; scoped events append `:<scope>`
+/// (session ID or run ID), e.g. `claude-output:<session_id>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export)]
+pub enum EventKind {
+    ClaudeOutput,
+    ClaudeOutputBatch,
+    ClaudeError,
+    ClaudeComplete,
+    ClaudeCancelled,
+    ClaudeSessionState,
+    AgentOutput,
+    AgentOutputBatch,
+    AgentError,
+    AgentComplete,
+    AgentCancelled,
+    AgentDelegation,
+    SessionOutputUpdate,
+    HookChainComplete,
+    HooksKillSwitch,
+    WeeklyDigestReady,
+    ProjectsScanProgress,
+    AgentDownloadProgress,
+    CheckpointProgress,
+    QueuedPromptStarted,
+    SafetyCheckpointCreated,
+    ClaudeSpawnFailed,
+    BackupProgress,
+    BurnRate,
+    Accessibility,
+    PowerRunPaused,
+    FileEdited,
+    CostCapReached,
+    ScheduledPromptStarted,
+    ScheduledPromptFinished,
+    UnseenCompletions,
+    SettingChanged,
+    FilesChanged,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 33] = [
+        EventKind::ClaudeOutput,
+        EventKind::ClaudeOutputBatch,
+        EventKind::ClaudeError,
+        EventKind::ClaudeComplete,
+        EventKind::ClaudeCancelled,
+        EventKind::ClaudeSessionState,
+        EventKind::AgentOutput,
+        EventKind::AgentOutputBatch,
+        EventKind::AgentError,
+        EventKind::AgentComplete,
+        EventKind::AgentCancelled,
+        EventKind::AgentDelegation,
+        EventKind::SessionOutputUpdate,
+        EventKind::HookChainComplete,
+        EventKind::HooksKillSwitch,
+        EventKind::WeeklyDigestReady,
+        EventKind::ProjectsScanProgress,
+        EventKind::AgentDownloadProgress,
+        EventKind::CheckpointProgress,
+        EventKind::QueuedPromptStarted,
+        EventKind::SafetyCheckpointCreated,
+        EventKind::ClaudeSpawnFailed,
+        EventKind::BackupProgress,
+        EventKind::BurnRate,
+        EventKind::Accessibility,
+        EventKind::PowerRunPaused,
+        EventKind::FileEdited,
+        EventKind::CostCapReached,
+        EventKind::ScheduledPromptStarted,
+        EventKind::ScheduledPromptFinished,
+        EventKind::UnseenCompletions,
+        EventKind::SettingChanged,
+        EventKind::FilesChanged,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventKind::ClaudeOutput => "claude-output",
+            EventKind::ClaudeOutputBatch => "claude-output-batch",
+            EventKind::ClaudeError => "claude-error",
+            EventKind::ClaudeComplete => "claude-complete",
+            EventKind::ClaudeCancelled => "claude-cancelled",
+            EventKind::ClaudeSessionState => "claude-session-state",
+            EventKind::AgentOutput => "agent-output",
+            EventKind::AgentOutputBatch => "agent-output-batch",
+            EventKind::AgentError => "agent-error",
+            EventKind::AgentComplete => "agent-complete",
+            EventKind::AgentCancelled => "agent-cancelled",
+            EventKind::AgentDelegation => "agent-delegation",
+            EventKind::SessionOutputUpdate => "session-output-update",
+            EventKind::HookChainComplete => "hook-chain-complete",
+            EventKind::HooksKillSwitch => "hooks-kill-switch",
+            EventKind::WeeklyDigestReady => "weekly-digest-ready",
+            EventKind::ProjectsScanProgress => "projects-scan-progress",
+            EventKind::AgentDownloadProgress => "agent-download-progress",
+            EventKind::CheckpointProgress => "checkpoint-progress",
+            EventKind::QueuedPromptStarted => "queued-prompt-started",
+            EventKind::SafetyCheckpointCreated => "safety-checkpoint-created",
+            EventKind::ClaudeSpawnFailed => "claude-spawn-failed",
+            EventKind::BackupProgress => "backup-progress",
+            EventKind::BurnRate => "burn-rate",
+            EventKind::Accessibility => "accessibility",
+            EventKind::PowerRunPaused => "power-run-paused",
+            EventKind::FileEdited => "file-edited",
+            EventKind::CostCapReached => "cost-cap-reached",
+            EventKind::ScheduledPromptStarted => "scheduled-prompt-started",
+            EventKind::ScheduledPromptFinished => "scheduled-prompt-finished",
+            EventKind::UnseenCompletions => "unseen-completions",
+            EventKind::SettingChanged => "setting-changed",
+            EventKind::FilesChanged => "files-changed",
+        }
+    }
+
+    /// Event name scoped to a session or run, e.g. `agent-output:42`
+    pub fn scoped(self, scope: impl Display) -> String {
+        format!("{}:{}", self.as_str(), scope)
+    }
+
+    fn descriptor(self) -> EventDescriptor {
+        let (scope, payload, description) = match self {
+            EventKind::ClaudeOutput => (
+                Some("session_id"),
+                "string",
+                "One JSONL line of Claude CLI stdout (raw output mode)",
+            ),
+            EventKind::ClaudeOutputBatch => (
+                Some("session_id"),
+                "OutputBatch",
+                "Coalesced Claude CLI stdout lines, in order",
+            ),
+            EventKind::ClaudeError => (Some("session_id"), "string", "One line of Claude CLI stderr"),
+            EventKind::ClaudeComplete => (
+                Some("session_id"),
+                "boolean",
+                "Claude process exited; payload is whether it succeeded",
+            ),
+            EventKind::ClaudeCancelled => (Some("session_id"), "boolean", "Claude execution was cancelled"),
+            EventKind::ClaudeSessionState => (
+                None,
+                "SessionStateEvent",
+                "A Claude session started or stopped",
+            ),
+            EventKind::AgentOutput => (
+                Some("run_id"),
+                "string",
+                "One JSONL line of agent run stdout (raw output mode)",
+            ),
+            EventKind::AgentOutputBatch => (
+                Some("run_id"),
+                "OutputBatch",
+                "Coalesced agent run stdout lines, in order",
+            ),
+            EventKind::AgentError => (Some("run_id"), "string", "One line of agent run stderr"),
+            EventKind::AgentComplete => (
+                Some("run_id"),
+                "boolean",
+                "Agent run finished; payload is whether it succeeded",
+            ),
+            EventKind::AgentCancelled => (Some("run_id"), "boolean", "Agent run was cancelled"),
+            EventKind::AgentDelegation => (
+                None,
+                "AgentDelegation",
+                "A delegated sub-run changed status",
+            ),
+            EventKind::SessionOutputUpdate => (
+                None,
+                "string",
+                "Session file content for a streamed run, formatted `<run_id>:<content>`",
+            ),
+            EventKind::HookChainComplete => (
+                Some("session_id"),
+                "Array<HookExecutionResult>",
+                "A hook chain finished executing",
+            ),
+            EventKind::HooksKillSwitch => (
+                None,
+                "HooksKillSwitchStatus",
+                "The global hooks kill switch was toggled",
+            ),
+            EventKind::WeeklyDigestReady => (None, "WeeklyDigest", "A weekly digest was generated"),
+            EventKind::ProjectsScanProgress => (
+                None,
+                "ProjectScanProgress",
+                "Partial or final results of a background project scan",
+            ),
+            EventKind::AgentDownloadProgress => (
+                None,
+                "AgentDownloadProgress",
+                "Progress of a GitHub agent download",
+            ),
+            EventKind::CheckpointProgress => (
+                Some("session_id"),
+                "CheckpointProgress",
+                "Progress of a checkpoint being created",
+            ),
+            EventKind::QueuedPromptStarted => (
+                Some("session_id"),
+                "QueuedPromptStarted",
+                "A queued follow-up prompt was dispatched after the previous run completed",
+            ),
+            EventKind::SafetyCheckpointCreated => (
+                Some("session_id"),
+                "SafetyCheckpoint",
+                "Files were snapshotted before a destructive tool call ran",
+            ),
+            EventKind::ClaudeSpawnFailed => (
+                None,
+                "SpawnFailure",
+                "The Claude CLI failed to start; payload explains why and how to recover",
+            ),
+            EventKind::BackupProgress => (
+                None,
+                "BackupProgress",
+                "Progress of a full backup or restore",
+            ),
+            EventKind::BurnRate => (
+                Some("session_id"),
+                "BurnRate",
+                "Tokens and estimated cost per minute of a running session, every few seconds",
+            ),
+            EventKind::Accessibility => (
+                None,
+                "AccessibilityAnnouncement",
+                "Short description of a run starting, editing files or finishing, for screen readers",
+            ),
+            EventKind::PowerRunPaused => (
+                None,
+                "PausedRun",
+                "A session was checkpointed and stopped because the machine switched to battery",
+            ),
+            EventKind::FileEdited => (
+                Some("session_id"),
+                "FileEdit",
+                "A tool call of a running session is editing a file; line counts are estimates",
+            ),
+            EventKind::CostCapReached => (
+                None,
+                "CostCapReached",
+                "A session or agent run was checkpointed and stopped for exceeding its cost cap",
+            ),
+            EventKind::ScheduledPromptStarted => (
+                None,
+                "ScheduledPrompt",
+                "A scheduled one-off prompt came due and its run started",
+            ),
+            EventKind::ScheduledPromptFinished => (
+                None,
+                "ScheduledPrompt",
+                "A scheduled prompt's run ended, or the prompt was skipped or failed to start",
+            ),
+            EventKind::UnseenCompletions => (
+                None,
+                "UnseenCompletionsChanged",
+                "A run finished unnoticed, or an unseen completion was marked seen",
+            ),
+            EventKind::SettingChanged => (
+                None,
+                "SettingChanged",
+                "A value in the settings store (hidden projects, execution config, ...) was written",
+            ),
+            EventKind::FilesChanged => (
+                None,
+                "FilesChanged",
+                "A debounced batch of file changes in a watched project",
+            ),
+        };
+
+        EventDescriptor {
+            kind: self,
+            name: self.as_str().to_string(),
+            scope: scope.map(str::to_string),
+            payload: payload.to_string(),
+            version: EVENT_SCHEMA_VERSION,
+            description: description.to_string(),
+        }
+    }
+}
+
+/// Lifecycle status reported by `claude-session-state`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum SessionStatus {
+    Started,
+    Stopped,
+    /// The process was suspended with `pause_execution`
+    Paused,
+    /// A paused process continued
+    Resumed,
+}
+
+/// Payload of `claude-session-state`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SessionStateEvent {
+    pub session_id: String,
+    pub status: SessionStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub project_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub run_id: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub success: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+}
+
+/// Catalog entry describing one event
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct EventDescriptor {
+    pub kind: EventKind,
+    pub name: String,
+    /// What the `:<scope>` suffix holds, if the event is also emitted scoped
+    pub scope: Option<String>,
+    /// TypeScript type of the payload
+    pub payload: String,
+    pub version: u32,
+    pub description: String,
+}
+
+/// A typed backend event
+#[derive(Debug, Clone)]
+pub enum AppEvent {
+    ClaudeError { session_id: Option<String>, line: String },
+    ClaudeComplete { session_id: Option<String>, success: bool },
+    ClaudeCancelled { session_id: Option<String> },
+    ClaudeSessionState(SessionStateEvent),
+    AgentError { run_id: i64, line: String },
+    AgentComplete { run_id: i64, success: bool },
+    AgentCancelled { run_id: i64 },
+    AgentDelegation(AgentDelegation),
+    SessionOutputUpdate { run_id: i64, content: String },
+    HookChainComplete { session_id: String, results: Vec<HookExecutionResult> },
+    HooksKillSwitch(HooksKillSwitchStatus),
+    WeeklyDigestReady(WeeklyDigest),
+    ProjectsScanProgress(ProjectScanProgress),
+    AgentDownloadProgress(AgentDownloadProgress),
+    CheckpointProgress(CheckpointProgress),
+    QueuedPromptStarted(QueuedPromptStarted),
+    SafetyCheckpointCreated(SafetyCheckpoint),
+    ClaudeSpawnFailed(SpawnFailure),
+    BackupProgress(BackupProgress),
+    BurnRate(BurnRate),
+    Accessibility(AccessibilityAnnouncement),
+    PowerRunPaused(PausedRun),
+    FileEdited(FileEdit),
+    CostCapReached(CostCapReached),
+    ScheduledPromptStarted(ScheduledPrompt),
+    ScheduledPromptFinished(ScheduledPrompt),
+    UnseenCompletions(UnseenCompletionsChanged),
+    SettingChanged(SettingChanged),
+    FilesChanged(FilesChanged),
+}
+
+impl AppEvent {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            AppEvent::ClaudeError { .. } => EventKind::ClaudeError,
+            AppEvent::ClaudeComplete { .. } => EventKind::ClaudeComplete,
+            AppEvent::ClaudeCancelled { .. } => EventKind::ClaudeCancelled,
+            AppEvent::ClaudeSessionState(_) => EventKind::ClaudeSessionState,
+            AppEvent::AgentError { .. } => EventKind::AgentError,
+            AppEvent::AgentComplete { .. } => EventKind::AgentComplete,
+            AppEvent::AgentCancelled { .. } => EventKind::AgentCancelled,
+            AppEvent::AgentDelegation(_) => EventKind::AgentDelegation,
+            AppEvent::SessionOutputUpdate { .. } => EventKind::SessionOutputUpdate,
+            AppEvent::HookChainComplete { .. } => EventKind::HookChainComplete,
+            AppEvent::HooksKillSwitch(_) => EventKind::HooksKillSwitch,
+            AppEvent::WeeklyDigestReady(_) => EventKind::WeeklyDigestReady,
+            AppEvent::ProjectsScanProgress(_) => EventKind::ProjectsScanProgress,
+            AppEvent::AgentDownloadProgress(_) => EventKind::AgentDownloadProgress,
+            AppEvent::CheckpointProgress(_) => EventKind::CheckpointProgress,
+            AppEvent::QueuedPromptStarted(_) => EventKind::QueuedPromptStarted,
+            AppEvent::SafetyCheckpointCreated(_) => EventKind::SafetyCheckpointCreated,
+            AppEvent::ClaudeSpawnFailed(_) => EventKind::ClaudeSpawnFailed,
+            AppEvent::BackupProgress(_) => EventKind::BackupProgress,
+            AppEvent::BurnRate(_) => EventKind::BurnRate,
+            AppEvent::Accessibility(_) => EventKind::Accessibility,
+            AppEvent::PowerRunPaused(_) => EventKind::PowerRunPaused,
+            AppEvent::FileEdited(_) => EventKind::FileEdited,
+            AppEvent::CostCapReached(_) => EventKind::CostCapReached,
+            AppEvent::ScheduledPromptStarted(_) => EventKind::ScheduledPromptStarted,
+            AppEvent::ScheduledPromptFinished(_) => EventKind::ScheduledPromptFinished,
+            AppEvent::UnseenCompletions(_) => EventKind::UnseenCompletions,
+            AppEvent::SettingChanged(_) => EventKind::SettingChanged,
+            AppEvent::FilesChanged(_) => EventKind::FilesChanged,
+        }
+    }
+
+    fn scope(&self) -> Option<String> {
+        match self {
+            AppEvent::ClaudeError { session_id, .. }
+            | AppEvent::ClaudeComplete { session_id, .. }
+            | AppEvent::ClaudeCancelled { session_id } => session_id.clone(),
+            AppEvent::AgentError { run_id, .. }
+            | AppEvent::AgentComplete { run_id, .. }
+            | AppEvent::AgentCancelled { run_id } => Some(run_id.to_string()),
+            AppEvent::HookChainComplete { session_id, .. } => Some(session_id.clone()),
+            AppEvent::CheckpointProgress(progress) => Some(progress.session_id.clone()),
+            AppEvent::QueuedPromptStarted(started) => Some(started.session_id.clone()),
+            AppEvent::SafetyCheckpointCreated(checkpoint) => Some(checkpoint.session_id.clone()),
+            AppEvent::BurnRate(rate) => Some(rate.session_id.clone()),
+            AppEvent::FileEdited(edit) => Some(edit.session_id.clone()),
+            _ => None,
+        }
+    }
+
+    /// Full event name, including the scope suffix when the event has one
+    pub fn name(&self) -> String {
+        match self.scope() {
+            Some(scope) => self.kind().scoped(scope),
+            None => self.kind().as_str().to_string(),
+        }
+    }
+
+    fn emit_as(&self, app: &AppHandle, name: &str) -> tauri::Result<()> {
+        match self {
+            AppEvent::ClaudeError { line, .. } | AppEvent::AgentError { line, .. } => emit(app, name, line),
+            AppEvent::ClaudeComplete { success, .. } | AppEvent::AgentComplete { success, .. } => {
+                emit(app, name, *success)
+            }
+            AppEvent::ClaudeCancelled { .. } | AppEvent::AgentCancelled { .. } => emit(app, name, true),
+            AppEvent::ClaudeSessionState(payload) => emit(app, name, payload),
+            AppEvent::AgentDelegation(payload) => emit(app, name, payload),
+            AppEvent::SessionOutputUpdate { run_id, content } => {
+                emit(app, name, format!("{}:{}", run_id, content))
+            }
+            AppEvent::HookChainComplete { results, .. } => emit(app, name, results),
+            AppEvent::HooksKillSwitch(payload) => emit(app, name, payload),
+            AppEvent::WeeklyDigestReady(payload) => emit(app, name, payload),
+            AppEvent::ProjectsScanProgress(payload) => emit(app, name, payload),
+            AppEvent::AgentDownloadProgress(payload) => emit(app, name, payload),
+            AppEvent::CheckpointProgress(payload) => emit(app, name, payload),
+            AppEvent::QueuedPromptStarted(payload) => emit(app, name, payload),
+            AppEvent::SafetyCheckpointCreated(payload) => emit(app, name, payload),
+            AppEvent::ClaudeSpawnFailed(payload) => emit(app, name, payload),
+            AppEvent::BackupProgress(payload) => emit(app, name, payload),
+            AppEvent::BurnRate(payload) => emit(app, name, payload),
+            AppEvent::Accessibility(payload) => emit(app, name, payload),
+            AppEvent::PowerRunPaused(payload) => emit(app, name, payload),
+            AppEvent::FileEdited(payload) => emit(app, name, payload),
+            AppEvent::CostCapReached(payload) => emit(app, name, payload),
+            AppEvent::ScheduledPromptStarted(payload) => emit(app, name, payload),
+            AppEvent::ScheduledPromptFinished(payload) => emit(app, name, payload),
+            AppEvent::UnseenCompletions(payload) => emit(app, name, payload),
+            AppEvent::SettingChanged(payload) => emit(app, name, payload),
+            AppEvent::FilesChanged(payload) => emit(app, name, payload),
+        }
+    }
+
+    /// Emit the event under its (scoped) name
+    pub fn emit(&self, app: &AppHandle) -> tauri::Result<()> {
+        self.emit_as(app, &self.name())
+    }
+
+    /// Emit the event under its unscoped name, which older listeners still use
+    pub fn emit_global(&self, app: &AppHandle) -> tauri::Result<()> {
+        self.emit_as(app, self.kind().as_str())
+    }
+
+    /// Emit the scoped event and, for backward compatibility, the unscoped one as well
+    pub fn emit_with_global(&self, app: &AppHandle) -> tauri::Result<()> {
+        if self.scope().is_some() {
+            self.emit(app)?;
+        }
+        self.emit_global(app)
+    }
+}
+
+/// List every backend event with its payload type and schema version
+#[tauri::command]
+pub async fn get_event_catalog() -> Result<Vec<EventDescriptor>, String> {
+    Ok(EventKind::ALL.iter().map(|kind| kind.descriptor()).collect())
+}
@@ -0,0 +1,363 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tauri::{AppHandle, State};
+
+use super::agents::AgentDb;
+use super::run_dedupe::RunHandle;
+use super::session_templates::{permission_preset, TEMPLATE_PERMISSION_PRESETS};
+use super::usage::estimate_message_cost;
+
+/// When a finished run is reported to the profile's webhook
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileNotify {
+    Always,
+    OnFailure,
+    #[default]
+    Never,
+}
+
+/// Everything a profile applies to a run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExecutionProfileConfig {
+    /// `development`, `safe` or `interactive`; None keeps the configured permissions
+    pub permission_preset: Option<String>,
+    /// Passed to `--max-turns`
+    pub max_turns: Option<u32>,
+    /// The run is stopped once its estimated cost exceeds this many USD
+    pub max_budget_usd: Option<f64>,
+    #[serde(default)]
+    pub notify: ProfileNotify,
+    /// Receives `{"text", "session_id", "profile", "success", "project_path"}` as JSON
+    pub webhook_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionProfile {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub config: ExecutionProfileConfig,
+    /// Shipped with the app; can be edited but not deleted
+    pub builtin: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A run to start with a profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfiledRun {
+    pub project_path: String,
+    pub prompt: String,
+    pub model: String,
+    pub provider_id: Option<String>,
+}
+
+/// Profiles created on first start
+fn builtin_profiles() -> Vec<(&'static str, &'static str, ExecutionProfileConfig)> {
+    vec![
+        (
+            "local-dev",
+            "Interactive work on your machine: edits allowed, no limits, notify on failure",
+            ExecutionProfileConfig {
+                permission_preset: Some("development".to_string()),
+                notify: ProfileNotify::OnFailure,
+                ..Default::default()
+            },
+        ),
+        (
+            "ci",
+            "Unattended runs: edits allowed, bounded turns and spend, no notifications",
+            ExecutionProfileConfig {
+                permission_preset: Some("development".to_string()),
+                max_turns: Some(30),
+                max_budget_usd: Some(5.0),
+                ..Default::default()
+            },
+        ),
+        (
+            "demo",
+            "Read-only, short and cheap runs for demonstrations",
+            ExecutionProfileConfig {
+                permission_preset: Some("safe".to_string()),
+                max_turns: Some(10),
+                max_budget_usd: Some(1.0),
+                notify: ProfileNotify::Always,
+                ..Default::default()
+            },
+        ),
+    ]
+}
+
+/// Insert the built-in profiles that don't exist yet; edited ones are kept
+pub fn seed_builtin_profiles(conn: &Connection) -> rusqlite::Result<()> {
+    for (name, description, config) in builtin_profiles() {
+        let config = serde_json::to_string(&config).unwrap_or_else(|_| "{}".to_string());
+        conn.execute(
+            "INSERT OR IGNORE INTO execution_profiles (name, description, config, builtin) VALUES (?1, ?2, ?3, 1)",
+            params![name, description, config],
+        )?;
+    }
+    Ok(())
+}
+
+fn row_to_profile(row: &rusqlite::Row) -> rusqlite::Result<ExecutionProfile> {
+    let config: String = row.get(3)?;
+    Ok(ExecutionProfile {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        config: serde_json::from_str(&config).unwrap_or_default(),
+        builtin: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+const PROFILE_COLUMNS: &str = "id, name, description, config, builtin, created_at, updated_at";
+
+pub fn load_profile(conn: &Connection, name: &str) -> Result<ExecutionProfile, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM execution_profiles WHERE name = ?1", PROFILE_COLUMNS),
+        params![name.trim()],
+        row_to_profile,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Execution profile '{}' not found", name.trim()))
+}
+
+fn validate(name: &str, config: &ExecutionProfileConfig) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Profile name is empty".to_string());
+    }
+    if let Some(preset) = &config.permission_preset {
+        if !TEMPLATE_PERMISSION_PRESETS.contains(&preset.as_str()) {
+            return Err(format!(
+                "Unknown permission preset '{}', expected one of: {}",
+                preset,
+                TEMPLATE_PERMISSION_PRESETS.join(", ")
+            ));
+        }
+    }
+    if config.max_turns == Some(0) {
+        return Err("max_turns must be at least 1".to_string());
+    }
+    if config.max_budget_usd.map_or(false, |budget| budget <= 0.0) {
+        return Err("max_budget_usd must be positive".to_string());
+    }
+    Ok(())
+}
+
+/// CLI arguments the profile adds to a run
+pub fn profile_args(profile: &ExecutionProfile) -> Vec<String> {
+    match profile.config.max_turns {
+        Some(max_turns) => vec!["--max-turns".to_string(), max_turns.to_string()],
+        None => Vec::new(),
+    }
+}
+
+/// Remember which profile a session ran with
+pub fn record_session_profile(conn: &Connection, session_id: &str, profile_name: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO session_run_profiles (session_id, profile_name, recorded_at)
+         VALUES (?1, ?2, CURRENT_TIMESTAMP)
+         ON CONFLICT(session_id) DO UPDATE SET
+             profile_name = excluded.profile_name,
+             recorded_at = excluded.recorded_at",
+        params![session_id, profile_name],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Estimated spend of a run checked against its profile's budget
+pub struct RunBudget {
+    limit_usd: f64,
+    spent_usd: f64,
+    /// Message ids already counted; the stream repeats a message once per content block
+    counted: HashSet<String>,
+}
+
+impl RunBudget {
+    pub fn with_limit(limit_usd: f64) -> Self {
+        Self {
+            limit_usd,
+            spent_usd: 0.0,
+            counted: HashSet::new(),
+        }
+    }
+
+    /// The tighter of the profile's budget and the run's own cost cap, if either is set
+    pub fn for_run(profile: Option<&ExecutionProfile>, max_cost_usd: Option<f64>) -> Option<Self> {
+        let profile_limit = profile.and_then(|profile| profile.config.max_budget_usd);
+        let limit_usd = match (profile_limit, max_cost_usd) {
+            (Some(profile_limit), Some(max_cost_usd)) => profile_limit.min(max_cost_usd),
+            (profile_limit, max_cost_usd) => profile_limit.or(max_cost_usd)?,
+        };
+        Some(Self::with_limit(limit_usd))
+    }
+
+    /// Add the cost of an assistant stream message; returns whether the budget is now exceeded
+    pub fn record(&mut self, msg: &serde_json::Value, default_model: &str) -> bool {
+        let message = &msg["message"];
+        let Some(usage) = message.get("usage") else {
+            return false;
+        };
+        if let Some(id) = message["id"].as_str() {
+            if !self.counted.insert(id.to_string()) {
+                return false;
+            }
+        }
+        let model = message["model"].as_str().unwrap_or(default_model);
+        self.spent_usd += estimate_message_cost(model, usage);
+        self.spent_usd > self.limit_usd
+    }
+
+    pub fn spent_usd(&self) -> f64 {
+        self.spent_usd
+    }
+
+    pub fn limit_usd(&self) -> f64 {
+        self.limit_usd
+    }
+}
+
+/// Report a finished run to the profile's webhook, if its notify setting asks for it
+pub async fn notify_run_finished(profile: &ExecutionProfile, session_id: Option<&str>, project_path: &str, success: bool) {
+    let wanted = match profile.config.notify {
+        ProfileNotify::Always => true,
+        ProfileNotify::OnFailure => !success,
+        ProfileNotify::Never => false,
+    };
+    let Some(url) = profile.config.webhook_url.as_deref().filter(|u| !u.trim().is_empty()) else {
+        return;
+    };
+    if !wanted {
+        return;
+    }
+
+    let payload = serde_json::json!({
+        "text": format!(
+            "Claude run in {} {} (profile {})",
+            project_path,
+            if success { "succeeded" } else { "failed" },
+            profile.name
+        ),
+        "session_id": session_id,
+        "profile": profile.name,
+        "success": success,
+        "project_path": project_path,
+    });
+    match reqwest::Client::new().post(url).json(&payload).send().await {
+        Ok(response) if response.status().is_success() => {
+            log::info!("Run notification delivered for profile {}", profile.name);
+        }
+        Ok(response) => log::warn!("Run notification webhook returned {}", response.status()),
+        Err(e) => log::warn!("Failed to deliver run notification: {}", e),
+    }
+}
+
+/// List execution profiles, built-in ones first
+#[tauri::command]
+pub async fn list_execution_profiles(db: State<'_, AgentDb>) -> Result<Vec<ExecutionProfile>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM execution_profiles ORDER BY builtin DESC, name",
+            PROFILE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let profiles = stmt
+        .query_map([], row_to_profile)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(profiles)
+}
+
+/// Create a profile or replace the settings of the one with the same name
+#[tauri::command]
+pub async fn save_execution_profile(
+    db: State<'_, AgentDb>,
+    name: String,
+    description: Option<String>,
+    config: ExecutionProfileConfig,
+) -> Result<ExecutionProfile, String> {
+    validate(&name, &config)?;
+    let config_json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO execution_profiles (name, description, config) VALUES (?1, ?2, ?3)
+         ON CONFLICT(name) DO UPDATE SET
+             description = excluded.description,
+             config = excluded.config,
+             updated_at = CURRENT_TIMESTAMP",
+        params![name.trim(), description.unwrap_or_default(), config_json],
+    )
+    .map_err(|e| e.to_string())?;
+    load_profile(&conn, &name)
+}
+
+/// Delete a user-defined profile
+#[tauri::command]
+pub async fn delete_execution_profile(db: State<'_, AgentDb>, name: String) -> Result<(), String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    if load_profile(&conn, &name)?.builtin {
+        return Err(format!("'{}' is a built-in profile and cannot be deleted", name.trim()));
+    }
+    conn.execute("DELETE FROM execution_profiles WHERE name = ?1", params![name.trim()])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Start a new session with a profile's permissions, turn limit, budget and
+/// notification settings; the profile is recorded on the session
+#[tauri::command]
+pub async fn apply_execution_profile(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    profile: String,
+    run: ProfiledRun,
+) -> Result<RunHandle, String> {
+    let profile = {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        load_profile(&conn, &profile)?
+    };
+    log::info!("Starting run in {} with execution profile '{}'", run.project_path, profile.name);
+
+    let permissions = profile.config.permission_preset.as_deref().and_then(permission_preset);
+    let args = profile_args(&profile);
+    super::claude::execute_claude_code_with(
+        app,
+        run.project_path,
+        run.prompt,
+        run.model,
+        run.provider_id,
+        super::claude::RunOverrides {
+            permissions,
+            extra_args: args,
+            profile: Some(profile),
+            max_cost_usd: None,
+            env_profile: None,
+            working_dir: None,
+        },
+    )
+    .await
+    .and_then(|run_id| super::run_dedupe::run_handle(run_id, "execute"))
+}
+
+/// The execution profile a session ran with, if any
+#[tauri::command]
+pub async fn get_session_profile(db: State<'_, AgentDb>, session_id: String) -> Result<Option<String>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    conn.query_row(
+        "SELECT profile_name FROM session_run_profiles WHERE session_id = ?1",
+        params![session_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
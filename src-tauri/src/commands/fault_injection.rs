@@ -0,0 +1,277 @@
+//! Simulated failures for resilience testing.
+//!
+//! Only builds with the `fault-injection` feature can inject anything; in other builds the
+//! hooks do nothing and `set_fault_injection` is refused. Faults fire deterministically on
+//! every `every`-th opportunity, up to `limit` times per kind, so a test knows exactly
+//! which operation fails.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// Faults to inject, set with `set_fault_injection`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FaultInjectionConfig {
+    /// Answer the API requests of Claude runs with this status: 429 (rate limited)
+    /// or 529 (overloaded)
+    pub provider_status: Option<u16>,
+    /// Claude runs find no API endpoint listening, as when the ccr router has crashed
+    pub router_down: bool,
+    /// Taking a database connection fails as if the database stayed locked
+    pub db_locked: bool,
+    /// Delay before a database connection is handed out, simulating lock contention
+    pub db_delay_ms: u64,
+    /// Delay added to each checkpoint blob and transcript read or write
+    pub disk_delay_ms: u64,
+    /// Inject on every n-th opportunity of each kind; 0 and 1 mean every time
+    pub every: u32,
+    /// Stop injecting a kind after this many faults
+    pub limit: Option<u32>,
+}
+
+/// Faults injected of each kind since the config was set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FaultCounts {
+    pub provider: u32,
+    pub router: u32,
+    pub db: u32,
+    pub disk: u32,
+}
+
+/// Returned by `get_fault_injection` and `set_fault_injection`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultInjectionStatus {
+    /// This build was made with the `fault-injection` feature
+    pub available: bool,
+    /// None while no faults are injected
+    pub config: Option<FaultInjectionConfig>,
+    pub injected: FaultCounts,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "fault-injection"), allow(dead_code))]
+enum FaultKind {
+    Provider,
+    Router,
+    Db,
+    Disk,
+}
+
+#[derive(Default)]
+struct FaultState {
+    config: Option<FaultInjectionConfig>,
+    /// Opportunities seen per kind, to apply `every`
+    #[cfg_attr(not(feature = "fault-injection"), allow(dead_code))]
+    seen: [u32; 4],
+    injected: FaultCounts,
+}
+
+static STATE: Lazy<Mutex<FaultState>> = Lazy::new(|| Mutex::new(FaultState::default()));
+
+/// Whether the fault of `kind` fires at this opportunity, counting it if so
+#[cfg_attr(not(feature = "fault-injection"), allow(dead_code))]
+fn fires(kind: FaultKind, state: &mut FaultState) -> bool {
+    let Some(config) = state.config.as_ref() else {
+        return false;
+    };
+    let injected = match kind {
+        FaultKind::Provider => &mut state.injected.provider,
+        FaultKind::Router => &mut state.injected.router,
+        FaultKind::Db => &mut state.injected.db,
+        FaultKind::Disk => &mut state.injected.disk,
+    };
+    if config.limit.map_or(false, |limit| *injected >= limit) {
+        return false;
+    }
+    let seen = &mut state.seen[kind as usize];
+    *seen += 1;
+    if *seen % config.every.max(1) != 0 {
+        return false;
+    }
+    *injected += 1;
+    log::warn!("Injecting {:?} fault", kind);
+    true
+}
+
+#[cfg(feature = "fault-injection")]
+mod inject {
+    use once_cell::sync::OnceCell;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Port of the stand-in API answering every request with an error status
+    static ERROR_SERVER_PORT: OnceCell<u16> = OnceCell::new();
+
+    fn error_response(status: u16) -> String {
+        let (reason, error_type) = match status {
+            529 => ("Overloaded", "overloaded_error"),
+            _ => ("Too Many Requests", "rate_limit_error"),
+        };
+        let body = serde_json::json!({
+            "type": "error",
+            "error": { "type": error_type, "message": format!("Injected {} fault", status) },
+        })
+        .to_string();
+        format!(
+            "HTTP/1.1 {} {}\r\ncontent-type: application/json\r\nretry-after: 1\r\n\
+             content-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            reason,
+            body.len(),
+            body
+        )
+    }
+
+    /// Start the stand-in API once; the status is read per request so changing the
+    /// config takes effect immediately
+    pub fn error_server_port() -> Result<u16, String> {
+        ERROR_SERVER_PORT
+            .get_or_try_init(|| {
+                let listener =
+                    std::net::TcpListener::bind("127.0.0.1:0").map_err(|e| e.to_string())?;
+                listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+                let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+                tauri::async_runtime::spawn(async move {
+                    let Ok(listener) = tokio::net::TcpListener::from_std(listener) else {
+                        return;
+                    };
+                    while let Ok((mut stream, _)) = listener.accept().await {
+                        tokio::spawn(async move {
+                            // Only the start of the request is read, the answer is fixed
+                            let mut request = [0u8; 4096];
+                            let _ = stream.read(&mut request).await;
+                            let status = super::STATE
+                                .lock()
+                                .ok()
+                                .and_then(|state| state.config.as_ref()?.provider_status)
+                                .unwrap_or(429);
+                            let _ = stream.write_all(error_response(status).as_bytes()).await;
+                            let _ = stream.shutdown().await;
+                        });
+                    }
+                });
+                log::info!("Fault injection API listening on 127.0.0.1:{}", port);
+                Ok(port)
+            })
+            .copied()
+    }
+
+    /// A local port nothing listens on
+    pub fn closed_port() -> Result<u16, String> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").map_err(|e| e.to_string())?;
+        listener.local_addr().map(|addr| addr.port()).map_err(|e| e.to_string())
+    }
+}
+
+/// Point a Claude run at the stand-in API or at a closed port when a provider or
+/// router fault fires. Called after the run's provider environment is applied.
+pub fn apply_provider_faults(cmd: &mut tokio::process::Command) {
+    #[cfg(feature = "fault-injection")]
+    {
+        let (router_down, provider_error) = match STATE.lock() {
+            Ok(mut state) => {
+                let configured = state.config.clone().unwrap_or_default();
+                let router_down = configured.router_down && fires(FaultKind::Router, &mut state);
+                let provider_error = !router_down
+                    && configured.provider_status.is_some()
+                    && fires(FaultKind::Provider, &mut state);
+                (router_down, provider_error)
+            }
+            Err(_) => return,
+        };
+        let port = if router_down {
+            inject::closed_port()
+        } else if provider_error {
+            inject::error_server_port()
+        } else {
+            return;
+        };
+        match port {
+            Ok(port) => {
+                cmd.env("ANTHROPIC_BASE_URL", format!("http://127.0.0.1:{}", port));
+            }
+            Err(e) => log::warn!("Failed to inject provider fault: {}", e),
+        }
+    }
+    #[cfg(not(feature = "fault-injection"))]
+    let _ = cmd;
+}
+
+/// Delay or fail taking a database connection when a db fault fires
+pub fn on_db_acquire() -> Result<(), String> {
+    #[cfg(feature = "fault-injection")]
+    {
+        let (delay, locked) = match STATE.lock() {
+            Ok(mut state) => {
+                let configured = state.config.clone().unwrap_or_default();
+                let configured_fault = configured.db_locked || configured.db_delay_ms > 0;
+                if !configured_fault || !fires(FaultKind::Db, &mut state) {
+                    return Ok(());
+                }
+                (configured.db_delay_ms, configured.db_locked)
+            }
+            Err(_) => return Ok(()),
+        };
+        std::thread::sleep(std::time::Duration::from_millis(delay));
+        if locked {
+            return Err("database is locked (injected fault)".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Slow down a checkpoint storage read or write when a disk fault fires
+pub fn on_disk_io() {
+    #[cfg(feature = "fault-injection")]
+    {
+        let delay = match STATE.lock() {
+            Ok(mut state) => {
+                let delay = state.config.as_ref().map_or(0, |c| c.disk_delay_ms);
+                if delay == 0 || !fires(FaultKind::Disk, &mut state) {
+                    return;
+                }
+                delay
+            }
+            Err(_) => return,
+        };
+        std::thread::sleep(std::time::Duration::from_millis(delay));
+    }
+}
+
+fn status(state: &FaultState) -> FaultInjectionStatus {
+    FaultInjectionStatus {
+        available: cfg!(feature = "fault-injection"),
+        config: state.config.clone(),
+        injected: state.injected.clone(),
+    }
+}
+
+/// Current fault injection config and how many faults were injected
+#[tauri::command]
+pub async fn get_fault_injection() -> Result<FaultInjectionStatus, String> {
+    let state = STATE.lock().map_err(|e| e.to_string())?;
+    Ok(status(&state))
+}
+
+/// Start injecting faults, or stop with `config: None`. Counters restart on every call.
+#[tauri::command]
+pub async fn set_fault_injection(
+    config: Option<FaultInjectionConfig>,
+) -> Result<FaultInjectionStatus, String> {
+    if config.is_some() && !cfg!(feature = "fault-injection") {
+        return Err("Fault injection is not available in this build".to_string());
+    }
+    if let Some(status) = config.as_ref().and_then(|c| c.provider_status) {
+        if status != 429 && status != 529 {
+            return Err(format!("Unsupported provider status {}, expected 429 or 529", status));
+        }
+    }
+
+    let mut state = STATE.lock().map_err(|e| e.to_string())?;
+    log::warn!("Fault injection set to {:?}", config);
+    *state = FaultState {
+        config,
+        ..FaultState::default()
+    };
+    Ok(status(&state))
+}
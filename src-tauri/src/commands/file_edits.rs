@@ -0,0 +1,209 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::{AppHandle, Manager, State};
+use ts_rs::TS;
+
+use super::agents::AgentDb;
+use super::events::AppEvent;
+
+/// Files larger than this are not read to estimate what a Write replaces
+const MAX_ESTIMATED_FILE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// One file edit made by a tool call, as announced by `file-edited`.
+///
+/// Line counts are estimated from the tool input when the call starts, before the tool
+/// has run; checkpoint diffs remain the exact record.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FileEdit {
+    pub session_id: String,
+    /// Edit, MultiEdit or Write
+    pub tool: String,
+    pub file_path: String,
+    pub additions: u32,
+    pub deletions: u32,
+    pub tool_use_id: Option<String>,
+    pub created_at: String,
+}
+
+/// Create `session_file_edits`
+pub fn init_file_edit_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_file_edits (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL,
+            tool TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            additions INTEGER NOT NULL DEFAULT 0,
+            deletions INTEGER NOT NULL DEFAULT 0,
+            tool_use_id TEXT,
+            created_at TEXT NOT NULL,
+            UNIQUE(session_id, tool_use_id, file_path)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_session_file_edits_session
+         ON session_file_edits(session_id)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Lines added and removed when `old` is replaced by `new`, not counting the lines
+/// both start and end with
+fn line_changes(old: &str, new: &str) -> (u32, u32) {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let additions = new.len() - prefix - suffix;
+    let deletions = old.len() - prefix - suffix;
+    (additions as u32, deletions as u32)
+}
+
+/// Lines of the file a Write is about to replace; 0 for a new or unreadable file
+fn current_line_count(path: &str) -> u32 {
+    let small = fs::metadata(path)
+        .map_or(false, |m| m.is_file() && m.len() <= MAX_ESTIMATED_FILE_BYTES);
+    if !small {
+        return 0;
+    }
+    fs::read_to_string(path).map_or(0, |content| content.lines().count() as u32)
+}
+
+/// Estimate the change of one Edit, MultiEdit or Write call
+fn estimate(tool: &str, input: &serde_json::Value) -> Option<(u32, u32)> {
+    let text =
+        |value: &serde_json::Value, key: &str| value[key].as_str().unwrap_or("").to_string();
+    match tool {
+        "Edit" => Some(line_changes(&text(input, "old_string"), &text(input, "new_string"))),
+        "MultiEdit" => {
+            let edits = input["edits"].as_array()?;
+            Some(edits.iter().fold((0, 0), |(added, removed), edit| {
+                let (a, r) = line_changes(&text(edit, "old_string"), &text(edit, "new_string"));
+                (added + a, removed + r)
+            }))
+        }
+        "Write" => {
+            let path = input["file_path"].as_str()?;
+            let content = text(input, "content");
+            Some((content.lines().count() as u32, current_line_count(path)))
+        }
+        _ => None,
+    }
+}
+
+/// File edits made by the tool calls of one stream-json assistant message
+pub fn parse_edits(session_id: &str, msg: &serde_json::Value) -> Vec<FileEdit> {
+    if msg["type"] != "assistant" {
+        return Vec::new();
+    }
+    let created_at = chrono::Utc::now().to_rfc3339();
+    msg["message"]["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|block| block["type"] == "tool_use")
+        .filter_map(|block| {
+            let tool = block["name"].as_str()?;
+            let input = &block["input"];
+            let file_path = input["file_path"].as_str()?;
+            let (additions, deletions) = estimate(tool, input)?;
+            Some(FileEdit {
+                session_id: session_id.to_string(),
+                tool: tool.to_string(),
+                file_path: file_path.to_string(),
+                additions,
+                deletions,
+                tool_use_id: block["id"].as_str().map(str::to_string),
+                created_at: created_at.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Record and announce the file edits of a stream-json message. A message repeated in
+/// the stream is stored and announced once.
+pub fn observe_message(app: &AppHandle, session_id: &str, msg: &serde_json::Value) {
+    let edits = parse_edits(session_id, msg);
+    if edits.is_empty() {
+        return;
+    }
+    let db = app.state::<AgentDb>();
+    let conn = match db.0.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::warn!("Failed to record file edits of {}: {}", session_id, e);
+            return;
+        }
+    };
+    for edit in edits {
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO session_file_edits
+             (session_id, tool, file_path, additions, deletions, tool_use_id, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                edit.session_id,
+                edit.tool,
+                edit.file_path,
+                edit.additions,
+                edit.deletions,
+                edit.tool_use_id,
+                edit.created_at
+            ],
+        );
+        match inserted {
+            Ok(0) => continue,
+            Ok(_) => {}
+            Err(e) => log::warn!("Failed to record file edit of {}: {}", edit.file_path, e),
+        }
+        if let Err(e) = AppEvent::FileEdited(edit).emit(app) {
+            log::warn!("Failed to emit file-edited event: {}", e);
+        }
+    }
+}
+
+/// `observe_message` for a raw output line
+pub fn observe_line(app: &AppHandle, session_id: &str, line: &str) {
+    if let Ok(msg) = serde_json::from_str::<serde_json::Value>(line) {
+        observe_message(app, session_id, &msg);
+    }
+}
+
+/// File edits of a session in the order they were made
+#[tauri::command]
+pub async fn list_session_file_edits(
+    db: State<'_, AgentDb>,
+    session_id: String,
+) -> Result<Vec<FileEdit>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT session_id, tool, file_path, additions, deletions, tool_use_id, created_at
+             FROM session_file_edits WHERE session_id = ?1 ORDER BY id",
+        )
+        .map_err(|e| e.to_string())?;
+    let edits = stmt
+        .query_map(params![session_id], |row| {
+            Ok(FileEdit {
+                session_id: row.get(0)?,
+                tool: row.get(1)?,
+                file_path: row.get(2)?,
+                additions: row.get(3)?,
+                deletions: row.get(4)?,
+                tool_use_id: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(edits)
+}
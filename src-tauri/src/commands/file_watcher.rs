@@ -0,0 +1,660 @@
+use notify::event::{EventKind, ModifyKind, RemoveKind, RenameMode};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, State};
+use tokio::sync::mpsc;
+use ts_rs::TS;
+
+use super::enhanced_hooks::{load_enhanced_hooks, EnhancedHook, HookContext, HookEvent, HookExecutor};
+use super::events::AppEvent;
+
+/// How often the project tree is rescanned when native file events are unavailable,
+/// and how often the event loop checks whether it was stopped
+const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Quiet period after the last detected change before the batch is flushed to hooks
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(1500);
+
+/// Upper bound on files tracked per project to keep scans cheap
+const MAX_TRACKED_FILES: usize = 50_000;
+
+/// Directories never watched (build output, VCS metadata, dependencies)
+const IGNORED_DIRS: &[&str] = &[
+    ".git", "node_modules", "target", "dist", "build", ".next", "__pycache__", ".claude",
+];
+
+/// Kind of change detected for a file
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum FileChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+/// A single file change passed to OnFileChange hooks
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FileChange {
+    /// Path relative to the project root, using forward slashes
+    pub path: String,
+    pub change_type: FileChangeKind,
+}
+
+/// Payload of `files-changed`: one debounced batch of changes in a watched project
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FilesChanged {
+    pub project_path: String,
+    pub changes: Vec<FileChange>,
+}
+
+/// Who asked for a project to be watched; a watcher runs until all of them stopped it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum WatchOwner {
+    /// `watch_project_path`, for the UI's `files-changed` stream
+    Ui,
+    /// `start_file_change_hooks`, for a session's OnFileChange hooks
+    Hooks,
+}
+
+struct RunningWatcher {
+    /// Stops the watch loop
+    stop: Arc<AtomicBool>,
+    owners: HashSet<WatchOwner>,
+}
+
+/// Running watchers keyed by project path
+#[derive(Default)]
+pub struct FileWatcherState {
+    watchers: Arc<Mutex<HashMap<String, RunningWatcher>>>,
+}
+
+type Snapshot = HashMap<PathBuf, (SystemTime, u64)>;
+
+/// When the hooks of the last batch started and finished
+type HookWindow = (SystemTime, SystemTime);
+
+/// Whether a file modified at `modified` was written by the hooks of the last batch
+/// (formatters, generators, ...), which must not trigger another round
+fn written_by_hooks(hook_window: Option<HookWindow>, modified: SystemTime) -> bool {
+    hook_window.map_or(false, |(started, finished)| modified >= started && modified <= finished)
+}
+
+/// Native file events of one watcher
+type FileEvents = mpsc::UnboundedReceiver<notify::Result<notify::Event>>;
+
+/// Take a (mtime, size) snapshot of every file in the project
+fn scan_project(root: &Path) -> Snapshot {
+    let mut snapshot = HashMap::new();
+
+    let walker = walkdir::WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|entry| {
+            !(entry.file_type().is_dir()
+                && entry
+                    .file_name()
+                    .to_str()
+                    .map(|name| IGNORED_DIRS.contains(&name))
+                    .unwrap_or(false))
+        });
+
+    for entry in walker.flatten() {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        if let Ok(metadata) = entry.metadata() {
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            snapshot.insert(entry.into_path(), (modified, metadata.len()));
+        }
+        if snapshot.len() >= MAX_TRACKED_FILES {
+            log::warn!(
+                "File watcher for {:?} reached the {} file limit, ignoring the rest",
+                root,
+                MAX_TRACKED_FILES
+            );
+            break;
+        }
+    }
+
+    snapshot
+}
+
+/// Merge one change into the pending batch
+fn record_change(pending: &mut BTreeMap<String, FileChangeKind>, path: String, kind: FileChangeKind) {
+    match (pending.get(&path), kind) {
+        // Created and deleted within one batch cancels out
+        (Some(FileChangeKind::Created), FileChangeKind::Deleted) => {
+            pending.remove(&path);
+        }
+        // A file created and then modified within one batch is still "created"
+        (Some(FileChangeKind::Created), _) => {}
+        // Editors save by replacing the file, which shows up as delete and create
+        (Some(_), FileChangeKind::Created) => {
+            pending.insert(path, FileChangeKind::Modified);
+        }
+        _ => {
+            pending.insert(path, kind);
+        }
+    }
+}
+
+/// Path relative to the project root with forward slashes; None inside an ignored
+/// directory or outside the project
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let ignored = relative.components().any(|component| {
+        component
+            .as_os_str()
+            .to_str()
+            .map_or(false, |name| IGNORED_DIRS.contains(&name))
+    });
+    if ignored || relative.as_os_str().is_empty() {
+        return None;
+    }
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// Compare two snapshots, merging the result into the pending change set
+fn diff_snapshots(
+    previous: &Snapshot,
+    current: &Snapshot,
+    root: &Path,
+    hook_window: Option<HookWindow>,
+    pending: &mut BTreeMap<String, FileChangeKind>,
+) -> bool {
+    let mut changed = false;
+
+    for (path, stat) in current {
+        let kind = match previous.get(path) {
+            _ if written_by_hooks(hook_window, stat.0) => None,
+            None => Some(FileChangeKind::Created),
+            Some(old) if old != stat => Some(FileChangeKind::Modified),
+            _ => None,
+        };
+        if let (Some(kind), Some(key)) = (kind, relative_path(root, path)) {
+            record_change(pending, key, kind);
+            changed = true;
+        }
+    }
+
+    for path in previous.keys() {
+        if !current.contains_key(path) {
+            if let Some(key) = relative_path(root, path) {
+                record_change(pending, key, FileChangeKind::Deleted);
+                changed = true;
+            }
+        }
+    }
+
+    changed
+}
+
+/// The file changes a native file event stands for
+fn event_changes(event: &notify::Event) -> Vec<(PathBuf, FileChangeKind)> {
+    let kind = match event.kind {
+        EventKind::Create(_) => FileChangeKind::Created,
+        EventKind::Remove(RemoveKind::Folder) => return Vec::new(),
+        EventKind::Remove(_) => FileChangeKind::Deleted,
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+            return vec![
+                (event.paths[0].clone(), FileChangeKind::Deleted),
+                (event.paths[1].clone(), FileChangeKind::Created),
+            ];
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => FileChangeKind::Deleted,
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => FileChangeKind::Created,
+        // Some platforms do not say which side of a rename a path is
+        EventKind::Modify(ModifyKind::Name(_)) => {
+            return event
+                .paths
+                .iter()
+                .map(|path| {
+                    let kind = if path.exists() {
+                        FileChangeKind::Created
+                    } else {
+                        FileChangeKind::Deleted
+                    };
+                    (path.clone(), kind)
+                })
+                .collect();
+        }
+        // Permission and timestamp changes leave the content alone
+        EventKind::Modify(ModifyKind::Metadata(_)) => return Vec::new(),
+        EventKind::Modify(_) => FileChangeKind::Modified,
+        _ => return Vec::new(),
+    };
+    event.paths.iter().map(|path| (path.clone(), kind)).collect()
+}
+
+/// Select the changes a hook is interested in based on its glob matcher
+fn filter_changes_for_hook(hook: &EnhancedHook, changes: &[FileChange]) -> Vec<FileChange> {
+    let patterns: Vec<glob::Pattern> = match &hook.matcher {
+        Some(matcher) if !matcher.trim().is_empty() && matcher.trim() != "*" => matcher
+            .split(',')
+            .filter_map(|p| glob::Pattern::new(p.trim()).ok())
+            .collect(),
+        _ => return changes.to_vec(),
+    };
+
+    changes
+        .iter()
+        .filter(|change| {
+            let file_name = change.path.rsplit('/').next().unwrap_or(&change.path);
+            patterns
+                .iter()
+                .any(|pattern| pattern.matches(&change.path) || pattern.matches(file_name))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Run the configured OnFileChange hooks for a batch of changes; returns when they
+/// ran, None if no hook ran
+async fn dispatch_file_changes(
+    app: &AppHandle,
+    project_path: &str,
+    session_id: &str,
+    changes: Vec<FileChange>,
+) -> Option<HookWindow> {
+    if !super::workspace_trust::is_project_trusted(app, project_path) {
+        log::debug!("Skipping OnFileChange hooks for untrusted project {}", project_path);
+        return None;
+    }
+
+    let hooks = match load_enhanced_hooks(&HookEvent::OnFileChange, project_path).await {
+        Ok(hooks) => hooks,
+        Err(e) => {
+            log::warn!("Failed to load OnFileChange hooks for {}: {}", project_path, e);
+            return None;
+        }
+    };
+
+    let executor = HookExecutor::new(app.clone());
+    let mut started = None;
+    for hook in hooks {
+        let matched = filter_changes_for_hook(&hook, &changes);
+        if matched.is_empty() {
+            continue;
+        }
+
+        started.get_or_insert_with(SystemTime::now);
+        let context = HookContext {
+            event: HookEvent::OnFileChange.as_str().to_string(),
+            session_id: session_id.to_string(),
+            project_path: project_path.to_string(),
+            data: serde_json::json!({
+                "changes": matched,
+                "count": matched.len(),
+            }),
+        };
+
+        if let Err(e) = executor
+            .execute_hook_chain(HookEvent::OnFileChange, context, vec![hook])
+            .await
+        {
+            log::warn!("OnFileChange hook failed for {}: {}", project_path, e);
+        }
+    }
+    started.map(|started| (started, SystemTime::now()))
+}
+
+/// Tell the UI about a batch of changes and run the hooks interested in it; returns
+/// when hooks ran
+async fn flush_changes(
+    app: &AppHandle,
+    project_path: &str,
+    session_id: &str,
+    pending: &mut BTreeMap<String, FileChangeKind>,
+) -> Option<HookWindow> {
+    let changes: Vec<FileChange> = std::mem::take(pending)
+        .into_iter()
+        .map(|(path, change_type)| FileChange { path, change_type })
+        .collect();
+    log::debug!("Dispatching {} file changes for {}", changes.len(), project_path);
+
+    let event = AppEvent::FilesChanged(FilesChanged {
+        project_path: project_path.to_string(),
+        changes: changes.clone(),
+    });
+    if let Err(e) = event.emit(app) {
+        log::warn!("Failed to emit file changes for {}: {}", project_path, e);
+    }
+    dispatch_file_changes(app, project_path, session_id, changes).await
+}
+
+/// Watch the project root and each top-level directory that is not ignored, so no
+/// OS watches are spent on dependencies or build output
+fn add_watches(watcher: &mut RecommendedWatcher, root: &Path) -> notify::Result<()> {
+    watcher.watch(root, RecursiveMode::NonRecursive)?;
+    for entry in std::fs::read_dir(root)?.flatten() {
+        if entry.file_type().map_or(false, |t| t.is_dir()) && relative_path(root, &entry.path()).is_some() {
+            watcher.watch(&entry.path(), RecursiveMode::Recursive)?;
+        }
+    }
+    Ok(())
+}
+
+/// Native file event watcher, or None when the platform cannot provide one (no
+/// backend, or the OS watch limit is reached)
+fn native_watcher(root: &Path) -> Option<(RecommendedWatcher, FileEvents)> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    });
+    let result = watcher.and_then(|mut watcher| add_watches(&mut watcher, root).map(|_| watcher));
+    match result {
+        Ok(watcher) => Some((watcher, rx)),
+        Err(e) => {
+            log::warn!("Native file events unavailable for {:?}, polling instead: {}", root, e);
+            None
+        }
+    }
+}
+
+/// Event loop for one project; exits when the stop flag is set
+async fn watch_events(
+    app: AppHandle,
+    project_path: String,
+    session_id: String,
+    stop: Arc<AtomicBool>,
+    mut watcher: RecommendedWatcher,
+    mut events: FileEvents,
+) {
+    let root = PathBuf::from(&project_path);
+    let mut pending: BTreeMap<String, FileChangeKind> = BTreeMap::new();
+    let mut last_change = Instant::now();
+    let mut hook_window = None;
+
+    log::info!("Started OnFileChange watcher for {}", project_path);
+
+    while !stop.load(Ordering::SeqCst) {
+        match tokio::time::timeout(POLL_INTERVAL, events.recv()).await {
+            Ok(Some(Ok(event))) => {
+                for (path, kind) in event_changes(&event) {
+                    let Some(key) = relative_path(&root, &path) else {
+                        continue;
+                    };
+                    if kind != FileChangeKind::Deleted && path.is_dir() {
+                        // New top-level directories are not covered by a watch yet
+                        if kind == FileChangeKind::Created && path.parent() == Some(root.as_path()) {
+                            if let Err(e) = watcher.watch(&path, RecursiveMode::Recursive) {
+                                log::warn!("Failed to watch {:?}: {}", path, e);
+                            }
+                        }
+                        continue;
+                    }
+                    if kind != FileChangeKind::Deleted {
+                        let modified = std::fs::metadata(&path).and_then(|metadata| metadata.modified());
+                        if modified.map_or(false, |modified| written_by_hooks(hook_window, modified)) {
+                            continue;
+                        }
+                    }
+                    record_change(&mut pending, key, kind);
+                    last_change = Instant::now();
+                }
+            }
+            Ok(Some(Err(e))) => log::warn!("File watcher error for {}: {}", project_path, e),
+            Ok(None) => break,
+            Err(_) => {}
+        }
+
+        if pending.is_empty() || last_change.elapsed() < DEBOUNCE_WINDOW {
+            continue;
+        }
+        hook_window = flush_changes(&app, &project_path, &session_id, &mut pending).await;
+    }
+
+    log::info!("Stopped OnFileChange watcher for {}", project_path);
+}
+
+/// Polling loop for one project; exits when the stop flag is set
+async fn poll_project(app: AppHandle, project_path: String, session_id: String, stop: Arc<AtomicBool>) {
+    let root = PathBuf::from(&project_path);
+    let scan_root = root.clone();
+    let mut snapshot = tokio::task::spawn_blocking(move || scan_project(&scan_root))
+        .await
+        .unwrap_or_default();
+    let mut pending: BTreeMap<String, FileChangeKind> = BTreeMap::new();
+    let mut last_change = Instant::now();
+    let mut hook_window = None;
+
+    log::info!("Started OnFileChange watcher for {} ({} files)", project_path, snapshot.len());
+
+    while !stop.load(Ordering::SeqCst) {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let scan_root = root.clone();
+        let current = match tokio::task::spawn_blocking(move || scan_project(&scan_root)).await {
+            Ok(current) => current,
+            Err(e) => {
+                log::warn!("File watcher scan failed for {}: {}", project_path, e);
+                continue;
+            }
+        };
+
+        if diff_snapshots(&snapshot, &current, &root, hook_window, &mut pending) {
+            last_change = Instant::now();
+        }
+        snapshot = current;
+
+        if pending.is_empty() || last_change.elapsed() < DEBOUNCE_WINDOW {
+            continue;
+        }
+        hook_window = flush_changes(&app, &project_path, &session_id, &mut pending).await;
+    }
+
+    log::info!("Stopped OnFileChange watcher for {}", project_path);
+}
+
+/// Start a watcher for a project unless one is running, and add `owner` to it
+fn spawn_watcher(
+    app: AppHandle,
+    state: &FileWatcherState,
+    project_path: String,
+    session_id: Option<String>,
+    owner: WatchOwner,
+) -> Result<(), String> {
+    let stop = {
+        let mut watchers = state.watchers.lock().map_err(|e| e.to_string())?;
+        if let Some(running) = watchers.get_mut(&project_path) {
+            running.owners.insert(owner);
+            return Ok(());
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let running = RunningWatcher {
+            stop: stop.clone(),
+            owners: HashSet::from([owner]),
+        };
+        watchers.insert(project_path.clone(), running);
+        stop
+    };
+
+    let watchers = state.watchers.clone();
+    let session_id = session_id.unwrap_or_default();
+    tauri::async_runtime::spawn(async move {
+        match native_watcher(Path::new(&project_path)) {
+            Some((watcher, events)) => {
+                watch_events(app, project_path.clone(), session_id, stop.clone(), watcher, events).await
+            }
+            None => poll_project(app, project_path.clone(), session_id, stop.clone()).await,
+        }
+        // A watcher for the same path may have been started since this one was stopped
+        if let Ok(mut watchers) = watchers.lock() {
+            if watchers.get(&project_path).map_or(false, |running| Arc::ptr_eq(&running.stop, &stop)) {
+                watchers.remove(&project_path);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Remove `owner` from a project's watcher, stopping it once no owner is left;
+/// false if `owner` was not watching the project
+fn stop_watcher(state: &FileWatcherState, project_path: &str, owner: WatchOwner) -> Result<bool, String> {
+    let mut watchers = state.watchers.lock().map_err(|e| e.to_string())?;
+    let Some(running) = watchers.get_mut(project_path) else {
+        return Ok(false);
+    };
+    if !running.owners.remove(&owner) {
+        return Ok(false);
+    }
+    if running.owners.is_empty() {
+        running.stop.store(true, Ordering::SeqCst);
+        watchers.remove(project_path);
+    }
+    Ok(true)
+}
+
+/// Start watching a project for OnFileChange hooks.
+/// Returns false (and does nothing) when the project has no OnFileChange hooks configured.
+#[tauri::command]
+pub async fn start_file_change_hooks(
+    app: AppHandle,
+    state: State<'_, FileWatcherState>,
+    project_path: String,
+    session_id: Option<String>,
+) -> Result<bool, String> {
+    if !Path::new(&project_path).is_dir() {
+        return Err(format!("Project path does not exist: {}", project_path));
+    }
+
+    let hooks = load_enhanced_hooks(&HookEvent::OnFileChange, &project_path).await?;
+    if hooks.is_empty() {
+        log::debug!("No OnFileChange hooks configured for {}, not watching", project_path);
+        return Ok(false);
+    }
+
+    spawn_watcher(app, &state, project_path, session_id, WatchOwner::Hooks)?;
+    Ok(true)
+}
+
+/// Stop the OnFileChange watcher of a project; a project the UI watches stays watched
+#[tauri::command]
+pub async fn stop_file_change_hooks(
+    state: State<'_, FileWatcherState>,
+    project_path: String,
+) -> Result<bool, String> {
+    stop_watcher(&state, &project_path, WatchOwner::Hooks)
+}
+
+/// Watch a project whether or not it has OnFileChange hooks. Changes arrive in
+/// debounced batches as `files-changed` events, and run the project's OnFileChange
+/// hooks as configured at that time.
+#[tauri::command]
+pub async fn watch_project_path(
+    app: AppHandle,
+    state: State<'_, FileWatcherState>,
+    project_path: String,
+    session_id: Option<String>,
+) -> Result<(), String> {
+    if !Path::new(&project_path).is_dir() {
+        return Err(format!("Project path does not exist: {}", project_path));
+    }
+    spawn_watcher(app, &state, project_path, session_id, WatchOwner::Ui)
+}
+
+/// Stop watching a project; false if it was not watched. OnFileChange hooks started
+/// for a session keep running.
+#[tauri::command]
+pub async fn unwatch_project_path(
+    state: State<'_, FileWatcherState>,
+    project_path: String,
+) -> Result<bool, String> {
+    stop_watcher(&state, &project_path, WatchOwner::Ui)
+}
+
+/// List projects currently watched for OnFileChange hooks
+#[tauri::command]
+pub async fn list_file_change_watchers(
+    state: State<'_, FileWatcherState>,
+) -> Result<Vec<String>, String> {
+    let watchers = state.watchers.lock().map_err(|e| e.to_string())?;
+    Ok(watchers.keys().cloned().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange};
+
+    fn batch(changes: &[(&str, FileChangeKind)]) -> BTreeMap<String, FileChangeKind> {
+        let mut pending = BTreeMap::new();
+        for (path, kind) in changes {
+            record_change(&mut pending, path.to_string(), *kind);
+        }
+        pending
+    }
+
+    #[test]
+    fn test_record_change_merges_a_batch() {
+        use FileChangeKind::*;
+        let pending = batch(&[
+            ("new.rs", Created),
+            ("new.rs", Modified),
+            ("tmp.rs", Created),
+            ("tmp.rs", Deleted),
+            ("saved.rs", Deleted),
+            ("saved.rs", Created),
+            ("gone.rs", Modified),
+            ("gone.rs", Deleted),
+        ]);
+        let expected: BTreeMap<String, FileChangeKind> = [
+            ("gone.rs".to_string(), Deleted),
+            ("new.rs".to_string(), Created),
+            ("saved.rs".to_string(), Modified),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(pending, expected);
+    }
+
+    #[test]
+    fn test_watcher_stops_with_its_last_owner() {
+        let state = FileWatcherState::default();
+        let stop = Arc::new(AtomicBool::new(false));
+        let running = RunningWatcher {
+            stop: stop.clone(),
+            owners: HashSet::from([WatchOwner::Ui, WatchOwner::Hooks]),
+        };
+        state.watchers.lock().unwrap().insert("/project".to_string(), running);
+
+        assert!(stop_watcher(&state, "/project", WatchOwner::Ui).unwrap());
+        assert!(!stop_watcher(&state, "/project", WatchOwner::Ui).unwrap());
+        assert!(!stop.load(Ordering::SeqCst));
+        assert!(stop_watcher(&state, "/project", WatchOwner::Hooks).unwrap());
+        assert!(stop.load(Ordering::SeqCst));
+        assert!(state.watchers.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_event_changes_and_ignored_paths() {
+        let root = Path::new("/project");
+        let rename = notify::Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+            .add_path(root.join("src/a.rs"))
+            .add_path(root.join("src/b.rs"));
+        assert_eq!(
+            event_changes(&rename),
+            vec![
+                (root.join("src/a.rs"), FileChangeKind::Deleted),
+                (root.join("src/b.rs"), FileChangeKind::Created),
+            ]
+        );
+        let write = notify::Event::new(EventKind::Modify(ModifyKind::Data(DataChange::Content)))
+            .add_path(root.join("src/a.rs"));
+        assert_eq!(event_changes(&write), vec![(root.join("src/a.rs"), FileChangeKind::Modified)]);
+        let create = notify::Event::new(EventKind::Create(CreateKind::File)).add_path(root.join("x"));
+        assert_eq!(event_changes(&create)[0].1, FileChangeKind::Created);
+
+        assert_eq!(relative_path(root, &root.join("src/a.rs")).as_deref(), Some("src/a.rs"));
+        assert_eq!(relative_path(root, &root.join("web/node_modules/x/index.js")), None);
+        assert_eq!(relative_path(root, Path::new("/elsewhere/a.rs")), None);
+    }
+}
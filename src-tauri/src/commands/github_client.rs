@@ -0,0 +1,216 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tauri::AppHandle;
+
+use super::secrets::{get_secret, set_secret};
+
+/// Secret store key for the personal access token
+const TOKEN_SECRET: &str = "github_token";
+
+const USER_AGENT: &str = "Claude-Workbench-App";
+
+/// Per-request timeout
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest we wait for a rate-limit window to reset before giving up
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+/// Retries after a secondary rate limit (403/429 with Retry-After)
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Only requests to the API host carry the token
+const API_HOST: &str = "api.github.com";
+
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(USER_AGENT)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+});
+
+/// Token loaded from the secret store, cached so requests don't read the file
+static TOKEN: Lazy<RwLock<Option<String>>> = Lazy::new(|| RwLock::new(None));
+
+/// Rate-limit quota reported by the last API response
+static RATE_LIMIT: Lazy<Mutex<Option<RateLimit>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    pub used: u64,
+    /// Unix timestamp when the quota resets
+    pub reset_at: i64,
+}
+
+/// Returned by `get_github_api_status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubApiStatus {
+    /// Whether a token is configured
+    pub authenticated: bool,
+    /// Latest known quota; None until the first API request
+    pub rate_limit: Option<RateLimit>,
+}
+
+/// Load the stored token, called once at startup
+pub fn init_github_client(app: &AppHandle) {
+    match get_secret(app, TOKEN_SECRET) {
+        Ok(token) => {
+            if let Ok(mut cached) = TOKEN.write() {
+                *cached = token;
+            }
+        }
+        Err(e) => log::warn!("Failed to load GitHub token: {}", e),
+    }
+}
+
+fn is_api_url(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h == API_HOST))
+        .unwrap_or(false)
+}
+
+/// GET request through the shared client, authenticated when the URL is the GitHub API
+pub fn get(url: &str) -> reqwest::RequestBuilder {
+    let request = CLIENT.get(url);
+    if !is_api_url(url) {
+        return request;
+    }
+    match TOKEN.read().ok().and_then(|t| t.clone()) {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+fn header_u64(response: &reqwest::Response, name: &str) -> Option<u64> {
+    response.headers().get(name)?.to_str().ok()?.parse().ok()
+}
+
+fn record_rate_limit(response: &reqwest::Response) {
+    let (Some(limit), Some(remaining), Some(reset)) = (
+        header_u64(response, "x-ratelimit-limit"),
+        header_u64(response, "x-ratelimit-remaining"),
+        header_u64(response, "x-ratelimit-reset"),
+    ) else {
+        return;
+    };
+    let used = header_u64(response, "x-ratelimit-used").unwrap_or(limit.saturating_sub(remaining));
+    if let Ok(mut state) = RATE_LIMIT.lock() {
+        *state = Some(RateLimit {
+            limit,
+            remaining,
+            used,
+            reset_at: reset as i64,
+        });
+    }
+}
+
+/// Time until the quota resets, if it is exhausted
+fn exhausted_for() -> Option<Duration> {
+    let state = RATE_LIMIT.lock().ok()?.clone()?;
+    if state.remaining > 0 {
+        return None;
+    }
+    let wait = state.reset_at - chrono::Utc::now().timestamp();
+    (wait > 0).then(|| Duration::from_secs(wait as u64))
+}
+
+/// Send a request built with `get`, honouring GitHub's rate-limit headers.
+///
+/// Waits (briefly) for an exhausted quota to reset, and backs off on secondary
+/// rate limits as instructed by `Retry-After`.
+pub async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, String> {
+    let mut retries = 0;
+    loop {
+        let attempt = request
+            .try_clone()
+            .ok_or("GitHub request body cannot be retried")?;
+        let is_api = attempt
+            .try_clone()
+            .and_then(|r| r.build().ok())
+            .map(|r| r.url().host_str() == Some(API_HOST))
+            .unwrap_or(false);
+
+        if is_api {
+            if let Some(wait) = exhausted_for() {
+                if wait > MAX_RATE_LIMIT_WAIT {
+                    return Err(format!(
+                        "GitHub API rate limit exceeded, resets in {} minutes. Add a GitHub token to raise the limit.",
+                        wait.as_secs().div_ceil(60)
+                    ));
+                }
+                log::info!("GitHub rate limit exhausted, waiting {}s for reset", wait.as_secs());
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        let response = attempt.send().await.map_err(|e| e.to_string())?;
+        if is_api {
+            record_rate_limit(&response);
+        }
+
+        let status = response.status();
+        let limited = status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            || (status == reqwest::StatusCode::FORBIDDEN
+                && (response.headers().contains_key("retry-after")
+                    || header_u64(&response, "x-ratelimit-remaining") == Some(0)));
+        if !limited || retries >= MAX_RATE_LIMIT_RETRIES {
+            return Ok(response);
+        }
+
+        retries += 1;
+        let wait = header_u64(&response, "retry-after")
+            .map(Duration::from_secs)
+            .or_else(exhausted_for)
+            .unwrap_or(Duration::from_secs(2u64.pow(retries)))
+            .min(MAX_RATE_LIMIT_WAIT);
+        log::warn!(
+            "GitHub rate limited (HTTP {}), retry {}/{} in {}s",
+            status,
+            retries,
+            MAX_RATE_LIMIT_RETRIES,
+            wait.as_secs()
+        );
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Store or clear the GitHub token used for API requests
+#[tauri::command]
+pub async fn set_github_token(app: AppHandle, token: Option<String>) -> Result<GitHubApiStatus, String> {
+    let token = token.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    set_secret(&app, TOKEN_SECRET, token.as_deref())?;
+    if let Ok(mut cached) = TOKEN.write() {
+        *cached = token;
+    }
+    // The previous quota belonged to the old credentials
+    if let Ok(mut state) = RATE_LIMIT.lock() {
+        *state = None;
+    }
+    get_github_api_status().await
+}
+
+/// Whether a token is configured and how much API quota remains.
+///
+/// Queries `/rate_limit`, which does not count against the quota; falls back
+/// to the last recorded headers when GitHub can't be reached.
+#[tauri::command]
+pub async fn get_github_api_status() -> Result<GitHubApiStatus, String> {
+    let authenticated = TOKEN.read().map(|t| t.is_some()).unwrap_or(false);
+    match send(get("https://api.github.com/rate_limit")).await {
+        Ok(response) if response.status() == reqwest::StatusCode::UNAUTHORIZED => {
+            return Err("GitHub rejected the configured token".to_string());
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("Failed to query GitHub rate limit: {}", e),
+    }
+
+    Ok(GitHubApiStatus {
+        authenticated,
+        rate_limit: RATE_LIMIT.lock().map_err(|e| e.to_string())?.clone(),
+    })
+}
@@ -0,0 +1,566 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::State;
+
+use super::agents::AgentDb;
+use super::claude::get_claude_dir;
+
+/// Searchable content kinds
+pub const SEARCH_KINDS: &[&str] = &["session", "agent", "checkpoint", "todo", "prompt", "slash_command"];
+
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// Maximum characters of session text indexed per session
+const MAX_SESSION_TEXT: usize = 200_000;
+
+/// Searches reuse the index for this long unless it was invalidated; catches sessions
+/// written by a CLI running outside the app
+const INDEX_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// Set when indexed content changed; the next search refreshes the index
+static INDEX_STALE: AtomicBool = AtomicBool::new(true);
+
+/// When the index was last brought up to date
+static LAST_REFRESH: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// A single search result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub kind: String,
+    /// Identifier of the item within its kind (session ID, agent ID, ...)
+    pub id: String,
+    pub title: String,
+    /// Matched excerpt with `[` `]` around the matched terms
+    pub snippet: String,
+    /// Relevance, higher is better
+    pub score: f64,
+    /// Where the UI should navigate when the hit is opened
+    pub target: JsonValue,
+}
+
+/// Summary of an index refresh
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchIndexStats {
+    pub sources_updated: usize,
+    pub sources_removed: usize,
+    pub documents: i64,
+}
+
+/// A document to be indexed
+struct SearchDocument {
+    kind: &'static str,
+    ref_id: String,
+    title: String,
+    body: String,
+    target: JsonValue,
+}
+
+/// A file-backed source with the documents it produced
+struct IndexedSource {
+    key: String,
+    modified: i64,
+    documents: Vec<SearchDocument>,
+}
+
+fn modified_secs(path: &Path) -> i64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn message_text(content: &JsonValue) -> String {
+    match content {
+        JsonValue::String(text) => text.clone(),
+        JsonValue::Array(blocks) => blocks
+            .iter()
+            .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn session_document(path: &Path, project_id: &str, session_id: &str) -> Option<SearchDocument> {
+    let file = fs::File::open(path).ok()?;
+    let mut title = None;
+    let mut body = String::new();
+    let mut project_path = None;
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        if body.len() >= MAX_SESSION_TEXT {
+            break;
+        }
+        let entry: JsonValue = match serde_json::from_str(&line) {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+        if project_path.is_none() {
+            project_path = entry.get("cwd").and_then(|c| c.as_str()).map(|s| s.to_string());
+        }
+        let role = entry.get("type").and_then(|t| t.as_str());
+        if !matches!(role, Some("user") | Some("assistant")) {
+            continue;
+        }
+        let text = message_text(&entry["message"]["content"]);
+        if text.trim().is_empty() {
+            continue;
+        }
+        if title.is_none() && role == Some("user") {
+            title = Some(text.chars().take(120).collect::<String>());
+        }
+        body.push_str(&text);
+        body.push('\n');
+    }
+
+    if body.is_empty() {
+        return None;
+    }
+
+    Some(SearchDocument {
+        kind: "session",
+        ref_id: session_id.to_string(),
+        title: title.unwrap_or_else(|| session_id.to_string()),
+        body,
+        target: serde_json::json!({
+            "type": "session",
+            "session_id": session_id,
+            "project_id": project_id,
+            "project_path": project_path,
+        }),
+    })
+}
+
+fn checkpoint_document(path: &Path) -> Option<SearchDocument> {
+    let checkpoint: crate::checkpoint::Checkpoint =
+        serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    let description = checkpoint.description.clone().unwrap_or_default();
+    let body = format!("{}\n{}", description, checkpoint.metadata.user_prompt);
+    if body.trim().is_empty() {
+        return None;
+    }
+
+    Some(SearchDocument {
+        kind: "checkpoint",
+        ref_id: checkpoint.id.clone(),
+        title: if description.is_empty() {
+            format!("Checkpoint {}", &checkpoint.id)
+        } else {
+            description
+        },
+        body,
+        target: serde_json::json!({
+            "type": "checkpoint",
+            "checkpoint_id": checkpoint.id,
+            "session_id": checkpoint.session_id,
+            "project_id": checkpoint.project_id,
+        }),
+    })
+}
+
+fn todo_document(path: &Path, session_id: &str) -> Option<SearchDocument> {
+    let todos: Vec<JsonValue> = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    let items: Vec<&str> = todos
+        .iter()
+        .filter_map(|todo| todo.get("content").and_then(|c| c.as_str()))
+        .collect();
+    if items.is_empty() {
+        return None;
+    }
+
+    Some(SearchDocument {
+        kind: "todo",
+        ref_id: session_id.to_string(),
+        title: items[0].chars().take(120).collect(),
+        body: items.join("\n"),
+        target: serde_json::json!({ "type": "session_todos", "session_id": session_id }),
+    })
+}
+
+fn prompt_documents(path: &Path) -> Vec<SearchDocument> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(_) => return Vec::new(),
+    };
+
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let entry: JsonValue = serde_json::from_str(&line).ok()?;
+            let prompt = entry.get("display").and_then(|d| d.as_str())?;
+            if prompt.trim().is_empty() {
+                return None;
+            }
+            Some(SearchDocument {
+                kind: "prompt",
+                ref_id: index.to_string(),
+                title: prompt.chars().take(120).collect(),
+                body: prompt.to_string(),
+                target: serde_json::json!({
+                    "type": "prompt",
+                    "prompt": prompt,
+                    "project_path": entry.get("project"),
+                    "timestamp": entry.get("timestamp"),
+                }),
+            })
+        })
+        .collect()
+}
+
+/// Collect all file-backed sources (sessions, checkpoints, todos, prompt history)
+/// whose modification time differs from the one recorded in the index
+fn collect_changed_sources(claude_dir: &Path, known: &HashMap<String, i64>) -> (Vec<IndexedSource>, Vec<String>) {
+    let mut changed = Vec::new();
+    let mut seen = HashSet::new();
+
+    let mut visit = |key: String, path: &Path, build: &dyn Fn() -> Vec<SearchDocument>| {
+        let modified = modified_secs(path);
+        seen.insert(key.clone());
+        if known.get(&key) != Some(&modified) {
+            changed.push(IndexedSource {
+                key,
+                modified,
+                documents: build(),
+            });
+        }
+    };
+
+    let projects_dir = claude_dir.join("projects");
+    for project in fs::read_dir(&projects_dir).into_iter().flatten().flatten() {
+        let project_dir = project.path();
+        if !project_dir.is_dir() {
+            continue;
+        }
+        let project_id = project.file_name().to_string_lossy().to_string();
+
+        for entry in fs::read_dir(&project_dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let session_id = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(id) => id.to_string(),
+                None => continue,
+            };
+            visit(format!("session:{}", path.display()), &path, &|| {
+                // Keep the listing sidecar current while the file is being read anyway
+                let _ = super::session_meta::load_session_meta(&path);
+                session_document(&path, &project_id, &session_id).into_iter().collect()
+            });
+        }
+
+        let pattern = project_dir.join(".timelines/*/checkpoints/*/metadata.json");
+        for path in glob::glob(&pattern.to_string_lossy()).into_iter().flatten().flatten() {
+            visit(format!("checkpoint:{}", path.display()), &path, &|| {
+                checkpoint_document(&path).into_iter().collect()
+            });
+        }
+    }
+
+    for entry in fs::read_dir(claude_dir.join("todos")).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let session_id = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+        visit(format!("todo:{}", path.display()), &path, &|| {
+            todo_document(&path, &session_id).into_iter().collect()
+        });
+    }
+
+    let history_path = claude_dir.join("history.jsonl");
+    if history_path.exists() {
+        visit(format!("prompt:{}", history_path.display()), &history_path, &|| {
+            prompt_documents(&history_path)
+        });
+    }
+
+    let removed = known
+        .keys()
+        .filter(|key| !key.starts_with("db:") && !seen.contains(key.as_str()))
+        .cloned()
+        .collect();
+
+    (changed, removed)
+}
+
+fn insert_documents(conn: &Connection, source: &str, documents: &[SearchDocument]) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(
+            "INSERT INTO search_index (kind, ref_id, title, body, target, source) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )
+        .map_err(|e| e.to_string())?;
+    for doc in documents {
+        stmt.execute(params![doc.kind, doc.ref_id, doc.title, doc.body, doc.target.to_string(), source])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn replace_source(conn: &Connection, key: &str, modified: i64, documents: &[SearchDocument]) -> Result<(), String> {
+    conn.execute("DELETE FROM search_index WHERE source = ?1", params![key])
+        .map_err(|e| e.to_string())?;
+    insert_documents(conn, key, documents)?;
+    conn.execute(
+        "INSERT INTO search_index_sources (source, modified) VALUES (?1, ?2)
+         ON CONFLICT(source) DO UPDATE SET modified = excluded.modified",
+        params![key, modified],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Agents live in the database and are cheap to reindex every time
+fn agent_documents(conn: &Connection) -> Result<Vec<SearchDocument>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, name, system_prompt, default_task FROM agents")
+        .map_err(|e| e.to_string())?;
+    let documents = stmt
+        .query_map([], |row| {
+            let id: i64 = row.get(0)?;
+            let name: String = row.get(1)?;
+            let system_prompt: String = row.get(2)?;
+            let default_task: Option<String> = row.get(3)?;
+            Ok(SearchDocument {
+                kind: "agent",
+                ref_id: id.to_string(),
+                title: name.clone(),
+                body: format!("{}\n{}\n{}", name, system_prompt, default_task.unwrap_or_default()),
+                target: serde_json::json!({ "type": "agent", "agent_id": id }),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(documents)
+}
+
+fn slash_command_documents(commands: Vec<super::slash_commands::SlashCommand>) -> Vec<SearchDocument> {
+    commands
+        .into_iter()
+        .map(|cmd| SearchDocument {
+            kind: "slash_command",
+            ref_id: cmd.id.clone(),
+            title: cmd.full_command.clone(),
+            body: format!(
+                "{}\n{}\n{}",
+                cmd.full_command,
+                cmd.description.clone().unwrap_or_default(),
+                cmd.content
+            ),
+            target: serde_json::json!({
+                "type": "slash_command",
+                "command_id": cmd.id,
+                "full_command": cmd.full_command,
+                "scope": cmd.scope,
+            }),
+        })
+        .collect()
+}
+
+/// Mark the search index out of date, e.g. after an agent was saved or a run ended
+pub fn invalidate_search_index() {
+    INDEX_STALE.store(true, Ordering::SeqCst);
+}
+
+/// Whether a search has to refresh the index first
+fn index_needs_refresh() -> bool {
+    let expired = LAST_REFRESH
+        .lock()
+        .map_or(true, |last| last.map_or(true, |at| at.elapsed() >= INDEX_MAX_AGE));
+    INDEX_STALE.load(Ordering::SeqCst) || expired
+}
+
+/// Bring the search index up to date; only changed files are re-read
+async fn refresh_search_index(db: &AgentDb, force: bool) -> Result<SearchIndexStats, String> {
+    // Cleared first, so a change during the refresh marks the index stale again
+    INDEX_STALE.store(false, Ordering::SeqCst);
+    let result = refresh_sources(db, force).await;
+    match &result {
+        Ok(_) => {
+            if let Ok(mut last) = LAST_REFRESH.lock() {
+                *last = Some(Instant::now());
+            }
+        }
+        Err(_) => invalidate_search_index(),
+    }
+    result
+}
+
+async fn refresh_sources(db: &AgentDb, force: bool) -> Result<SearchIndexStats, String> {
+    let known: HashMap<String, i64> = {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        if force {
+            conn.execute("DELETE FROM search_index", []).map_err(|e| e.to_string())?;
+            conn.execute("DELETE FROM search_index_sources", []).map_err(|e| e.to_string())?;
+        }
+        let mut stmt = conn
+            .prepare("SELECT source, modified FROM search_index_sources")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<HashMap<String, i64>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+
+    // File scanning happens without holding the database lock
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let (changed, removed) = tokio::task::spawn_blocking(move || collect_changed_sources(&claude_dir, &known))
+        .await
+        .map_err(|e| e.to_string())?;
+    let slash_commands = super::slash_commands::slash_commands_list(None)
+        .await
+        .unwrap_or_default();
+
+    let mut conn = db.0.get().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    for key in &removed {
+        tx.execute("DELETE FROM search_index WHERE source = ?1", params![key])
+            .map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM search_index_sources WHERE source = ?1", params![key])
+            .map_err(|e| e.to_string())?;
+    }
+    for source in &changed {
+        replace_source(&tx, &source.key, source.modified, &source.documents)?;
+    }
+
+    let agents = agent_documents(&tx)?;
+    replace_source(&tx, "db:agents", 0, &agents)?;
+    replace_source(&tx, "db:slash_commands", 0, &slash_command_documents(slash_commands))?;
+
+    let documents: i64 = tx
+        .query_row("SELECT COUNT(*) FROM search_index", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    if !changed.is_empty() || !removed.is_empty() {
+        log::info!(
+            "Search index refreshed: {} sources updated, {} removed, {} documents",
+            changed.len(),
+            removed.len(),
+            documents
+        );
+    }
+
+    Ok(SearchIndexStats {
+        sources_updated: changed.len(),
+        sources_removed: removed.len(),
+        documents,
+    })
+}
+
+/// Turn free text into an FTS5 query: every word must match, the last one as a prefix
+fn build_fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| !term.is_empty())
+        .collect();
+    if terms.is_empty() {
+        return None;
+    }
+
+    let last = terms.len() - 1;
+    Some(
+        terms
+            .iter()
+            .enumerate()
+            .map(|(i, term)| {
+                if i == last {
+                    format!("\"{}\"*", term)
+                } else {
+                    format!("\"{}\"", term)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Search sessions, agents, checkpoints, todos, prompt history and slash commands in one query
+#[tauri::command]
+pub async fn global_search(
+    db: State<'_, AgentDb>,
+    query: String,
+    kinds: Option<Vec<String>>,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    let fts_query = match build_fts_query(&query) {
+        Some(q) => q,
+        None => return Ok(Vec::new()),
+    };
+
+    let kinds: Vec<String> = match kinds {
+        Some(kinds) if !kinds.is_empty() => {
+            if let Some(unknown) = kinds.iter().find(|k| !SEARCH_KINDS.contains(&k.as_str())) {
+                return Err(format!(
+                    "Unknown search kind: {}. Valid values are: {}",
+                    unknown,
+                    SEARCH_KINDS.join(", ")
+                ));
+            }
+            kinds
+        }
+        _ => SEARCH_KINDS.iter().map(|k| k.to_string()).collect(),
+    };
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, 500);
+
+    if index_needs_refresh() {
+        refresh_search_index(&db, false).await?;
+    }
+
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let kinds_json = serde_json::to_string(&kinds).map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT kind, ref_id, title, snippet(search_index, 3, '[', ']', '…', 16), bm25(search_index, 0.0, 0.0, 10.0, 1.0), target
+             FROM search_index
+             WHERE search_index MATCH ?1
+               AND kind IN (SELECT value FROM json_each(?2))
+             ORDER BY bm25(search_index, 0.0, 0.0, 10.0, 1.0)
+             LIMIT ?3",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let hits = stmt
+        .query_map(params![fts_query, kinds_json, limit as i64], |row| {
+            let rank: f64 = row.get(4)?;
+            let target: String = row.get(5)?;
+            Ok(SearchHit {
+                kind: row.get(0)?,
+                id: row.get(1)?,
+                title: row.get(2)?,
+                snippet: row.get(3)?,
+                // bm25 is negative with better matches being more negative
+                score: -rank,
+                target: serde_json::from_str(&target).unwrap_or(JsonValue::Null),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(hits)
+}
+
+/// Drop and rebuild the whole search index
+#[tauri::command]
+pub async fn rebuild_search_index(db: State<'_, AgentDb>) -> Result<SearchIndexStats, String> {
+    refresh_search_index(&db, true).await
+}
@@ -0,0 +1,348 @@
+//! Trimmed views of session transcripts, which in agent runs are mostly tool chatter.
+//!
+//! History commands take an optional `HistoryFilter`. Every message of a filtered
+//! view carries its `historyIndex` in the transcript, so the full message can be
+//! loaded on demand with `load_session_history_page` and no filter.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// What to leave out of a session's history
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryFilter {
+    /// Replace tool results larger than this many KB with their size
+    pub max_tool_result_kb: Option<u64>,
+    /// Merge runs of two or more consecutive Bash calls and their results into one
+    /// `collapsed_tool_calls` message
+    pub collapse_bash: bool,
+    /// Keep only the text of user and assistant messages
+    pub text_only: bool,
+}
+
+impl HistoryFilter {
+    fn is_empty(&self) -> bool {
+        self.max_tool_result_kb.is_none() && !self.collapse_bash && !self.text_only
+    }
+}
+
+fn blocks(message: &Value) -> Option<&Vec<Value>> {
+    message
+        .pointer("/message/content")
+        .and_then(Value::as_array)
+}
+
+fn blocks_mut(message: &mut Value) -> Option<&mut Vec<Value>> {
+    message
+        .pointer_mut("/message/content")
+        .and_then(Value::as_array_mut)
+}
+
+fn message_type(message: &Value) -> &str {
+    message.get("type").and_then(Value::as_str).unwrap_or("")
+}
+
+fn block_type(block: &Value) -> &str {
+    block.get("type").and_then(Value::as_str).unwrap_or("")
+}
+
+fn serialized_len(value: &Value) -> usize {
+    serde_json::to_string(value).map_or(0, |s| s.len())
+}
+
+/// The user and assistant messages, with only their text blocks
+fn keep_text(messages: Vec<Value>) -> Vec<Value> {
+    messages
+        .into_iter()
+        .filter_map(|mut message| {
+            if !matches!(message_type(&message), "user" | "assistant") {
+                return None;
+            }
+            if let Some(object) = message.as_object_mut() {
+                object.remove("toolUseResult");
+            }
+            if let Some(blocks) = blocks_mut(&mut message) {
+                blocks.retain(|block| block_type(block) == "text");
+                if blocks.is_empty() {
+                    return None;
+                }
+            }
+            Some(message)
+        })
+        .collect()
+}
+
+/// Replace tool results over `max_bytes` with a placeholder recording their size
+fn hide_large_results(messages: &mut [Value], max_bytes: usize) {
+    for message in messages.iter_mut().filter(|m| message_type(m) == "user") {
+        if let Some(blocks) = blocks_mut(message) {
+            for block in blocks.iter_mut().filter(|b| block_type(b) == "tool_result") {
+                let size = block.get("content").map_or(0, serialized_len);
+                if size > max_bytes {
+                    block["content"] = json!(format!("[{} KB of tool output hidden]", size / 1024));
+                    block["hiddenBytes"] = json!(size);
+                }
+            }
+        }
+        // The CLI keeps a structured copy of the result next to the message
+        if let Some(result) = message.get_mut("toolUseResult") {
+            let size = serialized_len(result);
+            if size > max_bytes {
+                *result = json!({ "hiddenBytes": size });
+            }
+        }
+    }
+}
+
+/// The Bash calls of an assistant message made of nothing else, as (id, command)
+fn bash_calls(message: &Value) -> Option<Vec<(String, String)>> {
+    if message_type(message) != "assistant" {
+        return None;
+    }
+    let blocks = blocks(message).filter(|blocks| !blocks.is_empty())?;
+    blocks
+        .iter()
+        .map(|block| {
+            let is_bash = block_type(block) == "tool_use"
+                && block.get("name").and_then(Value::as_str) == Some("Bash");
+            is_bash.then(|| {
+                let id = block
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_string();
+                let command = block.pointer("/input/command").and_then(Value::as_str);
+                (id, command.unwrap_or("").to_string())
+            })
+        })
+        .collect()
+}
+
+/// The results of a user message made of nothing but results of `pending` calls,
+/// as (id, is_error)
+fn bash_results(message: &Value, pending: &[(String, String)]) -> Option<Vec<(String, bool)>> {
+    if message_type(message) != "user" {
+        return None;
+    }
+    let blocks = blocks(message).filter(|blocks| !blocks.is_empty())?;
+    blocks
+        .iter()
+        .map(|block| {
+            let id = block.get("tool_use_id").and_then(Value::as_str)?;
+            let known = block_type(block) == "tool_result" && pending.iter().any(|(p, _)| p == id);
+            known.then(|| (id.to_string(), block["is_error"].as_bool().unwrap_or(false)))
+        })
+        .collect()
+}
+
+/// One message standing for the Bash calls and results of `run`
+fn collapsed(run: &[Value], first_index: usize) -> Value {
+    let mut calls: Vec<(String, String)> = Vec::new();
+    let mut failed: Vec<String> = Vec::new();
+    for message in run {
+        if let Some(list) = bash_calls(message) {
+            calls.extend(list);
+        } else if let Some(results) = bash_results(message, &calls) {
+            failed.extend(
+                results
+                    .into_iter()
+                    .filter(|(_, error)| *error)
+                    .map(|(id, _)| id),
+            );
+        }
+    }
+    let calls: Vec<Value> = calls
+        .into_iter()
+        .map(|(id, command)| json!({ "command": command, "isError": failed.contains(&id) }))
+        .collect();
+
+    let last = run.last().unwrap_or(&Value::Null);
+    let mut message = json!({
+        "type": "collapsed_tool_calls",
+        "tool": "Bash",
+        "calls": calls,
+        "historyIndex": first_index,
+        "historyEnd": first_index + run.len() - 1,
+    });
+    for field in ["timestamp", "sessionId"] {
+        if let Some(value) = run[0].get(field) {
+            message[field] = value.clone();
+        }
+    }
+    if let Some(value) = last.get("receivedAt").or_else(|| last.get("sentAt")) {
+        message["receivedAt"] = value.clone();
+    }
+    message
+}
+
+/// Merge runs of consecutive Bash-only turns. `messages` carry their `historyIndex`.
+fn collapse_bash(messages: Vec<Value>) -> Vec<Value> {
+    let mut out = Vec::with_capacity(messages.len());
+    let mut run: Vec<Value> = Vec::new();
+    let mut pending: Vec<(String, String)> = Vec::new();
+    let mut calls = 0;
+
+    let flush = |run: &mut Vec<Value>, calls: usize, out: &mut Vec<Value>| {
+        if calls >= 2 {
+            let first = run[0]["historyIndex"].as_u64().unwrap_or(0) as usize;
+            out.push(collapsed(run, first));
+            run.clear();
+        } else {
+            out.append(run);
+        }
+    };
+
+    for message in messages {
+        if let Some(list) = bash_calls(&message) {
+            calls += list.len();
+            pending.extend(list);
+            run.push(message);
+        } else if !run.is_empty() && bash_results(&message, &pending).is_some() {
+            run.push(message);
+        } else {
+            flush(&mut run, calls, &mut out);
+            calls = 0;
+            pending.clear();
+            out.push(message);
+        }
+    }
+    flush(&mut run, calls, &mut out);
+    out
+}
+
+/// Apply `filter` to consecutive history messages starting at `first_index` in the
+/// transcript
+pub fn apply(messages: Vec<Value>, first_index: usize, filter: &HistoryFilter) -> Vec<Value> {
+    if filter.is_empty() {
+        return messages;
+    }
+    let mut messages: Vec<Value> = messages
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut message)| {
+            if message.is_object() {
+                message["historyIndex"] = json!(first_index + i);
+            }
+            message
+        })
+        .collect();
+
+    if filter.text_only {
+        return keep_text(messages);
+    }
+    if filter.collapse_bash {
+        messages = collapse_bash(messages);
+    }
+    if let Some(kb) = filter.max_tool_result_kb {
+        hide_large_results(&mut messages, kb as usize * 1024);
+    }
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bash(id: &str, command: &str) -> Value {
+        json!({ "type": "assistant", "message": { "content": [
+            { "type": "tool_use", "id": id, "name": "Bash", "input": { "command": command } }
+        ] } })
+    }
+
+    fn result(id: &str, output: &str, is_error: bool) -> Value {
+        json!({ "type": "user", "message": { "content": [
+            { "type": "tool_result", "tool_use_id": id, "content": output, "is_error": is_error }
+        ] } })
+    }
+
+    fn text(role: &str, text: &str) -> Value {
+        json!({ "type": role, "message": { "content": [{ "type": "text", "text": text }] } })
+    }
+
+    #[test]
+    fn test_empty_filter_returns_messages_unchanged() {
+        let messages = vec![text("user", "hi"), bash("a", "ls")];
+        assert_eq!(
+            apply(messages.clone(), 0, &HistoryFilter::default()),
+            messages
+        );
+    }
+
+    #[test]
+    fn test_collapse_consecutive_bash_calls() {
+        let messages = vec![
+            text("user", "fix the build"),
+            bash("a", "cargo build"),
+            result("a", "error", true),
+            bash("b", "cargo build"),
+            result("b", "ok", false),
+            text("assistant", "Fixed"),
+            bash("c", "git status"),
+            result("c", "clean", false),
+        ];
+        let filter = HistoryFilter {
+            collapse_bash: true,
+            ..Default::default()
+        };
+        let view = apply(messages, 10, &filter);
+
+        assert_eq!(view.len(), 5);
+        assert_eq!(view[1]["type"], "collapsed_tool_calls");
+        assert_eq!(view[1]["historyIndex"], 11);
+        assert_eq!(view[1]["historyEnd"], 14);
+        assert_eq!(
+            view[1]["calls"][0],
+            json!({ "command": "cargo build", "isError": true })
+        );
+        assert_eq!(view[1]["calls"][1]["isError"], false);
+        // A single call is left as is
+        assert_eq!(view[3]["historyIndex"], 16);
+        assert_eq!(view[4]["historyIndex"], 17);
+    }
+
+    #[test]
+    fn test_hide_large_tool_results() {
+        let mut large = result("a", &"x".repeat(3000), false);
+        large["toolUseResult"] = json!({ "stdout": "x".repeat(3000) });
+        let messages = vec![bash("a", "cat log"), large, result("b", "small", false)];
+        let filter = HistoryFilter {
+            max_tool_result_kb: Some(2),
+            ..Default::default()
+        };
+        let view = apply(messages, 0, &filter);
+
+        let block = &view[1]["message"]["content"][0];
+        assert_eq!(block["content"], "[2 KB of tool output hidden]");
+        assert_eq!(block["hiddenBytes"], 3002);
+        assert!(view[1]["toolUseResult"]["hiddenBytes"].is_number());
+        assert_eq!(view[2]["message"]["content"][0]["content"], "small");
+    }
+
+    #[test]
+    fn test_text_only() {
+        let mut mixed = text("assistant", "Running the tests");
+        mixed["message"]["content"]
+            .as_array_mut()
+            .unwrap()
+            .push(bash("a", "npm test")["message"]["content"][0].clone());
+        let messages = vec![
+            json!({ "type": "system", "content": "init" }),
+            text("user", "run the tests"),
+            mixed,
+            result("a", "ok", false),
+            json!({ "type": "user", "message": { "content": "plain prompt" } }),
+        ];
+        let filter = HistoryFilter {
+            text_only: true,
+            ..Default::default()
+        };
+        let view = apply(messages, 0, &filter);
+
+        let indexes: Vec<_> = view
+            .iter()
+            .map(|m| m["historyIndex"].as_u64().unwrap())
+            .collect();
+        assert_eq!(indexes, vec![1, 2, 4]);
+        assert_eq!(view[1]["message"]["content"].as_array().unwrap().len(), 1);
+    }
+}
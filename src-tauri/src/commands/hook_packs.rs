@@ -0,0 +1,325 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+
+use super::claude::{get_claude_dir, get_hooks_config, update_hooks_config};
+
+/// Curated packs shipped with the app
+const BUILTIN_PACKS: &str = include_str!("hook_packs.json");
+
+/// Directory under `~/.claude` holding user-installed pack bundles (`*.json`,
+/// each a single pack or an array of packs)
+const USER_PACKS_DIR: &str = "hook-packs";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookPackSource {
+    #[default]
+    Builtin,
+    User,
+}
+
+/// A bundle of hooks in the settings.json `hooks` format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookPack {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub source: HookPackSource,
+    /// Event name -> matcher groups, merged into the `hooks` section as is
+    pub hooks: Value,
+}
+
+/// An existing matcher group that already runs other hooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookPackConflict {
+    pub event: String,
+    pub matcher: String,
+    pub existing_commands: Vec<String>,
+    pub pack_commands: Vec<String>,
+}
+
+/// Returned by `preview_hook_pack`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookPackPreview {
+    pub pack: HookPack,
+    /// Hooks the install would add
+    pub new_hooks: usize,
+    /// Pack hooks already present in the scope
+    pub already_installed: usize,
+    pub conflicts: Vec<HookPackConflict>,
+}
+
+/// What to do with matcher groups that already run other hooks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookPackConflictStrategy {
+    /// Install nothing and report the conflicts
+    #[default]
+    Abort,
+    /// Run the pack hooks alongside the existing ones
+    Append,
+    /// Replace the existing hooks of the group with the pack's
+    Replace,
+}
+
+/// Returned by `install_hook_pack`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookPackInstallResult {
+    pub pack_id: String,
+    pub scope: String,
+    pub added: usize,
+    pub already_installed: usize,
+    /// Conflicting groups resolved by appending or replacing
+    pub conflicts_resolved: usize,
+}
+
+fn load_packs() -> Result<Vec<HookPack>, String> {
+    let mut packs: Vec<HookPack> =
+        serde_json::from_str(BUILTIN_PACKS).map_err(|e| format!("Failed to parse built-in hook packs: {}", e))?;
+
+    let Ok(dir) = get_claude_dir().map(|dir| dir.join(USER_PACKS_DIR)) else {
+        return Ok(packs);
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(packs);
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+        .collect();
+    files.sort();
+
+    for path in files {
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<Value>(&content).map_err(|e| e.to_string()))
+            .and_then(|value| match value {
+                Value::Array(_) => serde_json::from_value::<Vec<HookPack>>(value).map_err(|e| e.to_string()),
+                _ => serde_json::from_value::<HookPack>(value).map(|pack| vec![pack]).map_err(|e| e.to_string()),
+            });
+        match parsed {
+            Ok(user_packs) => {
+                for mut pack in user_packs {
+                    if packs.iter().any(|p| p.id == pack.id) {
+                        log::warn!("Skipping hook pack {} from {:?}: id already in use", pack.id, path);
+                        continue;
+                    }
+                    pack.source = HookPackSource::User;
+                    packs.push(pack);
+                }
+            }
+            Err(e) => log::warn!("Skipping invalid hook pack bundle {:?}: {}", path, e),
+        }
+    }
+    Ok(packs)
+}
+
+fn find_pack(id: &str) -> Result<HookPack, String> {
+    let pack = load_packs()?
+        .into_iter()
+        .find(|pack| pack.id == id)
+        .ok_or_else(|| format!("Hook pack {} not found", id))?;
+    if !pack.hooks.is_object() {
+        return Err(format!("Hook pack {} has no hooks object", id));
+    }
+    Ok(pack)
+}
+
+fn group_matcher(group: &Value) -> String {
+    group.get("matcher").and_then(|m| m.as_str()).unwrap_or_default().to_string()
+}
+
+fn group_commands(group: &Value) -> Vec<String> {
+    group
+        .get("hooks")
+        .and_then(|h| h.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|hook| hook.get("command").and_then(|c| c.as_str()).map(str::to_string))
+        .collect()
+}
+
+/// Pack matcher groups as `(event, group)`
+fn pack_groups(pack: &HookPack) -> Vec<(String, Value)> {
+    pack.hooks
+        .as_object()
+        .into_iter()
+        .flatten()
+        .flat_map(|(event, groups)| {
+            groups
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(move |group| (event.clone(), group.clone()))
+        })
+        .collect()
+}
+
+fn existing_group<'a>(config: &'a Value, event: &str, matcher: &str) -> Option<&'a Value> {
+    config
+        .get(event)
+        .and_then(|groups| groups.as_array())
+        .and_then(|groups| groups.iter().find(|group| group_matcher(group) == matcher))
+}
+
+/// Compare a pack against a scope's hooks: `(new hooks, already installed, conflicts)`
+fn compare(pack: &HookPack, config: &Value) -> (usize, usize, Vec<HookPackConflict>) {
+    let mut new_hooks = 0;
+    let mut installed = 0;
+    let mut conflicts = Vec::new();
+
+    for (event, group) in pack_groups(pack) {
+        let matcher = group_matcher(&group);
+        let pack_commands = group_commands(&group);
+        let existing_commands = existing_group(config, &event, &matcher)
+            .map(group_commands)
+            .unwrap_or_default();
+
+        let missing = pack_commands.iter().filter(|c| !existing_commands.contains(c)).count();
+        new_hooks += missing;
+        installed += pack_commands.len() - missing;
+        if missing > 0 && existing_commands.iter().any(|c| !pack_commands.contains(c)) {
+            conflicts.push(HookPackConflict {
+                event,
+                matcher,
+                existing_commands,
+                pack_commands,
+            });
+        }
+    }
+    (new_hooks, installed, conflicts)
+}
+
+/// List the built-in hook packs and the user's pack bundles
+#[tauri::command]
+pub async fn list_hook_packs() -> Result<Vec<HookPack>, String> {
+    load_packs()
+}
+
+/// Show what installing a pack would change; with a scope, also how it compares to
+/// the hooks configured there
+#[tauri::command]
+pub async fn preview_hook_pack(
+    id: String,
+    scope: Option<String>,
+    project_path: Option<String>,
+) -> Result<HookPackPreview, String> {
+    let pack = find_pack(&id)?;
+    let config = match scope {
+        Some(scope) => get_hooks_config(scope, project_path).await?,
+        None => json!({}),
+    };
+    let (new_hooks, already_installed, conflicts) = compare(&pack, &config);
+    Ok(HookPackPreview {
+        pack,
+        new_hooks,
+        already_installed,
+        conflicts,
+    })
+}
+
+/// Merge a pack's hooks into the hooks of `scope`; shared with the hook templates
+pub(crate) async fn merge_pack(
+    pack: &HookPack,
+    scope: String,
+    project_path: Option<String>,
+    on_conflict: Option<HookPackConflictStrategy>,
+) -> Result<HookPackInstallResult, String> {
+    let id = pack.id.clone();
+    let mut config = get_hooks_config(scope.clone(), project_path.clone()).await?;
+    if !config.is_object() {
+        config = json!({});
+    }
+
+    let (added, already_installed, conflicts) = compare(pack, &config);
+    let strategy = on_conflict.unwrap_or_default();
+    if !conflicts.is_empty() && strategy == HookPackConflictStrategy::Abort {
+        let groups: Vec<String> = conflicts
+            .iter()
+            .map(|c| if c.matcher.is_empty() { c.event.clone() } else { format!("{} ({})", c.event, c.matcher) })
+            .collect();
+        return Err(format!(
+            "{} conflicts with existing hooks for: {}",
+            pack.name,
+            groups.join(", ")
+        ));
+    }
+    if added == 0 {
+        return Ok(HookPackInstallResult {
+            pack_id: id,
+            scope,
+            added,
+            already_installed,
+            conflicts_resolved: 0,
+        });
+    }
+
+    for (event, group) in pack_groups(pack) {
+        let matcher = group_matcher(&group);
+        let groups = config
+            .as_object_mut()
+            .expect("hooks config is an object")
+            .entry(event.clone())
+            .or_insert_with(|| json!([]));
+        if !groups.is_array() {
+            *groups = json!([]);
+        }
+        let groups = groups.as_array_mut().expect("checked above");
+
+        match groups.iter().position(|existing| group_matcher(existing) == matcher) {
+            None => groups.push(group),
+            Some(index) => {
+                let existing = &mut groups[index];
+                let conflicting = conflicts.iter().any(|c| c.event == event && c.matcher == matcher);
+                if conflicting && strategy == HookPackConflictStrategy::Replace {
+                    existing["hooks"] = group["hooks"].clone();
+                    continue;
+                }
+                let existing_commands = group_commands(existing);
+                let missing: Vec<Value> = group["hooks"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|hook| {
+                        hook.get("command")
+                            .and_then(|c| c.as_str())
+                            .map_or(true, |c| !existing_commands.iter().any(|e| e == c))
+                    })
+                    .cloned()
+                    .collect();
+                match existing.get_mut("hooks").and_then(|h| h.as_array_mut()) {
+                    Some(hooks) => hooks.extend(missing),
+                    None => existing["hooks"] = Value::Array(missing),
+                }
+            }
+        }
+    }
+
+    update_hooks_config(scope.clone(), config, project_path).await?;
+    log::info!("Installed hooks of {} into {} scope ({} hooks added)", id, scope, added);
+    Ok(HookPackInstallResult {
+        pack_id: id,
+        scope,
+        added,
+        already_installed,
+        conflicts_resolved: conflicts.len(),
+    })
+}
+
+/// Merge a pack into the hooks of `scope` (`user`, `project` or `local`).
+///
+/// Hooks that are already present are skipped. Matcher groups that run other hooks
+/// are handled according to `on_conflict`, which aborts by default.
+#[tauri::command]
+pub async fn install_hook_pack(
+    id: String,
+    scope: String,
+    project_path: Option<String>,
+    on_conflict: Option<HookPackConflictStrategy>,
+) -> Result<HookPackInstallResult, String> {
+    let pack = find_pack(&id)?;
+    merge_pack(&pack, scope, project_path, on_conflict).await
+}
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+
+use super::claude::get_claude_dir;
+use super::hook_packs::{
+    merge_pack, HookPack, HookPackConflictStrategy, HookPackInstallResult, HookPackSource,
+};
+
+/// Templates shipped with the app
+const BUILTIN_TEMPLATES: &str = include_str!("hook_templates.json");
+
+/// Directory under `~/.claude` holding user-defined templates (`*.json`, each a
+/// single template or an array of templates)
+const USER_TEMPLATES_DIR: &str = "hook_templates";
+
+const SCOPES: &[&str] = &["user", "project", "local"];
+
+/// A value filled into the `{{name}}` placeholders of a template's hook commands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookTemplateParameter {
+    pub name: String,
+    pub description: String,
+    /// Parameters without a default must be given when applying the template
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+/// Hooks in the settings.json `hooks` format with `{{name}}` placeholders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub source: HookPackSource,
+    /// Scope the template is applied to when none is given
+    pub default_scope: String,
+    #[serde(default)]
+    pub parameters: Vec<HookTemplateParameter>,
+    pub hooks: Value,
+}
+
+fn check_template(template: &HookTemplate) -> Result<(), String> {
+    if !SCOPES.contains(&template.default_scope.as_str()) {
+        return Err(format!("invalid default scope {}", template.default_scope));
+    }
+    if !template.hooks.is_object() {
+        return Err("no hooks object".to_string());
+    }
+    Ok(())
+}
+
+fn load_templates() -> Result<Vec<HookTemplate>, String> {
+    let mut templates: Vec<HookTemplate> = serde_json::from_str(BUILTIN_TEMPLATES)
+        .map_err(|e| format!("Failed to parse built-in hook templates: {}", e))?;
+
+    let Ok(dir) = get_claude_dir().map(|dir| dir.join(USER_TEMPLATES_DIR)) else {
+        return Ok(templates);
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(templates);
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+        .collect();
+    files.sort();
+
+    for path in files {
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<Value>(&content).map_err(|e| e.to_string()))
+            .and_then(|value| match value {
+                Value::Array(_) => {
+                    serde_json::from_value::<Vec<HookTemplate>>(value).map_err(|e| e.to_string())
+                }
+                _ => serde_json::from_value::<HookTemplate>(value)
+                    .map(|template| vec![template])
+                    .map_err(|e| e.to_string()),
+            });
+        match parsed {
+            Ok(user_templates) => {
+                for mut template in user_templates {
+                    if templates.iter().any(|t| t.id == template.id) {
+                        log::warn!("Skipping hook template {} from {:?}: id already in use", template.id, path);
+                        continue;
+                    }
+                    if let Err(e) = check_template(&template) {
+                        log::warn!("Skipping hook template {} from {:?}: {}", template.id, path, e);
+                        continue;
+                    }
+                    template.source = HookPackSource::User;
+                    templates.push(template);
+                }
+            }
+            Err(e) => log::warn!("Skipping invalid hook template file {:?}: {}", path, e),
+        }
+    }
+    Ok(templates)
+}
+
+/// Replace the `{{name}}` placeholders of `text` in one pass, so filled-in values are
+/// never expanded again
+fn fill_text(text: &str, values: &HashMap<String, String>) -> Result<String, String> {
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .map(|end| start + end)
+            .ok_or_else(|| format!("unclosed placeholder in {}", text))?;
+        let name = &rest[start + 2..end];
+        let value = values.get(name).ok_or_else(|| format!("undeclared parameter {}", name))?;
+        filled.push_str(&rest[..start]);
+        filled.push_str(value);
+        rest = &rest[end + 2..];
+    }
+    filled.push_str(rest);
+    Ok(filled)
+}
+
+/// Fill the placeholders in every string of `value`
+fn fill(value: &Value, values: &HashMap<String, String>) -> Result<Value, String> {
+    Ok(match value {
+        Value::String(text) => Value::String(fill_text(text, values)?),
+        Value::Array(items) => Value::Array(
+            items.iter().map(|item| fill(item, values)).collect::<Result<_, _>>()?,
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, item)| Ok((key.clone(), fill(item, values)?)))
+                .collect::<Result<_, String>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+/// Fill a template's parameters into its hooks, giving a pack ready to merge
+fn render(
+    template: &HookTemplate,
+    mut params: HashMap<String, String>,
+) -> Result<HookPack, String> {
+    let declared = |name: &&String| template.parameters.iter().any(|p| &p.name == *name);
+    if let Some(unknown) = params.keys().find(|name| !declared(name)) {
+        return Err(format!("Hook template {} has no parameter {}", template.id, unknown));
+    }
+    let mut values = HashMap::new();
+    for parameter in &template.parameters {
+        let value = params
+            .remove(&parameter.name)
+            .or_else(|| parameter.default.clone())
+            .filter(|value| !value.trim().is_empty())
+            .ok_or_else(|| {
+                format!("Hook template {} needs a value for {}", template.id, parameter.name)
+            })?;
+        values.insert(parameter.name.clone(), value);
+    }
+
+    let hooks = fill(&template.hooks, &values)
+        .map_err(|e| format!("Hook template {}: {}", template.id, e))?;
+    Ok(HookPack {
+        id: template.id.clone(),
+        name: template.name.clone(),
+        description: template.description.clone(),
+        source: template.source,
+        hooks,
+    })
+}
+
+/// List the built-in hook templates and the user's templates in `~/.claude/hook_templates`
+#[tauri::command]
+pub async fn list_hook_templates() -> Result<Vec<HookTemplate>, String> {
+    load_templates()
+}
+
+/// Fill in a template's parameters and merge its hooks into `scope` (`user`,
+/// `project` or `local`; the template's default scope when omitted).
+///
+/// Parameters left out take their defaults. Conflicts with existing matcher groups
+/// are handled as by `install_hook_pack`.
+#[tauri::command]
+pub async fn apply_hook_template(
+    id: String,
+    scope: Option<String>,
+    project_path: Option<String>,
+    params: Option<HashMap<String, String>>,
+    on_conflict: Option<HookPackConflictStrategy>,
+) -> Result<HookPackInstallResult, String> {
+    let template = load_templates()?
+        .into_iter()
+        .find(|template| template.id == id)
+        .ok_or_else(|| format!("Hook template {} not found", id))?;
+    check_template(&template).map_err(|e| format!("Hook template {}: {}", id, e))?;
+    let pack = render(&template, params.unwrap_or_default())?;
+    let scope = scope.unwrap_or_else(|| template.default_scope.clone());
+    merge_pack(&pack, scope, project_path, on_conflict).await
+}
@@ -0,0 +1,448 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+use walkdir::WalkDir;
+
+use super::agents::AgentDb;
+use super::memory::memory_project_key;
+
+/// Length of the hashed embedding vectors
+const EMBEDDING_DIMS: usize = 512;
+
+/// Target chunk size in characters; chunks break at paragraph boundaries
+const CHUNK_CHARS: usize = 1200;
+
+/// Files larger than this are not ingested
+const MAX_DOC_BYTES: u64 = 1024 * 1024;
+
+/// Document extensions picked up from the docs folder
+const DOC_EXTENSIONS: &[&str] = &["md", "mdx", "markdown", "txt", "rst", "adoc"];
+
+const DEFAULT_QUERY_K: usize = 5;
+const MAX_QUERY_K: usize = 50;
+
+/// Chunks scoring below this are considered unrelated to the question
+const MIN_RELEVANCE: f32 = 0.05;
+
+/// Words too common to say anything about a chunk
+const STOPWORDS: &[&str] = &[
+    "an", "as", "at", "be", "by", "do", "if", "in", "is", "it", "of", "on", "or", "to", "we", "the",
+    "and", "for", "are", "but", "not", "you", "all", "can", "was", "one", "our", "has", "how", "its", "out", "use",
+    "this", "that", "with", "from", "have", "they", "will", "your", "what", "when", "which", "into", "then",
+    "there", "these", "their", "been", "also", "does", "should", "would", "could", "about",
+];
+
+/// A passage of a project document that matched a question
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeChunk {
+    /// Document path relative to the project root
+    pub source: String,
+    /// Nearest heading above the passage
+    pub heading: Option<String>,
+    pub content: String,
+    /// Cosine similarity to the question, 0-1
+    pub score: f32,
+}
+
+/// Returned by `query_project_knowledge`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeQueryResult {
+    pub chunks: Vec<KnowledgeChunk>,
+    /// The chunks formatted to be prepended to a prompt; empty when nothing matched
+    pub context: String,
+}
+
+/// Summary of an ingestion run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KnowledgeIngestStats {
+    pub files_indexed: usize,
+    pub files_unchanged: usize,
+    pub files_removed: usize,
+    /// Chunks stored for the project after the run
+    pub chunks: i64,
+}
+
+/// A document read from disk and split into chunks
+struct IngestedDoc {
+    source: String,
+    modified: i64,
+    chunks: Vec<(Option<String>, String)>,
+}
+
+/// Create the knowledge tables; chunks carry their embedding as little-endian f32s
+pub fn init_knowledge_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS knowledge_chunks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_path TEXT NOT NULL,
+            source TEXT NOT NULL,
+            heading TEXT,
+            content TEXT NOT NULL,
+            embedding BLOB NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_knowledge_chunks_source ON knowledge_chunks(project_path, source)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS knowledge_sources (
+            project_path TEXT NOT NULL,
+            source TEXT NOT NULL,
+            modified INTEGER NOT NULL,
+            PRIMARY KEY (project_path, source)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// 64-bit FNV-1a; stable across builds, unlike `DefaultHasher`, so stored vectors stay valid
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 2)
+        .map(str::to_lowercase)
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// Local embedding by feature hashing: words and word pairs are hashed into a fixed
+/// number of signed buckets, weighted by log term frequency and L2-normalized.
+/// Needs no model download and keeps related wording close in cosine distance.
+pub fn embed(text: &str) -> Vec<f32> {
+    let words = tokens(text);
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for word in &words {
+        *counts.entry(word.clone()).or_default() += 1;
+    }
+    for pair in words.windows(2) {
+        *counts.entry(format!("{} {}", pair[0], pair[1])).or_default() += 1;
+    }
+
+    let mut vector = vec![0f32; EMBEDDING_DIMS];
+    for (feature, count) in counts {
+        let hash = fnv1a(&feature);
+        let bucket = (hash % EMBEDDING_DIMS as u64) as usize;
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        // Word pairs count for less than single words
+        let weight = if feature.contains(' ') { 0.5 } else { 1.0 };
+        vector[bucket] += sign * weight * (1.0 + (count as f32).ln());
+    }
+
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+/// Dot product; both vectors are normalized, so this is the cosine similarity
+fn similarity(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Split a document into chunks of about `CHUNK_CHARS`, each tagged with the nearest
+/// Markdown heading above it. Chunks never span a heading.
+fn chunk_document(text: &str) -> Vec<(Option<String>, String)> {
+    let mut chunks = Vec::new();
+    let mut heading: Option<String> = None;
+    let mut current = String::new();
+
+    let flush = |chunks: &mut Vec<(Option<String>, String)>, heading: &Option<String>, current: &mut String| {
+        if !current.trim().is_empty() {
+            chunks.push((heading.clone(), current.trim().to_string()));
+        }
+        current.clear();
+    };
+
+    for paragraph in text.split("\n\n") {
+        let trimmed = paragraph.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if let Some(title) = trimmed.lines().next().filter(|line| line.starts_with('#')) {
+            flush(&mut chunks, &heading, &mut current);
+            heading = Some(title.trim_start_matches('#').trim().to_string());
+        }
+        if !current.is_empty() && current.chars().count() + trimmed.chars().count() > CHUNK_CHARS {
+            flush(&mut chunks, &heading, &mut current);
+        }
+        // A single paragraph longer than a chunk is split by lines
+        if trimmed.chars().count() > CHUNK_CHARS {
+            for line in trimmed.lines() {
+                if !current.is_empty() && current.chars().count() + line.chars().count() > CHUNK_CHARS {
+                    flush(&mut chunks, &heading, &mut current);
+                }
+                current.push_str(line);
+                current.push('\n');
+            }
+            continue;
+        }
+        current.push_str(trimmed);
+        current.push_str("\n\n");
+    }
+    flush(&mut chunks, &heading, &mut current);
+    chunks
+}
+
+fn modified_secs(path: &Path) -> i64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Document files under `docs_dir` as `(path relative to the project, absolute path)`
+fn doc_files(project_root: &Path, docs_dir: &Path) -> Vec<(String, PathBuf)> {
+    WalkDir::new(docs_dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .and_then(|e| e.to_str())
+                .map_or(false, |ext| DOC_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        })
+        .filter(|entry| entry.metadata().map_or(false, |m| m.len() <= MAX_DOC_BYTES))
+        .map(|entry| {
+            let relative = entry
+                .path()
+                .strip_prefix(project_root)
+                .unwrap_or(entry.path())
+                .to_string_lossy()
+                .replace('\\', "/");
+            (relative, entry.path().to_path_buf())
+        })
+        .collect()
+}
+
+/// Read and chunk the documents that changed since the last run; returns them with
+/// the sources that no longer exist and the number of unchanged files
+fn collect_changed_docs(
+    project_root: &Path,
+    docs_dir: &Path,
+    known: &HashMap<String, i64>,
+) -> (Vec<IngestedDoc>, Vec<String>, usize) {
+    let files = doc_files(project_root, docs_dir);
+    let present: HashSet<&String> = files.iter().map(|(source, _)| source).collect();
+    let removed = known.keys().filter(|source| !present.contains(source)).cloned().collect();
+
+    let mut changed = Vec::new();
+    let mut unchanged = 0;
+    for (source, path) in &files {
+        let modified = modified_secs(path);
+        if known.get(source) == Some(&modified) {
+            unchanged += 1;
+            continue;
+        }
+        let Ok(text) = fs::read_to_string(path) else {
+            continue;
+        };
+        changed.push(IngestedDoc {
+            source: source.clone(),
+            modified,
+            chunks: chunk_document(&text),
+        });
+    }
+    (changed, removed, unchanged)
+}
+
+/// The `k` chunks of a project most similar to `question`, best first
+pub fn query_chunks(conn: &Connection, project_path: &str, question: &str, k: usize) -> Result<Vec<KnowledgeChunk>, String> {
+    let query = embed(question);
+    let mut stmt = conn
+        .prepare("SELECT source, heading, content, embedding FROM knowledge_chunks WHERE project_path = ?1")
+        .map_err(|e| e.to_string())?;
+    let mut chunks = stmt
+        .query_map(params![memory_project_key(project_path)], |row| {
+            let embedding: Vec<u8> = row.get(3)?;
+            Ok(KnowledgeChunk {
+                source: row.get(0)?,
+                heading: row.get(1)?,
+                content: row.get(2)?,
+                score: similarity(&query, &from_blob(&embedding)),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(Result::ok)
+        .filter(|chunk| chunk.score >= MIN_RELEVANCE)
+        .collect::<Vec<_>>();
+
+    chunks.sort_by(|a, b| b.score.total_cmp(&a.score));
+    chunks.truncate(k.clamp(1, MAX_QUERY_K));
+    Ok(chunks)
+}
+
+/// Format chunks as a context block to prepend to a prompt
+pub fn format_context(chunks: &[KnowledgeChunk]) -> String {
+    if chunks.is_empty() {
+        return String::new();
+    }
+    let mut context = String::from("<project-knowledge>\n");
+    for chunk in chunks {
+        match &chunk.heading {
+            Some(heading) => context.push_str(&format!("## {} — {}\n", chunk.source, heading)),
+            None => context.push_str(&format!("## {}\n", chunk.source)),
+        }
+        context.push_str(chunk.content.trim());
+        context.push_str("\n\n");
+    }
+    context.push_str("</project-knowledge>\n\n");
+    context
+}
+
+/// Chunk and embed a project's documentation folder (`docs/` unless `docs_dir` is
+/// given) into the knowledge base. Only files changed since the last run are re-read;
+/// `force` rebuilds the project's knowledge from scratch.
+#[tauri::command]
+pub async fn ingest_project_knowledge(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    docs_dir: Option<String>,
+    force: Option<bool>,
+) -> Result<KnowledgeIngestStats, String> {
+    let project_root = PathBuf::from(&project_path);
+    let docs_dir = match docs_dir {
+        Some(dir) if Path::new(&dir).is_absolute() => PathBuf::from(dir),
+        Some(dir) => project_root.join(dir),
+        None => project_root.join("docs"),
+    };
+    if !docs_dir.is_dir() {
+        return Err(format!("Docs folder {:?} not found", docs_dir));
+    }
+    let project = memory_project_key(&project_path);
+
+    let known: HashMap<String, i64> = {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        if force.unwrap_or(false) {
+            conn.execute("DELETE FROM knowledge_chunks WHERE project_path = ?1", params![project])
+                .map_err(|e| e.to_string())?;
+            conn.execute("DELETE FROM knowledge_sources WHERE project_path = ?1", params![project])
+                .map_err(|e| e.to_string())?;
+        }
+        let mut stmt = conn
+            .prepare("SELECT source, modified FROM knowledge_sources WHERE project_path = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![project], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<HashMap<String, i64>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+
+    // Reading and embedding happen without holding a database connection
+    let (changed, removed, files_unchanged) = tokio::task::spawn_blocking(move || {
+        let (docs, removed, unchanged) = collect_changed_docs(&project_root, &docs_dir, &known);
+        let embedded: Vec<(IngestedDoc, Vec<Vec<u8>>)> = docs
+            .into_iter()
+            .map(|doc| {
+                let embeddings = doc
+                    .chunks
+                    .iter()
+                    .map(|(heading, content)| {
+                        let text = format!("{} {}", heading.as_deref().unwrap_or_default(), content);
+                        to_blob(&embed(&text))
+                    })
+                    .collect();
+                (doc, embeddings)
+            })
+            .collect();
+        (embedded, removed, unchanged)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut conn = db.0.get().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for source in removed.iter().chain(changed.iter().map(|(doc, _)| &doc.source)) {
+        tx.execute(
+            "DELETE FROM knowledge_chunks WHERE project_path = ?1 AND source = ?2",
+            params![project, source],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            "DELETE FROM knowledge_sources WHERE project_path = ?1 AND source = ?2",
+            params![project, source],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    for (doc, embeddings) in &changed {
+        for ((heading, content), embedding) in doc.chunks.iter().zip(embeddings) {
+            tx.execute(
+                "INSERT INTO knowledge_chunks (project_path, source, heading, content, embedding)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![project, doc.source, heading, content, embedding],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.execute(
+            "INSERT INTO knowledge_sources (project_path, source, modified) VALUES (?1, ?2, ?3)",
+            params![project, doc.source, doc.modified],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    let chunks: i64 = tx
+        .query_row(
+            "SELECT COUNT(*) FROM knowledge_chunks WHERE project_path = ?1",
+            params![project],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    log::info!(
+        "Ingested knowledge for {}: {} files indexed, {} removed, {} chunks",
+        project_path,
+        changed.len(),
+        removed.len(),
+        chunks
+    );
+    Ok(KnowledgeIngestStats {
+        files_indexed: changed.len(),
+        files_unchanged,
+        files_removed: removed.len(),
+        chunks,
+    })
+}
+
+/// Find the `k` passages of a project's ingested docs most relevant to `question`,
+/// along with a context block ready to prepend to a prompt
+#[tauri::command]
+pub async fn query_project_knowledge(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    question: String,
+    k: Option<usize>,
+) -> Result<KnowledgeQueryResult, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let chunks = query_chunks(&conn, &project_path, &question, k.unwrap_or(DEFAULT_QUERY_K))?;
+    let context = format_context(&chunks);
+    Ok(KnowledgeQueryResult { chunks, context })
+}
@@ -0,0 +1,455 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::agents::AgentDb;
+use super::capability_tokens::{authorize, Denial};
+use super::execution_profiles::ProfiledRun;
+use super::settings_store;
+
+/// `app_settings` key holding the local API configuration
+const CONFIG_SETTING: &str = "local_api_config";
+
+/// Default port, next to the router's default of 3456; moved when another app holds it
+const DEFAULT_PORT: u16 = 3460;
+
+/// Largest request (head plus body) the server reads
+const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+
+/// A client that sends nothing for this long is dropped
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Content type of the Prometheus text exposition format
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Local API settings; the API is off until enabled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalApiConfig {
+    pub enabled: bool,
+    pub port: u16,
+    /// Serve Prometheus metrics at `/metrics`
+    #[serde(default)]
+    pub metrics_enabled: bool,
+}
+
+impl Default for LocalApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            metrics_enabled: false,
+        }
+    }
+}
+
+/// Returned by the local API commands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalApiStatus {
+    pub config: LocalApiConfig,
+    pub running: bool,
+    /// Base URL while running
+    pub url: Option<String>,
+}
+
+struct RunningServer {
+    port: u16,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+static SERVER: Lazy<Mutex<Option<RunningServer>>> = Lazy::new(|| Mutex::new(None));
+
+fn load_config(app: &AppHandle) -> LocalApiConfig {
+    let db = app.state::<AgentDb>();
+    db.0.get()
+        .map(|conn| settings_store::get_or_default(&conn, CONFIG_SETTING))
+        .unwrap_or_default()
+}
+
+/// Port the local API is configured to listen on
+pub fn configured_port(app: &AppHandle) -> u16 {
+    load_config(app).port
+}
+
+/// Port the local API listens on, while it runs
+pub fn running_port() -> Option<u16> {
+    SERVER.lock().unwrap().as_ref().map(|server| server.port)
+}
+
+/// Keep the configured port unless another app holds it, else move to a free one and
+/// remember the move
+fn resolve_port(app: &AppHandle, config: &mut LocalApiConfig) -> Result<(), String> {
+    if running_port() == Some(config.port) || super::service_ports::is_free(config.port) {
+        return Ok(());
+    }
+    let router_port = super::router_usage::router_address().1;
+    let port = super::service_ports::choose_port(config.port, &[router_port])?;
+    log::warn!(
+        "Port {} is taken by another app; the local API will use port {}",
+        config.port,
+        port
+    );
+    super::service_ports::record_local_api_move(app, config.port, port);
+    config.port = port;
+    settings_store::set(app, CONFIG_SETTING, config)
+}
+
+fn status(config: LocalApiConfig) -> LocalApiStatus {
+    let port = SERVER.lock().unwrap().as_ref().map(|server| server.port);
+    LocalApiStatus {
+        config,
+        running: port.is_some(),
+        url: port.map(|port| format!("http://127.0.0.1:{}", port)),
+    }
+}
+
+fn stop_server() {
+    if let Some(server) = SERVER.lock().unwrap().take() {
+        server.task.abort();
+        log::info!("Stopped local API on port {}", server.port);
+    }
+}
+
+async fn start_server(app: AppHandle, port: u16) -> Result<(), String> {
+    stop_server();
+    // Loopback only: the API is for scripts and tools on this machine
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .map_err(|e| format!("Failed to start local API on port {}: {}", port, e))?;
+    log::info!("Local API listening on http://127.0.0.1:{}", port);
+
+    let task = tauri::async_runtime::spawn(async move {
+        loop {
+            let (stream, _) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    log::warn!("Local API failed to accept a connection: {}", e);
+                    continue;
+                }
+            };
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = handle_connection(app, stream).await {
+                    log::debug!("Local API connection failed: {}", e);
+                }
+            });
+        }
+    });
+    *SERVER.lock().unwrap() = Some(RunningServer { port, task });
+    Ok(())
+}
+
+/// Start the local API if it was enabled
+pub fn init_local_api(app: &AppHandle) {
+    let mut config = load_config(app);
+    if !config.enabled {
+        return;
+    }
+    if let Err(e) = resolve_port(app, &mut config) {
+        log::warn!("Failed to choose a local API port: {}", e);
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = start_server(app, config.port).await {
+            log::error!("{}", e);
+        }
+    });
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn ok(body: impl Serialize) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: serde_json::to_vec(&body).unwrap_or_default(),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_vec(&serde_json::json!({ "error": message.into() })).unwrap_or_default(),
+        }
+    }
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => (key.to_string(), value.to_string()),
+            None => (pair.to_string(), String::new()),
+        })
+        .collect()
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<Request, Response> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buf.len() > MAX_REQUEST_BYTES {
+            return Err(Response::error(413, "Request too large"));
+        }
+        let read = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| Response::error(400, e.to_string()))?;
+        if read == 0 {
+            return Err(Response::error(400, "Incomplete request"));
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let length: usize = headers
+        .get("content-length")
+        .map(|value| value.parse().map_err(|_| Response::error(400, "Invalid Content-Length")))
+        .transpose()?
+        .unwrap_or(0);
+    if head_end + 4 + length > MAX_REQUEST_BYTES {
+        return Err(Response::error(413, "Request too large"));
+    }
+    let mut body = buf[head_end + 4..].to_vec();
+    while body.len() < length {
+        let read = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| Response::error(400, e.to_string()))?;
+        if read == 0 {
+            return Err(Response::error(400, "Incomplete request body"));
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(length);
+
+    Ok(Request {
+        method,
+        path: path.to_string(),
+        query: parse_query(query),
+        headers,
+        body,
+    })
+}
+
+async fn handle_connection(app: AppHandle, mut stream: TcpStream) -> std::io::Result<()> {
+    let response = match tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok(request)) => route(&app, request).await,
+        Ok(Err(response)) => response,
+        Err(_) => Response::error(400, "Request timed out"),
+    };
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        status_text(response.status),
+        response.content_type,
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&response.body).await?;
+    stream.shutdown().await
+}
+
+/// Browsers can reach loopback ports too; refuse anything a web page could send
+fn is_browser_request(request: &Request) -> bool {
+    if request.headers.contains_key("origin") {
+        return true;
+    }
+    let host = request.headers.get("host").map(String::as_str).unwrap_or_default();
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if !port.ends_with(']') => name,
+        _ => host,
+    };
+    !matches!(host, "127.0.0.1" | "localhost" | "[::1]")
+}
+
+fn denied(denial: Denial) -> Response {
+    let status = match denial {
+        Denial::NotRemote => 404,
+        _ if denial.is_unauthenticated() => 401,
+        _ => 403,
+    };
+    Response::error(status, denial.reason())
+}
+
+/// Request body of `POST /v1/sessions`
+#[derive(Debug, Deserialize)]
+struct StartSession {
+    #[serde(flatten)]
+    run: ProfiledRun,
+    /// Execution profile to apply, by name
+    profile: Option<String>,
+}
+
+async fn route(app: &AppHandle, request: Request) -> Response {
+    if is_browser_request(&request) {
+        return Response::error(403, "Browser requests are not accepted");
+    }
+
+    let command = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/v1/health") => return Response::ok(serde_json::json!({ "status": "ok" })),
+        ("GET", "/v1/usage/stats") => "get_usage_stats",
+        ("GET", "/v1/projects") => "list_projects",
+        ("POST", "/v1/sessions") => "execute_claude_code",
+        ("GET", "/metrics") if load_config(app).metrics_enabled => "get_metrics",
+        _ => return Response::error(404, "Not found"),
+    };
+    let start: Option<StartSession> = if command == "execute_claude_code" {
+        match serde_json::from_slice(&request.body) {
+            Ok(start) => Some(start),
+            Err(e) => return Response::error(400, format!("Invalid request body: {}", e)),
+        }
+    } else {
+        None
+    };
+    // Profiled runs are checked against their own command
+    let command = match &start {
+        Some(StartSession { profile: Some(_), .. }) => "apply_execution_profile",
+        _ => command,
+    };
+
+    let secret = request
+        .headers
+        .get("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let db = app.state::<AgentDb>();
+    {
+        let conn = match db.0.get() {
+            Ok(conn) => conn,
+            Err(e) => return Response::error(500, e.to_string()),
+        };
+        if let Err(denial) = authorize(&conn, secret, command, "http") {
+            return denied(denial);
+        }
+    }
+
+    let started = Instant::now();
+    let result = match start {
+        Some(StartSession { run, profile: Some(profile) }) => {
+            super::execution_profiles::apply_execution_profile(app.clone(), db, profile, run)
+                .await
+                .map(|handle| Response::ok(serde_json::json!({ "started": true, "run_id": handle.run_id })))
+        }
+        Some(StartSession { run, profile: None }) => super::claude::execute_claude_code_with(
+            app.clone(),
+            run.project_path,
+            run.prompt,
+            run.model,
+            run.provider_id,
+            super::claude::RunOverrides::default(),
+        )
+        .await
+        .map(|run_id| Response::ok(serde_json::json!({ "started": true, "run_id": run_id }))),
+        None if command == "get_metrics" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn_blocking(move || super::metrics::render(&app))
+                .await
+                .map_err(|e| e.to_string())
+                .map(|text| Response {
+                    status: 200,
+                    content_type: METRICS_CONTENT_TYPE,
+                    body: text.into_bytes(),
+                })
+        }
+        None if command == "get_usage_stats" => {
+            let days = request.query.get("days").and_then(|days| days.parse().ok());
+            super::usage::get_usage_stats(days).map(Response::ok)
+        }
+        None => super::claude::list_projects(db, None).await.map(Response::ok),
+    };
+    super::metrics::record_latency(command, result.is_ok(), started.elapsed());
+    result.unwrap_or_else(|e| Response::error(500, e))
+}
+
+/// Whether the local API is enabled and running
+#[tauri::command]
+pub async fn get_local_api_status(app: AppHandle) -> Result<LocalApiStatus, String> {
+    Ok(status(load_config(&app)))
+}
+
+/// Enable or disable the local API, optionally on another port. Every request to it
+/// needs a capability token. Without a port, a configured port another app holds is
+/// swapped for a free one.
+#[tauri::command]
+pub async fn set_local_api_enabled(
+    app: AppHandle,
+    enabled: bool,
+    port: Option<u16>,
+) -> Result<LocalApiStatus, String> {
+    let mut config = load_config(&app);
+    config.enabled = enabled;
+    if let Some(port) = port {
+        if port == 0 {
+            return Err("Port must be between 1 and 65535".to_string());
+        }
+        config.port = port;
+    } else if enabled {
+        resolve_port(&app, &mut config)?;
+    }
+
+    if enabled {
+        start_server(app.clone(), config.port).await?;
+    } else {
+        stop_server();
+    }
+
+    settings_store::set(&app, CONFIG_SETTING, &config)?;
+    Ok(status(config))
+}
+
+/// Serve Prometheus metrics at `/metrics` on the local API. Scrapers need a
+/// capability token with the `metrics:read` scope, sent as a bearer token.
+#[tauri::command]
+pub async fn set_metrics_exporter_enabled(app: AppHandle, enabled: bool) -> Result<LocalApiStatus, String> {
+    let config = settings_store::update(&app, CONFIG_SETTING, |config: &mut LocalApiConfig| {
+        let changed = config.metrics_enabled != enabled;
+        config.metrics_enabled = enabled;
+        Ok(changed)
+    })?;
+    log::info!("Metrics exporter {}", if enabled { "enabled" } else { "disabled" });
+    Ok(status(config))
+}
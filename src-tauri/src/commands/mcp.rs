@@ -656,6 +656,37 @@ pub async fn mcp_serve(app: AppHandle) -> Result<String, String> {
     }
 }
 
+/// Registers the workbench's project memory as a user-scoped stdio MCP server, so
+/// every session can read and write it. Re-registering replaces the old entry.
+#[tauri::command]
+pub async fn mcp_register_memory_server(app: AppHandle) -> Result<String, String> {
+    use super::memory_mcp::{MEMORY_SERVER_ARG, MEMORY_SERVER_NAME};
+
+    info!("Registering memory MCP server");
+
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to locate app binary: {}", e))?;
+    let exe = exe.to_string_lossy().to_string();
+    let db_path = super::memory::memory_db_path(&app)?;
+    let db_path = db_path.to_string_lossy().to_string();
+
+    // Drop a stale registration (e.g. after the app moved); a missing one is fine
+    let _ = execute_claude_mcp_command(&app, vec!["remove", "-s", "user", MEMORY_SERVER_NAME]);
+
+    match execute_claude_mcp_command(
+        &app,
+        vec!["add", "-s", "user", MEMORY_SERVER_NAME, "--", &exe, MEMORY_SERVER_ARG, "--db", &db_path],
+    ) {
+        Ok(_) => {
+            info!("Registered memory MCP server as {}", MEMORY_SERVER_NAME);
+            Ok(MEMORY_SERVER_NAME.to_string())
+        }
+        Err(e) => {
+            error!("Failed to register memory MCP server: {}", e);
+            Err(e.to_string())
+        }
+    }
+}
+
 /// Tests connection to an MCP server
 #[tauri::command]
 pub async fn mcp_test_connection(app: AppHandle, name: String) -> Result<String, String> {
@@ -749,7 +780,7 @@ pub async fn mcp_read_project_config(project_path: String) -> Result<MCPProjectC
         });
     }
 
-    match fs::read_to_string(&mcp_json_path) {
+    match crate::config_file::read_to_string(&mcp_json_path) {
         Ok(content) => match serde_json::from_str::<MCPProjectConfig>(&content) {
             Ok(config) => Ok(config),
             Err(e) => {
@@ -777,7 +808,7 @@ pub async fn mcp_save_project_config(
     let json_content = serde_json::to_string_pretty(&config)
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
 
-    fs::write(&mcp_json_path, json_content)
+    crate::config_file::write(&mcp_json_path, json_content)
         .map_err(|e| format!("Failed to write .mcp.json: {}", e))?;
 
     Ok("Project MCP configuration saved".to_string())
@@ -0,0 +1,230 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use super::agents::AgentDb;
+use super::claude::normalize_path_for_comparison;
+
+/// Most entries returned by a listing
+const MAX_LISTED_ENTRIES: i64 = 500;
+
+/// Longest value or note accepted, in characters
+pub const MAX_MEMORY_CONTENT_CHARS: usize = 16 * 1024;
+
+/// A remembered value (with a key) or free-form note (without) of one project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryEntry {
+    pub id: i64,
+    pub project_path: String,
+    /// Set for key-value entries; notes have none
+    pub key: Option<String>,
+    pub content: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Create the memory table; keys are unique per project, notes are not keyed
+pub fn init_memory_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS memory_entries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_path TEXT NOT NULL,
+            key TEXT,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_memory_entries_key
+         ON memory_entries(project_path, key) WHERE key IS NOT NULL",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Project paths are stored normalized so the app and the MCP server agree on them
+pub fn memory_project_key(project_path: &str) -> String {
+    normalize_path_for_comparison(project_path)
+}
+
+fn check_content(content: &str) -> Result<(), String> {
+    if content.trim().is_empty() {
+        return Err("Memory content is empty".to_string());
+    }
+    if content.chars().count() > MAX_MEMORY_CONTENT_CHARS {
+        return Err(format!(
+            "Memory content is longer than {} characters",
+            MAX_MEMORY_CONTENT_CHARS
+        ));
+    }
+    Ok(())
+}
+
+fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<MemoryEntry> {
+    Ok(MemoryEntry {
+        id: row.get(0)?,
+        project_path: row.get(1)?,
+        key: row.get(2)?,
+        content: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+const ENTRY_COLUMNS: &str = "id, project_path, key, content, created_at, updated_at";
+
+fn get_entry(conn: &Connection, id: i64) -> Result<MemoryEntry, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM memory_entries WHERE id = ?1", ENTRY_COLUMNS),
+        params![id],
+        row_to_entry,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Memory entry {} not found", id))
+}
+
+/// List a project's entries, keyed values first; `query` filters keys and content
+pub fn list_entries(conn: &Connection, project_path: &str, query: Option<&str>) -> Result<Vec<MemoryEntry>, String> {
+    let pattern = format!("%{}%", query.unwrap_or_default().trim());
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM memory_entries
+             WHERE project_path = ?1 AND (key LIKE ?2 OR content LIKE ?2)
+             ORDER BY key IS NULL, key, updated_at DESC LIMIT ?3",
+            ENTRY_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let entries = stmt
+        .query_map(params![memory_project_key(project_path), pattern, MAX_LISTED_ENTRIES], row_to_entry)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(entries)
+}
+
+pub fn get_value(conn: &Connection, project_path: &str, key: &str) -> Result<Option<MemoryEntry>, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM memory_entries WHERE project_path = ?1 AND key = ?2",
+            ENTRY_COLUMNS
+        ),
+        params![memory_project_key(project_path), key.trim()],
+        row_to_entry,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Insert or overwrite the value stored under `key`
+pub fn set_value(conn: &Connection, project_path: &str, key: &str, content: &str) -> Result<MemoryEntry, String> {
+    if key.trim().is_empty() {
+        return Err("Memory key is empty".to_string());
+    }
+    check_content(content)?;
+    let project = memory_project_key(project_path);
+    conn.execute(
+        "INSERT INTO memory_entries (project_path, key, content) VALUES (?1, ?2, ?3)
+         ON CONFLICT(project_path, key) WHERE key IS NOT NULL
+         DO UPDATE SET content = excluded.content, updated_at = CURRENT_TIMESTAMP",
+        params![project, key.trim(), content],
+    )
+    .map_err(|e| e.to_string())?;
+    get_value(conn, project_path, key)?.ok_or_else(|| "Failed to store memory value".to_string())
+}
+
+pub fn add_note(conn: &Connection, project_path: &str, content: &str) -> Result<MemoryEntry, String> {
+    check_content(content)?;
+    conn.execute(
+        "INSERT INTO memory_entries (project_path, content) VALUES (?1, ?2)",
+        params![memory_project_key(project_path), content],
+    )
+    .map_err(|e| e.to_string())?;
+    get_entry(conn, conn.last_insert_rowid())
+}
+
+pub fn update_entry(conn: &Connection, id: i64, content: &str) -> Result<MemoryEntry, String> {
+    check_content(content)?;
+    conn.execute(
+        "UPDATE memory_entries SET content = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+        params![content, id],
+    )
+    .map_err(|e| e.to_string())?;
+    get_entry(conn, id)
+}
+
+/// Delete an entry by id; returns whether it existed
+pub fn delete_entry(conn: &Connection, id: i64) -> Result<bool, String> {
+    conn.execute("DELETE FROM memory_entries WHERE id = ?1", params![id])
+        .map(|deleted| deleted > 0)
+        .map_err(|e| e.to_string())
+}
+
+/// Delete the value stored under `key`; returns whether it existed
+pub fn delete_value(conn: &Connection, project_path: &str, key: &str) -> Result<bool, String> {
+    conn.execute(
+        "DELETE FROM memory_entries WHERE project_path = ?1 AND key = ?2",
+        params![memory_project_key(project_path), key.trim()],
+    )
+    .map(|deleted| deleted > 0)
+    .map_err(|e| e.to_string())
+}
+
+/// Path of the database the memory MCP server opens
+pub fn memory_db_path(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("agents.db"))
+}
+
+/// List a project's memory entries; `query` filters keys and content
+#[tauri::command]
+pub async fn list_memory_entries(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    query: Option<String>,
+) -> Result<Vec<MemoryEntry>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    list_entries(&conn, &project_path, query.as_deref())
+}
+
+/// Store a value under a key, replacing the previous one
+#[tauri::command]
+pub async fn set_memory_value(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    key: String,
+    content: String,
+) -> Result<MemoryEntry, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    set_value(&conn, &project_path, &key, &content)
+}
+
+/// Add a free-form note to a project's memory
+#[tauri::command]
+pub async fn add_memory_note(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    content: String,
+) -> Result<MemoryEntry, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    add_note(&conn, &project_path, &content)
+}
+
+/// Edit the content of a memory entry
+#[tauri::command]
+pub async fn update_memory_entry(db: State<'_, AgentDb>, id: i64, content: String) -> Result<MemoryEntry, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    update_entry(&conn, id, &content)
+}
+
+/// Delete a memory entry
+#[tauri::command]
+pub async fn delete_memory_entry(db: State<'_, AgentDb>, id: i64) -> Result<bool, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    delete_entry(&conn, id)
+}
@@ -0,0 +1,240 @@
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use super::knowledge_base::{format_context, query_chunks};
+use super::memory::{add_note, delete_value, get_value, list_entries, set_value};
+
+/// Argument that starts the binary as the memory MCP server instead of the app
+pub const MEMORY_SERVER_ARG: &str = "mcp-memory-server";
+
+/// Name the server is registered under with `claude mcp add`
+pub const MEMORY_SERVER_NAME: &str = "workbench-memory";
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// JSON-RPC error codes
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Run the memory MCP server when the process was started with
+/// `mcp-memory-server [--db <path>] [--project <path>]`; returns the exit code.
+/// Returns `None` for a normal app launch.
+pub fn run_from_args() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) != Some(MEMORY_SERVER_ARG) {
+        return None;
+    }
+    let flag = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+            .cloned()
+    };
+
+    let Some(db_path) = flag("--db").map(PathBuf::from) else {
+        eprintln!("{}: --db <path> is required", MEMORY_SERVER_ARG);
+        return Some(2);
+    };
+    // Claude Code starts stdio servers in the project directory
+    let project = flag("--project")
+        .or_else(|| std::env::var("CLAUDE_PROJECT_DIR").ok())
+        .or_else(|| std::env::current_dir().ok().map(|dir| dir.to_string_lossy().to_string()))
+        .unwrap_or_default();
+
+    let conn = match super::db_pool::open_connection(&db_path).and_then(|conn| {
+        super::db_migrations::migrate(&conn)?;
+        Ok(conn)
+    }) {
+        Ok(conn) => conn,
+        Err(e) => {
+            eprintln!("{}: failed to open {:?}: {}", MEMORY_SERVER_ARG, db_path, e);
+            return Some(1);
+        }
+    };
+
+    match serve(&conn, &project) {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("{}: {}", MEMORY_SERVER_ARG, e);
+            Some(1)
+        }
+    }
+}
+
+/// Answer newline-delimited JSON-RPC requests on stdin until it closes
+fn serve(conn: &Connection, project: &str) -> std::io::Result<()> {
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => handle(conn, project, &request),
+            Err(e) => Some(json!({
+                "jsonrpc": "2.0",
+                "id": Value::Null,
+                "error": { "code": -32700, "message": format!("Parse error: {}", e) },
+            })),
+        };
+        if let Some(response) = response {
+            writeln!(stdout, "{}", response)?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Response to one request; notifications (no `id`) get none
+fn handle(conn: &Connection, project: &str, request: &Value) -> Option<Value> {
+    let id = request.get("id").cloned()?;
+    let method = request.get("method").and_then(|m| m.as_str()).unwrap_or_default();
+    let params = request.get("params").cloned().unwrap_or_else(|| json!({}));
+
+    let result = match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": MEMORY_SERVER_NAME, "version": env!("CARGO_PKG_VERSION") },
+            "instructions": "Persistent memory for this project. Store decisions, conventions and \
+                user preferences worth keeping across conversations, and check it before asking \
+                the user something they may already have answered. Use knowledge_search to look \
+                things up in the project's documentation.",
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+        "tools/call" => call_tool(conn, project, &params),
+        _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+    };
+
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }),
+    })
+}
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "memory_set",
+            "description": "Remember a value under a key for this project, replacing any previous value",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "key": { "type": "string", "description": "Short identifier, e.g. \"test-command\"" },
+                    "value": { "type": "string" },
+                },
+                "required": ["key", "value"],
+            },
+        },
+        {
+            "name": "memory_get",
+            "description": "Read the value stored under a key for this project",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "key": { "type": "string" } },
+                "required": ["key"],
+            },
+        },
+        {
+            "name": "memory_delete",
+            "description": "Forget the value stored under a key for this project",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "key": { "type": "string" } },
+                "required": ["key"],
+            },
+        },
+        {
+            "name": "memory_add_note",
+            "description": "Add a free-form note (a decision, preference or lesson) to this project's memory",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "note": { "type": "string" } },
+                "required": ["note"],
+            },
+        },
+        {
+            "name": "memory_list",
+            "description": "List this project's remembered values and notes, optionally filtered by text",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "query": { "type": "string" } },
+            },
+        },
+        {
+            "name": "knowledge_search",
+            "description": "Search this project's ingested documentation for passages relevant to a question",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "question": { "type": "string" },
+                    "k": { "type": "integer", "description": "Number of passages to return, default 5" },
+                },
+                "required": ["question"],
+            },
+        },
+    ])
+}
+
+fn call_tool(conn: &Connection, project: &str, params: &Value) -> Result<Value, (i64, String)> {
+    let name = params.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+    let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+    let arg = |key: &str| {
+        arguments
+            .get(key)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| (INVALID_PARAMS, format!("Missing argument '{}' for {}", key, name)))
+    };
+
+    let outcome = match name {
+        "memory_set" => set_value(conn, project, arg("key")?, arg("value")?)
+            .map(|entry| format!("Stored {}", entry.key.unwrap_or_default())),
+        "memory_get" => get_value(conn, project, arg("key")?).map(|entry| match entry {
+            Some(entry) => entry.content,
+            None => format!("Nothing stored under {}", arg("key").unwrap_or_default()),
+        }),
+        "memory_delete" => delete_value(conn, project, arg("key")?).map(|deleted| {
+            if deleted {
+                "Deleted".to_string()
+            } else {
+                "Nothing stored under that key".to_string()
+            }
+        }),
+        "memory_add_note" => add_note(conn, project, arg("note")?).map(|entry| format!("Added note {}", entry.id)),
+        "memory_list" => list_entries(conn, project, arguments.get("query").and_then(|q| q.as_str())).map(|entries| {
+            if entries.is_empty() {
+                return "No memory stored for this project".to_string();
+            }
+            entries
+                .iter()
+                .map(|entry| match &entry.key {
+                    Some(key) => format!("- {}: {}", key, entry.content),
+                    None => format!("- (note {}) {}", entry.id, entry.content),
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }),
+        "knowledge_search" => {
+            let k = arguments.get("k").and_then(|k| k.as_u64()).unwrap_or(5) as usize;
+            query_chunks(conn, project, arg("question")?, k).map(|chunks| {
+                if chunks.is_empty() {
+                    "No relevant passages in the project's ingested docs".to_string()
+                } else {
+                    format_context(&chunks)
+                }
+            })
+        }
+        _ => return Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+    };
+
+    // Tool failures are reported to the model as results, not protocol errors
+    Ok(match outcome {
+        Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+        Err(e) => json!({ "content": [{ "type": "text", "text": e }], "isError": true }),
+    })
+}
@@ -0,0 +1,227 @@
+use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use super::usage_writer::UsageRow;
+use crate::process::ProcessRegistryState;
+
+/// Upper bounds, in seconds, of the command latency histogram buckets
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Window of the tokens-per-minute gauge
+const TOKEN_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// How long the router gets to accept a connection before it counts as down
+const ROUTER_PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Default)]
+struct TokenCounters {
+    input: u64,
+    output: u64,
+    cache_creation: u64,
+    cache_read: u64,
+    /// Tokens recorded in the last `TOKEN_RATE_WINDOW`
+    recent: VecDeque<(Instant, u64)>,
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// Cumulative counts, one per bucket in `LATENCY_BUCKETS`
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+static TOKENS: Lazy<Mutex<TokenCounters>> = Lazy::new(|| Mutex::new(TokenCounters::default()));
+
+/// Latencies by command and outcome (`ok` or `error`)
+static LATENCIES: Lazy<Mutex<BTreeMap<(String, &'static str), Histogram>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+fn prune_recent(recent: &mut VecDeque<(Instant, u64)>, now: Instant) {
+    while recent
+        .front()
+        .map_or(false, |(at, _)| now.duration_since(*at) > TOKEN_RATE_WINDOW)
+    {
+        recent.pop_front();
+    }
+}
+
+/// Count the tokens of a usage row as it is recorded
+pub fn record_tokens(row: &UsageRow) {
+    let Ok(mut tokens) = TOKENS.lock() else {
+        return;
+    };
+    tokens.input += row.input_tokens;
+    tokens.output += row.output_tokens;
+    tokens.cache_creation += row.cache_creation_tokens;
+    tokens.cache_read += row.cache_read_tokens;
+    let now = Instant::now();
+    tokens.recent.push_back((now, row.input_tokens + row.output_tokens));
+    prune_recent(&mut tokens.recent, now);
+}
+
+/// Record how long a command took
+pub fn record_latency(command: &str, ok: bool, elapsed: Duration) {
+    let Ok(mut latencies) = LATENCIES.lock() else {
+        return;
+    };
+    let histogram = latencies
+        .entry((command.to_string(), if ok { "ok" } else { "error" }))
+        .or_insert_with(|| Histogram {
+            buckets: vec![0; LATENCY_BUCKETS.len()],
+            ..Default::default()
+        });
+    let seconds = elapsed.as_secs_f64();
+    for (count, bound) in histogram.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+        if seconds <= *bound {
+            *count += 1;
+        }
+    }
+    histogram.sum += seconds;
+    histogram.count += 1;
+}
+
+/// Whether claude-code-router accepts connections on its configured address; not
+/// probed when the router subsystem is disabled
+fn router_up() -> bool {
+    if !super::startup::is_enabled(super::startup::Subsystem::Router) {
+        return false;
+    }
+    let (host, port) = super::router_usage::router_address();
+    (host.as_str(), port)
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .map_or(false, |addr| TcpStream::connect_timeout(&addr, ROUTER_PROBE_TIMEOUT).is_ok())
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, samples: &[(String, String)]) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    for (labels, value) in samples {
+        if labels.is_empty() {
+            let _ = writeln!(out, "{} {}", name, value);
+        } else {
+            let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+        }
+    }
+}
+
+/// Render all metrics in the Prometheus text exposition format (version 0.0.4).
+///
+/// Probes the router with a short TCP connect, so call it off the async runtime.
+pub fn render(app: &AppHandle) -> String {
+    let mut out = String::new();
+
+    let (claude_sessions, agent_runs) = {
+        let registry = app.state::<ProcessRegistryState>();
+        (
+            registry.0.get_running_claude_sessions().map(|s| s.len()).unwrap_or(0),
+            registry.0.get_running_agent_processes().map(|p| p.len()).unwrap_or(0),
+        )
+    };
+    write_metric(
+        &mut out,
+        "claude_workbench_active_sessions",
+        "gauge",
+        "Running Claude processes by kind.",
+        &[
+            ("kind=\"session\"".to_string(), claude_sessions.to_string()),
+            ("kind=\"agent\"".to_string(), agent_runs.to_string()),
+        ],
+    );
+
+    let (totals, per_minute) = match TOKENS.lock() {
+        Ok(mut tokens) => {
+            prune_recent(&mut tokens.recent, Instant::now());
+            (
+                [
+                    ("input", tokens.input),
+                    ("output", tokens.output),
+                    ("cache_creation", tokens.cache_creation),
+                    ("cache_read", tokens.cache_read),
+                ],
+                tokens.recent.iter().map(|(_, n)| n).sum::<u64>(),
+            )
+        }
+        Err(_) => ([("input", 0), ("output", 0), ("cache_creation", 0), ("cache_read", 0)], 0),
+    };
+    write_metric(
+        &mut out,
+        "claude_workbench_tokens_total",
+        "counter",
+        "Tokens recorded since the app started, by type.",
+        &totals
+            .iter()
+            .map(|(kind, n)| (format!("type=\"{}\"", kind), n.to_string()))
+            .collect::<Vec<_>>(),
+    );
+    write_metric(
+        &mut out,
+        "claude_workbench_tokens_per_minute",
+        "gauge",
+        "Input and output tokens recorded in the last minute.",
+        &[(String::new(), per_minute.to_string())],
+    );
+
+    write_metric(
+        &mut out,
+        "claude_workbench_queue_depth",
+        "gauge",
+        "Items waiting in the app's queues.",
+        &[
+            (
+                "queue=\"prompts\"".to_string(),
+                super::prompt_queue::queued_prompt_count().to_string(),
+            ),
+            (
+                "queue=\"usage_writes\"".to_string(),
+                super::usage_writer::pending_row_count().to_string(),
+            ),
+        ],
+    );
+
+    let mut buckets = Vec::new();
+    let mut sums = Vec::new();
+    let mut counts = Vec::new();
+    if let Ok(latencies) = LATENCIES.lock() {
+        for ((command, outcome), histogram) in latencies.iter() {
+            let labels = format!("command=\"{}\",outcome=\"{}\"", escape_label(command), outcome);
+            for (count, bound) in histogram.buckets.iter().zip(LATENCY_BUCKETS) {
+                buckets.push((format!("{},le=\"{}\"", labels, bound), count.to_string()));
+            }
+            buckets.push((format!("{},le=\"+Inf\"", labels), histogram.count.to_string()));
+            sums.push((labels.clone(), histogram.sum.to_string()));
+            counts.push((labels, histogram.count.to_string()));
+        }
+    }
+    let _ = writeln!(
+        out,
+        "# HELP claude_workbench_command_duration_seconds Time taken by commands invoked through the local API."
+    );
+    let _ = writeln!(out, "# TYPE claude_workbench_command_duration_seconds histogram");
+    for (suffix, samples) in [("_bucket", &buckets), ("_sum", &sums), ("_count", &counts)] {
+        for (labels, value) in samples {
+            let _ = writeln!(out, "claude_workbench_command_duration_seconds{}{{{}}} {}", suffix, labels, value);
+        }
+    }
+
+    write_metric(
+        &mut out,
+        "claude_workbench_router_up",
+        "gauge",
+        "Whether claude-code-router accepts connections (1) or not (0).",
+        &[(String::new(), if router_up() { "1" } else { "0" }.to_string())],
+    );
+
+    out
+}
@@ -0,0 +1,9 @@
+-- 移除子代理专业化系统的表和agents表的专业化字段
+DROP INDEX IF EXISTS idx_routing_log_specialty;
+DROP INDEX IF EXISTS idx_agents_specialty;
+DROP TABLE IF EXISTS subagent_routing_log;
+DROP TABLE IF EXISTS subagent_specialties;
+ALTER TABLE agents DROP COLUMN auto_invoke;
+ALTER TABLE agents DROP COLUMN routing_keywords;
+ALTER TABLE agents DROP COLUMN specialty_config;
+ALTER TABLE agents DROP COLUMN specialty;
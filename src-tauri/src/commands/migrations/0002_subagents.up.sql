@@ -1,12 +1,8 @@
 -- Subagents专业化系统 - 数据库Schema扩展
 -- 这个文件定义了子代理专业化所需的数据库结构
 
--- 扩展agents表，添加专业化字段
--- 注意：使用ALTER TABLE来保持向后兼容性
-ALTER TABLE agents ADD COLUMN specialty TEXT DEFAULT 'general';
-ALTER TABLE agents ADD COLUMN specialty_config TEXT; -- JSON配置：工具权限、触发条件等
-ALTER TABLE agents ADD COLUMN routing_keywords TEXT; -- JSON数组：用于智能路由的关键词
-ALTER TABLE agents ADD COLUMN auto_invoke BOOLEAN DEFAULT 0; -- 是否自动调用
+-- agents表的专业化字段（specialty、specialty_config、routing_keywords、auto_invoke）
+-- 由 subagents::add_specialty_columns 在本文件之前添加，因为旧版本可能已经添加过
 
 -- 创建子代理专业化配置表
 CREATE TABLE IF NOT EXISTS subagent_specialties (
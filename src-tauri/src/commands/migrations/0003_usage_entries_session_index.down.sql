@@ -0,0 +1 @@
+DROP INDEX IF EXISTS idx_usage_entries_session;
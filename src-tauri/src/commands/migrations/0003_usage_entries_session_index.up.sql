@@ -0,0 +1,2 @@
+-- Usage is summed per session, e.g. for the totals of a task
+CREATE INDEX IF NOT EXISTS idx_usage_entries_session ON usage_entries(session_id);
@@ -0,0 +1 @@
+DROP TABLE prompt_templates;
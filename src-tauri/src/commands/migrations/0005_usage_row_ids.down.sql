@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_usage_entries_row_id;
+ALTER TABLE usage_entries DROP COLUMN row_id;
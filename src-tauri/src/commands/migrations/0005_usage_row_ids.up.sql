@@ -0,0 +1,3 @@
+-- Rows replayed from the usage journal after a crash are recognized by their id
+ALTER TABLE usage_entries ADD COLUMN row_id TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_usage_entries_row_id ON usage_entries(row_id);
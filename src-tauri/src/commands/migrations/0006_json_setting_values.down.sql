@@ -0,0 +1,8 @@
+UPDATE app_settings SET value = json_extract(value, '$')
+WHERE json_valid(value) AND json_type(value) = 'text' AND key IN (
+    'claude_binary_path',
+    'operator_id',
+    'path_case_sensitivity',
+    'accessibility_verbosity',
+    'weekly_digest_last_sent'
+);
@@ -0,0 +1,10 @@
+-- Settings are read and written through the settings store, which keeps JSON values;
+-- these keys held bare strings before
+UPDATE app_settings SET value = json_quote(value)
+WHERE key IN (
+    'claude_binary_path',
+    'operator_id',
+    'path_case_sensitivity',
+    'accessibility_verbosity',
+    'weekly_digest_last_sent'
+);
@@ -13,3 +13,83 @@ pub mod context_commands;
 pub mod subagents;
 pub mod enhanced_hooks;
 pub mod message_operations;
+pub mod recent_sessions;
+pub mod session_health;
+pub mod workspace_trust;
+pub mod file_watcher;
+pub mod session_handoff;
+pub mod agent_delegation;
+pub mod global_search;
+pub mod time_tracking;
+pub mod weekly_digest;
+pub mod output_batching;
+pub mod db_pool;
+pub mod usage_writer;
+pub mod events;
+pub mod session_meta;
+pub mod project_scan;
+pub mod model_aliases;
+pub mod provider_env;
+pub mod agent_downloads;
+pub mod secrets;
+pub mod github_client;
+pub mod router_usage;
+pub mod currency;
+pub mod team_usage;
+pub mod session_lock;
+pub mod api_manifest;
+pub mod session_kind;
+pub mod safety;
+pub mod prompt_queue;
+pub mod prompt_suggestions;
+pub mod session_templates;
+pub mod event_subscriptions;
+pub mod session_export;
+pub mod claude_md_lint;
+pub mod hook_packs;
+pub mod memory;
+pub mod memory_mcp;
+pub mod knowledge_base;
+pub mod execution_profiles;
+pub mod spawn_diagnostics;
+pub mod project_matching;
+pub mod session_import;
+pub mod backup;
+pub mod capability_tokens;
+pub mod local_api;
+pub mod metrics;
+pub mod burn_rate;
+pub mod run_dedupe;
+pub mod run_status;
+pub mod tasks;
+pub mod crash_reports;
+pub mod startup;
+pub mod plugins;
+pub mod worktree;
+pub mod stale_state;
+pub mod context_exclusion;
+pub mod response_language;
+pub mod agent_schedules;
+pub mod accessibility;
+pub mod power;
+pub mod project_execution_config;
+pub mod session_history;
+pub mod fault_injection;
+pub mod file_edits;
+pub mod project_gitignore;
+pub mod provider_keys;
+pub mod cost_cap;
+pub mod workbench_settings;
+pub mod prompt_cost;
+pub mod scheduled_prompts;
+pub mod unseen_completions;
+pub mod hook_templates;
+pub mod settings_store;
+pub mod env_profiles;
+pub mod agent_results;
+pub mod project_bootstrap;
+pub mod db_migrations;
+pub mod history_filter;
+pub mod prompt_templates;
+pub mod service_ports;
+pub mod router_validation;
@@ -0,0 +1,155 @@
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use super::agents::AgentDb;
+
+/// Aliases shipped with the app, seeded into `model_aliases` on first start.
+/// `sonnet` and `opus` let the CLI resolve to the latest model of that family.
+const BUILTIN_ALIASES: &[(&str, &str, &str)] = &[
+    ("sonnet1m", "sonnet[1m]", "Sonnet with the 1M token context window"),
+    ("sonnet", "sonnet", "Latest Sonnet model"),
+    ("opus", "opus", "Latest Opus model"),
+];
+
+/// Maps a model ID used by the frontend to the value passed to `claude --model`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModelAlias {
+    pub alias: String,
+    pub target: String,
+    pub description: Option<String>,
+    /// Seeded by the app; can be edited but is restored if deleted
+    pub builtin: bool,
+    pub updated_at: String,
+}
+
+fn row_to_model_alias(row: &rusqlite::Row) -> SqliteResult<ModelAlias> {
+    Ok(ModelAlias {
+        alias: row.get(0)?,
+        target: row.get(1)?,
+        description: row.get(2)?,
+        builtin: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+/// Insert the built-in aliases that are missing, leaving user edits untouched
+pub fn seed_builtin_aliases(conn: &Connection) -> SqliteResult<()> {
+    for (alias, target, description) in BUILTIN_ALIASES {
+        conn.execute(
+            "INSERT OR IGNORE INTO model_aliases (alias, target, description, builtin) VALUES (?1, ?2, ?3, 1)",
+            params![alias, target, description],
+        )?;
+    }
+    Ok(())
+}
+
+/// Look up the CLI model for an alias in the table
+pub fn lookup_model_alias(conn: &Connection, model: &str) -> SqliteResult<Option<String>> {
+    conn.query_row(
+        "SELECT target FROM model_aliases WHERE alias = ?1",
+        params![model],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Resolve a frontend model ID to a Claude CLI model.
+///
+/// Unknown models are passed through unchanged so full model names keep working.
+/// Falls back to the built-in table when the database is unavailable.
+pub fn resolve_model_alias(app: &AppHandle, model: &str) -> String {
+    let resolved = app.try_state::<AgentDb>().and_then(|db| {
+        let conn = db.0.get().ok()?;
+        Some(lookup_model_alias(&conn, model))
+    });
+
+    match resolved {
+        Some(Ok(Some(target))) => target,
+        Some(Ok(None)) => model.to_string(),
+        Some(Err(e)) => {
+            log::warn!("Failed to look up model alias {}: {}", model, e);
+            builtin_target(model)
+        }
+        None => builtin_target(model),
+    }
+}
+
+fn builtin_target(model: &str) -> String {
+    BUILTIN_ALIASES
+        .iter()
+        .find(|(alias, _, _)| *alias == model)
+        .map(|(_, target, _)| target.to_string())
+        .unwrap_or_else(|| model.to_string())
+}
+
+/// List all model aliases
+#[tauri::command]
+pub async fn list_model_aliases(db: State<'_, AgentDb>) -> Result<Vec<ModelAlias>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT alias, target, description, builtin, updated_at FROM model_aliases ORDER BY builtin DESC, alias")
+        .map_err(|e| e.to_string())?;
+
+    let aliases = stmt
+        .query_map([], row_to_model_alias)
+        .map_err(|e| e.to_string())?
+        .collect::<SqliteResult<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+    Ok(aliases)
+}
+
+/// Add a model alias or change where an existing one points
+#[tauri::command]
+pub async fn upsert_model_alias(
+    db: State<'_, AgentDb>,
+    alias: String,
+    target: String,
+    description: Option<String>,
+) -> Result<ModelAlias, String> {
+    let alias = alias.trim().to_string();
+    let target = target.trim().to_string();
+    if alias.is_empty() || target.is_empty() {
+        return Err("Alias and target model must not be empty".to_string());
+    }
+    if target.chars().any(char::is_whitespace) {
+        return Err(format!("Invalid target model: {}", target));
+    }
+
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO model_aliases (alias, target, description, builtin)
+         VALUES (?1, ?2, ?3, 0)
+         ON CONFLICT(alias) DO UPDATE SET
+             target = excluded.target,
+             description = excluded.description,
+             updated_at = CURRENT_TIMESTAMP",
+        params![alias, target, description],
+    )
+    .map_err(|e| e.to_string())?;
+
+    log::info!("Model alias {} now maps to {}", alias, target);
+
+    conn.query_row(
+        "SELECT alias, target, description, builtin, updated_at FROM model_aliases WHERE alias = ?1",
+        params![alias],
+        row_to_model_alias,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Delete a model alias. Built-in aliases are reset to their default target instead.
+#[tauri::command]
+pub async fn delete_model_alias(db: State<'_, AgentDb>, alias: String) -> Result<(), String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let deleted = conn
+        .execute("DELETE FROM model_aliases WHERE alias = ?1", params![alias])
+        .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Model alias not found: {}", alias));
+    }
+
+    seed_builtin_aliases(&conn).map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use ts_rs::TS;
+
+use super::event_subscriptions;
+
+/// Per-session batching configuration, keyed by Claude session ID or `agent-run-<id>`
+static BATCH_CONFIGS: Lazy<Mutex<HashMap<String, OutputBatchConfig>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Default configuration used when a session has none of its own
+static DEFAULT_BATCH_CONFIG: Lazy<Mutex<OutputBatchConfig>> =
+    Lazy::new(|| Mutex::new(OutputBatchConfig::default()));
+
+/// How stream output lines are delivered to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputBatchConfig {
+    /// When false ("raw mode") every line is emitted as its own event, as before batching existed
+    pub enabled: bool,
+    /// Maximum time a line waits before its batch is flushed
+    pub flush_interval_ms: u64,
+    /// Flush early once a batch holds this many lines
+    pub max_batch_lines: usize,
+    /// Flush early once a batch holds this many bytes
+    pub max_batch_bytes: usize,
+}
+
+impl Default for OutputBatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            flush_interval_ms: 40,
+            max_batch_lines: 200,
+            max_batch_bytes: 256 * 1024,
+        }
+    }
+}
+
+impl OutputBatchConfig {
+    /// Configuration for a session, falling back to the default
+    pub fn for_key(key: Option<&str>) -> Self {
+        if let Some(key) = key {
+            if let Some(config) = BATCH_CONFIGS.lock().ok().and_then(|c| c.get(key).cloned()) {
+                return config;
+            }
+        }
+        DEFAULT_BATCH_CONFIG
+            .lock()
+            .map(|c| c.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Payload of a batched output event
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct OutputBatch {
+    /// Lines in emission order
+    pub lines: Vec<String>,
+    /// Per-event sequence number, lets listeners detect gaps
+    #[ts(type = "number")]
+    pub seq: u64,
+}
+
+/// Name of the batched counterpart of an output event:
+/// `claude-output:<id>` -> `claude-output-batch:<id>`, `agent-output` -> `agent-output-batch`
+pub fn batch_event_name(event: &str) -> String {
+    match event.split_once(':') {
+        Some((base, scope)) => format!("{}-batch:{}", base, scope),
+        None => format!("{}-batch", event),
+    }
+}
+
+#[derive(Default)]
+struct PendingBatch {
+    lines: Vec<String>,
+    bytes: usize,
+    seq: u64,
+}
+
+/// Coalesces high-volume output lines into periodic batch events.
+///
+/// All lines go through a single task so emission order is exactly the push order,
+/// also when the session switches between batched and raw mode mid-stream.
+pub struct OutputBatcher {
+    sender: Option<mpsc::UnboundedSender<(String, String)>>,
+    task: Option<JoinHandle<()>>,
+    config_key: Arc<Mutex<Option<String>>>,
+}
+
+impl OutputBatcher {
+    pub fn spawn(app: AppHandle, config_key: Option<String>) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let config_key = Arc::new(Mutex::new(config_key));
+        let task = tokio::spawn(run_batcher(app, receiver, config_key.clone()));
+
+        Self {
+            sender: Some(sender),
+            task: Some(task),
+            config_key,
+        }
+    }
+
+    /// Set the key used to look up the batching configuration (e.g. once the session ID is known)
+    pub fn set_config_key(&self, key: String) {
+        if let Ok(mut current) = self.config_key.lock() {
+            *current = Some(key);
+        }
+    }
+
+    /// Queue a line for `event`
+    pub fn emit(&self, event: impl Into<String>, line: &str) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send((event.into(), line.to_string()));
+        }
+    }
+
+    /// Flush everything still pending and stop the batcher
+    pub async fn finish(mut self) {
+        self.sender.take();
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+fn flush(app: &AppHandle, event: &str, batch: &mut PendingBatch) {
+    if batch.lines.is_empty() {
+        return;
+    }
+    batch.seq += 1;
+    let payload = OutputBatch {
+        lines: std::mem::take(&mut batch.lines),
+        seq: batch.seq,
+    };
+    batch.bytes = 0;
+    let _ = event_subscriptions::emit(app, &batch_event_name(event), &payload);
+}
+
+async fn run_batcher(
+    app: AppHandle,
+    mut receiver: mpsc::UnboundedReceiver<(String, String)>,
+    config_key: Arc<Mutex<Option<String>>>,
+) {
+    // Batches per event name; a Vec keeps flush order stable across events
+    let mut pending: Vec<(String, PendingBatch)> = Vec::new();
+    let current_config = || {
+        let key = config_key.lock().ok().and_then(|k| k.clone());
+        OutputBatchConfig::for_key(key.as_deref())
+    };
+    let mut config = current_config();
+    // Flush deadline of the oldest pending line
+    let mut deadline: Option<tokio::time::Instant> = None;
+
+    loop {
+        let message = match deadline {
+            Some(at) => match tokio::time::timeout_at(at, receiver.recv()).await {
+                Ok(message) => message,
+                Err(_) => {
+                    for (event, batch) in pending.iter_mut() {
+                        flush(&app, event, batch);
+                    }
+                    deadline = None;
+                    config = current_config();
+                    continue;
+                }
+            },
+            None => {
+                let message = receiver.recv().await;
+                config = current_config();
+                message
+            }
+        };
+
+        let Some((event, line)) = message else {
+            break;
+        };
+
+        if !config.enabled {
+            // Raw mode: keep ordering by flushing anything batched before this line
+            for (event, batch) in pending.iter_mut() {
+                flush(&app, event, batch);
+            }
+            deadline = None;
+            let _ = event_subscriptions::emit(&app, &event, &line);
+            continue;
+        }
+
+        let index = match pending.iter().position(|(name, _)| *name == event) {
+            Some(index) => index,
+            None => {
+                pending.push((event.clone(), PendingBatch::default()));
+                pending.len() - 1
+            }
+        };
+        let (event, batch) = &mut pending[index];
+        batch.bytes += line.len();
+        batch.lines.push(line);
+        if batch.lines.len() >= config.max_batch_lines || batch.bytes >= config.max_batch_bytes {
+            flush(&app, event, batch);
+        }
+        if deadline.is_none() {
+            deadline = Some(tokio::time::Instant::now() + Duration::from_millis(config.flush_interval_ms));
+        }
+    }
+
+    for (event, batch) in pending.iter_mut() {
+        flush(&app, event, batch);
+    }
+}
+
+/// Configure output batching for a session (`agent-run-<id>` for agent runs),
+/// or the default for all sessions when `session_id` is omitted.
+/// Passing no config removes a session's override.
+#[tauri::command]
+pub async fn set_output_batching(
+    session_id: Option<String>,
+    config: Option<OutputBatchConfig>,
+) -> Result<OutputBatchConfig, String> {
+    if let Some(config) = &config {
+        if config.flush_interval_ms == 0 || config.max_batch_lines == 0 || config.max_batch_bytes == 0 {
+            return Err("Batch interval and size limits must be greater than zero".to_string());
+        }
+    }
+
+    match session_id {
+        Some(session_id) => {
+            let mut configs = BATCH_CONFIGS.lock().map_err(|e| e.to_string())?;
+            match config {
+                Some(config) => {
+                    configs.insert(session_id.clone(), config);
+                }
+                None => {
+                    configs.remove(&session_id);
+                }
+            }
+            drop(configs);
+            Ok(OutputBatchConfig::for_key(Some(&session_id)))
+        }
+        None => {
+            let mut default = DEFAULT_BATCH_CONFIG.lock().map_err(|e| e.to_string())?;
+            *default = config.unwrap_or_default();
+            Ok(default.clone())
+        }
+    }
+}
+
+/// Get the effective output batching configuration of a session (or the default)
+#[tauri::command]
+pub async fn get_output_batching(session_id: Option<String>) -> Result<OutputBatchConfig, String> {
+    Ok(OutputBatchConfig::for_key(session_id.as_deref()))
+}
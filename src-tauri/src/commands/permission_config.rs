@@ -1,5 +1,13 @@
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::claude::normalize_path_for_comparison;
+
+/// 危险跳过模式未指定过期时间时的有效时长（小时）
+pub const DANGEROUS_SKIP_DEFAULT_HOURS: i64 = 8;
+/// 危险跳过模式允许的最长有效时长（小时）
+pub const DANGEROUS_SKIP_MAX_HOURS: i64 = 24;
+
 /// Claude权限管理配置结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaudePermissionConfig {
@@ -8,6 +16,12 @@ pub struct ClaudePermissionConfig {
     pub permission_mode: PermissionMode,
     pub auto_approve_edits: bool,
     pub enable_dangerous_skip: bool, // 向后兼容选项
+    /// 危险跳过模式的过期时间，到期后自动关闭
+    #[serde(default)]
+    pub dangerous_skip_expires_at: Option<DateTime<Utc>>,
+    /// 危险跳过模式生效的项目路径，为空时不对任何项目生效
+    #[serde(default)]
+    pub dangerous_skip_projects: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -29,7 +43,9 @@ impl Default for ClaudePermissionConfig {
             disallowed_tools: vec![],
             permission_mode: PermissionMode::Interactive,
             auto_approve_edits: false,
-            enable_dangerous_skip: true, // 默认保持现有行为
+            enable_dangerous_skip: false, // 需按项目显式开启
+            dangerous_skip_expires_at: None,
+            dangerous_skip_projects: vec![],
         }
     }
 }
@@ -174,6 +190,8 @@ impl ClaudePermissionConfig {
             permission_mode: PermissionMode::AcceptEdits,
             auto_approve_edits: true,
             enable_dangerous_skip: false,
+            dangerous_skip_expires_at: None,
+            dangerous_skip_projects: vec![],
         }
     }
     
@@ -185,6 +203,8 @@ impl ClaudePermissionConfig {
             permission_mode: PermissionMode::ReadOnly,
             auto_approve_edits: false,
             enable_dangerous_skip: false,
+            dangerous_skip_expires_at: None,
+            dangerous_skip_projects: vec![],
         }
     }
     
@@ -200,6 +220,8 @@ impl ClaudePermissionConfig {
             permission_mode: PermissionMode::Interactive,
             auto_approve_edits: false,
             enable_dangerous_skip: false,
+            dangerous_skip_expires_at: None,
+            dangerous_skip_projects: vec![],
         }
     }
     
@@ -211,6 +233,57 @@ impl ClaudePermissionConfig {
             permission_mode: PermissionMode::Interactive,
             auto_approve_edits: false,
             enable_dangerous_skip: true,
+            dangerous_skip_expires_at: None,
+            dangerous_skip_projects: vec![],
+        }
+    }
+}
+
+/// 危险跳过模式的安全约束
+impl ClaudePermissionConfig {
+    /// 已开启但已过期
+    pub fn dangerous_skip_expired(&self) -> bool {
+        self.enable_dangerous_skip
+            && self.dangerous_skip_expires_at.map_or(true, |at| at <= Utc::now())
+    }
+
+    /// 是否对该项目生效：已开启、未过期且项目在作用范围内
+    pub fn dangerous_skip_applies_to(&self, project_path: &str) -> bool {
+        let normalized = normalize_path_for_comparison(project_path);
+        self.enable_dangerous_skip
+            && !self.dangerous_skip_expired()
+            && self
+                .dangerous_skip_projects
+                .iter()
+                .any(|p| normalize_path_for_comparison(p) == normalized)
+    }
+
+    /// 过期后自动关闭，返回是否发生了变更
+    pub fn revert_expired_dangerous_skip(&mut self) -> bool {
+        if !self.dangerous_skip_expired() {
+            return false;
+        }
+        self.enable_dangerous_skip = false;
+        self.dangerous_skip_expires_at = None;
+        true
+    }
+
+    /// 保存前校验：开启时必须限定项目，并设置不超过上限的过期时间
+    pub fn prepare_dangerous_skip(&mut self) -> Result<(), String> {
+        if !self.enable_dangerous_skip {
+            self.dangerous_skip_expires_at = None;
+            return Ok(());
         }
+        if self.dangerous_skip_projects.is_empty() {
+            return Err("危险跳过模式必须指定至少一个项目".to_string());
+        }
+        let now = Utc::now();
+        let max = now + Duration::hours(DANGEROUS_SKIP_MAX_HOURS);
+        self.dangerous_skip_expires_at = Some(match self.dangerous_skip_expires_at {
+            Some(at) if at <= now => return Err("危险跳过模式的过期时间必须晚于当前时间".to_string()),
+            Some(at) => at.min(max),
+            None => now + Duration::hours(DANGEROUS_SKIP_DEFAULT_HOURS),
+        });
+        Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,546 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use super::agents::AgentDb;
+use super::enhanced_hooks::{read_capped, HookContext, HookEvent};
+
+/// Version of the stdin/stdout protocol plugins speak; bumped on breaking changes
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// File describing a plugin, at the root of its directory under `plugins/`
+const MANIFEST_FILE: &str = "plugin.json";
+
+/// Seconds a plugin call may run unless its manifest says otherwise
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Longest timeout a manifest can ask for
+const MAX_TIMEOUT_SECS: u64 = 600;
+
+/// Largest response read from a plugin; anything beyond fails the call
+const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
+/// Stderr kept from a failed call for the error message
+const MAX_STDERR_BYTES: usize = 4096;
+
+/// Failed calls in a row after which a plugin is disabled until re-enabled
+const MAX_CONSECUTIVE_FAILURES: i64 = 3;
+
+/// Environment passed to native plugins besides their `env` permission
+const BASE_ENV: &[&str] = &["PATH", "HOME", "USERPROFILE", "SYSTEMROOT", "TEMP", "TMP", "LANG"];
+
+/// How a plugin's entry point is run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginRuntime {
+    /// An executable run as a child process with a scrubbed environment
+    Native,
+    /// A WASI module run by `wasmtime`, which grants no filesystem or network access
+    /// beyond what the permissions allow
+    Wasm,
+}
+
+/// What a plugin may access; enforced fully for WASM plugins, while native plugins
+/// only get the environment and working directory restrictions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginPermissions {
+    /// Receive the project path and, for WASM plugins, read and write it
+    #[serde(default)]
+    pub project_access: bool,
+    /// Open network connections (WASM plugins only; native plugins always can)
+    #[serde(default)]
+    pub network: bool,
+    /// Environment variables passed through
+    #[serde(default)]
+    pub env: Vec<String>,
+}
+
+/// A named entry point of a plugin, for a command or an analyzer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginEntry {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Contents of `plugin.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// Must equal `PLUGIN_ABI_VERSION`
+    pub abi_version: u32,
+    pub runtime: PluginRuntime,
+    /// Executable or `.wasm` module, relative to the plugin directory
+    pub entry: String,
+    /// Extra commands callable through `invoke_plugin_command`
+    #[serde(default)]
+    pub commands: Vec<PluginEntry>,
+    /// Hook events the plugin is notified of, e.g. `OnSessionEnd`
+    #[serde(default)]
+    pub hook_events: Vec<String>,
+    /// Project analyzers callable through `run_plugin_analyzer`
+    #[serde(default)]
+    pub analyzers: Vec<PluginEntry>,
+    #[serde(default)]
+    pub permissions: PluginPermissions,
+    pub timeout_secs: Option<u64>,
+}
+
+/// Returned by `list_plugins`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    /// Directory name under `plugins/`
+    pub directory: String,
+    /// None when `plugin.json` is missing or invalid; see `load_error`
+    pub manifest: Option<PluginManifest>,
+    pub load_error: Option<String>,
+    pub enabled: bool,
+    pub consecutive_failures: i64,
+    pub last_error: Option<String>,
+    /// Set when the plugin was disabled after failing repeatedly
+    pub auto_disabled: bool,
+}
+
+/// Result of a plugin call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginCallResult {
+    pub plugin_id: String,
+    pub name: String,
+    pub output: Value,
+    pub duration_ms: u64,
+}
+
+/// What a plugin prints to stdout in answer to a request
+#[derive(Debug, Deserialize)]
+struct PluginResponse {
+    ok: bool,
+    #[serde(default)]
+    output: Value,
+    error: Option<String>,
+}
+
+struct PluginState {
+    enabled: bool,
+    consecutive_failures: i64,
+    last_error: Option<String>,
+    auto_disabled: bool,
+}
+
+pub fn init_plugin_tables(conn: &Connection) -> rusqlite::Result<()> {
+    // Plugins are disabled until enabled here
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS plugin_state (
+            plugin_id TEXT PRIMARY KEY,
+            enabled INTEGER NOT NULL DEFAULT 0,
+            consecutive_failures INTEGER NOT NULL DEFAULT 0,
+            last_error TEXT,
+            auto_disabled INTEGER NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn plugins_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join("plugins"))
+        .map_err(|e| format!("Failed to get app data dir: {}", e))
+}
+
+fn load_manifest(dir: &Path) -> Result<PluginManifest, String> {
+    let content = fs::read_to_string(dir.join(MANIFEST_FILE)).map_err(|e| format!("Cannot read {}: {}", MANIFEST_FILE, e))?;
+    let manifest: PluginManifest =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid {}: {}", MANIFEST_FILE, e))?;
+
+    if manifest.abi_version != PLUGIN_ABI_VERSION {
+        return Err(format!(
+            "Plugin targets ABI version {}, this build supports {}",
+            manifest.abi_version, PLUGIN_ABI_VERSION
+        ));
+    }
+    if manifest.id.is_empty() || !manifest.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.') {
+        return Err(format!("Invalid plugin id '{}'", manifest.id));
+    }
+    let entry = Path::new(&manifest.entry);
+    if entry.is_absolute() || entry.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+        return Err(format!("Plugin entry '{}' must be inside the plugin directory", manifest.entry));
+    }
+    if !dir.join(entry).is_file() {
+        return Err(format!("Plugin entry '{}' not found", manifest.entry));
+    }
+    Ok(manifest)
+}
+
+/// Plugin directories with their manifest or why it failed to load
+fn discover(app: &AppHandle) -> Result<Vec<(String, PathBuf, Result<PluginManifest, String>)>, String> {
+    let dir = plugins_dir(app)?;
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut plugins: Vec<_> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| {
+            let path = entry.path();
+            (entry.file_name().to_string_lossy().to_string(), path.clone(), load_manifest(&path))
+        })
+        .collect();
+    plugins.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(plugins)
+}
+
+fn find_plugin(app: &AppHandle, plugin_id: &str) -> Result<(PathBuf, PluginManifest), String> {
+    discover(app)?
+        .into_iter()
+        .find_map(|(_, path, manifest)| manifest.ok().filter(|m| m.id == plugin_id).map(|m| (path, m)))
+        .ok_or_else(|| format!("Plugin '{}' not found", plugin_id))
+}
+
+fn load_states(conn: &Connection) -> Result<HashMap<String, PluginState>, String> {
+    let mut stmt = conn
+        .prepare("SELECT plugin_id, enabled, consecutive_failures, last_error, auto_disabled FROM plugin_state")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                PluginState {
+                    enabled: row.get(1)?,
+                    consecutive_failures: row.get(2)?,
+                    last_error: row.get(3)?,
+                    auto_disabled: row.get(4)?,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+fn is_enabled(conn: &Connection, plugin_id: &str) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT enabled FROM plugin_state WHERE plugin_id = ?1",
+        params![plugin_id],
+        |row| row.get::<_, bool>(0),
+    )
+    .optional()
+    .map(|enabled| enabled.unwrap_or(false))
+    .map_err(|e| e.to_string())
+}
+
+/// Count a call towards the plugin's health; repeated failures disable it
+fn record_outcome(conn: &Connection, plugin_id: &str, error: Option<&str>) {
+    let result = match error {
+        None => conn.execute(
+            "UPDATE plugin_state SET consecutive_failures = 0, updated_at = CURRENT_TIMESTAMP WHERE plugin_id = ?1",
+            params![plugin_id],
+        ),
+        Some(error) => conn.execute(
+            "UPDATE plugin_state SET consecutive_failures = consecutive_failures + 1, last_error = ?2,
+                 auto_disabled = CASE WHEN consecutive_failures + 1 >= ?3 THEN 1 ELSE auto_disabled END,
+                 enabled = CASE WHEN consecutive_failures + 1 >= ?3 THEN 0 ELSE enabled END,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE plugin_id = ?1",
+            params![plugin_id, error, MAX_CONSECUTIVE_FAILURES],
+        ),
+    };
+    if let Err(e) = result {
+        log::warn!("Failed to record outcome of plugin {}: {}", plugin_id, e);
+    }
+}
+
+fn build_command(dir: &Path, manifest: &PluginManifest, project_path: Option<&str>) -> Command {
+    let entry = dir.join(&manifest.entry);
+    let permissions = &manifest.permissions;
+    let project = project_path.filter(|_| permissions.project_access);
+
+    let mut cmd = match manifest.runtime {
+        PluginRuntime::Native => {
+            let mut cmd = Command::new(&entry);
+            cmd.current_dir(dir).env_clear();
+            for name in BASE_ENV.iter().copied().chain(permissions.env.iter().map(String::as_str)) {
+                if let Ok(value) = std::env::var(name) {
+                    cmd.env(name, value);
+                }
+            }
+            cmd
+        }
+        PluginRuntime::Wasm => {
+            let mut cmd = Command::new("wasmtime");
+            cmd.arg("run").current_dir(dir).env_clear();
+            if let Ok(path) = std::env::var("PATH") {
+                cmd.env("PATH", path);
+            }
+            if let Some(project) = project {
+                cmd.arg("--dir").arg(format!("{}::/project", project));
+            }
+            if permissions.network {
+                cmd.arg("-S").arg("inherit-network=y");
+            }
+            for name in &permissions.env {
+                if let Ok(value) = std::env::var(name) {
+                    cmd.arg("--env").arg(format!("{}={}", name, value));
+                }
+            }
+            cmd.arg(&entry);
+            cmd
+        }
+    };
+    cmd.stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true);
+    cmd
+}
+
+/// Run one request through a plugin process. The process handles a single JSON request
+/// on stdin and answers with a single JSON response on stdout; crashes, timeouts and
+/// malformed answers fail the call without affecting the app.
+async fn call_plugin(
+    dir: &Path,
+    manifest: &PluginManifest,
+    kind: &str,
+    name: &str,
+    input: Value,
+    project_path: Option<&str>,
+) -> Result<Value, String> {
+    let request = json!({
+        "abi_version": PLUGIN_ABI_VERSION,
+        "kind": kind,
+        "name": name,
+        "input": input,
+        "project_path": if manifest.permissions.project_access { project_path } else { None },
+    });
+    let mut child = build_command(dir, manifest, project_path)
+        .spawn()
+        .map_err(|e| match manifest.runtime {
+            PluginRuntime::Wasm if e.kind() == std::io::ErrorKind::NotFound => {
+                "WASM plugins need wasmtime on the PATH".to_string()
+            }
+            _ => format!("Failed to start plugin: {}", e),
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let mut line = serde_json::to_vec(&request).map_err(|e| e.to_string())?;
+        line.push(b'\n');
+        // A plugin that exits without reading its input fails below
+        let _ = stdin.write_all(&line).await;
+    }
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let run = async {
+        let (status, (stdout, stdout_total), (stderr, _)) = tokio::join!(
+            child.wait(),
+            read_capped(stdout, MAX_RESPONSE_BYTES),
+            read_capped(stderr, MAX_STDERR_BYTES),
+        );
+        (status, stdout, stdout_total, stderr)
+    };
+    let timeout = Duration::from_secs(manifest.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS).min(MAX_TIMEOUT_SECS));
+    // Dropping the future on timeout kills the process through kill_on_drop
+    let (status, stdout, stdout_total, stderr) = tokio::time::timeout(timeout, run)
+        .await
+        .map_err(|_| format!("Plugin timed out after {}s", timeout.as_secs()))?;
+
+    let status = status.map_err(|e| format!("Plugin failed: {}", e))?;
+    if stdout_total > stdout.len() {
+        return Err(format!("Plugin response exceeds {} bytes", MAX_RESPONSE_BYTES));
+    }
+    let response = String::from_utf8_lossy(&stdout);
+    let Some(line) = response.lines().rev().find(|line| !line.trim().is_empty()) else {
+        let stderr = String::from_utf8_lossy(&stderr);
+        return Err(format!("Plugin exited ({}) without a response: {}", status, stderr.trim()));
+    };
+    let response: PluginResponse =
+        serde_json::from_str(line).map_err(|e| format!("Plugin sent an invalid response: {}", e))?;
+    if response.ok {
+        Ok(response.output)
+    } else {
+        Err(response.error.unwrap_or_else(|| "Plugin reported an error".to_string()))
+    }
+}
+
+/// Call an enabled plugin and record the outcome
+async fn call_enabled(
+    app: &AppHandle,
+    plugin_id: &str,
+    kind: &str,
+    name: &str,
+    input: Value,
+    project_path: Option<&str>,
+) -> Result<PluginCallResult, String> {
+    let (dir, manifest) = find_plugin(app, plugin_id)?;
+    let declared = match kind {
+        "command" => manifest.commands.iter().any(|c| c.name == name),
+        "analyzer" => manifest.analyzers.iter().any(|a| a.name == name),
+        _ => manifest.hook_events.iter().any(|event| event == name),
+    };
+    if !declared {
+        return Err(format!("Plugin '{}' has no {} '{}'", plugin_id, kind, name));
+    }
+    {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        if !is_enabled(&conn, plugin_id)? {
+            return Err(format!("Plugin '{}' is disabled", plugin_id));
+        }
+    }
+
+    let started = Instant::now();
+    let result = call_plugin(&dir, &manifest, kind, name, input, project_path).await;
+    let db = app.state::<AgentDb>();
+    if let Ok(conn) = db.0.get() {
+        record_outcome(&conn, plugin_id, result.as_ref().err().map(String::as_str));
+    }
+    if let Err(e) = &result {
+        log::warn!("Plugin {} failed on {} '{}': {}", plugin_id, kind, name, e);
+    }
+    Ok(PluginCallResult {
+        plugin_id: plugin_id.to_string(),
+        name: name.to_string(),
+        output: result?,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Notify enabled plugins subscribed to a hook event, in the background
+pub fn dispatch_hook_event(app: &AppHandle, event: &HookEvent, context: &HookContext) {
+    let Ok(plugins) = discover(app) else {
+        return;
+    };
+    let event_name = event.as_str().to_string();
+    let subscribed: Vec<String> = plugins
+        .into_iter()
+        .filter_map(|(_, _, manifest)| manifest.ok())
+        .filter(|manifest| manifest.hook_events.contains(&event_name))
+        .map(|manifest| manifest.id)
+        .collect();
+    if subscribed.is_empty() {
+        return;
+    }
+
+    let app = app.clone();
+    let input = serde_json::to_value(context).unwrap_or(Value::Null);
+    let project_path = context.project_path.clone();
+    tauri::async_runtime::spawn(async move {
+        for plugin_id in subscribed {
+            if let Err(e) = call_enabled(&app, &plugin_id, "hook", &event_name, input.clone(), Some(&project_path)).await {
+                log::debug!("Plugin {} skipped {}: {}", plugin_id, event_name, e);
+            }
+        }
+    });
+}
+
+/// Installed plugins with their manifest, state and load errors
+#[tauri::command]
+pub async fn list_plugins(app: AppHandle, db: State<'_, AgentDb>) -> Result<Vec<PluginInfo>, String> {
+    let states = {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        load_states(&conn)?
+    };
+    Ok(discover(&app)?
+        .into_iter()
+        .map(|(directory, _, manifest)| {
+            let (manifest, load_error) = match manifest {
+                Ok(manifest) => (Some(manifest), None),
+                Err(e) => (None, Some(e)),
+            };
+            let state = manifest.as_ref().and_then(|m| states.get(&m.id));
+            PluginInfo {
+                directory,
+                enabled: state.map_or(false, |s| s.enabled),
+                consecutive_failures: state.map_or(0, |s| s.consecutive_failures),
+                last_error: state.and_then(|s| s.last_error.clone()),
+                auto_disabled: state.map_or(false, |s| s.auto_disabled),
+                manifest,
+                load_error,
+            }
+        })
+        .collect())
+}
+
+/// Directory plugins are installed into, created if missing
+#[tauri::command]
+pub async fn get_plugins_dir(app: AppHandle) -> Result<String, String> {
+    let dir = plugins_dir(&app)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.to_string_lossy().to_string())
+}
+
+/// Enable or disable a plugin; enabling also clears its failure count
+#[tauri::command]
+pub async fn set_plugin_enabled(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    plugin_id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    if enabled {
+        find_plugin(&app, &plugin_id)?;
+    }
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO plugin_state (plugin_id, enabled) VALUES (?1, ?2)
+         ON CONFLICT(plugin_id) DO UPDATE SET enabled = ?2, updated_at = CURRENT_TIMESTAMP,
+             consecutive_failures = CASE WHEN ?2 THEN 0 ELSE consecutive_failures END,
+             auto_disabled = CASE WHEN ?2 THEN 0 ELSE auto_disabled END",
+        params![plugin_id, enabled],
+    )
+    .map_err(|e| e.to_string())?;
+    log::info!("Plugin {} {}", plugin_id, if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Run a command a plugin declares in its manifest
+#[tauri::command]
+pub async fn invoke_plugin_command(
+    app: AppHandle,
+    plugin_id: String,
+    command: String,
+    input: Option<Value>,
+    project_path: Option<String>,
+) -> Result<PluginCallResult, String> {
+    call_enabled(
+        &app,
+        &plugin_id,
+        "command",
+        &command,
+        input.unwrap_or(Value::Null),
+        project_path.as_deref(),
+    )
+    .await
+}
+
+/// Run a plugin's analyzer against a project
+#[tauri::command]
+pub async fn run_plugin_analyzer(
+    app: AppHandle,
+    plugin_id: String,
+    analyzer: String,
+    project_path: String,
+    input: Option<Value>,
+) -> Result<PluginCallResult, String> {
+    call_enabled(
+        &app,
+        &plugin_id,
+        "analyzer",
+        &analyzer,
+        input.unwrap_or(Value::Null),
+        Some(&project_path),
+    )
+    .await
+}
@@ -0,0 +1,393 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use super::accessibility::AnnouncedRun;
+use super::agents::AgentDb;
+use super::events::AppEvent;
+use super::settings_store;
+
+/// `app_settings` key holding the power policy as JSON
+const POLICY_SETTING: &str = "power_policy";
+
+/// How often the power source is checked while runs are active
+const POWER_TICK: Duration = Duration::from_secs(30);
+
+/// Shown by the OS as the reason sleep is blocked
+const INHIBIT_REASON: &str = "A Claude run is in progress";
+
+/// What to do about sleep while running on battery
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatteryAction {
+    /// Keep the machine awake, as on AC power
+    #[default]
+    Inhibit,
+    /// Checkpoint and stop the running sessions when the machine switches to battery, and
+    /// let it sleep. Agent runs cannot be resumed, so they still keep it awake.
+    PauseAndCheckpoint,
+    /// Let the machine sleep
+    Allow,
+}
+
+/// When the machine is kept from sleeping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerPolicy {
+    /// Block system sleep while any session or agent run is active
+    pub prevent_sleep: bool,
+    pub on_battery: BatteryAction,
+}
+
+impl Default for PowerPolicy {
+    fn default() -> Self {
+        Self {
+            prevent_sleep: true,
+            on_battery: BatteryAction::Inhibit,
+        }
+    }
+}
+
+/// Returned by `get_power_policy` and `update_power_policy`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerStatus {
+    pub policy: PowerPolicy,
+    pub active_runs: usize,
+    /// A sleep inhibitor is currently held
+    pub inhibiting: bool,
+    /// None when the power source could not be determined
+    pub on_battery: Option<bool>,
+}
+
+/// Payload of `power-run-paused`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PausedRun {
+    pub session_id: String,
+    pub project_path: String,
+    /// Checkpoint the session can be restored from; None if creating it failed
+    pub checkpoint_id: Option<String>,
+}
+
+static POLICY: Lazy<RwLock<PowerPolicy>> = Lazy::new(|| RwLock::new(PowerPolicy::default()));
+
+/// Active runs and their project paths
+static RUNS: Lazy<Mutex<HashMap<AnnouncedRun, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Process holding the OS sleep inhibitor
+static INHIBITOR: Lazy<Mutex<Option<Child>>> = Lazy::new(|| Mutex::new(None));
+
+/// Power source at the last check, None while no run is active
+static LAST_ON_BATTERY: Lazy<Mutex<Option<bool>>> = Lazy::new(|| Mutex::new(None));
+
+fn current_policy() -> PowerPolicy {
+    POLICY.read().map(|p| p.clone()).unwrap_or_default()
+}
+
+/// Load the policy into the cache, called once at startup
+pub fn init_power(app: &AppHandle) {
+    let db = app.state::<AgentDb>();
+    let Ok(conn) = db.0.get() else {
+        return;
+    };
+    let policy: PowerPolicy = settings_store::get_or_default(&conn, POLICY_SETTING);
+    if let Ok(mut cached) = POLICY.write() {
+        *cached = policy;
+    }
+}
+
+/// Whether the machine is running on battery
+fn on_battery() -> Option<bool> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut battery = None;
+        for entry in std::fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+            let read = |name: &str| {
+                std::fs::read_to_string(entry.path().join(name))
+                    .map(|s| s.trim().to_string())
+                    .unwrap_or_default()
+            };
+            match read("type").as_str() {
+                "Mains" | "USB" if read("online") == "1" => return Some(false),
+                "Battery" => {
+                    let discharging = read("status") == "Discharging";
+                    battery = Some(battery.unwrap_or(false) || discharging);
+                }
+                _ => {}
+            }
+        }
+        Some(battery.unwrap_or(false))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let output = Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        if text.contains("'Battery Power'") {
+            Some(true)
+        } else if text.contains("'AC Power'") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        // BatteryStatus 1 is "discharging"; machines without a battery print nothing
+        let output = Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "(Get-CimInstance Win32_Battery).BatteryStatus",
+            ])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .output()
+            .ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        Some(text.lines().any(|line| line.trim() == "1"))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        None
+    }
+}
+
+/// Start a process that blocks system sleep until its stdin is closed. The pipe also
+/// closes if the app dies, so a crash never leaves the machine unable to sleep.
+fn spawn_inhibitor() -> std::io::Result<Child> {
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = Command::new("caffeinate");
+        command.args(["-i", "-s", "cat"]);
+        command
+    };
+
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        use std::os::windows::process::CommandExt;
+        // ES_CONTINUOUS | ES_SYSTEM_REQUIRED, held by the thread until it exits
+        let script = concat!(
+            "$power = Add-Type -Name Power -Namespace Workbench -PassThru -MemberDefinition ",
+            "'[DllImport(\"kernel32.dll\")] ",
+            "public static extern uint SetThreadExecutionState(uint esFlags);'; ",
+            "[void]$power::SetThreadExecutionState([uint32]2147483649); ",
+            "[void][Console]::In.ReadToEnd()",
+        );
+        let mut command = Command::new("powershell");
+        command
+            .args(["-NoProfile", "-NonInteractive", "-Command", script])
+            .creation_flags(0x08000000); // CREATE_NO_WINDOW
+        command
+    };
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = {
+        let mut command = Command::new("systemd-inhibit");
+        command.args([
+            "--what=idle:sleep",
+            "--who=Claude Workbench",
+            &format!("--why={}", INHIBIT_REASON),
+            "--mode=block",
+            "cat",
+        ]);
+        command
+    };
+
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+}
+
+fn release_inhibitor(inhibitor: &mut Option<Child>) {
+    if let Some(mut child) = inhibitor.take() {
+        drop(child.stdin.take());
+        let _ = child.kill();
+        let _ = child.wait();
+        log::info!("Released the sleep inhibitor");
+    }
+}
+
+/// Take or release the sleep inhibitor to match the active runs and the policy, and pause
+/// sessions when the machine has just switched to battery
+fn refresh(app: &AppHandle) {
+    let policy = current_policy();
+    let runs: Vec<(AnnouncedRun, String)> = match RUNS.lock() {
+        Ok(runs) => runs.iter().map(|(run, path)| (run.clone(), path.clone())).collect(),
+        Err(_) => return,
+    };
+    let Ok(mut inhibitor) = INHIBITOR.lock() else {
+        return;
+    };
+
+    let battery = if runs.is_empty() || !policy.prevent_sleep {
+        None
+    } else if policy.on_battery == BatteryAction::Inhibit {
+        Some(false)
+    } else {
+        on_battery()
+    };
+    let switched_to_battery = match LAST_ON_BATTERY.lock() {
+        Ok(mut last) => {
+            let switched = *last == Some(false) && battery == Some(true);
+            *last = battery;
+            switched
+        }
+        Err(_) => false,
+    };
+
+    let keep_awake = policy.prevent_sleep
+        && runs.iter().any(|(run, _)| match (battery, policy.on_battery) {
+            (Some(true), BatteryAction::Allow) => false,
+            (Some(true), BatteryAction::PauseAndCheckpoint) => {
+                matches!(run, AnnouncedRun::Agent(_))
+            }
+            _ => true,
+        });
+
+    if let Some(child) = inhibitor.as_mut() {
+        if !matches!(child.try_wait(), Ok(None)) {
+            log::warn!("Sleep inhibitor exited unexpectedly");
+            *inhibitor = None;
+        }
+    }
+    if keep_awake && inhibitor.is_none() {
+        match spawn_inhibitor() {
+            Ok(child) => {
+                log::info!("Inhibiting system sleep: {} ({} active)", INHIBIT_REASON, runs.len());
+                *inhibitor = Some(child);
+            }
+            Err(e) => log::warn!("Failed to inhibit system sleep: {}", e),
+        }
+    } else if !keep_awake {
+        release_inhibitor(&mut inhibitor);
+    }
+    drop(inhibitor);
+
+    if switched_to_battery && policy.on_battery == BatteryAction::PauseAndCheckpoint {
+        for (run, project_path) in runs {
+            if let AnnouncedRun::Session(session_id) = run {
+                tauri::async_runtime::spawn(pause_session(app.clone(), session_id, project_path));
+            }
+        }
+    }
+}
+
+fn schedule_refresh(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || refresh(&app));
+}
+
+/// Checkpoint a session and stop it; it can be resumed once back on AC power
+async fn pause_session(app: AppHandle, session_id: String, project_path: String) {
+    log::info!("Pausing session {} on battery power", session_id);
+    let checkpoint = super::claude::create_checkpoint(
+        app.state::<crate::checkpoint::state::CheckpointState>(),
+        app.clone(),
+        session_id.clone(),
+        super::claude::encode_project_path(&project_path),
+        project_path.clone(),
+        None,
+        Some("Paused on battery power".to_string()),
+    )
+    .await;
+    let checkpoint_id = match checkpoint {
+        Ok(result) => Some(result.checkpoint.id),
+        Err(e) => {
+            log::warn!("Failed to checkpoint session {} before pausing: {}", session_id, e);
+            None
+        }
+    };
+    let stopped = super::claude::cancel_claude_execution(app.clone(), Some(session_id.clone()));
+    if let Err(e) = stopped.await {
+        log::warn!("Failed to stop session {} on battery power: {}", session_id, e);
+        return;
+    }
+    let paused = PausedRun {
+        session_id,
+        project_path,
+        checkpoint_id,
+    };
+    if let Err(e) = AppEvent::PowerRunPaused(paused).emit(&app) {
+        log::debug!("Failed to emit power-run-paused event: {}", e);
+    }
+}
+
+/// Record that a run started, inhibiting sleep if the policy asks for it
+pub fn run_started(app: &AppHandle, run: AnnouncedRun, project_path: &str) {
+    if let Ok(mut runs) = RUNS.lock() {
+        runs.insert(run, project_path.to_string());
+    }
+    schedule_refresh(app);
+}
+
+/// Record that a run finished or was cancelled, releasing the inhibitor after the last one
+pub fn run_finished(app: &AppHandle, run: &AnnouncedRun) {
+    let removed = RUNS.lock().map(|mut runs| runs.remove(run).is_some()).unwrap_or(false);
+    if removed {
+        schedule_refresh(app);
+    }
+}
+
+/// Re-check the power source every few seconds while runs are active
+pub fn start_power_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(POWER_TICK).await;
+            let active = RUNS.lock().map(|runs| !runs.is_empty()).unwrap_or(false);
+            if active {
+                let app = app.clone();
+                let _ = tauri::async_runtime::spawn_blocking(move || refresh(&app)).await;
+            }
+        }
+    });
+}
+
+/// Let the machine sleep again when the app exits
+pub fn release_on_exit() {
+    if let Ok(mut inhibitor) = INHIBITOR.lock() {
+        release_inhibitor(&mut inhibitor);
+    }
+}
+
+fn status() -> PowerStatus {
+    PowerStatus {
+        policy: current_policy(),
+        active_runs: RUNS.lock().map(|runs| runs.len()).unwrap_or(0),
+        inhibiting: INHIBITOR.lock().map(|i| i.is_some()).unwrap_or(false),
+        on_battery: on_battery(),
+    }
+}
+
+/// Current power policy, with the number of active runs and whether sleep is blocked
+#[tauri::command]
+pub async fn get_power_policy() -> Result<PowerStatus, String> {
+    tokio::task::spawn_blocking(status).await.map_err(|e| e.to_string())
+}
+
+/// Change when the machine is kept awake; takes effect for runs already active
+#[tauri::command]
+pub async fn update_power_policy(app: AppHandle, policy: PowerPolicy) -> Result<PowerStatus, String> {
+    settings_store::set(&app, POLICY_SETTING, &policy)?;
+    log::info!("Power policy updated: {:?}", policy);
+    if let Ok(mut cached) = POLICY.write() {
+        *cached = policy;
+    }
+
+    tokio::task::spawn_blocking(move || {
+        refresh(&app);
+        status()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,532 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+
+use super::hook_packs::HookPackConflictStrategy;
+use super::hook_templates::apply_hook_template;
+use super::project_execution_config::{
+    load_project_config, update_project_execution_config, ProjectExecutionConfig,
+};
+use super::session_templates::{permission_preset, TEMPLATE_PERMISSION_PRESETS};
+
+/// What was found in a project's manifests and lock files
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectStack {
+    pub languages: Vec<String>,
+    pub package_managers: Vec<String>,
+    pub test_frameworks: Vec<String>,
+    pub build_commands: Vec<String>,
+    pub test_commands: Vec<String>,
+    pub lint_commands: Vec<String>,
+    /// Formatter commands taking the file to format as their last argument, with the
+    /// shell patterns of the files they format
+    pub formatters: Vec<(String, String)>,
+}
+
+/// A hook template to apply to the project, with its parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendedHook {
+    pub template_id: String,
+    pub params: HashMap<String, String>,
+    pub reason: String,
+}
+
+/// Setup proposed for a project, applied as a whole with `apply_project_bootstrap`;
+/// the user may edit it first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootstrapProposal {
+    pub project_path: String,
+    pub stack: ProjectStack,
+    pub claude_md: String,
+    /// The project has a CLAUDE.md already, which applying keeps unless told otherwise
+    pub has_claude_md: bool,
+    /// `development`, `safe` or `interactive`
+    pub permission_preset: String,
+    pub permission_reason: String,
+    pub hooks: Vec<RecommendedHook>,
+}
+
+/// What `apply_project_bootstrap` changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootstrapResult {
+    pub claude_md_written: bool,
+    pub permission_preset: String,
+    /// Templates applied, by ID
+    pub hooks_applied: Vec<String>,
+    /// Hooks that could not be applied, with the reason
+    pub hook_errors: Vec<String>,
+}
+
+fn push_unique(list: &mut Vec<String>, item: impl Into<String>) {
+    let item = item.into();
+    if !list.contains(&item) {
+        list.push(item);
+    }
+}
+
+fn read(project: &Path, file: &str) -> Option<String> {
+    fs::read_to_string(project.join(file)).ok()
+}
+
+fn detect_node(project: &Path, stack: &mut ProjectStack) {
+    let Some(manifest) = read(project, "package.json") else {
+        return;
+    };
+    let manifest: Value = serde_json::from_str(&manifest).unwrap_or_default();
+    let has_dep = |name: &str| {
+        ["dependencies", "devDependencies"]
+            .iter()
+            .any(|deps| manifest[deps].get(name).is_some())
+    };
+
+    let typescript = project.join("tsconfig.json").is_file() || has_dep("typescript");
+    push_unique(
+        &mut stack.languages,
+        if typescript {
+            "TypeScript"
+        } else {
+            "JavaScript"
+        },
+    );
+
+    let pm = if project.join("pnpm-lock.yaml").is_file() {
+        "pnpm"
+    } else if project.join("yarn.lock").is_file() {
+        "yarn"
+    } else if project.join("bun.lockb").is_file() || project.join("bun.lock").is_file() {
+        "bun"
+    } else {
+        "npm"
+    };
+    push_unique(&mut stack.package_managers, pm);
+
+    let script = |name: &str| {
+        manifest["scripts"].get(name).map(|_| match (pm, name) {
+            ("npm", "test") => "npm test".to_string(),
+            ("npm", _) => format!("npm run {}", name),
+            _ => format!("{} {}", pm, name),
+        })
+    };
+    if let Some(build) = script("build") {
+        push_unique(&mut stack.build_commands, build);
+    }
+    if let Some(test) = script("test") {
+        push_unique(&mut stack.test_commands, test);
+    }
+    for lint in ["lint", "typecheck"].iter().filter_map(|name| script(name)) {
+        push_unique(&mut stack.lint_commands, lint);
+    }
+
+    for (dep, framework) in [
+        ("vitest", "Vitest"),
+        ("jest", "Jest"),
+        ("mocha", "Mocha"),
+        ("@playwright/test", "Playwright"),
+        ("cypress", "Cypress"),
+    ] {
+        if has_dep(dep) {
+            push_unique(&mut stack.test_frameworks, framework);
+        }
+    }
+
+    let patterns = "*.ts|*.tsx|*.js|*.jsx|*.json|*.css|*.md";
+    if has_dep("@biomejs/biome") {
+        stack.formatters.push((
+            "npx --no-install biome format --write".to_string(),
+            patterns.to_string(),
+        ));
+    } else if has_dep("prettier") {
+        stack.formatters.push((
+            "npx --no-install prettier --write".to_string(),
+            patterns.to_string(),
+        ));
+    }
+}
+
+fn detect_rust(project: &Path, stack: &mut ProjectStack) {
+    if !project.join("Cargo.toml").is_file() {
+        return;
+    }
+    push_unique(&mut stack.languages, "Rust");
+    push_unique(&mut stack.package_managers, "cargo");
+    push_unique(&mut stack.test_frameworks, "cargo test");
+    push_unique(&mut stack.build_commands, "cargo build");
+    push_unique(&mut stack.test_commands, "cargo test");
+    push_unique(&mut stack.lint_commands, "cargo clippy --all-targets");
+    stack
+        .formatters
+        .push(("rustfmt --edition 2021".to_string(), "*.rs".to_string()));
+}
+
+fn detect_python(project: &Path, stack: &mut ProjectStack) {
+    let manifests: Vec<String> = ["pyproject.toml", "requirements.txt", "setup.py", "Pipfile"]
+        .iter()
+        .filter_map(|file| read(project, file))
+        .collect();
+    if manifests.is_empty() {
+        return;
+    }
+    let mentions = |name: &str| manifests.iter().any(|content| content.contains(name));
+    push_unique(&mut stack.languages, "Python");
+
+    let (pm, run) = if project.join("uv.lock").is_file() {
+        ("uv", "uv run ")
+    } else if project.join("poetry.lock").is_file() || mentions("[tool.poetry]") {
+        ("poetry", "poetry run ")
+    } else if project.join("Pipfile").is_file() {
+        ("pipenv", "pipenv run ")
+    } else {
+        ("pip", "")
+    };
+    push_unique(&mut stack.package_managers, pm);
+
+    let pytest = mentions("pytest")
+        || project.join("pytest.ini").is_file()
+        || project.join("conftest.py").is_file();
+    if pytest {
+        push_unique(&mut stack.test_frameworks, "pytest");
+        push_unique(&mut stack.test_commands, format!("{}pytest", run));
+    }
+    if mentions("ruff") {
+        push_unique(&mut stack.lint_commands, format!("{}ruff check .", run));
+        stack
+            .formatters
+            .push((format!("{}ruff format", run), "*.py".to_string()));
+    } else if mentions("black") {
+        stack
+            .formatters
+            .push((format!("{}black -q", run), "*.py".to_string()));
+    }
+    if mentions("mypy") {
+        push_unique(&mut stack.lint_commands, format!("{}mypy .", run));
+    }
+}
+
+fn detect_others(project: &Path, stack: &mut ProjectStack) {
+    if project.join("go.mod").is_file() {
+        push_unique(&mut stack.languages, "Go");
+        push_unique(&mut stack.package_managers, "go modules");
+        push_unique(&mut stack.test_frameworks, "go test");
+        push_unique(&mut stack.build_commands, "go build ./...");
+        push_unique(&mut stack.test_commands, "go test ./...");
+        push_unique(&mut stack.lint_commands, "go vet ./...");
+        stack
+            .formatters
+            .push(("gofmt -w".to_string(), "*.go".to_string()));
+    }
+
+    if project.join("pom.xml").is_file() {
+        push_unique(&mut stack.languages, "Java");
+        push_unique(&mut stack.package_managers, "Maven");
+        push_unique(&mut stack.build_commands, "mvn -q package");
+        push_unique(&mut stack.test_commands, "mvn -q test");
+    }
+    let gradle = ["build.gradle", "build.gradle.kts"]
+        .iter()
+        .find(|f| project.join(f).is_file());
+    if let Some(build_file) = gradle {
+        let kotlin = build_file.ends_with(".kts") || project.join("src/main/kotlin").is_dir();
+        push_unique(&mut stack.languages, if kotlin { "Kotlin" } else { "Java" });
+        push_unique(&mut stack.package_managers, "Gradle");
+        let gradle = if project.join("gradlew").is_file() {
+            "./gradlew"
+        } else {
+            "gradle"
+        };
+        push_unique(&mut stack.build_commands, format!("{} build", gradle));
+        push_unique(&mut stack.test_commands, format!("{} test", gradle));
+    }
+
+    if project.join("Gemfile").is_file() {
+        push_unique(&mut stack.languages, "Ruby");
+        push_unique(&mut stack.package_managers, "Bundler");
+        if project.join("spec").is_dir() {
+            push_unique(&mut stack.test_frameworks, "RSpec");
+            push_unique(&mut stack.test_commands, "bundle exec rspec");
+        }
+    }
+
+    if let Some(composer) = read(project, "composer.json") {
+        push_unique(&mut stack.languages, "PHP");
+        push_unique(&mut stack.package_managers, "Composer");
+        if composer.contains("phpunit") {
+            push_unique(&mut stack.test_frameworks, "PHPUnit");
+            push_unique(&mut stack.test_commands, "vendor/bin/phpunit");
+        }
+    }
+
+    let dotnet = fs::read_dir(project)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .any(|entry| {
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            name.ends_with(".sln") || name.ends_with(".csproj")
+        });
+    if dotnet {
+        push_unique(&mut stack.languages, "C#");
+        push_unique(&mut stack.package_managers, "NuGet");
+        push_unique(&mut stack.build_commands, "dotnet build");
+        push_unique(&mut stack.test_commands, "dotnet test");
+    }
+
+    // A Makefile fills in what no manifest told
+    if let Some(makefile) = read(project, "Makefile") {
+        let has_target = |target: &str| {
+            makefile
+                .lines()
+                .any(|l| l.starts_with(&format!("{}:", target)))
+        };
+        if stack.build_commands.is_empty() && has_target("build") {
+            stack.build_commands.push("make build".to_string());
+        }
+        if stack.test_commands.is_empty() && has_target("test") {
+            stack.test_commands.push("make test".to_string());
+        }
+    }
+}
+
+/// Detect languages, package managers, test frameworks and commands from the
+/// manifests at the root of `project`
+pub fn detect_stack(project: &Path) -> ProjectStack {
+    let mut stack = ProjectStack::default();
+    detect_node(project, &mut stack);
+    detect_rust(project, &mut stack);
+    detect_python(project, &mut stack);
+    detect_others(project, &mut stack);
+    stack
+}
+
+fn command_lines(out: &mut String, label: &str, commands: &[String]) {
+    for command in commands {
+        out.push_str(&format!("- {}: `{}`\n", label, command));
+    }
+}
+
+/// A starter CLAUDE.md describing the stack and how to build and test the project
+fn render_claude_md(name: &str, stack: &ProjectStack) -> String {
+    let mut out = format!("# {}\n\n", name);
+
+    out.push_str("## Stack\n\n");
+    if stack.languages.is_empty() {
+        out.push_str("- No manifest found; describe the languages and tools used here.\n");
+    } else {
+        out.push_str(&format!("- Languages: {}\n", stack.languages.join(", ")));
+        out.push_str(&format!(
+            "- Package managers: {}\n",
+            stack.package_managers.join(", ")
+        ));
+    }
+    if !stack.test_frameworks.is_empty() {
+        out.push_str(&format!("- Tests: {}\n", stack.test_frameworks.join(", ")));
+    }
+
+    let has_commands = !(stack.build_commands.is_empty()
+        && stack.test_commands.is_empty()
+        && stack.lint_commands.is_empty());
+    if has_commands {
+        out.push_str("\n## Commands\n\n");
+        command_lines(&mut out, "Build", &stack.build_commands);
+        command_lines(&mut out, "Test", &stack.test_commands);
+        command_lines(&mut out, "Lint", &stack.lint_commands);
+    }
+
+    out.push_str("\n## Working in this project\n\n");
+    if !stack.test_commands.is_empty() {
+        out.push_str(
+            "- Run the tests after changing code and fix any failures before finishing.\n",
+        );
+    }
+    if !stack.lint_commands.is_empty() {
+        out.push_str("- Keep the linters clean.\n");
+    }
+    out.push_str("- Follow the conventions of the surrounding code.\n");
+    out.push_str("\n<!-- Add architecture notes, conventions and gotchas here -->\n");
+    out
+}
+
+fn recommend_hooks(stack: &ProjectStack) -> Vec<RecommendedHook> {
+    let mut hooks = Vec::new();
+    if let Some((formatter, patterns)) = stack.formatters.first() {
+        hooks.push(RecommendedHook {
+            template_id: "format-on-edit".to_string(),
+            params: HashMap::from([
+                ("formatter".to_string(), formatter.clone()),
+                ("patterns".to_string(), patterns.clone()),
+            ]),
+            reason: format!("Keeps edited files formatted with {}", formatter),
+        });
+    }
+    if let Some(test_command) = stack.test_commands.first() {
+        hooks.push(RecommendedHook {
+            template_id: "run-tests-on-stop".to_string(),
+            params: HashMap::from([("test_command".to_string(), test_command.clone())]),
+            reason: format!("Sends Claude back to work when `{}` fails", test_command),
+        });
+    }
+    hooks
+}
+
+/// Analyze a project's stack and propose a starter CLAUDE.md, a permission preset
+/// and hooks; nothing is written until the proposal is applied
+#[tauri::command]
+pub async fn analyze_project_stack(project_path: String) -> Result<BootstrapProposal, String> {
+    let project = Path::new(&project_path);
+    if !project.is_dir() {
+        return Err(format!(
+            "Project directory does not exist: {}",
+            project_path
+        ));
+    }
+    let stack = detect_stack(project);
+    let name = project
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| project_path.clone());
+
+    // Edits are easy to review and undo under version control
+    let (permission_preset, permission_reason) = if project.join(".git").exists() {
+        (
+            "development",
+            "The project is a git repository, so edits can be reviewed and reverted",
+        )
+    } else {
+        (
+            "safe",
+            "The project is not under version control, so edits are hard to undo",
+        )
+    };
+
+    Ok(BootstrapProposal {
+        claude_md: render_claude_md(&name, &stack),
+        has_claude_md: project.join("CLAUDE.md").is_file(),
+        permission_preset: permission_preset.to_string(),
+        permission_reason: permission_reason.to_string(),
+        hooks: recommend_hooks(&stack),
+        project_path,
+        stack,
+    })
+}
+
+/// Apply a proposal from `analyze_project_stack`: write its CLAUDE.md (an existing one
+/// only with `overwrite_claude_md`), set the permission preset in the project's
+/// execution config and apply the hook templates to the project scope.
+///
+/// Hooks are added alongside existing ones; a hook that fails is reported and the
+/// others are still applied.
+#[tauri::command]
+pub async fn apply_project_bootstrap(
+    app: AppHandle,
+    proposal: BootstrapProposal,
+    overwrite_claude_md: Option<bool>,
+) -> Result<BootstrapResult, String> {
+    let project_path = proposal.project_path.clone();
+    let permissions = permission_preset(&proposal.permission_preset).ok_or_else(|| {
+        format!(
+            "Unknown permission preset '{}', expected one of: {}",
+            proposal.permission_preset,
+            TEMPLATE_PERMISSION_PRESETS.join(", ")
+        )
+    })?;
+
+    let claude_md = Path::new(&project_path).join("CLAUDE.md");
+    let claude_md_written = !claude_md.exists() || overwrite_claude_md.unwrap_or(false);
+    if claude_md_written {
+        crate::config_file::write(&claude_md, &proposal.claude_md)
+            .map_err(|e| format!("Failed to write CLAUDE.md: {}", e))?;
+    }
+
+    let mut config: ProjectExecutionConfig =
+        load_project_config(&project_path)?.unwrap_or_default();
+    config.permissions = Some(permissions);
+    update_project_execution_config(app, project_path.clone(), Some(config)).await?;
+
+    let mut hooks_applied = Vec::new();
+    let mut hook_errors = Vec::new();
+    for hook in proposal.hooks {
+        let applied = apply_hook_template(
+            hook.template_id.clone(),
+            Some("project".to_string()),
+            Some(project_path.clone()),
+            Some(hook.params),
+            Some(HookPackConflictStrategy::Append),
+        )
+        .await;
+        match applied {
+            Ok(_) => hooks_applied.push(hook.template_id),
+            Err(e) => hook_errors.push(format!("{}: {}", hook.template_id, e)),
+        }
+    }
+
+    log::info!(
+        "Bootstrapped {} (CLAUDE.md written: {}, preset: {}, hooks: {:?})",
+        project_path,
+        claude_md_written,
+        proposal.permission_preset,
+        hooks_applied
+    );
+    Ok(BootstrapResult {
+        claude_md_written,
+        permission_preset: proposal.permission_preset,
+        hooks_applied,
+        hook_errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_node_with_pnpm_and_vitest() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts": {"build": "vite build", "test": "vitest run", "lint": "eslint ."},
+                "devDependencies": {"typescript": "5", "vitest": "1", "prettier": "3"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("pnpm-lock.yaml"), "").unwrap();
+
+        let stack = detect_stack(dir.path());
+        assert_eq!(stack.languages, vec!["TypeScript"]);
+        assert_eq!(stack.package_managers, vec!["pnpm"]);
+        assert_eq!(stack.test_frameworks, vec!["Vitest"]);
+        assert_eq!(stack.build_commands, vec!["pnpm build"]);
+        assert_eq!(stack.test_commands, vec!["pnpm test"]);
+        assert_eq!(stack.lint_commands, vec!["pnpm lint"]);
+        assert_eq!(stack.formatters[0].0, "npx --no-install prettier --write");
+    }
+
+    #[test]
+    fn test_detect_mixed_rust_and_python() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"x\"\n").unwrap();
+        fs::write(
+            dir.path().join("pyproject.toml"),
+            "[tool.ruff]\n[tool.pytest.ini_options]\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("uv.lock"), "").unwrap();
+
+        let stack = detect_stack(dir.path());
+        assert_eq!(stack.languages, vec!["Rust", "Python"]);
+        assert_eq!(stack.test_commands, vec!["cargo test", "uv run pytest"]);
+        let hooks = recommend_hooks(&stack);
+        assert_eq!(hooks[0].params["formatter"], "rustfmt --edition 2021");
+        assert_eq!(hooks[1].params["test_command"], "cargo test");
+    }
+
+    #[test]
+    fn test_claude_md_without_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        let stack = detect_stack(dir.path());
+        assert!(recommend_hooks(&stack).is_empty());
+        let claude_md = render_claude_md("empty", &stack);
+        assert!(claude_md.starts_with("# empty\n"));
+        assert!(!claude_md.contains("## Commands"));
+    }
+}
@@ -0,0 +1,219 @@
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::claude::get_claude_execution_config;
+use super::permission_config::{
+    ClaudeExecutionConfig, ClaudePermissionConfig, OutputFormat, DANGEROUS_SKIP_MAX_HOURS,
+};
+
+/// Project execution config, relative to the project root
+const PROJECT_CONFIG_FILE: &str = ".claude/execution_config.json";
+
+/// Execution settings a project overrides; unset fields keep the user's
+/// `execution_config.json` value
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectExecutionConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<OutputFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verbose: Option<bool>,
+    /// Replaces the user's permissions as a whole
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<ClaudePermissionConfig>,
+}
+
+/// Returned by `get_project_execution_config` and `update_project_execution_config`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectExecutionConfigState {
+    pub project_path: String,
+    /// Location of the project's config file
+    pub config_file: String,
+    /// None when the project has no config of its own
+    pub project: Option<ProjectExecutionConfig>,
+    /// The user's config with the project's overrides applied
+    pub effective: ClaudeExecutionConfig,
+}
+
+fn config_file(project_path: &str) -> PathBuf {
+    Path::new(project_path).join(PROJECT_CONFIG_FILE)
+}
+
+fn write_project_config(project_path: &str, config: &ProjectExecutionConfig) -> Result<(), String> {
+    let file = config_file(project_path);
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize project execution config: {}", e))?;
+    crate::config_file::write(&file, json)
+        .map_err(|e| format!("Failed to write {}: {}", file.display(), e))
+}
+
+/// Dangerous-skip set by a project only ever applies to that project, and keeps the
+/// same expiry rules as the user's setting
+fn scope_dangerous_skip(project_path: &str, permissions: &mut ClaudePermissionConfig) {
+    permissions.dangerous_skip_projects = if permissions.enable_dangerous_skip {
+        vec![project_path.to_string()]
+    } else {
+        Vec::new()
+    };
+}
+
+/// The file lives in the project and may not have been written by the workbench; an
+/// expiry further out than the workbench allows is not honoured
+fn reject_distant_expiry(project_path: &str, permissions: &mut ClaudePermissionConfig) {
+    let limit = Utc::now() + Duration::hours(DANGEROUS_SKIP_MAX_HOURS);
+    let too_far = permissions.dangerous_skip_expires_at.map_or(false, |at| at > limit);
+    if permissions.enable_dangerous_skip && too_far {
+        log::warn!(
+            "Ignoring dangerous-skip of {}: it expires more than {} hours from now",
+            project_path,
+            DANGEROUS_SKIP_MAX_HOURS
+        );
+        permissions.enable_dangerous_skip = false;
+        permissions.dangerous_skip_expires_at = None;
+    }
+}
+
+/// Read a project's config; an expired dangerous-skip is turned off and saved
+pub(crate) fn load_project_config(
+    project_path: &str,
+) -> Result<Option<ProjectExecutionConfig>, String> {
+    let file = config_file(project_path);
+    if !file.exists() {
+        return Ok(None);
+    }
+    let content = crate::config_file::read_to_string(&file)
+        .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+    let mut config: ProjectExecutionConfig = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid project execution config {}: {}", file.display(), e))?;
+
+    if let Some(permissions) = config.permissions.as_mut() {
+        reject_distant_expiry(project_path, permissions);
+        scope_dangerous_skip(project_path, permissions);
+        if permissions.revert_expired_dangerous_skip() {
+            log::warn!(
+                "Dangerous-skip of {} expired, reverting to regular permissions",
+                project_path
+            );
+            permissions.dangerous_skip_projects.clear();
+            write_project_config(project_path, &config)?;
+        }
+    }
+    Ok(Some(config))
+}
+
+/// Apply a project's overrides on top of the user's config
+fn merge(config: &mut ClaudeExecutionConfig, project: &ProjectExecutionConfig) {
+    if let Some(output_format) = &project.output_format {
+        config.output_format = output_format.clone();
+    }
+    if project.timeout_seconds.is_some() {
+        config.timeout_seconds = project.timeout_seconds;
+    }
+    if project.max_tokens.is_some() {
+        config.max_tokens = project.max_tokens;
+    }
+    if let Some(verbose) = project.verbose {
+        config.verbose = verbose;
+    }
+    if let Some(permissions) = &project.permissions {
+        config.permissions = permissions.clone();
+    }
+}
+
+async fn effective_config(
+    app: &AppHandle,
+    project: Option<&ProjectExecutionConfig>,
+) -> Result<ClaudeExecutionConfig, String> {
+    let mut config = get_claude_execution_config(app.clone()).await?;
+    if let Some(project) = project {
+        merge(&mut config, project);
+    }
+    Ok(config)
+}
+
+/// Execution config for a run in `project_path`: the user's config with the project's
+/// overrides applied. Falls back to the defaults, or to the user's config alone, when
+/// a config cannot be read.
+pub async fn resolve_execution_config(
+    app: &AppHandle,
+    project_path: &str,
+) -> ClaudeExecutionConfig {
+    let project = load_project_config(project_path).unwrap_or_else(|e| {
+        log::warn!("Ignoring project execution config: {}", e);
+        None
+    });
+    effective_config(app, project.as_ref()).await.unwrap_or_else(|e| {
+        log::warn!("Failed to load execution config, using default: {}", e);
+        ClaudeExecutionConfig::default()
+    })
+}
+
+async fn config_state(
+    app: &AppHandle,
+    project_path: String,
+    project: Option<ProjectExecutionConfig>,
+) -> Result<ProjectExecutionConfigState, String> {
+    let effective = effective_config(app, project.as_ref()).await?;
+    Ok(ProjectExecutionConfigState {
+        config_file: config_file(&project_path).to_string_lossy().into_owned(),
+        project_path,
+        project,
+        effective,
+    })
+}
+
+/// Get a project's own execution config and the config its runs use
+#[tauri::command]
+pub async fn get_project_execution_config(
+    app: AppHandle,
+    project_path: String,
+) -> Result<ProjectExecutionConfigState, String> {
+    let project = load_project_config(&project_path)?;
+    config_state(&app, project_path, project).await
+}
+
+/// Save a project's execution config to `<project>/.claude/execution_config.json`;
+/// None removes it so the user's config applies again
+#[tauri::command]
+pub async fn update_project_execution_config(
+    app: AppHandle,
+    project_path: String,
+    config: Option<ProjectExecutionConfig>,
+) -> Result<ProjectExecutionConfigState, String> {
+    if !Path::new(&project_path).is_dir() {
+        return Err(format!("Project directory does not exist: {}", project_path));
+    }
+
+    let config = match config {
+        Some(mut config) => {
+            if let Some(permissions) = config.permissions.as_mut() {
+                scope_dangerous_skip(&project_path, permissions);
+                permissions.prepare_dangerous_skip()?;
+            }
+            write_project_config(&project_path, &config)?;
+            log::info!("Updated execution config of {}", project_path);
+            Some(config)
+        }
+        None => {
+            let file = config_file(&project_path);
+            if file.exists() {
+                fs::remove_file(&file)
+                    .map_err(|e| format!("Failed to remove {}: {}", file.display(), e))?;
+                log::info!("Removed execution config of {}", project_path);
+            }
+            None
+        }
+    };
+    config_state(&app, project_path, config).await
+}
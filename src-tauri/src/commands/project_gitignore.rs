@@ -0,0 +1,284 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Manager};
+
+use super::agents::AgentDb;
+use super::settings_store;
+
+/// `app_settings` key of the consent to edit project .gitignore files
+const AUTO_IGNORE_SETTING: &str = "auto_gitignore_local_files";
+
+/// Comment above the entries the workbench adds to a .gitignore
+const GITIGNORE_HEADER: &str = "# Local Claude Workbench files";
+
+/// Files the workbench writes into projects, relative to the project root, and whether
+/// they only make sense on this machine. The others are meant to be shared but are
+/// checked for secrets.
+const WORKBENCH_FILES: &[(&str, bool)] = &[
+    (".claude/settings.local.json", true),
+    (".claude/settings.json", false),
+    (".claude/execution_config.json", false),
+    (".mcp.json", false),
+    ("HANDOFF.md", false),
+];
+
+/// Credentials recognised by their shape
+static SECRET_PATTERNS: Lazy<Vec<(&'static str, Regex)>> = Lazy::new(|| {
+    [
+        ("Anthropic API key", r"sk-ant-[A-Za-z0-9_\-]{20,}"),
+        ("OpenAI-style API key", r"\bsk-[A-Za-z0-9]{20,}"),
+        ("GitHub token", r"\bgh[pousr]_[A-Za-z0-9]{30,}"),
+        ("AWS access key", r"\bAKIA[0-9A-Z]{16}\b"),
+        ("Private key", r"-----BEGIN [A-Z ]*PRIVATE KEY-----"),
+    ]
+    .into_iter()
+    .filter_map(|(name, pattern)| Regex::new(pattern).ok().map(|re| (name, re)))
+    .collect()
+});
+
+/// Endings of JSON keys whose values are credentials
+const SECRET_KEY_SUFFIXES: &[&str] = &["token", "secret", "password", "apikey", "_key"];
+
+static AUTO_IGNORE: AtomicBool = AtomicBool::new(false);
+
+/// A workbench file in a project that git may pick up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitignoreFinding {
+    /// Relative to the project root
+    pub path: String,
+    /// The file only makes sense on this machine and belongs in .gitignore
+    pub local_only: bool,
+    pub ignored: bool,
+    /// Already committed; ignoring it no longer keeps it out of the repository
+    pub tracked: bool,
+    /// What looks like a credential in the file: JSON key paths or kinds of keys,
+    /// never the values
+    pub secrets: Vec<String>,
+}
+
+/// Returned by `audit_project_gitignore` and `ignore_local_files`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitignoreAudit {
+    pub project_path: String,
+    /// Nothing is at risk outside a git repository
+    pub is_git_repo: bool,
+    /// Local-only files are added to the project's .gitignore when written
+    pub auto_ignore: bool,
+    /// Files at risk of being committed
+    pub findings: Vec<GitignoreFinding>,
+}
+
+/// Load the consent to edit .gitignore files at startup
+pub fn init_project_gitignore(app: &AppHandle) {
+    let db = app.state::<AgentDb>();
+    let Ok(conn) = db.0.get() else {
+        return;
+    };
+    let enabled: bool = settings_store::get_or_default(&conn, AUTO_IGNORE_SETTING);
+    AUTO_IGNORE.store(enabled, Ordering::Relaxed);
+}
+
+/// Run git in `dir`, returning whether it succeeded
+fn git_succeeds(dir: &Path, args: &[&str]) -> bool {
+    let mut command = Command::new("git");
+    command.args(args).current_dir(dir);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    command.output().map_or(false, |output| output.status.success())
+}
+
+fn is_git_repo(project: &Path) -> bool {
+    git_succeeds(project, &["rev-parse", "--is-inside-work-tree"])
+}
+
+fn is_ignored(project: &Path, rel: &str) -> bool {
+    git_succeeds(project, &["check-ignore", "-q", rel])
+}
+
+fn is_tracked(project: &Path, rel: &str) -> bool {
+    git_succeeds(project, &["ls-files", "--error-unmatch", rel])
+}
+
+pub fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    SECRET_KEY_SUFFIXES.iter().any(|suffix| key.ends_with(suffix))
+}
+
+/// Key paths of string values under credential-like keys; `${VAR}` references don't count
+fn secret_keys(value: &serde_json::Value, path: &str, found: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match child.as_str() {
+                    Some(s) if is_secret_key(key) && s.len() >= 8 && !s.starts_with('$') => {
+                        found.push(child_path)
+                    }
+                    _ => secret_keys(child, &child_path, found),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                secret_keys(item, &format!("{}[{}]", path, i), found);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn find_secrets(content: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    if let Ok(json) = serde_json::from_str::<serde_json::Value>(content) {
+        secret_keys(&json, "", &mut found);
+    }
+    for (name, pattern) in SECRET_PATTERNS.iter() {
+        if pattern.is_match(content) {
+            found.push(name.to_string());
+        }
+    }
+    found
+}
+
+fn audit(project_path: &str) -> Result<GitignoreAudit, String> {
+    let project = Path::new(project_path);
+    if !project.is_dir() {
+        return Err(format!("Project directory does not exist: {}", project_path));
+    }
+    let mut result = GitignoreAudit {
+        project_path: project_path.to_string(),
+        is_git_repo: is_git_repo(project),
+        auto_ignore: AUTO_IGNORE.load(Ordering::Relaxed),
+        findings: Vec::new(),
+    };
+    if !result.is_git_repo {
+        return Ok(result);
+    }
+
+    for &(rel, local_only) in WORKBENCH_FILES {
+        let Ok(content) = fs::read_to_string(project.join(rel)) else {
+            continue;
+        };
+        let secrets = find_secrets(&content);
+        let tracked = is_tracked(project, rel);
+        let ignored = !tracked && is_ignored(project, rel);
+        let sensitive = local_only || !secrets.is_empty();
+        if sensitive && (tracked || !ignored) {
+            result.findings.push(GitignoreFinding {
+                path: rel.to_string(),
+                local_only,
+                ignored,
+                tracked,
+                secrets,
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// Append entries for `rels` to the project's .gitignore, skipping those already listed
+fn append_to_gitignore(project: &Path, rels: &[&str]) -> Result<(), String> {
+    let path = project.join(".gitignore");
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    let listed: Vec<&str> = existing.lines().map(str::trim).collect();
+    let entries: Vec<String> = rels
+        .iter()
+        .map(|rel| format!("/{}", rel))
+        .filter(|entry| !listed.contains(&entry.as_str()))
+        .collect();
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let mut content = existing.clone();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    if !listed.contains(&GITIGNORE_HEADER) {
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        content.push_str(GITIGNORE_HEADER);
+        content.push('\n');
+    }
+    for entry in &entries {
+        content.push_str(entry);
+        content.push('\n');
+    }
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    log::info!("Added {} to {}", entries.join(", "), path.display());
+    Ok(())
+}
+
+/// Called after the workbench writes a local-only file into a project; adds it to the
+/// project's .gitignore if the user agreed to that and git would otherwise pick it up
+pub fn local_file_written(project_path: &str, rel: &str) {
+    if !AUTO_IGNORE.load(Ordering::Relaxed) {
+        return;
+    }
+    let project = Path::new(project_path);
+    if !is_git_repo(project) || is_tracked(project, rel) || is_ignored(project, rel) {
+        return;
+    }
+    if let Err(e) = append_to_gitignore(project, &[rel]) {
+        log::warn!("Failed to ignore {} in {}: {}", rel, project_path, e);
+    }
+}
+
+/// Report workbench files in a project that could be committed: local-only files git
+/// does not ignore, and files that appear to contain credentials
+#[tauri::command]
+pub async fn audit_project_gitignore(project_path: String) -> Result<GitignoreAudit, String> {
+    tokio::task::spawn_blocking(move || audit(&project_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Add the given workbench files, or every local-only one found by the audit, to the
+/// project's .gitignore. Files already committed stay tracked until removed from git.
+#[tauri::command]
+pub async fn ignore_local_files(
+    project_path: String,
+    paths: Option<Vec<String>>,
+) -> Result<GitignoreAudit, String> {
+    tokio::task::spawn_blocking(move || {
+        let findings = audit(&project_path)?.findings;
+        let rels: Vec<&str> = findings
+            .iter()
+            .filter(|f| !f.ignored && !f.tracked)
+            .filter(|f| match &paths {
+                Some(paths) => paths.contains(&f.path),
+                None => f.local_only,
+            })
+            .map(|f| f.path.as_str())
+            .collect();
+        append_to_gitignore(Path::new(&project_path), &rels)?;
+        audit(&project_path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Allow or stop adding local-only files to project .gitignore files as they are written
+#[tauri::command]
+pub async fn set_auto_gitignore(app: AppHandle, enabled: bool) -> Result<(), String> {
+    settings_store::set(&app, AUTO_IGNORE_SETTING, &enabled)?;
+    AUTO_IGNORE.store(enabled, Ordering::Relaxed);
+    log::info!(
+        "Automatic .gitignore of local files {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(())
+}
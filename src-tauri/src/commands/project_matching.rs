@@ -0,0 +1,378 @@
+use rusqlite::{params, Connection, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use tauri::{AppHandle, Manager, State};
+
+use super::agents::AgentDb;
+use super::claude::{
+    get_claude_dir, list_project_dirs, merge_duplicate_projects, read_hidden_projects, scan_project_dir, Project,
+};
+use super::settings_store;
+use crate::paths::{self, CaseSensitivity};
+
+/// `app_settings` key holding the case sensitivity override
+const CASE_SETTING: &str = "path_case_sensitivity";
+
+/// Projects `list_projects` lists on their own even when they share a path with others
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeExclusions {
+    /// Project paths, e.g. the root of a worktree; compared like project paths
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Project directory IDs split off with `split_merged_project`
+    #[serde(default)]
+    pub project_ids: Vec<String>,
+}
+
+impl MergeExclusions {
+    pub fn excludes(&self, project: &Project) -> bool {
+        if self.project_ids.iter().any(|id| id == &project.id) {
+            return true;
+        }
+        if self.paths.is_empty() {
+            return false;
+        }
+        let key = paths::comparison_key(&project.path);
+        self.paths.iter().any(|path| paths::comparison_key(path) == key)
+    }
+}
+
+/// Read the merge exclusions; a missing or unreadable value means none
+pub(crate) fn read_merge_exclusions(conn: &Connection) -> MergeExclusions {
+    settings_store::get_or_default(conn, settings_store::MERGE_EXCLUSIONS)
+}
+
+/// How project paths are currently compared
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathCaseStatus {
+    pub mode: CaseSensitivity,
+    /// Whether the volume holding ~/.claude is treated as case-sensitive
+    pub claude_dir_case_sensitive: bool,
+    /// Assumed for volumes that can't be probed
+    pub platform_default_case_sensitive: bool,
+}
+
+/// Returned by `repair_merged_projects`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectRepairReport {
+    pub dry_run: bool,
+    /// Project paths that case-insensitive matching treated as one project
+    pub merged_groups: Vec<Vec<String>>,
+    /// Memory entries moved to the single project they belonged to
+    pub memory_entries_moved: usize,
+    /// Memory entries shared by merged projects, copied to each of them
+    pub memory_entries_copied: usize,
+    pub knowledge_sources_moved: usize,
+    /// Knowledge sources that can't be attributed to one project and were dropped
+    pub knowledge_sources_dropped: usize,
+    /// Projects whose docs have to be ingested again
+    pub reingest_projects: Vec<String>,
+    pub trust_entries_rekeyed: usize,
+}
+
+/// Load the case sensitivity override into the path module, called once at startup
+pub fn init_path_case_sensitivity(app: &AppHandle) {
+    let db = app.state::<AgentDb>();
+    let Ok(conn) = db.0.get() else {
+        return;
+    };
+    if let Some(mode) = settings_store::get_or_default::<Option<CaseSensitivity>>(&conn, CASE_SETTING) {
+        paths::set_case_mode(mode);
+    }
+}
+
+fn current_status() -> PathCaseStatus {
+    let claude_dir_case_sensitive = get_claude_dir()
+        .map(|dir| paths::is_case_sensitive(&dir.to_string_lossy()))
+        .unwrap_or_else(|_| paths::platform_default_case_sensitive());
+    PathCaseStatus {
+        mode: paths::case_mode(),
+        claude_dir_case_sensitive,
+        platform_default_case_sensitive: paths::platform_default_case_sensitive(),
+    }
+}
+
+/// Get how project paths are compared for case
+#[tauri::command]
+pub async fn get_path_case_sensitivity() -> Result<PathCaseStatus, String> {
+    Ok(current_status())
+}
+
+/// Override case sensitivity detection, or go back to `auto`
+#[tauri::command]
+pub async fn set_path_case_sensitivity(app: AppHandle, mode: CaseSensitivity) -> Result<PathCaseStatus, String> {
+    settings_store::set(&app, CASE_SETTING, &mode)?;
+    paths::set_case_mode(mode);
+    log::info!("Path case sensitivity set to {:?}", mode);
+    Ok(current_status())
+}
+
+/// Project paths known from session files and workspace trust decisions
+fn known_project_paths(conn: &Connection) -> Result<BTreeSet<String>, String> {
+    let mut known = BTreeSet::new();
+
+    let projects_dir = get_claude_dir().map_err(|e| e.to_string())?.join("projects");
+    if projects_dir.exists() {
+        for dir in list_project_dirs(&projects_dir, &[])? {
+            match scan_project_dir(&dir) {
+                Ok(project) => {
+                    known.insert(project.path);
+                }
+                Err(e) => log::debug!("Skipping project dir {:?}: {}", dir, e),
+            }
+        }
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT project_path FROM project_trust")
+        .map_err(|e| e.to_string())?;
+    let trusted = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    known.extend(trusted);
+    Ok(known)
+}
+
+fn distinct_keys(tx: &Transaction, sql: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = tx.prepare(sql)?;
+    let keys = stmt.query_map([], |row| row.get(0))?.collect();
+    keys
+}
+
+/// Re-key rows stored under a key the old case-insensitive matching produced
+fn repair_stores(
+    tx: &Transaction,
+    known: &BTreeSet<String>,
+    report: &mut ProjectRepairReport,
+) -> rusqlite::Result<()> {
+    // Old key -> current key -> a path with that key
+    let mut by_legacy_key: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    for path in known {
+        by_legacy_key
+            .entry(paths::comparison_key_with(path, false))
+            .or_default()
+            .insert(paths::comparison_key(path), path.clone());
+    }
+    let current_keys: BTreeSet<&String> = by_legacy_key.values().flat_map(|keys| keys.keys()).collect();
+    report.merged_groups = by_legacy_key
+        .values()
+        .filter(|keys| keys.len() > 1)
+        .map(|keys| keys.values().cloned().collect())
+        .collect();
+
+    // Stored keys that no project has any more, with the projects they were derived from
+    let stale = |key: &String| -> Option<&BTreeMap<String, String>> {
+        if current_keys.contains(key) {
+            return None;
+        }
+        by_legacy_key.get(key)
+    };
+
+    for key in distinct_keys(tx, "SELECT DISTINCT project_path FROM memory_entries")? {
+        let Some(targets) = stale(&key) else {
+            continue;
+        };
+        if targets.len() == 1 {
+            let target = targets.keys().next().unwrap();
+            report.memory_entries_moved += tx.execute(
+                "UPDATE OR IGNORE memory_entries SET project_path = ?1 WHERE project_path = ?2",
+                params![target, key],
+            )?;
+        } else {
+            for target in targets.keys() {
+                report.memory_entries_copied += tx.execute(
+                    "INSERT OR IGNORE INTO memory_entries (project_path, key, content, created_at, updated_at)
+                     SELECT ?1, key, content, created_at, updated_at FROM memory_entries WHERE project_path = ?2",
+                    params![target, key],
+                )?;
+            }
+        }
+        // Entries already stored under the new key win over the stale ones
+        tx.execute("DELETE FROM memory_entries WHERE project_path = ?1", params![key])?;
+    }
+
+    for key in distinct_keys(tx, "SELECT DISTINCT project_path FROM knowledge_sources")? {
+        let Some(targets) = stale(&key) else {
+            continue;
+        };
+        if targets.len() == 1 {
+            let target = targets.keys().next().unwrap();
+            // Sources the project already indexed under the new key keep their chunks
+            tx.execute(
+                "UPDATE knowledge_chunks SET project_path = ?1 WHERE project_path = ?2
+                 AND source NOT IN (SELECT source FROM knowledge_sources WHERE project_path = ?1)",
+                params![target, key],
+            )?;
+            report.knowledge_sources_moved += tx.execute(
+                "UPDATE OR IGNORE knowledge_sources SET project_path = ?1 WHERE project_path = ?2",
+                params![target, key],
+            )?;
+        } else {
+            // Chunks of several checkouts were indexed together; only re-ingesting can tell them apart
+            report.reingest_projects.extend(targets.values().cloned());
+        }
+        report.knowledge_sources_dropped +=
+            tx.execute("DELETE FROM knowledge_sources WHERE project_path = ?1", params![key])?;
+        tx.execute("DELETE FROM knowledge_chunks WHERE project_path = ?1", params![key])?;
+    }
+
+    // Trust rows carry their path, so the current key can be recomputed directly
+    let trust_rows: Vec<(String, String)> = {
+        let mut stmt = tx.prepare("SELECT normalized_path, project_path FROM project_trust")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect::<Result<_, _>>()?;
+        rows
+    };
+    for (stored_key, path) in trust_rows {
+        let key = paths::comparison_key(&path);
+        if key == stored_key {
+            continue;
+        }
+        report.trust_entries_rekeyed += tx.execute(
+            "UPDATE OR IGNORE project_trust SET normalized_path = ?1 WHERE normalized_path = ?2",
+            params![key, stored_key],
+        )?;
+        // A decision already recorded under the new key is more recent
+        tx.execute("DELETE FROM project_trust WHERE normalized_path = ?1", params![stored_key])?;
+    }
+
+    Ok(())
+}
+
+/// Split data that case-insensitive path matching attached to the wrong project.
+/// Project listing picks up the corrected matching on its own; this moves memory,
+/// knowledge and workspace trust stored under the old keys. With `dry_run` nothing
+/// is written.
+#[tauri::command]
+pub async fn repair_merged_projects(
+    db: State<'_, AgentDb>,
+    dry_run: Option<bool>,
+) -> Result<ProjectRepairReport, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    let mut conn = db.0.get().map_err(|e| e.to_string())?;
+    let known = known_project_paths(&conn)?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut report = ProjectRepairReport {
+        dry_run,
+        ..Default::default()
+    };
+    repair_stores(&tx, &known, &mut report).map_err(|e| e.to_string())?;
+    report.reingest_projects.sort();
+    report.reingest_projects.dedup();
+
+    if dry_run {
+        tx.rollback().map_err(|e| e.to_string())?;
+    } else {
+        tx.commit().map_err(|e| e.to_string())?;
+        log::info!(
+            "Repaired merged projects: {} groups, {} memory entries moved, {} copied, {} knowledge sources moved, {} dropped, {} trust entries",
+            report.merged_groups.len(),
+            report.memory_entries_moved,
+            report.memory_entries_copied,
+            report.knowledge_sources_moved,
+            report.knowledge_sources_dropped,
+            report.trust_entries_rekeyed
+        );
+    }
+    Ok(report)
+}
+
+/// Projects excluded from the duplicate merge
+#[tauri::command]
+pub async fn list_project_merge_exclusions(db: State<'_, AgentDb>) -> Result<MergeExclusions, String> {
+    Ok(read_merge_exclusions(&*db.0.get()?))
+}
+
+/// Keep every project directory for `path` out of the duplicate merge, e.g. for a
+/// worktree that should be listed separately
+#[tauri::command]
+pub async fn exclude_from_project_merge(app: AppHandle, path: String) -> Result<MergeExclusions, String> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("Project path is empty".to_string());
+    }
+    let key = paths::comparison_key(path);
+    settings_store::update(&app, settings_store::MERGE_EXCLUSIONS, |exclusions: &mut MergeExclusions| {
+        if exclusions.paths.iter().any(|p| paths::comparison_key(p) == key) {
+            return Ok(false);
+        }
+        exclusions.paths.push(path.to_string());
+        log::info!("Excluded {} from project merging", path);
+        Ok(true)
+    })
+}
+
+/// Let a path or project ID take part in the duplicate merge again
+#[tauri::command]
+pub async fn remove_project_merge_exclusion(
+    app: AppHandle,
+    entry: String,
+) -> Result<MergeExclusions, String> {
+    let entry = entry.trim();
+    let key = paths::comparison_key(entry);
+    settings_store::update(&app, settings_store::MERGE_EXCLUSIONS, |exclusions: &mut MergeExclusions| {
+        let before = exclusions.paths.len() + exclusions.project_ids.len();
+        exclusions.paths.retain(|p| paths::comparison_key(p) != key);
+        exclusions.project_ids.retain(|id| id != entry);
+        if exclusions.paths.len() + exclusions.project_ids.len() == before {
+            return Err(format!("'{}' is not excluded from project merging", entry));
+        }
+        log::info!("Removed project merge exclusion {}", entry);
+        Ok(true)
+    })
+}
+
+/// Undo the merge behind a listed project: the directories it was merged from are
+/// listed as separate projects from now on. Returns those projects.
+#[tauri::command]
+pub async fn split_merged_project(app: AppHandle, project_id: String) -> Result<Vec<Project>, String> {
+    use rayon::prelude::*;
+
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let projects_dir = claude_dir.join("projects");
+    if !projects_dir.exists() {
+        return Err(format!("Project not found: {}", project_id));
+    }
+    let (hidden_projects, exclusions) = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.get()?;
+        (read_hidden_projects(&conn), read_merge_exclusions(&conn))
+    };
+    let dirs = list_project_dirs(&projects_dir, &hidden_projects)?;
+    let scanned = tokio::task::spawn_blocking(move || {
+        dirs.par_iter()
+            .map(scan_project_dir)
+            .collect::<Result<Vec<_>, String>>()
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let merged = merge_duplicate_projects(scanned.clone(), &exclusions)
+        .into_iter()
+        .find(|project| project.id == project_id)
+        .ok_or_else(|| format!("Project not found: {}", project_id))?;
+    if merged.merged_from.is_empty() {
+        return Err(format!("Project '{}' is not merged with other projects", project_id));
+    }
+
+    let split_ids: HashSet<String> = std::iter::once(merged.id).chain(merged.merged_from).collect();
+    settings_store::update(&app, settings_store::MERGE_EXCLUSIONS, |exclusions: &mut MergeExclusions| {
+        let before = exclusions.project_ids.len();
+        for id in &split_ids {
+            if !exclusions.project_ids.contains(id) {
+                exclusions.project_ids.push(id.clone());
+            }
+        }
+        Ok(exclusions.project_ids.len() != before)
+    })?;
+    log::info!("Split project {} into {} projects", project_id, split_ids.len());
+
+    let mut projects: Vec<Project> = scanned
+        .into_iter()
+        .filter(|project| split_ids.contains(&project.id))
+        .collect();
+    projects.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(projects)
+}
@@ -0,0 +1,221 @@
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use ts_rs::TS;
+
+use super::agents::AgentDb;
+use super::claude::{
+    get_claude_dir, list_project_dirs, merge_duplicate_projects, read_hidden_projects,
+    scan_project_dir, Project,
+};
+use super::events::AppEvent;
+use super::project_matching::read_merge_exclusions;
+
+/// Minimum time between two `projects-scan-progress` events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Finished scans kept around for `get_project_scan`
+const MAX_KEPT_SCANS: usize = 4;
+
+/// State of one background scan
+struct ScanState {
+    started: Instant,
+    total: usize,
+    /// Directories scanned so far
+    scanned: usize,
+    projects: Vec<Project>,
+    done: bool,
+}
+
+static SCANS: Lazy<Mutex<HashMap<String, ScanState>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Result of the last completed scan, returned immediately by the next `start_project_scan`
+static LAST_SCAN: Lazy<Mutex<Vec<Project>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Returned by `start_project_scan` before the scan finishes
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ProjectScanSnapshot {
+    /// Identifies the scan in progress events and `get_project_scan`
+    pub token: String,
+    /// Number of project directories being scanned
+    #[ts(type = "number")]
+    pub total: usize,
+    /// Projects from the previous completed scan, to show while this one runs
+    pub projects: Vec<Project>,
+}
+
+/// Payload of `projects-scan-progress` and `get_project_scan`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ProjectScanProgress {
+    pub token: String,
+    #[ts(type = "number")]
+    pub scanned: usize,
+    #[ts(type = "number")]
+    pub total: usize,
+    pub done: bool,
+    /// Progress events carry only projects scanned since the previous event;
+    /// `get_project_scan` and the final event carry the merged, sorted list
+    pub projects: Vec<Project>,
+}
+
+fn forget_old_scans(scans: &mut HashMap<String, ScanState>) {
+    while scans.len() > MAX_KEPT_SCANS {
+        let oldest = scans
+            .iter()
+            .filter(|(_, scan)| scan.done)
+            .min_by_key(|(_, scan)| scan.started)
+            .map(|(token, _)| token.clone());
+        match oldest {
+            Some(token) => {
+                scans.remove(&token);
+            }
+            None => break,
+        }
+    }
+}
+
+fn run_scan(app: AppHandle, token: String, dirs: Vec<PathBuf>) {
+    let total = dirs.len();
+    let (sender, receiver) = mpsc::channel::<Project>();
+
+    std::thread::spawn(move || {
+        dirs.par_iter().for_each_with(sender, |sender, dir| match scan_project_dir(dir) {
+            Ok(project) => {
+                let _ = sender.send(project);
+            }
+            Err(e) => log::warn!("Failed to scan project directory {:?}: {}", dir, e),
+        });
+    });
+
+    let mut scanned = Vec::new();
+    let mut pending = Vec::new();
+    let mut last_emit = Instant::now();
+    loop {
+        match receiver.recv_timeout(PROGRESS_INTERVAL) {
+            Ok(project) => pending.push(project),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            // All directories scanned
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+        if pending.is_empty() || last_emit.elapsed() < PROGRESS_INTERVAL {
+            continue;
+        }
+
+        scanned.extend(pending.iter().cloned());
+        if let Ok(mut scans) = SCANS.lock() {
+            if let Some(scan) = scans.get_mut(&token) {
+                scan.scanned = scanned.len();
+                scan.projects = scanned.clone();
+            }
+        }
+        let _ = AppEvent::ProjectsScanProgress(ProjectScanProgress {
+            token: token.clone(),
+            scanned: scanned.len(),
+            total,
+            done: false,
+            projects: std::mem::take(&mut pending),
+        })
+        .emit(&app);
+        last_emit = Instant::now();
+    }
+
+    scanned.append(&mut pending);
+    let scanned_count = scanned.len();
+    let exclusions = app
+        .state::<AgentDb>()
+        .0
+        .get()
+        .map(|conn| read_merge_exclusions(&conn))
+        .unwrap_or_default();
+    let projects = merge_duplicate_projects(scanned, &exclusions);
+    log::info!("Project scan {} finished: {} directories, {} unique projects", token, total, projects.len());
+
+    if let Ok(mut last) = LAST_SCAN.lock() {
+        *last = projects.clone();
+    }
+    if let Ok(mut scans) = SCANS.lock() {
+        if let Some(scan) = scans.get_mut(&token) {
+            scan.scanned = scanned_count;
+            scan.projects = projects.clone();
+            scan.done = true;
+        }
+        forget_old_scans(&mut scans);
+    }
+    let _ = AppEvent::ProjectsScanProgress(ProjectScanProgress {
+        token,
+        scanned: scanned_count,
+        total,
+        done: true,
+        projects,
+    })
+    .emit(&app);
+}
+
+/// Start scanning ~/.claude/projects in the background.
+///
+/// Returns at once with a token and the previous scan's projects; partial results
+/// arrive as `projects-scan-progress` events, the last one with `done: true`.
+#[tauri::command]
+pub async fn start_project_scan(app: AppHandle) -> Result<ProjectScanSnapshot, String> {
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let projects_dir = claude_dir.join("projects");
+    let hidden_projects = read_hidden_projects(&*app.state::<AgentDb>().0.get()?);
+    let dirs = if projects_dir.exists() {
+        list_project_dirs(&projects_dir, &hidden_projects)?
+    } else {
+        Vec::new()
+    };
+
+    let token = uuid::Uuid::new_v4().to_string();
+    let total = dirs.len();
+    SCANS.lock().map_err(|e| e.to_string())?.insert(
+        token.clone(),
+        ScanState {
+            started: Instant::now(),
+            total,
+            scanned: 0,
+            projects: Vec::new(),
+            done: false,
+        },
+    );
+
+    let scan_token = token.clone();
+    tokio::task::spawn_blocking(move || run_scan(app, scan_token, dirs));
+
+    let projects = LAST_SCAN.lock().map_err(|e| e.to_string())?.clone();
+    Ok(ProjectScanSnapshot { token, total, projects })
+}
+
+/// Current state of a scan started with `start_project_scan`
+#[tauri::command]
+pub async fn get_project_scan(
+    db: State<'_, AgentDb>,
+    token: String,
+) -> Result<ProjectScanProgress, String> {
+    let scans = SCANS.lock().map_err(|e| e.to_string())?;
+    let scan = scans
+        .get(&token)
+        .ok_or_else(|| format!("Unknown project scan: {}", token))?;
+
+    let projects = if scan.done {
+        scan.projects.clone()
+    } else {
+        let exclusions = read_merge_exclusions(&*db.0.get()?);
+        merge_duplicate_projects(scan.projects.clone(), &exclusions)
+    };
+    Ok(ProjectScanProgress {
+        token,
+        scanned: scan.scanned,
+        total: scan.total,
+        done: scan.done,
+        projects,
+    })
+}
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::claude_md_lint::estimate_tokens;
+use super::usage::estimate_message_cost;
+
+/// Tokens the CLI's own system prompt and tool definitions add to a request, roughly
+const CLI_OVERHEAD_TOKENS: usize = 12_000;
+
+/// What Claude charges for an image of about 1.15 megapixels; larger ones are scaled down
+const IMAGE_TOKENS: usize = 1_600;
+
+/// Attachments larger than this are estimated from their size instead of being read
+const MAX_ATTACHMENT_BYTES: u64 = 4 * 1024 * 1024;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp"];
+
+/// Memory files the CLI loads from the project directory and each of its parents
+const CLAUDE_MD_FILES: &[&str] = &["CLAUDE.md", "CLAUDE.local.md", ".claude/CLAUDE.md"];
+
+/// Where part of the estimated input comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextSource {
+    Prompt,
+    /// CLI system prompt and tool definitions
+    Overhead,
+    ClaudeMd,
+    Attachment,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextPart {
+    pub source: ContextSource,
+    /// File the part was read from
+    pub path: Option<String>,
+    pub tokens: usize,
+}
+
+/// Returned by `estimate_prompt_cost`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptCostEstimate {
+    /// The model the prompt would run on, after alias resolution
+    pub model: String,
+    pub input_tokens: usize,
+    pub parts: Vec<ContextPart>,
+    /// Memory files and overhead read from the prompt cache
+    pub min_cost_usd: f64,
+    /// Memory files and overhead written to the prompt cache
+    pub max_cost_usd: f64,
+    /// False when there is no price for the model; the costs are then zero
+    pub priced: bool,
+}
+
+/// The CLI's bare aliases run the latest generation
+fn pricing_model(model: &str) -> &str {
+    match model {
+        "opus" => "claude-opus-4",
+        "sonnet" => "claude-sonnet-4",
+        other => other,
+    }
+}
+
+fn claude_md_files(project: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = project
+        .ancestors()
+        .flat_map(|dir| CLAUDE_MD_FILES.iter().map(move |name| dir.join(name)))
+        .collect();
+    if let Some(user) = dirs::home_dir().map(|home| home.join(".claude").join("CLAUDE.md")) {
+        if !files.contains(&user) {
+            files.push(user);
+        }
+    }
+    files.into_iter().filter(|path| path.is_file()).collect()
+}
+
+fn attachment_tokens(path: &Path) -> Result<usize, String> {
+    let metadata = fs::metadata(path)
+        .map_err(|e| format!("Failed to read attachment {}: {}", path.display(), e))?;
+    let is_image = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+    if is_image {
+        return Ok(IMAGE_TOKENS);
+    }
+    if metadata.len() > MAX_ATTACHMENT_BYTES {
+        return Ok((metadata.len() / 4) as usize);
+    }
+    let bytes =
+        fs::read(path).map_err(|e| format!("Failed to read attachment {}: {}", path.display(), e))?;
+    Ok(match String::from_utf8(bytes) {
+        Ok(text) => estimate_tokens(&text),
+        Err(e) => e.into_bytes().len().div_ceil(4),
+    })
+}
+
+fn estimate(
+    project_path: &str,
+    prompt: &str,
+    model: String,
+    attachments: &[String],
+) -> Result<PromptCostEstimate, String> {
+    let project = Path::new(project_path);
+    let mut parts = vec![
+        ContextPart { source: ContextSource::Prompt, path: None, tokens: estimate_tokens(prompt) },
+        ContextPart { source: ContextSource::Overhead, path: None, tokens: CLI_OVERHEAD_TOKENS },
+    ];
+    for path in claude_md_files(project) {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        parts.push(ContextPart {
+            source: ContextSource::ClaudeMd,
+            path: Some(path.to_string_lossy().to_string()),
+            tokens: estimate_tokens(&content),
+        });
+    }
+    for attachment in attachments {
+        let path = project.join(attachment);
+        parts.push(ContextPart {
+            source: ContextSource::Attachment,
+            tokens: attachment_tokens(&path)?,
+            path: Some(path.to_string_lossy().to_string()),
+        });
+    }
+
+    // The prompt and attachments are new input; the rest is the cacheable prefix
+    let (fresh, cacheable) = parts.iter().fold((0, 0), |(fresh, cacheable), part| {
+        match part.source {
+            ContextSource::Prompt | ContextSource::Attachment => (fresh + part.tokens, cacheable),
+            ContextSource::Overhead | ContextSource::ClaudeMd => (fresh, cacheable + part.tokens),
+        }
+    });
+    let priced_model = pricing_model(&model);
+    let min_cost_usd = estimate_message_cost(
+        priced_model,
+        &serde_json::json!({ "input_tokens": fresh, "cache_read_input_tokens": cacheable }),
+    );
+    let max_cost_usd = estimate_message_cost(
+        priced_model,
+        &serde_json::json!({ "input_tokens": fresh, "cache_creation_input_tokens": cacheable }),
+    );
+
+    Ok(PromptCostEstimate {
+        model,
+        input_tokens: fresh + cacheable,
+        parts,
+        min_cost_usd,
+        max_cost_usd,
+        priced: max_cost_usd > 0.0,
+    })
+}
+
+/// Estimate the input tokens and cost of sending a prompt: the prompt itself, the CLI's
+/// overhead, the CLAUDE.md files it loads and the attached files (relative to the
+/// project). Output tokens are not included; they depend on the answer.
+#[tauri::command]
+pub async fn estimate_prompt_cost(
+    app: AppHandle,
+    project_path: String,
+    prompt: String,
+    model: String,
+    attachments: Option<Vec<String>>,
+) -> Result<PromptCostEstimate, String> {
+    let model = super::model_aliases::resolve_model_alias(&app, &model);
+    tokio::task::spawn_blocking(move || {
+        estimate(&project_path, &prompt, model, &attachments.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
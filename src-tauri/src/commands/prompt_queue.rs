@@ -0,0 +1,193 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use tauri::AppHandle;
+use ts_rs::TS;
+
+use super::events::AppEvent;
+
+/// Follow-up prompts waiting for their session's current run to finish, by session id
+static QUEUES: Lazy<Mutex<HashMap<String, VecDeque<QueuedPrompt>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A prompt queued to run after the session's current run completes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedPrompt {
+    pub id: String,
+    pub session_id: String,
+    pub project_path: String,
+    pub prompt: String,
+    pub model: String,
+    pub provider_id: Option<String>,
+    pub queued_at: String,
+}
+
+/// Payload of `queued-prompt-started:<session_id>`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct QueuedPromptStarted {
+    pub session_id: String,
+    pub prompt_id: String,
+    pub prompt: String,
+    /// Prompts still queued after this one
+    #[ts(type = "number")]
+    pub remaining: usize,
+}
+
+fn queue_snapshot(session_id: &str) -> Result<Vec<QueuedPrompt>, String> {
+    let queues = QUEUES.lock().map_err(|e| e.to_string())?;
+    Ok(queues
+        .get(session_id)
+        .map(|queue| queue.iter().cloned().collect())
+        .unwrap_or_default())
+}
+
+/// Prompts queued across all sessions
+pub fn queued_prompt_count() -> usize {
+    QUEUES
+        .lock()
+        .map(|queues| queues.values().map(VecDeque::len).sum())
+        .unwrap_or(0)
+}
+
+fn pop_next(session_id: &str) -> Option<(QueuedPrompt, usize)> {
+    let mut queues = QUEUES.lock().ok()?;
+    let queue = queues.get_mut(session_id)?;
+    let next = queue.pop_front()?;
+    let remaining = queue.len();
+    if queue.is_empty() {
+        queues.remove(session_id);
+    }
+    Some((next, remaining))
+}
+
+/// Start the next queued prompt of a session whose run just completed.
+///
+/// Only called after a successful run; a failed run leaves the queue for the user to
+/// resume or clear. Returns false if nothing was queued.
+pub fn dispatch_next(app: &AppHandle, session_id: &str) -> bool {
+    let Some((next, remaining)) = pop_next(session_id) else {
+        return false;
+    };
+    log::info!(
+        "Dispatching queued prompt {} for session {} ({} left)",
+        next.id,
+        session_id,
+        remaining
+    );
+
+    let _ = AppEvent::QueuedPromptStarted(QueuedPromptStarted {
+        session_id: next.session_id.clone(),
+        prompt_id: next.id.clone(),
+        prompt: next.prompt.clone(),
+        remaining,
+    })
+    .emit(app);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let session_id = next.session_id.clone();
+        if let Err(e) = super::claude::resume_claude_code(
+            app.clone(),
+            next.project_path,
+            next.session_id,
+            next.prompt,
+            next.model,
+            next.provider_id,
+            None,
+            None,
+            None,
+        )
+        .await
+        {
+            log::error!("Failed to start queued prompt for session {}: {}", session_id, e);
+            let _ = AppEvent::ClaudeError {
+                session_id: Some(session_id),
+                line: format!("Failed to start queued prompt: {}", e),
+            }
+            .emit_with_global(&app);
+        }
+    });
+    true
+}
+
+/// Queue a prompt to run via resume as soon as the session's current run completes
+#[tauri::command]
+pub async fn queue_followup_prompt(
+    session_id: String,
+    project_path: String,
+    prompt: String,
+    model: String,
+    provider_id: Option<String>,
+) -> Result<QueuedPrompt, String> {
+    if prompt.trim().is_empty() {
+        return Err("Prompt is empty".to_string());
+    }
+    let queued = QueuedPrompt {
+        id: uuid::Uuid::new_v4().to_string(),
+        session_id: session_id.clone(),
+        project_path,
+        prompt,
+        model,
+        provider_id,
+        queued_at: chrono::Utc::now().to_rfc3339(),
+    };
+    QUEUES
+        .lock()
+        .map_err(|e| e.to_string())?
+        .entry(session_id)
+        .or_default()
+        .push_back(queued.clone());
+    Ok(queued)
+}
+
+/// List a session's queued prompts in dispatch order
+#[tauri::command]
+pub async fn list_queued_prompts(session_id: String) -> Result<Vec<QueuedPrompt>, String> {
+    queue_snapshot(&session_id)
+}
+
+/// Remove a queued prompt; returns false if it was not queued (e.g. already started)
+#[tauri::command]
+pub async fn remove_queued_prompt(session_id: String, prompt_id: String) -> Result<bool, String> {
+    let mut queues = QUEUES.lock().map_err(|e| e.to_string())?;
+    let Some(queue) = queues.get_mut(&session_id) else {
+        return Ok(false);
+    };
+    let before = queue.len();
+    queue.retain(|queued| queued.id != prompt_id);
+    let removed = queue.len() != before;
+    if queue.is_empty() {
+        queues.remove(&session_id);
+    }
+    Ok(removed)
+}
+
+/// Reorder a session's queue. `prompt_ids` lists the new order; queued prompts it
+/// omits (e.g. queued meanwhile) keep their relative order at the end.
+#[tauri::command]
+pub async fn reorder_queued_prompts(
+    session_id: String,
+    prompt_ids: Vec<String>,
+) -> Result<Vec<QueuedPrompt>, String> {
+    {
+        let mut queues = QUEUES.lock().map_err(|e| e.to_string())?;
+        if let Some(queue) = queues.get_mut(&session_id) {
+            let mut remaining: Vec<QueuedPrompt> = queue.drain(..).collect();
+            for id in &prompt_ids {
+                if let Some(index) = remaining.iter().position(|queued| &queued.id == id) {
+                    queue.push_back(remaining.remove(index));
+                }
+            }
+            queue.extend(remaining);
+        }
+    }
+    queue_snapshot(&session_id)
+}
+
+/// Drop every queued prompt of a session
+#[tauri::command]
+pub async fn clear_prompt_queue(session_id: String) -> Result<(), String> {
+    QUEUES.lock().map_err(|e| e.to_string())?.remove(&session_id);
+    Ok(())
+}
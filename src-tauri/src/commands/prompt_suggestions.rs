@@ -0,0 +1,298 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+
+/// Directories never scanned for TODOs
+const IGNORED_DIRS: &[&str] = &[
+    ".git", "node_modules", "target", "dist", "build", ".next", ".venv", "venv", "__pycache__", "vendor",
+];
+
+/// Source extensions scanned for TODO/FIXME comments
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "rs", "ts", "tsx", "js", "jsx", "py", "go", "java", "kt", "swift", "c", "cc", "cpp", "h", "hpp", "cs",
+    "rb", "php", "vue", "svelte",
+];
+
+/// Stop the TODO scan after this many files
+const MAX_SCANNED_FILES: usize = 2000;
+
+/// Skip source files larger than this
+const MAX_FILE_BYTES: u64 = 512 * 1024;
+
+/// Files whose unchecked `- [ ]` items are treated as open tasks
+const TASK_FILES: &[&str] = &["README.md", "TODO.md", "ROADMAP.md"];
+
+const DEFAULT_LIMIT: usize = 5;
+
+/// Where a suggestion came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionSource {
+    FailingTests,
+    UncommittedChanges,
+    Todo,
+    ReadmeTask,
+    RecentCommit,
+}
+
+/// A candidate next prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptSuggestion {
+    pub prompt: String,
+    pub source: SuggestionSource,
+    /// What the suggestion is based on, e.g. `src/main.rs:42` or a commit hash
+    pub provenance: String,
+    /// Rewritten by the model rather than filled from a template
+    pub refined: bool,
+}
+
+fn suggestion(source: SuggestionSource, provenance: String, prompt: String) -> PromptSuggestion {
+    PromptSuggestion {
+        prompt,
+        source,
+        provenance,
+        refined: false,
+    }
+}
+
+fn git(project_path: &Path, args: &[&str]) -> Option<String> {
+    let mut command = std::process::Command::new("git");
+    command.args(args).current_dir(project_path);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    let output = command.output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Uncommitted changes and the latest commit
+fn git_suggestions(project_path: &Path) -> Vec<PromptSuggestion> {
+    let mut suggestions = Vec::new();
+
+    if let Some(status) = git(project_path, &["status", "--porcelain"]) {
+        let changed = status.lines().filter(|l| !l.trim().is_empty()).count();
+        if changed > 0 {
+            suggestions.push(suggestion(
+                SuggestionSource::UncommittedChanges,
+                format!("git status: {} changed files", changed),
+                format!(
+                    "Review my {} uncommitted changed files for bugs, then propose a commit message",
+                    changed
+                ),
+            ));
+        }
+    }
+
+    if let Some(log) = git(project_path, &["log", "-n", "1", "--pretty=format:%h%x09%s"]) {
+        if let Some((hash, subject)) = log.trim().split_once('\t') {
+            suggestions.push(suggestion(
+                SuggestionSource::RecentCommit,
+                format!("commit {}", hash),
+                format!(
+                    "Check the latest commit {} (\"{}\") for edge cases and add missing tests",
+                    hash, subject
+                ),
+            ));
+        }
+    }
+    suggestions
+}
+
+/// Failures recorded by the last pytest or vitest run
+fn failing_test_suggestions(project_path: &Path) -> Vec<PromptSuggestion> {
+    let mut failed: Vec<(String, String)> = Vec::new();
+
+    // pytest: {"tests/test_x.py::test_y": true, ...}
+    let pytest_cache = project_path.join(".pytest_cache/v/cache/lastfailed");
+    if let Some(map) = fs::read_to_string(&pytest_cache)
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&content).ok())
+    {
+        failed.extend(map.keys().map(|test| (test.clone(), ".pytest_cache".to_string())));
+    }
+
+    // vitest: {"results": {"path": {"failed": true, ...}}}
+    let vitest_results = project_path.join("node_modules/.vitest/results.json");
+    if let Some(results) = fs::read_to_string(&vitest_results)
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|value| value.get("results").and_then(|r| r.as_object()).cloned())
+    {
+        failed.extend(
+            results
+                .iter()
+                .filter(|(_, result)| result.get("failed").and_then(|f| f.as_bool()) == Some(true))
+                .map(|(file, _)| (file.trim_start_matches('/').to_string(), "vitest results".to_string())),
+        );
+    }
+
+    if failed.is_empty() {
+        return Vec::new();
+    }
+    let names: Vec<&str> = failed.iter().take(3).map(|(test, _)| test.as_str()).collect();
+    let more = failed.len().saturating_sub(names.len());
+    vec![suggestion(
+        SuggestionSource::FailingTests,
+        format!("{}: {} failing", failed[0].1, failed.len()),
+        format!(
+            "Fix the failing tests: {}{}",
+            names.join(", "),
+            if more > 0 { format!(" and {} more", more) } else { String::new() }
+        ),
+    )]
+}
+
+/// TODO/FIXME comments in source files, FIXMEs first
+fn todo_suggestions(project_path: &Path, limit: usize) -> Vec<PromptSuggestion> {
+    let pattern = Regex::new(r"(?://|#|/\*|--)\s*(TODO|FIXME)\b[:(\s]*(.*)").expect("valid TODO pattern");
+    let mut found: Vec<(bool, String, String)> = Vec::new();
+
+    let walker = walkdir::WalkDir::new(project_path)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|entry| {
+            !(entry.file_type().is_dir()
+                && entry
+                    .file_name()
+                    .to_str()
+                    .map(|name| IGNORED_DIRS.contains(&name))
+                    .unwrap_or(false))
+        });
+
+    for entry in walker
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map_or(false, |ext| SOURCE_EXTENSIONS.contains(&ext))
+        })
+        .take(MAX_SCANNED_FILES)
+    {
+        if entry.metadata().map_or(true, |m| m.len() > MAX_FILE_BYTES) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let relative = entry
+            .path()
+            .strip_prefix(project_path)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .replace('\\', "/");
+        for (index, line) in content.lines().enumerate() {
+            if let Some(captures) = pattern.captures(line) {
+                let text = captures[2].trim().trim_end_matches("*/").trim();
+                if text.is_empty() {
+                    continue;
+                }
+                found.push((&captures[1] == "FIXME", format!("{}:{}", relative, index + 1), text.to_string()));
+            }
+        }
+    }
+
+    // FIXMEs before TODOs, otherwise in scan order
+    found.sort_by_key(|(fixme, _, _)| !fixme);
+    found
+        .into_iter()
+        .take(limit)
+        .map(|(fixme, location, text)| {
+            let kind = if fixme { "FIXME" } else { "TODO" };
+            suggestion(
+                SuggestionSource::Todo,
+                location.clone(),
+                format!("Resolve the {} at {}: {}", kind, location, text),
+            )
+        })
+        .collect()
+}
+
+/// Unchecked `- [ ]` items in README/TODO/ROADMAP files
+fn readme_task_suggestions(project_path: &Path, limit: usize) -> Vec<PromptSuggestion> {
+    let mut suggestions = Vec::new();
+    for file in TASK_FILES {
+        let Ok(content) = fs::read_to_string(project_path.join(file)) else {
+            continue;
+        };
+        for (index, line) in content.lines().enumerate() {
+            let trimmed = line.trim_start();
+            let task = trimmed
+                .strip_prefix("- [ ]")
+                .or_else(|| trimmed.strip_prefix("* [ ]"))
+                .map(str::trim);
+            if let Some(task) = task.filter(|t| !t.is_empty()) {
+                suggestions.push(suggestion(
+                    SuggestionSource::ReadmeTask,
+                    format!("{}:{}", file, index + 1),
+                    format!("Implement the open task from {}: {}", file, task),
+                ));
+                if suggestions.len() >= limit {
+                    return suggestions;
+                }
+            }
+        }
+    }
+    suggestions
+}
+
+/// Suggest next prompts for a project from its state: failing tests, uncommitted
+/// changes, TODO/FIXME comments, open README tasks and the latest commit.
+///
+/// Suggestions are template-based; with `refine_model`, each one is rewritten by that
+/// model using its provenance as context (keeping the template text if that fails).
+#[tauri::command]
+pub async fn suggest_prompts(
+    app: AppHandle,
+    project_path: String,
+    limit: Option<usize>,
+    refine_model: Option<String>,
+) -> Result<Vec<PromptSuggestion>, String> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).max(1);
+    let root = Path::new(&project_path).to_path_buf();
+    if !root.is_dir() {
+        return Err(format!("Project directory not found: {}", project_path));
+    }
+
+    let mut suggestions = tokio::task::spawn_blocking(move || {
+        let git = git_suggestions(&root);
+        let (recent_commit, uncommitted): (Vec<_>, Vec<_>) = git
+            .into_iter()
+            .partition(|s| s.source == SuggestionSource::RecentCommit);
+
+        // Most actionable first
+        let mut suggestions = failing_test_suggestions(&root);
+        suggestions.extend(uncommitted);
+        suggestions.extend(todo_suggestions(&root, limit));
+        suggestions.extend(readme_task_suggestions(&root, limit));
+        suggestions.extend(recent_commit);
+        suggestions.truncate(limit);
+        suggestions
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if let Some(model) = refine_model {
+        for suggestion in &mut suggestions {
+            let context = vec![format!("Project: {}\nBased on: {}", project_path, suggestion.provenance)];
+            match super::claude::enhance_prompt(suggestion.prompt.clone(), model.clone(), Some(context), app.clone()).await {
+                Ok(refined) => {
+                    suggestion.prompt = refined;
+                    suggestion.refined = true;
+                }
+                Err(e) => log::warn!("Failed to refine prompt suggestion, keeping template: {}", e),
+            }
+        }
+    }
+
+    Ok(suggestions)
+}
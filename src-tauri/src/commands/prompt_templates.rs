@@ -0,0 +1,278 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+
+use super::agents::AgentDb;
+
+/// A reusable prompt with `{{name}}` placeholders, e.g. "Fix {{error}} in {{file}}"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub content: String,
+    /// Placeholder names in order of first use
+    pub variables: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A template to expand into the prompt of `execute_claude_code`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplateUse {
+    pub template_id: i64,
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Split `content` into literal text and placeholders. Only `{{name}}` (with optional
+/// spaces inside the braces) is a placeholder; other braces are kept as written, so
+/// templates can contain code.
+fn parts(content: &str) -> Vec<(&str, Option<&str>)> {
+    let mut parts = Vec::new();
+    let mut rest = content;
+    let mut literal_start = 0;
+    while let Some(open) = rest[literal_start..].find("{{").map(|i| literal_start + i) {
+        let placeholder = rest[open + 2..].find("}}").map(|close| {
+            let name = rest[open + 2..open + 2 + close].trim();
+            (name, open + 2 + close + 2)
+        });
+        match placeholder {
+            Some((name, end)) if is_name(name) => {
+                parts.push((&rest[..open], Some(name)));
+                rest = &rest[end..];
+                literal_start = 0;
+            }
+            _ => literal_start = open + 2,
+        }
+    }
+    parts.push((rest, None));
+    parts
+}
+
+/// Placeholder names of `content` in order of first use
+pub fn variables(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in parts(content).into_iter().filter_map(|(_, name)| name) {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Fill in every placeholder of `content` in one pass, so values containing braces are
+/// never expanded again. Fails naming all the variables without a value.
+pub fn expand(content: &str, values: &HashMap<String, String>) -> Result<String, String> {
+    let missing: Vec<String> = variables(content)
+        .into_iter()
+        .filter(|name| !values.contains_key(name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Missing values for {}", missing.join(", ")));
+    }
+    Ok(parts(content)
+        .into_iter()
+        .map(|(text, name)| format!("{}{}", text, name.map_or("", |name| &values[name])))
+        .collect())
+}
+
+fn validate(name: &str, content: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Prompt template name is empty".to_string());
+    }
+    if content.trim().is_empty() {
+        return Err("Prompt template content is empty".to_string());
+    }
+    Ok(())
+}
+
+fn row_to_template(row: &rusqlite::Row) -> rusqlite::Result<PromptTemplate> {
+    let content: String = row.get(3)?;
+    Ok(PromptTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        variables: variables(&content),
+        content,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+fn get_template(conn: &Connection, template_id: i64) -> Result<PromptTemplate, String> {
+    conn.query_row(
+        "SELECT id, name, description, content, created_at, updated_at
+         FROM prompt_templates WHERE id = ?1",
+        params![template_id],
+        row_to_template,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Prompt template {} not found", template_id))
+}
+
+fn name_taken(name: &str) -> impl Fn(rusqlite::Error) -> String + '_ {
+    move |e| match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            format!("A prompt template named '{}' already exists", name)
+        }
+        e => e.to_string(),
+    }
+}
+
+/// The prompt a template gives with `variables`
+pub fn expand_template(conn: &Connection, template: &PromptTemplateUse) -> Result<String, String> {
+    let found = get_template(conn, template.template_id)?;
+    expand(&found.content, &template.variables)
+        .map_err(|e| format!("Prompt template '{}': {}", found.name, e))
+}
+
+/// List prompt templates by name
+#[tauri::command]
+pub async fn list_prompt_templates(db: State<'_, AgentDb>) -> Result<Vec<PromptTemplate>, String> {
+    let conn = db.0.get()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, description, content, created_at, updated_at
+             FROM prompt_templates ORDER BY name",
+        )
+        .map_err(|e| e.to_string())?;
+    let templates = stmt
+        .query_map([], row_to_template)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(templates)
+}
+
+/// Save a new prompt template
+#[tauri::command]
+pub async fn create_prompt_template(
+    db: State<'_, AgentDb>,
+    name: String,
+    description: Option<String>,
+    content: String,
+) -> Result<PromptTemplate, String> {
+    validate(&name, &content)?;
+    let name = name.trim();
+    let conn = db.0.get()?;
+    conn.execute(
+        "INSERT INTO prompt_templates (name, description, content) VALUES (?1, ?2, ?3)",
+        params![name, description.filter(|d| !d.trim().is_empty()), content],
+    )
+    .map_err(name_taken(name))?;
+    get_template(&conn, conn.last_insert_rowid())
+}
+
+/// Replace a prompt template's name, description and content
+#[tauri::command]
+pub async fn update_prompt_template(
+    db: State<'_, AgentDb>,
+    template_id: i64,
+    name: String,
+    description: Option<String>,
+    content: String,
+) -> Result<PromptTemplate, String> {
+    validate(&name, &content)?;
+    let name = name.trim();
+    let conn = db.0.get()?;
+    let updated = conn
+        .execute(
+            "UPDATE prompt_templates
+             SET name = ?2, description = ?3, content = ?4, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?1",
+            params![
+                template_id,
+                name,
+                description.filter(|d| !d.trim().is_empty()),
+                content
+            ],
+        )
+        .map_err(name_taken(name))?;
+    if updated == 0 {
+        return Err(format!("Prompt template {} not found", template_id));
+    }
+    get_template(&conn, template_id)
+}
+
+/// Delete a prompt template
+#[tauri::command]
+pub async fn delete_prompt_template(
+    db: State<'_, AgentDb>,
+    template_id: i64,
+) -> Result<(), String> {
+    let conn = db.0.get()?;
+    conn.execute(
+        "DELETE FROM prompt_templates WHERE id = ?1",
+        params![template_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Fill in a template's variables, e.g. to preview the prompt before sending it
+#[tauri::command]
+pub async fn expand_prompt_template(
+    db: State<'_, AgentDb>,
+    template_id: i64,
+    variables: HashMap<String, String>,
+) -> Result<String, String> {
+    let conn = db.0.get()?;
+    expand_template(
+        &conn,
+        &PromptTemplateUse {
+            template_id,
+            variables,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_fills_placeholders_once() {
+        let content = "Fix {{ error }} in {{file}}.\nThe error again: {{error}}";
+        assert_eq!(variables(content), vec!["error", "file"]);
+        let expanded = expand(
+            content,
+            &values(&[("error", "E0308 {{file}}"), ("file", "a.rs")]),
+        );
+        assert_eq!(
+            expanded.unwrap(),
+            "Fix E0308 {{file}} in a.rs.\nThe error again: E0308 {{file}}"
+        );
+    }
+
+    #[test]
+    fn test_braces_that_are_not_placeholders_are_kept() {
+        let content = "Render {{ user.name }} with {{selection}} and {{}} or {{x";
+        assert_eq!(variables(content), vec!["selection"]);
+        assert_eq!(
+            expand(content, &values(&[("selection", "code")])).unwrap(),
+            "Render {{ user.name }} with code and {{}} or {{x"
+        );
+    }
+
+    #[test]
+    fn test_missing_variables_are_reported_together() {
+        let err = expand("{{file}}: {{error}}", &HashMap::new()).unwrap_err();
+        assert_eq!(err, "Missing values for file, error");
+    }
+}
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs;
 use std::path::PathBuf;
-use tauri::{command, AppHandle};
+use tauri::{command, AppHandle, Manager};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProviderConfig {
@@ -66,13 +66,13 @@ fn load_settings() -> Result<Value, String> {
         let content = serde_json::to_string_pretty(&default_settings)
             .map_err(|e| format!("序列化默认设置失败: {}", e))?;
             
-        fs::write(&settings_path, content)
+        crate::config_file::write(&settings_path, content)
             .map_err(|e| format!("创建默认设置文件失败: {}", e))?;
             
         return Ok(default_settings);
     }
     
-    let content = fs::read_to_string(&settings_path)
+    let content = crate::config_file::read_to_string(&settings_path)
         .map_err(|e| format!("读取设置文件失败: {}", e))?;
     
     let settings: Value = serde_json::from_str(&content)
@@ -88,7 +88,7 @@ fn save_settings(settings: &Value) -> Result<(), String> {
     let content = serde_json::to_string_pretty(settings)
         .map_err(|e| format!("序列化设置失败: {}", e))?;
     
-    fs::write(&settings_path, content)
+    crate::config_file::write(&settings_path, content)
         .map_err(|e| format!("写入设置文件失败: {}", e))?;
     
     Ok(())
@@ -102,7 +102,7 @@ fn load_legacy_providers() -> Result<Vec<ProviderConfig>, String> {
         return Ok(vec![]);
     }
     
-    let content = fs::read_to_string(&legacy_path)
+    let content = crate::config_file::read_to_string(&legacy_path)
         .map_err(|e| format!("读取遗留配置文件失败: {}", e))?;
     
     if content.trim().is_empty() {
@@ -138,7 +138,7 @@ pub fn add_provider_config(config: ProviderConfig) -> Result<String, String> {
     let content = serde_json::to_string_pretty(&providers)
         .map_err(|e| format!("序列化配置失败: {}", e))?;
     
-    fs::write(&legacy_path, content)
+    crate::config_file::write(&legacy_path, content)
         .map_err(|e| format!("写入配置文件失败: {}", e))?;
     
     Ok(format!("成功添加代理商配置: {}", config.name))
@@ -159,7 +159,7 @@ pub fn update_provider_config(config: ProviderConfig) -> Result<String, String>
     let content = serde_json::to_string_pretty(&providers)
         .map_err(|e| format!("序列化配置失败: {}", e))?;
     
-    fs::write(&legacy_path, content)
+    crate::config_file::write(&legacy_path, content)
         .map_err(|e| format!("写入配置文件失败: {}", e))?;
     
     Ok(format!("成功更新代理商配置: {}", config.name))
@@ -180,7 +180,7 @@ pub fn delete_provider_config(id: String) -> Result<String, String> {
     let content = serde_json::to_string_pretty(&providers)
         .map_err(|e| format!("序列化配置失败: {}", e))?;
     
-    fs::write(&legacy_path, content)
+    crate::config_file::write(&legacy_path, content)
         .map_err(|e| format!("写入配置文件失败: {}", e))?;
     
     Ok(format!("成功删除代理商配置: {}", deleted_config.name))
@@ -196,15 +196,26 @@ pub fn get_provider_config(id: String) -> Result<ProviderConfig, String> {
         .ok_or_else(|| format!("未找到ID为 '{}' 的配置", id))
 }
 
-// 获取当前代理商配置（从settings.json的env字段和apiKeyHelper字段读取）
+// 获取当前代理商配置（工作台的默认代理商；未选择时从settings.json的env字段读取）
 #[command]
-pub fn get_current_provider_config() -> Result<CurrentConfig, String> {
+pub fn get_current_provider_config(app: AppHandle) -> Result<CurrentConfig, String> {
     let settings = load_settings()?;
     
-    let empty_map = serde_json::Map::new();
-    let env_vars = settings.get("env")
-        .and_then(|v| v.as_object())
-        .unwrap_or(&empty_map);
+    let default_provider = {
+        let db = app.state::<super::agents::AgentDb>();
+        let conn = db.0.get()?;
+        super::provider_env::default_provider(&conn)
+    };
+    let env_vars: serde_json::Map<String, Value> = match default_provider {
+        Some(provider) => provider_env_vars(&provider)
+            .into_iter()
+            .map(|(key, value)| (key, Value::String(value)))
+            .collect(),
+        None => settings.get("env")
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default(),
+    };
     
     // apiKeyHelper 是与 env 同级的独立字段
     let api_key_helper = settings.get("apiKeyHelper")
@@ -238,14 +249,54 @@ pub fn get_current_provider_config() -> Result<CurrentConfig, String> {
     })
 }
 
-// 切换代理商配置（写入settings.json的env字段）
+// 根据代理商配置计算需要设置的环境变量（不修改任何共享状态）
+pub fn provider_env_vars(config: &ProviderConfig) -> Vec<(String, String)> {
+    let mut vars = vec![("ANTHROPIC_BASE_URL".to_string(), config.base_url.clone())];
+
+    if let Some(token) = config.auth_token.as_ref().filter(|t| !t.is_empty()) {
+        vars.push(("ANTHROPIC_AUTH_TOKEN".to_string(), token.clone()));
+    }
+    if let Some(api_key) = config.api_key.as_ref().filter(|k| !k.is_empty()) {
+        vars.push(("ANTHROPIC_API_KEY".to_string(), api_key.clone()));
+    }
+
+    let model = config.model.as_ref().filter(|m| !m.is_empty());
+    if let Some(model) = model {
+        vars.push(("ANTHROPIC_MODEL".to_string(), model.clone()));
+    }
+
+    // 添加Claude Code 2025的标准环境变量
+    // 为第三方API优化超时设置
+    if config.base_url != "https://api.anthropic.com" {
+        vars.push(("API_TIMEOUT_MS".to_string(), "600000".to_string()));
+        vars.push(("CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC".to_string(), "1".to_string()));
+    }
+
+    // 设置小型快速模型（用于代码完成等任务）
+    // 对于第三方API，通常使用同一个模型作为fast model
+    if let Some(model) = model {
+        vars.push(("ANTHROPIC_SMALL_FAST_MODEL".to_string(), model.clone()));
+    }
+
+    vars
+}
+
+// 切换默认代理商配置
+//
+// 配置保存为工作台的默认代理商，由 `resolve_execution_env` 设置到每个启动的进程上。
+// 不再写入 settings.json 的 env 字段：CLI 优先使用那里的变量，会覆盖会话绑定的代理商。
 #[command]
-pub async fn switch_provider_config(_app: AppHandle, config: ProviderConfig) -> Result<String, String> {
+pub async fn switch_provider_config(app: AppHandle, config: ProviderConfig) -> Result<String, String> {
     log::info!("开始切换代理商配置: {} - {}", config.name, config.description);
 
     // 验证第三方API配置
     validate_third_party_config(&config)?;
 
+    // 指向 claude-code-router 时，先确认默认路由的提供商可用
+    if super::router_usage::is_router_base_url(&config.base_url) {
+        super::router_validation::ensure_routing_allowed().await?;
+    }
+
     let mut settings = load_settings()?;
     
     // 确保env字段存在
@@ -261,7 +312,7 @@ pub async fn switch_provider_config(_app: AppHandle, config: ProviderConfig) ->
     let env_obj = settings_obj.get_mut("env").unwrap().as_object_mut()
         .ok_or("env字段格式错误")?;
     
-    // 清理之前的ANTHROPIC环境变量
+    // 清理之前写入的ANTHROPIC环境变量，它们会覆盖每个进程的环境
     env_obj.remove("ANTHROPIC_API_KEY");
     env_obj.remove("ANTHROPIC_AUTH_TOKEN");
     env_obj.remove("ANTHROPIC_BASE_URL");
@@ -272,48 +323,14 @@ pub async fn switch_provider_config(_app: AppHandle, config: ProviderConfig) ->
     env_obj.remove("API_TIMEOUT_MS");
     env_obj.remove("CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC");
     
-    // 设置新的环境变量
-    env_obj.insert("ANTHROPIC_BASE_URL".to_string(), serde_json::Value::String(config.base_url.clone()));
-    
-    // 确定要使用的认证令牌值
-    let auth_token = if let Some(token) = &config.auth_token {
-        if !token.is_empty() {
-            env_obj.insert("ANTHROPIC_AUTH_TOKEN".to_string(), serde_json::Value::String(token.clone()));
-            Some(token.clone())
-        } else {
-            None
-        }
-    } else {
-        None
-    };
-    
-    if let Some(api_key) = &config.api_key {
-        if !api_key.is_empty() {
-            env_obj.insert("ANTHROPIC_API_KEY".to_string(), serde_json::Value::String(api_key.clone()));
-        }
-    }
-    
-    if let Some(model) = &config.model {
-        if !model.is_empty() {
-            env_obj.insert("ANTHROPIC_MODEL".to_string(), serde_json::Value::String(model.clone()));
-        }
-    }
-
-    // 添加Claude Code 2025的��准环境变量
-    // 为第三方API优化超时设置
+    // 保存为默认代理商，之后启动的进程使用它的环境变量
+    super::settings_store::set(&app, super::settings_store::DEFAULT_PROVIDER, &Some(&config))?;
     if config.base_url != "https://api.anthropic.com" {
-        env_obj.insert("API_TIMEOUT_MS".to_string(), serde_json::Value::String("600000".to_string()));
-        env_obj.insert("CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC".to_string(), serde_json::Value::String("1".to_string()));
         log::info!("设置第三方API优化参数: timeout=600s, disable_nonessential_traffic=true");
     }
 
-    // 设置小型快速模型（用于代码完成等任务）
-    if let Some(model) = &config.model {
-        if !model.is_empty() {
-            // 对于第三方API，通常使用同一个模型作为fast model
-            env_obj.insert("ANTHROPIC_SMALL_FAST_MODEL".to_string(), serde_json::Value::String(model.clone()));
-        }
-    }
+    // 确定要使用的认证令牌值
+    let auth_token = config.auth_token.clone().filter(|token| !token.is_empty());
 
     // apiKeyHelper 根据用户勾选状态决定是否自动生成
     if config.enable_auto_api_key_helper.unwrap_or(false) {
@@ -337,7 +354,7 @@ pub async fn switch_provider_config(_app: AppHandle, config: ProviderConfig) ->
     log::info!("代理商配置切换完成: {}", config.name);
     
     Ok(format!(
-        "✅ 已成功切换到 {} ({})\n\n配置已写入 ~/.claude/settings.json，即时生效！", 
+        "✅ 已成功切换到 {} ({})\n\n之后启动的会话和代理将使用此配置！", 
         config.name, 
         config.description
     ))
@@ -372,11 +389,13 @@ fn validate_third_party_config(config: &ProviderConfig) -> Result<(), String> {
     Ok(())
 }
 
-// 清理代理商配置（清理settings.json的env字段中的ANTHROPIC变量和apiKeyHelper字段）
+// 清理代理商配置（清除默认代理商，以及settings.json的env字段中的ANTHROPIC变量和apiKeyHelper字段）
 #[command]
-pub async fn clear_provider_config(_app: AppHandle) -> Result<String, String> {
+pub async fn clear_provider_config(app: AppHandle) -> Result<String, String> {
     log::info!("开始清理代理商配置");
     
+    super::settings_store::set(&app, super::settings_store::DEFAULT_PROVIDER, &None::<ProviderConfig>)?;
+    
     let mut settings = load_settings()?;
     
     // 如果有env字段，清理ANTHROPIC相关变量
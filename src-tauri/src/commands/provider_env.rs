@@ -0,0 +1,310 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Manager, State};
+use tokio::process::Command;
+
+use super::agents::AgentDb;
+use super::provider::{get_provider_config, provider_env_vars, ProviderConfig};
+use super::provider_keys::{key_variable, select_key};
+use super::settings_store::{self, DEFAULT_PROVIDER};
+
+/// Parts of the names of variables holding credentials (`ANTHROPIC_AUTH_TOKEN`,
+/// `ANTHROPIC_API_KEY`, ...); only a masked form is recorded in logs and run metadata
+const SECRET_NAME_PARTS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD"];
+
+fn is_secret(key: &str) -> bool {
+    let upper = key.to_ascii_uppercase();
+    SECRET_NAME_PARTS.iter().any(|part| upper.contains(part))
+}
+
+/// Environment computed for a single Claude process.
+///
+/// Built fresh for every spawn and applied to that process only, so concurrent
+/// sessions bound to different providers never see each other's settings.
+#[derive(Debug, Clone, Default)]
+pub struct ProviderEnv {
+    /// Provider the environment was resolved from; None uses ~/.claude/settings.json
+    pub provider_id: Option<String>,
+    /// Alias of the rotated key the environment carries, if the provider has any
+    pub key_alias: Option<String>,
+    vars: BTreeMap<String, String>,
+    /// Variables from an environment profile or the session's overrides; stored with
+    /// the session once it has an ID, so later turns get them again
+    overrides: BTreeMap<String, String>,
+}
+
+impl ProviderEnv {
+    /// Set the resolved variables on a command
+    pub fn apply(&self, cmd: &mut Command) {
+        cmd.envs(self.process_vars());
+        super::fault_injection::apply_provider_faults(cmd);
+    }
+
+    /// The variables to set on the process, for commands `apply` cannot take
+    pub fn process_vars(&self) -> BTreeMap<String, String> {
+        let mut vars = self.vars.clone();
+        // Follow the router when it was moved off a port another app took
+        if let Some(url) = self.base_url().and_then(super::service_ports::redirect_router_url) {
+            vars.insert("ANTHROPIC_BASE_URL".to_string(), url);
+        }
+        vars
+    }
+
+    /// Layer the variables of an environment profile or session over the resolved ones
+    pub fn extend(&mut self, vars: BTreeMap<String, String>) {
+        self.vars.extend(vars.clone());
+        self.overrides.extend(vars);
+    }
+
+    /// The variables layered over the provider's with `extend`
+    pub fn overrides(&self) -> &BTreeMap<String, String> {
+        &self.overrides
+    }
+
+    /// `ANTHROPIC_BASE_URL` the process is sent to, if the environment sets one
+    pub fn base_url(&self) -> Option<&str> {
+        self.vars.get("ANTHROPIC_BASE_URL").map(String::as_str)
+    }
+
+    /// The variables with credentials masked, for logs and run metadata
+    pub fn redacted(&self) -> BTreeMap<String, String> {
+        self.vars
+            .iter()
+            .map(|(key, value)| {
+                let value = if is_secret(key) {
+                    mask_secret(value)
+                } else {
+                    value.clone()
+                };
+                (key.clone(), value)
+            })
+            .collect()
+    }
+}
+
+pub fn mask_secret(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 8 {
+        return "****".to_string();
+    }
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("****{}", tail)
+}
+
+/// Compute the environment for one execution.
+///
+/// The provider's variables are applied first; `model` (the resolved CLI model of
+/// the request) then overrides `ANTHROPIC_MODEL` so the user's selection wins.
+pub fn resolve_provider_env(
+    provider: Option<&super::provider::ProviderConfig>,
+    model: Option<&str>,
+) -> ProviderEnv {
+    let mut vars = BTreeMap::new();
+    if let Some(provider) = provider {
+        vars.extend(provider_env_vars(provider));
+    }
+    if let Some(model) = model {
+        vars.insert("ANTHROPIC_MODEL".to_string(), model.to_string());
+    }
+
+    ProviderEnv {
+        provider_id: provider.map(|p| p.id.clone()),
+        key_alias: None,
+        vars,
+        overrides: BTreeMap::new(),
+    }
+}
+
+/// Provider bound to a session, if any
+pub fn get_bound_provider(conn: &Connection, session_id: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT provider_id FROM session_provider_bindings WHERE session_id = ?1",
+        params![session_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Bind a session to a provider so later turns resolve the same environment
+pub fn bind_provider(conn: &Connection, session_id: &str, provider_id: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO session_provider_bindings (session_id, provider_id, bound_at)
+         VALUES (?1, ?2, CURRENT_TIMESTAMP)
+         ON CONFLICT(session_id) DO UPDATE SET
+             provider_id = excluded.provider_id,
+             bound_at = excluded.bound_at",
+        params![session_id, provider_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Variables set on every turn of a session; empty when it has none
+pub fn get_session_env_vars(
+    conn: &Connection,
+    session_id: &str,
+) -> Result<BTreeMap<String, String>, String> {
+    let vars: Option<String> = conn
+        .query_row(
+            "SELECT vars FROM session_env_overrides WHERE session_id = ?1",
+            params![session_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match vars {
+        Some(vars) => serde_json::from_str(&vars)
+            .map_err(|e| format!("Invalid environment overrides of {}: {}", session_id, e)),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// Store the variables set on every turn of a session; empty `vars` remove them
+pub fn set_session_env_vars(
+    conn: &Connection,
+    session_id: &str,
+    vars: &BTreeMap<String, String>,
+) -> Result<(), String> {
+    if vars.is_empty() {
+        conn.execute(
+            "DELETE FROM session_env_overrides WHERE session_id = ?1",
+            params![session_id],
+        )
+        .map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+    let json = serde_json::to_string(vars).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO session_env_overrides (session_id, vars, updated_at)
+         VALUES (?1, ?2, CURRENT_TIMESTAMP)
+         ON CONFLICT(session_id) DO UPDATE SET
+             vars = excluded.vars,
+             updated_at = excluded.updated_at",
+        params![session_id, json],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The provider selected with `switch_provider_config`, if any
+pub fn default_provider(conn: &Connection) -> Option<ProviderConfig> {
+    settings_store::get_or_default(conn, DEFAULT_PROVIDER)
+}
+
+/// Resolve the environment for a spawn.
+///
+/// An explicit `provider_id` wins, then the provider bound to `session_id`, then
+/// the default provider; with none, only the model is set and the CLI uses
+/// ~/.claude/settings.json. The session's environment overrides are applied last.
+///
+/// The variables only reach the process when ~/.claude/settings.json does not set
+/// them in its `env`, which the CLI prefers over the process environment, so
+/// provider switches are no longer written there.
+pub fn resolve_execution_env(
+    app: &AppHandle,
+    session_id: Option<&str>,
+    provider_id: Option<&str>,
+    model: &str,
+) -> Result<ProviderEnv, String> {
+    let db = app.state::<AgentDb>();
+    let provider_id = match (provider_id, session_id) {
+        (Some(id), _) => Some(id.to_string()),
+        (None, Some(session_id)) => get_bound_provider(&*db.0.get()?, session_id)?,
+        (None, None) => None,
+    };
+
+    let provider = match provider_id.map(get_provider_config).transpose()? {
+        Some(provider) => Some(provider),
+        None => default_provider(&*db.0.get()?),
+    };
+    let mut env = resolve_provider_env(provider.as_ref(), Some(model));
+    if let Some(provider) = &provider {
+        // A provider with rotated keys uses one of them in place of the preset's key
+        match select_key(app, &provider.id) {
+            Ok(Some(key)) => {
+                env.vars.insert(key_variable(provider).to_string(), key.value);
+                env.key_alias = Some(key.alias);
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to rotate key of provider {}: {}", provider.id, e),
+        }
+    }
+    if let Some(session_id) = session_id {
+        env.extend(get_session_env_vars(&*db.0.get()?, session_id)?);
+    }
+    log::info!(
+        "Resolved execution environment (provider: {:?}, key: {:?}, overrides: {:?}): {:?}",
+        env.provider_id,
+        env.key_alias,
+        env.overrides.keys().collect::<Vec<_>>(),
+        env.redacted()
+    );
+    Ok(env)
+}
+
+/// Bind a session to a provider preset, or remove the binding with `provider_id: None`
+#[tauri::command]
+pub async fn bind_session_provider(
+    db: State<'_, AgentDb>,
+    session_id: String,
+    provider_id: Option<String>,
+) -> Result<(), String> {
+    let conn = db.0.get()?;
+    match provider_id {
+        Some(provider_id) => {
+            // Fail early for unknown presets rather than at the next spawn
+            get_provider_config(provider_id.clone())?;
+            bind_provider(&conn, &session_id, &provider_id)
+        }
+        None => {
+            conn.execute(
+                "DELETE FROM session_provider_bindings WHERE session_id = ?1",
+                params![session_id],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        }
+    }
+}
+
+/// Get the provider preset bound to a session
+#[tauri::command]
+pub async fn get_session_provider(
+    db: State<'_, AgentDb>,
+    session_id: String,
+) -> Result<Option<String>, String> {
+    let conn = db.0.get()?;
+    get_bound_provider(&conn, &session_id)
+}
+
+/// Set environment variables on every later turn of a session (e.g. `API_TIMEOUT_MS` or
+/// `ANTHROPIC_BASE_URL`), on top of its provider's; empty `vars` remove them.
+///
+/// A new session gets overrides at launch from an environment profile, which are
+/// stored with it the same way.
+#[tauri::command]
+pub async fn set_session_env(
+    db: State<'_, AgentDb>,
+    session_id: String,
+    vars: BTreeMap<String, String>,
+) -> Result<(), String> {
+    for key in vars.keys() {
+        super::env_profiles::check_var_name(key)?;
+    }
+    let conn = db.0.get()?;
+    set_session_env_vars(&conn, &session_id, &vars)?;
+    let redacted = ProviderEnv { vars, ..ProviderEnv::default() }.redacted();
+    log::info!("Set environment overrides of session {}: {:?}", session_id, redacted);
+    Ok(())
+}
+
+/// Get the environment overrides of a session
+#[tauri::command]
+pub async fn get_session_env(
+    db: State<'_, AgentDb>,
+    session_id: String,
+) -> Result<BTreeMap<String, String>, String> {
+    let conn = db.0.get()?;
+    get_session_env_vars(&conn, &session_id)
+}
@@ -0,0 +1,420 @@
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use super::agents::AgentDb;
+use super::provider::{get_provider_config, ProviderConfig};
+use super::provider_env::mask_secret;
+use super::secrets::{get_secret, set_secret};
+
+/// How long a key that hit a rate limit is passed over
+const RATE_LIMIT_COOLDOWN_SECS: i64 = 60;
+
+/// Largest weight a key can have under `Weighted`
+const MAX_KEY_WEIGHT: u32 = 100;
+
+/// How a provider's keys take turns serving executions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationStrategy {
+    /// Each execution uses the next key
+    #[default]
+    RoundRobin,
+    /// Use the first key until it is rate limited, then the next one
+    FailoverOn429,
+    /// Spread executions over the keys in proportion to their weights
+    Weighted,
+}
+
+impl RotationStrategy {
+    fn as_str(self) -> &'static str {
+        match self {
+            RotationStrategy::RoundRobin => "round_robin",
+            RotationStrategy::FailoverOn429 => "failover_on_429",
+            RotationStrategy::Weighted => "weighted",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "failover_on_429" => RotationStrategy::FailoverOn429,
+            "weighted" => RotationStrategy::Weighted,
+            _ => RotationStrategy::RoundRobin,
+        }
+    }
+}
+
+/// One key of a provider; the value itself stays in the secrets store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderKeyInfo {
+    pub alias: String,
+    pub weight: u32,
+    /// Last characters of the key, to tell keys apart
+    pub masked: String,
+    pub last_used_at: Option<String>,
+    pub rate_limit_count: u32,
+    /// The key is passed over until then after hitting a rate limit
+    pub cooldown_until: Option<String>,
+}
+
+/// Returned by the provider key commands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderKeyRotation {
+    pub provider_id: String,
+    pub strategy: RotationStrategy,
+    /// In the order they were added; with no keys the preset's own key is used
+    pub keys: Vec<ProviderKeyInfo>,
+}
+
+/// A key picked for one execution
+pub struct SelectedKey {
+    pub alias: String,
+    pub value: String,
+}
+
+struct KeyRow {
+    alias: String,
+    weight: u32,
+    last_used_at: Option<String>,
+    rate_limit_count: u32,
+    cooldown_until: Option<String>,
+}
+
+/// Next round-robin position per provider
+static ROUND_ROBIN: Lazy<Mutex<HashMap<String, usize>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Smooth weighted round-robin state per provider: the current weight of each key
+static WEIGHTED: Lazy<Mutex<HashMap<String, HashMap<String, i64>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Create `provider_keys` and `provider_key_rotation`
+pub fn init_provider_key_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS provider_keys (
+            provider_id TEXT NOT NULL,
+            alias TEXT NOT NULL,
+            weight INTEGER NOT NULL DEFAULT 1,
+            last_used_at TEXT,
+            rate_limit_count INTEGER NOT NULL DEFAULT 0,
+            cooldown_until TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (provider_id, alias)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS provider_key_rotation (
+            provider_id TEXT PRIMARY KEY,
+            strategy TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn secret_name(provider_id: &str, alias: &str) -> String {
+    format!("provider_key:{}:{}", provider_id, alias)
+}
+
+fn load_keys(conn: &Connection, provider_id: &str) -> Result<Vec<KeyRow>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT alias, weight, last_used_at, rate_limit_count, cooldown_until
+             FROM provider_keys WHERE provider_id = ?1 ORDER BY rowid",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![provider_id], |row| {
+            Ok(KeyRow {
+                alias: row.get(0)?,
+                weight: row.get(1)?,
+                last_used_at: row.get(2)?,
+                rate_limit_count: row.get(3)?,
+                cooldown_until: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+fn load_strategy(conn: &Connection, provider_id: &str) -> Result<RotationStrategy, String> {
+    let strategy: Option<String> = conn
+        .query_row(
+            "SELECT strategy FROM provider_key_rotation WHERE provider_id = ?1",
+            params![provider_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(strategy.map(|s| RotationStrategy::parse(&s)).unwrap_or_default())
+}
+
+fn cooling_down(key: &KeyRow, now: DateTime<Utc>) -> bool {
+    key.cooldown_until
+        .as_deref()
+        .and_then(|until| DateTime::parse_from_rfc3339(until).ok())
+        .map_or(false, |until| until > now)
+}
+
+/// Pick among `keys` (none cooling down, unless all are) by the provider's strategy
+fn pick<'a>(provider_id: &str, strategy: RotationStrategy, keys: &[&'a KeyRow]) -> &'a KeyRow {
+    match strategy {
+        RotationStrategy::FailoverOn429 => keys[0],
+        RotationStrategy::RoundRobin => {
+            let mut positions = ROUND_ROBIN.lock().unwrap_or_else(|e| e.into_inner());
+            let position = positions.entry(provider_id.to_string()).or_insert(0);
+            let key = keys[*position % keys.len()];
+            *position = position.wrapping_add(1);
+            key
+        }
+        RotationStrategy::Weighted => {
+            let mut states = WEIGHTED.lock().unwrap_or_else(|e| e.into_inner());
+            let current = states.entry(provider_id.to_string()).or_default();
+            current.retain(|alias, _| keys.iter().any(|key| &key.alias == alias));
+            let total: i64 = keys.iter().map(|key| key.weight as i64).sum();
+            let mut best = keys[0];
+            let mut best_weight = i64::MIN;
+            for &key in keys {
+                let weight = current.entry(key.alias.clone()).or_insert(0);
+                *weight += key.weight as i64;
+                if *weight > best_weight {
+                    best_weight = *weight;
+                    best = key;
+                }
+            }
+            if let Some(weight) = current.get_mut(&best.alias) {
+                *weight -= total;
+            }
+            best
+        }
+    }
+}
+
+/// Pick the key a provider's next execution uses, or None when the provider has no
+/// rotated keys and its preset key applies
+pub fn select_key(app: &AppHandle, provider_id: &str) -> Result<Option<SelectedKey>, String> {
+    let db = app.state::<AgentDb>();
+    let conn = db.0.get()?;
+    let keys = load_keys(&conn, provider_id)?;
+    if keys.is_empty() {
+        return Ok(None);
+    }
+    let strategy = load_strategy(&conn, provider_id)?;
+
+    let now = Utc::now();
+    let mut available: Vec<&KeyRow> = keys.iter().filter(|key| !cooling_down(key, now)).collect();
+    if available.is_empty() {
+        log::warn!("All keys of provider {} are rate limited, rotating anyway", provider_id);
+        available = keys.iter().collect();
+    }
+    let key = pick(provider_id, strategy, &available);
+
+    let value = get_secret(app, &secret_name(provider_id, &key.alias))?
+        .ok_or_else(|| format!("Key '{}' of provider {} is missing", key.alias, provider_id))?;
+    conn.execute(
+        "UPDATE provider_keys SET last_used_at = ?1 WHERE provider_id = ?2 AND alias = ?3",
+        params![now.to_rfc3339(), provider_id, key.alias],
+    )
+    .map_err(|e| e.to_string())?;
+    log::info!("Provider {} uses key '{}' ({})", provider_id, key.alias, strategy.as_str());
+    Ok(Some(SelectedKey {
+        alias: key.alias.clone(),
+        value,
+    }))
+}
+
+/// Variable a rotated key replaces: the one the preset sets its own key in
+pub fn key_variable(provider: &ProviderConfig) -> &'static str {
+    let has_token = provider.auth_token.as_ref().map_or(false, |t| !t.is_empty());
+    let has_api_key = provider.api_key.as_ref().map_or(false, |k| !k.is_empty());
+    if has_api_key && !has_token {
+        "ANTHROPIC_API_KEY"
+    } else {
+        "ANTHROPIC_AUTH_TOKEN"
+    }
+}
+
+fn is_rate_limited(text: &str) -> bool {
+    text.contains("rate_limit_error")
+        || text.contains("API Error: 429")
+        || text.contains("status 429")
+}
+
+/// Put a key aside for a while when an execution using it reports a rate limit.
+/// Called with stderr lines and error results of runs that use a rotated key.
+pub fn report_output(app: &AppHandle, provider_id: &str, alias: &str, text: &str) {
+    if !is_rate_limited(text) {
+        return;
+    }
+    let until = Utc::now() + Duration::seconds(RATE_LIMIT_COOLDOWN_SECS);
+    let db = app.state::<AgentDb>();
+    let updated = db.0.get().and_then(|conn| {
+        conn.execute(
+            "UPDATE provider_keys
+             SET rate_limit_count = rate_limit_count + 1, cooldown_until = ?1
+             WHERE provider_id = ?2 AND alias = ?3",
+            params![until.to_rfc3339(), provider_id, alias],
+        )
+        .map_err(|e| e.to_string())
+    });
+    match updated {
+        Ok(_) => log::warn!(
+            "Key '{}' of provider {} was rate limited, passing it over for {}s",
+            alias,
+            provider_id,
+            RATE_LIMIT_COOLDOWN_SECS
+        ),
+        Err(e) => log::warn!("Failed to record rate limit of key '{}': {}", alias, e),
+    }
+}
+
+/// Same as `report_output` for a stream-json message; only error results and API
+/// error messages are looked at, so tool output mentioning 429 does not count
+pub fn report_message(app: &AppHandle, provider_id: &str, alias: &str, msg: &serde_json::Value) {
+    let text = match msg["type"].as_str() {
+        Some("result") if msg["is_error"] == true => msg["result"].as_str(),
+        Some("assistant") => msg["message"]["content"][0]["text"]
+            .as_str()
+            .filter(|text| text.starts_with("API Error")),
+        _ => None,
+    };
+    if let Some(text) = text {
+        report_output(app, provider_id, alias, text);
+    }
+}
+
+fn rotation_state(
+    app: &AppHandle,
+    conn: &Connection,
+    provider_id: &str,
+) -> Result<ProviderKeyRotation, String> {
+    let keys = load_keys(conn, provider_id)?
+        .into_iter()
+        .map(|key| {
+            let masked = get_secret(app, &secret_name(provider_id, &key.alias))
+                .ok()
+                .flatten()
+                .map(|value| mask_secret(&value))
+                .unwrap_or_else(|| "missing".to_string());
+            ProviderKeyInfo {
+                alias: key.alias,
+                weight: key.weight,
+                masked,
+                last_used_at: key.last_used_at,
+                rate_limit_count: key.rate_limit_count,
+                cooldown_until: key.cooldown_until,
+            }
+        })
+        .collect();
+    Ok(ProviderKeyRotation {
+        provider_id: provider_id.to_string(),
+        strategy: load_strategy(conn, provider_id)?,
+        keys,
+    })
+}
+
+fn validate_alias(alias: &str) -> Result<(), String> {
+    let valid_chars = alias
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if alias.is_empty() || alias.len() > 64 || !valid_chars {
+        return Err(format!(
+            "Invalid key alias '{}': use up to 64 letters, digits, '-', '_' or '.'",
+            alias
+        ));
+    }
+    Ok(())
+}
+
+/// A provider's rotated keys and strategy
+#[tauri::command]
+pub async fn get_provider_keys(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    provider_id: String,
+) -> Result<ProviderKeyRotation, String> {
+    let conn = db.0.get()?;
+    rotation_state(&app, &conn, &provider_id)
+}
+
+/// Add a key to a provider's rotation, or replace the key stored under `alias`
+#[tauri::command]
+pub async fn add_provider_key(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    provider_id: String,
+    alias: String,
+    key: String,
+    weight: Option<u32>,
+) -> Result<ProviderKeyRotation, String> {
+    validate_alias(&alias)?;
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("Key must not be empty".to_string());
+    }
+    let weight = weight.unwrap_or(1).clamp(1, MAX_KEY_WEIGHT);
+    // Fail early for unknown presets rather than at the next spawn
+    get_provider_config(provider_id.clone())?;
+
+    set_secret(&app, &secret_name(&provider_id, &alias), Some(key))?;
+    let conn = db.0.get()?;
+    conn.execute(
+        "INSERT INTO provider_keys (provider_id, alias, weight) VALUES (?1, ?2, ?3)
+         ON CONFLICT(provider_id, alias) DO UPDATE SET
+             weight = excluded.weight,
+             rate_limit_count = 0,
+             cooldown_until = NULL",
+        params![provider_id, alias, weight],
+    )
+    .map_err(|e| e.to_string())?;
+    log::info!("Added key '{}' to provider {}", alias, provider_id);
+    rotation_state(&app, &conn, &provider_id)
+}
+
+/// Remove a key from a provider's rotation and delete the stored value
+#[tauri::command]
+pub async fn remove_provider_key(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    provider_id: String,
+    alias: String,
+) -> Result<ProviderKeyRotation, String> {
+    let conn = db.0.get()?;
+    let removed = conn
+        .execute(
+            "DELETE FROM provider_keys WHERE provider_id = ?1 AND alias = ?2",
+            params![provider_id, alias],
+        )
+        .map_err(|e| e.to_string())?;
+    if removed == 0 {
+        return Err(format!("Provider {} has no key '{}'", provider_id, alias));
+    }
+    set_secret(&app, &secret_name(&provider_id, &alias), None)?;
+    log::info!("Removed key '{}' from provider {}", alias, provider_id);
+    rotation_state(&app, &conn, &provider_id)
+}
+
+/// Choose how a provider's keys take turns
+#[tauri::command]
+pub async fn set_rotation_strategy(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    provider_id: String,
+    strategy: RotationStrategy,
+) -> Result<ProviderKeyRotation, String> {
+    let conn = db.0.get()?;
+    conn.execute(
+        "INSERT INTO provider_key_rotation (provider_id, strategy) VALUES (?1, ?2)
+         ON CONFLICT(provider_id) DO UPDATE SET strategy = excluded.strategy",
+        params![provider_id, strategy.as_str()],
+    )
+    .map_err(|e| e.to_string())?;
+    log::info!("Provider {} rotates keys by {}", provider_id, strategy.as_str());
+    rotation_state(&app, &conn, &provider_id)
+}
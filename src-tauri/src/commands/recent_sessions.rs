@@ -0,0 +1,151 @@
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use tauri::{State, Window};
+
+use super::agents::AgentDb;
+
+/// Maximum number of entries kept per window; older entries are pruned on insert
+const MAX_RECENT_SESSIONS_PER_WINDOW: i64 = 50;
+
+/// Represents a session that was recently closed or visited in a window
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecentSession {
+    pub id: i64,
+    /// Label of the window the session was shown in
+    pub window_label: String,
+    pub session_id: String,
+    pub project_id: String,
+    pub project_path: String,
+    /// Scroll offset of the message list when the session was left
+    pub scroll_position: Option<f64>,
+    /// 'closed' or 'visited'
+    pub event: String,
+    pub created_at: String,
+}
+
+fn row_to_recent_session(row: &rusqlite::Row) -> SqliteResult<RecentSession> {
+    Ok(RecentSession {
+        id: row.get(0)?,
+        window_label: row.get(1)?,
+        session_id: row.get(2)?,
+        project_id: row.get(3)?,
+        project_path: row.get(4)?,
+        scroll_position: row.get(5)?,
+        event: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+/// Drops the oldest entries of a window beyond the retention limit
+fn prune_recent_sessions(conn: &Connection, window_label: &str) -> SqliteResult<usize> {
+    conn.execute(
+        "DELETE FROM recent_sessions WHERE window_label = ?1 AND id NOT IN (
+            SELECT id FROM recent_sessions WHERE window_label = ?1 ORDER BY id DESC LIMIT ?2
+        )",
+        params![window_label, MAX_RECENT_SESSIONS_PER_WINDOW],
+    )
+}
+
+/// Record that a session was closed (or visited) in the calling window
+#[tauri::command]
+pub async fn record_recent_session(
+    window: Window,
+    db: State<'_, AgentDb>,
+    session_id: String,
+    project_id: String,
+    project_path: String,
+    scroll_position: Option<f64>,
+    event: Option<String>,
+) -> Result<(), String> {
+    let event = event.unwrap_or_else(|| "closed".to_string());
+    if event != "closed" && event != "visited" {
+        return Err(format!("Invalid recent session event: {}", event));
+    }
+
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let window_label = window.label().to_string();
+
+    // A session only appears once per window - the newest entry wins
+    conn.execute(
+        "DELETE FROM recent_sessions WHERE window_label = ?1 AND session_id = ?2",
+        params![window_label, session_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO recent_sessions (window_label, session_id, project_id, project_path, scroll_position, event)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![window_label, session_id, project_id, project_path, scroll_position, event],
+    )
+    .map_err(|e| e.to_string())?;
+
+    prune_recent_sessions(&conn, &window_label).map_err(|e| e.to_string())?;
+
+    log::debug!(
+        "Recorded recent session {} ({}) for window {}",
+        session_id,
+        event,
+        window_label
+    );
+    Ok(())
+}
+
+/// List the recently closed/visited sessions of the calling window, newest first
+#[tauri::command]
+pub async fn get_recent_sessions(
+    window: Window,
+    db: State<'_, AgentDb>,
+    limit: Option<i64>,
+) -> Result<Vec<RecentSession>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let limit = limit.unwrap_or(10).clamp(1, MAX_RECENT_SESSIONS_PER_WINDOW);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, window_label, session_id, project_id, project_path, scroll_position, event, created_at
+             FROM recent_sessions WHERE window_label = ?1 ORDER BY id DESC LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let sessions = stmt
+        .query_map(params![window.label(), limit], row_to_recent_session)
+        .map_err(|e| e.to_string())?
+        .collect::<SqliteResult<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(sessions)
+}
+
+/// Pop the most recently closed session of the calling window (Ctrl+Shift+T)
+///
+/// The entry is removed from the stack so repeated calls walk back through
+/// the closed sessions. Returns `None` when nothing is left to reopen.
+#[tauri::command]
+pub async fn reopen_last_session(
+    window: Window,
+    db: State<'_, AgentDb>,
+) -> Result<Option<RecentSession>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+
+    let last = conn
+        .query_row(
+            "SELECT id, window_label, session_id, project_id, project_path, scroll_position, event, created_at
+             FROM recent_sessions WHERE window_label = ?1 AND event = 'closed' ORDER BY id DESC LIMIT 1",
+            params![window.label()],
+            row_to_recent_session,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    if let Some(session) = &last {
+        conn.execute("DELETE FROM recent_sessions WHERE id = ?1", params![session.id])
+            .map_err(|e| e.to_string())?;
+        log::info!(
+            "Reopening session {} in window {}",
+            session.session_id,
+            session.window_label
+        );
+    }
+
+    Ok(last)
+}
@@ -0,0 +1,246 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use super::agents::AgentDb;
+
+/// Longest accepted language code or name
+const MAX_LANGUAGE_LEN: usize = 40;
+
+/// Names used in the instruction for common language codes
+const LANGUAGE_NAMES: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("zh", "Chinese"),
+    ("zh-cn", "Simplified Chinese"),
+    ("zh-tw", "Traditional Chinese"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("fr", "French"),
+    ("de", "German"),
+    ("es", "Spanish"),
+    ("pt", "Portuguese"),
+    ("ru", "Russian"),
+    ("it", "Italian"),
+];
+
+/// Response language of a project and a session, as returned by `get_response_language`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseLanguageSetting {
+    pub project_language: Option<String>,
+    /// The session sets its own language instead of the project's
+    pub session_overridden: bool,
+    /// The session's own language; None with an override means "no preference"
+    pub session_language: Option<String>,
+    /// Language runs of the session are instructed to answer in
+    pub effective: Option<String>,
+}
+
+/// Returned by `suggest_response_language`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseLanguageSuggestion {
+    /// Language `detect_text_language` found in the prompt
+    pub detected: String,
+    /// Language to offer as the setting; None when one is already set or the prompt is English
+    pub suggested: Option<String>,
+}
+
+pub fn init_response_language_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS project_response_languages (
+            project_path TEXT PRIMARY KEY,
+            language TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    // A row with a NULL language turns the project's language off for the session
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_response_languages (
+            session_id TEXT PRIMARY KEY,
+            language TEXT,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn normalize(language: Option<String>) -> Result<Option<String>, String> {
+    let Some(language) = language.map(|l| l.trim().to_string()).filter(|l| !l.is_empty()) else {
+        return Ok(None);
+    };
+    if language.chars().count() > MAX_LANGUAGE_LEN || language.contains(['\n', '\r']) {
+        return Err(format!("Invalid response language: {}", language));
+    }
+    Ok(Some(language))
+}
+
+fn load_setting(
+    conn: &Connection,
+    project_path: &str,
+    session_id: Option<&str>,
+) -> Result<ResponseLanguageSetting, String> {
+    let project_language: Option<String> = conn
+        .query_row(
+            "SELECT language FROM project_response_languages WHERE project_path = ?1",
+            [project_path],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let session_row: Option<Option<String>> = match session_id {
+        Some(session_id) => conn
+            .query_row(
+                "SELECT language FROM session_response_languages WHERE session_id = ?1",
+                [session_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?,
+        None => None,
+    };
+
+    let effective = match &session_row {
+        Some(session_language) => session_language.clone(),
+        None => project_language.clone(),
+    };
+    Ok(ResponseLanguageSetting {
+        project_language,
+        session_overridden: session_row.is_some(),
+        session_language: session_row.flatten(),
+        effective,
+    })
+}
+
+/// System prompt text asking for answers in `language`
+fn instruction(language: &str) -> String {
+    let name = LANGUAGE_NAMES
+        .iter()
+        .find(|(code, _)| code.eq_ignore_ascii_case(language))
+        .map_or(language, |(_, name)| *name);
+    format!(
+        "Always respond in {}, unless the user explicitly asks for another language. \
+         Keep code, identifiers, file paths, commands and quoted output unchanged.",
+        name
+    )
+}
+
+/// Append the response language instruction of a project (and session, when resuming)
+/// to the CLI arguments, merging it into an existing `--append-system-prompt`
+pub fn apply_response_language(
+    app: &AppHandle,
+    project_path: &str,
+    session_id: Option<&str>,
+    args: &mut Vec<String>,
+) {
+    let db = app.state::<AgentDb>();
+    let language = db
+        .0
+        .get()
+        .map_err(|e| e.to_string())
+        .and_then(|conn| load_setting(&conn, project_path, session_id))
+        .map(|setting| setting.effective);
+    let language = match language {
+        Ok(Some(language)) => language,
+        Ok(None) => return,
+        Err(e) => {
+            log::warn!("Failed to load response language: {}", e);
+            return;
+        }
+    };
+
+    let text = instruction(&language);
+    match args.iter().position(|arg| arg == "--append-system-prompt") {
+        Some(flag) if flag + 1 < args.len() => {
+            let existing = &mut args[flag + 1];
+            existing.push_str("\n\n");
+            existing.push_str(&text);
+        }
+        _ => {
+            args.push("--append-system-prompt".to_string());
+            args.push(text);
+        }
+    }
+    log::info!("Instructing the run to respond in {}", language);
+}
+
+/// Response language of a project and, if given, of one of its sessions
+#[tauri::command]
+pub async fn get_response_language(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    session_id: Option<String>,
+) -> Result<ResponseLanguageSetting, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    load_setting(&conn, &project_path, session_id.as_deref())
+}
+
+/// Set the language new runs in a project answer in; None clears it
+#[tauri::command]
+pub async fn set_project_response_language(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    language: Option<String>,
+) -> Result<ResponseLanguageSetting, String> {
+    let language = normalize(language)?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    match &language {
+        Some(language) => conn.execute(
+            "INSERT INTO project_response_languages (project_path, language) VALUES (?1, ?2)
+             ON CONFLICT(project_path) DO UPDATE SET language = ?2, updated_at = CURRENT_TIMESTAMP",
+            params![project_path, language],
+        ),
+        None => conn.execute(
+            "DELETE FROM project_response_languages WHERE project_path = ?1",
+            params![project_path],
+        ),
+    }
+    .map_err(|e| e.to_string())?;
+    log::info!("Response language of {} set to {:?}", project_path, language);
+    load_setting(&conn, &project_path, None)
+}
+
+/// Override the project's response language for one session. `language` None means no
+/// preference for this session; `inherit` drops the override so the project's applies.
+#[tauri::command]
+pub async fn set_session_response_language(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    session_id: String,
+    language: Option<String>,
+    inherit: Option<bool>,
+) -> Result<ResponseLanguageSetting, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    if inherit.unwrap_or(false) {
+        conn.execute(
+            "DELETE FROM session_response_languages WHERE session_id = ?1",
+            params![session_id],
+        )
+    } else {
+        conn.execute(
+            "INSERT INTO session_response_languages (session_id, language) VALUES (?1, ?2)
+             ON CONFLICT(session_id) DO UPDATE SET language = ?2, updated_at = CURRENT_TIMESTAMP",
+            params![session_id, normalize(language)?],
+        )
+    }
+    .map_err(|e| e.to_string())?;
+    load_setting(&conn, &project_path, Some(&session_id))
+}
+
+/// Suggest a response language from a session's first prompt, using the translator's
+/// language detection; nothing is suggested when a language already applies
+#[tauri::command]
+pub async fn suggest_response_language(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    session_id: Option<String>,
+    prompt: String,
+) -> Result<ResponseLanguageSuggestion, String> {
+    let detected = super::translator::detect_text_language(prompt).await?;
+    let effective = {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        load_setting(&conn, &project_path, session_id.as_deref())?.effective
+    };
+    let suggested = (effective.is_none() && detected != "en").then(|| detected.clone());
+    Ok(ResponseLanguageSuggestion { detected, suggested })
+}
@@ -0,0 +1,294 @@
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use super::usage::{calculate_cost_fast, UsageEntry};
+
+/// Usage sent straight to the recorded API base URL
+pub const SOURCE_DIRECT: &str = "direct";
+/// Usage sent through the router, provider unknown (no matching trace)
+pub const SOURCE_ROUTER: &str = "router";
+/// Usage sent through the router and attributed to a provider from its trace
+pub const SOURCE_ROUTER_TRACE: &str = "router_trace";
+
+/// Port claude-code-router listens on unless configured otherwise
+const DEFAULT_ROUTER_PORT: u16 = 3456;
+
+/// How long before a usage entry its router request may have started
+const MATCH_WINDOW_SECS: i64 = 600;
+
+/// Clock slack allowed for a trace logged after the usage entry
+const MATCH_SLACK_SECS: i64 = 5;
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct RouterConfig {
+    #[serde(rename = "HOST")]
+    host: Option<String>,
+    #[serde(rename = "PORT")]
+    port: Option<u16>,
+    #[serde(rename = "Providers", alias = "providers", default)]
+    pub(crate) providers: Vec<RouterProvider>,
+    #[serde(rename = "Router", default)]
+    pub(crate) router: RouterRoutes,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RouterProvider {
+    pub(crate) name: String,
+    pub(crate) api_base_url: String,
+    /// May name an environment variable as `$VAR` or `${VAR}`
+    #[serde(default)]
+    pub(crate) api_key: Option<String>,
+    #[serde(default)]
+    pub(crate) models: Vec<String>,
+}
+
+/// Routes as `provider,model`
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct RouterRoutes {
+    pub(crate) default: Option<String>,
+}
+
+/// One request the router forwarded, as recorded in its log
+#[derive(Debug, Clone)]
+struct RouterTrace {
+    timestamp: DateTime<Utc>,
+    provider: String,
+    model: String,
+    /// Input and output tokens, when the log line carries the response usage
+    tokens: Option<(u64, u64)>,
+}
+
+pub fn router_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".claude-code-router"))
+}
+
+pub(crate) fn load_router_config() -> RouterConfig {
+    router_dir()
+        .and_then(|dir| fs::read_to_string(dir.join("config.json")).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn is_loopback(host: &str) -> bool {
+    matches!(host, "localhost" | "127.0.0.1" | "::1" | "[::1]" | "0.0.0.0")
+}
+
+fn is_router_url(url: &str, config: &RouterConfig) -> bool {
+    // URLs still naming the port the router was moved off
+    let redirected = super::service_ports::redirect_router_url(url);
+    let Ok(url) = reqwest::Url::parse(redirected.as_deref().unwrap_or(url)) else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let host_matches = match config.host.as_deref() {
+        Some(configured) if !is_loopback(configured) => host == configured,
+        _ => is_loopback(host),
+    };
+    host_matches && url.port_or_known_default() == Some(config.port.unwrap_or(DEFAULT_ROUTER_PORT))
+}
+
+/// Host and port the router listens on
+pub fn router_address() -> (String, u16) {
+    let config = load_router_config();
+    let host = match config.host {
+        Some(host) if !is_loopback(&host) => host,
+        _ => "127.0.0.1".to_string(),
+    };
+    (host, config.port.unwrap_or(DEFAULT_ROUTER_PORT))
+}
+
+/// Whether `url` sends requests through the router
+pub fn is_router_base_url(url: &str) -> bool {
+    is_router_url(url, &load_router_config())
+}
+
+/// Attribution source for usage recorded against `api_base_url`
+pub fn provider_source_for(api_base_url: &str) -> &'static str {
+    if is_router_url(api_base_url, &load_router_config()) {
+        SOURCE_ROUTER
+    } else {
+        SOURCE_DIRECT
+    }
+}
+
+/// The router rewrites the request model to `provider,model`
+fn find_routed_model(value: &serde_json::Value, depth: usize) -> Option<(String, String)> {
+    if depth > 4 {
+        return None;
+    }
+    match value {
+        serde_json::Value::Object(map) => {
+            if let Some((provider, model)) = map
+                .get("model")
+                .and_then(|m| m.as_str())
+                .and_then(|m| m.split_once(','))
+            {
+                return Some((provider.trim().to_string(), model.trim().to_string()));
+            }
+            map.values().find_map(|v| find_routed_model(v, depth + 1))
+        }
+        _ => None,
+    }
+}
+
+/// Input/output tokens from an Anthropic- or OpenAI-style usage object
+fn find_usage(value: &serde_json::Value, depth: usize) -> Option<(u64, u64)> {
+    if depth > 4 {
+        return None;
+    }
+    let map = value.as_object()?;
+    if let Some(usage) = map.get("usage").and_then(|u| u.as_object()) {
+        let input = usage.get("input_tokens").or_else(|| usage.get("prompt_tokens"));
+        let output = usage.get("output_tokens").or_else(|| usage.get("completion_tokens"));
+        if let (Some(input), Some(output)) = (input.and_then(|v| v.as_u64()), output.and_then(|v| v.as_u64())) {
+            return Some((input, output));
+        }
+    }
+    map.values().find_map(|v| find_usage(v, depth + 1))
+}
+
+fn parse_time(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    match value {
+        serde_json::Value::Number(ms) => Utc.timestamp_millis_opt(ms.as_i64()?).single(),
+        serde_json::Value::String(s) => DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.with_timezone(&Utc)),
+        _ => None,
+    }
+}
+
+/// Parse the router's JSON-lines logs into forwarded requests.
+///
+/// Request and response lines share a `reqId`; the routed model comes from the
+/// request and the token counts, when logged, from the response.
+fn load_router_traces() -> Vec<RouterTrace> {
+    let Some(dir) = router_dir() else {
+        return Vec::new();
+    };
+    let mut log_files = vec![dir.join("claude-code-router.log")];
+    if let Ok(entries) = fs::read_dir(dir.join("logs")) {
+        log_files.extend(
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("log")),
+        );
+    }
+
+    let mut by_request: HashMap<String, RouterTrace> = HashMap::new();
+    let mut traces = Vec::new();
+    for path in log_files {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for line in content.lines() {
+            let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+                continue;
+            };
+            let timestamp = value
+                .get("time")
+                .or_else(|| value.get("timestamp"))
+                .and_then(parse_time);
+            let request_id = value.get("reqId").and_then(|v| v.as_str()).map(str::to_string);
+
+            if let (Some(timestamp), Some((provider, model))) = (timestamp, find_routed_model(&value, 0)) {
+                let trace = RouterTrace {
+                    timestamp,
+                    provider,
+                    model,
+                    tokens: find_usage(&value, 0),
+                };
+                match request_id {
+                    Some(id) => {
+                        by_request.insert(id, trace);
+                    }
+                    None => traces.push(trace),
+                }
+            } else if let (Some(id), Some(tokens)) = (request_id, find_usage(&value, 0)) {
+                if let Some(trace) = by_request.get_mut(&id) {
+                    trace.tokens = Some(tokens);
+                }
+            }
+        }
+    }
+
+    traces.extend(by_request.into_values());
+    traces.sort_by_key(|t| t.timestamp);
+    traces
+}
+
+/// Re-attribute router-proxied usage to the provider and model that served it.
+///
+/// Each entry recorded against the router is matched with an unused trace from
+/// the preceding `MATCH_WINDOW_SECS`, preferring one with identical token counts,
+/// else the latest. Matched entries get the provider's base URL, the routed model
+/// and a cost recomputed for that model. Does nothing while the router subsystem
+/// is disabled.
+pub fn reconcile_router_usage(entries: &mut [UsageEntry]) {
+    if !super::startup::is_enabled(super::startup::Subsystem::Router) {
+        return;
+    }
+    let config = load_router_config();
+    let routed: Vec<usize> = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| is_router_url(&e.api_base_url, &config))
+        .map(|(i, _)| i)
+        .collect();
+    if routed.is_empty() {
+        return;
+    }
+
+    let traces = load_router_traces();
+    let base_urls: HashMap<&str, &str> = config
+        .providers
+        .iter()
+        .map(|p| (p.name.as_str(), p.api_base_url.as_str()))
+        .collect();
+    let mut used = vec![false; traces.len()];
+    let mut matched = 0;
+
+    for index in routed {
+        let entry = &mut entries[index];
+        entry.provider_source = SOURCE_ROUTER.to_string();
+        let Ok(at) = DateTime::parse_from_rfc3339(&entry.timestamp).map(|dt| dt.with_timezone(&Utc)) else {
+            continue;
+        };
+
+        let window_start = at - chrono::Duration::seconds(MATCH_WINDOW_SECS);
+        let window_end = at + chrono::Duration::seconds(MATCH_SLACK_SECS);
+        let candidates: Vec<usize> = (0..traces.len())
+            .filter(|&i| !used[i] && traces[i].timestamp >= window_start && traces[i].timestamp <= window_end)
+            .collect();
+        let best = candidates
+            .iter()
+            .copied()
+            .find(|&i| traces[i].tokens == Some((entry.input_tokens, entry.output_tokens)))
+            .or_else(|| candidates.last().copied());
+        let Some(best) = best else {
+            continue;
+        };
+
+        used[best] = true;
+        matched += 1;
+        let trace = &traces[best];
+        entry.api_base_url = base_urls
+            .get(trace.provider.as_str())
+            .map(|url| url.to_string())
+            .unwrap_or_else(|| format!("ccr://{}", trace.provider));
+        entry.model = trace.model.clone();
+        entry.cost = calculate_cost_fast(
+            &entry.model,
+            entry.input_tokens,
+            entry.output_tokens,
+            entry.cache_creation_tokens,
+            entry.cache_read_tokens,
+        );
+        entry.provider_source = SOURCE_ROUTER_TRACE.to_string();
+    }
+
+    log::debug!("Attributed {} router-proxied usage entries from router traces", matched);
+}
@@ -0,0 +1,358 @@
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::router_usage::{load_router_config, RouterConfig, RouterProvider};
+use super::startup::{ensure_enabled, is_enabled, Subsystem};
+
+/// How long a provider gets to answer its probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest provider response body kept in a validation detail
+const MAX_DETAIL_CHARS: usize = 200;
+
+/// Outcome of a provider's auth probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderStatus {
+    /// The provider accepted the key
+    Ok,
+    /// No API key is configured, or the environment variable it names is unset
+    MissingKey,
+    /// The provider rejected the key
+    AuthFailed,
+    /// Nothing answers at the base URL, or the URL is malformed
+    Unreachable,
+    /// The host answers but has no API at the base URL's path
+    WrongBaseUrl,
+    /// The provider answered without confirming or rejecting the key, e.g. rate limited
+    Unverified,
+}
+
+impl ProviderStatus {
+    fn works(self) -> bool {
+        matches!(self, ProviderStatus::Ok | ProviderStatus::Unverified)
+    }
+}
+
+/// Validation result of one provider of the ccr config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderValidation {
+    pub provider: String,
+    pub api_base_url: String,
+    pub status: ProviderStatus,
+    /// HTTP status or error of the probe
+    pub detail: Option<String>,
+    /// Configured or routed models the provider does not list
+    pub missing_models: Vec<String>,
+    /// Whether requests routed to the provider should succeed
+    pub works: bool,
+}
+
+/// Returned by `router_validate_providers`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouterValidation {
+    pub providers: Vec<ProviderValidation>,
+    /// `provider,model` of the default route
+    pub default_route: Option<String>,
+    /// Whether routing can be switched on: the default route's provider works and
+    /// serves the route's model
+    pub routing_allowed: bool,
+    pub blocked_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProbeStyle {
+    OpenAi,
+    Anthropic,
+    Gemini,
+}
+
+/// The models endpoint next to a provider's completions endpoint, which every
+/// supported API authenticates like a completion without spending tokens
+fn probe_target(api_base_url: &str) -> (String, ProbeStyle) {
+    let url = api_base_url.trim().trim_end_matches('/');
+    if url.contains("generativelanguage.googleapis.com") {
+        let version_end = ["/v1beta", "/v1"]
+            .iter()
+            .find_map(|version| url.find(version).map(|i| i + version.len()));
+        let base = version_end.map_or(url, |end| &url[..end]);
+        return (format!("{}/models?pageSize=1000", base), ProbeStyle::Gemini);
+    }
+    if let Some(base) = url.strip_suffix("/messages") {
+        return (format!("{}/models?limit=1000", base), ProbeStyle::Anthropic);
+    }
+    let base = ["/chat/completions", "/completions", "/responses"]
+        .iter()
+        .find_map(|suffix| url.strip_suffix(suffix))
+        .unwrap_or(url);
+    (format!("{}/models", base), ProbeStyle::OpenAi)
+}
+
+/// The provider's key, with a `$VAR` or `${VAR}` reference resolved
+fn resolve_key(raw: Option<&str>) -> Option<String> {
+    let raw = raw?.trim();
+    let variable = raw
+        .strip_prefix("${")
+        .and_then(|rest| rest.strip_suffix('}'))
+        .or_else(|| raw.strip_prefix('$'));
+    let key = match variable {
+        Some(name) => std::env::var(name).ok()?,
+        None => raw.to_string(),
+    };
+    (!key.trim().is_empty()).then_some(key)
+}
+
+fn is_local(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .map_or(false, |host| {
+            matches!(host.as_str(), "localhost" | "127.0.0.1" | "::1" | "[::1]")
+        })
+}
+
+fn classify(style: ProbeStyle, status: u16) -> ProviderStatus {
+    match status {
+        200..=299 => ProviderStatus::Ok,
+        401 | 403 => ProviderStatus::AuthFailed,
+        // Google answers a bad key with 400 INVALID_ARGUMENT
+        400 if style == ProbeStyle::Gemini => ProviderStatus::AuthFailed,
+        404 | 405 => ProviderStatus::WrongBaseUrl,
+        _ => ProviderStatus::Unverified,
+    }
+}
+
+/// Model IDs of an OpenAI, Anthropic or Gemini model list
+fn listed_models(body: &serde_json::Value) -> Option<Vec<String>> {
+    if let Some(data) = body.get("data").and_then(|d| d.as_array()) {
+        return Some(
+            data.iter()
+                .filter_map(|m| m.get("id").and_then(|id| id.as_str()))
+                .map(str::to_string)
+                .collect(),
+        );
+    }
+    let models = body.get("models").and_then(|m| m.as_array())?;
+    Some(
+        models
+            .iter()
+            .filter_map(|m| m.get("name").and_then(|name| name.as_str()))
+            .map(|name| name.trim_start_matches("models/").to_string())
+            .collect(),
+    )
+}
+
+fn truncate(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(MAX_DETAIL_CHARS) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Probe one provider; `wanted` are the models it should serve
+async fn validate_provider(
+    client: &reqwest::Client,
+    provider: &RouterProvider,
+    wanted: Vec<String>,
+) -> ProviderValidation {
+    let result =
+        |status: ProviderStatus, detail: Option<String>, missing: Vec<String>| ProviderValidation {
+            provider: provider.name.clone(),
+            api_base_url: provider.api_base_url.clone(),
+            status,
+            detail,
+            missing_models: missing,
+            works: status.works(),
+        };
+
+    let key = resolve_key(provider.api_key.as_deref());
+    if key.is_none() && !is_local(&provider.api_base_url) {
+        return result(ProviderStatus::MissingKey, None, Vec::new());
+    }
+    let (url, style) = probe_target(&provider.api_base_url);
+    let mut request = client.get(&url);
+    if let Some(key) = &key {
+        request = match style {
+            ProbeStyle::OpenAi => request.bearer_auth(key),
+            ProbeStyle::Anthropic => request
+                .header("x-api-key", key)
+                .header("anthropic-version", "2023-06-01"),
+            ProbeStyle::Gemini => request.header("x-goog-api-key", key),
+        };
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => return result(ProviderStatus::Unreachable, Some(e.to_string()), Vec::new()),
+    };
+    let code = response.status();
+    let status = classify(style, code.as_u16());
+    let body = response.text().await.unwrap_or_default();
+    if status != ProviderStatus::Ok {
+        let detail = format!("HTTP {}: {}", code.as_u16(), truncate(&body));
+        return result(status, Some(detail), Vec::new());
+    }
+
+    // A provider without a parsable model list cannot rule models out
+    let missing = serde_json::from_str(&body)
+        .ok()
+        .and_then(|body| listed_models(&body))
+        .map(|listed| wanted.into_iter().filter(|m| !listed.contains(m)).collect())
+        .unwrap_or_default();
+    result(status, None, missing)
+}
+
+/// Why the default route cannot serve requests, if it cannot
+fn default_route_problem(
+    default: Option<&str>,
+    providers: &[ProviderValidation],
+) -> Option<String> {
+    let Some(route) = default.filter(|route| !route.trim().is_empty()) else {
+        return Some("The router has no default route".to_string());
+    };
+    let Some((name, model)) = route.split_once(',') else {
+        return Some(format!("Default route '{}' is not 'provider,model'", route));
+    };
+    let (name, model) = (name.trim(), model.trim());
+    let Some(provider) = providers.iter().find(|p| p.provider == name) else {
+        return Some(format!(
+            "Default route uses provider '{}', which is not configured",
+            name
+        ));
+    };
+    if !provider.works {
+        let reason = provider.detail.as_deref().unwrap_or("no API key");
+        return Some(format!(
+            "Provider '{}' of the default route failed validation ({:?}: {})",
+            name, provider.status, reason
+        ));
+    }
+    if provider.missing_models.iter().any(|m| m == model) {
+        return Some(format!(
+            "Provider '{}' does not serve model '{}' of the default route",
+            name, model
+        ));
+    }
+    None
+}
+
+async fn validate(config: &RouterConfig) -> RouterValidation {
+    let client = reqwest::Client::builder()
+        .timeout(PROBE_TIMEOUT)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new());
+    let default_route = config.router.default.clone();
+    let probes = config.providers.iter().map(|provider| {
+        let mut wanted = provider.models.clone();
+        if let Some((name, model)) = default_route.as_deref().and_then(|r| r.split_once(',')) {
+            if name.trim() == provider.name && !wanted.iter().any(|m| m == model.trim()) {
+                wanted.push(model.trim().to_string());
+            }
+        }
+        validate_provider(&client, provider, wanted)
+    });
+    let providers = join_all(probes).await;
+
+    let blocked_reason = default_route_problem(default_route.as_deref(), &providers);
+    RouterValidation {
+        providers,
+        default_route,
+        routing_allowed: blocked_reason.is_none(),
+        blocked_reason,
+    }
+}
+
+/// Fail when Claude should not be pointed at the router because the default route's
+/// provider does not work. Does nothing while the router subsystem is disabled.
+pub async fn ensure_routing_allowed() -> Result<(), String> {
+    if !is_enabled(Subsystem::Router) {
+        return Ok(());
+    }
+    let validation = validate(&load_router_config()).await;
+    match validation.blocked_reason {
+        Some(reason) => Err(format!("Routing cannot be enabled: {}", reason)),
+        None => Ok(()),
+    }
+}
+
+/// Probe every provider of the claude-code-router config with its key, and report
+/// which will work and whether the default route can serve requests
+#[tauri::command]
+pub async fn router_validate_providers() -> Result<RouterValidation, String> {
+    ensure_enabled(Subsystem::Router)?;
+    Ok(validate(&load_router_config()).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_target() {
+        assert_eq!(
+            probe_target("https://api.deepseek.com/chat/completions"),
+            (
+                "https://api.deepseek.com/models".to_string(),
+                ProbeStyle::OpenAi
+            )
+        );
+        assert_eq!(
+            probe_target("https://openrouter.ai/api/v1/chat/completions/"),
+            (
+                "https://openrouter.ai/api/v1/models".to_string(),
+                ProbeStyle::OpenAi
+            )
+        );
+        assert_eq!(
+            probe_target("https://api.anthropic.com/v1/messages").1,
+            ProbeStyle::Anthropic
+        );
+        assert_eq!(
+            probe_target("https://generativelanguage.googleapis.com/v1beta/models/").0,
+            "https://generativelanguage.googleapis.com/v1beta/models?pageSize=1000"
+        );
+    }
+
+    #[test]
+    fn test_resolve_key() {
+        assert_eq!(resolve_key(Some("sk-123")), Some("sk-123".to_string()));
+        assert_eq!(resolve_key(Some("  ")), None);
+        assert_eq!(resolve_key(Some("${ROUTER_VALIDATION_UNSET_KEY}")), None);
+        assert_eq!(resolve_key(None), None);
+    }
+
+    #[test]
+    fn test_default_route_problem() {
+        let provider = |name: &str, status: ProviderStatus, missing: &[&str]| ProviderValidation {
+            provider: name.to_string(),
+            api_base_url: String::new(),
+            status,
+            detail: None,
+            missing_models: missing.iter().map(|m| m.to_string()).collect(),
+            works: status.works(),
+        };
+        let providers = vec![
+            provider("deepseek", ProviderStatus::Ok, &["deepseek-reasoner"]),
+            provider("openrouter", ProviderStatus::AuthFailed, &[]),
+        ];
+
+        assert_eq!(
+            default_route_problem(Some("deepseek,deepseek-chat"), &providers),
+            None
+        );
+        assert!(
+            default_route_problem(Some("deepseek,deepseek-reasoner"), &providers)
+                .unwrap()
+                .contains("does not serve")
+        );
+        assert!(default_route_problem(Some("openrouter,x"), &providers)
+            .unwrap()
+            .contains("AuthFailed"));
+        assert!(default_route_problem(Some("ollama,llama3"), &providers)
+            .unwrap()
+            .contains("not configured"));
+        assert!(default_route_problem(None, &providers).is_some());
+    }
+}
@@ -0,0 +1,199 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::watch;
+
+use super::agents::AgentDb;
+use super::settings_store;
+
+/// `app_settings` key holding the dedupe window in seconds
+const WINDOW_SETTING: &str = "run_dedupe_window_secs";
+
+/// Seconds a key is remembered unless configured otherwise
+const DEFAULT_WINDOW_SECS: u64 = 120;
+
+/// Longest window that can be configured
+const MAX_WINDOW_SECS: u64 = 24 * 60 * 60;
+
+static WINDOW_SECS: AtomicU64 = AtomicU64::new(DEFAULT_WINDOW_SECS);
+
+/// Outcome of a claimed start: the run ID, or why it failed
+type StartOutcome = Option<Result<i64, String>>;
+
+struct Claim {
+    claimed_at: Instant,
+    operation: &'static str,
+    project_path: String,
+    session_id: Option<String>,
+    outcome: watch::Receiver<StartOutcome>,
+}
+
+/// Recent execution requests by idempotency key
+static CLAIMS: Lazy<Mutex<HashMap<String, Claim>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Returned by `execute_claude_code`, `continue_claude_code` and `resume_claude_code`
+/// as soon as the CLI is spawned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunHandle {
+    /// Pass to `get_run_status`; also the `run_id` of `claude-session-state` events
+    pub run_id: i64,
+    pub idempotency_key: Option<String>,
+    /// `execute`, `continue` or `resume`
+    pub operation: String,
+    pub project_path: String,
+    /// Process id of the Claude CLI
+    pub pid: u32,
+    /// The resumed session's id, or an id made up at spawn until the CLI reports its own
+    pub provisional_session_id: String,
+    /// Known once the CLI reports its session
+    pub session_id: Option<String>,
+    pub started_at: String,
+    /// An earlier request with the same key started this run; nothing new was spawned
+    pub deduplicated: bool,
+}
+
+/// What a start request is for, so a reused key can be told apart from a retry
+pub struct RunRequest {
+    pub operation: &'static str,
+    pub project_path: String,
+    pub session_id: Option<String>,
+}
+
+pub fn init_run_dedupe(app: &AppHandle) {
+    let db = app.state::<AgentDb>();
+    let stored = db
+        .0
+        .get()
+        .and_then(|conn| settings_store::get::<u64>(&conn, WINDOW_SETTING));
+    match stored {
+        Ok(Some(secs)) => WINDOW_SECS.store(secs, Ordering::Relaxed),
+        Ok(None) => {}
+        Err(e) => log::warn!("{}, using the default dedupe window", e),
+    }
+}
+
+fn window() -> Duration {
+    Duration::from_secs(WINDOW_SECS.load(Ordering::Relaxed))
+}
+
+fn handle(key: Option<String>, operation: &str, run_id: i64, deduplicated: bool) -> Result<RunHandle, String> {
+    let status = super::run_status::get(run_id).ok_or_else(|| format!("Run {} not found", run_id))?;
+    Ok(RunHandle {
+        run_id,
+        idempotency_key: key,
+        operation: operation.to_string(),
+        project_path: status.project_path,
+        pid: status.pid,
+        provisional_session_id: status.provisional_session_id,
+        session_id: status.session_id,
+        started_at: status.started_at,
+        deduplicated,
+    })
+}
+
+/// Handle of a run started without an idempotency key
+pub fn run_handle(run_id: i64, operation: &str) -> Result<RunHandle, String> {
+    handle(None, operation, run_id, false)
+}
+
+/// Start a run unless a request with the same idempotency key already did within the
+/// dedupe window, in which case that run's handle is returned. A retry that arrives
+/// while the first request is still spawning waits for its outcome. Failed starts are
+/// forgotten so they can be retried; `force_new_run` always spawns.
+pub async fn run_once<F>(
+    key: Option<String>,
+    force_new_run: bool,
+    request: RunRequest,
+    start: F,
+) -> Result<RunHandle, String>
+where
+    F: Future<Output = Result<i64, String>>,
+{
+    let key = key.map(|key| key.trim().to_string()).filter(|key| !key.is_empty());
+    let Some(key) = key else {
+        let run_id = start.await?;
+        return handle(None, request.operation, run_id, false);
+    };
+
+    let (sender, mut existing) = {
+        let mut claims = CLAIMS.lock().map_err(|e| e.to_string())?;
+        let window = window();
+        claims.retain(|_, claim| claim.claimed_at.elapsed() < window);
+
+        match claims.get(&key) {
+            Some(claim) if !force_new_run => {
+                if claim.operation != request.operation
+                    || claim.project_path != request.project_path
+                    || claim.session_id != request.session_id
+                {
+                    return Err(format!(
+                        "Idempotency key {} was already used for a different run ({} in {})",
+                        key, claim.operation, claim.project_path
+                    ));
+                }
+                (None, Some(claim.outcome.clone()))
+            }
+            _ => {
+                let (sender, outcome) = watch::channel(None);
+                claims.insert(
+                    key.clone(),
+                    Claim {
+                        claimed_at: Instant::now(),
+                        operation: request.operation,
+                        project_path: request.project_path.clone(),
+                        session_id: request.session_id.clone(),
+                        outcome,
+                    },
+                );
+                (Some(sender), None)
+            }
+        }
+    };
+
+    if let Some(outcome) = existing.as_mut() {
+        log::info!("Request with idempotency key {} is a retry; not spawning another run", key);
+        let result = outcome
+            .wait_for(Option::is_some)
+            .await
+            .map_err(|_| "The original run request was abandoned".to_string())?
+            .clone()
+            .expect("waited for an outcome");
+        let run_id = result?;
+        return handle(Some(key), request.operation, run_id, true);
+    }
+
+    let result = start.await;
+    if result.is_err() {
+        if let Ok(mut claims) = CLAIMS.lock() {
+            claims.remove(&key);
+        }
+    }
+    if let Some(sender) = sender {
+        let _ = sender.send(Some(result.clone()));
+    }
+    let run_id = result?;
+    handle(Some(key), request.operation, run_id, false)
+}
+
+/// How long idempotency keys of execution requests are remembered, in seconds
+#[tauri::command]
+pub async fn get_run_dedupe_window() -> Result<u64, String> {
+    Ok(WINDOW_SECS.load(Ordering::Relaxed))
+}
+
+/// Set how long idempotency keys are remembered; 0 turns deduplication off
+#[tauri::command]
+pub async fn set_run_dedupe_window(app: AppHandle, seconds: u64) -> Result<u64, String> {
+    if seconds > MAX_WINDOW_SECS {
+        return Err(format!("The dedupe window can be at most {} seconds", MAX_WINDOW_SECS));
+    }
+    settings_store::set(&app, WINDOW_SETTING, &seconds)?;
+    WINDOW_SECS.store(seconds, Ordering::Relaxed);
+    log::info!("Run dedupe window set to {}s", seconds);
+    Ok(seconds)
+}
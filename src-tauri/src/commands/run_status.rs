@@ -0,0 +1,156 @@
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use super::cost_cap::CostCapReached;
+
+/// Finished runs kept for status lookups; the oldest are forgotten first
+const MAX_FINISHED_RUNS: usize = 200;
+
+/// Where a Claude run is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    /// Spawned; the CLI has not reported its session yet
+    Starting,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+    /// Stopped after a checkpoint because its estimated cost exceeded its cap
+    CostCapReached,
+}
+
+impl RunState {
+    fn is_finished(self) -> bool {
+        matches!(
+            self,
+            RunState::Completed | RunState::Failed | RunState::Cancelled | RunState::CostCapReached
+        )
+    }
+}
+
+/// Returned by `get_run_status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunStatus {
+    pub run_id: i64,
+    pub pid: u32,
+    pub project_path: String,
+    pub model: String,
+    /// The resumed session's id, or an id made up at spawn until the CLI reports its own
+    pub provisional_session_id: String,
+    /// The id the CLI reported; events are scoped to it
+    pub session_id: Option<String>,
+    pub state: RunState,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    /// Set when `cancel_claude_execution` was called for the run
+    pub cancel_requested: bool,
+    /// Set when the run was stopped at its cost cap
+    pub cost_cap: Option<CostCapReached>,
+}
+
+static RUNS: Lazy<Mutex<BTreeMap<i64, RunStatus>>> = Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+fn update(run_id: i64, apply: impl FnOnce(&mut RunStatus)) {
+    if let Ok(mut runs) = RUNS.lock() {
+        if let Some(run) = runs.get_mut(&run_id) {
+            apply(run);
+        }
+    }
+}
+
+/// Record a just-spawned run. `resumed_session` is the session being resumed, if any.
+pub fn record_spawn(run_id: i64, pid: u32, project_path: &str, model: &str, resumed_session: Option<&str>) {
+    let status = RunStatus {
+        run_id,
+        pid,
+        project_path: project_path.to_string(),
+        model: model.to_string(),
+        provisional_session_id: resumed_session
+            .map(str::to_string)
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        session_id: None,
+        state: RunState::Starting,
+        started_at: Utc::now().to_rfc3339(),
+        finished_at: None,
+        cancel_requested: false,
+        cost_cap: None,
+    };
+    let Ok(mut runs) = RUNS.lock() else {
+        return;
+    };
+    runs.insert(run_id, status);
+
+    let finished: Vec<i64> = runs
+        .values()
+        .filter(|run| run.state.is_finished())
+        .map(|run| run.run_id)
+        .collect();
+    for run_id in finished.iter().take(finished.len().saturating_sub(MAX_FINISHED_RUNS)) {
+        runs.remove(run_id);
+    }
+}
+
+/// The CLI reported the run's session id
+pub fn record_session(run_id: i64, session_id: &str) {
+    update(run_id, |run| {
+        run.session_id = Some(session_id.to_string());
+        run.state = RunState::Running;
+    });
+}
+
+/// The run's process exited
+pub fn record_exit(run_id: i64, success: bool) {
+    update(run_id, |run| {
+        run.state = if run.cost_cap.is_some() {
+            RunState::CostCapReached
+        } else if run.cancel_requested {
+            RunState::Cancelled
+        } else if success {
+            RunState::Completed
+        } else {
+            RunState::Failed
+        };
+        run.finished_at = Some(Utc::now().to_rfc3339());
+    });
+}
+
+/// The run is being stopped at its cost cap
+pub fn record_cost_cap(run_id: i64, outcome: CostCapReached) {
+    update(run_id, |run| run.cost_cap = Some(outcome));
+}
+
+/// Flag the unfinished runs of a session, or the latest unfinished run when no
+/// session is given, as cancelled
+pub fn record_cancel_request(session_id: Option<&str>) {
+    let Ok(mut runs) = RUNS.lock() else {
+        return;
+    };
+    let mut active = runs.values_mut().rev().filter(|run| !run.state.is_finished());
+    match session_id {
+        Some(session_id) => active
+            .filter(|run| {
+                run.session_id.as_deref() == Some(session_id) || run.provisional_session_id == session_id
+            })
+            .for_each(|run| run.cancel_requested = true),
+        None => {
+            if let Some(run) = active.next() {
+                run.cancel_requested = true;
+            }
+        }
+    }
+}
+
+pub fn get(run_id: i64) -> Option<RunStatus> {
+    RUNS.lock().ok().and_then(|runs| runs.get(&run_id).cloned())
+}
+
+/// Status of a Claude run started by `execute_claude_code`, `continue_claude_code` or
+/// `resume_claude_code`
+#[tauri::command]
+pub async fn get_run_status(run_id: i64) -> Result<RunStatus, String> {
+    get(run_id).ok_or_else(|| format!("Run {} not found", run_id))
+}
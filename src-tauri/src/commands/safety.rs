@@ -0,0 +1,141 @@
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use super::agents::AgentDb;
+use super::claude::{get_claude_execution_config, update_claude_execution_config};
+use super::permission_config::ClaudeExecutionConfig;
+
+/// Audit entries included in the safety status
+const RECENT_AUDIT_LIMIT: i64 = 20;
+
+/// One execution that ran with `--dangerously-skip-permissions`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DangerousSkipAuditEntry {
+    pub id: i64,
+    /// `execute`, `continue`, `resume` or `agent`
+    pub source: String,
+    pub project_path: String,
+    pub session_id: Option<String>,
+    /// Expiry of the dangerous-skip setting at the time of the run
+    pub expires_at: Option<String>,
+    pub created_at: String,
+}
+
+/// Returned by `get_safety_status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafetyStatus {
+    /// Dangerous-skip is on and not yet expired
+    pub dangerous_skip_active: bool,
+    pub dangerous_skip_expires_at: Option<DateTime<Utc>>,
+    pub dangerous_skip_remaining_minutes: Option<i64>,
+    /// Projects dangerous-skip applies to
+    pub dangerous_skip_projects: Vec<String>,
+    /// Global hooks kill switch
+    pub hooks_disabled: bool,
+    pub recent_audit: Vec<DangerousSkipAuditEntry>,
+}
+
+/// Record a run that skips permission checks
+pub fn record_dangerous_skip_run(
+    app: &AppHandle,
+    source: &str,
+    project_path: &str,
+    session_id: Option<&str>,
+    expires_at: Option<DateTime<Utc>>,
+) {
+    let Some(db) = app.try_state::<AgentDb>() else {
+        return;
+    };
+    let result = db.0.get().map_err(|e| e.to_string()).and_then(|conn| {
+        conn.execute(
+            "INSERT INTO dangerous_skip_audit (source, project_path, session_id, expires_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![source, project_path, session_id, expires_at.map(|at| at.to_rfc3339())],
+        )
+        .map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        log::error!("Failed to audit dangerous-skip run in {}: {}", project_path, e);
+    }
+}
+
+/// Apply the dangerous-skip policy to one execution.
+///
+/// The flag is dropped unless it is active for `project_path`; when it stays on the
+/// run is audited.
+pub fn apply_dangerous_skip_policy(
+    app: &AppHandle,
+    source: &str,
+    project_path: &str,
+    session_id: Option<&str>,
+    config: &mut ClaudeExecutionConfig,
+) {
+    let permissions = &mut config.permissions;
+    if !permissions.enable_dangerous_skip {
+        return;
+    }
+    if !permissions.dangerous_skip_applies_to(project_path) {
+        log::info!(
+            "Dangerous-skip is expired or not enabled for {}, using regular permissions",
+            project_path
+        );
+        permissions.enable_dangerous_skip = false;
+        return;
+    }
+
+    log::warn!("Running in {} with permission checks skipped", project_path);
+    record_dangerous_skip_run(app, source, project_path, session_id, permissions.dangerous_skip_expires_at);
+}
+
+/// Get the current safety settings and the latest dangerous-skip runs
+#[tauri::command]
+pub async fn get_safety_status(app: AppHandle, db: State<'_, AgentDb>) -> Result<SafetyStatus, String> {
+    // Loading the config reverts an expired dangerous-skip
+    let permissions = get_claude_execution_config(app).await?.permissions;
+    let active = permissions.enable_dangerous_skip && !permissions.dangerous_skip_expired();
+    let expires_at = permissions.dangerous_skip_expires_at.filter(|_| active);
+
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, source, project_path, session_id, expires_at, created_at
+             FROM dangerous_skip_audit ORDER BY id DESC LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let recent_audit = stmt
+        .query_map([RECENT_AUDIT_LIMIT], |row| {
+            Ok(DangerousSkipAuditEntry {
+                id: row.get(0)?,
+                source: row.get(1)?,
+                project_path: row.get(2)?,
+                session_id: row.get(3)?,
+                expires_at: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(SafetyStatus {
+        dangerous_skip_active: active,
+        dangerous_skip_expires_at: expires_at,
+        dangerous_skip_remaining_minutes: expires_at.map(|at| (at - Utc::now()).num_minutes().max(0)),
+        dangerous_skip_projects: if active { permissions.dangerous_skip_projects } else { Vec::new() },
+        hooks_disabled: super::enhanced_hooks::hooks_disabled(),
+        recent_audit,
+    })
+}
+
+/// Kill switch: turn dangerous-skip off immediately
+#[tauri::command]
+pub async fn disable_dangerous_skip(app: AppHandle) -> Result<(), String> {
+    let mut config = get_claude_execution_config(app.clone()).await?;
+    config.permissions.enable_dangerous_skip = false;
+    config.permissions.dangerous_skip_expires_at = None;
+    update_claude_execution_config(app, config).await?;
+    log::warn!("Dangerous-skip permission mode disabled");
+    Ok(())
+}
@@ -0,0 +1,437 @@
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use ts_rs::TS;
+
+use super::agents::AgentDb;
+use super::events::AppEvent;
+use super::run_status::RunState;
+
+/// How far in the past `run_at` may be, to allow for clock drift in the UI
+const RUN_AT_SLACK_SECS: i64 = 60;
+
+/// A claimed prompt without a run after this long was interrupted while starting
+const LAUNCH_TIMEOUT_SECS: i64 = 10 * 60;
+
+/// Where a scheduled prompt is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum ScheduledPromptStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+    /// Not started because its estimated cost was already over its cap
+    Skipped,
+    CostCapReached,
+}
+
+impl ScheduledPromptStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            ScheduledPromptStatus::Pending => "pending",
+            ScheduledPromptStatus::Running => "running",
+            ScheduledPromptStatus::Completed => "completed",
+            ScheduledPromptStatus::Failed => "failed",
+            ScheduledPromptStatus::Cancelled => "cancelled",
+            ScheduledPromptStatus::Skipped => "skipped",
+            ScheduledPromptStatus::CostCapReached => "cost_cap_reached",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "pending" => ScheduledPromptStatus::Pending,
+            "running" => ScheduledPromptStatus::Running,
+            "completed" => ScheduledPromptStatus::Completed,
+            "cancelled" => ScheduledPromptStatus::Cancelled,
+            "skipped" => ScheduledPromptStatus::Skipped,
+            "cost_cap_reached" => ScheduledPromptStatus::CostCapReached,
+            _ => ScheduledPromptStatus::Failed,
+        }
+    }
+}
+
+/// A prompt run once at a set time in a new session; payload of
+/// `scheduled-prompt-started` and `scheduled-prompt-finished`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScheduledPrompt {
+    pub id: i64,
+    pub project_path: String,
+    pub prompt: String,
+    pub model: String,
+    /// RFC 3339, UTC
+    pub run_at: String,
+    /// Skip the prompt if its estimated input cost exceeds this, and stop the run
+    /// once its spend does
+    pub max_cost_usd: Option<f64>,
+    pub status: ScheduledPromptStatus,
+    /// The Claude run, once started
+    pub run_id: Option<i64>,
+    pub session_id: Option<String>,
+    /// Why the prompt failed or was skipped
+    pub error: Option<String>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+pub fn init_scheduled_prompt_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS scheduled_prompts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_path TEXT NOT NULL,
+            prompt TEXT NOT NULL,
+            model TEXT NOT NULL,
+            run_at TEXT NOT NULL,
+            max_cost_usd REAL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            run_id INTEGER,
+            session_id TEXT,
+            error TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            started_at TEXT,
+            finished_at TEXT
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_scheduled_prompts_status ON scheduled_prompts(status)",
+        [],
+    )?;
+    Ok(())
+}
+
+const PROMPT_COLUMNS: &str = "id, project_path, prompt, model, run_at, max_cost_usd, status, \
+    run_id, session_id, error, created_at, started_at, finished_at";
+
+fn row_to_prompt(row: &rusqlite::Row) -> rusqlite::Result<ScheduledPrompt> {
+    Ok(ScheduledPrompt {
+        id: row.get(0)?,
+        project_path: row.get(1)?,
+        prompt: row.get(2)?,
+        model: row.get(3)?,
+        run_at: row.get(4)?,
+        max_cost_usd: row.get(5)?,
+        status: ScheduledPromptStatus::parse(&row.get::<_, String>(6)?),
+        run_id: row.get(7)?,
+        session_id: row.get(8)?,
+        error: row.get(9)?,
+        created_at: row.get(10)?,
+        started_at: row.get(11)?,
+        finished_at: row.get(12)?,
+    })
+}
+
+fn load_prompt(conn: &Connection, id: i64) -> Result<ScheduledPrompt, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM scheduled_prompts WHERE id = ?1", PROMPT_COLUMNS),
+        params![id],
+        row_to_prompt,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Scheduled prompt {} not found", id))
+}
+
+fn prompts_with_status(
+    conn: &Connection,
+    status: ScheduledPromptStatus,
+) -> Result<Vec<ScheduledPrompt>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM scheduled_prompts WHERE status = ?1 ORDER BY run_at, id",
+            PROMPT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![status.as_str()], row_to_prompt)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Check the inputs and normalize `run_at` to UTC
+fn validate(
+    project_path: &str,
+    prompt: &str,
+    run_at: &str,
+    max_cost_usd: Option<f64>,
+) -> Result<String, String> {
+    if prompt.trim().is_empty() {
+        return Err("A scheduled prompt cannot be empty".to_string());
+    }
+    if !std::path::Path::new(project_path).is_dir() {
+        return Err(format!("Project directory {} does not exist", project_path));
+    }
+    super::cost_cap::validate(max_cost_usd)?;
+    let at = DateTime::parse_from_rfc3339(run_at)
+        .map_err(|e| format!("Invalid run_at '{}': {}", run_at, e))?
+        .with_timezone(&Utc);
+    if at < Utc::now() - Duration::seconds(RUN_AT_SLACK_SECS) {
+        return Err(format!("run_at {} is in the past", run_at));
+    }
+    Ok(at.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+/// Record how a started prompt ended and announce it
+fn finish(
+    app: &AppHandle,
+    conn: &Connection,
+    id: i64,
+    status: ScheduledPromptStatus,
+    session_id: Option<&str>,
+    error: Option<&str>,
+) {
+    let updated = conn.execute(
+        "UPDATE scheduled_prompts
+         SET status = ?2, session_id = COALESCE(?3, session_id), error = ?4, finished_at = ?5
+         WHERE id = ?1",
+        params![id, status.as_str(), session_id, error, Utc::now().to_rfc3339()],
+    );
+    if let Err(e) = updated {
+        log::warn!("Failed to record the outcome of scheduled prompt {}: {}", id, e);
+        return;
+    }
+    log::info!("Scheduled prompt {} finished: {}", id, status.as_str());
+    if let Ok(prompt) = load_prompt(conn, id) {
+        let _ = AppEvent::ScheduledPromptFinished(prompt).emit(app);
+    }
+}
+
+/// Move started prompts whose runs have ended to their final status
+fn track_running(app: &AppHandle, conn: &Connection) {
+    let Ok(running) = prompts_with_status(conn, ScheduledPromptStatus::Running) else {
+        return;
+    };
+    for prompt in running {
+        let Some(run_id) = prompt.run_id else {
+            // Still starting, unless the app closed while it was
+            let stalled = prompt
+                .started_at
+                .as_deref()
+                .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                .map_or(true, |at| at < Utc::now() - Duration::seconds(LAUNCH_TIMEOUT_SECS));
+            if stalled {
+                let reason = "The run never started";
+                finish(app, conn, prompt.id, ScheduledPromptStatus::Failed, None, Some(reason));
+            }
+            continue;
+        };
+        let Some(run) = super::run_status::get(run_id) else {
+            let reason = "The app closed before the run finished";
+            finish(app, conn, prompt.id, ScheduledPromptStatus::Failed, None, Some(reason));
+            continue;
+        };
+        let status = match run.state {
+            RunState::Starting | RunState::Running => continue,
+            RunState::Completed => ScheduledPromptStatus::Completed,
+            RunState::Failed => ScheduledPromptStatus::Failed,
+            RunState::Cancelled => ScheduledPromptStatus::Cancelled,
+            RunState::CostCapReached => ScheduledPromptStatus::CostCapReached,
+        };
+        let error = (status == ScheduledPromptStatus::Failed).then_some("The run failed");
+        finish(app, conn, prompt.id, status, run.session_id.as_deref(), error);
+    }
+}
+
+/// Start one due prompt, unless its estimated cost is already over its cap
+async fn launch(app: &AppHandle, prompt: ScheduledPrompt) {
+    {
+        let db = app.state::<AgentDb>();
+        let Ok(conn) = db.0.get() else {
+            return;
+        };
+        // Claim it first, so a slow launch isn't started again on the next tick
+        let claimed = conn
+            .execute(
+                "UPDATE scheduled_prompts SET status = 'running', started_at = ?2
+                 WHERE id = ?1 AND status = 'pending'",
+                params![prompt.id, Utc::now().to_rfc3339()],
+            )
+            .unwrap_or(0);
+        if claimed == 0 {
+            return;
+        }
+    }
+
+    if let Some(cap) = prompt.max_cost_usd {
+        let estimate = super::prompt_cost::estimate_prompt_cost(
+            app.clone(),
+            prompt.project_path.clone(),
+            prompt.prompt.clone(),
+            prompt.model.clone(),
+            None,
+        )
+        .await;
+        let skip_reason = match estimate {
+            Ok(estimate) if estimate.min_cost_usd > cap => Some(format!(
+                "Skipped: the estimated cost ${:.2} exceeds the cap of ${:.2}",
+                estimate.min_cost_usd, cap
+            )),
+            Ok(_) => None,
+            Err(e) => Some(format!("Skipped: the cost could not be estimated: {}", e)),
+        };
+        if let Some(reason) = skip_reason {
+            let db = app.state::<AgentDb>();
+            if let Ok(conn) = db.0.get() {
+                let status = ScheduledPromptStatus::Skipped;
+                finish(app, &conn, prompt.id, status, None, Some(&reason));
+            }
+            return;
+        }
+    }
+
+    log::info!("Starting scheduled prompt {} in {}", prompt.id, prompt.project_path);
+    let result = super::claude::execute_claude_code_with(
+        app.clone(),
+        prompt.project_path.clone(),
+        prompt.prompt.clone(),
+        prompt.model.clone(),
+        None,
+        super::claude::RunOverrides {
+            max_cost_usd: prompt.max_cost_usd,
+            ..super::claude::RunOverrides::default()
+        },
+    )
+    .await;
+
+    let db = app.state::<AgentDb>();
+    let Ok(conn) = db.0.get() else {
+        return;
+    };
+    match result {
+        Ok(run_id) => {
+            let _ = conn.execute(
+                "UPDATE scheduled_prompts SET run_id = ?2 WHERE id = ?1",
+                params![prompt.id, run_id],
+            );
+            if let Ok(started) = load_prompt(&conn, prompt.id) {
+                let _ = AppEvent::ScheduledPromptStarted(started).emit(app);
+            }
+        }
+        Err(e) => {
+            log::warn!("Scheduled prompt {} failed to start: {}", prompt.id, e);
+            finish(app, &conn, prompt.id, ScheduledPromptStatus::Failed, None, Some(&e));
+        }
+    }
+}
+
+/// Called on every scheduler tick: record finished runs, then start the prompts that
+/// are due. A prompt that came due while the app was closed starts at the next launch.
+pub async fn dispatch_scheduled_prompts(app: &AppHandle) {
+    let due = {
+        let db = app.state::<AgentDb>();
+        let Ok(conn) = db.0.get() else {
+            return;
+        };
+        track_running(app, &conn);
+        let now = Utc::now();
+        prompts_with_status(&conn, ScheduledPromptStatus::Pending)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|prompt| {
+                DateTime::parse_from_rfc3339(&prompt.run_at).map_or(false, |at| at <= now)
+            })
+            .collect::<Vec<_>>()
+    };
+    for prompt in due {
+        launch(app, prompt).await;
+    }
+}
+
+/// Run a prompt once, in a new session, at `run_at` (RFC 3339)
+#[tauri::command]
+pub async fn schedule_prompt(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    prompt: String,
+    run_at: String,
+    model: String,
+    max_cost_usd: Option<f64>,
+) -> Result<ScheduledPrompt, String> {
+    let run_at = validate(&project_path, &prompt, &run_at, max_cost_usd)?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO scheduled_prompts (project_path, prompt, model, run_at, max_cost_usd)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![project_path, prompt, model, run_at, max_cost_usd],
+    )
+    .map_err(|e| e.to_string())?;
+    let id = conn.last_insert_rowid();
+    log::info!("Scheduled prompt {} for {} in {}", id, run_at, project_path);
+    load_prompt(&conn, id)
+}
+
+/// Replace a pending prompt's settings
+#[tauri::command]
+pub async fn update_scheduled_prompt(
+    db: State<'_, AgentDb>,
+    id: i64,
+    project_path: String,
+    prompt: String,
+    run_at: String,
+    model: String,
+    max_cost_usd: Option<f64>,
+) -> Result<ScheduledPrompt, String> {
+    let run_at = validate(&project_path, &prompt, &run_at, max_cost_usd)?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let updated = conn
+        .execute(
+            "UPDATE scheduled_prompts
+             SET project_path = ?2, prompt = ?3, model = ?4, run_at = ?5, max_cost_usd = ?6
+             WHERE id = ?1 AND status = 'pending'",
+            params![id, project_path, prompt, model, run_at, max_cost_usd],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        load_prompt(&conn, id)?;
+        return Err(format!("Scheduled prompt {} has already started", id));
+    }
+    load_prompt(&conn, id)
+}
+
+/// Cancel a pending prompt; one that already started is cancelled like any other run
+#[tauri::command]
+pub async fn cancel_scheduled_prompt(
+    db: State<'_, AgentDb>,
+    id: i64,
+) -> Result<ScheduledPrompt, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let cancelled = conn
+        .execute(
+            "UPDATE scheduled_prompts SET status = 'cancelled', finished_at = ?2
+             WHERE id = ?1 AND status = 'pending'",
+            params![id, Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| e.to_string())?;
+    if cancelled == 0 {
+        load_prompt(&conn, id)?;
+        return Err(format!("Scheduled prompt {} has already started", id));
+    }
+    load_prompt(&conn, id)
+}
+
+/// Scheduled prompts by run time; finished ones only when `include_finished` is set
+#[tauri::command]
+pub async fn list_scheduled_prompts(
+    db: State<'_, AgentDb>,
+    include_finished: Option<bool>,
+) -> Result<Vec<ScheduledPrompt>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM scheduled_prompts
+             WHERE ?1 OR status IN ('pending', 'running')
+             ORDER BY run_at, id",
+            PROMPT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![include_finished.unwrap_or(false)], row_to_prompt)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
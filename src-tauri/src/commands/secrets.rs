@@ -0,0 +1,54 @@
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// File in the app data dir holding the secrets map
+const SECRETS_FILE: &str = "secrets.json";
+
+/// Serializes read-modify-write cycles on the secrets file
+static SECRETS_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+fn secrets_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    Ok(dir.join(SECRETS_FILE))
+}
+
+fn read_secrets(path: &PathBuf) -> Result<BTreeMap<String, String>, String> {
+    match crate::config_file::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| format!("Failed to parse secrets file: {}", e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(format!("Failed to read secrets file: {}", e)),
+    }
+}
+
+fn write_secrets(path: &PathBuf, secrets: &BTreeMap<String, String>) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(secrets).map_err(|e| e.to_string())?;
+    // Only the current user may read the file
+    crate::config_file::write_private(path, content)
+        .map_err(|e| format!("Failed to write secrets file: {}", e))
+}
+
+/// Read a stored secret
+pub fn get_secret(app: &AppHandle, key: &str) -> Result<Option<String>, String> {
+    let _guard = SECRETS_LOCK.lock().map_err(|e| e.to_string())?;
+    Ok(read_secrets(&secrets_path(app)?)?.remove(key))
+}
+
+/// Store a secret, or remove it with `None`
+pub fn set_secret(app: &AppHandle, key: &str, value: Option<&str>) -> Result<(), String> {
+    let _guard = SECRETS_LOCK.lock().map_err(|e| e.to_string())?;
+    let path = secrets_path(app)?;
+    let mut secrets = read_secrets(&path)?;
+    match value {
+        Some(value) => secrets.insert(key.to_string(), value.to_string()),
+        None => secrets.remove(key),
+    };
+    write_secrets(&path, &secrets)
+}
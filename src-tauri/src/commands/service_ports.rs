@@ -0,0 +1,291 @@
+//! Ports of the services the workbench talks to on this machine: claude-code-router
+//! and the local API.
+//!
+//! When another app holds a service's port, the service is moved to the next free
+//! one and the move is stored under `SERVICE_PORTS`. The router's new port is
+//! written to its config.json, which `router_usage::router_address` and the health
+//! probe read, and runs whose `ANTHROPIC_BASE_URL` still names the old port are
+//! sent to the new one.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::net::TcpListener;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use super::agents::AgentDb;
+use super::settings_store::{self, SERVICE_PORTS};
+
+/// How many ports after the preferred one are tried before asking the OS for any
+const PORT_SEARCH_RANGE: u16 = 20;
+
+/// How long the router gets to answer its health check
+const ROUTER_HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Ports services were moved from after a conflict, kept so URLs naming the old
+/// port keep working
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PortMoves {
+    /// (from, to) of claude-code-router
+    pub router: Option<(u16, u16)>,
+    /// (from, to) of the local API
+    pub local_api: Option<(u16, u16)>,
+}
+
+/// What is bound to a service's port
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortState {
+    /// The service itself is listening
+    Listening,
+    /// Nothing is bound; the service is not running
+    Free,
+    /// Another app holds the port
+    Conflict,
+    /// A router on another host does not answer
+    Unreachable,
+}
+
+/// Returned by `get_service_ports`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServicePort {
+    /// "router" or "local_api"
+    pub service: String,
+    pub host: String,
+    pub port: u16,
+    pub state: PortState,
+    /// Port the service used before it was moved off a conflict
+    pub moved_from: Option<u16>,
+}
+
+static MOVES: Lazy<Mutex<PortMoves>> = Lazy::new(|| Mutex::new(PortMoves::default()));
+
+/// Whether nothing on this machine listens on `port`
+pub fn is_free(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+/// `preferred` if it is free, else the first free port after it, else any port the
+/// OS hands out. Ports in `avoid` belong to other services and are skipped.
+fn pick_port(preferred: u16, avoid: &[u16], is_free: impl Fn(u16) -> bool) -> Option<u16> {
+    let last = preferred.saturating_add(PORT_SEARCH_RANGE);
+    (preferred..=last)
+        .filter(|port| *port != 0 && !avoid.contains(port))
+        .find(|port| is_free(*port))
+}
+
+/// A free port for a service that prefers `preferred`
+pub fn choose_port(preferred: u16, avoid: &[u16]) -> Result<u16, String> {
+    if let Some(port) = pick_port(preferred, avoid, is_free) {
+        return Ok(port);
+    }
+    let listener = TcpListener::bind("127.0.0.1:0").map_err(|e| e.to_string())?;
+    listener
+        .local_addr()
+        .map(|addr| addr.port())
+        .map_err(|e| e.to_string())
+}
+
+fn is_loopback(host: &str) -> bool {
+    matches!(host, "localhost" | "127.0.0.1" | "::1" | "[::1]")
+}
+
+/// `url` with its port replaced when it is a loopback URL on the `from` port
+fn redirect_url(url: &str, (from, to): (u16, u16)) -> Option<String> {
+    let mut parsed = reqwest::Url::parse(url).ok()?;
+    if !parsed.host_str().map_or(false, is_loopback) || parsed.port() != Some(from) {
+        return None;
+    }
+    parsed.set_port(Some(to)).ok()?;
+    let redirected = parsed.to_string();
+    // Url adds a trailing slash to a bare origin
+    if !url.ends_with('/') && parsed.path() == "/" {
+        return Some(redirected.trim_end_matches('/').to_string());
+    }
+    Some(redirected)
+}
+
+/// The router URL a run should use instead of `url`, if the router was moved off
+/// the port `url` names
+pub fn redirect_router_url(url: &str) -> Option<String> {
+    let moved = MOVES.lock().ok()?.router?;
+    redirect_url(url, moved)
+}
+
+fn record_move(app: &AppHandle, modify: impl FnOnce(&mut PortMoves)) {
+    let result = settings_store::update(app, SERVICE_PORTS, |moves: &mut PortMoves| {
+        modify(moves);
+        Ok(true)
+    });
+    match result {
+        Ok(moves) => {
+            if let Ok(mut current) = MOVES.lock() {
+                *current = moves;
+            }
+        }
+        Err(e) => log::warn!("Failed to save service port move: {}", e),
+    }
+}
+
+/// Record that the local API moved from `from` to `to`
+pub fn record_local_api_move(app: &AppHandle, from: u16, to: u16) {
+    record_move(app, |moves| {
+        // Keep the first port so URLs naming it still map to the latest one
+        let original = moves.local_api.map_or(from, |(first, _)| first);
+        moves.local_api = (original != to).then_some((original, to));
+    });
+}
+
+/// Whether claude-code-router answers its health check on `host:port`
+async fn router_healthy(host: &str, port: u16) -> bool {
+    let Ok(client) = reqwest::Client::builder()
+        .timeout(ROUTER_HEALTH_TIMEOUT)
+        .build()
+    else {
+        return false;
+    };
+    client
+        .get(format!("http://{}:{}/health", host, port))
+        .send()
+        .await
+        .map_or(false, |response| response.status().is_success())
+}
+
+/// Write the router's new port to its config.json, if the router is set up
+fn write_router_port(port: u16) -> Result<bool, String> {
+    let Some(path) = super::router_usage::router_dir().map(|dir| dir.join("config.json")) else {
+        return Ok(false);
+    };
+    if !path.is_file() {
+        return Ok(false);
+    }
+    crate::config_file::update_json(&path, |config| {
+        config["PORT"] = serde_json::json!(port);
+        Ok(())
+    })?;
+    Ok(true)
+}
+
+/// Move the router off its port when another app holds it. The router has to be
+/// stopped for that to happen, so it starts on the new port next time.
+async fn resolve_router_port(app: &AppHandle) -> Result<(), String> {
+    let (host, port) = super::router_usage::router_address();
+    if !is_loopback(&host) || is_free(port) || router_healthy(&host, port).await {
+        return Ok(());
+    }
+    let local_api_port = super::local_api::configured_port(app);
+    let new_port = choose_port(port.saturating_add(1), &[local_api_port])?;
+    if !write_router_port(new_port)? {
+        return Ok(());
+    }
+    log::warn!(
+        "Port {} is taken by another app; claude-code-router will use port {}",
+        port,
+        new_port
+    );
+    record_move(app, |moves| {
+        let original = moves.router.map_or(port, |(first, _)| first);
+        moves.router = (original != new_port).then_some((original, new_port));
+    });
+    Ok(())
+}
+
+/// Load past port moves and move the router off a port another app took; needs the
+/// database, so run it after it opens
+pub async fn init_service_ports(app: AppHandle) {
+    let stored = app
+        .state::<AgentDb>()
+        .0
+        .get()
+        .map(|conn| settings_store::get_or_default::<PortMoves>(&conn, SERVICE_PORTS));
+    if let (Ok(stored), Ok(mut moves)) = (stored, MOVES.lock()) {
+        *moves = stored;
+    }
+    if !super::startup::is_enabled(super::startup::Subsystem::Router) {
+        return;
+    }
+    if let Err(e) = resolve_router_port(&app).await {
+        log::warn!("Failed to check the router port: {}", e);
+    }
+}
+
+/// Where claude-code-router and the local API are bound, and whether another app
+/// holds either port
+#[tauri::command]
+pub async fn get_service_ports(app: AppHandle) -> Result<Vec<ServicePort>, String> {
+    let moves = MOVES.lock().map_err(|e| e.to_string())?.clone();
+    let mut ports = Vec::new();
+
+    let (host, port) = super::router_usage::router_address();
+    let state = if router_healthy(&host, port).await {
+        PortState::Listening
+    } else if !is_loopback(&host) {
+        PortState::Unreachable
+    } else if is_free(port) {
+        PortState::Free
+    } else {
+        PortState::Conflict
+    };
+    ports.push(ServicePort {
+        service: "router".to_string(),
+        host,
+        port,
+        state,
+        moved_from: moves
+            .router
+            .filter(|(_, to)| *to == port)
+            .map(|(from, _)| from),
+    });
+
+    let port = super::local_api::configured_port(&app);
+    let state = if super::local_api::running_port() == Some(port) {
+        PortState::Listening
+    } else if is_free(port) {
+        PortState::Free
+    } else {
+        PortState::Conflict
+    };
+    ports.push(ServicePort {
+        service: "local_api".to_string(),
+        host: "127.0.0.1".to_string(),
+        port,
+        state,
+        moved_from: moves
+            .local_api
+            .filter(|(_, to)| *to == port)
+            .map(|(from, _)| from),
+    });
+    Ok(ports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_port_skips_taken_and_avoided_ports() {
+        let taken = [3456, 3457];
+        let free = |port: u16| !taken.contains(&port);
+        assert_eq!(pick_port(3460, &[], free), Some(3460));
+        assert_eq!(pick_port(3456, &[], free), Some(3458));
+        assert_eq!(pick_port(3456, &[3458], free), Some(3459));
+        assert_eq!(pick_port(3456, &[], |_| false), None);
+    }
+
+    #[test]
+    fn test_redirect_url_only_rewrites_the_moved_loopback_port() {
+        let moved = (3456, 3457);
+        assert_eq!(
+            redirect_url("http://127.0.0.1:3456", moved).as_deref(),
+            Some("http://127.0.0.1:3457")
+        );
+        assert_eq!(
+            redirect_url("http://localhost:3456/v1/", moved).as_deref(),
+            Some("http://localhost:3457/v1/")
+        );
+        assert_eq!(redirect_url("http://127.0.0.1:3460", moved), None);
+        assert_eq!(redirect_url("https://api.example.com:3456", moved), None);
+    }
+}
@@ -0,0 +1,474 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
+
+use super::session_handoff::{content_text, is_user_prompt, truncate_chars};
+use super::session_meta::find_session_file;
+
+/// Messages rendered by `get_export_preview`
+const PREVIEW_MESSAGES: usize = 6;
+
+/// Characters of each message shown in a preview
+const PREVIEW_MESSAGE_CHARS: usize = 600;
+
+/// Lines of a single tool call's diff kept in an export
+const MAX_DIFF_LINES: usize = 400;
+
+/// Characters of a tool call's input shown when it has no better summary
+const TOOL_INPUT_CHARS: usize = 300;
+
+/// Color variables of the light theme
+const LIGHT_VARS: &str = "--bg:#ffffff;--fg:#1f2328;--muted:#656d76;--border:#d0d7de;\
+    --user-bg:#f6f8fa;--assistant-bg:#ffffff;--accent:#0969da;--code-bg:#f6f8fa;\
+    --add:#1a7f37;--del:#cf222e";
+
+/// Color variables of the dark theme
+const DARK_VARS: &str = "--bg:#0d1117;--fg:#e6edf3;--muted:#8d96a0;--border:#30363d;\
+    --user-bg:#161b22;--assistant-bg:#0d1117;--accent:#4493f8;--code-bg:#161b22;\
+    --add:#3fb950;--del:#f85149";
+
+/// Styles shared by both themes, written against the color variables
+const BASE_CSS: &str = "body{background:var(--bg);color:var(--fg);font-family:-apple-system,\
+    'Segoe UI',sans-serif;max-width:900px;margin:2em auto;padding:0 1em;line-height:1.5}\
+    h1,h2{border-bottom:1px solid var(--border);padding-bottom:.3em}\
+    a{color:var(--accent)}table{border-collapse:collapse;width:100%}\
+    th,td{border:1px solid var(--border);padding:4px 8px;text-align:left}\
+    pre,code{background:var(--code-bg);border-radius:4px}\
+    .message{border:1px solid var(--border);border-radius:6px;margin:1em 0;padding:.75em 1em}\
+    .message.user{background:var(--user-bg)}.message.assistant{background:var(--assistant-bg)}\
+    .role{font-weight:600;font-size:.85em;text-transform:uppercase;color:var(--muted)}\
+    .time,.tools{font-size:.8em;color:var(--muted)}.text{white-space:pre-wrap;margin:.5em 0}\
+    .tool{margin:.5em 0}.tool-name{font-weight:600}\
+    pre.diff{padding:.5em;overflow-x:auto;font-size:.85em}\
+    .diff .add{color:var(--add)}.diff .del{color:var(--del)}";
+
+/// Color theme of an HTML export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportTheme {
+    Light,
+    Dark,
+    /// Both variants, chosen by the viewer's `prefers-color-scheme`
+    #[default]
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Html,
+    Markdown,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Html => "html",
+            ExportFormat::Markdown => "md",
+        }
+    }
+}
+
+/// Returned by `get_export_preview`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportPreview {
+    pub format: ExportFormat,
+    pub theme: ExportTheme,
+    /// Rendered document covering the first messages only
+    pub content: String,
+    pub total_messages: usize,
+    pub truncated: bool,
+}
+
+/// A tool the assistant called, with what it was called on
+struct ToolCall {
+    name: String,
+    /// Command, path or pattern the call worked on
+    detail: Option<String>,
+    /// Line diff of a file edit, each line prefixed with `+`, `-` or a space
+    diff: Option<String>,
+}
+
+/// One user prompt or assistant reply of a transcript
+struct TranscriptMessage {
+    role: &'static str,
+    text: String,
+    tools: Vec<ToolCall>,
+    timestamp: Option<String>,
+}
+
+/// Totals shown at the top of an export
+#[derive(Default)]
+struct TranscriptSummary {
+    started_at: Option<String>,
+    ended_at: Option<String>,
+    prompts: usize,
+    tool_calls: usize,
+    models: BTreeSet<String>,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+}
+
+struct Transcript {
+    messages: Vec<TranscriptMessage>,
+    summary: TranscriptSummary,
+}
+
+pub fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Stylesheet for a theme; `Auto` bundles both variants behind a media query
+pub fn theme_css(theme: ExportTheme) -> String {
+    let vars = match theme {
+        ExportTheme::Light => format!(":root{{{}}}", LIGHT_VARS),
+        ExportTheme::Dark => format!(":root{{{}}}", DARK_VARS),
+        ExportTheme::Auto => format!(
+            ":root{{{}}}@media (prefers-color-scheme: dark){{:root{{{}}}}}",
+            LIGHT_VARS, DARK_VARS
+        ),
+    };
+    format!("{}{}", vars, BASE_CSS)
+}
+
+/// Standalone HTML document with the theme's stylesheet inlined
+pub fn html_document(title: &str, body: &str, theme: ExportTheme) -> String {
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{}</title><style>{}</style></head><body>{}</body></html>",
+        html_escape(title),
+        theme_css(theme),
+        body
+    )
+}
+
+/// Removed lines of `old` followed by added lines of `new`
+fn push_diff_lines(diff: &mut Vec<String>, old: &str, new: &str) {
+    diff.extend(old.lines().map(|line| format!("-{}", line)));
+    diff.extend(new.lines().map(|line| format!("+{}", line)));
+}
+
+/// Diff of a file-editing tool call, cut to `MAX_DIFF_LINES`
+fn tool_diff(name: &str, input: &serde_json::Value) -> Option<String> {
+    let text = |value: &serde_json::Value, key: &str| {
+        value.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string()
+    };
+    let mut diff = Vec::new();
+    match name {
+        "Edit" => push_diff_lines(&mut diff, &text(input, "old_string"), &text(input, "new_string")),
+        "MultiEdit" => {
+            for edit in input.get("edits").and_then(|e| e.as_array()).into_iter().flatten() {
+                if !diff.is_empty() {
+                    diff.push(" …".to_string());
+                }
+                push_diff_lines(&mut diff, &text(edit, "old_string"), &text(edit, "new_string"));
+            }
+        }
+        "Write" => push_diff_lines(&mut diff, "", &text(input, "content")),
+        _ => return None,
+    }
+    if diff.is_empty() {
+        return None;
+    }
+    if diff.len() > MAX_DIFF_LINES {
+        let omitted = diff.len() - MAX_DIFF_LINES;
+        diff.truncate(MAX_DIFF_LINES);
+        diff.push(format!(" … {} more lines", omitted));
+    }
+    Some(diff.join("\n"))
+}
+
+/// What a tool call worked on: its command, path or pattern, else its raw input
+fn tool_detail(input: &serde_json::Value) -> Option<String> {
+    ["command", "file_path", "notebook_path", "path", "pattern", "url", "query", "description"]
+        .iter()
+        .find_map(|key| input.get(*key).and_then(|v| v.as_str()))
+        .map(|detail| truncate_chars(detail, TOOL_INPUT_CHARS))
+        .or_else(|| {
+            let raw = input.to_string();
+            (raw != "{}" && raw != "null").then(|| truncate_chars(&raw, TOOL_INPUT_CHARS))
+        })
+}
+
+fn read_transcript(path: &Path) -> Result<Transcript, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open session file: {}", e))?;
+    let mut messages = Vec::new();
+    let mut summary = TranscriptSummary::default();
+    // The CLI writes one line per content block, each repeating the message's usage
+    let mut counted_usage = HashSet::new();
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        let timestamp = entry.get("timestamp").and_then(|t| t.as_str()).map(str::to_string);
+        if let Some(timestamp) = &timestamp {
+            summary.started_at.get_or_insert_with(|| timestamp.clone());
+            summary.ended_at = Some(timestamp.clone());
+        }
+        let message = &entry["message"];
+        let content = &message["content"];
+
+        match entry.get("type").and_then(|t| t.as_str()) {
+            Some("user") if is_user_prompt(content) => {
+                summary.prompts += 1;
+                messages.push(TranscriptMessage {
+                    role: "user",
+                    text: content_text(content),
+                    tools: Vec::new(),
+                    timestamp,
+                })
+            }
+            Some("assistant") => {
+                if let Some(model) = message.get("model").and_then(|m| m.as_str()) {
+                    if model != "<synthetic>" {
+                        summary.models.insert(model.to_string());
+                    }
+                }
+                let usage_key = message.get("id").and_then(|id| id.as_str()).map(str::to_string);
+                if usage_key.map_or(true, |key| counted_usage.insert(key)) {
+                    let usage = &message["usage"];
+                    let tokens = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+                    summary.input_tokens += tokens("input_tokens");
+                    summary.output_tokens += tokens("output_tokens");
+                    summary.cache_creation_tokens += tokens("cache_creation_input_tokens");
+                    summary.cache_read_tokens += tokens("cache_read_input_tokens");
+                }
+
+                let tools: Vec<ToolCall> = content
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+                    .filter_map(|b| {
+                        let name = b.get("name").and_then(|n| n.as_str())?;
+                        let input = &b["input"];
+                        Some(ToolCall {
+                            name: name.to_string(),
+                            detail: tool_detail(input),
+                            diff: tool_diff(name, input),
+                        })
+                    })
+                    .collect();
+                summary.tool_calls += tools.len();
+                let text = content_text(content);
+                if text.trim().is_empty() && tools.is_empty() {
+                    continue;
+                }
+                messages.push(TranscriptMessage {
+                    role: "assistant",
+                    text,
+                    tools,
+                    timestamp,
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(Transcript { messages, summary })
+}
+
+/// Summary rows as (label, value)
+fn summary_rows(summary: &TranscriptSummary) -> Vec<(&'static str, String)> {
+    let mut rows = Vec::new();
+    if let Some(started_at) = &summary.started_at {
+        rows.push(("Started", started_at.clone()));
+    }
+    if let Some(ended_at) = &summary.ended_at {
+        rows.push(("Ended", ended_at.clone()));
+    }
+    if !summary.models.is_empty() {
+        rows.push(("Models", summary.models.iter().cloned().collect::<Vec<_>>().join(", ")));
+    }
+    rows.push(("Prompts", summary.prompts.to_string()));
+    rows.push(("Tool calls", summary.tool_calls.to_string()));
+    rows.push(("Input tokens", summary.input_tokens.to_string()));
+    rows.push(("Output tokens", summary.output_tokens.to_string()));
+    rows.push(("Cache write tokens", summary.cache_creation_tokens.to_string()));
+    rows.push(("Cache read tokens", summary.cache_read_tokens.to_string()));
+    rows
+}
+
+fn render_markdown(session_id: &str, transcript: &Transcript) -> String {
+    let mut md = format!("# Session {}\n\n", session_id);
+    md.push_str("| | |\n|---|---|\n");
+    for (label, value) in summary_rows(&transcript.summary) {
+        md.push_str(&format!("| {} | {} |\n", label, value.replace('|', "\\|")));
+    }
+    md.push('\n');
+
+    for message in &transcript.messages {
+        let role = if message.role == "user" { "User" } else { "Assistant" };
+        md.push_str(&format!("## {}\n\n", role));
+        if let Some(timestamp) = &message.timestamp {
+            md.push_str(&format!("_{}_\n\n", timestamp));
+        }
+        if !message.text.trim().is_empty() {
+            md.push_str(message.text.trim());
+            md.push_str("\n\n");
+        }
+        for tool in &message.tools {
+            match &tool.detail {
+                Some(detail) => {
+                    md.push_str(&format!("**{}** `{}`\n\n", tool.name, detail.replace('`', "'")))
+                }
+                None => md.push_str(&format!("**{}**\n\n", tool.name)),
+            }
+            if let Some(diff) = &tool.diff {
+                // A fence longer than any backtick run in the diff can't be closed early
+                let longest_run = diff.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+                let fence = "`".repeat(longest_run.max(2) + 1);
+                md.push_str(&format!("{}diff\n{}\n{}\n\n", fence, diff, fence));
+            }
+        }
+    }
+    md
+}
+
+fn render_html(session_id: &str, transcript: &Transcript, theme: ExportTheme) -> String {
+    let mut body = format!("<h1>Session {}</h1><table>", html_escape(session_id));
+    for (label, value) in summary_rows(&transcript.summary) {
+        body.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>", label, html_escape(&value)));
+    }
+    body.push_str("</table>");
+
+    for message in &transcript.messages {
+        body.push_str(&format!(
+            "<div class=\"message {}\"><div class=\"role\">{}</div>",
+            message.role, message.role
+        ));
+        if let Some(timestamp) = &message.timestamp {
+            body.push_str(&format!("<div class=\"time\">{}</div>", html_escape(timestamp)));
+        }
+        if !message.text.trim().is_empty() {
+            body.push_str(&format!("<div class=\"text\">{}</div>", html_escape(message.text.trim())));
+        }
+        for tool in &message.tools {
+            body.push_str(&format!(
+                "<div class=\"tool\"><span class=\"tool-name\">{}</span>",
+                html_escape(&tool.name)
+            ));
+            if let Some(detail) = &tool.detail {
+                body.push_str(&format!(" <code>{}</code>", html_escape(detail)));
+            }
+            if let Some(diff) = &tool.diff {
+                body.push_str("<pre class=\"diff\">");
+                for line in diff.lines() {
+                    let class = match line.chars().next() {
+                        Some('+') => "add",
+                        Some('-') => "del",
+                        _ => "ctx",
+                    };
+                    body.push_str(&format!("<span class=\"{}\">{}</span>\n", class, html_escape(line)));
+                }
+                body.push_str("</pre>");
+            }
+            body.push_str("</div>");
+        }
+        body.push_str("</div>");
+    }
+    html_document(&format!("Session {}", session_id), &body, theme)
+}
+
+fn render(session_id: &str, transcript: &Transcript, format: ExportFormat, theme: ExportTheme) -> String {
+    match format {
+        ExportFormat::Html => render_html(session_id, transcript, theme),
+        ExportFormat::Markdown => render_markdown(session_id, transcript),
+    }
+}
+
+fn load_transcript(session_id: &str) -> Result<Transcript, String> {
+    let (_, path) = find_session_file(session_id, None)?
+        .ok_or_else(|| format!("Session {} not found", session_id))?;
+    read_transcript(&path)
+}
+
+/// Ask where to save an export; None if the dialog was cancelled
+async fn choose_output_path(
+    app: &AppHandle,
+    session_id: &str,
+    format: ExportFormat,
+) -> Result<Option<String>, String> {
+    let filter = match format {
+        ExportFormat::Html => "HTML",
+        ExportFormat::Markdown => "Markdown",
+    };
+    let extension = format.extension();
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .file()
+        .add_filter(filter, &[extension])
+        .set_file_name(format!("session-{}.{}", session_id, extension))
+        .save_file(move |path| {
+            let _ = tx.send(path);
+        });
+    match rx.await.map_err(|e| e.to_string())? {
+        Some(path) => path
+            .into_path()
+            .map(|path| Some(path.to_string_lossy().into_owned()))
+            .map_err(|e| format!("Invalid export path: {}", e)),
+        None => Ok(None),
+    }
+}
+
+/// Export a session transcript, with tool calls, edit diffs and a token usage summary,
+/// as a self-contained HTML (themed) or Markdown file; without `output_path` the user
+/// picks one in a save dialog. Returns the written path.
+#[tauri::command]
+pub async fn export_session(
+    app: AppHandle,
+    session_id: String,
+    format: ExportFormat,
+    theme: Option<ExportTheme>,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let transcript = load_transcript(&session_id)?;
+    let output_path = match output_path {
+        Some(path) => path,
+        None => choose_output_path(&app, &session_id, format)
+            .await?
+            .ok_or_else(|| "Export cancelled".to_string())?,
+    };
+    let content = render(&session_id, &transcript, format, theme.unwrap_or_default());
+    fs::write(&output_path, content).map_err(|e| format!("Failed to write export: {}", e))?;
+    log::info!("Exported session {} to {}", session_id, output_path);
+    Ok(output_path)
+}
+
+/// Render the first messages of a session export so the format and theme can be
+/// checked before the full file is written
+#[tauri::command]
+pub async fn get_export_preview(
+    session_id: String,
+    format: ExportFormat,
+    theme: Option<ExportTheme>,
+) -> Result<ExportPreview, String> {
+    let theme = theme.unwrap_or_default();
+    let transcript = load_transcript(&session_id)?;
+    let total_messages = transcript.messages.len();
+    let preview = Transcript {
+        messages: transcript
+            .messages
+            .into_iter()
+            .take(PREVIEW_MESSAGES)
+            .map(|message| TranscriptMessage {
+                text: truncate_chars(&message.text, PREVIEW_MESSAGE_CHARS),
+                ..message
+            })
+            .collect(),
+        summary: transcript.summary,
+    };
+
+    Ok(ExportPreview {
+        format,
+        theme,
+        content: render(&session_id, &preview, format, theme),
+        total_messages,
+        truncated: total_messages > PREVIEW_MESSAGES,
+    })
+}
@@ -0,0 +1,397 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::enhanced_hooks::{spawn_hook_event, HookContext, HookEvent};
+use super::session_meta::find_session_file;
+
+/// File written into the project root by the built-in handoff action
+pub const HANDOFF_FILE_NAME: &str = "HANDOFF.md";
+
+/// Maximum number of items listed per handoff section
+const MAX_SECTION_ITEMS: usize = 10;
+
+/// Summary and statistics of a finished session, passed to OnSessionEnd hooks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionEndSummary {
+    pub session_id: String,
+    pub project_path: String,
+    pub success: bool,
+    /// First user prompt of the session, i.e. the task that was asked for
+    pub task: Option<String>,
+    /// Short generated summary of what was done
+    pub summary: String,
+    pub user_messages: usize,
+    pub assistant_messages: usize,
+    /// Tool name -> number of invocations
+    pub tool_uses: BTreeMap<String, usize>,
+    /// Files touched by Edit/Write/MultiEdit/NotebookEdit tools
+    pub files_modified: Vec<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_cost_usd: Option<f64>,
+    pub duration_ms: Option<u64>,
+    pub open_questions: Vec<String>,
+    pub next_steps: Vec<String>,
+}
+
+/// Extract plain text from a message `content` field (string or content blocks)
+pub fn content_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Whether a user message is a real prompt rather than a tool result
+pub fn is_user_prompt(content: &serde_json::Value) -> bool {
+    match content {
+        serde_json::Value::String(text) => !text.trim().is_empty(),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .any(|b| b.get("type").and_then(|t| t.as_str()) == Some("text")),
+        _ => false,
+    }
+}
+
+/// Trim and cut text to `max` characters, marking the cut with an ellipsis
+pub fn truncate_chars(text: &str, max: usize) -> String {
+    let trimmed = text.trim();
+    if trimmed.chars().count() <= max {
+        trimmed.to_string()
+    } else {
+        format!("{}…", trimmed.chars().take(max).collect::<String>())
+    }
+}
+
+/// Strip list markers ("- ", "* ", "1. ") from a line
+fn strip_list_marker(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if let Some(rest) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some(rest.trim());
+    }
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        if let Some(rest) = line[digits..].strip_prefix(". ") {
+            return Some(rest.trim());
+        }
+    }
+    None
+}
+
+/// Collect questions and "next steps" list items from the final assistant message
+fn extract_followups(text: &str) -> (Vec<String>, Vec<String>) {
+    let mut questions = Vec::new();
+    let mut next_steps = Vec::new();
+    let mut in_next_section = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let lower = trimmed.to_lowercase();
+        let is_heading = trimmed.starts_with('#') || (trimmed.ends_with(':') && strip_list_marker(trimmed).is_none());
+        if is_heading {
+            in_next_section = ["next step", "todo", "remaining", "follow-up", "follow up", "后续", "下一步"]
+                .iter()
+                .any(|k| lower.contains(k));
+            continue;
+        }
+
+        if trimmed.ends_with('?') || trimmed.ends_with('？') {
+            questions.push(strip_list_marker(trimmed).unwrap_or(trimmed).to_string());
+        } else if in_next_section {
+            if let Some(item) = strip_list_marker(trimmed) {
+                next_steps.push(item.to_string());
+            }
+        }
+    }
+
+    (questions, next_steps)
+}
+
+/// Build a summary of a session from its JSONL transcript
+pub fn build_session_summary(session_id: &str, project_path: &str, success: bool) -> Result<SessionEndSummary, String> {
+    let mut summary = SessionEndSummary {
+        session_id: session_id.to_string(),
+        project_path: project_path.to_string(),
+        success,
+        ..Default::default()
+    };
+
+    let session_path = match find_session_file(session_id, None)? {
+        Some((_, path)) => path,
+        None => {
+            summary.summary = "Session transcript not found.".to_string();
+            return Ok(summary);
+        }
+    };
+
+    let file = fs::File::open(&session_path).map_err(|e| format!("Failed to open session file: {}", e))?;
+    let mut files_modified = BTreeSet::new();
+    let mut last_assistant_text = String::new();
+    let mut pending_todos: Vec<String> = Vec::new();
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let entry: serde_json::Value = match serde_json::from_str(&line) {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+
+        match entry.get("type").and_then(|t| t.as_str()) {
+            Some("user") => {
+                let content = &entry["message"]["content"];
+                if is_user_prompt(content) {
+                    summary.user_messages += 1;
+                    if summary.task.is_none() {
+                        summary.task = Some(truncate_chars(&content_text(content), 500));
+                    }
+                }
+            }
+            Some("assistant") => {
+                summary.assistant_messages += 1;
+                let message = &entry["message"];
+
+                if let Some(usage) = message.get("usage") {
+                    summary.input_tokens += usage["input_tokens"].as_u64().unwrap_or(0);
+                    summary.output_tokens += usage["output_tokens"].as_u64().unwrap_or(0);
+                }
+
+                let text = content_text(&message["content"]);
+                if !text.trim().is_empty() {
+                    last_assistant_text = text;
+                }
+
+                for block in message["content"].as_array().into_iter().flatten() {
+                    if block.get("type").and_then(|t| t.as_str()) != Some("tool_use") {
+                        continue;
+                    }
+                    let name = block["name"].as_str().unwrap_or("unknown").to_string();
+                    let input = &block["input"];
+
+                    if matches!(name.as_str(), "Edit" | "Write" | "MultiEdit" | "NotebookEdit") {
+                        if let Some(path) = input["file_path"].as_str().or_else(|| input["notebook_path"].as_str()) {
+                            let relative = Path::new(path)
+                                .strip_prefix(project_path)
+                                .map(|p| p.to_string_lossy().replace('\\', "/"))
+                                .unwrap_or_else(|_| path.to_string());
+                            files_modified.insert(relative);
+                        }
+                    }
+
+                    // The latest TodoWrite call holds the current todo list
+                    if name == "TodoWrite" {
+                        pending_todos = input["todos"]
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .filter(|todo| todo["status"].as_str() != Some("completed"))
+                            .filter_map(|todo| todo["content"].as_str().map(|s| s.to_string()))
+                            .collect();
+                    }
+
+                    *summary.tool_uses.entry(name).or_insert(0) += 1;
+                }
+            }
+            Some("result") => {
+                summary.total_cost_usd = entry["total_cost_usd"].as_f64().or(summary.total_cost_usd);
+                summary.duration_ms = entry["duration_ms"].as_u64().or(summary.duration_ms);
+            }
+            _ => {}
+        }
+    }
+
+    let (questions, mut next_steps) = extract_followups(&last_assistant_text);
+    for todo in pending_todos {
+        if !next_steps.contains(&todo) {
+            next_steps.push(todo);
+        }
+    }
+
+    summary.files_modified = files_modified.into_iter().collect();
+    summary.open_questions = questions.into_iter().take(MAX_SECTION_ITEMS).collect();
+    summary.next_steps = next_steps.into_iter().take(MAX_SECTION_ITEMS).collect();
+    summary.summary = if last_assistant_text.trim().is_empty() {
+        format!(
+            "{} messages exchanged, {} files modified.",
+            summary.user_messages + summary.assistant_messages,
+            summary.files_modified.len()
+        )
+    } else {
+        truncate_chars(&last_assistant_text, 1200)
+    };
+
+    Ok(summary)
+}
+
+/// Render a session summary as a handoff document
+pub fn render_handoff_markdown(summary: &SessionEndSummary) -> String {
+    let mut md = String::new();
+    md.push_str("# Session Handoff\n\n");
+    md.push_str(&format!(
+        "- **Session:** `{}`\n- **Generated:** {}\n- **Outcome:** {}\n\n",
+        summary.session_id,
+        chrono::Local::now().format("%Y-%m-%d %H:%M"),
+        if summary.success { "completed" } else { "ended with errors" }
+    ));
+
+    if let Some(task) = &summary.task {
+        md.push_str("## Task\n\n");
+        md.push_str(task);
+        md.push_str("\n\n");
+    }
+
+    md.push_str("## What was done\n\n");
+    md.push_str(&summary.summary);
+    md.push_str("\n\n");
+
+    if !summary.files_modified.is_empty() {
+        md.push_str("### Files modified\n\n");
+        for file in &summary.files_modified {
+            md.push_str(&format!("- `{}`\n", file));
+        }
+        md.push('\n');
+    }
+
+    md.push_str("## Open questions\n\n");
+    if summary.open_questions.is_empty() {
+        md.push_str("_None recorded._\n\n");
+    } else {
+        for question in &summary.open_questions {
+            md.push_str(&format!("- {}\n", question));
+        }
+        md.push('\n');
+    }
+
+    md.push_str("## Next steps\n\n");
+    if summary.next_steps.is_empty() {
+        md.push_str("_None recorded._\n\n");
+    } else {
+        for step in &summary.next_steps {
+            md.push_str(&format!("- [ ] {}\n", step));
+        }
+        md.push('\n');
+    }
+
+    md.push_str("## Stats\n\n");
+    md.push_str(&format!(
+        "- Messages: {} user / {} assistant\n- Tokens: {} input / {} output\n",
+        summary.user_messages, summary.assistant_messages, summary.input_tokens, summary.output_tokens
+    ));
+    if let Some(cost) = summary.total_cost_usd {
+        md.push_str(&format!("- Cost: ${:.4}\n", cost));
+    }
+    if let Some(duration) = summary.duration_ms {
+        md.push_str(&format!("- Duration: {:.1} min\n", duration as f64 / 60_000.0));
+    }
+    if !summary.tool_uses.is_empty() {
+        let tools = summary
+            .tool_uses
+            .iter()
+            .map(|(name, count)| format!("{} ×{}", name, count))
+            .collect::<Vec<_>>()
+            .join(", ");
+        md.push_str(&format!("- Tools: {}\n", tools));
+    }
+
+    md
+}
+
+/// Write `HANDOFF.md` into the project root and return its path
+pub fn write_handoff_file(summary: &SessionEndSummary) -> Result<PathBuf, String> {
+    let project_dir = PathBuf::from(&summary.project_path);
+    if !project_dir.is_dir() {
+        return Err(format!("Project path does not exist: {}", summary.project_path));
+    }
+
+    let path = project_dir.join(HANDOFF_FILE_NAME);
+    fs::write(&path, render_handoff_markdown(summary))
+        .map_err(|e| format!("Failed to write {}: {}", HANDOFF_FILE_NAME, e))?;
+
+    log::info!("Wrote session handoff to {:?}", path);
+    Ok(path)
+}
+
+/// Built-in hook action: write the handoff for the session described by a hook context.
+/// Reuses the summary already attached to OnSessionEnd contexts, otherwise builds one.
+pub fn write_handoff_from_context(context: &HookContext) -> Result<PathBuf, String> {
+    let summary = match serde_json::from_value::<SessionEndSummary>(context.data.clone()) {
+        Ok(summary) if summary.session_id == context.session_id => summary,
+        _ => build_session_summary(&context.session_id, &context.project_path, true)?,
+    };
+    write_handoff_file(&summary)
+}
+
+/// Fire OnSessionEnd hooks for a finished session in the background
+pub fn fire_session_end(app: &AppHandle, session_id: String, project_path: String, success: bool) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let summary = {
+            let (session_id, project_path) = (session_id.clone(), project_path.clone());
+            tokio::task::spawn_blocking(move || build_session_summary(&session_id, &project_path, success)).await
+        };
+        let summary = match summary {
+            Ok(Ok(summary)) => summary,
+            Ok(Err(e)) => {
+                log::warn!("Failed to summarize session {}: {}", session_id, e);
+                return;
+            }
+            Err(e) => {
+                log::warn!("Session summary task failed for {}: {}", session_id, e);
+                return;
+            }
+        };
+
+        let data = match serde_json::to_value(&summary) {
+            Ok(data) => data,
+            Err(e) => {
+                log::warn!("Failed to serialize session summary: {}", e);
+                return;
+            }
+        };
+
+        let context = HookContext {
+            event: HookEvent::OnSessionEnd.as_str().to_string(),
+            session_id,
+            project_path,
+            data,
+        };
+        spawn_hook_event(&app, HookEvent::OnSessionEnd, context);
+    });
+}
+
+/// Generate a summary for a session (used for previews in the UI)
+#[tauri::command]
+pub async fn get_session_end_summary(
+    session_id: String,
+    project_path: String,
+) -> Result<SessionEndSummary, String> {
+    tokio::task::spawn_blocking(move || build_session_summary(&session_id, &project_path, true))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Write `HANDOFF.md` for a session on demand; returns the written file path
+#[tauri::command]
+pub async fn write_session_handoff(
+    session_id: String,
+    project_path: String,
+) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        let summary = build_session_summary(&session_id, &project_path, true)?;
+        write_handoff_file(&summary).map(|path| path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,365 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+use tauri_plugin_shell::ShellExt;
+
+use super::session_meta::{find_session_file, SessionMeta};
+
+/// Result of probing the Claude CLI for `--resume` support, keyed by binary path
+static CLI_RESUME_PROBE: once_cell::sync::Lazy<Mutex<HashMap<String, bool>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Resumability report for a single session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionResumability {
+    pub session_id: String,
+    pub project_id: Option<String>,
+    /// Absolute path of the JSONL file (if found)
+    pub file_path: Option<String>,
+    pub file_exists: bool,
+    /// Whether every non-empty line parsed as JSON
+    pub well_formed: bool,
+    pub total_lines: usize,
+    pub malformed_lines: usize,
+    /// Whether the transcript contains at least one user or assistant message
+    pub has_conversation: bool,
+    /// Whether the working directory recorded in the transcript still exists
+    pub cwd_exists: Option<bool>,
+    /// Whether the installed Claude CLI lists `--resume` in its help (None if the probe
+    /// failed). Says nothing about this session.
+    pub cli_supports_resume: Option<bool>,
+    pub resumable: bool,
+    /// "resumable", "view_only" or "missing"
+    pub status: String,
+    /// Human readable reasons explaining a non-resumable status
+    pub reasons: Vec<String>,
+}
+
+/// Whether the end of a JSONL after the sidecar's last complete line is damaged, e.g.
+/// a line a force-killed writer left unterminated
+fn has_damaged_tail(jsonl_path: &Path, meta: &SessionMeta) -> bool {
+    let Ok(mut file) = fs::File::open(jsonl_path) else {
+        return false;
+    };
+    let mut tail = Vec::new();
+    if file.seek(SeekFrom::Start(meta.scanned_bytes)).is_err() || file.read_to_end(&mut tail).is_err() {
+        return false;
+    }
+    let Ok(tail) = std::str::from_utf8(&tail) else {
+        return true;
+    };
+    !tail.trim().is_empty() && serde_json::from_str::<serde_json::Value>(tail).is_err()
+}
+
+/// Resumability status for session lists, from the sidecar metadata kept in step with
+/// the JSONL: "resumable" when every line parses, the transcript has a conversation
+/// and its working directory still exists, "view_only" otherwise. Unlike
+/// `check_session_resumability` it does not probe the CLI.
+pub fn listed_resume_status(jsonl_path: &Path, meta: &SessionMeta) -> String {
+    let has_conversation = meta.user_messages + meta.assistant_messages > 0;
+    let cwd_exists = meta.cwd.as_deref().map_or(true, |cwd| Path::new(cwd).is_dir());
+    let resumable =
+        meta.malformed_lines == 0 && has_conversation && cwd_exists && !has_damaged_tail(jsonl_path, meta);
+    if resumable { "resumable" } else { "view_only" }.to_string()
+}
+
+/// Whether the installed Claude CLI lists `--resume` in its `--help` output. This only
+/// tells whether the CLI version has the flag, not whether a particular session can be
+/// resumed; the CLI has no dry-run mode to check that without starting a conversation.
+async fn probe_cli_resume_support(app: &AppHandle) -> Option<bool> {
+    let claude_path = crate::claude_binary::find_claude_binary(app).ok()?;
+
+    if let Ok(cache) = CLI_RESUME_PROBE.lock() {
+        if let Some(supported) = cache.get(&claude_path) {
+            return Some(*supported);
+        }
+    }
+
+    let help_output = if claude_path == "claude-code" {
+        let output = app
+            .shell()
+            .sidecar("claude-code")
+            .ok()?
+            .args(["--help"])
+            .current_dir(std::env::temp_dir())
+            .output()
+            .await
+            .ok()?;
+        String::from_utf8_lossy(&output.stdout).to_string()
+    } else {
+        let mut cmd = std::process::Command::new(&claude_path);
+        cmd.arg("--help");
+
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+        }
+
+        let output = cmd.output().ok()?;
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let supported = help_output.contains("--resume");
+    if let Ok(mut cache) = CLI_RESUME_PROBE.lock() {
+        cache.insert(claude_path, supported);
+    }
+    Some(supported)
+}
+
+/// Check whether a session can still be resumed or only viewed
+#[tauri::command]
+pub async fn check_session_resumability(
+    app: AppHandle,
+    session_id: String,
+    project_id: Option<String>,
+) -> Result<SessionResumability, String> {
+    log::info!("Checking resumability of session: {}", session_id);
+
+    let mut report = SessionResumability {
+        session_id: session_id.clone(),
+        project_id: project_id.clone(),
+        file_path: None,
+        file_exists: false,
+        well_formed: false,
+        total_lines: 0,
+        malformed_lines: 0,
+        has_conversation: false,
+        cwd_exists: None,
+        cli_supports_resume: None,
+        resumable: false,
+        status: "missing".to_string(),
+        reasons: Vec::new(),
+    };
+
+    let (found_project_id, session_path) = match find_session_file(&session_id, project_id.as_deref())? {
+        Some(found) => found,
+        None => {
+            report.reasons.push("Session file not found".to_string());
+            return Ok(report);
+        }
+    };
+
+    report.project_id = Some(found_project_id);
+    report.file_path = Some(session_path.to_string_lossy().to_string());
+    report.file_exists = true;
+
+    let file = fs::File::open(&session_path)
+        .map_err(|e| format!("Failed to open session file: {}", e))?;
+    let reader = BufReader::new(file);
+    let mut cwd: Option<String> = None;
+
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => {
+                report.total_lines += 1;
+                report.malformed_lines += 1;
+                continue;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        report.total_lines += 1;
+
+        match serde_json::from_str::<serde_json::Value>(&line) {
+            Ok(json) => {
+                if cwd.is_none() {
+                    cwd = json.get("cwd").and_then(|v| v.as_str()).map(|s| s.to_string());
+                }
+                if matches!(
+                    json.get("type").and_then(|t| t.as_str()),
+                    Some("user") | Some("assistant")
+                ) {
+                    report.has_conversation = true;
+                }
+            }
+            Err(_) => report.malformed_lines += 1,
+        }
+    }
+
+    report.well_formed = report.malformed_lines == 0 && report.total_lines > 0;
+    if report.total_lines == 0 {
+        report.reasons.push("Session file is empty".to_string());
+    } else if report.malformed_lines > 0 {
+        report.reasons.push(format!(
+            "{} of {} lines are not valid JSON",
+            report.malformed_lines, report.total_lines
+        ));
+    }
+    if !report.has_conversation {
+        report.reasons.push("Session contains no conversation messages".to_string());
+    }
+
+    if let Some(cwd) = &cwd {
+        let exists = std::path::Path::new(cwd).is_dir();
+        report.cwd_exists = Some(exists);
+        if !exists {
+            report.reasons.push(format!("Working directory no longer exists: {}", cwd));
+        }
+    }
+
+    report.cli_supports_resume = probe_cli_resume_support(&app).await;
+    if report.cli_supports_resume == Some(false) {
+        report.reasons.push("Installed Claude CLI does not accept --resume".to_string());
+    }
+
+    report.resumable = report.well_formed
+        && report.has_conversation
+        && report.cwd_exists != Some(false)
+        && report.cli_supports_resume != Some(false);
+    report.status = if report.resumable { "resumable" } else { "view_only" }.to_string();
+
+    log::info!("Session {} resumability: {}", session_id, report.status);
+    Ok(report)
+}
+
+/// A line removed from a session file during repair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemovedLine {
+    /// 1-based line number in the original file
+    pub line_number: usize,
+    /// Parse error or reason for removal
+    pub reason: String,
+    /// First characters of the removed line
+    pub preview: String,
+}
+
+/// Report produced by `repair_session_file`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRepairReport {
+    pub session_id: String,
+    pub file_path: String,
+    /// Sidecar file the removed lines were appended to (None if nothing was removed)
+    pub quarantine_path: Option<String>,
+    pub total_lines: usize,
+    pub kept_lines: usize,
+    pub removed_lines: Vec<RemovedLine>,
+    /// Whether the session file was rewritten
+    pub repaired: bool,
+}
+
+/// Scan a session JSONL for truncated/garbage lines, quarantine them and rewrite a clean file.
+///
+/// Refused while a run or another process writes the session, whose appends would
+/// go to the replaced file.
+#[tauri::command]
+pub async fn repair_session_file(
+    registry: tauri::State<'_, crate::process::ProcessRegistryState>,
+    session_id: String,
+    project_id: Option<String>,
+) -> Result<SessionRepairReport, String> {
+    log::info!("Repairing session file for session: {}", session_id);
+
+    if registry.0.get_claude_session_by_id(&session_id)?.is_some() {
+        return Err(format!("Session {} is running; stop it before repairing", session_id));
+    }
+    let (_, session_path) = find_session_file(&session_id, project_id.as_deref())?
+        .ok_or_else(|| format!("Session file not found: {}", session_id))?;
+
+    // Hold the session lock so no run starts writing while the file is replaced
+    super::session_lock::acquire(&session_id, std::process::id())?;
+    let result = rewrite_session_file(&session_id, session_path);
+    super::session_lock::release(&session_id);
+    result
+}
+
+fn rewrite_session_file(session_id: &str, session_path: PathBuf) -> Result<SessionRepairReport, String> {
+    // Read raw bytes - force-killed writers can leave partial UTF-8 sequences behind
+    let raw = fs::read(&session_path).map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    let mut clean = Vec::with_capacity(raw.len());
+    let mut quarantined = Vec::new();
+    let mut removed_lines = Vec::new();
+    let mut total_lines = 0;
+    let mut kept_lines = 0;
+
+    for (idx, bytes) in raw.split(|b| *b == b'\n').enumerate() {
+        let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+        if bytes.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        total_lines += 1;
+
+        let reason = match std::str::from_utf8(bytes) {
+            Err(_) => Some("Invalid UTF-8".to_string()),
+            Ok(line) => match serde_json::from_str::<serde_json::Value>(line) {
+                Ok(json) if json.is_object() => None,
+                Ok(_) => Some("Line is not a JSON object".to_string()),
+                Err(e) => Some(e.to_string()),
+            },
+        };
+
+        match reason {
+            None => {
+                clean.extend_from_slice(bytes);
+                clean.push(b'\n');
+                kept_lines += 1;
+            }
+            Some(reason) => {
+                // The original bytes, so a line with broken UTF-8 can still be recovered
+                quarantined.extend_from_slice(bytes);
+                quarantined.push(b'\n');
+                removed_lines.push(RemovedLine {
+                    line_number: idx + 1,
+                    reason,
+                    preview: String::from_utf8_lossy(bytes).chars().take(120).collect(),
+                });
+            }
+        }
+    }
+
+    let mut report = SessionRepairReport {
+        session_id: session_id.to_string(),
+        file_path: session_path.to_string_lossy().to_string(),
+        quarantine_path: None,
+        total_lines,
+        kept_lines,
+        removed_lines,
+        repaired: false,
+    };
+
+    if report.removed_lines.is_empty() {
+        log::info!("Session {} has no damaged lines, nothing to repair", session_id);
+        return Ok(report);
+    }
+
+    // Append removed lines to the quarantine sidecar so nothing is lost
+    let quarantine_path = session_path.with_extension("jsonl.quarantine");
+    {
+        use std::io::Write;
+        let mut quarantine_file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&quarantine_path)
+            .map_err(|e| format!("Failed to open quarantine file: {}", e))?;
+        quarantine_file
+            .write_all(&quarantined)
+            .map_err(|e| format!("Failed to write quarantine file: {}", e))?;
+    }
+
+    // Write the clean version next to the original and swap it in atomically
+    let temp_path = session_path.with_extension("jsonl.repair");
+    fs::write(&temp_path, &clean)
+        .map_err(|e| format!("Failed to write repaired session file: {}", e))?;
+    fs::rename(&temp_path, &session_path).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        format!("Failed to replace session file: {}", e)
+    })?;
+
+    report.quarantine_path = Some(quarantine_path.to_string_lossy().to_string());
+    report.repaired = true;
+
+    log::info!(
+        "Repaired session {}: kept {} lines, quarantined {} lines",
+        session_id,
+        report.kept_lines,
+        report.removed_lines.len()
+    );
+    Ok(report)
+}
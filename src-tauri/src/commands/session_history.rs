@@ -0,0 +1,374 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use super::claude::get_claude_dir;
+use super::history_filter::{self, HistoryFilter};
+
+/// Messages per page when no limit is given
+const DEFAULT_PAGE_SIZE: usize = 200;
+
+/// Largest page a single request may ask for
+const MAX_PAGE_SIZE: usize = 2000;
+
+/// Bytes before the indexed end that must be unchanged for the file to count as appended to
+const FINGERPRINT_LEN: u64 = 256;
+
+/// Indexes kept in memory; the least recently used one is dropped beyond this
+const MAX_CACHED_INDEXES: usize = 32;
+
+/// One page of a session transcript, as returned by `load_session_history_page`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionHistoryPage {
+    /// Messages in transcript order, with the same display timestamps `load_session_history` adds
+    /// and trimmed by the filter if one was given
+    pub messages: Vec<serde_json::Value>,
+    /// Index of the first message in the page; indexes match `load_session_history`
+    pub offset: usize,
+    /// Messages in the whole transcript
+    pub total: usize,
+    pub has_more_before: bool,
+    pub has_more_after: bool,
+    /// Pass to `get_session_history_delta` to get the messages after this page
+    pub cursor: String,
+}
+
+/// Messages appended to a transcript since a cursor, as returned by
+/// `get_session_history_delta`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionHistoryDelta {
+    pub messages: Vec<serde_json::Value>,
+    /// Index of the first message in `messages`
+    pub offset: usize,
+    /// Messages in the whole transcript
+    pub total: usize,
+    /// Where the next delta starts
+    pub cursor: String,
+    /// The transcript no longer matches the cursor, e.g. after a checkpoint restore:
+    /// the delta starts over at the first message and replaces the viewer's copy
+    pub reset: bool,
+    /// More messages were appended than one delta returns; ask again with `cursor`
+    pub has_more: bool,
+}
+
+/// Byte offsets of the messages in a transcript. Lines that are not valid JSON are left
+/// out, as `load_session_history` skips them.
+struct LineIndex {
+    /// Start of each message on a complete (newline-terminated) line
+    offsets: Vec<u64>,
+    /// End of the last complete line
+    scanned_to: u64,
+    /// Bytes just before `scanned_to`, to tell appends from rewrites
+    fingerprint: Vec<u8>,
+    modified: SystemTime,
+    last_used: SystemTime,
+}
+
+static INDEXES: Lazy<Mutex<HashMap<PathBuf, LineIndex>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn session_path(session_id: &str, project_id: &str) -> Result<PathBuf, String> {
+    for id in [session_id, project_id] {
+        if id.is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+            return Err(format!("Invalid session or project id: {}", id));
+        }
+    }
+    let path = get_claude_dir()
+        .map_err(|e| e.to_string())?
+        .join("projects")
+        .join(project_id)
+        .join(format!("{}.jsonl", session_id));
+    if !path.exists() {
+        return Err(format!("Session file not found: {}", session_id));
+    }
+    Ok(path)
+}
+
+/// The transcript of a session in any project, for callers that only know its ID
+fn find_session_path(session_id: &str) -> Result<PathBuf, String> {
+    if session_id.is_empty() || session_id.contains(['/', '\\']) || session_id.contains("..") {
+        return Err(format!("Invalid session id: {}", session_id));
+    }
+    let projects = get_claude_dir().map_err(|e| e.to_string())?.join("projects");
+    fs::read_dir(&projects)
+        .map_err(|e| format!("Failed to read projects directory: {}", e))?
+        .flatten()
+        .map(|entry| entry.path().join(format!("{}.jsonl", session_id)))
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("Session file not found: {}", session_id))
+}
+
+fn is_message(line: &[u8]) -> bool {
+    serde_json::from_slice::<serde::de::IgnoredAny>(line).is_ok()
+}
+
+fn read_fingerprint(file: &mut fs::File, end: u64) -> std::io::Result<Vec<u8>> {
+    let start = end.saturating_sub(FINGERPRINT_LEN);
+    let mut bytes = vec![0; (end - start) as usize];
+    file.seek(SeekFrom::Start(start))?;
+    file.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Index complete lines from `start`, returning the end of the last one
+fn scan_lines(file: &mut fs::File, start: u64, offsets: &mut Vec<u64>) -> std::io::Result<u64> {
+    file.seek(SeekFrom::Start(start))?;
+    let mut reader = BufReader::new(file);
+    let mut position = start;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)? as u64;
+        if read == 0 || line.last() != Some(&b'\n') {
+            return Ok(position);
+        }
+        if is_message(&line) {
+            offsets.push(position);
+        }
+        position += read;
+    }
+}
+
+/// Message offsets of a transcript, updating the cached index when the file changed.
+/// Appended lines are indexed incrementally; any other change rebuilds the index.
+fn message_offsets(path: &Path) -> Result<(Vec<u64>, SystemTime), String> {
+    let metadata = fs::metadata(path).map_err(|e| format!("Failed to read file metadata: {}", e))?;
+    let modified = metadata.modified().unwrap_or_else(|_| SystemTime::now());
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open session file: {}", e))?;
+
+    let mut indexes = INDEXES.lock().map_err(|e| e.to_string())?;
+    let reusable = indexes.remove(path).filter(|index| {
+        if index.modified == modified {
+            return true;
+        }
+        metadata.len() >= index.scanned_to
+            && read_fingerprint(&mut file, index.scanned_to).ok().as_ref() == Some(&index.fingerprint)
+    });
+    let mut index = reusable.unwrap_or_else(|| LineIndex {
+        offsets: Vec::new(),
+        scanned_to: 0,
+        fingerprint: Vec::new(),
+        modified,
+        last_used: SystemTime::now(),
+    });
+
+    if index.modified != modified || index.scanned_to == 0 {
+        let scanned_to = scan_lines(&mut file, index.scanned_to, &mut index.offsets)
+            .map_err(|e| format!("Failed to index session file: {}", e))?;
+        index.fingerprint = read_fingerprint(&mut file, scanned_to)
+            .map_err(|e| format!("Failed to index session file: {}", e))?;
+        index.scanned_to = scanned_to;
+        index.modified = modified;
+    }
+
+    // A last line without a newline may still be in the middle of being written;
+    // include it when it is already complete JSON, without caching it
+    let mut offsets = index.offsets.clone();
+    if metadata.len() > index.scanned_to {
+        let mut tail = Vec::new();
+        file.seek(SeekFrom::Start(index.scanned_to))
+            .and_then(|_| file.read_to_end(&mut tail))
+            .map_err(|e| format!("Failed to read session file: {}", e))?;
+        if is_message(&tail) {
+            offsets.push(index.scanned_to);
+        }
+    }
+
+    index.last_used = SystemTime::now();
+    indexes.insert(path.to_path_buf(), index);
+    if indexes.len() > MAX_CACHED_INDEXES {
+        let oldest = indexes
+            .iter()
+            .min_by_key(|(_, index)| index.last_used)
+            .map(|(path, _)| path.clone());
+        if let Some(oldest) = oldest {
+            indexes.remove(&oldest);
+        }
+    }
+    Ok((offsets, modified))
+}
+
+/// Give a history message the display timestamp `load_session_history` uses when the
+/// transcript has none: five seconds apart, ending at the file's modification time
+pub fn add_display_timestamp(
+    message: &mut serde_json::Value,
+    index: usize,
+    total: usize,
+    base_time: SystemTime,
+) {
+    if !message.is_object() {
+        return;
+    }
+    let field = match message.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+        "user" => "sentAt",
+        _ => "receivedAt",
+    };
+    if message.get(field).is_some() {
+        return;
+    }
+    let time_offset = (total - index - 1) as u64 * 5;
+    let message_time = base_time - std::time::Duration::from_secs(time_offset);
+    let timestamp_iso = chrono::DateTime::<chrono::Utc>::from(message_time).to_rfc3339();
+    message[field] = serde_json::Value::String(timestamp_iso);
+}
+
+fn read_page(
+    path: &Path,
+    offsets: &[u64],
+    start: usize,
+    total: usize,
+    base_time: SystemTime,
+) -> Result<Vec<serde_json::Value>, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open session file: {}", e))?;
+    let mut reader = BufReader::new(file);
+    let mut messages = Vec::with_capacity(offsets.len());
+    let mut line = Vec::new();
+    for (i, offset) in offsets.iter().enumerate() {
+        line.clear();
+        reader
+            .seek(SeekFrom::Start(*offset))
+            .and_then(|_| reader.read_until(b'\n', &mut line))
+            .map_err(|e| format!("Failed to read session file: {}", e))?;
+        // The file may have been rewritten since it was indexed
+        let mut message: serde_json::Value = serde_json::from_slice(&line)
+            .map_err(|_| "Session file changed while loading, please retry".to_string())?;
+        add_display_timestamp(&mut message, start + i, total, base_time);
+        messages.push(message);
+    }
+    Ok(messages)
+}
+
+/// Where a viewer's copy of a transcript ends: the number of messages it has and the
+/// byte offset of the last one, to notice when the transcript was rewritten
+fn cursor(offsets: &[u64], end: usize) -> String {
+    match end.checked_sub(1) {
+        Some(last) => format!("{}:{}", end, offsets[last]),
+        None => "0:0".to_string(),
+    }
+}
+
+/// The index of the first message after `cursor`, None when the cursor does not
+/// match the transcript
+fn resume_index(cursor: &str, offsets: &[u64]) -> Option<usize> {
+    let (count, offset) = cursor.split_once(':')?;
+    let count: usize = count.parse().ok()?;
+    let offset: u64 = offset.parse().ok()?;
+    match count.checked_sub(1) {
+        Some(last) => (offsets.get(last) == Some(&offset)).then_some(count),
+        None => Some(0),
+    }
+}
+
+/// Load part of a session's history without reading the whole transcript.
+///
+/// Without `offset` the last `limit` messages are returned, which is where a viewer
+/// usually starts; earlier pages are loaded by passing the previous page's offset minus
+/// the limit. `filter` trims the messages of the page; offsets and counts still refer
+/// to the whole transcript.
+#[tauri::command]
+pub async fn load_session_history_page(
+    session_id: String,
+    project_id: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    filter: Option<HistoryFilter>,
+) -> Result<SessionHistoryPage, String> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    tokio::task::spawn_blocking(move || {
+        let path = session_path(&session_id, &project_id)?;
+        let (offsets, modified) = message_offsets(&path)?;
+        let total = offsets.len();
+        let start = offset.unwrap_or_else(|| total.saturating_sub(limit)).min(total);
+        let end = (start + limit).min(total);
+
+        let messages = read_page(&path, &offsets[start..end], start, total, modified)?;
+        let messages = history_filter::apply(messages, start, &filter.unwrap_or_default());
+        Ok(SessionHistoryPage {
+            messages,
+            offset: start,
+            total,
+            has_more_before: start > 0,
+            has_more_after: end < total,
+            cursor: cursor(&offsets, end),
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Number of messages in a session, as `load_session_history` would return them
+#[tauri::command]
+pub async fn get_session_message_count(
+    session_id: String,
+    project_id: String,
+) -> Result<usize, String> {
+    tokio::task::spawn_blocking(move || {
+        let path = session_path(&session_id, &project_id)?;
+        message_offsets(&path).map(|(offsets, _)| offsets.len())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Messages appended to a session's transcript since `since_cursor`, from a page or
+/// an earlier delta, so a viewer can refresh without reloading the whole history.
+/// Without a cursor the delta starts at the first message.
+///
+/// Without `project_id` the transcript is looked up in every project, as for agent
+/// runs. `filter` trims the messages like for `load_session_history_page`.
+#[tauri::command]
+pub async fn get_session_history_delta(
+    session_id: String,
+    since_cursor: Option<String>,
+    project_id: Option<String>,
+    filter: Option<HistoryFilter>,
+) -> Result<SessionHistoryDelta, String> {
+    tokio::task::spawn_blocking(move || {
+        let path = match &project_id {
+            Some(project_id) => session_path(&session_id, project_id)?,
+            None => find_session_path(&session_id)?,
+        };
+        let (offsets, modified) = message_offsets(&path)?;
+        let total = offsets.len();
+        let resumed = since_cursor
+            .as_deref()
+            .map(|since| resume_index(since, &offsets))
+            .unwrap_or(Some(0));
+        let start = resumed.unwrap_or(0);
+        let end = (start + MAX_PAGE_SIZE).min(total);
+
+        let messages = read_page(&path, &offsets[start..end], start, total, modified)?;
+        Ok(SessionHistoryDelta {
+            messages: history_filter::apply(messages, start, &filter.unwrap_or_default()),
+            offset: start,
+            total,
+            cursor: cursor(&offsets, end),
+            reset: resumed.is_none(),
+            has_more: end < total,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_resumes_after_its_last_message() {
+        let offsets = [0, 120, 300, 420];
+        assert_eq!(resume_index(&cursor(&offsets, 0), &offsets), Some(0));
+        assert_eq!(resume_index(&cursor(&offsets, 3), &offsets), Some(3));
+        assert_eq!(resume_index(&cursor(&offsets, 4), &offsets), Some(4));
+
+        // The transcript was rewritten or truncated under the cursor
+        assert_eq!(resume_index("3:299", &offsets), None);
+        assert_eq!(resume_index("9:420", &offsets), None);
+        assert_eq!(resume_index("garbage", &offsets), None);
+    }
+}
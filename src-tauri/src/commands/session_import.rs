@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+use super::agents::{insert_usage_rows, AgentDb};
+use super::claude::{encode_project_path, get_claude_dir};
+use super::session_meta::{find_session_file, load_session_meta};
+use super::usage_writer::UsageRow;
+use crate::paths;
+
+/// Returned by `import_session_file`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedSession {
+    pub session_id: String,
+    /// Directory name under ~/.claude/projects
+    pub project_id: String,
+    pub project_path: String,
+    /// Where the transcript was copied to
+    pub jsonl_path: String,
+    pub message_count: u64,
+    /// Usage rows registered from the transcript's assistant messages
+    pub usage_rows: usize,
+    /// Lines that were not valid JSON and were left out
+    pub skipped_lines: usize,
+}
+
+/// Parsed transcript lines plus what the import needs to know about them
+struct Transcript {
+    entries: Vec<serde_json::Value>,
+    session_id: Option<String>,
+    cwd: Option<String>,
+    skipped_lines: usize,
+}
+
+fn read_transcript(path: &Path) -> Result<Transcript, String> {
+    let content = fs::read_to_string(paths::long_path(path))
+        .map_err(|e| format!("Failed to read transcript {}: {}", paths::display(path), e))?;
+
+    let mut transcript = Transcript {
+        entries: Vec::new(),
+        session_id: None,
+        cwd: None,
+        skipped_lines: 0,
+    };
+    for line in content.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(line) else {
+            transcript.skipped_lines += 1;
+            continue;
+        };
+        if !entry.is_object() {
+            transcript.skipped_lines += 1;
+            continue;
+        }
+        if transcript.session_id.is_none() {
+            transcript.session_id = entry["sessionId"].as_str().map(str::to_string);
+        }
+        if transcript.cwd.is_none() {
+            transcript.cwd = entry["cwd"].as_str().map(str::to_string);
+        }
+        transcript.entries.push(entry);
+    }
+
+    if !transcript.entries.iter().any(|entry| entry.get("message").is_some()) {
+        return Err(format!("{} is not a Claude transcript: it has no messages", paths::display(path)));
+    }
+    Ok(transcript)
+}
+
+/// The transcript's session id, or the file name when it is a UUID, or a new one
+fn pick_session_id(transcript: &Transcript, path: &Path) -> String {
+    transcript
+        .session_id
+        .iter()
+        .map(String::as_str)
+        .chain(path.file_stem().and_then(|stem| stem.to_str()))
+        .find(|id| uuid::Uuid::parse_str(id).is_ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+}
+
+/// Usage of each assistant message; the CLI repeats a message once per content block
+fn usage_rows(entries: &[serde_json::Value], session_id: &str, project_path: &str) -> Vec<UsageRow> {
+    let mut counted = HashSet::new();
+    let mut rows = Vec::new();
+    for entry in entries {
+        let message = &entry["message"];
+        let Some(usage) = message.get("usage") else {
+            continue;
+        };
+        if let Some(id) = message["id"].as_str() {
+            if !counted.insert(id.to_string()) {
+                continue;
+            }
+        }
+        let tokens = |field: &str| usage[field].as_u64().unwrap_or(0);
+        rows.push(UsageRow {
+            session_id: session_id.to_string(),
+            timestamp: entry["timestamp"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+            model: message["model"].as_str().unwrap_or("unknown").to_string(),
+            input_tokens: tokens("input_tokens"),
+            output_tokens: tokens("output_tokens"),
+            cache_creation_tokens: tokens("cache_creation_input_tokens"),
+            cache_read_tokens: tokens("cache_read_input_tokens"),
+            project_path: project_path.to_string(),
+            provider_source: super::router_usage::SOURCE_DIRECT.to_string(),
+            operator_id: super::team_usage::current_operator_id(),
+            provider_key: None,
+            row_id: None,
+            attempts: 0,
+        });
+    }
+    rows
+}
+
+/// Copy a Claude CLI transcript from anywhere into ~/.claude/projects so it shows up
+/// like the app's own sessions.
+///
+/// The project is `project_path`, or the `cwd` recorded in the transcript. The copy
+/// gets the session id and project path on every entry, a listing sidecar, and usage
+/// rows for its assistant messages.
+#[tauri::command]
+pub async fn import_session_file(
+    db: State<'_, AgentDb>,
+    jsonl_path: String,
+    project_path: Option<String>,
+) -> Result<ImportedSession, String> {
+    let source = Path::new(jsonl_path.trim()).to_path_buf();
+    if !paths::long_path(&source).is_file() {
+        return Err(format!("Transcript not found: {}", paths::display(&source)));
+    }
+    log::info!("Importing session transcript {}", paths::display(&source));
+
+    let transcript = {
+        let source = source.clone();
+        tokio::task::spawn_blocking(move || read_transcript(&source))
+            .await
+            .map_err(|e| e.to_string())??
+    };
+
+    let project_path = project_path
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .or_else(|| transcript.cwd.clone())
+        .ok_or_else(|| "The transcript does not record its project directory; pass a project path".to_string())?;
+    let project_path = paths::canonical_display(Path::new(&project_path)).unwrap_or(project_path);
+
+    let session_id = pick_session_id(&transcript, &source);
+    if let Some((_, existing)) = find_session_file(&session_id, None)? {
+        return Err(format!(
+            "Session {} already exists at {}",
+            session_id,
+            paths::display(&existing)
+        ));
+    }
+
+    let project_id = encode_project_path(&project_path);
+    let project_dir = get_claude_dir()
+        .map_err(|e| e.to_string())?
+        .join("projects")
+        .join(&project_id);
+    let target = project_dir.join(format!("{}.jsonl", session_id));
+
+    // Entries point at the imported session and project, as if the CLI had written them there
+    let mut content = String::new();
+    for entry in &transcript.entries {
+        let mut entry = entry.clone();
+        if let Some(object) = entry.as_object_mut() {
+            if object.contains_key("sessionId") {
+                object.insert("sessionId".to_string(), session_id.clone().into());
+            }
+            if object.contains_key("cwd") {
+                object.insert("cwd".to_string(), project_path.clone().into());
+            }
+        }
+        content.push_str(&serde_json::to_string(&entry).map_err(|e| e.to_string())?);
+        content.push('\n');
+    }
+
+    fs::create_dir_all(paths::long_path(&project_dir))
+        .map_err(|e| format!("Failed to create project directory: {}", e))?;
+    fs::write(paths::long_path(&target), content).map_err(|e| format!("Failed to write session: {}", e))?;
+    let meta = load_session_meta(&target)?;
+
+    let rows = usage_rows(&transcript.entries, &session_id, &project_path);
+    if !rows.is_empty() {
+        let mut conn = db.0.get().map_err(|e| e.to_string())?;
+        insert_usage_rows(&mut conn, &rows)?;
+    }
+
+    log::info!(
+        "Imported session {} into {} ({} messages, {} usage rows, {} lines skipped)",
+        session_id,
+        project_id,
+        meta.message_count,
+        rows.len(),
+        transcript.skipped_lines
+    );
+    Ok(ImportedSession {
+        session_id,
+        project_id,
+        project_path,
+        jsonl_path: paths::display(&target),
+        message_count: meta.message_count,
+        usage_rows: rows.len(),
+        skipped_lines: transcript.skipped_lines,
+    })
+}
@@ -0,0 +1,78 @@
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Who produced a session transcript
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionKind {
+    /// A conversation started by the user
+    #[default]
+    Interactive,
+    /// The transcript of an agent run
+    Agent,
+}
+
+/// Create `session_kinds` and keep it filled from `agent_runs`.
+///
+/// Sessions outlive their run rows (deleting an agent cascades to its runs), so the
+/// kind is recorded separately; existing runs are migrated on first start.
+pub fn init_session_kinds(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_kinds (
+            session_id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS record_agent_session_kind_on_insert
+         AFTER INSERT ON agent_runs
+         FOR EACH ROW WHEN NEW.session_id != ''
+         BEGIN
+             INSERT OR IGNORE INTO session_kinds (session_id, kind) VALUES (NEW.session_id, 'agent');
+         END",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS record_agent_session_kind_on_update
+         AFTER UPDATE OF session_id ON agent_runs
+         FOR EACH ROW WHEN NEW.session_id != ''
+         BEGIN
+             INSERT OR IGNORE INTO session_kinds (session_id, kind) VALUES (NEW.session_id, 'agent');
+         END",
+        [],
+    )?;
+
+    conn.execute(
+        "INSERT OR IGNORE INTO session_kinds (session_id, kind)
+         SELECT DISTINCT session_id, 'agent' FROM agent_runs
+         WHERE session_id IS NOT NULL AND session_id != ''",
+        [],
+    )?;
+    Ok(())
+}
+
+/// IDs of all sessions recorded as agent runs
+pub fn agent_session_ids(conn: &Connection) -> Result<HashSet<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT session_id FROM session_kinds WHERE kind = 'agent'")
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashSet<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(ids)
+}
+
+/// Kind of a session given the set from `agent_session_ids`
+pub fn kind_of(agent_sessions: &HashSet<String>, session_id: &str) -> SessionKind {
+    if agent_sessions.contains(session_id) {
+        SessionKind::Agent
+    } else {
+        SessionKind::Interactive
+    }
+}
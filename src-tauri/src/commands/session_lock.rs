@@ -0,0 +1,239 @@
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use super::claude::get_claude_dir;
+use super::session_meta::find_session_file;
+
+/// A JSONL written this recently without a lock is probably being appended to by the CLI
+const ACTIVE_WRITE_SECS: u64 = 30;
+
+/// When this workbench last released each session's lock; JSONL writes before that
+/// were made by our own runs
+static RELEASED_AT: Lazy<Mutex<HashMap<String, SystemTime>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Contents of `~/.claude/session-locks/<session_id>.lock`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionLockInfo {
+    pub session_id: String,
+    /// Process writing the session: the Claude CLI once started, the workbench before that
+    pub pid: u32,
+    /// Workbench process that took the lock
+    pub owner_pid: u32,
+    pub acquired_at: DateTime<Utc>,
+}
+
+/// Returned by `get_session_lock`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionLockStatus {
+    pub session_id: String,
+    pub lock: Option<SessionLockInfo>,
+    /// The lock belongs to this workbench instance
+    pub held_by_us: bool,
+    /// The locking process is gone; the lock can be force-unlocked safely
+    pub stale: bool,
+    /// The JSONL was written in the last few seconds without a lock, e.g. by the CLI
+    pub recently_modified: bool,
+}
+
+/// Directory holding the session lock files
+pub(crate) fn lock_dir() -> Result<PathBuf, String> {
+    Ok(get_claude_dir().map_err(|e| e.to_string())?.join("session-locks"))
+}
+
+fn lock_path(session_id: &str) -> Result<PathBuf, String> {
+    let dir = lock_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create lock directory: {}", e))?;
+    Ok(dir.join(format!("{}.lock", session_id)))
+}
+
+pub(crate) fn is_pid_alive(pid: u32) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid)])
+            .args(["/FO", "CSV", "/NH"])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid)))
+            .unwrap_or(false)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+}
+
+fn read_lock(session_id: &str) -> Option<SessionLockInfo> {
+    let content = fs::read_to_string(lock_path(session_id).ok()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn is_ours(lock: &SessionLockInfo) -> bool {
+    lock.owner_pid == std::process::id()
+}
+
+/// Whether the lock still protects a running writer
+pub(crate) fn is_live(lock: &SessionLockInfo) -> bool {
+    is_pid_alive(lock.pid) || (lock.owner_pid != lock.pid && is_pid_alive(lock.owner_pid))
+}
+
+/// Whether something other than our finished runs wrote the JSONL in the last
+/// `ACTIVE_WRITE_SECS`, e.g. the CLI in a terminal
+fn recently_modified(session_id: &str) -> bool {
+    let Some(modified) = find_session_file(session_id, None)
+        .ok()
+        .flatten()
+        .and_then(|(_, path)| fs::metadata(path).ok())
+        .and_then(|metadata| metadata.modified().ok())
+    else {
+        return false;
+    };
+    let recent = modified
+        .elapsed()
+        .map_or(false, |age| age.as_secs() < ACTIVE_WRITE_SECS);
+    let released_at = RELEASED_AT
+        .lock()
+        .ok()
+        .and_then(|released| released.get(session_id).copied());
+    recent && released_at.map_or(true, |released_at| modified > released_at)
+}
+
+/// Current lock state of a session
+pub fn lock_status(session_id: &str) -> SessionLockStatus {
+    let lock = read_lock(session_id);
+    let held_by_us = lock.as_ref().map_or(false, is_ours);
+    let stale = lock.as_ref().map_or(false, |lock| !is_live(lock));
+    SessionLockStatus {
+        session_id: session_id.to_string(),
+        recently_modified: lock.is_none() && recently_modified(session_id),
+        lock,
+        held_by_us,
+        stale,
+    }
+}
+
+/// Lock a session for writing by `pid`.
+///
+/// A run locks its session with the workbench's pid before spawning and hands the
+/// lock to its CLI process once that starts. Any other acquisition of a lock whose
+/// process is alive fails, including one held by another run of this workbench. A
+/// stale lock is replaced. Without a lock, a JSONL something else wrote in the
+/// last few seconds counts as in use.
+pub fn acquire(session_id: &str, pid: u32) -> Result<(), String> {
+    let path = lock_path(session_id)?;
+    let own_pid = std::process::id();
+    let info = SessionLockInfo {
+        session_id: session_id.to_string(),
+        pid,
+        owner_pid: own_pid,
+        acquired_at: Utc::now(),
+    };
+    let content = serde_json::to_string_pretty(&info).map_err(|e| e.to_string())?;
+
+    // A run's own CLI process is the writer the check would see
+    if pid == own_pid && read_lock(session_id).is_none() && recently_modified(session_id) {
+        return Err(format!(
+            "Session {} was written in the last {} seconds by another process, probably the Claude CLI. Wait for it to finish.",
+            session_id, ACTIVE_WRITE_SECS
+        ));
+    }
+
+    // create_new makes taking a free lock atomic; on conflict inspect the holder and retry once
+    for _ in 0..2 {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                return file
+                    .write_all(content.as_bytes())
+                    .map_err(|e| format!("Failed to write session lock: {}", e));
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(format!("Failed to create session lock: {}", e)),
+        }
+
+        match read_lock(session_id) {
+            Some(existing) if is_ours(&existing) => {
+                // Only the run holding the lock may hand it to its CLI process
+                let handover = existing.pid == pid || (existing.pid == own_pid && pid != own_pid);
+                if !handover && is_pid_alive(existing.pid) {
+                    return Err(format!(
+                        "Session {} is already being written by another run (process {}, locked at {}). Wait for it to finish.",
+                        session_id,
+                        existing.pid,
+                        existing.acquired_at.to_rfc3339()
+                    ));
+                }
+                return fs::write(&path, &content).map_err(|e| format!("Failed to update session lock: {}", e));
+            }
+            Some(existing) if is_live(&existing) => {
+                return Err(format!(
+                    "Session {} is in use by process {} (locked at {}). Close it there, or force unlock if that process is gone.",
+                    session_id,
+                    existing.pid,
+                    existing.acquired_at.to_rfc3339()
+                ));
+            }
+            existing => {
+                log::warn!(
+                    "Replacing stale lock on session {} (pid {:?})",
+                    session_id,
+                    existing.map(|lock| lock.pid)
+                );
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+    Err(format!("Failed to lock session {}", session_id))
+}
+
+/// Release a lock taken by this workbench instance; foreign locks are left alone
+pub fn release(session_id: &str) {
+    if read_lock(session_id).map_or(false, |lock| is_ours(&lock)) {
+        if let Ok(path) = lock_path(session_id) {
+            if let Err(e) = fs::remove_file(&path) {
+                log::warn!("Failed to release lock on session {}: {}", session_id, e);
+            }
+        }
+        if let Ok(mut released) = RELEASED_AT.lock() {
+            released.insert(session_id.to_string(), SystemTime::now());
+        }
+    }
+}
+
+/// Get the lock state of a session, to warn before starting a conflicting run
+#[tauri::command]
+pub async fn get_session_lock(session_id: String) -> Result<SessionLockStatus, String> {
+    Ok(lock_status(&session_id))
+}
+
+/// Remove a session's lock regardless of its owner, e.g. after a crash left it behind.
+///
+/// Returns false if the session was not locked.
+#[tauri::command]
+pub async fn force_unlock_session(session_id: String) -> Result<bool, String> {
+    let path = lock_path(&session_id)?;
+    if !path.exists() {
+        return Ok(false);
+    }
+    if let Some(lock) = read_lock(&session_id) {
+        log::warn!(
+            "Force-unlocking session {} held by process {} (live: {})",
+            session_id,
+            lock.pid,
+            is_live(&lock)
+        );
+    }
+    fs::remove_file(&path).map_err(|e| format!("Failed to remove session lock: {}", e))?;
+    Ok(true)
+}
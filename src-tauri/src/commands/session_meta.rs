@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use super::claude::get_claude_dir;
+
+/// Bumped when the sidecar layout changes; older sidecars are rebuilt
+const SESSION_META_VERSION: u32 = 2;
+
+/// Maximum title length derived from the first message
+const TITLE_MAX_CHARS: usize = 80;
+
+const CAVEAT_MARKER: &str = "Caveat: The messages below were generated by the user while running local commands";
+
+/// Cached session listing metadata, stored as `<session>.meta.json` next to the JSONL file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionMeta {
+    pub version: u32,
+    /// First user message content (if available)
+    pub first_message: Option<String>,
+    /// Timestamp of the first user message (if available)
+    pub message_timestamp: Option<String>,
+    /// Short title derived from the first message
+    pub title: Option<String>,
+    pub message_count: u64,
+    pub user_messages: u64,
+    pub assistant_messages: u64,
+    /// Timestamp of the latest entry
+    pub last_activity: Option<String>,
+    /// Bytes of the JSONL file already folded into this metadata (complete lines only)
+    pub scanned_bytes: u64,
+    /// JSONL modification time (unix seconds) when the sidecar was written
+    pub jsonl_modified: u64,
+    /// Operator id configured when the session was first scanned with new activity
+    #[serde(default)]
+    pub operator_id: Option<String>,
+    /// Complete lines that are not valid JSON
+    pub malformed_lines: u64,
+    /// First working directory recorded in the transcript
+    pub cwd: Option<String>,
+}
+
+/// Path of the sidecar for a session JSONL file
+pub fn sidecar_path(jsonl_path: &Path) -> PathBuf {
+    jsonl_path.with_extension("meta.json")
+}
+
+fn modified_secs(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn read_sidecar(jsonl_path: &Path) -> Option<SessionMeta> {
+    let content = fs::read_to_string(sidecar_path(jsonl_path)).ok()?;
+    serde_json::from_str::<SessionMeta>(&content)
+        .ok()
+        .filter(|meta| meta.version == SESSION_META_VERSION)
+}
+
+fn write_sidecar(jsonl_path: &Path, meta: &SessionMeta) -> Result<(), String> {
+    let path = sidecar_path(jsonl_path);
+    let tmp = path.with_extension("json.tmp");
+    let content = serde_json::to_string(meta).map_err(|e| e.to_string())?;
+    fs::write(&tmp, content).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+fn make_title(message: &str) -> String {
+    let first_line = message.lines().find(|l| !l.trim().is_empty()).unwrap_or("").trim();
+    if first_line.chars().count() > TITLE_MAX_CHARS {
+        let truncated: String = first_line.chars().take(TITLE_MAX_CHARS - 1).collect();
+        format!("{}…", truncated)
+    } else {
+        first_line.to_string()
+    }
+}
+
+/// Fold one JSONL line into the metadata
+fn apply_line(meta: &mut SessionMeta, line: &str) {
+    let Ok(entry) = serde_json::from_str::<serde_json::Value>(line) else {
+        if !line.trim().is_empty() {
+            meta.malformed_lines += 1;
+        }
+        return;
+    };
+    if meta.cwd.is_none() {
+        meta.cwd = entry.get("cwd").and_then(|c| c.as_str()).map(str::to_string);
+    }
+
+    let timestamp = entry.get("timestamp").and_then(|t| t.as_str());
+    if let Some(timestamp) = timestamp {
+        meta.last_activity = Some(timestamp.to_string());
+    }
+
+    let Some(message) = entry.get("message") else {
+        return;
+    };
+    meta.message_count += 1;
+    match message.get("role").and_then(|r| r.as_str()) {
+        Some("user") => meta.user_messages += 1,
+        Some("assistant") => meta.assistant_messages += 1,
+        _ => {}
+    }
+
+    // Same rules as the JSONL scan used for listings: string content only, skipping command output
+    if meta.first_message.is_none() && message.get("role").and_then(|r| r.as_str()) == Some("user") {
+        if let Some(content) = message.get("content").and_then(|c| c.as_str()) {
+            if !content.contains(CAVEAT_MARKER)
+                && !content.starts_with("<command-name>")
+                && !content.starts_with("<local-command-stdout>")
+            {
+                meta.title = Some(make_title(content));
+                meta.first_message = Some(content.to_string());
+                meta.message_timestamp = timestamp.map(str::to_string);
+            }
+        }
+    }
+}
+
+/// Scan the JSONL from `meta.scanned_bytes`, folding in every complete line
+fn scan_from(jsonl_path: &Path, meta: &mut SessionMeta) -> Result<(), String> {
+    let mut file = fs::File::open(jsonl_path).map_err(|e| e.to_string())?;
+    file.seek(SeekFrom::Start(meta.scanned_bytes)).map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(file);
+
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let read = reader.read_until(b'\n', &mut buf).map_err(|e| e.to_string())?;
+        // Stop at EOF or at a line that is still being written
+        if read == 0 || buf.last() != Some(&b'\n') {
+            break;
+        }
+        apply_line(meta, &String::from_utf8_lossy(&buf));
+        meta.scanned_bytes += read as u64;
+    }
+    Ok(())
+}
+
+/// Load a session's metadata, updating the sidecar incrementally when the JSONL has grown
+/// and rebuilding it when the JSONL was rewritten
+pub fn load_session_meta(jsonl_path: &Path) -> Result<SessionMeta, String> {
+    let metadata = fs::metadata(jsonl_path).map_err(|e| e.to_string())?;
+    let size = metadata.len();
+    let modified = modified_secs(&metadata);
+
+    let mut meta = match read_sidecar(jsonl_path) {
+        Some(meta) if meta.scanned_bytes == size && meta.jsonl_modified == modified => return Ok(meta),
+        // Appended since the last scan: continue where it stopped
+        Some(meta) if meta.scanned_bytes <= size => meta,
+        // Truncated or rewritten: start over
+        _ => SessionMeta {
+            version: SESSION_META_VERSION,
+            ..Default::default()
+        },
+    };
+
+    let before = meta.scanned_bytes;
+    scan_from(jsonl_path, &mut meta)?;
+    if meta.scanned_bytes != before && meta.operator_id.is_none() {
+        meta.operator_id = super::team_usage::current_operator_id();
+    }
+    if meta.scanned_bytes != before || meta.jsonl_modified != modified {
+        meta.jsonl_modified = modified;
+        if let Err(e) = write_sidecar(jsonl_path, &meta) {
+            log::debug!("Failed to write session sidecar for {:?}: {}", jsonl_path, e);
+        }
+    }
+    Ok(meta)
+}
+
+/// Operator id stored in a session's sidecar, without rescanning the session
+pub fn recorded_operator_id(session_id: &str) -> Option<String> {
+    let (_, path) = find_session_file(session_id, None).ok()??;
+    read_sidecar(&path)?.operator_id
+}
+
+/// Locate a session's JSONL file under ~/.claude/projects, in the given project or
+/// else in any project; returns the project ID with the path
+pub fn find_session_file(session_id: &str, project_id: Option<&str>) -> Result<Option<(String, PathBuf)>, String> {
+    let projects_dir = get_claude_dir().map_err(|e| e.to_string())?.join("projects");
+    let file_name = format!("{}.jsonl", session_id);
+
+    if let Some(project_id) = project_id {
+        let path = projects_dir.join(project_id).join(&file_name);
+        return Ok(path.is_file().then(|| (project_id.to_string(), path)));
+    }
+
+    let Ok(entries) = fs::read_dir(&projects_dir) else {
+        return Ok(None);
+    };
+    Ok(entries.flatten().find_map(|entry| {
+        let path = entry.path().join(&file_name);
+        path.is_file()
+            .then(|| (entry.file_name().to_string_lossy().to_string(), path))
+    }))
+}
+
+/// Refresh a session's sidecar in the background, e.g. after a streamed turn completes
+pub fn spawn_refresh(session_id: String) {
+    tokio::task::spawn_blocking(move || {
+        if let Ok(Some((_, path))) = find_session_file(&session_id, None) {
+            if let Err(e) = load_session_meta(&path) {
+                log::debug!("Failed to refresh session metadata for {}: {}", session_id, e);
+            }
+        }
+    });
+}
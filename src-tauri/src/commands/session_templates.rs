@@ -0,0 +1,216 @@
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+use super::agents::AgentDb;
+use super::mcp::MCPServerConfig;
+use super::permission_config::ClaudePermissionConfig;
+use super::run_dedupe::RunHandle;
+
+/// Permission presets a template may use. `legacy` is excluded: skipping permission
+/// checks is only available through the scoped, expiring safety setting.
+pub const TEMPLATE_PERMISSION_PRESETS: &[&str] = &["development", "safe", "interactive"];
+
+/// Everything a template applies when it starts a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTemplateConfig {
+    pub model: String,
+    /// `development`, `safe` or `interactive`; None keeps the configured permissions
+    pub permission_preset: Option<String>,
+    /// Directories passed to `--add-dir`
+    #[serde(default)]
+    pub extra_dirs: Vec<String>,
+    /// MCP servers added for the session, in `.mcp.json` format
+    #[serde(default)]
+    pub mcp_servers: HashMap<String, MCPServerConfig>,
+    /// CLAUDE.md-style instructions appended to the system prompt
+    pub claude_md: Option<String>,
+    pub initial_prompt: String,
+    pub provider_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTemplate {
+    pub id: i64,
+    pub name: String,
+    pub config: SessionTemplateConfig,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+pub fn permission_preset(name: &str) -> Option<ClaudePermissionConfig> {
+    match name {
+        "development" => Some(ClaudePermissionConfig::development_mode()),
+        "safe" => Some(ClaudePermissionConfig::safe_mode()),
+        "interactive" => Some(ClaudePermissionConfig::interactive_mode()),
+        _ => None,
+    }
+}
+
+fn validate(name: &str, config: &SessionTemplateConfig) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Template name is empty".to_string());
+    }
+    if config.model.trim().is_empty() {
+        return Err("Template model is empty".to_string());
+    }
+    if config.initial_prompt.trim().is_empty() {
+        return Err("Template initial prompt is empty".to_string());
+    }
+    if let Some(preset) = &config.permission_preset {
+        if !TEMPLATE_PERMISSION_PRESETS.contains(&preset.as_str()) {
+            return Err(format!(
+                "Unknown permission preset '{}', expected one of: {}",
+                preset,
+                TEMPLATE_PERMISSION_PRESETS.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn row_to_template(row: &rusqlite::Row) -> rusqlite::Result<SessionTemplate> {
+    let config: String = row.get(2)?;
+    Ok(SessionTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        config: serde_json::from_str(&config).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+/// Write the template's MCP servers to a config file for `--mcp-config`
+fn write_mcp_config(template: &SessionTemplate) -> Result<String, String> {
+    let path = std::env::temp_dir().join(format!("claude-workbench-template-{}.mcp.json", template.id));
+    let content = serde_json::to_string_pretty(&serde_json::json!({
+        "mcpServers": template.config.mcp_servers
+    }))
+    .map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write MCP config: {}", e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// CLI arguments for the template's extra dirs, MCP servers and instructions
+fn template_args(template: &SessionTemplate) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    for dir in &template.config.extra_dirs {
+        if Path::new(dir).is_dir() {
+            args.push("--add-dir".to_string());
+            args.push(dir.clone());
+        } else {
+            log::warn!("Template '{}' skips missing directory {}", template.name, dir);
+        }
+    }
+    if !template.config.mcp_servers.is_empty() {
+        args.push("--mcp-config".to_string());
+        args.push(write_mcp_config(template)?);
+    }
+    if let Some(claude_md) = template.config.claude_md.as_ref().filter(|c| !c.trim().is_empty()) {
+        args.push("--append-system-prompt".to_string());
+        args.push(claude_md.clone());
+    }
+    Ok(args)
+}
+
+/// Save a new session template
+#[tauri::command]
+pub async fn create_session_template(
+    db: State<'_, AgentDb>,
+    name: String,
+    config: SessionTemplateConfig,
+) -> Result<SessionTemplate, String> {
+    validate(&name, &config)?;
+    let config_json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO session_templates (name, config) VALUES (?1, ?2)",
+        params![name.trim(), config_json],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _) if err.code == rusqlite::ErrorCode::ConstraintViolation => {
+            format!("A session template named '{}' already exists", name.trim())
+        }
+        e => e.to_string(),
+    })?;
+
+    conn.query_row(
+        "SELECT id, name, config, created_at, updated_at FROM session_templates WHERE id = ?1",
+        params![conn.last_insert_rowid()],
+        row_to_template,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// List session templates by name
+#[tauri::command]
+pub async fn list_session_templates(db: State<'_, AgentDb>) -> Result<Vec<SessionTemplate>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT id, name, config, created_at, updated_at FROM session_templates ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let templates = stmt
+        .query_map([], row_to_template)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(templates)
+}
+
+/// Delete a session template
+#[tauri::command]
+pub async fn delete_session_template(db: State<'_, AgentDb>, template_id: i64) -> Result<(), String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM session_templates WHERE id = ?1", params![template_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Start a new session in `project_path` with the template's model, permissions,
+/// extra dirs, MCP servers and instructions, running its initial prompt
+#[tauri::command]
+pub async fn start_session_from_template(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    template_id: i64,
+    project_path: String,
+) -> Result<RunHandle, String> {
+    let template = {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        conn.query_row(
+            "SELECT id, name, config, created_at, updated_at FROM session_templates WHERE id = ?1",
+            params![template_id],
+            row_to_template,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Session template {} not found", template_id))?
+    };
+    log::info!("Starting session from template '{}' in {}", template.name, project_path);
+
+    let permissions = template.config.permission_preset.as_deref().and_then(permission_preset);
+    let args = template_args(&template)?;
+    let config = template.config;
+    super::claude::execute_claude_code_with(
+        app,
+        project_path,
+        config.initial_prompt,
+        config.model,
+        config.provider_id,
+        super::claude::RunOverrides {
+            permissions,
+            extra_args: args,
+            profile: None,
+            max_cost_usd: None,
+            env_profile: None,
+            working_dir: None,
+        },
+    )
+    .await
+    .and_then(|run_id| super::run_dedupe::run_handle(run_id, "execute"))
+}
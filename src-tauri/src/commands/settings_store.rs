@@ -0,0 +1,189 @@
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use super::agents::AgentDb;
+use super::claude::get_claude_dir;
+use super::events::AppEvent;
+
+/// Project directory IDs hidden from the project list
+pub const HIDDEN_PROJECTS: &str = "hidden_projects";
+
+/// The global `ClaudeExecutionConfig`
+pub const EXECUTION_CONFIG: &str = "execution_config";
+
+/// Projects the duplicate merge must leave alone
+pub const MERGE_EXCLUSIONS: &str = "project_merge_exclusions";
+
+/// Named environment profiles runs can be started with
+pub const ENV_PROFILES: &str = "env_profiles";
+
+/// Ports services were moved from after another app took them
+pub const SERVICE_PORTS: &str = "service_ports";
+
+/// Claude CLI binary chosen by the user or found by discovery
+pub const CLAUDE_BINARY_PATH: &str = "claude_binary_path";
+
+/// Provider selected with `switch_provider_config`, used by processes without a
+/// provider of their own
+pub const DEFAULT_PROVIDER: &str = "default_provider";
+
+/// JSON files in ~/.claude whose contents moved into `app_settings`, with their keys
+const MIGRATED_FILES: &[(&str, &str)] = &[
+    ("hidden_projects.json", HIDDEN_PROJECTS),
+    ("execution_config.json", EXECUTION_CONFIG),
+    ("project_merge_exclusions.json", MERGE_EXCLUSIONS),
+];
+
+/// Appended to a migrated file's name; the file is kept as a backup
+const BACKUP_SUFFIX: &str = ".bak";
+
+/// Payload of `setting-changed`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SettingChanged {
+    pub key: String,
+    #[ts(type = "unknown")]
+    pub value: Value,
+}
+
+/// Read a JSON value of `app_settings`; None when the key is not set
+pub fn get<T: DeserializeOwned>(conn: &Connection, key: &str) -> Result<Option<T>, String> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM app_settings WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+        .map_err(|e| format!("Failed to read setting {}: {}", key, e))?;
+    value
+        .map(|value| serde_json::from_str(&value))
+        .transpose()
+        .map_err(|e| format!("Failed to parse setting {}: {}", key, e))
+}
+
+/// Like `get`, with the default for a missing or unreadable value
+pub fn get_or_default<T: DeserializeOwned + Default>(conn: &Connection, key: &str) -> T {
+    match get(conn, key) {
+        Ok(value) => value.unwrap_or_default(),
+        Err(e) => {
+            log::warn!("{}, using the default", e);
+            T::default()
+        }
+    }
+}
+
+fn write(conn: &Connection, key: &str, value: &Value) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO app_settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value.to_string()],
+    )
+    .map_err(|e| format!("Failed to save setting {}: {}", key, e))?;
+    Ok(())
+}
+
+fn changed(app: &AppHandle, key: &str, value: Value) {
+    let _ = AppEvent::SettingChanged(SettingChanged { key: key.to_string(), value }).emit(app);
+}
+
+/// Store a JSON value in `app_settings` and emit `setting-changed`
+pub fn set<T: Serialize>(app: &AppHandle, key: &str, value: &T) -> Result<(), String> {
+    let db = app.state::<AgentDb>();
+    let conn = db.0.get()?;
+    set_in(app, &conn, key, value)
+}
+
+/// Like `set`, through a connection of the caller's, e.g. before the pool is managed
+pub fn set_in<T: Serialize>(app: &AppHandle, conn: &Connection, key: &str, value: &T) -> Result<(), String> {
+    let value = serde_json::to_value(value).map_err(|e| e.to_string())?;
+    write(conn, key, &value)?;
+    changed(app, key, value);
+    Ok(())
+}
+
+/// Remove a value from `app_settings`; `setting-changed` reports it as null
+pub fn remove(app: &AppHandle, key: &str) -> Result<(), String> {
+    let db = app.state::<AgentDb>();
+    let conn = db.0.get()?;
+    remove_in(app, &conn, key)
+}
+
+/// Like `remove`, through a connection of the caller's
+pub fn remove_in(app: &AppHandle, conn: &Connection, key: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM app_settings WHERE key = ?1", [key])
+        .map_err(|e| format!("Failed to remove setting {}: {}", key, e))?;
+    changed(app, key, Value::Null);
+    Ok(())
+}
+
+/// Read, modify and write back a value in one transaction, so concurrent updates
+/// cannot overwrite each other. `modify` returns whether it changed the value;
+/// unchanged values are not written. Returns the resulting value.
+pub fn update<T, F>(app: &AppHandle, key: &str, modify: F) -> Result<T, String>
+where
+    T: DeserializeOwned + Serialize + Default,
+    F: FnOnce(&mut T) -> Result<bool, String>,
+{
+    let db = app.state::<AgentDb>();
+    let mut conn = db.0.get()?;
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let mut current: T = get_or_default(&tx, key);
+    if !modify(&mut current)? {
+        return Ok(current);
+    }
+    let value = serde_json::to_value(&current).map_err(|e| e.to_string())?;
+    write(&tx, key, &value)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    changed(app, key, value);
+    Ok(current)
+}
+
+/// Move the state kept in JSON files under ~/.claude into `app_settings`.
+///
+/// Each file is renamed to `<name>.bak` afterwards. A file whose key is already set
+/// is left over from an older version and only backed up; so is an unreadable one.
+pub fn migrate_json_files(conn: &Connection) {
+    let Ok(claude_dir) = get_claude_dir() else {
+        return;
+    };
+    for (file, key) in MIGRATED_FILES {
+        let path = claude_dir.join(file);
+        if !path.is_file() {
+            continue;
+        }
+        let stored = conn
+            .query_row("SELECT 1 FROM app_settings WHERE key = ?1", [key], |_| Ok(()))
+            .optional();
+        match stored {
+            Ok(Some(())) => log::info!("{} is already in the settings store", file),
+            Ok(None) => {
+                let parsed = fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|content| {
+                    serde_json::from_str::<Value>(&content).map_err(|e| e.to_string())
+                });
+                match parsed {
+                    Ok(value) => {
+                        // Keep the file in place to retry on the next start
+                        if let Err(e) = write(conn, key, &value) {
+                            log::warn!("Failed to migrate {}: {}", file, e);
+                            continue;
+                        }
+                        log::info!("Migrated {} into the settings store", file);
+                    }
+                    Err(e) => log::warn!("{} is unreadable, keeping it as a backup only: {}", file, e),
+                }
+            }
+            Err(e) => {
+                log::warn!("Failed to migrate {}: {}", file, e);
+                continue;
+            }
+        }
+        let backup = claude_dir.join(format!("{}{}", file, BACKUP_SUFFIX));
+        if let Err(e) = fs::rename(&path, &backup) {
+            log::warn!("Failed to move {} to {:?}: {}", file, backup, e);
+        }
+    }
+}
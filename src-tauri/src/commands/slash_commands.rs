@@ -679,8 +679,9 @@ pub async fn slash_command_save(
     full_content.push_str(&content);
     
     // Write file
-    fs::write(&file_path, &full_content)
+    crate::config_file::write(&file_path, &full_content)
         .map_err(|e| format!("Failed to write command file: {}", e))?;
+    super::global_search::invalidate_search_index();
     
     // Load and return the saved command
     load_command_from_file(&file_path, &base_dir, &scope)
@@ -717,6 +718,7 @@ pub async fn slash_command_delete(command_id: String, project_path: Option<Strin
     if let Some(parent) = Path::new(&command.file_path).parent() {
         let _ = remove_empty_dirs(parent);
     }
+    super::global_search::invalidate_search_index();
     
     Ok(format!("Deleted command: {}", command.full_command))
 }
@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use ts_rs::TS;
+
+use super::events::AppEvent;
+
+/// Why the Claude CLI could not be started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export)]
+pub enum SpawnFailureCause {
+    /// No Claude installation was found, or the selected one no longer exists
+    BinaryMissing,
+    /// The installed Node.js is too old for the CLI
+    NodeVersion,
+    /// `node` (or the CLI's shim) is not on the PATH the app runs with
+    PathIssue,
+    /// The OS refused to execute the binary (EPERM / EACCES / access denied)
+    PermissionDenied,
+    /// Antivirus or endpoint policy blocked the binary
+    AntivirusBlocked,
+    Unknown,
+}
+
+/// Something the user can do to fix a failed launch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+#[ts(export)]
+pub enum RecoveryAction {
+    /// A shell command to run in a terminal
+    RunCommand { label: String, command: String },
+    /// A settings tab, e.g. `general` for the Claude installation or `environment` for PATH
+    OpenSettings { label: String, section: String },
+    /// Re-run the installation check (`check_claude_version`)
+    RunDoctor { label: String },
+    OpenUrl { label: String, url: String },
+}
+
+/// A classified launch failure. Payload of `claude-spawn-failed`.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SpawnFailure {
+    pub cause: SpawnFailureCause,
+    /// One sentence explaining the cause
+    pub summary: String,
+    /// The original error or stderr output
+    pub detail: String,
+    /// Most useful first
+    pub actions: Vec<RecoveryAction>,
+}
+
+const INSTALL_COMMAND: &str = "npm install -g @anthropic-ai/claude-code";
+const NODE_DOWNLOAD_URL: &str = "https://nodejs.org/en/download";
+
+/// Stderr fragments of a Node.js that is too old for the CLI
+const NODE_VERSION_PATTERNS: &[&str] = &[
+    "requires node",
+    "node.js version",
+    "unsupported engine",
+    "syntaxerror: unexpected token",
+    "err_require_esm",
+    "node: bad option",
+];
+
+/// `node` itself cannot be found when the CLI's shim runs
+const NODE_MISSING_PATTERNS: &[&str] = &[
+    "env: node",
+    "env: 'node'",
+    "node: not found",
+    "node: command not found",
+    "'node' is not recognized",
+];
+
+const BINARY_MISSING_PATTERNS: &[&str] = &[
+    "claude cli not found",
+    "no working claude cli installation",
+    "no such file or directory",
+    "cannot find the file",
+    "is not recognized as an internal or external command",
+    "enoent",
+];
+
+const PERMISSION_PATTERNS: &[&str] = &["permission denied", "operation not permitted", "eperm", "eacces", "access is denied"];
+
+const ANTIVIRUS_PATTERNS: &[&str] = &[
+    "virus",
+    "potentially unwanted",
+    "windows defender",
+    "smartscreen",
+    "blocked by group policy",
+    "blocked by your administrator",
+];
+
+/// The `N` of an `(os error N)` suffix from `std::io::Error`
+fn os_error_code(message: &str) -> Option<i32> {
+    let start = message.rfind("os error ")? + "os error ".len();
+    let digits: String = message[start..].chars().take_while(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+/// ERROR_ACCESS_DENIED on Windows, EPERM / EACCES elsewhere
+fn is_permission_code(code: Option<i32>) -> bool {
+    if cfg!(windows) {
+        code == Some(5)
+    } else {
+        matches!(code, Some(1 | 13))
+    }
+}
+
+fn classify(message: &str) -> SpawnFailureCause {
+    let lower = message.to_lowercase();
+    let matches = |patterns: &[&str]| patterns.iter().any(|pattern| lower.contains(pattern));
+    let code = os_error_code(message);
+
+    // ERROR_VIRUS_INFECTED, ERROR_VIRUS_DELETED, ERROR_ACCESS_DISABLED_BY_POLICY
+    if matches(ANTIVIRUS_PATTERNS) || matches!(code, Some(225 | 226 | 1260)) {
+        SpawnFailureCause::AntivirusBlocked
+    } else if matches(NODE_VERSION_PATTERNS) {
+        SpawnFailureCause::NodeVersion
+    } else if matches(NODE_MISSING_PATTERNS) || code == Some(193) {
+        // 193 (not a valid Win32 application) means a shim was run without its interpreter
+        SpawnFailureCause::PathIssue
+    } else if matches(PERMISSION_PATTERNS) || is_permission_code(code) {
+        SpawnFailureCause::PermissionDenied
+    } else if matches(BINARY_MISSING_PATTERNS) || matches!(code, Some(2 | 3)) {
+        SpawnFailureCause::BinaryMissing
+    } else {
+        SpawnFailureCause::Unknown
+    }
+}
+
+fn run_command(label: &str, command: impl Into<String>) -> RecoveryAction {
+    RecoveryAction::RunCommand {
+        label: label.to_string(),
+        command: command.into(),
+    }
+}
+
+fn open_settings(label: &str, section: &str) -> RecoveryAction {
+    RecoveryAction::OpenSettings {
+        label: label.to_string(),
+        section: section.to_string(),
+    }
+}
+
+fn run_doctor() -> RecoveryAction {
+    RecoveryAction::RunDoctor {
+        label: "Check the Claude installation again".to_string(),
+    }
+}
+
+/// Classify a launch error and suggest how to recover.
+/// `claude_path` is the binary that was started, when one was found.
+pub fn analyze(error: &str, claude_path: Option<&str>) -> SpawnFailure {
+    let cause = classify(error);
+    let which = if cfg!(windows) { "where" } else { "which" };
+
+    let (summary, actions) = match cause {
+        SpawnFailureCause::BinaryMissing => (
+            "Claude Code is not installed, or the selected installation no longer exists.",
+            vec![
+                run_command("Install Claude Code", INSTALL_COMMAND),
+                open_settings("Choose a Claude installation", "general"),
+                run_doctor(),
+            ],
+        ),
+        SpawnFailureCause::NodeVersion => (
+            "The installed Node.js is too old for Claude Code; version 18 or newer is required.",
+            vec![
+                RecoveryAction::OpenUrl {
+                    label: "Download a current Node.js".to_string(),
+                    url: NODE_DOWNLOAD_URL.to_string(),
+                },
+                run_command("Check the Node.js version", "node --version"),
+                run_doctor(),
+            ],
+        ),
+        SpawnFailureCause::PathIssue => (
+            "Node.js is not on the PATH the app runs with. Apps started from the desktop often \
+             miss PATH changes made in shell profiles.",
+            vec![
+                run_command("Find Node.js", format!("{} node", which)),
+                open_settings("Add the Node.js directory to PATH", "environment"),
+                open_settings("Choose a Claude installation", "general"),
+                run_doctor(),
+            ],
+        ),
+        SpawnFailureCause::PermissionDenied => {
+            let mut actions = Vec::new();
+            if let Some(path) = claude_path {
+                if cfg!(windows) {
+                    actions.push(run_command(
+                        "Unblock the downloaded binary (PowerShell)",
+                        format!("Unblock-File -Path \"{}\"", path),
+                    ));
+                } else {
+                    actions.push(run_command("Make the binary executable", format!("chmod +x \"{}\"", path)));
+                }
+            }
+            actions.push(open_settings("Choose an installation in your user directory", "general"));
+            actions.push(run_doctor());
+            ("The operating system refused to run the Claude binary.", actions)
+        }
+        SpawnFailureCause::AntivirusBlocked => {
+            let mut actions = Vec::new();
+            if let Some(dir) = claude_path.and_then(|path| std::path::Path::new(path).parent()) {
+                if cfg!(windows) {
+                    actions.push(run_command(
+                        "Exclude the installation from Defender (PowerShell as administrator)",
+                        format!("Add-MpPreference -ExclusionPath \"{}\"", dir.display()),
+                    ));
+                }
+            }
+            actions.push(open_settings("Choose another Claude installation", "general"));
+            actions.push(run_doctor());
+            (
+                "Antivirus or a security policy blocked Claude Code. Ask your IT department to allow \
+                 it if you cannot add an exclusion.",
+                actions,
+            )
+        }
+        SpawnFailureCause::Unknown => (
+            "Claude Code could not be started.",
+            vec![run_doctor(), open_settings("Choose a Claude installation", "general")],
+        ),
+    };
+
+    SpawnFailure {
+        cause,
+        summary: summary.to_string(),
+        detail: error.to_string(),
+        actions,
+    }
+}
+
+/// Analyze a failed launch, emit `claude-spawn-failed` with the recovery actions and
+/// return the error for the command with the explanation appended
+pub fn report(app: &AppHandle, error: String, claude_path: Option<&str>) -> String {
+    let failure = analyze(&error, claude_path);
+    log::warn!("Claude launch failed ({:?}): {}", failure.cause, error);
+    if let Err(e) = AppEvent::ClaudeSpawnFailed(failure.clone()).emit(app) {
+        log::warn!("Failed to emit claude-spawn-failed event: {}", e);
+    }
+    format!("{} {}", error, failure.summary)
+}
+
+/// Report a process that exited before Claude initialized a session, when its
+/// stderr points at a known cause
+pub fn report_early_exit(app: &AppHandle, stderr: &str) {
+    let failure = analyze(stderr, None);
+    if failure.cause == SpawnFailureCause::Unknown {
+        return;
+    }
+    log::warn!("Claude exited during startup ({:?})", failure.cause);
+    if let Err(e) = AppEvent::ClaudeSpawnFailed(failure).emit(app) {
+        log::warn!("Failed to emit claude-spawn-failed event: {}", e);
+    }
+}
+
+/// Classify a launch error string and list recovery actions
+#[tauri::command]
+pub async fn diagnose_spawn_failure(error: String, claude_path: Option<String>) -> Result<SpawnFailure, String> {
+    Ok(analyze(&error, claude_path.as_deref()))
+}
@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::claude::get_claude_dir;
+use super::session_lock::{is_live, is_pid_alive, lock_dir, SessionLockInfo};
+
+/// Lock files without a pid are orphaned once untouched this long; lock holders such
+/// as the CLI's config lock refresh theirs every few seconds
+const ORPHANED_LOCK_SECS: u64 = 10 * 60;
+
+/// Empty session files younger than this may still be getting their first line
+const EMPTY_SESSION_GRACE_SECS: u64 = 5 * 60;
+
+/// What kind of leftover an entry is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleStateKind {
+    /// Workbench session lock whose writer and owner processes are gone
+    SessionLock,
+    /// IDE integration lock (`~/.claude/ide/*.lock`) of a process that is gone
+    IdeLock,
+    /// Lock file or directory with no process to check, untouched for a long time
+    OrphanedLock,
+    /// Zero-byte session JSONL that resume chokes on
+    EmptySession,
+}
+
+/// One leftover found by `clean_stale_claude_state`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleStateEntry {
+    pub kind: StaleStateKind,
+    pub path: String,
+    /// Why the entry counts as stale
+    pub reason: String,
+    /// Whether it was deleted; always false on a dry run
+    pub removed: bool,
+    pub error: Option<String>,
+}
+
+/// Returned by `clean_stale_claude_state`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleStateReport {
+    pub dry_run: bool,
+    pub entries: Vec<StaleStateEntry>,
+    pub removed: usize,
+}
+
+fn age(path: &Path) -> Option<Duration> {
+    fs::symlink_metadata(path).ok()?.modified().ok()?.elapsed().ok()
+}
+
+fn untouched_long(path: &Path) -> bool {
+    age(path).map_or(false, |age| age.as_secs() > ORPHANED_LOCK_SECS)
+}
+
+fn entry(kind: StaleStateKind, path: &Path, reason: String) -> StaleStateEntry {
+    StaleStateEntry {
+        kind,
+        path: path.to_string_lossy().into_owned(),
+        reason,
+        removed: false,
+        error: None,
+    }
+}
+
+/// Whether a session's JSONL lives in the given project
+fn in_project(session_id: &str, project_id: &str) -> bool {
+    super::session_meta::find_session_file(session_id, Some(project_id))
+        .ok()
+        .flatten()
+        .is_some()
+}
+
+/// Session locks whose processes are gone, or unreadable and old
+fn find_session_locks(project_id: Option<&str>) -> Result<Vec<StaleStateEntry>, String> {
+    let mut found = Vec::new();
+    let Ok(entries) = fs::read_dir(lock_dir()?) else {
+        return Ok(found);
+    };
+    for path in entries.flatten().map(|e| e.path()) {
+        let Some(session_id) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".lock"))
+        else {
+            continue;
+        };
+        if project_id.map_or(false, |project_id| !in_project(session_id, project_id)) {
+            continue;
+        }
+        let lock = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<SessionLockInfo>(&content).ok());
+        match lock {
+            Some(lock) if !is_live(&lock) => {
+                let reason = if lock.pid == lock.owner_pid {
+                    format!("Process {} is not running", lock.pid)
+                } else {
+                    format!("Processes {} and {} are not running", lock.pid, lock.owner_pid)
+                };
+                found.push(entry(StaleStateKind::SessionLock, &path, reason))
+            }
+            Some(_) => {}
+            None if untouched_long(&path) => found.push(entry(
+                StaleStateKind::OrphanedLock,
+                &path,
+                "Unreadable session lock".to_string(),
+            )),
+            None => {}
+        }
+    }
+    Ok(found)
+}
+
+/// IDE integration locks whose process is gone
+fn find_ide_locks(claude_dir: &Path) -> Vec<StaleStateEntry> {
+    let mut found = Vec::new();
+    let Ok(entries) = fs::read_dir(claude_dir.join("ide")) else {
+        return found;
+    };
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.extension().and_then(|ext| ext.to_str()) != Some("lock") {
+            continue;
+        }
+        let pid = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .and_then(|lock| lock.get("pid").and_then(|pid| pid.as_u64()))
+            .and_then(|pid| u32::try_from(pid).ok());
+        match pid {
+            Some(pid) if !is_pid_alive(pid) => found.push(entry(
+                StaleStateKind::IdeLock,
+                &path,
+                format!("Process {} is not running", pid),
+            )),
+            Some(_) => {}
+            None if untouched_long(&path) => found.push(entry(
+                StaleStateKind::OrphanedLock,
+                &path,
+                "IDE lock without a process id".to_string(),
+            )),
+            None => {}
+        }
+    }
+    found
+}
+
+/// `*.lock` files and directories directly in `~/.claude`, plus the CLI's config lock
+/// next to it, that nothing has touched for a long time
+fn find_orphaned_locks(claude_dir: &Path) -> Vec<StaleStateEntry> {
+    let mut candidates: Vec<PathBuf> = fs::read_dir(claude_dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    if let Some(home) = claude_dir.parent() {
+        candidates.push(home.join(".claude.json.lock"));
+    }
+
+    candidates
+        .into_iter()
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("lock"))
+        .filter_map(|path| {
+            let age = age(&path)?;
+            (age.as_secs() > ORPHANED_LOCK_SECS).then(|| {
+                let reason = format!("Untouched for {} minutes", age.as_secs() / 60);
+                entry(StaleStateKind::OrphanedLock, &path, reason)
+            })
+        })
+        .collect()
+}
+
+/// Zero-byte session files that are neither new nor locked by a running session
+fn find_empty_sessions(claude_dir: &Path, project_id: Option<&str>) -> Vec<StaleStateEntry> {
+    let projects_dir = claude_dir.join("projects");
+    let project_dirs: Vec<PathBuf> = match project_id {
+        Some(project_id) => vec![projects_dir.join(project_id)],
+        None => fs::read_dir(&projects_dir)
+            .map(|entries| entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect())
+            .unwrap_or_default(),
+    };
+
+    let mut found = Vec::new();
+    for path in project_dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten().map(|e| e.path()))
+    {
+        if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if !metadata.is_file() || metadata.len() != 0 {
+            continue;
+        }
+        if age(&path).map_or(true, |age| age.as_secs() < EMPTY_SESSION_GRACE_SECS) {
+            continue;
+        }
+        let session_id = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+        if super::session_lock::lock_status(session_id).lock.as_ref().map_or(false, is_live) {
+            continue;
+        }
+        found.push(entry(
+            StaleStateKind::EmptySession,
+            &path,
+            "Empty session file".to_string(),
+        ));
+    }
+    found
+}
+
+/// Delete one leftover; lock directories are only removed when empty
+fn remove(path: &Path) -> Result<(), String> {
+    let metadata = fs::symlink_metadata(path).map_err(|e| e.to_string())?;
+    if metadata.is_dir() {
+        fs::remove_dir(path).map_err(|e| e.to_string())
+    } else {
+        fs::remove_file(path).map_err(|e| e.to_string())
+    }
+}
+
+/// Find lock and state files the Claude CLI or a crashed workbench left behind: session
+/// and IDE locks of dead processes, long-untouched lock files, and empty session JSONL
+/// files that block resume. Removes them unless `dry_run` is set.
+///
+/// With `project_id`, only that project's session locks and session files are checked;
+/// global lock files are left for an unscoped run.
+#[tauri::command]
+pub async fn clean_stale_claude_state(
+    project_id: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<StaleStateReport, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    if let Some(project_id) = &project_id {
+        if project_id.contains(['/', '\\']) || project_id.contains("..") {
+            return Err(format!("Invalid project id: {}", project_id));
+        }
+    }
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let project_id = project_id.as_deref();
+
+    let mut entries = find_session_locks(project_id)?;
+    if project_id.is_none() {
+        entries.extend(find_ide_locks(&claude_dir));
+        entries.extend(find_orphaned_locks(&claude_dir));
+    }
+    entries.extend(find_empty_sessions(&claude_dir, project_id));
+
+    let mut removed = 0;
+    if !dry_run {
+        for entry in &mut entries {
+            match remove(Path::new(&entry.path)) {
+                Ok(()) => {
+                    entry.removed = true;
+                    removed += 1;
+                }
+                Err(e) => entry.error = Some(e),
+            }
+        }
+    }
+
+    log::info!(
+        "Stale Claude state: {} found, {} removed{}",
+        entries.len(),
+        removed,
+        if dry_run { " (dry run)" } else { "" }
+    );
+    Ok(StaleStateReport {
+        dry_run,
+        entries,
+        removed,
+    })
+}
@@ -0,0 +1,222 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+
+use super::agents::AgentDb;
+use super::settings_store;
+
+/// `app_settings` key holding the `StartupConfig` JSON
+const CONFIG_SETTING: &str = "startup_config";
+
+/// Optional subsystems that can be turned off by users who never use them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    Translator,
+    Router,
+    Subagents,
+}
+
+impl Subsystem {
+    fn as_str(self) -> &'static str {
+        match self {
+            Subsystem::Translator => "translator",
+            Subsystem::Router => "router",
+            Subsystem::Subagents => "subagents",
+        }
+    }
+}
+
+/// Which optional subsystems are enabled; everything is on by default
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupConfig {
+    /// Translation of prompts and responses
+    #[serde(default = "enabled")]
+    pub translator: bool,
+    /// claude-code-router usage attribution and health probes
+    #[serde(default = "enabled")]
+    pub router: bool,
+    /// Subagent specialization and routing
+    #[serde(default = "enabled")]
+    pub subagents: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        Self {
+            translator: true,
+            router: true,
+            subagents: true,
+        }
+    }
+}
+
+impl StartupConfig {
+    fn is_enabled(&self, subsystem: Subsystem) -> bool {
+        match subsystem {
+            Subsystem::Translator => self.translator,
+            Subsystem::Router => self.router,
+            Subsystem::Subagents => self.subagents,
+        }
+    }
+}
+
+/// How a startup step ran
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupMode {
+    /// Ran in setup before the window could show
+    Blocking,
+    /// Spawned from setup; launch did not wait for it
+    Background,
+}
+
+/// One step of app startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupTiming {
+    pub name: String,
+    pub mode: StartupMode,
+    /// Milliseconds after process start the step began
+    pub started_at_ms: u64,
+    /// None while a background step is still running
+    pub duration_ms: Option<u64>,
+}
+
+/// Returned by `get_startup_timings`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupTimings {
+    /// Milliseconds after process start setup finished, once it has
+    pub setup_finished_at_ms: Option<u64>,
+    pub steps: Vec<StartupTiming>,
+}
+
+static PROCESS_START: Lazy<Instant> = Lazy::new(Instant::now);
+
+static CONFIG: Lazy<Mutex<StartupConfig>> = Lazy::new(|| Mutex::new(StartupConfig::default()));
+
+static TIMINGS: Lazy<Mutex<StartupTimings>> = Lazy::new(|| {
+    Mutex::new(StartupTimings {
+        setup_finished_at_ms: None,
+        steps: Vec::new(),
+    })
+});
+
+fn elapsed_ms(since: Instant) -> u64 {
+    since.elapsed().as_millis() as u64
+}
+
+/// Start the startup clock; call first thing in `main`
+pub fn mark_process_start() {
+    Lazy::force(&PROCESS_START);
+}
+
+/// Mark the end of setup
+pub fn mark_setup_finished() {
+    if let Ok(mut timings) = TIMINGS.lock() {
+        timings.setup_finished_at_ms = Some(elapsed_ms(*PROCESS_START));
+    }
+    log::info!("Startup setup finished after {}ms", elapsed_ms(*PROCESS_START));
+}
+
+fn begin(name: &str, mode: StartupMode) -> usize {
+    let Ok(mut timings) = TIMINGS.lock() else {
+        return usize::MAX;
+    };
+    timings.steps.push(StartupTiming {
+        name: name.to_string(),
+        mode,
+        started_at_ms: elapsed_ms(*PROCESS_START),
+        duration_ms: None,
+    });
+    timings.steps.len() - 1
+}
+
+fn finish(index: usize, started: Instant) {
+    if let Ok(mut timings) = TIMINGS.lock() {
+        if let Some(step) = timings.steps.get_mut(index) {
+            step.duration_ms = Some(elapsed_ms(started));
+        }
+    }
+}
+
+/// Run a setup step now and record how long it took
+pub fn blocking<T>(name: &str, step: impl FnOnce() -> T) -> T {
+    let index = begin(name, StartupMode::Blocking);
+    let started = Instant::now();
+    let result = step();
+    finish(index, started);
+    result
+}
+
+/// Spawn a setup step without waiting for it and record how long it took
+pub fn background<F>(name: &str, step: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let index = begin(name, StartupMode::Background);
+    tauri::async_runtime::spawn(async move {
+        let started = Instant::now();
+        step.await;
+        finish(index, started);
+    });
+}
+
+pub fn is_enabled(subsystem: Subsystem) -> bool {
+    CONFIG.lock().map_or(true, |config| config.is_enabled(subsystem))
+}
+
+/// Error for commands of a subsystem that was turned off
+pub fn ensure_enabled(subsystem: Subsystem) -> Result<(), String> {
+    if is_enabled(subsystem) {
+        Ok(())
+    } else {
+        Err(format!(
+            "The {} subsystem is disabled; enable it in the startup settings",
+            subsystem.as_str()
+        ))
+    }
+}
+
+/// Load which subsystems are enabled; needs the database, so run it right after it opens
+pub fn init_startup_config(app: &AppHandle) {
+    let db = app.state::<AgentDb>();
+    let Ok(conn) = db.0.get() else {
+        return;
+    };
+    let config: StartupConfig = settings_store::get_or_default(&conn, CONFIG_SETTING);
+    if let Ok(mut current) = CONFIG.lock() {
+        *current = config;
+    }
+}
+
+/// How long each startup step took, in the order they started
+#[tauri::command]
+pub async fn get_startup_timings() -> Result<StartupTimings, String> {
+    TIMINGS.lock().map(|timings| timings.clone()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_startup_config() -> Result<StartupConfig, String> {
+    CONFIG.lock().map(|config| config.clone()).map_err(|e| e.to_string())
+}
+
+/// Turn optional subsystems on or off; takes effect right away
+#[tauri::command]
+pub async fn set_startup_config(app: AppHandle, config: StartupConfig) -> Result<StartupConfig, String> {
+    settings_store::set(&app, CONFIG_SETTING, &config)?;
+    *CONFIG.lock().map_err(|e| e.to_string())? = config.clone();
+    log::info!(
+        "Startup config set: translator={}, router={}, subagents={}",
+        config.translator,
+        config.router,
+        config.subagents
+    );
+    Ok(config)
+}
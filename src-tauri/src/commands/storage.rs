@@ -49,7 +49,7 @@ pub struct QueryResult {
 /// List all tables in the database
 #[tauri::command]
 pub async fn storage_list_tables(db: State<'_, AgentDb>) -> Result<Vec<TableInfo>, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
     
     // Query for all tables
     let mut stmt = conn
@@ -116,7 +116,7 @@ pub async fn storage_read_table(
     pageSize: i64,
     searchQuery: Option<String>,
 ) -> Result<TableData, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
     
     // Validate table name to prevent SQL injection
     if !is_valid_table_name(&conn, &tableName)? {
@@ -234,7 +234,7 @@ pub async fn storage_update_row(
     primaryKeyValues: HashMap<String, JsonValue>,
     updates: HashMap<String, JsonValue>,
 ) -> Result<(), String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
     
     // Validate table name
     if !is_valid_table_name(&conn, &tableName)? {
@@ -289,7 +289,7 @@ pub async fn storage_delete_row(
     tableName: String,
     primaryKeyValues: HashMap<String, JsonValue>,
 ) -> Result<(), String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
     
     // Validate table name
     if !is_valid_table_name(&conn, &tableName)? {
@@ -330,7 +330,7 @@ pub async fn storage_insert_row(
     tableName: String,
     values: HashMap<String, JsonValue>,
 ) -> Result<i64, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
     
     // Validate table name
     if !is_valid_table_name(&conn, &tableName)? {
@@ -369,7 +369,7 @@ pub async fn storage_execute_sql(
     db: State<'_, AgentDb>,
     query: String,
 ) -> Result<QueryResult, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
     
     // Check if it's a SELECT query
     let is_select = query.trim().to_uppercase().starts_with("SELECT");
@@ -435,7 +435,7 @@ pub async fn storage_reset_database(app: AppHandle) -> Result<(), String> {
     {
         // Drop all existing tables within a scoped block
         let db_state = app.state::<AgentDb>();
-        let conn = db_state.0.lock()
+        let conn = db_state.0.get()
             .map_err(|e| e.to_string())?;
         
         // Disable foreign key constraints temporarily to allow dropping tables
@@ -460,18 +460,16 @@ pub async fn storage_reset_database(app: AppHandle) -> Result<(), String> {
     // Re-initialize the database which will recreate all tables empty
     let new_conn = init_database(&app).map_err(|e| format!("Failed to reset database: {}", e))?;
     
-    // Update the managed state with the new connection
+    // Replace pooled connections with the new one
     {
         let db_state = app.state::<AgentDb>();
-        let mut conn_guard = db_state.0.lock()
-            .map_err(|e| e.to_string())?;
-        *conn_guard = new_conn;
+        db_state.0.reset(new_conn)?;
     }
     
     // Run VACUUM to optimize the database
     {
         let db_state = app.state::<AgentDb>();
-        let conn = db_state.0.lock()
+        let conn = db_state.0.get()
             .map_err(|e| e.to_string())?;
         conn.execute("VACUUM", [])
             .map_err(|e| e.to_string())?;
@@ -8,7 +8,6 @@
 
 use rusqlite::{params, Connection, Result as SqliteResult};
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
 use tauri::State;
 use log::{info, warn, debug, error};
 
@@ -104,13 +103,13 @@ pub struct RoutingDecision {
 
 /// 智能路由器 - 保留用于未来扩展
 #[allow(dead_code)]
-pub struct SubagentRouter {
-    db: std::sync::Arc<Mutex<Connection>>,
+pub struct SubagentRouter<'a> {
+    db: &'a Connection,
 }
 
 #[allow(dead_code)]
-impl SubagentRouter {
-    pub fn new(db: std::sync::Arc<Mutex<Connection>>) -> Self {
+impl<'a> SubagentRouter<'a> {
+    pub fn new(db: &'a Connection) -> Self {
         Self { db }
     }
 
@@ -120,7 +119,7 @@ impl SubagentRouter {
         debug!("Routing request: {}", user_request);
 
         // 获取所有可用的专业化子代理
-        let conn = self.db.lock().map_err(|e| e.to_string())?;
+        let conn = self.db;
 
         let mut stmt = conn.prepare(
             "SELECT a.id, a.specialty, a.name, a.routing_keywords, s.routing_patterns, s.display_name
@@ -249,7 +248,7 @@ impl SubagentRouter {
         confidence: f64,
         reasoning: &str,
     ) -> Result<(), String> {
-        let conn = self.db.lock().map_err(|e| e.to_string())?;
+        let conn = self.db;
 
         conn.execute(
             "INSERT INTO subagent_routing_log (user_request, selected_agent_id, selected_specialty, confidence_score, routing_reason)
@@ -268,27 +267,39 @@ impl SubagentRouter {
 pub async fn init_subagent_system(
     db: State<'_, crate::commands::agents::AgentDb>
 ) -> Result<String, String> {
+    crate::commands::startup::ensure_enabled(crate::commands::startup::Subsystem::Subagents)?;
     info!("Initializing subagent specialization system");
 
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    // 表结构由数据库迁移（migrations/0002_subagents）创建，这里只确认它已就绪
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    conn.query_row("SELECT COUNT(*) FROM subagent_specialties", [], |row| row.get::<_, i64>(0))
+        .map_err(|e| format!("Subagent tables are missing: {}", e))?;
 
-    // 执行schema初始化
-    let schema_sql = include_str!("subagents_schema.sql");
+    info!("Subagent system initialized successfully");
+    Ok("Subagent system initialized".to_string())
+}
 
-    // 分割并执行每个SQL语句
-    for statement in schema_sql.split(';') {
-        let trimmed = statement.trim();
-        if !trimmed.is_empty() && !trimmed.starts_with("--") {
-            conn.execute(trimmed, []).map_err(|e| {
-                warn!("Failed to execute SQL statement: {}", e);
-                // 继续执行，因为某些语句（如ALTER TABLE）可能已经存在
-                e.to_string()
-            }).ok();
+/// 为agents表添加专业化字段（迁移 0002 的第一步）
+///
+/// 旧版本在 `init_subagent_system` 中直接执行 ALTER TABLE，数据库里可能已经有这些字段，
+/// 所以只添加缺少的字段。
+pub(crate) fn add_specialty_columns(conn: &Connection) -> SqliteResult<()> {
+    let existing: Vec<String> = conn
+        .prepare("SELECT name FROM pragma_table_info('agents')")?
+        .query_map([], |row| row.get(0))?
+        .collect::<SqliteResult<_>>()?;
+    let columns = [
+        ("specialty", "TEXT DEFAULT 'general'"),
+        ("specialty_config", "TEXT"), // JSON配置：工具权限、触发条件等
+        ("routing_keywords", "TEXT"), // JSON数组：用于智能路由的关键词
+        ("auto_invoke", "BOOLEAN DEFAULT 0"), // 是否自动调用
+    ];
+    for (name, definition) in columns {
+        if !existing.iter().any(|column| column == name) {
+            conn.execute(&format!("ALTER TABLE agents ADD COLUMN {} {}", name, definition), [])?;
         }
     }
-
-    info!("Subagent system initialized successfully");
-    Ok("Subagent system initialized".to_string())
+    Ok(())
 }
 
 /// 获取所有专业化类型
@@ -296,7 +307,7 @@ pub async fn init_subagent_system(
 pub async fn list_subagent_specialties(
     db: State<'_, crate::commands::agents::AgentDb>
 ) -> Result<Vec<SubagentSpecialty>, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
 
     let mut stmt = conn.prepare(
         "SELECT id, specialty_type, display_name, description, default_system_prompt, default_tools, routing_patterns, icon_suggestion, created_at
@@ -329,7 +340,8 @@ pub async fn route_to_subagent(
     db: State<'_, crate::commands::agents::AgentDb>,
     user_request: String,
 ) -> Result<RoutingDecision, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    crate::commands::startup::ensure_enabled(crate::commands::startup::Subsystem::Subagents)?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
 
     let request_lower = user_request.to_lowercase();
 
@@ -447,7 +459,7 @@ pub async fn update_subagent_specialty(
     routing_keywords: Option<String>,
     auto_invoke: Option<bool>,
 ) -> Result<(), String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
 
     conn.execute(
         "UPDATE agents SET specialty = ?1, specialty_config = ?2, routing_keywords = ?3, auto_invoke = ?4
@@ -465,7 +477,7 @@ pub async fn get_routing_history(
     db: State<'_, crate::commands::agents::AgentDb>,
     limit: Option<i64>,
 ) -> Result<Vec<serde_json::Value>, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
     let limit = limit.unwrap_or(50);
 
     let mut stmt = conn.prepare(
@@ -499,7 +511,7 @@ pub async fn provide_routing_feedback(
     log_id: i64,
     feedback: i32, // 1: good, 0: neutral, -1: bad
 ) -> Result<(), String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
 
     conn.execute(
         "UPDATE subagent_routing_log SET user_feedback = ?1 WHERE id = ?2",
@@ -544,7 +556,7 @@ pub async fn execute_code_review(
     let mut files_reviewed = Vec::new();
 
     // 获取code-reviewer的专业化配置
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.get().map_err(|e| e.to_string())?;
     let _specialty_config = conn.query_row(
         "SELECT default_system_prompt, default_tools FROM subagent_specialties WHERE specialty_type = 'code-reviewer'",
         [],
@@ -0,0 +1,406 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use tauri::State;
+
+use super::agents::AgentDb;
+use super::claude::get_claude_dir;
+use super::session_handoff::build_session_summary;
+use super::session_meta::{find_session_file, load_session_meta};
+use crate::checkpoint::{CheckpointPaths, SessionTimeline, TimelineNode};
+
+/// A piece of work spanning several sessions and agent runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Task {
+    pub id: i64,
+    pub name: String,
+    pub project_path: String,
+    pub description: Option<String>,
+    pub created_at: String,
+    /// Sessions and agent runs assigned to the task
+    pub member_count: i64,
+}
+
+/// What can be assigned to a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskMemberKind {
+    Session,
+    AgentRun,
+}
+
+impl TaskMemberKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            TaskMemberKind::Session => "session",
+            TaskMemberKind::AgentRun => "agent_run",
+        }
+    }
+}
+
+/// A checkpoint of one of the task's sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskCheckpoint {
+    pub id: String,
+    pub timestamp: String,
+    pub description: Option<String>,
+    pub file_changes: usize,
+}
+
+/// One session of a task, either assigned directly or through an agent run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskSessionOverview {
+    pub session_id: String,
+    /// Set when the session belongs to an assigned agent run
+    pub agent_run_id: Option<i64>,
+    pub agent_name: Option<String>,
+    /// Agent run status, e.g. `completed`
+    pub agent_run_status: Option<String>,
+    pub title: Option<String>,
+    pub message_count: u64,
+    pub first_activity: Option<String>,
+    pub last_activity: Option<String>,
+    /// Whether the transcript was found on disk
+    pub transcript_found: bool,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    /// Files touched by edit tools
+    pub files_modified: Vec<String>,
+    pub checkpoints: Vec<TaskCheckpoint>,
+}
+
+/// Returned by `get_task_overview`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskOverview {
+    pub task: Task,
+    pub sessions: Vec<TaskSessionOverview>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    /// Files touched across all sessions
+    pub files_modified: Vec<String>,
+    pub checkpoint_count: usize,
+    pub first_activity: Option<String>,
+    pub last_activity: Option<String>,
+}
+
+pub fn init_task_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tasks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            project_path TEXT NOT NULL,
+            description TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    // A session or agent run belongs to at most one task
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS task_members (
+            task_id INTEGER NOT NULL,
+            member_kind TEXT NOT NULL,
+            member_id TEXT NOT NULL,
+            added_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (member_kind, member_id),
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_task_members_task ON task_members(task_id)", [])?;
+    Ok(())
+}
+
+const TASK_COLUMNS: &str = "t.id, t.name, t.project_path, t.description, t.created_at,
+    (SELECT COUNT(*) FROM task_members m WHERE m.task_id = t.id)";
+
+fn row_to_task(row: &rusqlite::Row) -> rusqlite::Result<Task> {
+    Ok(Task {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        project_path: row.get(2)?,
+        description: row.get(3)?,
+        created_at: row.get(4)?,
+        member_count: row.get(5)?,
+    })
+}
+
+fn load_task(conn: &Connection, task_id: i64) -> Result<Task, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM tasks t WHERE t.id = ?1", TASK_COLUMNS),
+        params![task_id],
+        row_to_task,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Task {} not found", task_id))
+}
+
+/// Checkpoints in a session's timeline, oldest first
+fn session_checkpoints(project_id: &str, session_id: &str) -> Vec<TaskCheckpoint> {
+    let Ok(claude_dir) = get_claude_dir() else {
+        return Vec::new();
+    };
+    let paths = CheckpointPaths::new(&claude_dir, project_id, session_id);
+    let Some(timeline) = fs::read_to_string(&paths.timeline_file)
+        .ok()
+        .and_then(|content| serde_json::from_str::<SessionTimeline>(&content).ok())
+    else {
+        return Vec::new();
+    };
+
+    fn walk(node: &TimelineNode, out: &mut Vec<TaskCheckpoint>) {
+        let checkpoint = &node.checkpoint;
+        out.push(TaskCheckpoint {
+            id: checkpoint.id.clone(),
+            timestamp: checkpoint.timestamp.to_rfc3339(),
+            description: checkpoint.description.clone(),
+            file_changes: checkpoint.metadata.file_changes,
+        });
+        for child in &node.children {
+            walk(child, out);
+        }
+    }
+    let mut checkpoints = Vec::new();
+    if let Some(root) = &timeline.root_node {
+        walk(root, &mut checkpoints);
+    }
+    checkpoints.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    checkpoints
+}
+
+/// Transcript statistics, files and checkpoints of a session; reads files, so run it blocking
+fn session_overview(session_id: &str, project_path: &str) -> TaskSessionOverview {
+    let mut overview = TaskSessionOverview {
+        session_id: session_id.to_string(),
+        ..Default::default()
+    };
+    let Ok(Some((project_id, path))) = find_session_file(session_id, None) else {
+        return overview;
+    };
+    overview.transcript_found = true;
+    if let Ok(meta) = load_session_meta(&path) {
+        overview.title = meta.title;
+        overview.message_count = meta.message_count;
+        overview.first_activity = meta.message_timestamp;
+        overview.last_activity = meta.last_activity;
+    }
+    if let Ok(summary) = build_session_summary(session_id, project_path, true) {
+        overview.files_modified = summary.files_modified;
+    }
+    overview.checkpoints = session_checkpoints(&project_id, session_id);
+    overview
+}
+
+/// Create a task to group the sessions and agent runs of one piece of work
+#[tauri::command]
+pub async fn create_task(
+    db: State<'_, AgentDb>,
+    name: String,
+    project_path: String,
+    description: Option<String>,
+) -> Result<Task, String> {
+    if name.trim().is_empty() {
+        return Err("Task name is empty".to_string());
+    }
+    if project_path.trim().is_empty() {
+        return Err("Task project path is empty".to_string());
+    }
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO tasks (name, project_path, description) VALUES (?1, ?2, ?3)",
+        params![
+            name.trim(),
+            project_path.trim(),
+            description.map(|d| d.trim().to_string()).filter(|d| !d.is_empty())
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    load_task(&conn, conn.last_insert_rowid())
+}
+
+/// List tasks, newest first, optionally only those of one project
+#[tauri::command]
+pub async fn list_tasks(db: State<'_, AgentDb>, project_path: Option<String>) -> Result<Vec<Task>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM tasks t WHERE ?1 IS NULL OR t.project_path = ?1 ORDER BY t.id DESC",
+            TASK_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let tasks = stmt
+        .query_map(params![project_path], row_to_task)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(tasks)
+}
+
+/// Delete a task; its sessions and agent runs are kept
+#[tauri::command]
+pub async fn delete_task(db: State<'_, AgentDb>, task_id: i64) -> Result<(), String> {
+    let mut conn = db.0.get().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM task_members WHERE task_id = ?1", params![task_id])
+        .map_err(|e| e.to_string())?;
+    let deleted = tx
+        .execute("DELETE FROM tasks WHERE id = ?1", params![task_id])
+        .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Task {} not found", task_id));
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Assign a session (by session id) or agent run (by run id) to a task, moving it
+/// out of any task it was in
+#[tauri::command]
+pub async fn assign_to_task(
+    db: State<'_, AgentDb>,
+    task_id: i64,
+    kind: TaskMemberKind,
+    member_id: String,
+) -> Result<Task, String> {
+    let member_id = member_id.trim().to_string();
+    if member_id.is_empty() {
+        return Err("Member id is empty".to_string());
+    }
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    load_task(&conn, task_id)?;
+    if kind == TaskMemberKind::AgentRun {
+        let exists = conn
+            .query_row(
+                "SELECT 1 FROM agent_runs WHERE id = ?1",
+                params![member_id.parse::<i64>().map_err(|_| format!("Invalid agent run id {}", member_id))?],
+                |_| Ok(()),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        if exists.is_none() {
+            return Err(format!("Agent run {} not found", member_id));
+        }
+    }
+    conn.execute(
+        "INSERT INTO task_members (task_id, member_kind, member_id) VALUES (?1, ?2, ?3)
+         ON CONFLICT(member_kind, member_id) DO UPDATE SET task_id = ?1, added_at = CURRENT_TIMESTAMP",
+        params![task_id, kind.as_str(), member_id],
+    )
+    .map_err(|e| e.to_string())?;
+    load_task(&conn, task_id)
+}
+
+/// Take a session or agent run out of its task
+#[tauri::command]
+pub async fn remove_from_task(db: State<'_, AgentDb>, kind: TaskMemberKind, member_id: String) -> Result<(), String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM task_members WHERE member_kind = ?1 AND member_id = ?2",
+        params![kind.as_str(), member_id.trim()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Transcripts, cost, file changes and checkpoints across all sessions and agent runs
+/// of a task
+#[tauri::command]
+pub async fn get_task_overview(db: State<'_, AgentDb>, task_id: i64) -> Result<TaskOverview, String> {
+    let (task, mut sessions) = {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        let task = load_task(&conn, task_id)?;
+
+        let mut sessions: Vec<TaskSessionOverview> = Vec::new();
+        let mut stmt = conn
+            .prepare("SELECT member_id FROM task_members WHERE task_id = ?1 AND member_kind = 'session' ORDER BY added_at")
+            .map_err(|e| e.to_string())?;
+        let session_ids = stmt
+            .query_map(params![task_id], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        sessions.extend(session_ids.into_iter().map(|session_id| TaskSessionOverview {
+            session_id,
+            ..Default::default()
+        }));
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT r.id, r.agent_name, r.status, r.session_id FROM task_members m
+                 JOIN agent_runs r ON r.id = CAST(m.member_id AS INTEGER)
+                 WHERE m.task_id = ?1 AND m.member_kind = 'agent_run' ORDER BY m.added_at",
+            )
+            .map_err(|e| e.to_string())?;
+        let runs = stmt
+            .query_map(params![task_id], |row| {
+                Ok(TaskSessionOverview {
+                    agent_run_id: Some(row.get(0)?),
+                    agent_name: Some(row.get(1)?),
+                    agent_run_status: row.get(2)?,
+                    session_id: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                    ..Default::default()
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        sessions.extend(runs);
+
+        for session in sessions.iter_mut().filter(|s| !s.session_id.is_empty()) {
+            let (input, output, cost): (i64, i64, f64) = conn
+                .query_row(
+                    "SELECT COALESCE(SUM(input_tokens), 0), COALESCE(SUM(output_tokens), 0), COALESCE(SUM(cost), 0)
+                     FROM usage_entries WHERE session_id = ?1",
+                    params![session.session_id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .map_err(|e| e.to_string())?;
+            session.input_tokens = input as u64;
+            session.output_tokens = output as u64;
+            session.cost_usd = cost;
+        }
+        (task, sessions)
+    };
+
+    // Transcripts and timelines are read from disk
+    let project_path = task.project_path.clone();
+    sessions = tokio::task::spawn_blocking(move || {
+        sessions
+            .into_iter()
+            .map(|session| {
+                if session.session_id.is_empty() {
+                    return session;
+                }
+                TaskSessionOverview {
+                    agent_run_id: session.agent_run_id,
+                    agent_name: session.agent_name,
+                    agent_run_status: session.agent_run_status,
+                    input_tokens: session.input_tokens,
+                    output_tokens: session.output_tokens,
+                    cost_usd: session.cost_usd,
+                    ..session_overview(&session.session_id, &project_path)
+                }
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let files_modified: BTreeSet<String> = sessions
+        .iter()
+        .flat_map(|s| s.files_modified.iter().cloned())
+        .collect();
+    Ok(TaskOverview {
+        input_tokens: sessions.iter().map(|s| s.input_tokens).sum(),
+        output_tokens: sessions.iter().map(|s| s.output_tokens).sum(),
+        cost_usd: sessions.iter().map(|s| s.cost_usd).sum(),
+        files_modified: files_modified.into_iter().collect(),
+        checkpoint_count: sessions.iter().map(|s| s.checkpoints.len()).sum(),
+        first_activity: sessions.iter().filter_map(|s| s.first_activity.clone()).min(),
+        last_activity: sessions.iter().filter_map(|s| s.last_activity.clone()).max(),
+        task,
+        sessions,
+    })
+}
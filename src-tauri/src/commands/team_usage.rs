@@ -0,0 +1,245 @@
+use chrono::{NaiveDate, Utc};
+use once_cell::sync::Lazy;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::RwLock;
+use tauri::{AppHandle, Manager, State};
+
+use super::agents::AgentDb;
+use super::settings_store;
+use super::router_usage::reconcile_router_usage;
+use super::usage::usage_entries_between;
+
+/// `app_settings` key holding the operator id
+const OPERATOR_SETTING: &str = "operator_id";
+
+/// Bumped when the export record layout changes
+const TEAM_USAGE_SCHEMA_VERSION: u32 = 1;
+
+/// Operator of usage recorded before an operator id was set
+const UNKNOWN_OPERATOR: &str = "unknown";
+
+/// Operator id loaded from `app_settings`, cached for the usage stream and session scans
+static OPERATOR_ID: Lazy<RwLock<Option<String>>> = Lazy::new(|| RwLock::new(None));
+
+/// One usage entry in the normalized team format.
+///
+/// Exports from several machines can be concatenated and deduplicated on `record_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamUsageRecord {
+    /// Stable hash of operator, session, timestamp, model and token counts
+    pub record_id: String,
+    pub operator_id: String,
+    pub session_id: String,
+    /// Project directory name only; full local paths stay on the machine
+    pub project: String,
+    /// UTC date (YYYY-MM-DD)
+    pub date: String,
+    pub timestamp: String,
+    pub model: String,
+    pub api_base_url: String,
+    pub provider_source: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Returned by `export_team_usage`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamUsageExport {
+    pub schema_version: u32,
+    pub operator_id: String,
+    pub exported_at: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub records: Vec<TeamUsageRecord>,
+    /// Where the export was written, if an output path was given
+    pub output_path: Option<String>,
+}
+
+/// Load the operator id into the cache, called once at startup
+pub fn init_operator_id(app: &AppHandle) {
+    let db = app.state::<AgentDb>();
+    let Ok(conn) = db.0.get() else {
+        return;
+    };
+    let stored: Option<String> = settings_store::get_or_default(&conn, OPERATOR_SETTING);
+    if let Ok(mut cached) = OPERATOR_ID.write() {
+        *cached = stored;
+    }
+}
+
+/// The configured operator id, stamped onto usage rows and session metadata
+pub fn current_operator_id() -> Option<String> {
+    OPERATOR_ID.read().ok().and_then(|id| id.clone())
+}
+
+/// Get the operator id identifying this user in team usage exports
+#[tauri::command]
+pub async fn get_operator_id() -> Result<Option<String>, String> {
+    Ok(current_operator_id())
+}
+
+/// Set or clear the operator id
+#[tauri::command]
+pub async fn set_operator_id(app: AppHandle, operator_id: Option<String>) -> Result<Option<String>, String> {
+    let operator_id = operator_id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty());
+    match &operator_id {
+        Some(id) => settings_store::set(&app, OPERATOR_SETTING, id),
+        None => settings_store::remove(&app, OPERATOR_SETTING),
+    }
+    .map_err(|e| format!("Failed to save operator id: {}", e))?;
+
+    if let Ok(mut cached) = OPERATOR_ID.write() {
+        *cached = operator_id.clone();
+    }
+    Ok(operator_id)
+}
+
+fn record_id(operator_id: &str, record: &TeamUsageRecord) -> String {
+    let key = format!(
+        "{}|{}|{}|{}|{}|{}|{}|{}",
+        operator_id,
+        record.session_id,
+        record.timestamp,
+        record.model,
+        record.input_tokens,
+        record.output_tokens,
+        record.cache_creation_tokens,
+        record.cache_read_tokens
+    );
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+/// Operators stamped onto usage rows when they were recorded
+#[derive(Default)]
+struct RecordedOperators {
+    /// By (session, timestamp) of a usage row
+    rows: HashMap<(String, String), String>,
+    /// First operator recorded for each session
+    sessions: HashMap<String, String>,
+}
+
+impl RecordedOperators {
+    fn load(conn: &Connection) -> Result<Self, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT session_id, timestamp, operator_id FROM usage_entries
+                 WHERE operator_id IS NOT NULL AND operator_id != '' ORDER BY id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))
+            .map_err(|e| e.to_string())?;
+
+        let mut recorded = Self::default();
+        for row in rows {
+            let (session_id, timestamp, operator_id) = row.map_err(|e| e.to_string())?;
+            recorded.sessions.entry(session_id.clone()).or_insert_with(|| operator_id.clone());
+            recorded.rows.insert((session_id, timestamp), operator_id);
+        }
+        Ok(recorded)
+    }
+
+    /// Operator of an entry: its usage row's, its session's, then the one in the
+    /// session's sidecar; "unknown" when none was recorded
+    fn operator(&mut self, session_id: &str, timestamp: &str) -> String {
+        if let Some(operator_id) = self.rows.get(&(session_id.to_string(), timestamp.to_string())) {
+            return operator_id.clone();
+        }
+        if let Some(operator_id) = self.sessions.get(session_id) {
+            return operator_id.clone();
+        }
+        let operator_id = super::session_meta::recorded_operator_id(session_id)
+            .unwrap_or_else(|| UNKNOWN_OPERATOR.to_string());
+        // Look the sidecar up once per session
+        self.sessions.insert(session_id.to_string(), operator_id.clone());
+        operator_id
+    }
+}
+
+/// Export this machine's usage between two dates (YYYY-MM-DD, inclusive) in the
+/// normalized team format, optionally writing it to `output_path` as JSON.
+///
+/// Each record carries the operator recorded with it, so usage from before an
+/// operator change is not attributed to the current operator.
+#[tauri::command]
+pub async fn export_team_usage(
+    db: State<'_, AgentDb>,
+    start_date: String,
+    end_date: String,
+    output_path: Option<String>,
+) -> Result<TeamUsageExport, String> {
+    let operator_id = current_operator_id().ok_or("Set an operator id before exporting team usage")?;
+    let start = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid start date: {}", e))?;
+    let end = NaiveDate::parse_from_str(&end_date, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid end date: {}", e))?;
+    if end < start {
+        return Err("End date is before start date".to_string());
+    }
+
+    let mut recorded = {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        RecordedOperators::load(&conn)?
+    };
+    let mut entries = tokio::task::spawn_blocking(move || usage_entries_between(start, end))
+        .await
+        .map_err(|e| e.to_string())??;
+    reconcile_router_usage(&mut entries);
+
+    let records = entries
+        .into_iter()
+        .map(|entry| {
+            let entry_operator = recorded.operator(&entry.session_id, &entry.timestamp);
+            let date = chrono::DateTime::parse_from_rfc3339(&entry.timestamp)
+                .map(|dt| dt.with_timezone(&Utc).format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            let project = Path::new(&entry.project_path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| entry.project_path.clone());
+            let mut record = TeamUsageRecord {
+                record_id: String::new(),
+                operator_id: entry_operator,
+                session_id: entry.session_id,
+                project,
+                date,
+                timestamp: entry.timestamp,
+                model: entry.model,
+                api_base_url: entry.api_base_url,
+                provider_source: entry.provider_source,
+                input_tokens: entry.input_tokens,
+                output_tokens: entry.output_tokens,
+                cache_creation_tokens: entry.cache_creation_tokens,
+                cache_read_tokens: entry.cache_read_tokens,
+                cost_usd: entry.cost,
+            };
+            record.record_id = record_id(&record.operator_id, &record);
+            record
+        })
+        .collect();
+
+    let mut export = TeamUsageExport {
+        schema_version: TEAM_USAGE_SCHEMA_VERSION,
+        operator_id,
+        exported_at: Utc::now().to_rfc3339(),
+        start_date,
+        end_date,
+        records,
+        output_path: None,
+    };
+
+    if let Some(path) = output_path {
+        let content = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
+        fs::write(&path, content).map_err(|e| format!("Failed to write team usage export: {}", e))?;
+        export.output_path = Some(path);
+    }
+    Ok(export)
+}
@@ -0,0 +1,378 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use tauri::State;
+
+use super::agents::AgentDb;
+use super::claude::get_claude_dir;
+
+/// Gap between two messages after which the user is considered idle
+pub(crate) const DEFAULT_IDLE_GAP_MINUTES: u32 = 15;
+
+/// Time credited after the last message of an activity burst (reading the answer, reviewing changes)
+const BURST_TAIL_MINUTES: i64 = 2;
+
+/// Inclusive local date range, dates as `YYYY-MM-DD` (RFC3339 timestamps are accepted too)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeRange {
+    pub start_date: String,
+    pub end_date: String,
+}
+
+/// One row of a time report; grouping fields that don't apply are `None`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeReportRow {
+    pub project_path: Option<String>,
+    pub date: Option<String>,
+    pub session_id: Option<String>,
+    pub active_seconds: i64,
+    pub hours: f64,
+    /// Number of distinct sessions and agent runs contributing to the row
+    pub session_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeReport {
+    pub start_date: String,
+    pub end_date: String,
+    pub group_by: String,
+    pub idle_gap_minutes: u32,
+    pub total_seconds: i64,
+    pub total_hours: f64,
+    pub rows: Vec<TimeReportRow>,
+}
+
+/// A span of activity attributed to a project
+#[derive(Debug, Clone)]
+struct Activity {
+    project_path: String,
+    /// Session ID, or `agent-run-<id>` for agent runs
+    source: String,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+}
+
+fn parse_range_date(value: &str, label: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").or_else(|_| {
+        DateTime::parse_from_rfc3339(value)
+            .map(|dt| dt.with_timezone(&Local).date_naive())
+            .map_err(|e| format!("Invalid {} date: {}", label, e))
+    })
+}
+
+fn local_midnight(date: NaiveDate) -> DateTime<Local> {
+    let naive = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .unwrap_or_else(|| Local.from_utc_datetime(&naive))
+}
+
+/// Parse timestamps from session files (RFC3339) and SQLite (`YYYY-MM-DD HH:MM:SS`, UTC)
+fn parse_timestamp(value: &str) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Local))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|naive| Utc.from_utc_datetime(&naive).with_timezone(&Local))
+        })
+}
+
+/// Turn message timestamps into activity bursts separated by idle gaps
+fn bursts_from_timestamps(mut timestamps: Vec<DateTime<Local>>, idle_gap: Duration) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+    timestamps.sort();
+    let tail = Duration::minutes(BURST_TAIL_MINUTES);
+    let mut bursts = Vec::new();
+    let mut iter = timestamps.into_iter();
+
+    let Some(first) = iter.next() else {
+        return bursts;
+    };
+    let (mut start, mut last) = (first, first);
+    for ts in iter {
+        if ts - last > idle_gap {
+            bursts.push((start, last + tail));
+            start = ts;
+        }
+        last = ts;
+    }
+    bursts.push((start, last + tail));
+    bursts
+}
+
+/// Collect activity bursts from all session transcripts overlapping the window
+fn session_activities(window_start: DateTime<Local>, idle_gap: Duration) -> Vec<Activity> {
+    let projects_dir = match get_claude_dir() {
+        Ok(dir) => dir.join("projects"),
+        Err(_) => return Vec::new(),
+    };
+    let window_start_system: std::time::SystemTime = window_start.into();
+    let mut activities = Vec::new();
+
+    for project in fs::read_dir(&projects_dir).into_iter().flatten().flatten() {
+        for entry in fs::read_dir(project.path()).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            // Files untouched since before the window can't contain activity inside it
+            let modified = entry.metadata().and_then(|m| m.modified()).ok();
+            if modified.map(|m| m < window_start_system).unwrap_or(false) {
+                continue;
+            }
+            let session_id = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+            let file = match fs::File::open(&path) {
+                Ok(file) => file,
+                Err(_) => continue,
+            };
+
+            let mut project_path: Option<String> = None;
+            let mut timestamps = Vec::new();
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                let entry: serde_json::Value = match serde_json::from_str(&line) {
+                    Ok(entry) => entry,
+                    Err(_) => continue,
+                };
+                if project_path.is_none() {
+                    project_path = entry.get("cwd").and_then(|c| c.as_str()).map(|s| s.to_string());
+                }
+                if let Some(ts) = entry.get("timestamp").and_then(|t| t.as_str()).and_then(parse_timestamp) {
+                    timestamps.push(ts);
+                }
+            }
+
+            let project_path = project_path
+                .unwrap_or_else(|| project.file_name().to_string_lossy().to_string());
+            for (start, end) in bursts_from_timestamps(timestamps, idle_gap) {
+                activities.push(Activity {
+                    project_path: project_path.clone(),
+                    source: session_id.clone(),
+                    start,
+                    end,
+                });
+            }
+        }
+    }
+
+    activities
+}
+
+/// Agent runs count for their whole process lifetime
+fn agent_run_activities(db: &AgentDb) -> Result<Vec<Activity>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, project_path, COALESCE(process_started_at, created_at), completed_at
+             FROM agent_runs WHERE completed_at IS NOT NULL",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut activities = Vec::new();
+    for row in rows.flatten() {
+        let (id, project_path, started, completed) = row;
+        let (Some(start), Some(end)) = (
+            started.as_deref().and_then(parse_timestamp),
+            completed.as_deref().and_then(parse_timestamp),
+        ) else {
+            continue;
+        };
+        if end > start {
+            activities.push(Activity {
+                project_path,
+                source: format!("agent-run-{}", id),
+                start,
+                end,
+            });
+        }
+    }
+    Ok(activities)
+}
+
+/// Clip an activity to the window and split it at local midnights
+fn split_by_day(activity: &Activity, window_start: DateTime<Local>, window_end: DateTime<Local>) -> Vec<(NaiveDate, DateTime<Local>, DateTime<Local>)> {
+    let mut pieces = Vec::new();
+    let mut start = activity.start.max(window_start);
+    let end = activity.end.min(window_end);
+
+    while start < end {
+        let day = start.date_naive();
+        let next_midnight = local_midnight(day + Duration::days(1));
+        let piece_end = end.min(next_midnight);
+        pieces.push((day, start, piece_end));
+        start = piece_end;
+    }
+    pieces
+}
+
+/// Total length of the union of intervals, so parallel sessions aren't counted twice
+fn union_seconds(mut intervals: Vec<(DateTime<Local>, DateTime<Local>)>) -> i64 {
+    intervals.sort();
+    let mut total = 0;
+    let mut current: Option<(DateTime<Local>, DateTime<Local>)> = None;
+
+    for (start, end) in intervals {
+        current = match current {
+            Some((cur_start, cur_end)) if start <= cur_end => Some((cur_start, cur_end.max(end))),
+            Some((cur_start, cur_end)) => {
+                total += (cur_end - cur_start).num_seconds();
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+    if let Some((start, end)) = current {
+        total += (end - start).num_seconds();
+    }
+    total
+}
+
+type GroupKey = (Option<String>, Option<String>, Option<String>);
+
+pub(crate) fn build_time_report(db: &AgentDb, range: &TimeRange, group_by: &str, idle_gap_minutes: u32) -> Result<TimeReport, String> {
+    let start_date = parse_range_date(&range.start_date, "start")?;
+    let end_date = parse_range_date(&range.end_date, "end")?;
+    if end_date < start_date {
+        return Err("End date must not be before start date".to_string());
+    }
+    if !matches!(group_by, "project" | "day" | "project_day" | "session") {
+        return Err(format!(
+            "Invalid group_by: {}. Valid values are: project, day, project_day, session",
+            group_by
+        ));
+    }
+
+    let window_start = local_midnight(start_date);
+    let window_end = local_midnight(end_date + Duration::days(1));
+    let idle_gap = Duration::minutes(idle_gap_minutes as i64);
+
+    let mut activities = session_activities(window_start, idle_gap);
+    activities.extend(agent_run_activities(db)?);
+
+    let mut groups: BTreeMap<GroupKey, (Vec<(DateTime<Local>, DateTime<Local>)>, BTreeSet<String>)> = BTreeMap::new();
+    for activity in &activities {
+        for (day, start, end) in split_by_day(activity, window_start, window_end) {
+            let project = Some(activity.project_path.clone());
+            let date = Some(day.format("%Y-%m-%d").to_string());
+            let key = match group_by {
+                "project" => (project, None, None),
+                "day" => (None, date, None),
+                "project_day" => (project, date, None),
+                _ => (project, None, Some(activity.source.clone())),
+            };
+            let group = groups.entry(key).or_default();
+            group.0.push((start, end));
+            group.1.insert(activity.source.clone());
+        }
+    }
+
+    let mut rows: Vec<TimeReportRow> = groups
+        .into_iter()
+        .map(|((project_path, date, session_id), (intervals, sources))| {
+            let active_seconds = union_seconds(intervals);
+            TimeReportRow {
+                project_path,
+                date,
+                session_id,
+                active_seconds,
+                hours: (active_seconds as f64 / 3600.0 * 100.0).round() / 100.0,
+                session_count: sources.len(),
+            }
+        })
+        .collect();
+
+    // Project groupings list the busiest projects first; day groupings stay chronological
+    if group_by == "project" || group_by == "session" {
+        rows.sort_by(|a, b| b.active_seconds.cmp(&a.active_seconds));
+    }
+
+    // The overall total is wall-clock time, independent of the grouping
+    let total_seconds = union_seconds(
+        activities
+            .iter()
+            .flat_map(|a| split_by_day(a, window_start, window_end))
+            .map(|(_, start, end)| (start, end))
+            .collect(),
+    );
+
+    Ok(TimeReport {
+        start_date: start_date.format("%Y-%m-%d").to_string(),
+        end_date: end_date.format("%Y-%m-%d").to_string(),
+        group_by: group_by.to_string(),
+        idle_gap_minutes,
+        total_seconds,
+        total_hours: (total_seconds as f64 / 3600.0 * 100.0).round() / 100.0,
+        rows,
+    })
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn report_to_csv(report: &TimeReport) -> String {
+    let mut csv = String::from("project_path,date,session_id,active_seconds,hours,session_count\n");
+    for row in &report.rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{:.2},{}\n",
+            csv_field(row.project_path.as_deref().unwrap_or("")),
+            csv_field(row.date.as_deref().unwrap_or("")),
+            csv_field(row.session_id.as_deref().unwrap_or("")),
+            row.active_seconds,
+            row.hours,
+            row.session_count
+        ));
+    }
+    csv
+}
+
+/// Active working time derived from session activity and agent run lifetimes.
+/// `group_by` is one of `project`, `day`, `project_day` or `session`.
+#[tauri::command]
+pub async fn get_time_report(
+    db: State<'_, AgentDb>,
+    range: TimeRange,
+    group_by: String,
+    idle_gap_minutes: Option<u32>,
+) -> Result<TimeReport, String> {
+    build_time_report(&db, &range, &group_by, idle_gap_minutes.unwrap_or(DEFAULT_IDLE_GAP_MINUTES))
+}
+
+/// Export a time report as CSV. Writes to `output_path` when given and returns the CSV content.
+#[tauri::command]
+pub async fn export_time_report_csv(
+    db: State<'_, AgentDb>,
+    range: TimeRange,
+    group_by: String,
+    idle_gap_minutes: Option<u32>,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let report = build_time_report(&db, &range, &group_by, idle_gap_minutes.unwrap_or(DEFAULT_IDLE_GAP_MINUTES))?;
+    let csv = report_to_csv(&report);
+
+    if let Some(path) = output_path {
+        fs::write(&path, &csv).map_err(|e| format!("Failed to write time report: {}", e))?;
+        log::info!("Exported time report to {}", path);
+    }
+
+    Ok(csv)
+}
@@ -385,10 +385,14 @@ pub struct CacheStats {
     pub active_entries: usize,
 }
 
-/// 全局翻译服务实例
+/// 全局翻译服务实例（首次使用时加载保存的配置，不拖慢启动）
 static TRANSLATION_SERVICE: once_cell::sync::Lazy<Arc<Mutex<TranslationService>>> =
     once_cell::sync::Lazy::new(|| {
-        Arc::new(Mutex::new(TranslationService::new(TranslationConfig::default())))
+        let config = load_translation_config_from_file().unwrap_or_else(|e| {
+            warn!("Failed to load saved translation config: {}, using default", e);
+            TranslationConfig::default()
+        });
+        Arc::new(Mutex::new(TranslationService::new(config)))
     });
 
 /// 初始化翻译服务
@@ -398,20 +402,6 @@ pub async fn init_translation_service(config: TranslationConfig) {
     info!("Translation service initialized");
 }
 
-/// 使用保存的配置初始化翻译服务
-pub async fn init_translation_service_with_saved_config() {
-    match load_translation_config_from_file() {
-        Ok(config) => {
-            info!("Initializing translation service with saved config");
-            init_translation_service(config).await;
-        }
-        Err(e) => {
-            warn!("Failed to load saved translation config: {}, using default", e);
-            init_translation_service(TranslationConfig::default()).await;
-        }
-    }
-}
-
 /// 获取全局翻译服务
 fn get_translation_service() -> Arc<Mutex<TranslationService>> {
     TRANSLATION_SERVICE.clone()
@@ -419,6 +409,10 @@ fn get_translation_service() -> Arc<Mutex<TranslationService>> {
 
 /// 翻译文本（公共接口）
 pub async fn translate_text(text: &str, target_lang: Option<&str>) -> Result<String> {
+    // 翻译子系统被禁用时原样返回
+    if !super::startup::is_enabled(super::startup::Subsystem::Translator) {
+        return Ok(text.to_string());
+    }
     let service_arc = get_translation_service();
     let service = service_arc.lock().await;
     service.translate(text, target_lang).await
@@ -437,6 +431,9 @@ pub async fn translate(text: String, target_lang: Option<String>) -> Result<Stri
 /// Tauri命令：批量翻译
 #[tauri::command]
 pub async fn translate_batch(texts: Vec<String>, target_lang: Option<String>) -> Result<Vec<String>, String> {
+    if !super::startup::is_enabled(super::startup::Subsystem::Translator) {
+        return Ok(texts);
+    }
     let service_arc = get_translation_service();
     let service = service_arc.lock().await;
     let target = target_lang.as_deref();
@@ -535,7 +532,7 @@ fn load_translation_config_from_file() -> Result<TranslationConfig, String> {
         return Ok(TranslationConfig::default());
     }
     
-    let content = fs::read_to_string(&config_path)
+    let content = crate::config_file::read_to_string(&config_path)
         .map_err(|e| format!("Failed to read translation config: {}", e))?;
     
     let config: TranslationConfig = serde_json::from_str(&content)
@@ -552,7 +549,7 @@ fn save_translation_config_to_file(config: &TranslationConfig) -> Result<(), Str
     let json_string = serde_json::to_string_pretty(config)
         .map_err(|e| format!("Failed to serialize translation config: {}", e))?;
     
-    fs::write(&config_path, json_string)
+    crate::config_file::write(&config_path, json_string)
         .map_err(|e| format!("Failed to write translation config: {}", e))?;
     
     info!("Saved translation config to file: {:?}", config_path);
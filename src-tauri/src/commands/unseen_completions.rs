@@ -0,0 +1,222 @@
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use ts_rs::TS;
+
+use super::agents::AgentDb;
+use super::events::AppEvent;
+use crate::paths;
+
+/// Project the window is showing, as reported by the frontend
+static ACTIVE_PROJECT: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+/// A run that finished while nobody was looking
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct UnseenCompletion {
+    /// The session ID, or `agent-run:<id>` for an agent run that never reported one
+    pub id: String,
+    pub session_id: Option<String>,
+    pub agent_run_id: Option<i64>,
+    pub project_path: String,
+    pub success: bool,
+    pub completed_at: String,
+}
+
+/// Payload of `unseen-completions`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct UnseenCompletionsChanged {
+    pub count: u32,
+    /// The completion just recorded; None when one was marked seen
+    pub completion: Option<UnseenCompletion>,
+}
+
+pub fn init_unseen_completion_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS unseen_completions (
+            id TEXT PRIMARY KEY,
+            session_id TEXT,
+            agent_run_id INTEGER,
+            project_path TEXT NOT NULL,
+            success INTEGER NOT NULL,
+            completed_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn count(conn: &Connection) -> u32 {
+    conn.query_row("SELECT COUNT(*) FROM unseen_completions", [], |row| row.get(0))
+        .unwrap_or(0)
+}
+
+/// Whether a run finishing now in `project_path` would go unnoticed: the window is
+/// hidden, minimized or in the background, or shows another project
+fn unattended(app: &AppHandle, project_path: &str) -> bool {
+    let Some(window) = app.get_webview_window("main") else {
+        return true;
+    };
+    let hidden = !window.is_visible().unwrap_or(true) || window.is_minimized().unwrap_or(false);
+    if hidden || !window.is_focused().unwrap_or(true) {
+        return true;
+    }
+    let active = ACTIVE_PROJECT.lock().ok().and_then(|active| active.clone());
+    active.map_or(false, |active| {
+        paths::comparison_key(&active) != paths::comparison_key(project_path)
+    })
+}
+
+/// Show the unseen count on the app's dock/taskbar badge and tell the frontend
+fn publish(app: &AppHandle, count: u32, completion: Option<UnseenCompletion>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let badge = (count > 0).then_some(i64::from(count));
+        if let Err(e) = window.set_badge_count(badge) {
+            log::debug!("Badge count is not supported here: {}", e);
+        }
+    }
+    let _ = AppEvent::UnseenCompletions(UnseenCompletionsChanged { count, completion }).emit(app);
+}
+
+fn record(app: &AppHandle, completion: UnseenCompletion) {
+    let db = app.state::<AgentDb>();
+    let Ok(conn) = db.0.get() else {
+        return;
+    };
+    let recorded = conn.execute(
+        "INSERT OR REPLACE INTO unseen_completions
+             (id, session_id, agent_run_id, project_path, success, completed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            completion.id,
+            completion.session_id,
+            completion.agent_run_id,
+            completion.project_path,
+            completion.success,
+            completion.completed_at
+        ],
+    );
+    match recorded {
+        Ok(_) => publish(app, count(&conn), Some(completion)),
+        Err(e) => log::warn!("Failed to record unseen completion {}: {}", completion.id, e),
+    }
+}
+
+/// Record a finished Claude session as unseen if it finished unnoticed
+pub fn session_finished(app: &AppHandle, session_id: &str, project_path: &str, success: bool) {
+    if !unattended(app, project_path) {
+        return;
+    }
+    record(
+        app,
+        UnseenCompletion {
+            id: session_id.to_string(),
+            session_id: Some(session_id.to_string()),
+            agent_run_id: None,
+            project_path: project_path.to_string(),
+            success,
+            completed_at: Utc::now().to_rfc3339(),
+        },
+    );
+}
+
+/// Record a finished agent run as unseen if it finished unnoticed
+pub fn agent_run_finished(app: &AppHandle, run_id: i64, success: bool) {
+    let run = app.state::<AgentDb>().0.get().ok().and_then(|conn| {
+        conn.query_row(
+            "SELECT session_id, project_path FROM agent_runs WHERE id = ?1",
+            params![run_id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        )
+        .optional()
+        .ok()
+        .flatten()
+    });
+    let Some((session_id, project_path)) = run else {
+        return;
+    };
+    if !unattended(app, &project_path) {
+        return;
+    }
+    let session_id = Some(session_id).filter(|id| !id.is_empty());
+    record(
+        app,
+        UnseenCompletion {
+            id: session_id.clone().unwrap_or_else(|| format!("agent-run:{}", run_id)),
+            session_id,
+            agent_run_id: Some(run_id),
+            project_path,
+            success,
+            completed_at: Utc::now().to_rfc3339(),
+        },
+    );
+}
+
+/// Restore the badge from the completions left unseen when the app last closed
+pub fn init_unseen_completions(app: &AppHandle) {
+    let db = app.state::<AgentDb>();
+    if let Ok(conn) = db.0.get() {
+        let unseen = count(&conn);
+        if unseen > 0 {
+            publish(app, unseen, None);
+        }
+    };
+}
+
+/// Runs that finished while the window was hidden or showing another project,
+/// newest first
+#[tauri::command]
+pub async fn list_unseen_completions(
+    db: State<'_, AgentDb>,
+) -> Result<Vec<UnseenCompletion>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, session_id, agent_run_id, project_path, success, completed_at
+             FROM unseen_completions ORDER BY completed_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(UnseenCompletion {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                agent_run_id: row.get(2)?,
+                project_path: row.get(3)?,
+                success: row.get(4)?,
+                completed_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Clear an unseen completion by its ID (usually the session ID); returns the count left
+#[tauri::command]
+pub async fn mark_session_seen(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    id: String,
+) -> Result<u32, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let removed = conn
+        .execute("DELETE FROM unseen_completions WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    let unseen = count(&conn);
+    if removed > 0 {
+        publish(&app, unseen, None);
+    }
+    Ok(unseen)
+}
+
+/// Tell the backend which project the window shows, so runs finishing in other
+/// projects are counted as unseen; None when no project is open
+#[tauri::command]
+pub async fn set_active_project(project_path: Option<String>) -> Result<(), String> {
+    *ACTIVE_PROJECT.lock().map_err(|e| e.to_string())? = project_path;
+    Ok(())
+}
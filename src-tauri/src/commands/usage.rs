@@ -76,7 +76,7 @@ fn is_cache_valid(entry: &UsageCacheEntry, current_hash: &str) -> bool {
     is_fresh && hash_matches
 }
 
-fn get_api_base_url() -> String {
+pub(crate) fn get_api_base_url() -> String {
     // First check environment variable
     if let Ok(api_base_url) = env::var("ANTHROPIC_BASE_URL") {
         return api_base_url;
@@ -104,21 +104,35 @@ fn get_api_base_url() -> String {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UsageEntry {
-    timestamp: String,
-    model: String,
-    input_tokens: u64,
-    output_tokens: u64,
-    cache_creation_tokens: u64,
-    cache_read_tokens: u64,
-    cost: f64,
-    session_id: String,
-    project_path: String,
-    api_base_url: String,
+    pub(crate) timestamp: String,
+    pub(crate) model: String,
+    pub(crate) input_tokens: u64,
+    pub(crate) output_tokens: u64,
+    pub(crate) cache_creation_tokens: u64,
+    pub(crate) cache_read_tokens: u64,
+    pub(crate) cost: f64,
+    /// `cost` in the display currency
+    #[serde(default)]
+    pub(crate) cost_local: f64,
+    pub(crate) session_id: String,
+    pub(crate) project_path: String,
+    pub(crate) api_base_url: String,
+    /// How the provider was attributed: `direct`, `router` or `router_trace`
+    #[serde(default = "default_provider_source")]
+    pub(crate) provider_source: String,
+    /// Time from the preceding user or tool-result message to this response, when known
+    #[serde(default)]
+    pub(crate) latency_ms: Option<u64>,
+}
+
+fn default_provider_source() -> String {
+    super::router_usage::SOURCE_DIRECT.to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UsageStats {
     total_cost: f64,
+    total_cost_local: f64,
     total_tokens: u64,
     total_input_tokens: u64,
     total_output_tokens: u64,
@@ -135,6 +149,7 @@ pub struct UsageStats {
 pub struct ModelUsage {
     model: String,
     total_cost: f64,
+    total_cost_local: f64,
     total_tokens: u64,
     input_tokens: u64,
     output_tokens: u64,
@@ -147,6 +162,7 @@ pub struct ModelUsage {
 pub struct DailyUsage {
     date: String,
     total_cost: f64,
+    total_cost_local: f64,
     total_tokens: u64,
     models_used: Vec<String>,
 }
@@ -156,6 +172,7 @@ pub struct ProjectUsage {
     project_path: String,
     project_name: String,
     total_cost: f64,
+    total_cost_local: f64,
     total_tokens: u64,
     session_count: u64,
     last_used: String,
@@ -164,7 +181,9 @@ pub struct ProjectUsage {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ApiBaseUrlUsage {
     api_base_url: String,
+    provider_source: String,
     total_cost: f64,
+    total_cost_local: f64,
     total_tokens: u64,
     input_tokens: u64,
     output_tokens: u64,
@@ -173,6 +192,27 @@ pub struct ApiBaseUrlUsage {
     session_count: u64,
 }
 
+impl UsageStats {
+    /// Fill in the display-currency amounts (`*_local`) from the USD ones
+    fn localized(mut self) -> Self {
+        let rate = super::currency::currency_status().rate;
+        self.total_cost_local = self.total_cost * rate;
+        for model in &mut self.by_model {
+            model.total_cost_local = model.total_cost * rate;
+        }
+        for day in &mut self.by_date {
+            day.total_cost_local = day.total_cost * rate;
+        }
+        for project in &mut self.by_project {
+            project.total_cost_local = project.total_cost * rate;
+        }
+        for api in &mut self.by_api_base_url {
+            api.total_cost_local = api.total_cost * rate;
+        }
+        self
+    }
+}
+
 // Claude 4 pricing constants (per million tokens) - Updated January 2025
 const OPUS_4_INPUT_PRICE: f64 = 15.0;
 const OPUS_4_OUTPUT_PRICE: f64 = 75.0;
@@ -260,6 +300,13 @@ struct UsageData {
     cache_read_input_tokens: Option<u64>,
 }
 
+/// Estimated cost of one message from its `usage` object, for live budget checks
+pub fn estimate_message_cost(model: &str, usage: &serde_json::Value) -> f64 {
+    serde_json::from_value::<UsageData>(usage.clone())
+        .map(|usage| calculate_cost(model, &usage))
+        .unwrap_or(0.0)
+}
+
 fn calculate_cost(model: &str, usage: &UsageData) -> f64 {
     let input_tokens = usage.input_tokens.unwrap_or(0) as f64;
     let output_tokens = usage.output_tokens.unwrap_or(0) as f64;
@@ -326,6 +373,8 @@ fn parse_jsonl_file(
             .and_then(|n| n.to_str())
             .unwrap_or("unknown")
             .to_string();
+        let provider_source = super::router_usage::provider_source_for(&get_api_base_url());
+        let mut prompted_at: Option<DateTime<chrono::FixedOffset>> = None;
 
         for line in content.lines() {
             if line.trim().is_empty() {
@@ -340,6 +389,14 @@ fn parse_jsonl_file(
                     }
                 }
 
+                // Remember when the model was last handed a turn, to time its response
+                if json_value.get("type").and_then(|v| v.as_str()) == Some("user") {
+                    prompted_at = json_value
+                        .get("timestamp")
+                        .and_then(|v| v.as_str())
+                        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok());
+                }
+
                 // Get API Base URL from configuration
                 let api_base_url = get_api_base_url();
 
@@ -391,6 +448,10 @@ fn parse_jsonl_file(
                             let project_path = actual_project_path
                                 .clone()
                                 .unwrap_or_else(|| encoded_project_name.to_string());
+                            let latency_ms = prompted_at.take().and_then(|from| {
+                                let at = DateTime::parse_from_rfc3339(&entry.timestamp).ok()?;
+                                u64::try_from((at - from).num_milliseconds()).ok()
+                            });
 
                             entries.push(UsageEntry {
                                 timestamp: entry.timestamp,
@@ -405,9 +466,12 @@ fn parse_jsonl_file(
                                     .unwrap_or(0),
                                 cache_read_tokens: usage.cache_read_input_tokens.unwrap_or(0),
                                 cost,
+                                cost_local: 0.0,
                                 session_id: entry.session_id.unwrap_or_else(|| session_id.clone()),
                                 project_path,
                                 api_base_url,
+                                provider_source: provider_source.to_string(),
+                                latency_ms,
                             });
                         }
                     }
@@ -520,6 +584,8 @@ fn parse_jsonl_file_fast(
         .and_then(|n| n.to_str())
         .unwrap_or("unknown")
         .to_string();
+    let api_base_url = get_api_base_url();
+    let provider_source = super::router_usage::provider_source_for(&api_base_url);
 
     let mut line_count = 0;
     const MAX_LINES_PER_FILE: usize = 10000; // 限制单文件处理行数
@@ -597,9 +663,12 @@ fn parse_jsonl_file_fast(
                     cache_creation_tokens,
                     cache_read_tokens,
                     cost,
+                    cost_local: 0.0,
                     session_id: json_value.get("sessionId").and_then(|v| v.as_str()).unwrap_or(&session_id).to_string(),
                     project_path,
-                    api_base_url: get_api_base_url(),
+                    api_base_url: api_base_url.clone(),
+                    provider_source: provider_source.to_string(),
+                    latency_ms: None,
                 });
             }
         }
@@ -609,7 +678,7 @@ fn parse_jsonl_file_fast(
 }
 
 // 优化的成本计算函数
-fn calculate_cost_fast(model: &str, input_tokens: u64, output_tokens: u64, cache_creation_tokens: u64, cache_read_tokens: u64) -> f64 {
+pub(crate) fn calculate_cost_fast(model: &str, input_tokens: u64, output_tokens: u64, cache_creation_tokens: u64, cache_read_tokens: u64) -> f64 {
     let (input_price, output_price, cache_write_price, cache_read_price) = match model {
         m if m.contains("opus-4") || m.contains("claude-opus-4") =>
             (OPUS_4_INPUT_PRICE, OPUS_4_OUTPUT_PRICE, OPUS_4_CACHE_WRITE_PRICE, OPUS_4_CACHE_READ_PRICE),
@@ -668,6 +737,25 @@ fn get_all_usage_entries(claude_path: &PathBuf) -> Vec<UsageEntry> {
     all_entries
 }
 
+/// Usage entries whose local date falls within `start..=end`
+pub(crate) fn usage_entries_between(start: NaiveDate, end: NaiveDate) -> Result<Vec<UsageEntry>, String> {
+    let claude_path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?
+        .join(".claude");
+
+    Ok(get_all_usage_entries(&claude_path)
+        .into_iter()
+        .filter(|e| {
+            DateTime::parse_from_rfc3339(&e.timestamp)
+                .map(|dt| {
+                    let date = dt.with_timezone(&Local).date_naive();
+                    date >= start && date <= end
+                })
+                .unwrap_or(false)
+        })
+        .collect())
+}
+
 #[command]
 pub fn get_usage_stats(days: Option<u32>) -> Result<UsageStats, String> {
     let claude_path = dirs::home_dir()
@@ -685,7 +773,7 @@ pub fn get_usage_stats(days: Option<u32>) -> Result<UsageStats, String> {
         if let Some(cached_entry) = cache.get(&cache_key) {
             if is_cache_valid(cached_entry, &current_hash) {
                 log::debug!("Using cached usage stats for key: {}", cache_key);
-                return Ok(cached_entry.data.clone());
+                return Ok(cached_entry.data.clone().localized());
             }
         }
     }
@@ -696,6 +784,7 @@ pub fn get_usage_stats(days: Option<u32>) -> Result<UsageStats, String> {
     if all_entries.is_empty() {
         let empty_stats = UsageStats {
             total_cost: 0.0,
+            total_cost_local: 0.0,
             total_tokens: 0,
             total_input_tokens: 0,
             total_output_tokens: 0,
@@ -717,7 +806,7 @@ pub fn get_usage_stats(days: Option<u32>) -> Result<UsageStats, String> {
             });
         }
         
-        return Ok(empty_stats);
+        return Ok(empty_stats.localized());
     }
 
     // Filter by days if specified
@@ -757,7 +846,7 @@ pub fn get_usage_stats(days: Option<u32>) -> Result<UsageStats, String> {
         }
     }
 
-    Ok(stats)
+    Ok(stats.localized())
 }
 
 // 优化的统计计算函数
@@ -813,6 +902,7 @@ fn calculate_usage_stats_fast(filtered_entries: &[UsageEntry]) -> UsageStats {
         let model_stat = model_stats.entry(entry.model.clone()).or_insert_with(|| ModelUsage {
             model: entry.model.clone(),
             total_cost: 0.0,
+            total_cost_local: 0.0,
             total_tokens: 0,
             input_tokens: 0,
             output_tokens: 0,
@@ -834,6 +924,7 @@ fn calculate_usage_stats_fast(filtered_entries: &[UsageEntry]) -> UsageStats {
         let daily_stat = daily_stats.entry(date.clone()).or_insert_with(|| DailyUsage {
             date,
             total_cost: 0.0,
+            total_cost_local: 0.0,
             total_tokens: 0,
             models_used: Vec::with_capacity(5),
         });
@@ -852,6 +943,7 @@ fn calculate_usage_stats_fast(filtered_entries: &[UsageEntry]) -> UsageStats {
             project_path: entry.project_path.clone(),
             project_name,
             total_cost: 0.0,
+            total_cost_local: 0.0,
             total_tokens: 0,
             session_count: 0,
             last_used: entry.timestamp.clone(),
@@ -868,7 +960,9 @@ fn calculate_usage_stats_fast(filtered_entries: &[UsageEntry]) -> UsageStats {
         // 更新API URL统计
         let api_stat = api_base_url_stats.entry(entry.api_base_url.clone()).or_insert_with(|| ApiBaseUrlUsage {
             api_base_url: entry.api_base_url.clone(),
+            provider_source: entry.provider_source.clone(),
             total_cost: 0.0,
+            total_cost_local: 0.0,
             total_tokens: 0,
             input_tokens: 0,
             output_tokens: 0,
@@ -914,6 +1008,7 @@ fn calculate_usage_stats_fast(filtered_entries: &[UsageEntry]) -> UsageStats {
 
     UsageStats {
         total_cost,
+        total_cost_local: 0.0,
         total_tokens,
         total_input_tokens,
         total_output_tokens,
@@ -932,6 +1027,7 @@ fn calculate_usage_stats_fast(filtered_entries: &[UsageEntry]) -> UsageStats {
 #[derive(Debug, Serialize)]
 pub struct PaginatedUsageStats {
     total_cost: f64,
+    total_cost_local: f64,
     total_tokens: u64,
     total_input_tokens: u64,
     total_output_tokens: u64,
@@ -958,10 +1054,13 @@ pub struct PaginatedData<T> {
 #[derive(Debug, Serialize)]
 pub struct UsageOverview {
     total_cost: f64,
+    total_cost_local: f64,
     total_sessions: u64,
     total_tokens: u64,
     today_cost: f64,
+    today_cost_local: f64,
     week_cost: f64,
+    week_cost_local: f64,
     top_model: Option<String>,
     top_project: Option<String>,
 }
@@ -979,10 +1078,13 @@ pub fn get_usage_overview() -> Result<UsageOverview, String> {
     if recent_entries.is_empty() {
         return Ok(UsageOverview {
             total_cost: 0.0,
+            total_cost_local: 0.0,
             total_sessions: 0,
             total_tokens: 0,
             today_cost: 0.0,
+            today_cost_local: 0.0,
             week_cost: 0.0,
+            week_cost_local: 0.0,
             top_model: None,
             top_project: None,
         });
@@ -1028,12 +1130,16 @@ pub fn get_usage_overview() -> Result<UsageOverview, String> {
         .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
         .map(|(project, _)| project);
 
+    let rate = super::currency::currency_status().rate;
     Ok(UsageOverview {
         total_cost,
+        total_cost_local: total_cost * rate,
         total_sessions: unique_sessions.len() as u64,
         total_tokens,
         today_cost,
+        today_cost_local: today_cost * rate,
         week_cost,
+        week_cost_local: week_cost * rate,
         top_model,
         top_project,
     })
@@ -1135,6 +1241,7 @@ pub fn get_usage_by_date_range(start_date: String, end_date: String) -> Result<U
     if filtered_entries.is_empty() {
         return Ok(UsageStats {
             total_cost: 0.0,
+            total_cost_local: 0.0,
             total_tokens: 0,
             total_input_tokens: 0,
             total_output_tokens: 0,
@@ -1145,7 +1252,8 @@ pub fn get_usage_by_date_range(start_date: String, end_date: String) -> Result<U
             by_date: vec![],
             by_project: vec![],
             by_api_base_url: vec![],
-        });
+        }
+        .localized());
     }
 
     // Calculate aggregated stats (same logic as get_usage_stats)
@@ -1201,6 +1309,7 @@ pub fn get_usage_by_date_range(start_date: String, end_date: String) -> Result<U
             .or_insert(ModelUsage {
                 model: entry.model.clone(),
                 total_cost: 0.0,
+                total_cost_local: 0.0,
                 total_tokens: 0,
                 input_tokens: 0,
                 output_tokens: 0,
@@ -1226,6 +1335,7 @@ pub fn get_usage_by_date_range(start_date: String, end_date: String) -> Result<U
         let daily_stat = daily_stats.entry(date.clone()).or_insert(DailyUsage {
             date,
             total_cost: 0.0,
+            total_cost_local: 0.0,
             total_tokens: 0,
             models_used: vec![],
         });
@@ -1251,6 +1361,7 @@ pub fn get_usage_by_date_range(start_date: String, end_date: String) -> Result<U
                         .unwrap_or(&entry.project_path)
                         .to_string(),
                     total_cost: 0.0,
+                    total_cost_local: 0.0,
                     total_tokens: 0,
                     session_count: 0,
                     last_used: entry.timestamp.clone(),
@@ -1270,7 +1381,9 @@ pub fn get_usage_by_date_range(start_date: String, end_date: String) -> Result<U
             .entry(entry.api_base_url.clone())
             .or_insert(ApiBaseUrlUsage {
                 api_base_url: entry.api_base_url.clone(),
+                provider_source: entry.provider_source.clone(),
                 total_cost: 0.0,
+                total_cost_local: 0.0,
                 total_tokens: 0,
                 input_tokens: 0,
                 output_tokens: 0,
@@ -1317,6 +1430,7 @@ pub fn get_usage_by_date_range(start_date: String, end_date: String) -> Result<U
 
     Ok(UsageStats {
         total_cost,
+        total_cost_local: 0.0,
         total_tokens,
         total_input_tokens,
         total_output_tokens,
@@ -1327,6 +1441,175 @@ pub fn get_usage_by_date_range(start_date: String, end_date: String) -> Result<U
         by_date,
         by_project,
         by_api_base_url,
+    }
+    .localized())
+}
+
+/// One model, as reached through one provider source, in a model comparison report
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModelComparison {
+    model: String,
+    /// `direct`, `router` or `router_trace`
+    provider_source: String,
+    requests: u64,
+    session_count: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    total_tokens: u64,
+    total_cost: f64,
+    total_cost_local: f64,
+    /// Cost of the same tokens on the model the CLI asked for, before the router
+    /// rerouted them; equal to `total_cost` for direct usage
+    requested_cost: f64,
+    cost_per_million_tokens: f64,
+    /// Share of input tokens served from the prompt cache
+    cache_hit_rate: f64,
+    /// None when no response in the group could be timed
+    avg_latency_ms: Option<u64>,
+    median_latency_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModelComparisonReport {
+    start_date: String,
+    end_date: String,
+    total_cost: f64,
+    total_cost_local: f64,
+    /// What router-proxied usage cost on the models it was routed to
+    router_cost: f64,
+    /// What the same usage would have cost on the models the CLI requested
+    router_requested_cost: f64,
+    /// `router_requested_cost - router_cost`; negative when routing cost more
+    router_savings: f64,
+    /// Sorted by cost, highest first
+    models: Vec<ModelComparison>,
+}
+
+#[derive(Default)]
+struct ComparisonTotals {
+    requests: u64,
+    sessions: HashSet<String>,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    cost: f64,
+    requested_cost: f64,
+    latencies: Vec<u64>,
+}
+
+fn parse_report_date(value: &str, label: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").or_else(|_| {
+        DateTime::parse_from_rfc3339(value)
+            .map(|dt| dt.naive_local().date())
+            .map_err(|e| format!("Invalid {} date: {}", label, e))
+    })
+}
+
+/// Compare cost, latency, cache hit rate and tokens per model over a date range. Usage
+/// that went through the router is attributed to the model it was routed to and priced
+/// against the model that was requested, to show what the routing saved.
+#[command]
+pub fn get_model_comparison_report(
+    start_date: String,
+    end_date: String,
+) -> Result<ModelComparisonReport, String> {
+    let start = parse_report_date(&start_date, "start")?;
+    let end = parse_report_date(&end_date, "end")?;
+    if end < start {
+        return Err("The end date is before the start date".to_string());
+    }
+
+    let mut entries = usage_entries_between(start, end)?;
+    let requested_models: Vec<String> = entries.iter().map(|e| e.model.clone()).collect();
+    super::router_usage::reconcile_router_usage(&mut entries);
+
+    let mut groups: HashMap<(String, String), ComparisonTotals> = HashMap::new();
+    for (entry, requested_model) in entries.iter().zip(&requested_models) {
+        let requested_cost = if entry.provider_source == super::router_usage::SOURCE_DIRECT {
+            entry.cost
+        } else {
+            calculate_cost_fast(
+                requested_model,
+                entry.input_tokens,
+                entry.output_tokens,
+                entry.cache_creation_tokens,
+                entry.cache_read_tokens,
+            )
+        };
+        let totals = groups
+            .entry((entry.model.clone(), entry.provider_source.clone()))
+            .or_default();
+        totals.requests += 1;
+        totals.sessions.insert(entry.session_id.clone());
+        totals.input_tokens += entry.input_tokens;
+        totals.output_tokens += entry.output_tokens;
+        totals.cache_creation_tokens += entry.cache_creation_tokens;
+        totals.cache_read_tokens += entry.cache_read_tokens;
+        totals.cost += entry.cost;
+        totals.requested_cost += requested_cost;
+        totals.latencies.extend(entry.latency_ms);
+    }
+
+    let rate = super::currency::currency_status().rate;
+    let mut models: Vec<ModelComparison> = groups
+        .into_iter()
+        .map(|((model, provider_source), mut totals)| {
+            let prompt_tokens =
+                totals.input_tokens + totals.cache_creation_tokens + totals.cache_read_tokens;
+            let total_tokens = prompt_tokens + totals.output_tokens;
+            totals.latencies.sort_unstable();
+            let latency_count = totals.latencies.len() as u64;
+            ModelComparison {
+                model,
+                provider_source,
+                requests: totals.requests,
+                session_count: totals.sessions.len() as u64,
+                input_tokens: totals.input_tokens,
+                output_tokens: totals.output_tokens,
+                cache_creation_tokens: totals.cache_creation_tokens,
+                cache_read_tokens: totals.cache_read_tokens,
+                total_tokens,
+                total_cost: totals.cost,
+                total_cost_local: totals.cost * rate,
+                requested_cost: totals.requested_cost,
+                cost_per_million_tokens: if total_tokens > 0 {
+                    totals.cost * 1_000_000.0 / total_tokens as f64
+                } else {
+                    0.0
+                },
+                cache_hit_rate: if prompt_tokens > 0 {
+                    totals.cache_read_tokens as f64 / prompt_tokens as f64
+                } else {
+                    0.0
+                },
+                avg_latency_ms: (latency_count > 0)
+                    .then(|| totals.latencies.iter().sum::<u64>() / latency_count),
+                median_latency_ms: totals.latencies.get(totals.latencies.len() / 2).copied(),
+            }
+        })
+        .collect();
+    models.sort_by(|a, b| b.total_cost.partial_cmp(&a.total_cost).unwrap());
+
+    let total_cost: f64 = models.iter().map(|m| m.total_cost).sum();
+    let routed = models
+        .iter()
+        .filter(|m| m.provider_source != super::router_usage::SOURCE_DIRECT);
+    let (router_cost, router_requested_cost) = routed.fold((0.0, 0.0), |(cost, requested), m| {
+        (cost + m.total_cost, requested + m.requested_cost)
+    });
+
+    Ok(ModelComparisonReport {
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        total_cost,
+        total_cost_local: total_cost * rate,
+        router_cost,
+        router_requested_cost,
+        router_savings: router_requested_cost - router_cost,
+        models,
     })
 }
 
@@ -1351,6 +1634,10 @@ pub fn get_usage_details(
         all_entries.retain(|e| e.timestamp.starts_with(&date));
     }
 
+    let rate = super::currency::currency_status().rate;
+    for item in &mut all_entries {
+        item.cost_local = item.cost * rate;
+    }
     Ok(all_entries)
 }
 
@@ -1380,6 +1667,7 @@ pub fn get_today_usage_stats() -> Result<UsageStats, String> {
     if today_entries.is_empty() {
         return Ok(UsageStats {
             total_cost: 0.0,
+            total_cost_local: 0.0,
             total_tokens: 0,
             total_input_tokens: 0,
             total_output_tokens: 0,
@@ -1390,7 +1678,8 @@ pub fn get_today_usage_stats() -> Result<UsageStats, String> {
             by_date: vec![],
             by_project: vec![],
             by_api_base_url: vec![],
-        });
+        }
+        .localized());
     }
 
     // Calculate aggregated stats for today
@@ -1446,6 +1735,7 @@ pub fn get_today_usage_stats() -> Result<UsageStats, String> {
             .or_insert(ModelUsage {
                 model: entry.model.clone(),
                 total_cost: 0.0,
+                total_cost_local: 0.0,
                 total_tokens: 0,
                 input_tokens: 0,
                 output_tokens: 0,
@@ -1471,6 +1761,7 @@ pub fn get_today_usage_stats() -> Result<UsageStats, String> {
         let daily_stat = daily_stats.entry(date.clone()).or_insert(DailyUsage {
             date,
             total_cost: 0.0,
+            total_cost_local: 0.0,
             total_tokens: 0,
             models_used: vec![],
         });
@@ -1496,6 +1787,7 @@ pub fn get_today_usage_stats() -> Result<UsageStats, String> {
                         .unwrap_or(&entry.project_path)
                         .to_string(),
                     total_cost: 0.0,
+                    total_cost_local: 0.0,
                     total_tokens: 0,
                     session_count: 0,
                     last_used: entry.timestamp.clone(),
@@ -1515,7 +1807,9 @@ pub fn get_today_usage_stats() -> Result<UsageStats, String> {
             .entry(entry.api_base_url.clone())
             .or_insert(ApiBaseUrlUsage {
                 api_base_url: entry.api_base_url.clone(),
+                provider_source: entry.provider_source.clone(),
                 total_cost: 0.0,
+                total_cost_local: 0.0,
                 total_tokens: 0,
                 input_tokens: 0,
                 output_tokens: 0,
@@ -1562,6 +1856,7 @@ pub fn get_today_usage_stats() -> Result<UsageStats, String> {
 
     Ok(UsageStats {
         total_cost,
+        total_cost_local: 0.0,
         total_tokens,
         total_input_tokens,
         total_output_tokens,
@@ -1572,7 +1867,8 @@ pub fn get_today_usage_stats() -> Result<UsageStats, String> {
         by_date,
         by_project,
         by_api_base_url,
-    })
+    }
+    .localized())
 }
 
 #[command]
@@ -1613,6 +1909,7 @@ pub fn get_session_stats(
                 project_path: entry.project_path.clone(),
                 project_name: entry.session_id.clone(), // Using session_id as project_name for session view
                 total_cost: 0.0,
+                total_cost_local: 0.0,
                 total_tokens: 0,
                 session_count: 0, // In this context, this will count entries per session
                 last_used: " ".to_string(),
@@ -1643,6 +1940,10 @@ pub fn get_session_stats(
         by_session.sort_by(|a, b| b.last_used.cmp(&a.last_used));
     }
 
+    let rate = super::currency::currency_status().rate;
+    for item in &mut by_session {
+        item.total_cost_local = item.total_cost * rate;
+    }
     Ok(by_session)
 }
 
@@ -1652,28 +1953,34 @@ pub fn get_usage_by_api_base_url() -> Result<Vec<ApiBaseUrlUsage>, String> {
         .ok_or("Failed to get home directory")?
         .join(".claude");
 
-    let all_entries = get_all_usage_entries(&claude_path);
+    let mut all_entries = get_all_usage_entries(&claude_path);
+    // Usage proxied by claude-code-router is attributed to the provider that served it
+    super::router_usage::reconcile_router_usage(&mut all_entries);
 
     if all_entries.is_empty() {
         return Ok(vec![]);
     }
 
-    let mut api_base_url_stats: HashMap<String, ApiBaseUrlUsage> = HashMap::new();
+    // Keyed by (API base URL, provider source)
+    let mut api_base_url_stats: HashMap<(String, String), ApiBaseUrlUsage> = HashMap::new();
     
     // Track unique sessions for accurate counting
-    let mut api_sessions: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut api_sessions: HashMap<(String, String), HashSet<String>> = HashMap::new();
 
     for entry in &all_entries {
+        let key = (entry.api_base_url.clone(), entry.provider_source.clone());
         // Track sessions per API base URL
         api_sessions
-            .entry(entry.api_base_url.clone())
+            .entry(key.clone())
             .or_insert_with(HashSet::new)
             .insert(entry.session_id.clone());
         let api_base_url_stat = api_base_url_stats
-            .entry(entry.api_base_url.clone())
+            .entry(key)
             .or_insert(ApiBaseUrlUsage {
                 api_base_url: entry.api_base_url.clone(),
+                provider_source: entry.provider_source.clone(),
                 total_cost: 0.0,
+                total_cost_local: 0.0,
                 total_tokens: 0,
                 input_tokens: 0,
                 output_tokens: 0,
@@ -1691,12 +1998,16 @@ pub fn get_usage_by_api_base_url() -> Result<Vec<ApiBaseUrlUsage>, String> {
         // Session count will be set later from unique session tracking
     }
 
-    let mut by_api_base_url: Vec<ApiBaseUrlUsage> = api_base_url_stats.into_iter().map(|(api_url, mut stat)| {
-        stat.session_count = api_sessions.get(&api_url).map(|s| s.len()).unwrap_or(0) as u64;
+    let mut by_api_base_url: Vec<ApiBaseUrlUsage> = api_base_url_stats.into_iter().map(|(key, mut stat)| {
+        stat.session_count = api_sessions.get(&key).map(|s| s.len()).unwrap_or(0) as u64;
         stat
     }).collect();
     by_api_base_url.sort_by(|a, b| b.total_cost.partial_cmp(&a.total_cost).unwrap());
 
+    let rate = super::currency::currency_status().rate;
+    for item in &mut by_api_base_url {
+        item.total_cost_local = item.total_cost * rate;
+    }
     Ok(by_api_base_url)
 }
 
@@ -1708,6 +2019,7 @@ pub struct ActiveSessionInfo {
     last_activity: String,
     total_tokens: u64,
     total_cost: f64,
+    total_cost_local: f64,
     time_remaining_hours: f64,
     is_active: bool,
 }
@@ -1758,6 +2070,7 @@ pub fn get_active_sessions() -> Result<Vec<ActiveSessionInfo>, String> {
                 last_activity,
                 total_tokens,
                 total_cost,
+                total_cost_local: 0.0,
                 time_remaining_hours: time_remaining.max(0.0),
                 is_active,
             });
@@ -1773,6 +2086,10 @@ pub fn get_active_sessions() -> Result<Vec<ActiveSessionInfo>, String> {
         }
     });
     
+    let rate = super::currency::currency_status().rate;
+    for item in &mut active_sessions {
+        item.total_cost_local = item.total_cost * rate;
+    }
     Ok(active_sessions)
 }
 
@@ -1925,20 +2242,20 @@ pub async fn get_realtime_usage_stats(app: AppHandle) -> Result<Vec<UsageEntry>,
 
     // Get the database from app state
     let agent_db = app.state::<AgentDb>();
-    let conn = agent_db.0.lock().map_err(|e| e.to_string())?;
+    let conn = agent_db.0.get().map_err(|e| e.to_string())?;
 
     // Query recent usage entries from database
     let mut stmt = conn
         .prepare(
             "SELECT session_id, timestamp, model, input_tokens, output_tokens,
-                    cache_creation_tokens, cache_read_tokens, cost, project_path, created_at
+                    cache_creation_tokens, cache_read_tokens, cost, project_path, provider_source
              FROM usage_entries
              ORDER BY created_at DESC
              LIMIT 1000"
         )
         .map_err(|e| e.to_string())?;
 
-    let usage_entries = stmt
+    let mut usage_entries: Vec<UsageEntry> = stmt
         .query_map([], |row| {
             Ok(UsageEntry {
                 session_id: row.get(0)?,
@@ -1949,13 +2266,20 @@ pub async fn get_realtime_usage_stats(app: AppHandle) -> Result<Vec<UsageEntry>,
                 cache_creation_tokens: row.get::<_, i64>(5)? as u64,
                 cache_read_tokens: row.get::<_, i64>(6)? as u64,
                 cost: row.get(7)?,
+                cost_local: 0.0,
                 project_path: row.get(8)?,
                 api_base_url: "https://api.anthropic.com".to_string(), // Default API base URL
+                provider_source: row.get(9)?,
+                latency_ms: None,
             })
         })
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
 
+    let rate = super::currency::currency_status().rate;
+    for item in &mut usage_entries {
+        item.cost_local = item.cost * rate;
+    }
     Ok(usage_entries)
 }
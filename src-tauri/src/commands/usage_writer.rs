@@ -0,0 +1,318 @@
+use once_cell::sync::Lazy;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+
+use super::agents::{insert_usage_rows, AgentDb};
+
+/// Flush buffered rows at least this often
+const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Flush early once this many rows are buffered
+const FLUSH_THRESHOLD: usize = 100;
+
+/// Journal of rows not yet committed, replayed on the next start after a crash
+const JOURNAL_FILE: &str = "usage_journal.jsonl";
+
+/// Rows the database kept rejecting, set aside so they stop being retried
+const DEAD_LETTER_FILE: &str = "usage_dead_letter.jsonl";
+
+/// Failed inserts of a single row before it is moved to the dead-letter file
+const MAX_ROW_ATTEMPTS: u32 = 20;
+
+/// A usage row waiting to be written to `usage_entries`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRow {
+    pub session_id: String,
+    pub timestamp: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub project_path: String,
+    /// `direct`, or `router` when the process talked to claude-code-router
+    #[serde(default = "default_provider_source")]
+    pub provider_source: String,
+    /// Operator id configured when the usage was recorded
+    #[serde(default)]
+    pub operator_id: Option<String>,
+    /// Alias of the rotated provider key that served the session, never the key itself
+    #[serde(default)]
+    pub provider_key: Option<String>,
+    /// Assigned when the row is queued; a row replayed after it was already committed
+    /// is skipped on its id
+    #[serde(default)]
+    pub row_id: Option<String>,
+    /// Failed inserts of this row on its own
+    #[serde(default)]
+    pub attempts: u32,
+}
+
+fn default_provider_source() -> String {
+    super::router_usage::SOURCE_DIRECT.to_string()
+}
+
+#[derive(Default)]
+struct UsageQueue {
+    rows: Vec<UsageRow>,
+    journal: Option<PathBuf>,
+    /// The journal, kept open for appends between rewrites
+    journal_file: Option<File>,
+}
+
+impl UsageQueue {
+    fn append_to_journal(&mut self, row: &UsageRow) -> Result<(), String> {
+        let Some(journal) = &self.journal else {
+            return Ok(());
+        };
+        let file = match &mut self.journal_file {
+            Some(file) => file,
+            None => self.journal_file.insert(open_for_append(journal)?),
+        };
+        let line = serde_json::to_string(row).map_err(|e| e.to_string())?;
+        let written = writeln!(file, "{}", line).map_err(|e| e.to_string());
+        if written.is_err() {
+            // Reopen on the next append
+            self.journal_file = None;
+        }
+        written
+    }
+
+    /// Rewrite the journal so it holds exactly the rows still buffered
+    fn rewrite_journal(&mut self) -> Result<(), String> {
+        let Some(journal) = self.journal.clone() else {
+            return Ok(());
+        };
+        // The open handle points at the file the rewrite replaces
+        self.journal_file = None;
+        rewrite_journal(&journal, &self.rows)
+    }
+}
+
+static USAGE_QUEUE: Lazy<Mutex<UsageQueue>> = Lazy::new(|| Mutex::new(UsageQueue::default()));
+
+/// Wakes the writer when the queue reaches `FLUSH_THRESHOLD`
+static FLUSH_SIGNAL: Lazy<Notify> = Lazy::new(Notify::new);
+
+/// Serializes flushes so the journal is only rewritten by one flush at a time
+static FLUSH_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+/// Buffer a usage row for the next batched write.
+///
+/// The row is appended to the journal before it is buffered, so it survives a crash
+/// before the flush. Cheap enough to call from the stdout streaming loop.
+pub fn queue_usage_entry(row: UsageRow) {
+    let mut queue = match USAGE_QUEUE.lock() {
+        Ok(queue) => queue,
+        Err(e) => {
+            log::warn!("Usage queue unavailable, dropping usage row: {}", e);
+            return;
+        }
+    };
+
+    let mut row = row;
+    if row.row_id.is_none() {
+        row.row_id = Some(uuid::Uuid::new_v4().to_string());
+    }
+    if let Err(e) = queue.append_to_journal(&row) {
+        log::warn!("Failed to journal usage row: {}", e);
+    }
+    super::metrics::record_tokens(&row);
+    queue.rows.push(row);
+
+    if queue.rows.len() >= FLUSH_THRESHOLD {
+        FLUSH_SIGNAL.notify_one();
+    }
+}
+
+fn open_for_append(path: &Path) -> Result<File, String> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| e.to_string())
+}
+
+/// Append rows the database keeps rejecting to the dead-letter file next to the journal
+fn dead_letter(journal: &Path, rows: &[UsageRow]) -> Result<(), String> {
+    let mut file = open_for_append(&journal.with_file_name(DEAD_LETTER_FILE))?;
+    for row in rows {
+        let line = serde_json::to_string(row).map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Rewrite the journal file so it holds exactly `rows`
+fn rewrite_journal(journal: &Path, rows: &[UsageRow]) -> Result<(), String> {
+    if rows.is_empty() {
+        return match fs::remove_file(journal) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+            _ => Ok(()),
+        };
+    }
+
+    let mut content = String::new();
+    for row in rows {
+        content.push_str(&serde_json::to_string(row).map_err(|e| e.to_string())?);
+        content.push('\n');
+    }
+    let tmp = journal.with_extension("jsonl.tmp");
+    fs::write(&tmp, content).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, journal).map_err(|e| e.to_string())
+}
+
+fn read_journal(journal: &Path) -> Vec<UsageRow> {
+    let Ok(file) = fs::File::open(journal) else {
+        return Vec::new();
+    };
+
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str::<UsageRow>(&line) {
+            Ok(row) => Some(row),
+            Err(e) => {
+                // A torn last line from a crash mid-write
+                log::warn!("Skipping unreadable usage journal entry: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Rows buffered but not yet written
+pub fn pending_row_count() -> usize {
+    USAGE_QUEUE.lock().map(|queue| queue.rows.len()).unwrap_or(0)
+}
+
+/// Insert `rows` in one transaction, or one by one when the batch fails so a bad row
+/// cannot hold back the others. Returns the rows that could not be written.
+fn write_rows(conn: &mut Connection, rows: Vec<UsageRow>) -> Vec<UsageRow> {
+    let Err(e) = insert_usage_rows(conn, &rows) else {
+        return Vec::new();
+    };
+    log::warn!("Failed to write {} usage rows, retrying one by one: {}", rows.len(), e);
+    rows.into_iter()
+        .filter_map(|mut row| match insert_usage_rows(conn, std::slice::from_ref(&row)) {
+            Ok(()) => None,
+            Err(e) => {
+                row.attempts += 1;
+                log::warn!("Failed to write usage row {:?} (attempt {}): {}", row.row_id, row.attempts, e);
+                Some(row)
+            }
+        })
+        .collect()
+}
+
+/// Write all buffered rows. Rows that still fail stay queued until they have failed
+/// `MAX_ROW_ATTEMPTS` times, then move to the dead-letter file. Returns the number of
+/// rows written.
+pub async fn flush_usage_queue(app: &AppHandle) -> Result<usize, String> {
+    let _flush = FLUSH_LOCK.lock().await;
+
+    let rows = {
+        let mut queue = USAGE_QUEUE.lock().map_err(|e| e.to_string())?;
+        std::mem::take(&mut queue.rows)
+    };
+    if rows.is_empty() {
+        return Ok(0);
+    }
+    let total = rows.len();
+
+    let db = app.state::<AgentDb>();
+    let failed = match db.0.get() {
+        Ok(mut conn) => Ok(write_rows(&mut conn, rows)),
+        Err(e) => Err((e, rows)),
+    };
+
+    let mut queue = USAGE_QUEUE.lock().map_err(|e| e.to_string())?;
+    let (failed, error) = match failed {
+        Ok(failed) => (failed, None),
+        // Without a connection no row was tried, so none used up an attempt
+        Err((e, rows)) => (rows, Some(e)),
+    };
+
+    let (exhausted, retry): (Vec<_>, Vec<_>) = failed
+        .into_iter()
+        .partition(|row| row.attempts >= MAX_ROW_ATTEMPTS);
+    if !exhausted.is_empty() {
+        log::error!(
+            "Giving up on {} usage rows after {} attempts, moving them to {}",
+            exhausted.len(),
+            MAX_ROW_ATTEMPTS,
+            DEAD_LETTER_FILE
+        );
+        if let Some(journal) = &queue.journal {
+            if let Err(e) = dead_letter(journal, &exhausted) {
+                log::warn!("Failed to write usage dead-letter file: {}", e);
+            }
+        }
+    }
+
+    // Keep the failed rows, in order, ahead of anything queued meanwhile
+    let written = total - exhausted.len() - retry.len();
+    let newer = std::mem::replace(&mut queue.rows, retry);
+    queue.rows.extend(newer);
+    // Also records the attempts of rows that failed again
+    if error.is_none() {
+        if let Err(e) = queue.rewrite_journal() {
+            log::warn!("Failed to compact usage journal: {}", e);
+        }
+    }
+    match error {
+        Some(e) => Err(e),
+        None => Ok(written),
+    }
+}
+
+/// Replay rows left in the journal by a previous run, then start the background writer
+pub fn start_usage_writer(app: AppHandle) {
+    let journal = match app.path().app_data_dir() {
+        Ok(dir) => dir.join(JOURNAL_FILE),
+        Err(e) => {
+            log::warn!("Usage journal disabled, no app data dir: {}", e);
+            spawn_writer(app);
+            return;
+        }
+    };
+
+    let pending = read_journal(&journal);
+    if let Ok(mut queue) = USAGE_QUEUE.lock() {
+        queue.journal = Some(journal);
+        if !pending.is_empty() {
+            // Rows committed just before a crash are skipped on their row_id
+            log::info!("Replaying {} usage rows from journal", pending.len());
+            // Replayed rows go first; the journal already holds them
+            let newer = std::mem::replace(&mut queue.rows, pending);
+            for row in &newer {
+                let _ = queue.append_to_journal(row);
+            }
+            queue.rows.extend(newer);
+        }
+    }
+
+    spawn_writer(app);
+}
+
+fn spawn_writer(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(FLUSH_INTERVAL) => {}
+                _ = FLUSH_SIGNAL.notified() => {}
+            }
+            if let Err(e) = flush_usage_queue(&app).await {
+                log::warn!("Failed to flush usage rows: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,597 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, Timelike};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, State};
+use ts_rs::TS;
+
+use super::agents::AgentDb;
+use super::events::AppEvent;
+use super::session_export::{html_document, html_escape, ExportTheme};
+use super::settings_store;
+use super::time_tracking::{build_time_report, TimeRange, DEFAULT_IDLE_GAP_MINUTES};
+
+/// app_settings key holding the digest schedule/delivery configuration
+const DIGEST_CONFIG_KEY: &str = "weekly_digest_config";
+
+/// app_settings key holding the date of the last scheduled digest
+const DIGEST_LAST_SENT_KEY: &str = "weekly_digest_last_sent";
+
+/// How often the scheduler checks whether a digest is due
+const SCHEDULER_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+const TOP_N: usize = 5;
+
+/// Schedule and delivery settings for the weekly digest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyDigestConfig {
+    /// Generate the digest automatically on schedule
+    pub enabled: bool,
+    /// Day of week to generate on, 0 = Monday ... 6 = Sunday
+    pub weekday: u32,
+    /// Local hour (0-23) after which the digest is generated
+    pub hour: u32,
+    /// Emit a `weekly-digest-ready` event so the UI can show a notification
+    pub notify: bool,
+    /// Optional webhook receiving the digest as JSON (`{"text": <markdown>, ...}`)
+    pub webhook_url: Option<String>,
+}
+
+impl Default for WeeklyDigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            weekday: 0,
+            hour: 9,
+            notify: true,
+            webhook_url: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DigestProject {
+    pub project_path: String,
+    pub cost: f64,
+    #[ts(type = "number")]
+    pub tokens: u64,
+    #[ts(type = "number")]
+    pub sessions: usize,
+    pub active_hours: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DigestSession {
+    pub session_id: String,
+    pub project_path: String,
+    pub cost: f64,
+    pub active_hours: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DigestAgentStats {
+    pub agent_name: String,
+    #[ts(type = "number")]
+    pub runs: usize,
+    #[ts(type = "number")]
+    pub completed: usize,
+    #[ts(type = "number")]
+    pub failed: usize,
+    pub success_rate: f64,
+}
+
+/// Compiled digest data plus the paths of the rendered reports
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct WeeklyDigest {
+    pub start_date: String,
+    pub end_date: String,
+    pub generated_at: String,
+    pub total_cost: f64,
+    /// `total_cost` in `currency`
+    #[serde(default)]
+    pub total_cost_local: f64,
+    /// Display currency when the digest was generated
+    #[serde(default)]
+    pub currency: String,
+    #[ts(type = "number")]
+    pub total_tokens: u64,
+    #[ts(type = "number")]
+    pub total_sessions: usize,
+    pub active_hours: f64,
+    pub cost_by_model: Vec<(String, f64)>,
+    pub top_projects: Vec<DigestProject>,
+    pub costliest_sessions: Vec<DigestSession>,
+    pub longest_sessions: Vec<DigestSession>,
+    pub agents: Vec<DigestAgentStats>,
+    pub anomalies: Vec<String>,
+    pub markdown_path: Option<String>,
+    pub html_path: Option<String>,
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn default_range() -> TimeRange {
+    let today = Local::now().date_naive();
+    TimeRange {
+        start_date: (today - Duration::days(6)).format("%Y-%m-%d").to_string(),
+        end_date: today.format("%Y-%m-%d").to_string(),
+    }
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .or_else(|_| chrono::DateTime::parse_from_rfc3339(value).map(|dt| dt.with_timezone(&Local).date_naive()))
+        .map_err(|e| format!("Invalid date '{}': {}", value, e))
+}
+
+fn load_agent_stats(conn: &Connection, start: NaiveDate, end: NaiveDate) -> Result<Vec<DigestAgentStats>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT agent_name, status, COUNT(*) FROM agent_runs
+             WHERE date(created_at, 'localtime') BETWEEN ?1 AND ?2
+             GROUP BY agent_name, status",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut by_agent: BTreeMap<String, DigestAgentStats> = BTreeMap::new();
+    let rows = stmt
+        .query_map(
+            params![start.format("%Y-%m-%d").to_string(), end.format("%Y-%m-%d").to_string()],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, i64>(2)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    for row in rows.flatten() {
+        let (name, status, count) = row;
+        let stats = by_agent.entry(name.clone()).or_insert(DigestAgentStats {
+            agent_name: name,
+            runs: 0,
+            completed: 0,
+            failed: 0,
+            success_rate: 0.0,
+        });
+        stats.runs += count as usize;
+        match status.as_deref() {
+            Some("completed") => stats.completed += count as usize,
+            Some("failed") | Some("cancelled") => stats.failed += count as usize,
+            _ => {}
+        }
+    }
+
+    let mut agents: Vec<DigestAgentStats> = by_agent
+        .into_values()
+        .map(|mut stats| {
+            stats.success_rate = if stats.runs > 0 {
+                round2(stats.completed as f64 / stats.runs as f64)
+            } else {
+                0.0
+            };
+            stats
+        })
+        .collect();
+    agents.sort_by(|a, b| b.runs.cmp(&a.runs));
+    Ok(agents)
+}
+
+/// Compile digest data for a date range
+fn compile_digest(db: &AgentDb, range: &TimeRange) -> Result<WeeklyDigest, String> {
+    let start = parse_date(&range.start_date)?;
+    let end = parse_date(&range.end_date)?;
+    let entries = super::usage::usage_entries_between(start, end)?;
+
+    let mut total_cost = 0.0;
+    let mut total_tokens = 0u64;
+    let mut sessions: HashSet<String> = HashSet::new();
+    let mut by_model: HashMap<String, f64> = HashMap::new();
+    let mut by_day: BTreeMap<String, f64> = BTreeMap::new();
+    let mut by_project: HashMap<String, (f64, u64, HashSet<String>)> = HashMap::new();
+    let mut by_session: HashMap<String, (String, f64)> = HashMap::new();
+
+    for entry in &entries {
+        let tokens = entry.input_tokens + entry.output_tokens + entry.cache_creation_tokens + entry.cache_read_tokens;
+        total_cost += entry.cost;
+        total_tokens += tokens;
+        sessions.insert(entry.session_id.clone());
+        *by_model.entry(entry.model.clone()).or_default() += entry.cost;
+        *by_day.entry(entry.timestamp.chars().take(10).collect()).or_default() += entry.cost;
+
+        let project = by_project.entry(entry.project_path.clone()).or_default();
+        project.0 += entry.cost;
+        project.1 += tokens;
+        project.2.insert(entry.session_id.clone());
+
+        by_session
+            .entry(entry.session_id.clone())
+            .or_insert_with(|| (entry.project_path.clone(), 0.0))
+            .1 += entry.cost;
+    }
+
+    let project_hours = build_time_report(db, range, "project", DEFAULT_IDLE_GAP_MINUTES)?;
+    let session_hours = build_time_report(db, range, "session", DEFAULT_IDLE_GAP_MINUTES)?;
+    let hours_for_project: HashMap<String, f64> = project_hours
+        .rows
+        .iter()
+        .filter_map(|row| row.project_path.clone().map(|p| (p, row.hours)))
+        .collect();
+    let hours_for_session: HashMap<String, f64> = session_hours
+        .rows
+        .iter()
+        .filter_map(|row| row.session_id.clone().map(|s| (s, row.hours)))
+        .collect();
+
+    let mut top_projects: Vec<DigestProject> = by_project
+        .into_iter()
+        .map(|(project_path, (cost, tokens, sessions))| DigestProject {
+            active_hours: hours_for_project.get(&project_path).copied().unwrap_or(0.0),
+            project_path,
+            cost: round2(cost),
+            tokens,
+            sessions: sessions.len(),
+        })
+        .collect();
+    top_projects.sort_by(|a, b| b.cost.partial_cmp(&a.cost).unwrap_or(std::cmp::Ordering::Equal));
+    top_projects.truncate(TOP_N);
+
+    let all_sessions: Vec<DigestSession> = by_session
+        .iter()
+        .map(|(session_id, (project_path, cost))| DigestSession {
+            session_id: session_id.clone(),
+            project_path: project_path.clone(),
+            cost: round2(*cost),
+            active_hours: hours_for_session.get(session_id).copied().unwrap_or(0.0),
+        })
+        .collect();
+    let mut costliest_sessions = all_sessions.clone();
+    costliest_sessions.sort_by(|a, b| b.cost.partial_cmp(&a.cost).unwrap_or(std::cmp::Ordering::Equal));
+    costliest_sessions.truncate(TOP_N);
+    let mut longest_sessions = all_sessions.clone();
+    longest_sessions.sort_by(|a, b| b.active_hours.partial_cmp(&a.active_hours).unwrap_or(std::cmp::Ordering::Equal));
+    longest_sessions.retain(|s| s.active_hours > 0.0);
+    longest_sessions.truncate(TOP_N);
+
+    let agents = {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        load_agent_stats(&conn, start, end)?
+    };
+
+    // Anomalies: cost spikes on single days or sessions, and unreliable agents
+    let mut anomalies = Vec::new();
+    if by_day.len() >= 3 {
+        let mut daily: Vec<f64> = by_day.values().copied().collect();
+        daily.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let median = daily[daily.len() / 2];
+        for (day, cost) in &by_day {
+            if median > 0.0 && *cost > median * 2.5 && *cost > 1.0 {
+                anomalies.push(format!(
+                    "Spend on {} was ${:.2}, {:.1}× the daily median of ${:.2}",
+                    day, cost, cost / median, median
+                ));
+            }
+        }
+    }
+    if all_sessions.len() >= 3 {
+        let mean = total_cost / all_sessions.len() as f64;
+        for session in &costliest_sessions {
+            if mean > 0.0 && session.cost > mean * 4.0 && session.cost > 1.0 {
+                anomalies.push(format!(
+                    "Session {} cost ${:.2}, {:.1}× the average session",
+                    session.session_id,
+                    session.cost,
+                    session.cost / mean
+                ));
+            }
+        }
+    }
+    for agent in &agents {
+        if agent.runs >= 3 && agent.success_rate < 0.5 {
+            anomalies.push(format!(
+                "Agent '{}' succeeded in only {} of {} runs",
+                agent.agent_name, agent.completed, agent.runs
+            ));
+        }
+    }
+
+    let mut cost_by_model: Vec<(String, f64)> = by_model.into_iter().map(|(m, c)| (m, round2(c))).collect();
+    cost_by_model.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let currency = super::currency::currency_status();
+    Ok(WeeklyDigest {
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        generated_at: Local::now().to_rfc3339(),
+        total_cost: round2(total_cost),
+        total_cost_local: round2(total_cost * currency.rate),
+        currency: currency.display_currency,
+        total_tokens,
+        total_sessions: sessions.len(),
+        active_hours: project_hours.total_hours,
+        cost_by_model,
+        top_projects,
+        costliest_sessions,
+        longest_sessions,
+        agents,
+        anomalies,
+        markdown_path: None,
+        html_path: None,
+    })
+}
+
+fn render_markdown(digest: &WeeklyDigest) -> String {
+    let mut md = format!("# Weekly Digest: {} – {}\n\n", digest.start_date, digest.end_date);
+
+    md.push_str("## Summary\n\n");
+    md.push_str(&format!(
+        "- **Cost:** ${:.2}\n- **Tokens:** {}\n- **Sessions:** {}\n- **Active time:** {:.1} h\n\n",
+        digest.total_cost, digest.total_tokens, digest.total_sessions, digest.active_hours
+    ));
+
+    if !digest.cost_by_model.is_empty() {
+        md.push_str("### Cost by model\n\n| Model | Cost |\n|---|---:|\n");
+        for (model, cost) in &digest.cost_by_model {
+            md.push_str(&format!("| {} | ${:.2} |\n", model, cost));
+        }
+        md.push('\n');
+    }
+
+    md.push_str("## Top projects\n\n");
+    if digest.top_projects.is_empty() {
+        md.push_str("_No activity._\n\n");
+    } else {
+        md.push_str("| Project | Cost | Sessions | Active hours |\n|---|---:|---:|---:|\n");
+        for p in &digest.top_projects {
+            md.push_str(&format!("| `{}` | ${:.2} | {} | {:.1} |\n", p.project_path, p.cost, p.sessions, p.active_hours));
+        }
+        md.push('\n');
+    }
+
+    md.push_str("## Notable sessions\n\n");
+    if digest.costliest_sessions.is_empty() {
+        md.push_str("_No sessions._\n\n");
+    } else {
+        md.push_str("**Most expensive**\n\n");
+        for s in &digest.costliest_sessions {
+            md.push_str(&format!("- `{}` ({}) – ${:.2}\n", s.session_id, s.project_path, s.cost));
+        }
+        md.push('\n');
+        if !digest.longest_sessions.is_empty() {
+            md.push_str("**Longest**\n\n");
+            for s in &digest.longest_sessions {
+                md.push_str(&format!("- `{}` ({}) – {:.1} h\n", s.session_id, s.project_path, s.active_hours));
+            }
+            md.push('\n');
+        }
+    }
+
+    md.push_str("## Agents\n\n");
+    if digest.agents.is_empty() {
+        md.push_str("_No agent runs._\n\n");
+    } else {
+        md.push_str("| Agent | Runs | Completed | Failed | Success rate |\n|---|---:|---:|---:|---:|\n");
+        for a in &digest.agents {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {:.0}% |\n",
+                a.agent_name, a.runs, a.completed, a.failed, a.success_rate * 100.0
+            ));
+        }
+        md.push('\n');
+    }
+
+    md.push_str("## Anomalies\n\n");
+    if digest.anomalies.is_empty() {
+        md.push_str("_Nothing unusual this week._\n");
+    } else {
+        for anomaly in &digest.anomalies {
+            md.push_str(&format!("- ⚠️ {}\n", anomaly));
+        }
+    }
+
+    md
+}
+
+fn render_html(digest: &WeeklyDigest) -> String {
+    let table = |headers: &[&str], rows: Vec<Vec<String>>| -> String {
+        let mut html = String::from("<table><thead><tr>");
+        for h in headers {
+            html.push_str(&format!("<th>{}</th>", h));
+        }
+        html.push_str("</tr></thead><tbody>");
+        for row in rows {
+            html.push_str("<tr>");
+            for cell in row {
+                html.push_str(&format!("<td>{}</td>", html_escape(&cell)));
+            }
+            html.push_str("</tr>");
+        }
+        html.push_str("</tbody></table>");
+        html
+    };
+
+    let mut body = format!(
+        "<h1>Weekly Digest: {} – {}</h1><ul><li><b>Cost:</b> ${:.2}</li><li><b>Tokens:</b> {}</li><li><b>Sessions:</b> {}</li><li><b>Active time:</b> {:.1} h</li></ul>",
+        digest.start_date, digest.end_date, digest.total_cost, digest.total_tokens, digest.total_sessions, digest.active_hours
+    );
+    body.push_str("<h2>Top projects</h2>");
+    body.push_str(&table(
+        &["Project", "Cost", "Sessions", "Active hours"],
+        digest
+            .top_projects
+            .iter()
+            .map(|p| vec![p.project_path.clone(), format!("${:.2}", p.cost), p.sessions.to_string(), format!("{:.1}", p.active_hours)])
+            .collect(),
+    ));
+    body.push_str("<h2>Most expensive sessions</h2>");
+    body.push_str(&table(
+        &["Session", "Project", "Cost"],
+        digest
+            .costliest_sessions
+            .iter()
+            .map(|s| vec![s.session_id.clone(), s.project_path.clone(), format!("${:.2}", s.cost)])
+            .collect(),
+    ));
+    body.push_str("<h2>Longest sessions</h2>");
+    body.push_str(&table(
+        &["Session", "Project", "Active hours"],
+        digest
+            .longest_sessions
+            .iter()
+            .map(|s| vec![s.session_id.clone(), s.project_path.clone(), format!("{:.1}", s.active_hours)])
+            .collect(),
+    ));
+    body.push_str("<h2>Agents</h2>");
+    body.push_str(&table(
+        &["Agent", "Runs", "Completed", "Failed", "Success rate"],
+        digest
+            .agents
+            .iter()
+            .map(|a| {
+                vec![
+                    a.agent_name.clone(),
+                    a.runs.to_string(),
+                    a.completed.to_string(),
+                    a.failed.to_string(),
+                    format!("{:.0}%", a.success_rate * 100.0),
+                ]
+            })
+            .collect(),
+    ));
+    body.push_str("<h2>Anomalies</h2><ul>");
+    if digest.anomalies.is_empty() {
+        body.push_str("<li>Nothing unusual this week.</li>");
+    }
+    for anomaly in &digest.anomalies {
+        body.push_str(&format!("<li>{}</li>", html_escape(anomaly)));
+    }
+    body.push_str("</ul>");
+
+    // Digests are generated in the background, so follow the viewer's color scheme
+    html_document(&format!("Weekly Digest {}", digest.end_date), &body, ExportTheme::Auto)
+}
+
+fn digests_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("digests");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create digests directory: {}", e))?;
+    Ok(dir)
+}
+
+fn load_config(conn: &Connection) -> WeeklyDigestConfig {
+    settings_store::get_or_default(conn, DIGEST_CONFIG_KEY)
+}
+
+/// Compile, render and save the digest; deliver it according to the configuration when `deliver` is set
+async fn build_and_save_digest(app: &AppHandle, range: TimeRange, deliver: bool) -> Result<WeeklyDigest, String> {
+    let db = app.state::<AgentDb>();
+    let mut digest = compile_digest(&db, &range)?;
+    let config = {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        load_config(&conn)
+    };
+
+    let markdown = render_markdown(&digest);
+    let dir = digests_dir(app)?;
+    let base = format!("digest-{}_{}", digest.start_date, digest.end_date);
+    let markdown_path = dir.join(format!("{}.md", base));
+    let html_path = dir.join(format!("{}.html", base));
+    fs::write(&markdown_path, &markdown).map_err(|e| format!("Failed to write digest: {}", e))?;
+    fs::write(&html_path, render_html(&digest)).map_err(|e| format!("Failed to write digest: {}", e))?;
+    digest.markdown_path = Some(markdown_path.to_string_lossy().to_string());
+    digest.html_path = Some(html_path.to_string_lossy().to_string());
+    log::info!("Weekly digest written to {:?}", markdown_path);
+
+    if deliver {
+        if config.notify {
+            let _ = AppEvent::WeeklyDigestReady(digest.clone()).emit(app);
+        }
+        if let Some(url) = config.webhook_url.as_deref().filter(|u| !u.trim().is_empty()) {
+            let payload = serde_json::json!({
+                "text": markdown,
+                "digest": digest,
+            });
+            match reqwest::Client::new().post(url).json(&payload).send().await {
+                Ok(response) if response.status().is_success() => {
+                    log::info!("Weekly digest delivered to webhook");
+                }
+                Ok(response) => log::warn!("Digest webhook returned {}", response.status()),
+                Err(e) => log::warn!("Failed to deliver digest webhook: {}", e),
+            }
+        }
+    }
+
+    Ok(digest)
+}
+
+/// Start the background task that generates the digest on its configured weekday/hour
+pub fn start_digest_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SCHEDULER_INTERVAL).await;
+
+            let now = Local::now();
+            let today = now.date_naive().format("%Y-%m-%d").to_string();
+            let (config, last_sent) = {
+                let db = app.state::<AgentDb>();
+                let conn = match db.0.get() {
+                    Ok(conn) => conn,
+                    Err(_) => continue,
+                };
+                let last_sent: Option<String> = settings_store::get_or_default(&conn, DIGEST_LAST_SENT_KEY);
+                (load_config(&conn), last_sent)
+            };
+
+            let due = config.enabled
+                && now.weekday().num_days_from_monday() == config.weekday
+                && now.hour() >= config.hour
+                && last_sent.as_deref() != Some(today.as_str());
+            if !due {
+                continue;
+            }
+
+            match build_and_save_digest(&app, default_range(), true).await {
+                Ok(_) => {
+                    if let Err(e) = settings_store::set(&app, DIGEST_LAST_SENT_KEY, &today) {
+                        log::warn!("{}", e);
+                    }
+                }
+                Err(e) => log::warn!("Scheduled weekly digest failed: {}", e),
+            };
+        }
+    });
+}
+
+/// Generate the digest for a date range (defaults to the last 7 days) and save it as Markdown and HTML.
+/// Set `deliver` to also send it through the configured notification/webhook.
+#[tauri::command]
+pub async fn generate_weekly_digest(
+    app: AppHandle,
+    range: Option<TimeRange>,
+    deliver: Option<bool>,
+) -> Result<WeeklyDigest, String> {
+    build_and_save_digest(&app, range.unwrap_or_else(default_range), deliver.unwrap_or(false)).await
+}
+
+/// Get the digest schedule and delivery configuration
+#[tauri::command]
+pub async fn get_weekly_digest_config(db: State<'_, AgentDb>) -> Result<WeeklyDigestConfig, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    Ok(load_config(&conn))
+}
+
+/// Update the digest schedule and delivery configuration
+#[tauri::command]
+pub async fn set_weekly_digest_config(app: AppHandle, config: WeeklyDigestConfig) -> Result<(), String> {
+    if config.weekday > 6 || config.hour > 23 {
+        return Err("weekday must be 0-6 and hour 0-23".to_string());
+    }
+    settings_store::set(&app, DIGEST_CONFIG_KEY, &config)
+}
@@ -0,0 +1,700 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tauri::{AppHandle, State};
+
+use super::agents::AgentDb;
+use super::api_manifest::{API_VERSION_MAJOR, API_VERSION_MINOR};
+use super::claude::get_claude_dir;
+use super::execution_profiles::ExecutionProfileConfig;
+use super::permission_config::ClaudeExecutionConfig;
+use super::project_gitignore::is_secret_key;
+use super::provider::ProviderConfig;
+use super::settings_store;
+use super::translator::TranslationConfig;
+
+/// Bumped when the layout of the exported file changes
+const SETTINGS_FORMAT_VERSION: u32 = 1;
+
+const SECTION_AGENTS: &str = "agents";
+const SECTION_PROVIDERS: &str = "providers";
+const SECTION_HOOKS: &str = "hooks";
+const SECTION_SLASH_COMMANDS: &str = "slash_commands";
+const SECTION_TRANSLATION: &str = "translation";
+const SECTION_ROUTER: &str = "router";
+const SECTION_EXECUTION_CONFIG: &str = "execution_config";
+const SECTION_EXECUTION_PROFILES: &str = "execution_profiles";
+
+/// What `import_workbench_settings` does with an item that already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// Keep the existing item
+    #[default]
+    Skip,
+    /// Replace the existing item
+    Overwrite,
+    /// Import the item under a new name next to the existing one. Hooks, translation,
+    /// router and execution config exist only once and are kept.
+    Rename,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedAgent {
+    pub name: String,
+    pub icon: String,
+    pub system_prompt: String,
+    pub default_task: Option<String>,
+    pub model: String,
+    pub enable_file_read: bool,
+    pub enable_file_write: bool,
+    pub enable_network: bool,
+    pub hooks: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedProfile {
+    pub name: String,
+    pub description: String,
+    pub config: ExecutionProfileConfig,
+}
+
+/// The exported file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkbenchSettings {
+    pub format_version: u32,
+    pub app_version: String,
+    /// Backend API version, `major.minor`
+    pub api_version: String,
+    pub exported_at: String,
+    /// API keys and tokens are blanked unless the export asked for them
+    pub includes_secrets: bool,
+    #[serde(default)]
+    pub agents: Vec<ExportedAgent>,
+    /// Provider presets
+    #[serde(default)]
+    pub providers: Vec<ProviderConfig>,
+    /// `hooks` of ~/.claude/settings.json
+    pub hooks: Option<Value>,
+    /// User slash commands, by path relative to ~/.claude/commands
+    #[serde(default)]
+    pub slash_commands: BTreeMap<String, String>,
+    pub translation: Option<Value>,
+    /// ~/.claude-code-router/config.json
+    pub router: Option<Value>,
+    /// Default permissions and CLI options
+    pub execution_config: Option<Value>,
+    /// Named permission and budget presets
+    #[serde(default)]
+    pub execution_profiles: Vec<ExportedProfile>,
+}
+
+/// Returned by `export_workbench_settings`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsExportSummary {
+    pub path: String,
+    pub exported_at: String,
+    pub includes_secrets: bool,
+    /// Items exported per section
+    pub sections: BTreeMap<String, usize>,
+}
+
+/// What happened to the items of one section
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SectionImport {
+    pub imported: usize,
+    pub replaced: usize,
+    pub renamed: usize,
+    pub skipped: usize,
+}
+
+/// Returned by `import_workbench_settings`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsImportSummary {
+    /// Version of the app that created the file
+    pub app_version: String,
+    pub exported_at: String,
+    pub sections: BTreeMap<String, SectionImport>,
+    /// Items that could not be imported; the rest of the file still is
+    pub warnings: Vec<String>,
+}
+
+fn commands_dir() -> Result<PathBuf, String> {
+    Ok(get_claude_dir().map_err(|e| e.to_string())?.join("commands"))
+}
+
+fn settings_path() -> Result<PathBuf, String> {
+    Ok(get_claude_dir().map_err(|e| e.to_string())?.join("settings.json"))
+}
+
+fn translation_path() -> Result<PathBuf, String> {
+    Ok(get_claude_dir().map_err(|e| e.to_string())?.join("translation_config.json"))
+}
+
+fn router_path() -> Result<PathBuf, String> {
+    super::router_usage::router_dir()
+        .map(|dir| dir.join("config.json"))
+        .ok_or_else(|| "Could not find home directory".to_string())
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    let content = crate::config_file::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_json(path: &Path, value: &Value) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    crate::config_file::write_json(path, value)
+}
+
+/// Blank the string values under credential-like keys
+fn strip_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                if is_secret_key(key) && child.is_string() {
+                    *child = Value::String(String::new());
+                } else {
+                    strip_secrets(child);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_secrets),
+        _ => {}
+    }
+}
+
+/// Fill secrets blanked by `strip_secrets` from the matching values of `existing`
+fn restore_secrets(incoming: &mut Value, existing: &Value) {
+    match (incoming, existing) {
+        (Value::Object(map), Value::Object(old)) => {
+            for (key, child) in map.iter_mut() {
+                let Some(old_child) = old.get(key) else {
+                    continue;
+                };
+                if is_secret_key(key) && child.as_str() == Some("") {
+                    *child = old_child.clone();
+                } else {
+                    restore_secrets(child, old_child);
+                }
+            }
+        }
+        (Value::Array(items), Value::Array(old)) => {
+            for (item, old_item) in items.iter_mut().zip(old) {
+                restore_secrets(item, old_item);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Markdown files under `dir`, by path relative to `base` with `/` separators
+fn collect_commands(base: &Path, dir: &Path, found: &mut BTreeMap<String, String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_commands(base, &path, found);
+        } else if path.extension().map_or(false, |ext| ext == "md") {
+            let (Ok(rel), Ok(content)) = (path.strip_prefix(base), fs::read_to_string(&path)) else {
+                continue;
+            };
+            let rel: Vec<_> = rel.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+            found.insert(rel.join("/"), content);
+        }
+    }
+}
+
+/// Refuse command paths that would escape ~/.claude/commands
+fn command_path(base: &Path, rel: &str) -> Result<PathBuf, String> {
+    let rel_path = Path::new(rel);
+    let plain = rel_path.components().all(|c| matches!(c, Component::Normal(_)));
+    if !plain || rel_path.extension().map_or(true, |ext| ext != "md") {
+        return Err(format!("Invalid slash command path: {}", rel));
+    }
+    Ok(base.join(rel_path))
+}
+
+/// `name (Imported)`, numbered when that is taken too
+fn unique_name(
+    name: &str,
+    exists: impl Fn(&str) -> Result<bool, String>,
+) -> Result<String, String> {
+    let mut candidate = format!("{} (Imported)", name);
+    let mut n = 2;
+    while exists(&candidate)? {
+        candidate = format!("{} (Imported {})", name, n);
+        n += 1;
+    }
+    Ok(candidate)
+}
+
+fn export_agents(conn: &Connection) -> Result<Vec<ExportedAgent>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name, icon, system_prompt, default_task, model, enable_file_read,
+                    enable_file_write, enable_network, hooks
+             FROM agents ORDER BY name",
+        )
+        .map_err(|e| e.to_string())?;
+    let agents = stmt
+        .query_map([], |row| {
+            Ok(ExportedAgent {
+                name: row.get(0)?,
+                icon: row.get(1)?,
+                system_prompt: row.get(2)?,
+                default_task: row.get(3)?,
+                model: row.get(4)?,
+                enable_file_read: row.get(5)?,
+                enable_file_write: row.get(6)?,
+                enable_network: row.get(7)?,
+                hooks: row.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(agents)
+}
+
+fn export_profiles(conn: &Connection) -> Result<Vec<ExportedProfile>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name, description, config FROM execution_profiles ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(name, description, config)| {
+            let config = serde_json::from_str(&config).ok()?;
+            Some(ExportedProfile { name, description, config })
+        })
+        .collect())
+}
+
+fn collect_settings(conn: &Connection, include_secrets: bool) -> Result<WorkbenchSettings, String> {
+    let mut providers = super::provider::get_provider_presets()?;
+    let mut translation = read_json(&translation_path()?);
+    let mut router = read_json(&router_path()?);
+    if !include_secrets {
+        providers = providers
+            .into_iter()
+            .map(|provider| {
+                let mut value = serde_json::to_value(&provider).map_err(|e| e.to_string())?;
+                strip_secrets(&mut value);
+                serde_json::from_value(value).map_err(|e| e.to_string())
+            })
+            .collect::<Result<_, String>>()?;
+        translation.iter_mut().for_each(strip_secrets);
+        router.iter_mut().for_each(strip_secrets);
+    }
+
+    let mut slash_commands = BTreeMap::new();
+    let commands = commands_dir()?;
+    collect_commands(&commands, &commands, &mut slash_commands);
+
+    Ok(WorkbenchSettings {
+        format_version: SETTINGS_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        api_version: format!("{}.{}", API_VERSION_MAJOR, API_VERSION_MINOR),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        includes_secrets: include_secrets,
+        agents: export_agents(conn)?,
+        providers,
+        hooks: read_json(&settings_path()?).and_then(|settings| settings.get("hooks").cloned()),
+        slash_commands,
+        translation,
+        router,
+        execution_config: settings_store::get(conn, settings_store::EXECUTION_CONFIG).ok().flatten(),
+        execution_profiles: export_profiles(conn)?,
+    })
+}
+
+fn import_agents(
+    conn: &Connection,
+    agents: Vec<ExportedAgent>,
+    on_conflict: ConflictResolution,
+    result: &mut SectionImport,
+) -> Result<(), String> {
+    let exists = |name: &str| {
+        conn.query_row("SELECT COUNT(*) FROM agents WHERE name = ?1", [name], |row| {
+            row.get::<_, i64>(0)
+        })
+        .map(|count| count > 0)
+        .map_err(|e| e.to_string())
+    };
+    for mut agent in agents {
+        if exists(&agent.name)? {
+            match on_conflict {
+                ConflictResolution::Skip => {
+                    result.skipped += 1;
+                    continue;
+                }
+                ConflictResolution::Overwrite => {
+                    conn.execute(
+                        "UPDATE agents SET icon = ?2, system_prompt = ?3, default_task = ?4,
+                             model = ?5, enable_file_read = ?6, enable_file_write = ?7,
+                             enable_network = ?8, hooks = ?9, updated_at = CURRENT_TIMESTAMP
+                         WHERE name = ?1",
+                        params![
+                            agent.name,
+                            agent.icon,
+                            agent.system_prompt,
+                            agent.default_task,
+                            agent.model,
+                            agent.enable_file_read,
+                            agent.enable_file_write,
+                            agent.enable_network,
+                            agent.hooks
+                        ],
+                    )
+                    .map_err(|e| e.to_string())?;
+                    result.replaced += 1;
+                    continue;
+                }
+                ConflictResolution::Rename => {
+                    agent.name = unique_name(&agent.name, &exists)?;
+                    result.renamed += 1;
+                }
+            }
+        } else {
+            result.imported += 1;
+        }
+        conn.execute(
+            "INSERT INTO agents (name, icon, system_prompt, default_task, model, enable_file_read,
+                                 enable_file_write, enable_network, hooks)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                agent.name,
+                agent.icon,
+                agent.system_prompt,
+                agent.default_task,
+                agent.model,
+                agent.enable_file_read,
+                agent.enable_file_write,
+                agent.enable_network,
+                agent.hooks
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn import_providers(
+    providers: Vec<ProviderConfig>,
+    on_conflict: ConflictResolution,
+    result: &mut SectionImport,
+    warnings: &mut Vec<String>,
+) -> Result<(), String> {
+    let existing = super::provider::get_provider_presets()?;
+    for mut provider in providers {
+        let current = existing.iter().find(|p| p.id == provider.id);
+        match (current, on_conflict) {
+            (Some(_), ConflictResolution::Skip) => {
+                result.skipped += 1;
+                continue;
+            }
+            (Some(current), ConflictResolution::Overwrite) => {
+                let mut value = serde_json::to_value(&provider).map_err(|e| e.to_string())?;
+                let old = serde_json::to_value(current).map_err(|e| e.to_string())?;
+                restore_secrets(&mut value, &old);
+                provider = serde_json::from_value(value).map_err(|e| e.to_string())?;
+            }
+            (Some(_), ConflictResolution::Rename) => {
+                let taken = |id: &str| existing.iter().any(|p| p.id == id);
+                let mut id = format!("{}-imported", provider.id);
+                let mut n = 2;
+                while taken(&id) {
+                    id = format!("{}-imported-{}", provider.id, n);
+                    n += 1;
+                }
+                provider.id = id;
+                provider.name = format!("{} (Imported)", provider.name);
+            }
+            (None, _) => {}
+        }
+        provider.auth_token = provider.auth_token.filter(|s| !s.is_empty());
+        provider.api_key = provider.api_key.filter(|s| !s.is_empty());
+
+        let replacing = current.is_some() && on_conflict == ConflictResolution::Overwrite;
+        let name = provider.name.clone();
+        let saved = if replacing {
+            super::provider::update_provider_config(provider)
+        } else {
+            super::provider::add_provider_config(provider)
+        };
+        match saved {
+            Ok(_) if replacing => result.replaced += 1,
+            Ok(_) if current.is_some() => result.renamed += 1,
+            Ok(_) => result.imported += 1,
+            Err(e) => warnings.push(format!("Provider {}: {}", name, e)),
+        }
+    }
+    Ok(())
+}
+
+fn import_slash_commands(
+    commands: BTreeMap<String, String>,
+    on_conflict: ConflictResolution,
+    result: &mut SectionImport,
+    warnings: &mut Vec<String>,
+) -> Result<(), String> {
+    let base = commands_dir()?;
+    for (rel, content) in commands {
+        let mut path = match command_path(&base, &rel) {
+            Ok(path) => path,
+            Err(e) => {
+                warnings.push(e);
+                continue;
+            }
+        };
+        if path.exists() {
+            match on_conflict {
+                ConflictResolution::Skip => {
+                    result.skipped += 1;
+                    continue;
+                }
+                ConflictResolution::Overwrite => result.replaced += 1,
+                ConflictResolution::Rename => {
+                    let stem = path
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let mut n = 1;
+                    while path.exists() {
+                        let suffix = if n == 1 { String::new() } else { format!("-{}", n) };
+                        path.set_file_name(format!("{}-imported{}.md", stem, suffix));
+                        n += 1;
+                    }
+                    result.renamed += 1;
+                }
+            }
+        } else {
+            result.imported += 1;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        crate::config_file::write(&path, content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+fn import_profiles(
+    conn: &Connection,
+    profiles: Vec<ExportedProfile>,
+    on_conflict: ConflictResolution,
+    result: &mut SectionImport,
+) -> Result<(), String> {
+    let exists = |name: &str| {
+        conn.query_row("SELECT COUNT(*) FROM execution_profiles WHERE name = ?1", [name], |row| {
+            row.get::<_, i64>(0)
+        })
+        .map(|count| count > 0)
+        .map_err(|e| e.to_string())
+    };
+    for mut profile in profiles {
+        if exists(&profile.name)? {
+            match on_conflict {
+                ConflictResolution::Skip => {
+                    result.skipped += 1;
+                    continue;
+                }
+                ConflictResolution::Overwrite => result.replaced += 1,
+                ConflictResolution::Rename => {
+                    profile.name = unique_name(&profile.name, &exists)?;
+                    result.renamed += 1;
+                }
+            }
+        } else {
+            result.imported += 1;
+        }
+        let config = serde_json::to_string(&profile.config).map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO execution_profiles (name, description, config) VALUES (?1, ?2, ?3)
+             ON CONFLICT(name) DO UPDATE SET
+                 description = excluded.description,
+                 config = excluded.config,
+                 updated_at = CURRENT_TIMESTAMP",
+            params![profile.name, profile.description, config],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Whether a single config is written: it is missing, or the import overwrites it
+fn should_write(exists: bool, on_conflict: ConflictResolution, result: &mut SectionImport) -> bool {
+    if !exists {
+        result.imported += 1;
+        true
+    } else if on_conflict == ConflictResolution::Overwrite {
+        result.replaced += 1;
+        true
+    } else {
+        result.skipped += 1;
+        false
+    }
+}
+
+/// Write agents, provider presets, hooks, user slash commands, translation, router and
+/// execution config and execution profiles to a single JSON file. API keys and tokens are
+/// left out unless `include_secrets` is set.
+#[tauri::command]
+pub async fn export_workbench_settings(
+    db: State<'_, AgentDb>,
+    dest: String,
+    include_secrets: Option<bool>,
+) -> Result<SettingsExportSummary, String> {
+    let include_secrets = include_secrets.unwrap_or(false);
+    let settings = {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        collect_settings(&conn, include_secrets)?
+    };
+
+    let sections = BTreeMap::from([
+        (SECTION_AGENTS.to_string(), settings.agents.len()),
+        (SECTION_PROVIDERS.to_string(), settings.providers.len()),
+        (SECTION_HOOKS.to_string(), settings.hooks.iter().count()),
+        (SECTION_SLASH_COMMANDS.to_string(), settings.slash_commands.len()),
+        (SECTION_TRANSLATION.to_string(), settings.translation.iter().count()),
+        (SECTION_ROUTER.to_string(), settings.router.iter().count()),
+        (SECTION_EXECUTION_CONFIG.to_string(), settings.execution_config.iter().count()),
+        (SECTION_EXECUTION_PROFILES.to_string(), settings.execution_profiles.len()),
+    ]);
+    let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    fs::write(&dest, content).map_err(|e| format!("Failed to write {}: {}", dest, e))?;
+    log::info!("Exported workbench settings to {}", dest);
+
+    Ok(SettingsExportSummary {
+        path: dest,
+        exported_at: settings.exported_at,
+        includes_secrets: settings.includes_secrets,
+        sections,
+    })
+}
+
+/// Import a file written by `export_workbench_settings`. Items that already exist are
+/// handled by `on_conflict`, skipped by default; secrets left out of the export keep
+/// their current values when an item is overwritten.
+#[tauri::command]
+pub async fn import_workbench_settings(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    path: String,
+    on_conflict: Option<ConflictResolution>,
+) -> Result<SettingsImportSummary, String> {
+    let on_conflict = on_conflict.unwrap_or_default();
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let settings: WorkbenchSettings =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid settings file: {}", e))?;
+    if settings.format_version > SETTINGS_FORMAT_VERSION {
+        return Err(format!(
+            "Settings file format {} is newer than this version of the app supports ({})",
+            settings.format_version, SETTINGS_FORMAT_VERSION
+        ));
+    }
+
+    let mut sections: BTreeMap<String, SectionImport> = BTreeMap::new();
+    let mut warnings = Vec::new();
+    {
+        let conn = db.0.get().map_err(|e| e.to_string())?;
+        let result = sections.entry(SECTION_AGENTS.to_string()).or_default();
+        import_agents(&conn, settings.agents, on_conflict, result)?;
+        let result = sections.entry(SECTION_EXECUTION_PROFILES.to_string()).or_default();
+        import_profiles(&conn, settings.execution_profiles, on_conflict, result)?;
+    }
+
+    let result = sections.entry(SECTION_PROVIDERS.to_string()).or_default();
+    import_providers(settings.providers, on_conflict, result, &mut warnings)?;
+
+    let result = sections.entry(SECTION_SLASH_COMMANDS.to_string()).or_default();
+    import_slash_commands(settings.slash_commands, on_conflict, result, &mut warnings)?;
+
+    if let Some(hooks) = settings.hooks {
+        let path = settings_path()?;
+        let mut current = read_json(&path).unwrap_or_else(|| serde_json::json!({}));
+        let exists = current.get("hooks").map_or(false, |hooks| {
+            hooks.as_object().map_or(!hooks.is_null(), |map| !map.is_empty())
+        });
+        let result = sections.entry(SECTION_HOOKS.to_string()).or_default();
+        if should_write(exists, on_conflict, result) {
+            match current.as_object_mut() {
+                Some(map) => {
+                    map.insert("hooks".to_string(), hooks);
+                    write_json(&path, &current)?;
+                }
+                None => warnings.push(format!("{} is not a JSON object", path.display())),
+            }
+        }
+    }
+
+    if let Some(mut translation) = settings.translation {
+        let existing = read_json(&translation_path()?);
+        let result = sections.entry(SECTION_TRANSLATION.to_string()).or_default();
+        if should_write(existing.is_some(), on_conflict, result) {
+            if let Some(existing) = &existing {
+                restore_secrets(&mut translation, existing);
+            }
+            match serde_json::from_value::<TranslationConfig>(translation) {
+                Ok(config) => {
+                    if let Err(e) = super::translator::update_translation_config(config).await {
+                        warnings.push(format!("Translation config: {}", e));
+                    }
+                }
+                Err(e) => warnings.push(format!("Translation config: {}", e)),
+            }
+        }
+    }
+
+    if let Some(mut router) = settings.router {
+        let path = router_path()?;
+        let existing = read_json(&path);
+        let result = sections.entry(SECTION_ROUTER.to_string()).or_default();
+        if should_write(path.exists(), on_conflict, result) {
+            if let Some(existing) = &existing {
+                restore_secrets(&mut router, existing);
+            }
+            write_json(&path, &router)?;
+        }
+    }
+
+    if let Some(execution_config) = settings.execution_config {
+        let stored = settings_store::get::<Value>(&*db.0.get()?, settings_store::EXECUTION_CONFIG);
+        let exists = stored.ok().flatten().is_some();
+        let result = sections.entry(SECTION_EXECUTION_CONFIG.to_string()).or_default();
+        if should_write(exists, on_conflict, result) {
+            match serde_json::from_value::<ClaudeExecutionConfig>(execution_config) {
+                Ok(config) => {
+                    let updated = super::claude::update_claude_execution_config(app, config).await;
+                    if let Err(e) = updated {
+                        warnings.push(format!("Execution config: {}", e));
+                    }
+                }
+                Err(e) => warnings.push(format!("Execution config: {}", e)),
+            }
+        }
+    }
+
+    log::info!("Imported workbench settings from {} ({:?} on conflict)", path, on_conflict);
+    Ok(SettingsImportSummary {
+        app_version: settings.app_version,
+        exported_at: settings.exported_at,
+        sections,
+        warnings,
+    })
+}
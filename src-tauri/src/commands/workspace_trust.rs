@@ -0,0 +1,193 @@
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use super::agents::AgentDb;
+use super::claude::normalize_path_for_comparison;
+use super::permission_config::{build_permission_args, ClaudeExecutionConfig, ClaudePermissionConfig};
+
+/// Trust decision for a project path
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectTrust {
+    pub project_path: String,
+    pub trusted: bool,
+    /// When the project was first opened in the workbench
+    pub first_seen_at: String,
+    /// When the user last made an explicit trust decision (None if never asked)
+    pub decided_at: Option<String>,
+}
+
+/// Trust overview for the diagnostics panel
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkspaceTrustDiagnostics {
+    pub total: usize,
+    pub trusted: usize,
+    pub untrusted: usize,
+    pub pending_decision: usize,
+    pub projects: Vec<ProjectTrust>,
+}
+
+fn row_to_project_trust(row: &rusqlite::Row) -> SqliteResult<ProjectTrust> {
+    Ok(ProjectTrust {
+        project_path: row.get(0)?,
+        trusted: row.get(1)?,
+        first_seen_at: row.get(2)?,
+        decided_at: row.get(3)?,
+    })
+}
+
+/// Look up the trust decision for a path, recording it as untrusted the first time it's seen
+pub fn load_or_register_trust(conn: &Connection, project_path: &str) -> Result<ProjectTrust, String> {
+    let normalized = normalize_path_for_comparison(project_path);
+
+    conn.execute(
+        "INSERT OR IGNORE INTO project_trust (normalized_path, project_path, trusted) VALUES (?1, ?2, 0)",
+        params![normalized, project_path],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.query_row(
+        "SELECT project_path, trusted, first_seen_at, decided_at FROM project_trust WHERE normalized_path = ?1",
+        params![normalized],
+        row_to_project_trust,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Whether a project is trusted. Fails closed when the database is unavailable.
+pub fn is_project_trusted(app: &AppHandle, project_path: &str) -> bool {
+    let db = match app.try_state::<AgentDb>() {
+        Some(db) => db,
+        None => return false,
+    };
+    let conn = match db.0.get() {
+        Ok(conn) => conn,
+        Err(_) => return false,
+    };
+
+    match load_or_register_trust(&conn, project_path) {
+        Ok(trust) => trust.trusted,
+        Err(e) => {
+            log::warn!("Failed to load trust for {}: {}", project_path, e);
+            false
+        }
+    }
+}
+
+/// CLI arguments that turn off every hook configured in Claude settings files
+pub fn disable_hooks_args() -> Vec<String> {
+    vec![
+        "--settings".to_string(),
+        serde_json::json!({ "disableAllHooks": true }).to_string(),
+    ]
+}
+
+/// Restrict an execution config for an untrusted project.
+///
+/// Permissions are forced to the read-only preset and the returned arguments
+/// disable any hooks the project's own `.claude/settings.json` may define.
+/// Hooks are also disabled for trusted projects while the global hooks kill switch is on.
+pub fn enforce_workspace_trust(
+    app: &AppHandle,
+    project_path: &str,
+    config: &mut ClaudeExecutionConfig,
+) -> Vec<String> {
+    if is_project_trusted(app, project_path) {
+        return if super::enhanced_hooks::hooks_disabled() {
+            disable_hooks_args()
+        } else {
+            Vec::new()
+        };
+    }
+
+    log::warn!(
+        "Project {} is not trusted, restricting execution to read-only permissions without hooks",
+        project_path
+    );
+    config.permissions = ClaudePermissionConfig::safe_mode();
+
+    disable_hooks_args()
+}
+
+/// Permission arguments used for agent runs in untrusted projects
+pub fn untrusted_permission_args() -> Vec<String> {
+    build_permission_args(&ClaudePermissionConfig::safe_mode())
+}
+
+/// Get the trust state of a project path (registers it as untrusted on first open)
+#[tauri::command]
+pub async fn get_project_trust(
+    db: State<'_, AgentDb>,
+    path: String,
+) -> Result<ProjectTrust, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    load_or_register_trust(&conn, &path)
+}
+
+/// Explicitly trust or distrust a project path
+#[tauri::command]
+pub async fn set_project_trust(
+    db: State<'_, AgentDb>,
+    path: String,
+    trusted: bool,
+) -> Result<ProjectTrust, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let normalized = normalize_path_for_comparison(&path);
+
+    conn.execute(
+        "INSERT INTO project_trust (normalized_path, project_path, trusted, decided_at)
+         VALUES (?1, ?2, ?3, CURRENT_TIMESTAMP)
+         ON CONFLICT(normalized_path) DO UPDATE SET
+             project_path = excluded.project_path,
+             trusted = excluded.trusted,
+             decided_at = excluded.decided_at",
+        params![normalized, path, trusted],
+    )
+    .map_err(|e| e.to_string())?;
+
+    log::info!(
+        "Project {} marked as {}",
+        path,
+        if trusted { "trusted" } else { "untrusted" }
+    );
+
+    conn.query_row(
+        "SELECT project_path, trusted, first_seen_at, decided_at FROM project_trust WHERE normalized_path = ?1",
+        params![normalized],
+        row_to_project_trust,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Failed to persist trust decision for {}", path))
+}
+
+/// List all persisted trust decisions for diagnostics
+#[tauri::command]
+pub async fn get_workspace_trust_diagnostics(
+    db: State<'_, AgentDb>,
+) -> Result<WorkspaceTrustDiagnostics, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT project_path, trusted, first_seen_at, decided_at FROM project_trust ORDER BY first_seen_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let projects = stmt
+        .query_map([], row_to_project_trust)
+        .map_err(|e| e.to_string())?
+        .collect::<SqliteResult<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+
+    let trusted = projects.iter().filter(|p| p.trusted).count();
+    let pending_decision = projects.iter().filter(|p| p.decided_at.is_none()).count();
+
+    Ok(WorkspaceTrustDiagnostics {
+        total: projects.len(),
+        trusted,
+        untrusted: projects.len() - trusted,
+        pending_decision,
+        projects,
+    })
+}
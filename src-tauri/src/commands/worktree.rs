@@ -0,0 +1,438 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+
+use super::agents::AgentDb;
+use super::run_status::RunState;
+
+/// Prefix of the branches session worktrees are created on
+const BRANCH_PREFIX: &str = "claude/";
+
+/// A git worktree a session runs in, so parallel sessions don't touch each other's files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionWorktree {
+    pub id: i64,
+    /// Root of the repository the worktree belongs to
+    pub project_path: String,
+    pub worktree_path: String,
+    /// Where sessions run: the worktree's counterpart of the requested project directory
+    pub session_path: String,
+    pub branch: String,
+    /// Branch the worktree was created from and merges back into
+    pub base_branch: String,
+    pub base_commit: String,
+    pub run_id: Option<i64>,
+    pub session_id: Option<String>,
+    /// `active`, `merged` or `removed`
+    pub status: String,
+    pub created_at: String,
+    pub merged_at: Option<String>,
+    /// Uncommitted changes in the worktree; None once it is removed
+    pub changed_files: Option<usize>,
+    /// Commits on the worktree branch not yet in the base branch
+    pub commits_ahead: Option<usize>,
+}
+
+/// Returned by `merge_session_worktree`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeMergeResult {
+    pub worktree: SessionWorktree,
+    /// Uncommitted changes were committed on the worktree branch first
+    pub committed_changes: bool,
+    pub merged_commits: usize,
+    pub merge_commit: Option<String>,
+    pub removed: bool,
+}
+
+pub fn init_worktree_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_worktrees (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_path TEXT NOT NULL,
+            worktree_path TEXT NOT NULL UNIQUE,
+            session_path TEXT NOT NULL,
+            branch TEXT NOT NULL,
+            base_branch TEXT NOT NULL,
+            base_commit TEXT NOT NULL,
+            run_id INTEGER,
+            session_id TEXT,
+            status TEXT NOT NULL DEFAULT 'active',
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            merged_at TEXT
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_session_worktrees_project ON session_worktrees(project_path)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Run git in `dir`; the error carries git's stderr
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let mut command = std::process::Command::new("git");
+    command.args(args).current_dir(dir);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    let output = command.output().map_err(|e| format!("Failed to run git: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+fn slugify(name: &str) -> String {
+    let slug: String = name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    slug.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-")
+}
+
+const COLUMNS: &str = "id, project_path, worktree_path, session_path, branch, base_branch, base_commit,
+    run_id, session_id, status, created_at, merged_at";
+
+fn row_to_worktree(row: &rusqlite::Row) -> rusqlite::Result<SessionWorktree> {
+    Ok(SessionWorktree {
+        id: row.get(0)?,
+        project_path: row.get(1)?,
+        worktree_path: row.get(2)?,
+        session_path: row.get(3)?,
+        branch: row.get(4)?,
+        base_branch: row.get(5)?,
+        base_commit: row.get(6)?,
+        run_id: row.get(7)?,
+        session_id: row.get(8)?,
+        status: row.get(9)?,
+        created_at: row.get(10)?,
+        merged_at: row.get(11)?,
+        changed_files: None,
+        commits_ahead: None,
+    })
+}
+
+fn load_worktree(conn: &Connection, worktree_id: i64) -> Result<SessionWorktree, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM session_worktrees WHERE id = ?1", COLUMNS),
+        params![worktree_id],
+        row_to_worktree,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Worktree {} not found", worktree_id))
+}
+
+fn changed_files(worktree_path: &Path) -> Result<usize, String> {
+    Ok(git(worktree_path, &["status", "--porcelain"])?.lines().filter(|l| !l.is_empty()).count())
+}
+
+fn commits_ahead(repo: &Path, base: &str, branch: &str) -> Result<usize, String> {
+    git(repo, &["rev-list", "--count", &format!("{}..{}", base, branch)])?
+        .trim()
+        .parse()
+        .map_err(|e| format!("Unexpected git rev-list output: {}", e))
+}
+
+/// Fill in live git state, and the session id once the CLI has reported it
+fn refresh(conn: &Connection, mut worktree: SessionWorktree) -> SessionWorktree {
+    if worktree.session_id.is_none() {
+        let reported = worktree
+            .run_id
+            .and_then(super::run_status::get)
+            .and_then(|status| status.session_id);
+        if let Some(session_id) = reported {
+            let _ = conn.execute(
+                "UPDATE session_worktrees SET session_id = ?1 WHERE id = ?2",
+                params![session_id, worktree.id],
+            );
+            worktree.session_id = Some(session_id);
+        }
+    }
+    if worktree.status == "active" {
+        let path = PathBuf::from(&worktree.worktree_path);
+        worktree.changed_files = changed_files(&path).ok();
+        worktree.commits_ahead =
+            commits_ahead(Path::new(&worktree.project_path), &worktree.base_branch, &worktree.branch).ok();
+    }
+    worktree
+}
+
+/// Whether a run started in the worktree is still going
+fn has_active_run(worktree: &SessionWorktree) -> bool {
+    worktree
+        .run_id
+        .and_then(super::run_status::get)
+        .map_or(false, |status| matches!(status.state, RunState::Starting | RunState::Running))
+}
+
+/// Create a worktree on a new branch off `base_branch` (default: the checked-out
+/// branch), next to the repository in `<repo>-worktrees/`
+pub fn create_worktree(
+    conn: &Connection,
+    project_path: &str,
+    name: Option<&str>,
+    base_branch: Option<&str>,
+) -> Result<SessionWorktree, String> {
+    let project = Path::new(project_path);
+    let root = PathBuf::from(git(project, &["rev-parse", "--show-toplevel"]).map_err(|_| {
+        format!("{} is not inside a git repository", project_path)
+    })?);
+    let base_branch = match base_branch {
+        Some(branch) => branch.to_string(),
+        None => {
+            let current = git(&root, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+            if current == "HEAD" {
+                return Err("The repository is in detached HEAD state; pass a base branch".to_string());
+            }
+            current
+        }
+    };
+    let base_commit = git(&root, &["rev-parse", &base_branch])?;
+
+    let suffix = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+    let slug = match name.map(slugify).filter(|slug| !slug.is_empty()) {
+        Some(slug) => format!("{}-{}", slug, suffix),
+        None => format!("session-{}", suffix),
+    };
+    let branch = format!("{}{}", BRANCH_PREFIX, slug);
+    let repo_name = root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let worktree_path = root
+        .parent()
+        .ok_or("The repository has no parent directory to put worktrees in")?
+        .join(format!("{}-worktrees", repo_name))
+        .join(&slug);
+    let worktree_str = worktree_path.to_string_lossy().to_string();
+
+    git(&root, &["worktree", "add", "-b", &branch, &worktree_str, &base_commit])?;
+
+    // Run sessions in the same subdirectory the project path points at
+    let session_path = std::fs::canonicalize(project)
+        .ok()
+        .zip(std::fs::canonicalize(&root).ok())
+        .and_then(|(project, root)| project.strip_prefix(&root).map(|rel| worktree_path.join(rel)).ok())
+        .unwrap_or_else(|| worktree_path.clone());
+
+    let root_str = root.to_string_lossy().to_string();
+    conn.execute(
+        "INSERT INTO session_worktrees (project_path, worktree_path, session_path, branch, base_branch, base_commit)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![root_str, worktree_str, session_path.to_string_lossy(), branch, base_branch, base_commit],
+    )
+    .map_err(|e| e.to_string())?;
+    log::info!("Created worktree {} on {} from {}", worktree_str, branch, base_branch);
+    load_worktree(conn, conn.last_insert_rowid())
+}
+
+/// Record the run started in a worktree
+pub fn attach_run(conn: &Connection, worktree_id: i64, run_id: i64) -> Result<(), String> {
+    conn.execute(
+        "UPDATE session_worktrees SET run_id = ?1, session_id = NULL WHERE id = ?2",
+        params![run_id, worktree_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn remove_worktree(conn: &Connection, worktree: &SessionWorktree, force: bool) -> Result<(), String> {
+    let root = Path::new(&worktree.project_path);
+    let mut args = vec!["worktree", "remove"];
+    if force {
+        args.push("--force");
+    }
+    args.push(&worktree.worktree_path);
+    if Path::new(&worktree.worktree_path).exists() {
+        git(root, &args)?;
+    } else {
+        git(root, &["worktree", "prune"])?;
+    }
+    // -d keeps branches with unmerged commits unless forced
+    if let Err(e) = git(root, &["branch", if force { "-D" } else { "-d" }, &worktree.branch]) {
+        log::warn!("Kept branch {}: {}", worktree.branch, e);
+    }
+    conn.execute(
+        "UPDATE session_worktrees SET status = 'removed' WHERE id = ?1",
+        params![worktree.id],
+    )
+    .map_err(|e| e.to_string())?;
+    log::info!("Removed worktree {}", worktree.worktree_path);
+    Ok(())
+}
+
+/// Create a git worktree for a session to run in
+#[tauri::command]
+pub async fn create_session_worktree(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    name: Option<String>,
+    base_branch: Option<String>,
+) -> Result<SessionWorktree, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let worktree = create_worktree(&conn, &project_path, name.as_deref(), base_branch.as_deref())?;
+    Ok(refresh(&conn, worktree))
+}
+
+/// Session worktrees that were not removed, newest first, optionally of one repository
+#[tauri::command]
+pub async fn list_session_worktrees(
+    db: State<'_, AgentDb>,
+    project_path: Option<String>,
+) -> Result<Vec<SessionWorktree>, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let root = project_path
+        .as_deref()
+        .and_then(|path| git(Path::new(path), &["rev-parse", "--show-toplevel"]).ok());
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM session_worktrees
+             WHERE status != 'removed' AND (?1 IS NULL OR project_path = ?1)
+             ORDER BY id DESC",
+            COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let worktrees = stmt
+        .query_map(params![root.or(project_path)], row_to_worktree)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(worktrees.into_iter().map(|worktree| refresh(&conn, worktree)).collect())
+}
+
+/// Commit what the session left uncommitted and merge its branch into the base branch,
+/// which must be checked out in the repository without uncommitted changes. A
+/// conflicting merge is aborted and nothing changes.
+#[tauri::command]
+pub async fn merge_session_worktree(
+    db: State<'_, AgentDb>,
+    worktree_id: i64,
+    commit_message: Option<String>,
+    remove_after: Option<bool>,
+) -> Result<WorktreeMergeResult, String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let worktree = load_worktree(&conn, worktree_id)?;
+    if worktree.status != "active" {
+        return Err(format!("Worktree {} is already {}", worktree_id, worktree.status));
+    }
+    if has_active_run(&worktree) {
+        return Err("A session is still running in this worktree".to_string());
+    }
+    let root = Path::new(&worktree.project_path);
+    let worktree_path = Path::new(&worktree.worktree_path);
+
+    // Refused merges must not leave a commit on the session branch
+    let current = git(root, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+    if current != worktree.base_branch {
+        return Err(format!(
+            "Check out {} in {} to merge (currently on {})",
+            worktree.base_branch, worktree.project_path, current
+        ));
+    }
+    if !git(root, &["status", "--porcelain", "--untracked-files=no"])?.is_empty() {
+        return Err(format!(
+            "{} has uncommitted changes; commit or stash them before merging",
+            worktree.project_path
+        ));
+    }
+
+    let committed_changes = changed_files(worktree_path)? > 0;
+    if committed_changes {
+        let message = commit_message
+            .filter(|message| !message.trim().is_empty())
+            .unwrap_or_else(|| format!("Session changes from {}", worktree.branch));
+        git(worktree_path, &["add", "-A"])?;
+        git(worktree_path, &["commit", "-m", &message])?;
+    }
+
+    let merged_commits = commits_ahead(root, &worktree.base_branch, &worktree.branch)?;
+    let merge_commit = if merged_commits > 0 {
+        let message = format!("Merge {}", worktree.branch);
+        if let Err(e) = git(root, &["merge", "--no-ff", "-m", &message, &worktree.branch]) {
+            let conflicts = git(root, &["diff", "--name-only", "--diff-filter=U"]).unwrap_or_default();
+            let _ = git(root, &["merge", "--abort"]);
+            return Err(if conflicts.is_empty() {
+                e
+            } else {
+                format!("Merge aborted, conflicts in: {}", conflicts.lines().collect::<Vec<_>>().join(", "))
+            });
+        }
+        Some(git(root, &["rev-parse", "HEAD"])?)
+    } else {
+        None
+    };
+
+    conn.execute(
+        "UPDATE session_worktrees SET status = 'merged', merged_at = CURRENT_TIMESTAMP WHERE id = ?1",
+        params![worktree_id],
+    )
+    .map_err(|e| e.to_string())?;
+    log::info!("Merged {} ({} commits) into {}", worktree.branch, merged_commits, worktree.base_branch);
+
+    let removed = remove_after.unwrap_or(false);
+    if removed {
+        let merged = load_worktree(&conn, worktree_id)?;
+        remove_worktree(&conn, &merged, false)?;
+    }
+    Ok(WorktreeMergeResult {
+        worktree: refresh(&conn, load_worktree(&conn, worktree_id)?),
+        committed_changes,
+        merged_commits,
+        merge_commit,
+        removed,
+    })
+}
+
+/// Remove a session worktree and its branch. Unmerged work is only discarded with `force`.
+#[tauri::command]
+pub async fn remove_session_worktree(
+    db: State<'_, AgentDb>,
+    worktree_id: i64,
+    force: Option<bool>,
+) -> Result<(), String> {
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    let worktree = load_worktree(&conn, worktree_id)?;
+    if worktree.status == "removed" {
+        return Ok(());
+    }
+    if has_active_run(&worktree) {
+        return Err("A session is still running in this worktree".to_string());
+    }
+    let force = force.unwrap_or(false);
+    if worktree.status == "active" && !force {
+        let worktree = refresh(&conn, worktree.clone());
+        if worktree.changed_files.unwrap_or(0) > 0 || worktree.commits_ahead.unwrap_or(0) > 0 {
+            return Err("The worktree has unmerged changes; merge it or remove it with force".to_string());
+        }
+    }
+    remove_worktree(&conn, &worktree, force)
+}
+
+/// Create a worktree for a new session and return where to run it
+pub fn prepare_session_worktree(app: &AppHandle, project_path: &str) -> Result<SessionWorktree, String> {
+    let db = app.state::<AgentDb>();
+    let conn = db.0.get().map_err(|e| e.to_string())?;
+    create_worktree(&conn, project_path, None, None)
+}
+
+/// Link a run to the worktree it was started in; a worktree whose run failed to
+/// start is removed again
+pub fn record_worktree_run(app: &AppHandle, worktree: &SessionWorktree, run_id: Option<i64>) {
+    let db = app.state::<AgentDb>();
+    let result = db.0.get().and_then(|conn| match run_id {
+        Some(run_id) => attach_run(&conn, worktree.id, run_id),
+        None => remove_worktree(&conn, worktree, true),
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to update worktree {}: {}", worktree.worktree_path, e);
+    }
+}
@@ -0,0 +1,259 @@
+//! Crash-safe reads and writes of the JSON config files the app shares with the
+//! Claude CLI and with other instances of itself (settings.json, .mcp.json, ...).
+//!
+//! A write takes an advisory lock on the file, writes a temp file next to it, fsyncs
+//! it and renames it over the original, so a crash leaves either the old or the new
+//! content, never a mix. Before the rename the current content is copied to an
+//! automatic backup if it is intact. A reader that finds a JSON file that no longer
+//! parses restores it from that backup.
+//!
+//! Lock files and backups live in the app data dir, keyed by a hash of the path, so
+//! project `.claude` directories are not cluttered with them.
+
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+static STATE_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+/// Keep lock files and backups under `dir` (the app data dir) from now on
+pub fn set_state_dir(dir: PathBuf) -> Result<(), String> {
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    STATE_DIR
+        .set(dir)
+        .map_err(|_| "Config file state dir is already set".to_string())
+}
+
+/// Where the lock, backup or corrupted copy (`kind`) of `path` is kept
+fn state_file(path: &Path, kind: &str) -> PathBuf {
+    let dir = STATE_DIR
+        .get()
+        .cloned()
+        .unwrap_or_else(|| std::env::temp_dir().join("claude-workbench-config"));
+    let key = crate::paths::comparison_key(&path.to_string_lossy());
+    let hash = format!("{:x}", Sha256::digest(key.as_bytes()));
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    dir.join(format!("{}-{}.{}", &hash[..16], name, kind))
+}
+
+/// Held while a file is read for an update or replaced; released on drop
+struct FileLock {
+    _file: File,
+}
+
+fn lock(path: &Path) -> io::Result<FileLock> {
+    let lock_path = state_file(path, "lock");
+    if let Some(dir) = lock_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = OpenOptions::new().create(true).truncate(false).write(true).open(&lock_path)?;
+    file.lock()?;
+    Ok(FileLock { _file: file })
+}
+
+/// Whether `content` is usable: JSON files must parse, other files always are
+fn is_intact(path: &Path, content: &[u8]) -> bool {
+    let is_json = path.extension().map_or(false, |ext| ext.eq_ignore_ascii_case("json"));
+    !is_json || serde_json::from_slice::<Value>(content).is_ok()
+}
+
+/// Write `contents` to a temp file in the target's directory, fsync it and rename it
+/// over `path`. The temp file gets `permissions`, e.g. those of the file replaced.
+fn replace(path: &Path, contents: &[u8], permissions: Option<fs::Permissions>) -> io::Result<()> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let tmp = dir.join(format!(".{}.{}.tmp", name, uuid::Uuid::new_v4()));
+
+    let written = (|| {
+        let mut file = File::create(&tmp)?;
+        if let Some(permissions) = permissions {
+            file.set_permissions(permissions)?;
+        }
+        file.write_all(contents)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&tmp, path)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+        return written;
+    }
+
+    // Persist the rename itself; Windows has no directory handles to sync
+    #[cfg(unix)]
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn private_permissions() -> Option<fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(fs::Permissions::from_mode(0o600))
+}
+
+#[cfg(not(unix))]
+fn private_permissions() -> Option<fs::Permissions> {
+    None
+}
+
+/// Replace `path`, backing up its current content first if intact. Needs the lock.
+fn replace_locked(path: &Path, contents: &[u8], private: bool) -> io::Result<()> {
+    let permissions = private
+        .then(private_permissions)
+        .flatten()
+        .or_else(|| fs::metadata(path).ok().map(|meta| meta.permissions()));
+
+    if let Ok(current) = fs::read(path) {
+        if is_intact(path, &current) {
+            if let Err(e) = replace(&state_file(path, "bak"), &current, permissions.clone()) {
+                log::warn!("Failed to back up {}: {}", path.display(), e);
+            }
+        }
+    }
+    replace(path, contents, permissions)
+}
+
+/// Read `path`, restoring it from its backup if it is corrupted. Needs the lock.
+/// Without a usable backup the corrupted content is returned as is.
+fn read_locked(path: &Path) -> io::Result<String> {
+    let content = fs::read_to_string(path)?;
+    if is_intact(path, content.as_bytes()) {
+        return Ok(content);
+    }
+    let Some(backup) = fs::read_to_string(state_file(path, "bak"))
+        .ok()
+        .filter(|backup| is_intact(path, backup.as_bytes()))
+    else {
+        log::error!("{} is corrupted and has no usable backup", path.display());
+        return Ok(content);
+    };
+
+    // Keep the corrupted file for inspection
+    let _ = fs::write(state_file(path, "corrupt"), &content);
+    let permissions = fs::metadata(path).ok().map(|meta| meta.permissions());
+    replace(path, backup.as_bytes(), permissions)?;
+    log::warn!("{} was corrupted and has been restored from its last backup", path.display());
+    Ok(backup)
+}
+
+/// Read a config file like `fs::read_to_string`, restoring a corrupted JSON file from
+/// its automatic backup
+pub fn read_to_string(path: &Path) -> io::Result<String> {
+    let content = fs::read_to_string(path)?;
+    if is_intact(path, content.as_bytes()) {
+        return Ok(content);
+    }
+    let _lock = lock(path)?;
+    read_locked(path)
+}
+
+/// Atomically replace a config file, like a crash-safe `fs::write`
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let _lock = lock(path)?;
+    replace_locked(path, contents.as_ref(), false)
+}
+
+/// Like `write`, but only the current user may read the file (on Unix)
+pub fn write_private(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let _lock = lock(path)?;
+    replace_locked(path, contents.as_ref(), true)
+}
+
+/// Atomically write `value` as pretty-printed JSON
+pub fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Read, modify and write back a JSON file under its lock, so concurrent updates of
+/// different keys do not lose each other's changes. A missing file starts as `{}`.
+/// Returns the written value.
+pub fn update_json<F>(path: &Path, modify: F) -> Result<Value, String>
+where
+    F: FnOnce(&mut Value) -> Result<(), String>,
+{
+    let _lock = lock(path).map_err(|e| format!("Failed to lock {}: {}", path.display(), e))?;
+    let mut value = match read_locked(path) {
+        Ok(content) if content.trim().is_empty() => serde_json::json!({}),
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => serde_json::json!({}),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    modify(&mut value)?;
+    let content = serde_json::to_string_pretty(&value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    replace_locked(path, content.as_bytes(), false)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_replaces_content_and_leaves_no_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        write(&path, r#"{"a":1}"#).unwrap();
+        write(&path, r#"{"a":2}"#).unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), r#"{"a":2}"#);
+        let names: Vec<_> =
+            fs::read_dir(dir.path()).unwrap().flatten().map(|e| e.file_name()).collect();
+        assert_eq!(names, vec![std::ffi::OsString::from("settings.json")]);
+    }
+
+    #[test]
+    fn test_corrupted_json_is_restored_from_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        write(&path, r#"{"a":1}"#).unwrap();
+        write(&path, r#"{"a":2}"#).unwrap();
+        // A writer that bypassed the lock crashed halfway
+        fs::write(&path, r#"{"a":"#).unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), r#"{"a":1}"#);
+        assert_eq!(fs::read_to_string(&path).unwrap(), r#"{"a":1}"#);
+    }
+
+    #[test]
+    fn test_corruption_without_backup_is_reported_as_is() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fresh.json");
+        fs::write(&path, "not json").unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), "not json");
+        assert!(update_json(&path, |_| Ok(())).is_err());
+    }
+
+    #[test]
+    fn test_update_json_keeps_other_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        update_json(&path, |value| {
+            value["hooks"] = serde_json::json!({});
+            Ok(())
+        })
+        .unwrap();
+        let value = update_json(&path, |value| {
+            value["env"] = serde_json::json!({ "A": "1" });
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(value, serde_json::json!({ "hooks": {}, "env": { "A": "1" } }));
+    }
+
+    #[test]
+    fn test_non_json_files_are_never_treated_as_corrupted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".gitignore");
+        write(&path, "target/\n").unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), "target/\n");
+    }
+}
@@ -4,6 +4,8 @@
 mod checkpoint;
 mod claude_binary;
 mod commands;
+mod config_file;
+mod paths;
 mod process;
 
 use std::sync::Arc;
@@ -12,21 +14,25 @@ use commands::agents::{
     cleanup_finished_processes, create_agent, delete_agent, execute_agent, export_agent,
     export_agent_to_file, fetch_github_agent_content, fetch_github_agents, get_agent,
     get_agent_run, get_agent_run_with_real_time_metrics, get_claude_binary_path,
-    get_live_session_output, get_session_output, get_session_status, import_agent,
+    get_live_output_limit, get_live_output_range, get_live_session_output, get_session_output,
+    get_session_status, import_agent, set_live_output_limit,
     import_agent_from_file, import_agent_from_github, init_database, kill_agent_session,
     list_agent_runs, list_agent_runs_with_metrics, list_agents, list_claude_installations,
     list_running_sessions, load_agent_session_history, set_claude_binary_path, stream_session_output, update_agent, AgentDb,
 };
 use commands::claude::{
-    cancel_claude_execution, check_auto_checkpoint, check_claude_version, cleanup_old_checkpoints,
-    cleanup_old_checkpoints_by_age, clear_checkpoint_manager, continue_claude_code, create_checkpoint, delete_project, execute_claude_code,
-    find_claude_md_files, fork_from_checkpoint, get_checkpoint_diff, get_checkpoint_settings,
+    cancel_checkpoint_job, cancel_claude_execution, check_auto_checkpoint, check_claude_version, cleanup_old_checkpoints,
+    cleanup_old_checkpoints_by_age, clear_checkpoint_manager, continue_claude_code, create_checkpoint, create_checkpoint_async, delete_project, diff_checkpoint_against_workdir, diff_checkpoint_to_worktree, execute_claude_code,
+    find_claude_md_files, fork_from_checkpoint, get_checkpoint_diff, get_checkpoint_scope, get_checkpoint_settings,
     get_checkpoint_state_stats, get_claude_session_output, get_claude_settings, get_project_sessions,
     get_recently_modified_files, get_session_timeline, get_system_prompt, list_checkpoints,
     list_directory_contents, list_projects, list_running_claude_sessions, load_session_history,
     open_new_session, read_claude_md_file, restore_checkpoint, resume_claude_code,
+    list_restore_conflicts, resolve_restore_conflict, get_checkpoint_storage_stats,
+    compact_checkpoint_storage, pause_execution, resume_execution,
     save_claude_md_file, save_claude_settings, save_system_prompt, search_files,
-    track_checkpoint_message, track_session_messages, update_checkpoint_settings,
+    track_checkpoint_message, track_session_messages, update_checkpoint_scope, update_checkpoint_settings,
+    list_safety_checkpoints, restore_safety_checkpoint, undo_last_destructive_action,
     get_hooks_config, update_hooks_config, validate_hook_command,
     get_claude_execution_config, update_claude_execution_config, reset_claude_execution_config,
     get_claude_permission_config, update_claude_permission_config, get_permission_presets,
@@ -38,13 +44,14 @@ use commands::claude::{
 use commands::mcp::{
     mcp_add, mcp_add_from_claude_desktop, mcp_add_json, mcp_export_config, mcp_get, mcp_get_server_status, mcp_list,
     mcp_read_project_config, mcp_remove, mcp_reset_project_choices, mcp_save_project_config,
-    mcp_serve, mcp_test_connection,
+    mcp_register_memory_server, mcp_serve, mcp_test_connection,
 };
 
 use commands::usage::{
     get_session_stats, get_usage_by_date_range, get_usage_details, get_usage_stats,
     get_today_usage_stats, get_usage_by_api_base_url, get_active_sessions, get_burn_rate_analysis,
     get_usage_overview, get_session_cache_tokens, get_realtime_usage_stats,
+    get_model_comparison_report,
 };
 use commands::storage::{
     storage_list_tables, storage_read_table, storage_update_row, storage_delete_row,
@@ -70,19 +77,155 @@ use commands::subagents::{
 };
 use commands::enhanced_hooks::{
     trigger_hook_event, test_hook_condition, execute_pre_commit_review,
+    get_hook_execution_log, clear_hook_execution_log, set_hooks_disabled, get_hooks_kill_switch,
 };
 use commands::message_operations::{
     message_undo, message_truncate_to_index, message_edit, message_delete,
     message_get_count, message_get_by_index, message_get_all, CheckpointManagerRegistry,
 };
+use commands::recent_sessions::{
+    record_recent_session, get_recent_sessions, reopen_last_session,
+};
+use commands::session_health::{check_session_resumability, repair_session_file};
+use commands::workspace_trust::{
+    get_project_trust, set_project_trust, get_workspace_trust_diagnostics,
+};
+use commands::weekly_digest::{
+    generate_weekly_digest, get_weekly_digest_config, set_weekly_digest_config,
+};
+use commands::output_batching::{get_output_batching, set_output_batching};
+use commands::events::get_event_catalog;
+use commands::project_scan::{get_project_scan, start_project_scan};
+use commands::model_aliases::{delete_model_alias, list_model_aliases, upsert_model_alias};
+use commands::provider_env::{
+    bind_session_provider, get_session_env, get_session_provider, set_session_env,
+};
+use commands::github_client::{get_github_api_status, set_github_token};
+use commands::currency::{get_currency_config, update_currency_config};
+use commands::team_usage::{export_team_usage, get_operator_id, set_operator_id};
+use commands::session_lock::{force_unlock_session, get_session_lock};
+use commands::api_manifest::{get_api_version, get_command_manifest};
+use commands::safety::{disable_dangerous_skip, get_safety_status};
+use commands::prompt_queue::{
+    clear_prompt_queue, list_queued_prompts, queue_followup_prompt, remove_queued_prompt,
+    reorder_queued_prompts,
+};
+use commands::prompt_suggestions::suggest_prompts;
+use commands::session_templates::{
+    create_session_template, delete_session_template, list_session_templates,
+    start_session_from_template,
+};
+use commands::event_subscriptions::{subscribe_events, unsubscribe_events};
+use commands::session_export::{export_session, get_export_preview};
+use commands::claude_md_lint::{analyze_claude_md, apply_claude_md_fixes};
+use commands::hook_packs::{install_hook_pack, list_hook_packs, preview_hook_pack};
+use commands::memory::{
+    add_memory_note, delete_memory_entry, list_memory_entries, set_memory_value, update_memory_entry,
+};
+use commands::knowledge_base::{ingest_project_knowledge, query_project_knowledge};
+use commands::execution_profiles::{
+    apply_execution_profile, delete_execution_profile, get_session_profile, list_execution_profiles,
+    save_execution_profile,
+};
+use commands::spawn_diagnostics::diagnose_spawn_failure;
+use commands::project_matching::{
+    exclude_from_project_merge, get_path_case_sensitivity, list_project_merge_exclusions,
+    remove_project_merge_exclusion, repair_merged_projects, set_path_case_sensitivity,
+    split_merged_project,
+};
+use commands::session_import::import_session_file;
+use commands::backup::{create_full_backup, restore_full_backup};
+use commands::capability_tokens::{
+    create_capability_token, list_capability_tokens, list_remote_invocation_audit, revoke_capability_token,
+};
+use commands::burn_rate::get_active_burn_rates;
+use commands::run_dedupe::{get_run_dedupe_window, set_run_dedupe_window};
+use commands::run_status::get_run_status;
+use commands::crash_reports::{export_crash_report, list_crash_reports};
+use commands::startup::{get_startup_config, get_startup_timings, set_startup_config};
+use commands::worktree::{
+    create_session_worktree, list_session_worktrees, merge_session_worktree, remove_session_worktree,
+};
+use commands::stale_state::clean_stale_claude_state;
+use commands::context_exclusion::{list_context_exclusions, set_message_context_inclusion};
+use commands::response_language::{
+    get_response_language, set_project_response_language, set_session_response_language,
+    suggest_response_language,
+};
+use commands::agent_schedules::{
+    create_agent_schedule, delete_agent_schedule, list_agent_schedule_runs, list_agent_schedules,
+    update_agent_schedule,
+};
+use commands::accessibility::{get_accessibility_verbosity, set_accessibility_verbosity};
+use commands::power::{get_power_policy, update_power_policy};
+use commands::project_execution_config::{
+    get_project_execution_config, update_project_execution_config,
+};
+use commands::session_history::{
+    get_session_history_delta, get_session_message_count, load_session_history_page,
+};
+use commands::fault_injection::{get_fault_injection, set_fault_injection};
+use commands::file_edits::list_session_file_edits;
+use commands::project_gitignore::{
+    audit_project_gitignore, ignore_local_files, set_auto_gitignore,
+};
+use commands::provider_keys::{
+    add_provider_key, get_provider_keys, remove_provider_key, set_rotation_strategy,
+};
+use commands::workbench_settings::{export_workbench_settings, import_workbench_settings};
+use commands::prompt_cost::estimate_prompt_cost;
+use commands::scheduled_prompts::{
+    cancel_scheduled_prompt, list_scheduled_prompts, schedule_prompt, update_scheduled_prompt,
+};
+use commands::unseen_completions::{list_unseen_completions, mark_session_seen, set_active_project};
+use commands::hook_templates::{apply_hook_template, list_hook_templates};
+use commands::env_profiles::{delete_env_profile, list_env_profiles, save_env_profile};
+use commands::agent_results::{
+    get_agent_output_schema, get_agent_run_result, set_agent_output_schema,
+};
+use commands::project_bootstrap::{analyze_project_stack, apply_project_bootstrap};
+use commands::db_migrations::{get_db_schema_info, rollback_db_schema};
+use commands::prompt_templates::{
+    create_prompt_template, delete_prompt_template, expand_prompt_template, list_prompt_templates,
+    update_prompt_template,
+};
+use commands::service_ports::get_service_ports;
+use commands::router_validation::router_validate_providers;
+use commands::plugins::{
+    get_plugins_dir, invoke_plugin_command, list_plugins, run_plugin_analyzer, set_plugin_enabled,
+};
+use commands::tasks::{assign_to_task, create_task, delete_task, get_task_overview, list_tasks, remove_from_task};
+use commands::local_api::{get_local_api_status, set_local_api_enabled, set_metrics_exporter_enabled};
+use commands::time_tracking::{get_time_report, export_time_report_csv};
+use commands::global_search::{global_search, rebuild_search_index};
+use commands::agent_delegation::{get_delegation_tree, list_run_delegations};
+use commands::session_handoff::{get_session_end_summary, write_session_handoff};
+use commands::file_watcher::{
+    start_file_change_hooks, stop_file_change_hooks, list_file_change_watchers, unwatch_project_path,
+    watch_project_path, FileWatcherState,
+};
 use process::ProcessRegistryState;
-use std::sync::Mutex;
 use tauri::Manager;
 use tauri_plugin_window_state::Builder as WindowStatePlugin;
 
+/// `tauri::generate_handler!` that also records the command names for the API manifest
+macro_rules! command_handler {
+    ($($commands:tt)*) => {{
+        commands::api_manifest::register_commands(stringify!($($commands)*));
+        tauri::generate_handler![$($commands)*]
+    }};
+}
+
 fn main() {
-    // Initialize logger
-    env_logger::init();
+    commands::startup::mark_process_start();
+
+    // Initialize logger and write a crash report on panic
+    commands::crash_reports::install();
+
+    // Claude Code launches this binary as the memory MCP server
+    if let Some(code) = commands::memory_mcp::run_from_args() {
+        std::process::exit(code);
+    }
 
 
     tauri::Builder::default()
@@ -95,33 +238,59 @@ fn main() {
                 .build()
         )
         .setup(|app| {
+            // Keep config file locks and backups in the app data dir
+            match app.path().app_data_dir() {
+                Ok(dir) => {
+                    if let Err(e) = config_file::set_state_dir(dir.join("config_files")) {
+                        log::warn!("Config file backups will be kept in the temp dir: {}", e);
+                    }
+                }
+                Err(e) => log::warn!("Config file backups will be kept in the temp dir: {}", e),
+            }
+
             // Initialize agents database
-            let conn = init_database(&app.handle()).expect("Failed to initialize agents database");
-            app.manage(AgentDb(Mutex::new(conn)));
+            let pool = commands::startup::blocking("database", || {
+                let conn = init_database(&app.handle()).expect("Failed to initialize agents database");
+                commands::enhanced_hooks::load_hooks_kill_switch(&conn);
+                commands::db_pool::DbPool::new(conn, commands::db_pool::DB_POOL_SIZE)
+                    .expect("Failed to create agents database pool")
+            });
+            app.manage(AgentDb(pool));
 
-            // Initialize checkpoint state
-            let checkpoint_state = CheckpointState::new();
+            // Load which optional subsystems the user turned off
+            commands::startup::init_startup_config(app.handle());
 
-            // Set the Claude directory path
-            if let Ok(claude_dir) = dirs::home_dir()
-                .ok_or_else(|| "Could not find home directory")
-                .and_then(|home| {
-                    let claude_path = home.join(".claude");
-                    claude_path
-                        .canonicalize()
-                        .map_err(|_| "Could not find ~/.claude directory")
-                })
-            {
-                let state_clone = checkpoint_state.clone();
-                tauri::async_runtime::spawn(async move {
+            // Initialize checkpoint state; the Claude directory is resolved in the background
+            let checkpoint_state = CheckpointState::new();
+            let state_clone = checkpoint_state.clone();
+            commands::startup::background("checkpoint_state", async move {
+                // Set the Claude directory path
+                if let Ok(claude_dir) = dirs::home_dir()
+                    .ok_or_else(|| "Could not find home directory")
+                    .and_then(|home| {
+                        let claude_path = home.join(".claude");
+                        claude_path
+                            .canonicalize()
+                            .map_err(|_| "Could not find ~/.claude directory")
+                    })
+                {
                     state_clone.set_claude_dir(claude_dir).await;
-                });
-            }
+                }
+            });
 
             app.manage(checkpoint_state);
 
-            // Initialize process registry
-            app.manage(ProcessRegistryState::default());
+            // Initialize process registry, journaling live output so it survives a restart
+            let registry = ProcessRegistryState::default();
+            match app.path().app_data_dir() {
+                Ok(dir) => {
+                    if let Err(e) = registry.0.set_live_output_dir(dir.join("live_output")) {
+                        log::warn!("Live output won't be persisted: {}", e);
+                    }
+                }
+                Err(e) => log::warn!("Live output won't be persisted: {}", e),
+            }
+            app.manage(registry);
 
             // Initialize Claude process state
             app.manage(ClaudeProcessState::default());
@@ -132,7 +301,7 @@ fn main() {
             let manager_for_monitor = auto_compact_manager.clone();
 
             // Start monitoring in background
-            tauri::async_runtime::spawn(async move {
+            commands::startup::background("auto_compact_monitor", async move {
                 if let Err(e) = manager_for_monitor.start_monitoring(app_handle_for_monitor).await {
                     log::error!("Failed to start auto-compact monitoring: {}", e);
                 }
@@ -140,17 +309,75 @@ fn main() {
 
             app.manage(commands::context_manager::AutoCompactState(auto_compact_manager));
 
-            // Initialize translation service with saved configuration
-            tauri::async_runtime::spawn(async move {
-                commands::translator::init_translation_service_with_saved_config().await;
-            });
+            // The translation service loads its saved configuration on first use
 
             // Initialize checkpoint manager registry for message operations
             app.manage(CheckpointManagerRegistry::default());
 
+            // Initialize file watcher registry for OnFileChange hooks
+            app.manage(FileWatcherState::default());
+
+            commands::startup::blocking("settings", || {
+                // Load the GitHub token used by agent imports
+                commands::github_client::init_github_client(app.handle());
+
+                // Load the operator id stamped onto usage rows
+                commands::team_usage::init_operator_id(app.handle());
+
+                // Load the path case sensitivity override used to match projects
+                commands::project_matching::init_path_case_sensitivity(app.handle());
+
+                // Load how long execution idempotency keys are remembered
+                commands::run_dedupe::init_run_dedupe(app.handle());
+
+                // Load how much the accessibility channel announces
+                commands::accessibility::init_accessibility(app.handle());
+
+                // Load when system sleep is blocked during runs
+                commands::power::init_power(app.handle());
+
+                // Load whether local-only files may be added to project .gitignore files
+                commands::project_gitignore::init_project_gitignore(app.handle());
+            });
+
+            // Keep crash reports in the app data dir and offer recovery after a crash
+            commands::startup::blocking("crash_reports", || {
+                commands::crash_reports::init_crash_reports(app.handle())
+            });
+
+            // Start the weekly digest scheduler (no-op unless enabled in settings)
+            commands::weekly_digest::start_digest_scheduler(app.handle().clone());
+
+            // Launch scheduled agent runs and one-off prompts as they come due
+            commands::agent_schedules::start_agent_scheduler(app.handle().clone());
+
+            // Show runs that finished unnoticed before the last exit on the badge
+            commands::unseen_completions::init_unseen_completions(app.handle());
+
+            // Follow the power source while runs keep the machine awake
+            commands::power::start_power_monitor(app.handle().clone());
+
+            // Replay the usage journal and start the batched usage writer
+            commands::startup::blocking("usage_writer", || {
+                commands::usage_writer::start_usage_writer(app.handle().clone())
+            });
+
+            // Start the local API if it was enabled (needs a capability token per request)
+            commands::startup::blocking("local_api", || commands::local_api::init_local_api(app.handle()));
+
+            // Move the router off its port if another app took it
+            commands::startup::background(
+                "service_ports",
+                commands::service_ports::init_service_ports(app.handle().clone()),
+            );
+
+            // Emit per-session token and cost burn rates while sessions run
+            commands::burn_rate::start_burn_rate_ticker(app.handle().clone());
+
+            commands::startup::mark_setup_finished();
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(command_handler![
             // Claude & Project Management
             list_projects,
             get_project_sessions,
@@ -168,10 +395,15 @@ fn main() {
             read_claude_md_file,
             save_claude_md_file,
             load_session_history,
+            load_session_history_page,
+            get_session_message_count,
+            get_session_history_delta,
             execute_claude_code,
             continue_claude_code,
             resume_claude_code,
             cancel_claude_execution,
+            pause_execution,
+            resume_execution,
             list_running_claude_sessions,
             get_claude_session_output,
             list_directory_contents,
@@ -197,17 +429,30 @@ fn main() {
             enhance_prompt_with_gemini,
             // Checkpoint Management
             create_checkpoint,
+            create_checkpoint_async,
+            cancel_checkpoint_job,
             restore_checkpoint,
+            list_restore_conflicts,
+            resolve_restore_conflict,
             list_checkpoints,
             fork_from_checkpoint,
             get_session_timeline,
             update_checkpoint_settings,
             get_checkpoint_diff,
+            diff_checkpoint_to_worktree,
+            diff_checkpoint_against_workdir,
+            get_checkpoint_scope,
+            update_checkpoint_scope,
+            list_safety_checkpoints,
+            restore_safety_checkpoint,
+            undo_last_destructive_action,
             track_checkpoint_message,
             track_session_messages,
             check_auto_checkpoint,
             cleanup_old_checkpoints,
             cleanup_old_checkpoints_by_age,
+            get_checkpoint_storage_stats,
+            compact_checkpoint_storage,
             get_checkpoint_settings,
             clear_checkpoint_manager,
             get_checkpoint_state_stats,
@@ -229,6 +474,9 @@ fn main() {
             cleanup_finished_processes,
             get_session_output,
             get_live_session_output,
+            get_live_output_range,
+            get_live_output_limit,
+            set_live_output_limit,
             stream_session_output,
             load_agent_session_history,
             get_claude_binary_path,
@@ -255,6 +503,10 @@ fn main() {
             trigger_hook_event,
             test_hook_condition,
             execute_pre_commit_review,
+            get_hook_execution_log,
+            clear_hook_execution_log,
+            set_hooks_disabled,
+            get_hooks_kill_switch,
 
             // Usage & Analytics
             get_usage_stats,
@@ -268,6 +520,7 @@ fn main() {
             get_burn_rate_analysis,
             get_session_cache_tokens,
             get_realtime_usage_stats,
+            get_model_comparison_report,
             
             // MCP (Model Context Protocol)
             mcp_add,
@@ -345,7 +598,287 @@ fn main() {
             message_get_count,
             message_get_by_index,
             message_get_all,
+
+            // Recently Closed Sessions
+            record_recent_session,
+            get_recent_sessions,
+            reopen_last_session,
+
+            // Session Health
+            check_session_resumability,
+            repair_session_file,
+
+            // Workspace Trust
+            get_project_trust,
+            set_project_trust,
+            get_workspace_trust_diagnostics,
+
+            // File Change Hooks
+            start_file_change_hooks,
+            stop_file_change_hooks,
+            list_file_change_watchers,
+            watch_project_path,
+            unwatch_project_path,
+
+            // Session Handoff
+            get_session_end_summary,
+            write_session_handoff,
+
+            // Agent Delegation
+            get_delegation_tree,
+            list_run_delegations,
+
+            // Global Search
+            global_search,
+            rebuild_search_index,
+
+            // Time Tracking
+            get_time_report,
+            export_time_report_csv,
+
+            // Weekly Digest
+            generate_weekly_digest,
+            get_weekly_digest_config,
+            set_weekly_digest_config,
+
+            // Output Batching
+            set_output_batching,
+            get_output_batching,
+
+            // Event Catalog
+            get_event_catalog,
+
+            // Project Scanning
+            start_project_scan,
+            get_project_scan,
+
+            // Model Aliases
+            list_model_aliases,
+            upsert_model_alias,
+            delete_model_alias,
+
+            // Provider Environment
+            bind_session_provider,
+            get_session_provider,
+            set_session_env,
+            get_session_env,
+
+            // GitHub API
+            get_github_api_status,
+            set_github_token,
+            // Currency
+            get_currency_config,
+            update_currency_config,
+            // Team Usage
+            get_operator_id,
+            set_operator_id,
+            export_team_usage,
+            // Session Locks
+            get_session_lock,
+            force_unlock_session,
+            // API Versioning
+            get_api_version,
+            get_command_manifest,
+            // Safety
+            get_safety_status,
+            disable_dangerous_skip,
+            // Prompt Queue
+            queue_followup_prompt,
+            list_queued_prompts,
+            remove_queued_prompt,
+            reorder_queued_prompts,
+            clear_prompt_queue,
+            // Prompt Suggestions
+            suggest_prompts,
+            // Session Templates
+            create_session_template,
+            list_session_templates,
+            delete_session_template,
+            start_session_from_template,
+            // Event Subscriptions
+            subscribe_events,
+            unsubscribe_events,
+            // Session Export
+            export_session,
+            get_export_preview,
+            // CLAUDE.md Lint
+            analyze_claude_md,
+            apply_claude_md_fixes,
+            // Hook Packs
+            list_hook_packs,
+            preview_hook_pack,
+            install_hook_pack,
+            // Memory
+            list_memory_entries,
+            set_memory_value,
+            add_memory_note,
+            update_memory_entry,
+            delete_memory_entry,
+            mcp_register_memory_server,
+            // Project Knowledge
+            ingest_project_knowledge,
+            query_project_knowledge,
+            // Execution Profiles
+            list_execution_profiles,
+            save_execution_profile,
+            delete_execution_profile,
+            apply_execution_profile,
+            get_session_profile,
+            // Spawn Diagnostics
+            diagnose_spawn_failure,
+            // Project Matching
+            get_path_case_sensitivity,
+            set_path_case_sensitivity,
+            repair_merged_projects,
+            list_project_merge_exclusions,
+            exclude_from_project_merge,
+            remove_project_merge_exclusion,
+            split_merged_project,
+            // Session Import
+            import_session_file,
+            // Backup
+            create_full_backup,
+            restore_full_backup,
+            // Local API
+            create_capability_token,
+            list_capability_tokens,
+            revoke_capability_token,
+            list_remote_invocation_audit,
+            get_local_api_status,
+            set_local_api_enabled,
+            set_metrics_exporter_enabled,
+            // Burn Rate
+            get_active_burn_rates,
+            // Run Deduplication
+            get_run_dedupe_window,
+            set_run_dedupe_window,
+            // Run Status
+            get_run_status,
+            // Tasks
+            create_task,
+            list_tasks,
+            delete_task,
+            assign_to_task,
+            remove_from_task,
+            get_task_overview,
+            // Crash Reports
+            list_crash_reports,
+            export_crash_report,
+            // Startup
+            get_startup_timings,
+            get_startup_config,
+            set_startup_config,
+            // Plugins
+            list_plugins,
+            get_plugins_dir,
+            set_plugin_enabled,
+            invoke_plugin_command,
+            run_plugin_analyzer,
+            // Worktrees
+            create_session_worktree,
+            list_session_worktrees,
+            merge_session_worktree,
+            remove_session_worktree,
+            // Maintenance
+            clean_stale_claude_state,
+            // Context Exclusion
+            set_message_context_inclusion,
+            list_context_exclusions,
+            // Response Language
+            get_response_language,
+            set_project_response_language,
+            set_session_response_language,
+            suggest_response_language,
+
+            // Agent Schedules
+            create_agent_schedule,
+            update_agent_schedule,
+            delete_agent_schedule,
+            list_agent_schedules,
+            list_agent_schedule_runs,
+
+            // Accessibility
+            get_accessibility_verbosity,
+            set_accessibility_verbosity,
+
+            // Power Management
+            get_power_policy,
+            update_power_policy,
+
+            // Project Execution Config
+            get_project_execution_config,
+            update_project_execution_config,
+            // Fault Injection
+            get_fault_injection,
+            set_fault_injection,
+            // File Edits
+            list_session_file_edits,
+            // Project Gitignore
+            audit_project_gitignore,
+            ignore_local_files,
+            set_auto_gitignore,
+            // Provider Key Rotation
+            get_provider_keys,
+            add_provider_key,
+            remove_provider_key,
+            set_rotation_strategy,
+            // Workbench Settings Transfer
+            export_workbench_settings,
+            import_workbench_settings,
+            // Prompt Cost
+            estimate_prompt_cost,
+
+            // Scheduled Prompts
+            schedule_prompt,
+            update_scheduled_prompt,
+            cancel_scheduled_prompt,
+            list_scheduled_prompts,
+
+            // Unseen Completions
+            list_unseen_completions,
+            mark_session_seen,
+            set_active_project,
+            // Hook Templates
+            list_hook_templates,
+            apply_hook_template,
+            // Environment Profiles
+            list_env_profiles,
+            save_env_profile,
+            delete_env_profile,
+            // Agent Results
+            set_agent_output_schema,
+            get_agent_output_schema,
+            get_agent_run_result,
+            // Project Bootstrap
+            analyze_project_stack,
+            apply_project_bootstrap,
+            // Database Migrations
+            get_db_schema_info,
+            rollback_db_schema,
+            // Prompt Templates
+            list_prompt_templates,
+            create_prompt_template,
+            update_prompt_template,
+            delete_prompt_template,
+            expand_prompt_template,
+            // Service Ports
+            get_service_ports,
+            // Router Validation
+            router_validate_providers,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .unwrap_or_else(|e| commands::crash_reports::fatal_error("error while building tauri application", e))
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                commands::power::release_on_exit();
+
+                // Write out buffered usage rows before the process goes away
+                let app = app.clone();
+                if let Err(e) = tauri::async_runtime::block_on(async move {
+                    commands::usage_writer::flush_usage_queue(&app).await
+                }) {
+                    log::warn!("Failed to flush usage rows on exit: {}", e);
+                }
+            }
+        });
 }
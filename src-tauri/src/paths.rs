@@ -0,0 +1,391 @@
+//! Path spellings shared by project listing, checkpoints, file browsing and deletion.
+//!
+//! Windows names the same location in several ways: `C:\p`, the verbatim `\\?\C:\p`
+//! that `canonicalize` returns, UNC shares `\\server\share\p` and their verbatim form
+//! `\\?\UNC\server\share\p`. Paths shown to the user and compared across sessions use
+//! the plain spellings; filesystem calls on paths past MAX_PATH need the verbatim
+//! ones, because the app is not manifested as long-path aware. Call sites opt in to
+//! that with [`long_path`] right before touching the filesystem.
+//!
+//! Whether case matters when comparing paths is decided per volume: it is probed on
+//! the filesystem the first time a volume is seen, unless overridden by the user.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+
+const VERBATIM_PREFIX: &str = r"\\?\";
+const VERBATIM_UNC_PREFIX: &str = r"\\?\UNC\";
+const DEVICE_PREFIX: &str = r"\\.\";
+
+/// Length from which [`long_path`] switches to the verbatim form. Directory creation
+/// already fails at MAX_PATH (260) minus room for an 8.3 file name.
+const LONG_PATH_THRESHOLD: usize = 248;
+
+/// How paths are compared for case
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseSensitivity {
+    /// Probe each volume
+    #[default]
+    Auto,
+    Sensitive,
+    Insensitive,
+}
+
+static CASE_MODE: Lazy<RwLock<CaseSensitivity>> = Lazy::new(|| RwLock::new(CaseSensitivity::Auto));
+
+/// Probe results by volume
+static VOLUME_CASE: Lazy<Mutex<HashMap<String, bool>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Remove a verbatim prefix: `\\?\C:\p` becomes `C:\p` and `\\?\UNC\server\share`
+/// becomes `\\server\share`. Other paths are returned unchanged.
+pub fn strip_verbatim(path: &str) -> String {
+    let unc = path
+        .get(..VERBATIM_UNC_PREFIX.len())
+        .map_or(false, |prefix| prefix.eq_ignore_ascii_case(VERBATIM_UNC_PREFIX));
+    if unc {
+        format!(r"\\{}", &path[VERBATIM_UNC_PREFIX.len()..])
+    } else if let Some(rest) = path.strip_prefix(VERBATIM_PREFIX) {
+        rest.to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Whether the path is on a network share, in any of its spellings
+pub fn is_unc(path: &str) -> bool {
+    let path = strip_verbatim(path);
+    (path.starts_with(r"\\") || path.starts_with("//")) && !path.starts_with(DEVICE_PREFIX)
+}
+
+/// A path as shown to the user, without a verbatim prefix
+pub fn display(path: &Path) -> String {
+    strip_verbatim(&path.to_string_lossy())
+}
+
+/// Canonical spelling of an existing path for display, or `None` if it can't be resolved.
+/// A mapped network drive keeps its drive letter instead of turning into the share it
+/// points to, so it matches the working directory Claude recorded.
+pub fn canonical_display(path: &Path) -> Option<String> {
+    let canonical = display(&path.canonicalize().ok()?);
+    Some(prefer_original_spelling(&path.to_string_lossy(), canonical))
+}
+
+fn prefer_original_spelling(original: &str, canonical: String) -> String {
+    if is_unc(&canonical) && !is_unc(original) {
+        original.to_string()
+    } else {
+        canonical
+    }
+}
+
+pub fn case_mode() -> CaseSensitivity {
+    CASE_MODE.read().map(|mode| *mode).unwrap_or_default()
+}
+
+pub fn set_case_mode(mode: CaseSensitivity) {
+    if let Ok(mut current) = CASE_MODE.write() {
+        *current = mode;
+    }
+}
+
+/// What a volume is assumed to be when it can't be probed
+pub fn platform_default_case_sensitive() -> bool {
+    !cfg!(any(windows, target_os = "macos"))
+}
+
+/// Whether two paths differing only in case are different files where `path` lives
+pub fn is_case_sensitive(path: &str) -> bool {
+    match case_mode() {
+        CaseSensitivity::Sensitive => true,
+        CaseSensitivity::Insensitive => false,
+        CaseSensitivity::Auto => {
+            let path = Path::new(path);
+            let Some(volume) = volume_id(path) else {
+                return platform_default_case_sensitive();
+            };
+            if let Some(cached) = VOLUME_CASE.lock().ok().and_then(|cache| cache.get(&volume).copied()) {
+                return cached;
+            }
+            let detected = probe_case_sensitive(path).unwrap_or_else(platform_default_case_sensitive);
+            log::debug!("Volume {} is case-{}", volume, if detected { "sensitive" } else { "insensitive" });
+            if let Ok(mut cache) = VOLUME_CASE.lock() {
+                cache.insert(volume, detected);
+            }
+            detected
+        }
+    }
+}
+
+/// Identifies the volume a path is on: the device of its closest existing ancestor
+#[cfg(unix)]
+fn volume_id(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+    path.ancestors()
+        .find_map(|ancestor| fs::metadata(ancestor).ok())
+        .map(|metadata| metadata.dev().to_string())
+}
+
+/// Identifies the volume a path is on: its drive or share
+#[cfg(not(unix))]
+fn volume_id(path: &Path) -> Option<String> {
+    match path.components().next()? {
+        std::path::Component::Prefix(prefix) => Some(strip_verbatim(&prefix.as_os_str().to_string_lossy()).to_lowercase()),
+        _ => None,
+    }
+}
+
+/// Flip the case of the closest existing ancestor with letters in its name and see
+/// whether that names the same entry; `None` if nothing could be probed
+fn probe_case_sensitive(path: &Path) -> Option<bool> {
+    for ancestor in path.ancestors() {
+        let Some(name) = ancestor.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let flipped: String = name
+            .chars()
+            .map(|c| if c.is_ascii_uppercase() { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() })
+            .collect();
+        if flipped == name {
+            continue;
+        }
+        let Ok(original) = fs::metadata(long_path(ancestor)) else {
+            continue;
+        };
+        return Some(match fs::metadata(long_path(&ancestor.with_file_name(&flipped))) {
+            Err(_) => true,
+            Ok(twin) => !same_entry(&original, &twin),
+        });
+    }
+    None
+}
+
+#[cfg(unix)]
+fn same_entry(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+/// Without inode numbers, a twin that exists is taken to be the same entry
+#[cfg(not(unix))]
+fn same_entry(_a: &fs::Metadata, _b: &fs::Metadata) -> bool {
+    true
+}
+
+/// Key under which two spellings of the same path compare equal, taking the case
+/// sensitivity of the path's volume into account. See [`comparison_key_with`].
+pub fn comparison_key(path: &str) -> String {
+    comparison_key_with(path, is_case_sensitive(path))
+}
+
+/// Key under which two spellings of the same path compare equal: forward slashes,
+/// no verbatim prefix, no trailing or leading slash, and `C:/p` as `c/p`. Everything
+/// is lowercased unless `case_sensitive`; drive letters always are. Shares keep one
+/// leading slash (`/server/share/p`) so they can't collide with a relative path.
+pub fn comparison_key_with(path: &str, case_sensitive: bool) -> String {
+    let stripped = strip_verbatim(path);
+    let mut key = if case_sensitive { stripped } else { stripped.to_lowercase() }.replace('\\', "/");
+
+    if key.ends_with('/') && key.len() > 1 {
+        key.pop();
+    }
+    if key.starts_with('/') {
+        key.remove(0);
+    }
+
+    // Drive letters: `C:` becomes `c`, `C:/p` becomes `c/p`
+    let bytes = key.as_bytes();
+    if bytes.len() >= 2 && bytes[1] == b':' {
+        let drive = key[..1].to_lowercase();
+        if bytes.len() == 2 {
+            key = drive;
+        } else if bytes[2] == b'/' {
+            key = if key.len() == 3 {
+                drive
+            } else {
+                format!("{}/{}", drive, &key[3..])
+            };
+        }
+    }
+
+    key
+}
+
+/// Verbatim spelling of an absolute Windows path that is too long for the regular
+/// APIs, with separators normalized and `.`/`..` resolved (verbatim paths are not
+/// parsed by Windows). Short, relative and already verbatim paths are returned as is.
+fn long_path_str(path: &str) -> String {
+    if path.len() < LONG_PATH_THRESHOLD || path.starts_with(VERBATIM_PREFIX) || path.starts_with(DEVICE_PREFIX) {
+        return path.to_string();
+    }
+    let path = path.replace('/', r"\");
+
+    let (prefix, root, rest) = if let Some(share_path) = path.strip_prefix(r"\\") {
+        // The server and share names are the root and can't be left with `..`
+        let mut parts = share_path.splitn(3, '\\');
+        let (Some(server), Some(share)) = (parts.next(), parts.next()) else {
+            return path;
+        };
+        (VERBATIM_UNC_PREFIX, format!(r"{}\{}", server, share), parts.next().unwrap_or(""))
+    } else if path.len() >= 3 && path.as_bytes()[1] == b':' && path.as_bytes()[2] == b'\\' {
+        (VERBATIM_PREFIX, path[..2].to_string(), &path[3..])
+    } else {
+        return path;
+    };
+
+    let mut components: Vec<&str> = Vec::new();
+    for component in rest.split('\\') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            _ => components.push(component),
+        }
+    }
+
+    let mut long = format!("{}{}", prefix, root);
+    for component in components {
+        long.push('\\');
+        long.push_str(component);
+    }
+    long
+}
+
+/// Path to hand to filesystem calls: on Windows, long absolute paths (including UNC
+/// shares) get the verbatim prefix; everywhere else the path is returned unchanged
+pub fn long_path(path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        if let Some(path) = path.to_str() {
+            return PathBuf::from(long_path_str(path));
+        }
+    }
+    path.to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `root` followed by enough `node_modules` levels to pass MAX_PATH
+    fn deep(root: &str, separator: &str) -> String {
+        let mut path = root.to_string();
+        for _ in 0..25 {
+            path.push_str(separator);
+            path.push_str("node_modules");
+        }
+        path
+    }
+
+    #[test]
+    fn test_strip_verbatim() {
+        assert_eq!(strip_verbatim(r"\\?\C:\work\app"), r"C:\work\app");
+        assert_eq!(strip_verbatim(r"\\?\UNC\nas\projects\app"), r"\\nas\projects\app");
+        assert_eq!(strip_verbatim(r"\\?\unc\nas\projects\app"), r"\\nas\projects\app");
+        assert_eq!(strip_verbatim(r"\\nas\projects\app"), r"\\nas\projects\app");
+        assert_eq!(strip_verbatim("/home/user/app"), "/home/user/app");
+    }
+
+    #[test]
+    fn test_network_drive_project_spellings_compare_equal() {
+        let key = comparison_key_with(r"\\nas\projects\app", false);
+        assert_eq!(key, "/nas/projects/app");
+        assert_eq!(comparison_key_with(r"\\?\UNC\nas\projects\app", false), key);
+        assert_eq!(comparison_key_with(r"\\NAS\Projects\App\", false), key);
+        assert_eq!(comparison_key_with("//nas/projects/app", false), key);
+
+        // A share never collides with a relative or drive path of the same shape
+        assert_ne!(comparison_key_with("nas/projects/app", false), key);
+        assert_ne!(comparison_key_with(r"N:\projects\app", false), key);
+    }
+
+    #[test]
+    fn test_local_comparison_keys() {
+        assert_eq!(comparison_key_with(r"C:\Users\dev\app\", false), "c/users/dev/app");
+        assert_eq!(comparison_key_with(r"\\?\C:\Users\dev\app", false), "c/users/dev/app");
+        assert_eq!(comparison_key_with("C:", false), "c");
+        assert_eq!(comparison_key_with(r"C:\", false), "c");
+        assert_eq!(comparison_key_with("/home/dev/app/", false), "home/dev/app");
+    }
+
+    #[test]
+    fn test_case_sensitive_keys_keep_case() {
+        assert_eq!(comparison_key_with("/home/dev/App", true), "home/dev/App");
+        assert_ne!(
+            comparison_key_with("/home/dev/App", true),
+            comparison_key_with("/home/dev/app", true)
+        );
+        // Drive letters never depend on case
+        assert_eq!(comparison_key_with(r"C:\Work\App", true), "c/Work/App");
+        assert_eq!(comparison_key_with(r"\\?\UNC\nas\Projects", true), "/nas/Projects");
+    }
+
+    #[test]
+    fn test_probe_matches_filesystem() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("CaseProbe");
+        fs::create_dir(&project).unwrap();
+        let twin_exists = temp_dir.path().join("caseprobe").exists();
+
+        assert_eq!(probe_case_sensitive(&project.join("missing")), Some(!twin_exists));
+    }
+
+    #[test]
+    fn test_is_unc() {
+        assert!(is_unc(r"\\nas\projects"));
+        assert!(is_unc(r"\\?\UNC\nas\projects"));
+        assert!(is_unc("//nas/projects"));
+        assert!(!is_unc(r"\\?\C:\work"));
+        assert!(!is_unc(r"\\.\pipe\name"));
+        assert!(!is_unc("/home/dev"));
+    }
+
+    #[test]
+    fn test_mapped_drive_keeps_its_letter() {
+        assert_eq!(
+            prefer_original_spelling(r"Z:\app", r"\\nas\projects\app".to_string()),
+            r"Z:\app"
+        );
+        assert_eq!(
+            prefer_original_spelling(r"\\nas\projects\app", r"\\nas\projects\app".to_string()),
+            r"\\nas\projects\app"
+        );
+        assert_eq!(
+            prefer_original_spelling(r"C:\work\..\app", r"C:\app".to_string()),
+            r"C:\app"
+        );
+    }
+
+    #[test]
+    fn test_long_paths_get_verbatim_prefix() {
+        let local = deep(r"C:\work\app", r"\");
+        assert_eq!(long_path_str(&local), format!(r"\\?\{}", local));
+
+        let share = deep(r"\\nas\projects\app", r"\");
+        assert_eq!(long_path_str(&share), format!(r"\\?\UNC\{}", &share[2..]));
+
+        // Separators are normalized and dot components resolved
+        let mixed = deep("C:/work/./app/../app", "/");
+        assert_eq!(long_path_str(&mixed), format!(r"\\?\{}", deep(r"C:\work\app", r"\")));
+
+        // `..` can't climb above the share
+        let climbing = format!(r"\\nas\projects\..\..\{}", "x".repeat(260));
+        assert_eq!(long_path_str(&climbing), format!(r"\\?\UNC\nas\projects\{}", "x".repeat(260)));
+    }
+
+    #[test]
+    fn test_short_relative_and_verbatim_paths_unchanged() {
+        assert_eq!(long_path_str(r"C:\work\app"), r"C:\work\app");
+        assert_eq!(long_path_str(r"\\nas\projects\app"), r"\\nas\projects\app");
+
+        let relative = deep("app", r"\");
+        assert_eq!(long_path_str(&relative), relative);
+
+        let verbatim = format!(r"\\?\{}", deep(r"C:\work", r"\"));
+        assert_eq!(long_path_str(&verbatim), verbatim);
+    }
+}
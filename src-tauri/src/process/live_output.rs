@@ -0,0 +1,289 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Default in-memory cap for a single process' live output
+pub const DEFAULT_LIVE_OUTPUT_MAX_BYTES: usize = 8 * 1024 * 1024;
+
+/// Largest window a single range read may return
+pub const MAX_LIVE_OUTPUT_RANGE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Smallest accepted in-memory cap
+pub const MIN_LIVE_OUTPUT_MAX_BYTES: usize = 64 * 1024;
+
+/// Journals not written to for this long are deleted at startup
+pub const LIVE_OUTPUT_JOURNAL_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// A window into a process' live output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveOutputRange {
+    /// Absolute byte offset of `data` in the full output
+    pub offset: u64,
+    pub data: String,
+    /// Offset to request next to continue reading
+    pub next_offset: u64,
+    /// Total bytes produced by the process so far
+    pub total_bytes: u64,
+    /// Offset of the first byte still held in memory; everything before it lives in the spill file
+    pub retained_from: u64,
+}
+
+/// Bounded live output buffer.
+///
+/// Holds the most recent output in memory up to `max_bytes`. Older output is
+/// evicted at line boundaries and appended to a spill file, so the full stream
+/// stays addressable by absolute byte offset. With a journal, every line is also
+/// appended to a file that outlives the buffer and the app.
+pub struct LiveOutputBuffer {
+    run_id: i64,
+    memory: VecDeque<u8>,
+    /// Absolute offset of `memory[0]`
+    start_offset: u64,
+    max_bytes: usize,
+    spill: Option<File>,
+    spill_path: Option<PathBuf>,
+    journal: Option<File>,
+}
+
+impl LiveOutputBuffer {
+    pub fn new(run_id: i64, max_bytes: usize) -> Self {
+        Self {
+            run_id,
+            memory: VecDeque::new(),
+            start_offset: 0,
+            max_bytes: max_bytes.max(MIN_LIVE_OUTPUT_MAX_BYTES),
+            spill: None,
+            spill_path: None,
+            journal: None,
+        }
+    }
+
+    /// Also append every line to `path`, replacing what an earlier run with the same
+    /// id left there
+    pub fn with_journal(mut self, path: &Path) -> Self {
+        match OpenOptions::new().create(true).write(true).truncate(true).open(path) {
+            Ok(file) => self.journal = Some(file),
+            Err(e) => log::warn!("Live output of run {} won't be persisted: {}", self.run_id, e),
+        }
+        self
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.start_offset + self.memory.len() as u64
+    }
+
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes.max(MIN_LIVE_OUTPUT_MAX_BYTES);
+        self.enforce_limit();
+    }
+
+    /// Append one output line
+    pub fn push_line(&mut self, line: &str) {
+        if let Some(journal) = self.journal.as_mut() {
+            if let Err(e) = journal.write_all(format!("{}\n", line).as_bytes()) {
+                log::warn!("Stopped persisting live output of run {}: {}", self.run_id, e);
+                self.journal = None;
+            }
+        }
+        self.memory.extend(line.as_bytes());
+        self.memory.push_back(b'\n');
+        self.enforce_limit();
+    }
+
+    /// Output still held in memory (the most recent `max_bytes` at most)
+    pub fn retained(&self) -> String {
+        let (front, back) = self.memory.as_slices();
+        let mut bytes = Vec::with_capacity(self.memory.len());
+        bytes.extend_from_slice(front);
+        bytes.extend_from_slice(back);
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// Read up to `len` bytes starting at absolute `offset`, reading from the spill file when needed.
+    /// The window is adjusted to UTF-8 character boundaries.
+    pub fn read_range(&mut self, offset: u64, len: usize) -> Result<LiveOutputRange, String> {
+        let total = self.total_bytes();
+        let offset = offset.min(total);
+        let end = offset
+            .saturating_add(len.min(MAX_LIVE_OUTPUT_RANGE_BYTES) as u64)
+            .min(total);
+
+        let mut bytes = Vec::with_capacity((end - offset) as usize);
+        if offset < self.start_offset {
+            let spill_end = end.min(self.start_offset);
+            let spill = self
+                .spill
+                .as_mut()
+                .ok_or_else(|| format!("Spilled output for run {} is unavailable", self.run_id))?;
+            spill.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+            let mut chunk = vec![0u8; (spill_end - offset) as usize];
+            spill.read_exact(&mut chunk).map_err(|e| e.to_string())?;
+            bytes.extend_from_slice(&chunk);
+        }
+        if end > self.start_offset {
+            let from = offset.max(self.start_offset) - self.start_offset;
+            let to = end - self.start_offset;
+            bytes.extend(self.memory.range(from as usize..to as usize));
+        }
+
+        Ok(char_window(&bytes, offset, total, self.start_offset))
+    }
+
+    fn enforce_limit(&mut self) {
+        if self.memory.len() <= self.max_bytes {
+            return;
+        }
+
+        // Evict down to 3/4 of the cap so eviction doesn't run on every line
+        let target = self.max_bytes / 4 * 3;
+        let mut evict = self.memory.len() - target;
+        // Extend to the end of the current line so the retained output starts on a line
+        while evict < self.memory.len() && self.memory[evict - 1] != b'\n' {
+            evict += 1;
+        }
+
+        let evicted: Vec<u8> = self.memory.drain(..evict).collect();
+        if let Err(e) = self.spill_bytes(&evicted) {
+            log::warn!("Failed to spill live output for run {}: {}", self.run_id, e);
+        }
+        self.start_offset += evicted.len() as u64;
+    }
+
+    fn spill_bytes(&mut self, bytes: &[u8]) -> Result<(), String> {
+        if self.spill.is_none() {
+            let dir = std::env::temp_dir().join("claude-workbench-live-output");
+            fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            let path = dir.join(format!("run-{}-{}.log", self.run_id, std::process::id()));
+            let file = OpenOptions::new()
+                .create(true)
+                .read(true)
+                .write(true)
+                .truncate(true)
+                .open(&path)
+                .map_err(|e| e.to_string())?;
+            self.spill = Some(file);
+            self.spill_path = Some(path);
+        }
+
+        let spill = self.spill.as_mut().expect("spill file opened above");
+        spill.seek(SeekFrom::Start(self.start_offset)).map_err(|e| e.to_string())?;
+        spill.write_all(bytes).map_err(|e| e.to_string())
+    }
+}
+
+/// Build a range from bytes read at `offset`, dropping a partial character at either edge
+fn char_window(bytes: &[u8], offset: u64, total_bytes: u64, retained_from: u64) -> LiveOutputRange {
+    let lead = bytes.iter().take_while(|b| (**b & 0xC0) == 0x80).count();
+    let mut valid = &bytes[lead..];
+    if let Err(e) = std::str::from_utf8(valid) {
+        if e.error_len().is_none() {
+            valid = &valid[..e.valid_up_to()];
+        }
+    }
+    let start = offset + lead as u64;
+
+    LiveOutputRange {
+        offset: start,
+        data: String::from_utf8_lossy(valid).into_owned(),
+        next_offset: start + valid.len() as u64,
+        total_bytes,
+        retained_from,
+    }
+}
+
+/// Where the journal of a run's live output is kept under `dir`
+pub fn journal_path(dir: &Path, run_id: i64) -> PathBuf {
+    dir.join(format!("run-{}.log", run_id))
+}
+
+fn journal_run_id(path: &Path) -> Option<i64> {
+    path.file_name()?.to_str()?.strip_prefix("run-")?.strip_suffix(".log")?.parse().ok()
+}
+
+/// Delete journals not written to within `max_age`; returns the highest run id kept
+pub fn prune_journals(dir: &Path, max_age: Duration) -> Option<i64> {
+    let cutoff = SystemTime::now().checked_sub(max_age)?;
+    let mut highest = None;
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        let Some(run_id) = journal_run_id(&path) else {
+            continue;
+        };
+        let stale = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .map_or(false, |modified| modified < cutoff);
+        if stale {
+            if let Err(e) = fs::remove_file(&path) {
+                log::warn!("Failed to delete live output journal {}: {}", path.display(), e);
+            }
+        } else {
+            highest = highest.max(Some(run_id));
+        }
+    }
+    highest
+}
+
+fn open_journal(dir: &Path, run_id: i64) -> Result<Option<(File, u64)>, String> {
+    match File::open(journal_path(dir, run_id)) {
+        Ok(file) => {
+            let len = file.metadata().map_err(|e| e.to_string())?.len();
+            Ok(Some((file, len)))
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Read a byte window of a run's journal; None if the run has none
+pub fn read_journal_range(
+    dir: &Path,
+    run_id: i64,
+    offset: u64,
+    len: usize,
+) -> Result<Option<LiveOutputRange>, String> {
+    let Some((mut file, total)) = open_journal(dir, run_id)? else {
+        return Ok(None);
+    };
+    let offset = offset.min(total);
+    let end = offset
+        .saturating_add(len.min(MAX_LIVE_OUTPUT_RANGE_BYTES) as u64)
+        .min(total);
+    file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+    let mut bytes = vec![0u8; (end - offset) as usize];
+    file.read_exact(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(Some(char_window(&bytes, offset, total, 0)))
+}
+
+/// The last `max_bytes` of a run's journal, starting on a line; None if the run has none
+pub fn read_journal_tail(
+    dir: &Path,
+    run_id: i64,
+    max_bytes: usize,
+) -> Result<Option<String>, String> {
+    let Some((mut file, total)) = open_journal(dir, run_id)? else {
+        return Ok(None);
+    };
+    let start = total.saturating_sub(max_bytes as u64);
+    file.seek(SeekFrom::Start(start)).map_err(|e| e.to_string())?;
+    let mut bytes = Vec::with_capacity((total - start) as usize);
+    file.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+    let from = if start > 0 {
+        bytes.iter().position(|b| *b == b'\n').map_or(bytes.len(), |i| i + 1)
+    } else {
+        0
+    };
+    Ok(Some(String::from_utf8_lossy(&bytes[from..]).into_owned()))
+}
+
+impl Drop for LiveOutputBuffer {
+    fn drop(&mut self) {
+        self.spill.take();
+        if let Some(path) = self.spill_path.take() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
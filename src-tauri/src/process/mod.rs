@@ -1,3 +1,6 @@
+pub mod live_output;
 pub mod registry;
+pub mod suspend;
 
+pub use live_output::*;
 pub use registry::*;
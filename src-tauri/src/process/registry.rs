@@ -1,9 +1,16 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use tokio::process::Child;
 
+use super::live_output::{
+    journal_path, prune_journals, read_journal_range, read_journal_tail, LiveOutputBuffer,
+    LiveOutputRange, DEFAULT_LIVE_OUTPUT_MAX_BYTES, LIVE_OUTPUT_JOURNAL_MAX_AGE,
+};
+use super::suspend::{resume_tree, suspend_tree};
+
 /// Type of process being tracked
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ProcessType {
@@ -26,6 +33,12 @@ pub struct ProcessInfo {
     pub project_path: String,
     pub task: String,
     pub model: String,
+    /// Environment resolved for this run, with credentials masked
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// When the process tree was suspended with `pause_process`; None while running
+    #[serde(default)]
+    pub paused_at: Option<DateTime<Utc>>,
 }
 
 /// Information about a running process with handle
@@ -33,13 +46,15 @@ pub struct ProcessInfo {
 pub struct ProcessHandle {
     pub info: ProcessInfo,
     pub child: Arc<Mutex<Option<Child>>>,
-    pub live_output: Arc<Mutex<String>>,
+    pub live_output: Arc<Mutex<LiveOutputBuffer>>,
 }
 
 /// Registry for tracking active agent processes
 pub struct ProcessRegistry {
     processes: Arc<Mutex<HashMap<i64, ProcessHandle>>>, // run_id -> ProcessHandle
     next_id: Arc<Mutex<i64>>, // Auto-incrementing ID for non-agent processes
+    live_output_max_bytes: Arc<Mutex<usize>>, // In-memory cap per process, overflow spills to disk
+    live_output_dir: Arc<Mutex<Option<PathBuf>>>, // Where live output is journaled, once known
 }
 
 impl ProcessRegistry {
@@ -47,7 +62,26 @@ impl ProcessRegistry {
         Self {
             processes: Arc::new(Mutex::new(HashMap::new())),
             next_id: Arc::new(Mutex::new(1000000)), // Start at high number to avoid conflicts
+            live_output_max_bytes: Arc::new(Mutex::new(DEFAULT_LIVE_OUTPUT_MAX_BYTES)),
+            live_output_dir: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Journal the live output of new processes under `dir`, so it can still be read
+    /// after a restart. Old journals are pruned, and generated run IDs continue above
+    /// the ones kept so a new run doesn't overwrite them.
+    pub fn set_live_output_dir(&self, dir: PathBuf) -> Result<(), String> {
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        if let Some(highest) = prune_journals(&dir, LIVE_OUTPUT_JOURNAL_MAX_AGE) {
+            let mut next_id = self.next_id.lock().map_err(|e| e.to_string())?;
+            *next_id = (*next_id).max(highest + 1);
         }
+        *self.live_output_dir.lock().map_err(|e| e.to_string())? = Some(dir);
+        Ok(())
+    }
+
+    fn live_output_dir(&self) -> Option<PathBuf> {
+        self.live_output_dir.lock().ok()?.clone()
     }
 
     /// Generate a unique ID for non-agent processes
@@ -78,22 +112,23 @@ impl ProcessRegistry {
             project_path,
             task,
             model,
+            env: BTreeMap::new(),
+            paused_at: None,
         };
 
         self.register_process_internal(run_id, process_info, child)
     }
 
-    /// Register a new Claude session (without child process - handled separately)
-    pub fn register_claude_session(
+    /// Register a Claude session under a run ID handed out by `generate_id` at spawn
+    pub fn register_claude_session_as(
         &self,
+        run_id: i64,
         session_id: String,
         pid: u32,
         project_path: String,
         task: String,
         model: String,
-    ) -> Result<i64, String> {
-        let run_id = self.generate_id()?;
-        
+    ) -> Result<(), String> {
         let process_info = ProcessInfo {
             run_id,
             process_type: ProcessType::ClaudeSession { session_id },
@@ -102,19 +137,22 @@ impl ProcessRegistry {
             project_path,
             task,
             model,
+            env: BTreeMap::new(),
+            paused_at: None,
         };
 
         // Register without child - Claude sessions use ClaudeProcessState for process management
+        let live_output = self.new_live_output(run_id)?;
         let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
         
         let process_handle = ProcessHandle {
             info: process_info,
             child: Arc::new(Mutex::new(None)), // No child handle for Claude sessions
-            live_output: Arc::new(Mutex::new(String::new())),
+            live_output,
         };
 
         processes.insert(run_id, process_handle);
-        Ok(run_id)
+        Ok(())
     }
 
     /// Internal method to register any process
@@ -124,18 +162,28 @@ impl ProcessRegistry {
         process_info: ProcessInfo,
         child: Child,
     ) -> Result<(), String> {
+        let live_output = self.new_live_output(run_id)?;
         let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
 
         let process_handle = ProcessHandle {
             info: process_info,
             child: Arc::new(Mutex::new(Some(child))),
-            live_output: Arc::new(Mutex::new(String::new())),
+            live_output,
         };
 
         processes.insert(run_id, process_handle);
         Ok(())
     }
 
+    fn new_live_output(&self, run_id: i64) -> Result<Arc<Mutex<LiveOutputBuffer>>, String> {
+        let max_bytes = *self.live_output_max_bytes.lock().map_err(|e| e.to_string())?;
+        let mut buffer = LiveOutputBuffer::new(run_id, max_bytes);
+        if let Some(dir) = self.live_output_dir() {
+            buffer = buffer.with_journal(&journal_path(&dir, run_id));
+        }
+        Ok(Arc::new(Mutex::new(buffer)))
+    }
+
     /// Get all running Claude sessions
     pub fn get_running_claude_sessions(&self) -> Result<Vec<ProcessInfo>, String> {
         let processes = self.processes.lock().map_err(|e| e.to_string())?;
@@ -164,6 +212,15 @@ impl ProcessRegistry {
             .map(|handle| handle.info.clone()))
     }
 
+    /// Record the (redacted) environment a run was started with
+    pub fn set_process_env(&self, run_id: i64, env: BTreeMap<String, String>) -> Result<(), String> {
+        let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
+        if let Some(handle) = processes.get_mut(&run_id) {
+            handle.info.env = env;
+        }
+        Ok(())
+    }
+
     /// Unregister a process (called when it completes)
     #[allow(dead_code)]
     pub fn unregister_process(&self, run_id: i64) -> Result<(), String> {
@@ -203,10 +260,45 @@ impl ProcessRegistry {
         Ok(processes.get(&run_id).map(|handle| handle.info.clone()))
     }
 
+    /// Suspend a process and its descendants, returning its updated info
+    pub fn pause_process(&self, run_id: i64) -> Result<ProcessInfo, String> {
+        let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
+        let handle = processes
+            .get_mut(&run_id)
+            .ok_or_else(|| format!("Process {} not found in registry", run_id))?;
+        if handle.info.paused_at.is_none() {
+            suspend_tree(handle.info.pid)?;
+            handle.info.paused_at = Some(Utc::now());
+            log::info!("Paused process {} (PID: {})", run_id, handle.info.pid);
+        }
+        Ok(handle.info.clone())
+    }
+
+    /// Resume a process paused with `pause_process`, returning its updated info
+    pub fn resume_process(&self, run_id: i64) -> Result<ProcessInfo, String> {
+        let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
+        let handle = processes
+            .get_mut(&run_id)
+            .ok_or_else(|| format!("Process {} not found in registry", run_id))?;
+        if handle.info.paused_at.is_some() {
+            resume_tree(handle.info.pid)?;
+            handle.info.paused_at = None;
+            log::info!("Resumed process {} (PID: {})", run_id, handle.info.pid);
+        }
+        Ok(handle.info.clone())
+    }
+
     /// Kill a running process with proper cleanup
     pub async fn kill_process(&self, run_id: i64) -> Result<bool, String> {
         use log::{error, info, warn};
 
+        // A suspended tree would not act on SIGTERM and its children would stay stopped
+        if self.get_process(run_id)?.map_or(false, |info| info.paused_at.is_some()) {
+            if let Err(e) = self.resume_process(run_id) {
+                warn!("Failed to resume paused process {} before killing it: {}", run_id, e);
+            }
+        }
+
         // First check if the process exists and get its PID
         let (pid, child_arc) = {
             let processes = self.processes.lock().map_err(|e| e.to_string())?;
@@ -445,23 +537,74 @@ impl ProcessRegistry {
         let processes = self.processes.lock().map_err(|e| e.to_string())?;
         if let Some(handle) = processes.get(&run_id) {
             let mut live_output = handle.live_output.lock().map_err(|e| e.to_string())?;
-            live_output.push_str(output);
-            live_output.push('\n');
+            live_output.push_line(output);
         }
         Ok(())
     }
 
-    /// Get live output for a process
+    /// Get live output still held in memory for a process, or read back from its journal
+    /// once it is gone from the registry.
+    /// For very chatty runs this is only the most recent part; use `get_live_output_range` for the rest.
     pub fn get_live_output(&self, run_id: i64) -> Result<String, String> {
         let processes = self.processes.lock().map_err(|e| e.to_string())?;
         if let Some(handle) = processes.get(&run_id) {
             let live_output = handle.live_output.lock().map_err(|e| e.to_string())?;
-            Ok(live_output.clone())
+            Ok(live_output.retained())
         } else {
-            Ok(String::new())
+            drop(processes);
+            Ok(self.persisted_live_output(run_id)?.unwrap_or_default())
         }
     }
 
+    /// The most recent `live_output_max_bytes` of a run's journal; None if it has none
+    pub fn persisted_live_output(&self, run_id: i64) -> Result<Option<String>, String> {
+        let Some(dir) = self.live_output_dir() else {
+            return Ok(None);
+        };
+        read_journal_tail(&dir, run_id, self.live_output_max_bytes()?)
+    }
+
+    /// Read a byte window of a process' live output, including output spilled to disk.
+    /// Runs no longer in the registry are read from their journal.
+    pub fn get_live_output_range(
+        &self,
+        run_id: i64,
+        offset: u64,
+        len: usize,
+    ) -> Result<Option<LiveOutputRange>, String> {
+        let live_output = {
+            let processes = self.processes.lock().map_err(|e| e.to_string())?;
+            match processes.get(&run_id) {
+                Some(handle) => handle.live_output.clone(),
+                None => {
+                    drop(processes);
+                    return match self.live_output_dir() {
+                        Some(dir) => read_journal_range(&dir, run_id, offset, len),
+                        None => Ok(None),
+                    };
+                }
+            }
+        };
+        let mut live_output = live_output.lock().map_err(|e| e.to_string())?;
+        live_output.read_range(offset, len).map(Some)
+    }
+
+    /// Get the in-memory live output cap per process
+    pub fn live_output_max_bytes(&self) -> Result<usize, String> {
+        Ok(*self.live_output_max_bytes.lock().map_err(|e| e.to_string())?)
+    }
+
+    /// Set the in-memory live output cap, applied to running processes as well
+    pub fn set_live_output_max_bytes(&self, max_bytes: usize) -> Result<(), String> {
+        *self.live_output_max_bytes.lock().map_err(|e| e.to_string())? = max_bytes;
+        let processes = self.processes.lock().map_err(|e| e.to_string())?;
+        for handle in processes.values() {
+            let mut live_output = handle.live_output.lock().map_err(|e| e.to_string())?;
+            live_output.set_max_bytes(max_bytes);
+        }
+        Ok(())
+    }
+
     /// Cleanup finished processes
     #[allow(dead_code)]
     pub async fn cleanup_finished_processes(&self) -> Result<Vec<i64>, String> {
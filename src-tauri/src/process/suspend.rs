@@ -0,0 +1,122 @@
+//! Suspending and resuming a process together with everything it spawned, so that tools a
+//! run started (shells, test runners, MCP servers) stop as well.
+
+#[cfg(not(target_os = "windows"))]
+use std::collections::HashMap;
+use std::process::Command;
+
+/// Suspends or resumes the processes listed in `$ids` and their descendants
+#[cfg(target_os = "windows")]
+const WINDOWS_TREE_SCRIPT: &str = r#"
+$ids = @(__PID__); $i = 0
+$all = Get-CimInstance Win32_Process | Select-Object ProcessId, ParentProcessId
+while ($i -lt $ids.Count) {
+    $ids += @($all | Where-Object { $_.ParentProcessId -eq $ids[$i] } | ForEach-Object { $_.ProcessId })
+    $i++
+}
+$nt = Add-Type -Name Nt -Namespace Workbench -PassThru -MemberDefinition '[DllImport("ntdll.dll")] public static extern int NtSuspendProcess(IntPtr h); [DllImport("ntdll.dll")] public static extern int NtResumeProcess(IntPtr h);'
+foreach ($id in $ids) {
+    try { [void]$nt::__CALL__([Diagnostics.Process]::GetProcessById($id).Handle) } catch {}
+}
+"#;
+
+/// `pid` followed by its descendants, parents before children
+#[cfg(not(target_os = "windows"))]
+fn process_tree(pid: u32) -> Vec<u32> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    if let Ok(output) = Command::new("ps").args(["-A", "-o", "pid=", "-o", "ppid="]).output() {
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let mut fields = line.split_whitespace().filter_map(|f| f.parse::<u32>().ok());
+            if let (Some(child), Some(parent)) = (fields.next(), fields.next()) {
+                children.entry(parent).or_default().push(child);
+            }
+        }
+    }
+
+    let mut tree = vec![pid];
+    let mut next = 0;
+    while next < tree.len() {
+        let parent = tree[next];
+        for &child in children.get(&parent).into_iter().flatten() {
+            if !tree.contains(&child) {
+                tree.push(child);
+            }
+        }
+        next += 1;
+    }
+    tree
+}
+
+#[cfg(not(target_os = "windows"))]
+fn signal_tree(pid: u32, signal: &str) -> Result<(), String> {
+    let mut tree = process_tree(pid);
+    // Stop the parent before its children so it cannot start new ones meanwhile;
+    // continue the children first so the parent finds them running
+    if signal == "-CONT" {
+        tree.reverse();
+    }
+    for target in &tree {
+        let output = Command::new("kill")
+            .args([signal, &target.to_string()])
+            .output()
+            .map_err(|e| format!("Failed to execute kill command: {}", e))?;
+        // Descendants may exit in between; only the run's own process has to be signalled
+        if !output.status.success() && *target == pid {
+            return Err(format!(
+                "Failed to signal PID {}: {}",
+                pid,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn windows_tree(pid: u32, call: &str) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+    let script = WINDOWS_TREE_SCRIPT
+        .replace("__PID__", &pid.to_string())
+        .replace("__CALL__", call);
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .map_err(|e| format!("Failed to run PowerShell: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Failed to {} PID {}: {}",
+            if call == "NtSuspendProcess" { "suspend" } else { "resume" },
+            pid,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Suspend a process and its descendants (SIGSTOP, or NtSuspendProcess on Windows)
+pub fn suspend_tree(pid: u32) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        windows_tree(pid, "NtSuspendProcess")
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        signal_tree(pid, "-STOP")
+    }
+}
+
+/// Resume a process and its descendants suspended by `suspend_tree`
+pub fn resume_tree(pid: u32) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        windows_tree(pid, "NtResumeProcess")
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        signal_tree(pid, "-CONT")
+    }
+}
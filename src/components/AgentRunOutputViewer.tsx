@@ -23,6 +23,7 @@ import { Popover } from '@/components/ui/popover';
 import { api, type AgentRunWithMetrics } from '@/lib/api';
 import { useOutputCache } from '@/lib/outputCache';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { listenOutputLines } from '@/lib/outputStream';
 import { StreamMessage } from './StreamMessage';
 import { ErrorBoundary } from './ErrorBoundary';
 import { formatISOTimestamp } from '@/lib/date-utils';
@@ -269,7 +270,7 @@ export function AgentRunOutputViewer({
       }, 100);
 
       // Set up live event listeners with run ID isolation
-      const outputUnlisten = await listen<string>(`agent-output:${run.id}`, (event) => {
+      const outputUnlisten = await listenOutputLines(`agent-output:${run.id}`, (event) => {
         try {
           // Skip messages during initial load phase
           if (isInitialLoadRef.current) {
@@ -29,6 +29,7 @@ import { api, type Session, type Project } from "@/lib/api";
 import { cn, normalizeUsageData } from "@/lib/utils";
 import { open } from "@tauri-apps/plugin-dialog";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import { listenOutputLines } from "@/lib/outputStream";
 // import { StreamMessage } from "./StreamMessage"; // 已替换为StreamMessageV2
 import { StreamMessageV2 } from "./message";
 import { FloatingPromptInput, type FloatingPromptInputRef } from "./FloatingPromptInput";
@@ -1468,7 +1469,7 @@ export const ClaudeCodeSession: React.FC<ClaudeCodeSessionProps> = ({
     isListeningRef.current = true;
     
     // Set up session-specific listeners
-    const outputUnlisten = await listen<string>(`claude-output:${sessionId}`, async (event) => {
+    const outputUnlisten = await listenOutputLines(`claude-output:${sessionId}`, async (event) => {
       try {
         console.log('[ClaudeCodeSession] Received claude-output on reconnect:', event.payload);
 
@@ -1619,7 +1620,7 @@ export const ClaudeCodeSession: React.FC<ClaudeCodeSessionProps> = ({
         const attachSessionSpecificListeners = async (sid: string) => {
           console.log('[ClaudeCodeSession] Attaching session-specific listeners for', sid);
 
-          const specificOutputUnlisten = await listen<string>(`claude-output:${sid}`, (evt) => {
+          const specificOutputUnlisten = await listenOutputLines(`claude-output:${sid}`, (evt) => {
             handleStreamMessage(evt.payload, userInputTranslation || undefined);
           });
 
@@ -1639,7 +1640,7 @@ export const ClaudeCodeSession: React.FC<ClaudeCodeSessionProps> = ({
         };
 
         // Generic listeners (catch-all) - ALWAYS process to ensure user sees output
-        const genericOutputUnlisten = await listen<string>('claude-output', async (event) => {
+        const genericOutputUnlisten = await listenOutputLines('claude-output', async (event) => {
           // Always handle generic events as fallback to ensure output visibility
           handleStreamMessage(event.payload, userInputTranslation || undefined);
 
@@ -1855,6 +1856,22 @@ export const ClaudeCodeSession: React.FC<ClaudeCodeSessionProps> = ({
         if (effectiveSession && !isFirstPrompt) {
           // Resume existing session
           console.log('[ClaudeCodeSession] Resuming session:', effectiveSession.id);
+
+          // Warn before interleaving writes with another process on the same session
+          const lockStatus = await api.getSessionLock(effectiveSession.id).catch(() => null);
+          if (lockStatus?.lock && !lockStatus.held_by_us) {
+            if (!lockStatus.stale && !confirm(`会话正被进程 ${lockStatus.lock.pid} 使用，同时写入会损坏会话记录。确定要强制解锁并继续吗？`)) {
+              setIsLoading(false);
+              hasActiveSessionRef.current = false;
+              return;
+            }
+            await api.forceUnlockSession(effectiveSession.id);
+          } else if (lockStatus?.recently_modified && !confirm('会话刚刚被其他进程（如 CLI）写入，同时写入会损坏会话记录。确定要继续吗？')) {
+            setIsLoading(false);
+            hasActiveSessionRef.current = false;
+            return;
+          }
+
           try {
             await api.resumeClaudeCode(projectPath, effectiveSession.id, processedPrompt, model);
           } catch (resumeError) {
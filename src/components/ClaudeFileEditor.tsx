@@ -1,10 +1,10 @@
 import React, { useState, useEffect } from "react";
 import MDEditor from "@uiw/react-md-editor";
 import { motion } from "framer-motion";
-import { ArrowLeft, Save, Loader2 } from "lucide-react";
+import { ArrowLeft, Save, Loader2, SearchCheck, Wand2 } from "lucide-react";
 import { Button } from "@/components/ui/button";
 import { Toast, ToastContainer } from "@/components/ui/toast";
-import { api, type ClaudeMdFile } from "@/lib/api";
+import { api, type ClaudeMdFile, type ClaudeMdAnalysis, type ClaudeMdFix } from "@/lib/api";
 import { cn } from "@/lib/utils";
 
 interface ClaudeFileEditorProps {
@@ -42,8 +42,14 @@ export const ClaudeFileEditor: React.FC<ClaudeFileEditorProps> = ({
   const [saving, setSaving] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const [toast, setToast] = useState<{ message: string; type: "success" | "error" } | null>(null);
+  const [analysis, setAnalysis] = useState<ClaudeMdAnalysis | null>(null);
+  const [analyzing, setAnalyzing] = useState(false);
   
   const hasChanges = content !== originalContent;
+  // The project root is the absolute path without the relative one
+  const projectPath = file.absolute_path
+    .slice(0, file.absolute_path.length - file.relative_path.length)
+    .replace(/[\\/]$/, "");
   
   // Load the file content on mount
   useEffect(() => {
@@ -82,6 +88,32 @@ export const ClaudeFileEditor: React.FC<ClaudeFileEditorProps> = ({
     }
   };
   
+  const handleAnalyze = async (text: string = content) => {
+    try {
+      setAnalyzing(true);
+      setAnalysis(await api.analyzeClaudeMd(file.absolute_path, projectPath, text));
+    } catch (err) {
+      console.error("Failed to analyze file:", err);
+      setToast({ message: "Failed to analyze CLAUDE.md", type: "error" });
+    } finally {
+      setAnalyzing(false);
+    }
+  };
+  
+  const handleApplyFixes = async (fixes: ClaudeMdFix[]) => {
+    try {
+      const updated = await api.applyClaudeMdFixes(content, fixes);
+      setContent(updated);
+      // Line numbers have shifted, so analyze the new content again
+      await handleAnalyze(updated);
+    } catch (err) {
+      console.error("Failed to apply fixes:", err);
+      setToast({ message: "Failed to apply fixes", type: "error" });
+    }
+  };
+  
+  const fixableIssues = analysis?.issues.filter((issue) => issue.fix) ?? [];
+  
   const handleBack = () => {
     if (hasChanges) {
       const confirmLeave = window.confirm(
@@ -119,18 +151,33 @@ export const ClaudeFileEditor: React.FC<ClaudeFileEditorProps> = ({
             </div>
           </div>
           
-          <Button
-            onClick={handleSave}
-            disabled={!hasChanges || saving}
-            size="sm"
-          >
-            {saving ? (
-              <Loader2 className="mr-2 h-4 w-4 animate-spin" />
-            ) : (
-              <Save className="mr-2 h-4 w-4" />
-            )}
-            {saving ? "Saving..." : "Save"}
-          </Button>
+          <div className="flex items-center gap-2">
+            <Button
+              onClick={() => handleAnalyze()}
+              disabled={loading || analyzing}
+              size="sm"
+              variant="outline"
+            >
+              {analyzing ? (
+                <Loader2 className="mr-2 h-4 w-4 animate-spin" />
+              ) : (
+                <SearchCheck className="mr-2 h-4 w-4" />
+              )}
+              Analyze
+            </Button>
+            <Button
+              onClick={handleSave}
+              disabled={!hasChanges || saving}
+              size="sm"
+            >
+              {saving ? (
+                <Loader2 className="mr-2 h-4 w-4 animate-spin" />
+              ) : (
+                <Save className="mr-2 h-4 w-4" />
+              )}
+              {saving ? "Saving..." : "Save"}
+            </Button>
+          </div>
         </motion.div>
         
         {/* Error display */}
@@ -144,6 +191,57 @@ export const ClaudeFileEditor: React.FC<ClaudeFileEditorProps> = ({
           </motion.div>
         )}
         
+        {/* Analysis */}
+        {analysis && (
+          <motion.div
+            initial={{ opacity: 0 }}
+            animate={{ opacity: 1 }}
+            className="mx-4 mt-4 rounded-lg border border-border p-3 text-xs max-h-56 overflow-y-auto"
+          >
+            <div className="flex items-center justify-between mb-2">
+              <span className="font-medium">
+                ~{analysis.estimated_tokens} tokens · {analysis.line_count} lines ·{" "}
+                {analysis.issues.length === 0 ? "no issues found" : `${analysis.issues.length} issues`}
+              </span>
+              <div className="flex items-center gap-2">
+                {fixableIssues.length > 1 && (
+                  <Button
+                    size="sm"
+                    variant="outline"
+                    onClick={() => handleApplyFixes(fixableIssues.map((issue) => issue.fix!))}
+                  >
+                    <Wand2 className="mr-2 h-3 w-3" />
+                    Apply all fixes
+                  </Button>
+                )}
+                <Button size="sm" variant="ghost" onClick={() => setAnalysis(null)}>
+                  Close
+                </Button>
+              </div>
+            </div>
+            <ul className="space-y-2">
+              {analysis.issues.map((issue, index) => (
+                <li key={index} className="flex items-start justify-between gap-3">
+                  <div className="min-w-0">
+                    <p>
+                      {issue.message}
+                      {issue.lines.length > 0 && (
+                        <span className="text-muted-foreground"> (line {issue.lines.join(", ")})</span>
+                      )}
+                    </p>
+                    <p className="text-muted-foreground">{issue.suggestion}</p>
+                  </div>
+                  {issue.fix && (
+                    <Button size="sm" variant="outline" onClick={() => handleApplyFixes([issue.fix!])}>
+                      Apply
+                    </Button>
+                  )}
+                </li>
+              ))}
+            </ul>
+          </motion.div>
+        )}
+        
         {/* Editor */}
         <div className="flex-1 p-4 overflow-hidden">
           {loading ? (
@@ -5,6 +5,7 @@ import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
 import { Progress } from '@/components/ui/progress';
 import { Badge } from '@/components/ui/badge';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { listenOutputLines } from '@/lib/outputStream';
 import { cn } from '@/lib/utils';
 
 interface SessionMetrics {
@@ -171,7 +172,7 @@ export const SessionCostTracker: React.FC<SessionCostTrackerProps> = ({
         });
 
         // Message events to track message count
-        const messageUnlisten = await listen<any>(`claude-output:${sessionId}`, (event) => {
+        const messageUnlisten = await listenOutputLines(`claude-output:${sessionId}`, (event) => {
           try {
             const message = JSON.parse(event.payload);
             if (message.type === 'assistant' || message.type === 'user') {
@@ -10,6 +10,7 @@ import { api } from '@/lib/api';
 import { useOutputCache } from '@/lib/outputCache';
 import type { AgentRun } from '@/lib/api';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { listenOutputLines } from '@/lib/outputStream';
 import { StreamMessage } from './StreamMessage';
 import { ErrorBoundary } from './ErrorBoundary';
 import { InlineTokenCounter } from './TokenCounter';
@@ -208,7 +209,7 @@ export function SessionOutputViewer({ session, onClose, className }: SessionOutp
       unlistenRefs.current = [];
 
       // Set up live event listeners with run ID isolation
-      const outputUnlisten = await listen<string>(`agent-output:${session.id}`, (event) => {
+      const outputUnlisten = await listenOutputLines(`agent-output:${session.id}`, (event) => {
         try {
           // Store raw JSONL
           setRawJsonlOutput(prev => [...prev, event.payload]);
@@ -25,7 +25,7 @@ export const useSessionSync = () => {
       try {
         unlisten = await listen<{
           session_id: string;
-          status: 'started' | 'stopped';
+          status: 'started' | 'stopped' | 'paused' | 'resumed';
           success?: boolean;
           error?: string;
           project_path?: string;
@@ -1,5 +1,17 @@
 import { invoke } from "@tauri-apps/api/core";
 import type { HooksConfiguration } from '@/types/hooks';
+import type { HookShell } from '@/types/enhanced-hooks';
+import { CLIENT_API_VERSION } from '@/types/api-manifest';
+import type {
+  AccessibilityVerbosity,
+  BurnRate,
+  CostCapReached,
+  FileEdit,
+  SafetyCheckpoint,
+  ScheduledPrompt,
+  SpawnFailure,
+  UnseenCompletion,
+} from '@/types/events';
 
 /** Process type for tracking in ProcessRegistry */
 export type ProcessType = 
@@ -15,6 +27,8 @@ export interface ProcessInfo {
   project_path: string;
   task: string;
   model: string;
+  env?: Record<string, string>; // Resolved environment, credentials masked
+  paused_at?: string | null; // Set while suspended with pauseExecution
 }
 
 /**
@@ -29,8 +43,13 @@ export interface Project {
   sessions: string[];
   /** Unix timestamp when the project directory was created */
   created_at: number;
+  /** IDs of other directories for the same path whose sessions were merged into this project */
+  merged_from?: string[];
 }
 
+/** Who produced a session transcript */
+export type SessionKind = "interactive" | "agent";
+
 /**
  * Represents a session with its metadata
  */
@@ -49,6 +68,16 @@ export interface Session {
   first_message?: string;
   /** Timestamp of the first user message (if available) */
   message_timestamp?: string;
+  /** Resumability from the transcript, absent if its metadata could not be read */
+  resume_status?: "resumable" | "view_only";
+  /** Short title derived from the first message (if available) */
+  title?: string;
+  /** Number of message entries in the session */
+  message_count?: number;
+  /** Timestamp of the latest entry (if available) */
+  last_activity?: string;
+  /** Whether the session is the transcript of an agent run */
+  kind: SessionKind;
 }
 
 /**
@@ -84,6 +113,51 @@ export interface ClaudeMdFile {
   modified: number;
 }
 
+export type ClaudeMdIssueKind =
+  | "oversized"
+  | "duplicate"
+  | "conflict"
+  | "stale_reference"
+  | "unused_section";
+
+/**
+ * Edit that resolves a CLAUDE.md issue
+ */
+export interface ClaudeMdFix {
+  /** 1-based line numbers to delete */
+  remove_lines: number[];
+}
+
+export interface ClaudeMdIssue {
+  kind: ClaudeMdIssueKind;
+  message: string;
+  /** 1-based line numbers the issue refers to */
+  lines: number[];
+  suggestion: string;
+  /** Set when the suggestion can be applied automatically */
+  fix: ClaudeMdFix | null;
+}
+
+export interface ClaudeMdSection {
+  heading: string;
+  start_line: number;
+  end_line: number;
+  estimated_tokens: number;
+}
+
+/**
+ * Result of analyzing a CLAUDE.md file
+ */
+export interface ClaudeMdAnalysis {
+  path: string;
+  estimated_tokens: number;
+  line_count: number;
+  sections: ClaudeMdSection[];
+  issues: ClaudeMdIssue[];
+  /** Recent sessions the relevance check looked at */
+  sessions_checked: number;
+}
+
 /**
  * Represents a file or directory entry
  */
@@ -143,6 +217,21 @@ export interface GitHubAgentFile {
   sha: string;
 }
 
+/**
+ * GitHub API quota as reported by the last response
+ */
+export interface GitHubRateLimit {
+  limit: number;
+  remaining: number;
+  used: number;
+  reset_at: number; // Unix timestamp
+}
+
+export interface GitHubApiStatus {
+  authenticated: boolean;
+  rate_limit: GitHubRateLimit | null;
+}
+
 export interface AgentRun {
   id?: number;
   agent_id: number;
@@ -184,6 +273,114 @@ export interface AgentRunWithMetrics {
   output?: string; // Real-time JSONL content
 }
 
+/**
+ * The structured result of a run of an agent with an output schema
+ */
+export interface AgentRunResult {
+  run_id: number;
+  /** missing: the final response held no JSON value */
+  status: "valid" | "invalid" | "missing";
+  /** The extracted value; also kept when it does not match the schema */
+  result: unknown | null;
+  /** Where and why the value does not match the schema */
+  errors: string[];
+  extracted_at: string;
+}
+
+/**
+ * What was found in a project's manifests and lock files
+ */
+export interface ProjectStack {
+  languages: string[];
+  package_managers: string[];
+  test_frameworks: string[];
+  build_commands: string[];
+  test_commands: string[];
+  lint_commands: string[];
+  /** Formatter commands with the shell patterns of the files they format */
+  formatters: [string, string][];
+}
+
+export interface RecommendedHook {
+  template_id: string;
+  params: Record<string, string>;
+  reason: string;
+}
+
+/**
+ * Setup proposed for a project by analyzeProjectStack; may be edited before applying
+ */
+export interface BootstrapProposal {
+  project_path: string;
+  stack: ProjectStack;
+  claude_md: string;
+  /** An existing CLAUDE.md is kept unless applying with overwriteClaudeMd */
+  has_claude_md: boolean;
+  permission_preset: "development" | "safe" | "interactive";
+  permission_reason: string;
+  hooks: RecommendedHook[];
+}
+
+export interface BootstrapResult {
+  claude_md_written: boolean;
+  permission_preset: string;
+  hooks_applied: string[];
+  hook_errors: string[];
+}
+
+/**
+ * A migration of the agents database and whether it has been applied
+ */
+export interface MigrationStatus {
+  version: number;
+  name: string;
+  /** null while pending */
+  applied_at: string | null;
+  reversible: boolean;
+  /** The migration changed after it was applied */
+  modified: boolean;
+}
+
+export interface DbSchemaInfo {
+  db_path: string;
+  sqlite_version: string;
+  current_version: number;
+  latest_version: number;
+  migrations: MigrationStatus[];
+  /** Versions applied by a newer build of the app */
+  unknown_versions: number[];
+  tables: { name: string; rows: number }[];
+}
+
+export interface SchemaRollback {
+  from_version: number;
+  to_version: number;
+  /** Copy of the database taken before reverting */
+  backup_path: string;
+}
+
+/**
+ * A byte window into a running process' live output
+ */
+export interface LiveOutputRange {
+  offset: number;
+  data: string;
+  next_offset: number;
+  total_bytes: number;
+  retained_from: number; // Bytes before this offset were spilled to disk
+}
+
+/**
+ * Maps a frontend model ID to the model passed to the Claude CLI
+ */
+export interface ModelAlias {
+  alias: string;
+  target: string;
+  description?: string;
+  builtin: boolean; // Seeded by the app; deleting resets it to the default target
+  updated_at: string;
+}
+
 // Usage Dashboard types
 export interface UsageEntry {
   project: string;
@@ -199,6 +396,7 @@ export interface UsageEntry {
 export interface ModelUsage {
   model: string;
   total_cost: number;
+  total_cost_local: number;
   total_tokens: number;
   input_tokens: number;
   output_tokens: number;
@@ -210,6 +408,7 @@ export interface ModelUsage {
 export interface DailyUsage {
   date: string;
   total_cost: number;
+  total_cost_local: number;
   total_tokens: number;
   models_used: string[];
 }
@@ -218,6 +417,7 @@ export interface ProjectUsage {
   project_path: string;
   project_name: string;
   total_cost: number;
+  total_cost_local: number;
   total_tokens: number;
   session_count: number;
   last_used: string;
@@ -225,7 +425,13 @@ export interface ProjectUsage {
 
 export interface ApiBaseUrlUsage {
   api_base_url: string;
+  /**
+   * 'direct', 'router' (proxied by claude-code-router, provider unknown)
+   * or 'router_trace' (attributed from the router's logs)
+   */
+  provider_source: 'direct' | 'router' | 'router_trace';
   total_cost: number;
+  total_cost_local: number;
   total_tokens: number;
   input_tokens: number;
   output_tokens: number;
@@ -234,1901 +440,5362 @@ export interface ApiBaseUrlUsage {
   session_count: number;
 }
 
-export interface UsageStats {
-  total_cost: number;
+export interface ModelComparison {
+  model: string;
+  provider_source: 'direct' | 'router' | 'router_trace';
+  requests: number;
+  session_count: number;
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_tokens: number;
+  cache_read_tokens: number;
   total_tokens: number;
-  total_input_tokens: number;
-  total_output_tokens: number;
-  total_cache_creation_tokens: number;
-  total_cache_read_tokens: number;
-  total_sessions: number;
-  by_model: ModelUsage[];
-  by_date: DailyUsage[];
-  by_project: ProjectUsage[];
-  by_api_base_url?: ApiBaseUrlUsage[];
-}
-
-export interface UsageOverview {
   total_cost: number;
-  total_sessions: number;
-  total_tokens: number;
-  today_cost: number;
-  week_cost: number;
-  top_model?: string;
-  top_project?: string;
+  total_cost_local: number;
+  /** Cost of the same tokens on the model the CLI requested, before routing */
+  requested_cost: number;
+  cost_per_million_tokens: number;
+  /** Share of input tokens served from the prompt cache (0-1) */
+  cache_hit_rate: number;
+  avg_latency_ms: number | null;
+  median_latency_ms: number | null;
 }
 
-export interface SessionCacheTokens {
-  session_id: string;
-  total_cache_creation_tokens: number;
-  total_cache_read_tokens: number;
+export interface ModelComparisonReport {
+  start_date: string;
+  end_date: string;
+  total_cost: number;
+  total_cost_local: number;
+  router_cost: number;
+  router_requested_cost: number;
+  /** Negative when routing cost more than the requested models would have */
+  router_savings: number;
+  models: ModelComparison[];
 }
 
 /**
- * Represents a checkpoint in the session timeline
+ * Display currency for costs; `*_local` amounts are in `display_currency`
  */
-export interface Checkpoint {
-  id: string;
-  sessionId: string;
-  projectId: string;
-  messageIndex: number;
-  timestamp: string;
-  description?: string;
-  parentCheckpointId?: string;
-  metadata: CheckpointMetadata;
+export interface CurrencyConfig {
+  /** ISO 4217 code */
+  currency: string;
+  auto_update: boolean;
+  /** Units of `currency` per USD, used when auto_update is off */
+  manual_rate?: number | null;
+  last_known_rate?: number | null;
+  rate_updated_at?: string | null;
 }
 
-/**
- * Metadata associated with a checkpoint
- */
-export interface CheckpointMetadata {
-  totalTokens: number;
-  modelUsed: string;
-  userPrompt: string;
-  fileChanges: number;
-  snapshotSize: number;
+export interface CurrencyStatus {
+  config: CurrencyConfig;
+  display_currency: string;
+  rate: number;
+  stale: boolean;
 }
 
-/**
- * Represents a file snapshot at a checkpoint
- */
-export interface FileSnapshot {
-  checkpointId: string;
-  filePath: string;
-  content: string;
-  hash: string;
-  isDeleted: boolean;
-  permissions?: number;
-  size: number;
+export interface QueuedPrompt {
+  id: string;
+  session_id: string;
+  project_path: string;
+  prompt: string;
+  model: string;
+  provider_id?: string | null;
+  queued_at: string;
 }
 
-/**
- * Represents a node in the timeline tree
- */
-export interface TimelineNode {
-  checkpoint: Checkpoint;
-  children: TimelineNode[];
-  fileSnapshotIds: string[];
+export type SuggestionSource =
+  | "failing_tests"
+  | "uncommitted_changes"
+  | "todo"
+  | "readme_task"
+  | "recent_commit";
+
+export interface PromptSuggestion {
+  prompt: string;
+  source: SuggestionSource;
+  /** What the suggestion is based on, e.g. `src/main.rs:42` or a commit hash */
+  provenance: string;
+  /** Rewritten by the model rather than filled from a template */
+  refined: boolean;
 }
 
 /**
- * The complete timeline for a session
+ * A bundle of hooks in the settings.json `hooks` format
  */
-export interface SessionTimeline {
-  sessionId: string;
-  rootNode?: TimelineNode;
-  currentCheckpointId?: string;
-  autoCheckpointEnabled: boolean;
-  checkpointStrategy: CheckpointStrategy;
-  totalCheckpoints: number;
+export interface HookPack {
+  id: string;
+  name: string;
+  description: string;
+  source: "builtin" | "user";
+  hooks: HooksConfiguration;
 }
 
 /**
- * Strategy for automatic checkpoint creation
+ * An existing matcher group that already runs other hooks
  */
-export type CheckpointStrategy = 'manual' | 'per_prompt' | 'per_tool_use' | 'smart';
+export interface HookPackConflict {
+  event: string;
+  matcher: string;
+  existing_commands: string[];
+  pack_commands: string[];
+}
 
-/**
- * Result of a checkpoint operation
- */
-export interface CheckpointResult {
-  checkpoint: Checkpoint;
-  filesProcessed: number;
-  warnings: string[];
+export interface HookPackPreview {
+  pack: HookPack;
+  /** Hooks the install would add */
+  new_hooks: number;
+  /** Pack hooks already present in the scope */
+  already_installed: number;
+  conflicts: HookPackConflict[];
 }
 
-/**
- * Diff between two checkpoints
- */
-export interface CheckpointDiff {
-  fromCheckpointId: string;
-  toCheckpointId: string;
-  modifiedFiles: FileDiff[];
-  addedFiles: string[];
-  deletedFiles: string[];
-  tokenDelta: number;
+/** "abort" installs nothing when a matcher group already runs other hooks */
+export type HookPackConflictStrategy = "abort" | "append" | "replace";
+
+export interface HookPackInstallResult {
+  pack_id: string;
+  scope: string;
+  added: number;
+  already_installed: number;
+  conflicts_resolved: number;
 }
 
 /**
- * Diff for a single file
+ * A value filled into the `{{name}}` placeholders of a hook template
  */
-export interface FileDiff {
-  path: string;
-  additions: number;
-  deletions: number;
-  diffContent?: string;
+export interface HookTemplateParameter {
+  name: string;
+  description: string;
+  /** Parameters without a default must be given when applying the template */
+  default: string | null;
 }
 
 /**
- * Provider configuration for API switching
+ * Hooks in the settings.json `hooks` format with `{{name}}` placeholders
  */
-export interface ProviderConfig {
+export interface HookTemplate {
   id: string;
   name: string;
   description: string;
-  base_url: string;
-  auth_token?: string;
-  api_key?: string;
-  api_key_helper?: string;
-  model?: string;
-  enable_auto_api_key_helper?: boolean;
+  source: "builtin" | "user";
+  default_scope: "user" | "project" | "local";
+  parameters: HookTemplateParameter[];
+  hooks: HooksConfiguration;
 }
 
 /**
- * Current provider configuration from environment variables
+ * Named environment variables a single session or agent run can be started with
  */
-export interface CurrentProviderConfig {
-  anthropic_base_url?: string;
-  anthropic_auth_token?: string;
-  anthropic_api_key?: string;
-  anthropic_api_key_helper?: string;
-  anthropic_model?: string;
+export interface EnvProfile {
+  name: string;
+  description: string | null;
+  vars: Record<string, string>;
+  updated_at: string;
 }
 
 /**
- * Represents an MCP server configuration
+ * A remembered value (with a key) or free-form note (without) of one project
  */
-export interface MCPServer {
-  /** Server name/identifier */
-  name: string;
-  /** Transport type: "stdio" or "sse" */
-  transport: string;
-  /** Command to execute (for stdio) */
-  command?: string;
-  /** Command arguments (for stdio) */
-  args: string[];
-  /** Environment variables */
-  env: Record<string, string>;
-  /** URL endpoint (for SSE) */
-  url?: string;
-  /** Configuration scope: "local", "project", or "user" */
-  scope: string;
-  /** Whether the server is currently active */
-  is_active: boolean;
-  /** Server status */
-  status: ServerStatus;
+export interface MemoryEntry {
+  id: number;
+  project_path: string;
+  key: string | null;
+  content: string;
+  created_at: string;
+  updated_at: string;
 }
 
 /**
- * Server status information
+ * A passage of a project document that matched a question
  */
-export interface ServerStatus {
-  /** Whether the server is running */
-  running: boolean;
-  /** Last error message if any */
-  error?: string;
-  /** Last checked timestamp */
-  last_checked?: number;
+export interface KnowledgeChunk {
+  /** Document path relative to the project root */
+  source: string;
+  heading: string | null;
+  content: string;
+  /** Cosine similarity to the question, 0-1 */
+  score: number;
 }
 
-/**
- * MCP configuration for project scope (.mcp.json)
- */
-export interface MCPProjectConfig {
-  mcpServers: Record<string, MCPServerConfig>;
+export interface KnowledgeQueryResult {
+  chunks: KnowledgeChunk[];
+  /** The chunks formatted to be prepended to a prompt; empty when nothing matched */
+  context: string;
 }
 
-/**
- * Individual server configuration in .mcp.json
- */
-export interface MCPServerConfig {
-  command: string;
-  args: string[];
-  env: Record<string, string>;
+export interface KnowledgeIngestStats {
+  files_indexed: number;
+  files_unchanged: number;
+  files_removed: number;
+  chunks: number;
 }
 
-/**
- * Represents a custom slash command
- */
-export interface SlashCommand {
-  /** Unique identifier for the command */
-  id: string;
-  /** Command name (without prefix) */
+export type ProfileNotify = "always" | "on_failure" | "never";
+
+export interface ExecutionProfileConfig {
+  /** "development", "safe" or "interactive"; null keeps the configured permissions */
+  permission_preset?: string | null;
+  /** Passed to `--max-turns` */
+  max_turns?: number | null;
+  /** The run is stopped once its estimated cost exceeds this many USD */
+  max_budget_usd?: number | null;
+  notify: ProfileNotify;
+  webhook_url?: string | null;
+}
+
+export interface ExecutionProfile {
+  id: number;
   name: string;
-  /** Full command with prefix (e.g., "/project:optimize") */
-  full_command: string;
-  /** Command scope: "project" or "user" */
-  scope: string;
-  /** Optional namespace (e.g., "frontend" in "/project:frontend:component") */
-  namespace?: string;
-  /** Path to the markdown file */
-  file_path: string;
-  /** Command content (markdown body) */
-  content: string;
-  /** Optional description from frontmatter */
-  description?: string;
-  /** Allowed tools from frontmatter */
-  allowed_tools: string[];
-  /** Whether the command has bash commands (!) */
-  has_bash_commands: boolean;
-  /** Whether the command has file references (@) */
-  has_file_references: boolean;
-  /** Whether the command uses $ARGUMENTS placeholder */
-  accepts_arguments: boolean;
+  description: string;
+  config: ExecutionProfileConfig;
+  /** Shipped with the app; can be edited but not deleted */
+  builtin: boolean;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface ProfiledRun {
+  project_path: string;
+  prompt: string;
+  model: string;
+  provider_id?: string | null;
+}
+
+/** "auto" probes each volume */
+export type CaseSensitivity = "auto" | "sensitive" | "insensitive";
+
+export interface PathCaseStatus {
+  mode: CaseSensitivity;
+  /** Whether the volume holding ~/.claude is treated as case-sensitive */
+  claude_dir_case_sensitive: boolean;
+  /** Assumed for volumes that can't be probed */
+  platform_default_case_sensitive: boolean;
+}
+
+export interface ProjectRepairReport {
+  dry_run: boolean;
+  /** Project paths that case-insensitive matching treated as one project */
+  merged_groups: string[][];
+  memory_entries_moved: number;
+  /** Memory entries shared by merged projects, copied to each of them */
+  memory_entries_copied: number;
+  knowledge_sources_moved: number;
+  knowledge_sources_dropped: number;
+  /** Projects whose docs have to be ingested again */
+  reingest_projects: string[];
+  trust_entries_rekeyed: number;
+}
+
+/** Projects listed on their own even when they share a path with others */
+export interface MergeExclusions {
+  /** Project paths, e.g. the root of a worktree */
+  paths: string[];
+  /** Project directory IDs split off with `splitMergedProject` */
+  project_ids: string[];
+}
+
+export interface ImportedSession {
+  session_id: string;
+  /** Directory name under ~/.claude/projects */
+  project_id: string;
+  project_path: string;
+  /** Where the transcript was copied to */
+  jsonl_path: string;
+  message_count: number;
+  /** Usage rows registered from the transcript's assistant messages */
+  usage_rows: number;
+  /** Lines that were not valid JSON and were left out */
+  skipped_lines: number;
 }
 
+export interface BackupSummary {
+  path: string;
+  files: number;
+  /** Size of the backed up files */
+  bytes: number;
+  /** Size of the backup file */
+  archive_bytes: number;
+  encrypted: boolean;
+  includes_secrets: boolean;
+  created_at: string;
+}
+
+export interface RestoreSummary {
+  /** Version of the app that created the backup */
+  app_version: string;
+  created_at: string;
+  files_restored: number;
+  database_restored: boolean;
+  secrets_restored: boolean;
+  /** Settings cached at startup keep their old values until the app restarts */
+  restart_recommended: boolean;
+}
+
+/** What a capability token allows */
+export type CapabilityScope = "analytics:read" | "projects:read" | "sessions:execute" | "metrics:read";
+
+export interface CapabilityToken {
+  id: number;
+  name: string;
+  scopes: CapabilityScope[];
+  created_at: string;
+  /** RFC 3339; null for tokens that don't expire */
+  expires_at?: string | null;
+  revoked_at?: string | null;
+  last_used_at?: string | null;
+}
+
+export interface CreatedCapabilityToken {
+  token: CapabilityToken;
+  /** Shown once; only its hash is stored */
+  secret: string;
+}
+
+export interface RemoteInvocationAudit {
+  id: number;
+  token_id?: number | null;
+  token_name?: string | null;
+  /** Where the invocation came from, e.g. "http" */
+  source: string;
+  command: string;
+  allowed: boolean;
+  /** Why the invocation was refused */
+  reason?: string | null;
+  created_at: string;
+}
+
+export interface LocalApiStatus {
+  /** `metrics_enabled` serves Prometheus metrics at `/metrics` */
+  config: { enabled: boolean; port: number; metrics_enabled: boolean };
+  running: boolean;
+  /** Base URL while running */
+  url?: string | null;
+}
+
+/** Where a local service is bound; `conflict` means another app holds the port */
+export interface ServicePort {
+  service: "router" | "local_api";
+  host: string;
+  port: number;
+  state: "listening" | "free" | "conflict" | "unreachable";
+  /** Port the service used before it was moved off a conflict */
+  moved_from?: number | null;
+}
+
+/** Options of executeClaudeCode, continueClaudeCode and resumeClaudeCode */
+export interface RunStartOptions {
+  /** Reuse the same key when retrying; a retry within the dedupe window gets the original run */
+  idempotencyKey?: string;
+  /** Spawn a new run even if the key was seen recently */
+  forceNewRun?: boolean;
+  /** Checkpoint and stop the run once its estimated cost exceeds this many dollars */
+  maxCostUsd?: number;
+}
+
+export interface ExecuteOptions extends RunStartOptions {
+  /** Run the new session in its own git worktree; merge it back with mergeSessionWorktree */
+  useWorktree?: boolean;
+  /** Name of an environment profile whose variables are set on this session only */
+  envProfile?: string;
+  /** Expand a prompt template into the prompt; a non-empty prompt is appended to it */
+  promptTemplate?: PromptTemplateUse;
+}
 
 /**
- * Result of saving clipboard image
+ * A reusable prompt with {{name}} placeholders, e.g. "Fix {{error}} in {{file}}"
  */
-export interface SavedImageResult {
-  success: boolean;
-  file_path?: string;
-  error?: string;
+export interface PromptTemplate {
+  id: number;
+  name: string;
+  description?: string | null;
+  content: string;
+  /** Placeholder names in order of first use */
+  variables: string[];
+  created_at: string;
+  updated_at: string;
+}
+
+export interface PromptTemplateUse {
+  template_id: number;
+  variables: Record<string, string>;
+}
+
+/** The run started (or found again) by an execution command, returned as soon as the CLI is spawned */
+export interface RunHandle {
+  /** Pass to getRunStatus; also the run_id of claude-session-state events */
+  run_id: number;
+  idempotency_key?: string | null;
+  operation: "execute" | "continue" | "resume";
+  project_path: string;
+  pid: number;
+  /** The resumed session's id, or an id made up at spawn until the CLI reports its own */
+  provisional_session_id: string;
+  /** Known once the CLI reports its session */
+  session_id?: string | null;
+  started_at: string;
+  /** An earlier request with the same key started this run */
+  deduplicated: boolean;
+}
+
+export type RunState =
+  | "starting"
+  | "running"
+  | "completed"
+  | "failed"
+  | "cancelled"
+  | "cost_cap_reached";
+
+export interface RunStatus {
+  run_id: number;
+  pid: number;
+  project_path: string;
+  model: string;
+  provisional_session_id: string;
+  /** The id the CLI reported; events are scoped to it */
+  session_id?: string | null;
+  state: RunState;
+  started_at: string;
+  finished_at?: string | null;
+  cancel_requested: boolean;
+  /** Set when the run was stopped at its cost cap */
+  cost_cap?: CostCapReached | null;
+}
+
+/** A piece of work spanning several sessions and agent runs */
+export interface Task {
+  id: number;
+  name: string;
+  project_path: string;
+  description?: string | null;
+  created_at: string;
+  member_count: number;
+}
+
+export type TaskMemberKind = "session" | "agent_run";
+
+export interface TaskCheckpoint {
+  id: string;
+  timestamp: string;
+  description?: string | null;
+  file_changes: number;
+}
+
+/** One session of a task, either assigned directly or through an agent run */
+export interface TaskSessionOverview {
+  session_id: string;
+  agent_run_id?: number | null;
+  agent_name?: string | null;
+  agent_run_status?: string | null;
+  title?: string | null;
+  message_count: number;
+  first_activity?: string | null;
+  last_activity?: string | null;
+  transcript_found: boolean;
+  input_tokens: number;
+  output_tokens: number;
+  cost_usd: number;
+  files_modified: string[];
+  checkpoints: TaskCheckpoint[];
+}
+
+export interface TaskOverview {
+  task: Task;
+  sessions: TaskSessionOverview[];
+  input_tokens: number;
+  output_tokens: number;
+  cost_usd: number;
+  files_modified: string[];
+  checkpoint_count: number;
+  first_activity?: string | null;
+  last_activity?: string | null;
 }
 
 /**
- * Result of adding a server
+ * A crash report written by the panic hook; contains no prompt content
  */
-export interface AddServerResult {
-  success: boolean;
+export interface CrashReportSummary {
+  id: string;
+  created_at: string;
+  /** "panic" or "fatal_error" */
+  kind: string;
   message: string;
-  server_name?: string;
+  location?: string | null;
+  app_version: string;
 }
 
 /**
- * Translation configuration interface
+ * Optional subsystems; turning one off skips its work and disables its commands
  */
-export interface TranslationConfig {
-  enabled: boolean;
-  api_base_url: string;
-  api_key: string;
-  model: string;
-  timeout_seconds: number;
-  cache_ttl_seconds: number;
+export interface StartupConfig {
+  translator: boolean;
+  router: boolean;
+  subagents: boolean;
 }
 
-/**
- * Translation cache statistics
- */
-export interface TranslationCacheStats {
-  total_entries: number;
-  expired_entries: number;
-  active_entries: number;
+export interface StartupTiming {
+  name: string;
+  mode: "blocking" | "background";
+  /** Milliseconds after process start */
+  started_at_ms: number;
+  duration_ms?: number | null;
+}
+
+export interface StartupTimings {
+  setup_finished_at_ms?: number | null;
+  steps: StartupTiming[];
+}
+
+export interface PluginEntry {
+  name: string;
+  description: string;
+}
+
+export interface PluginPermissions {
+  /** Receive the project path; WASM plugins can also read and write it */
+  project_access: boolean;
+  /** Open network connections (enforced for WASM plugins only) */
+  network: boolean;
+  env: string[];
 }
 
 /**
- * Auto-compact configuration
+ * Contents of a plugin's plugin.json
  */
-export interface AutoCompactConfig {
-  /** Enable automatic compaction */
+export interface PluginManifest {
+  id: string;
+  name: string;
+  version: string;
+  description: string;
+  abi_version: number;
+  runtime: "native" | "wasm";
+  entry: string;
+  commands: PluginEntry[];
+  hook_events: string[];
+  analyzers: PluginEntry[];
+  permissions: PluginPermissions;
+  timeout_secs?: number | null;
+}
+
+export interface PluginInfo {
+  directory: string;
+  manifest?: PluginManifest | null;
+  load_error?: string | null;
   enabled: boolean;
-  /** Maximum context tokens before triggering compaction */
-  max_context_tokens: number;
-  /** Threshold percentage to trigger compaction (0.0-1.0) */
-  compaction_threshold: number;
-  /** Minimum time between compactions in seconds */
-  min_compaction_interval: number;
-  /** Strategy for compaction */
-  compaction_strategy: CompactionStrategy;
-  /** Whether to preserve recent messages */
-  preserve_recent_messages: boolean;
-  /** Number of recent messages to preserve */
-  preserve_message_count: number;
-  /** Custom compaction instructions */
-  custom_instructions?: string;
+  consecutive_failures: number;
+  last_error?: string | null;
+  /** Disabled after failing repeatedly */
+  auto_disabled: boolean;
+}
+
+export interface PluginCallResult {
+  plugin_id: string;
+  name: string;
+  output: any;
+  duration_ms: number;
 }
 
 /**
- * Compaction strategies
+ * A git worktree a session runs in, so parallel sessions don't clobber each other
  */
-export type CompactionStrategy =
-  | 'Smart'
-  | 'Aggressive'
-  | 'Conservative'
-  | { Custom: string };
+export interface SessionWorktree {
+  id: number;
+  /** Root of the repository */
+  project_path: string;
+  worktree_path: string;
+  /** Where sessions run inside the worktree */
+  session_path: string;
+  branch: string;
+  base_branch: string;
+  base_commit: string;
+  run_id?: number | null;
+  session_id?: string | null;
+  status: "active" | "merged" | "removed";
+  created_at: string;
+  merged_at?: string | null;
+  changed_files?: number | null;
+  commits_ahead?: number | null;
+}
+
+export interface WorktreeMergeResult {
+  worktree: SessionWorktree;
+  committed_changes: boolean;
+  merged_commits: number;
+  merge_commit?: string | null;
+  removed: boolean;
+}
+
+export type StaleStateKind = "session_lock" | "ide_lock" | "orphaned_lock" | "empty_session";
+
+export interface StaleStateEntry {
+  kind: StaleStateKind;
+  path: string;
+  /** Why the entry counts as stale */
+  reason: string;
+  /** Whether it was deleted; always false on a dry run */
+  removed: boolean;
+  error: string | null;
+}
+
+export interface StaleStateReport {
+  dry_run: boolean;
+  entries: StaleStateEntry[];
+  removed: number;
+}
 
 /**
- * Session context information
+ * A message left out of the context when its session is resumed
  */
-export interface SessionContext {
-  session_id: string;
-  project_path: string;
-  current_tokens: number;
-  message_count: number;
-  last_compaction?: string; // ISO timestamp
-  compaction_count: number;
-  model: string;
-  status: SessionStatus;
+export interface ContextExclusion {
+  /** Position as returned by loadSessionHistory; null if no longer in the transcript */
+  index: number | null;
+  entry_uuid: string;
+  excluded_at: string;
 }
 
 /**
- * Session status
+ * Response language of a project and one of its sessions
  */
-export type SessionStatus =
-  | 'Active'
-  | 'Idle'
-  | 'Compacting'
-  | { CompactionFailed: string };
+export interface ResponseLanguageSetting {
+  project_language: string | null;
+  /** The session sets its own language instead of the project's */
+  session_overridden: boolean;
+  /** The session's own language; null with an override means no preference */
+  session_language: string | null;
+  /** Language runs of the session are instructed to answer in */
+  effective: string | null;
+}
+
+export interface ResponseLanguageSuggestion {
+  /** Language detected in the prompt */
+  detected: string;
+  /** Language to offer as the setting; null when one is already set or the prompt is English */
+  suggested: string | null;
+}
 
 /**
- * Auto-compact status information
+ * A recurring agent run, by cron expression (local time) or interval
  */
-export interface AutoCompactStatus {
+export interface AgentSchedule {
+  id: number;
+  agent_id: number;
+  project_path: string;
+  task: string;
+  model: string | null;
+  cron: string | null;
+  interval_secs: number | null;
   enabled: boolean;
-  is_monitoring: boolean;
-  sessions_count: number;
-  total_compactions: number;
-  max_context_tokens: number;
-  compaction_threshold: number;
+  next_run_at: string | null;
+  last_run_at: string | null;
+  last_run_id: number | null;
+  last_error: string | null;
+  created_at: string;
 }
 
 /**
- * Import result for multiple servers
+ * Settings of a schedule; exactly one of `cron` and `interval_secs` must be set
  */
-export interface ImportResult {
-  imported_count: number;
-  failed_count: number;
-  servers: ImportServerResult[];
+export interface AgentScheduleInput {
+  agent_id: number;
+  project_path: string;
+  task: string;
+  model?: string | null;
+  cron?: string | null;
+  interval_secs?: number | null;
+  enabled?: boolean;
+}
+
+export interface AgentScheduleRun {
+  schedule_id: number;
+  run_id: number | null;
+  started_at: string;
+  error: string | null;
+}
+
+/** "inhibit" keeps the machine awake, "pause_and_checkpoint" stops sessions, "allow" lets it sleep */
+export type BatteryAction = "inhibit" | "pause_and_checkpoint" | "allow";
+
+export interface PowerPolicy {
+  /** Block system sleep while any session or agent run is active */
+  prevent_sleep: boolean;
+  on_battery: BatteryAction;
 }
 
+export interface PowerStatus {
+  policy: PowerPolicy;
+  active_runs: number;
+  /** A sleep inhibitor is currently held */
+  inhibiting: boolean;
+  /** null when the power source could not be determined */
+  on_battery: boolean | null;
+}
+
+/** One page of a session transcript, as returned by loadSessionHistoryPage */
 /**
- * Result for individual server import
+ * Trims tool chatter out of a session's history. Filtered messages carry their
+ * `historyIndex` in the transcript; load that index with loadSessionHistoryPage and
+ * no filter for the full message.
  */
-export interface ImportServerResult {
-  name: string;
-  success: boolean;
-  error?: string;
+export interface HistoryFilter {
+  /** Replace tool results larger than this many KB with their size */
+  max_tool_result_kb?: number;
+  /** Merge consecutive Bash calls into one `collapsed_tool_calls` message */
+  collapse_bash?: boolean;
+  /** Keep only the text of user and assistant messages */
+  text_only?: boolean;
+}
+
+export interface SessionHistoryPage {
+  messages: any[];
+  /** Index of the first message; indexes match loadSessionHistory */
+  offset: number;
+  total: number;
+  has_more_before: boolean;
+  has_more_after: boolean;
+  /** Pass to getSessionHistoryDelta for the messages after this page */
+  cursor: string;
 }
 
 /**
- * API client for interacting with the Rust backend
+ * Messages appended to a transcript since a cursor
  */
-export const api = {
-  /**
-   * Lists all projects in the ~/.claude/projects directory
-   * @returns Promise resolving to an array of projects
-   */
-  async listProjects(): Promise<Project[]> {
-    try {
-      return await invoke<Project[]>("list_projects");
-    } catch (error) {
-      console.error("Failed to list projects:", error);
-      throw error;
-    }
-  },
+export interface SessionHistoryDelta {
+  messages: any[];
+  /** Index of the first message in `messages` */
+  offset: number;
+  total: number;
+  /** Where the next delta starts */
+  cursor: string;
+  /** The transcript was rewritten: the delta starts over and replaces the viewer's copy */
+  reset: boolean;
+  /** More messages are waiting; ask again with `cursor` */
+  has_more: boolean;
+}
 
-  /**
-   * Retrieves sessions for a specific project
-   * @param projectId - The ID of the project to retrieve sessions for
-   * @returns Promise resolving to an array of sessions
-   */
-  async getProjectSessions(projectId: string): Promise<Session[]> {
-    try {
-      return await invoke<Session[]>('get_project_sessions', { projectId });
-    } catch (error) {
-      console.error("Failed to get project sessions:", error);
-      throw error;
-    }
-  },
+export interface ClaudePermissionConfig {
+  allowed_tools: string[];
+  disallowed_tools: string[];
+  permission_mode: "Interactive" | "AcceptEdits" | "ReadOnly";
+  auto_approve_edits: boolean;
+  enable_dangerous_skip: boolean;
+  dangerous_skip_expires_at?: string | null;
+  dangerous_skip_projects?: string[];
+}
 
-  /**
+export interface ClaudeExecutionConfig {
+  output_format: "StreamJson" | "Json" | "Text";
+  timeout_seconds: number | null;
+  max_tokens: number | null;
+  verbose: boolean;
+  permissions: ClaudePermissionConfig;
+}
+
+/** Overrides stored in `<project>/.claude/execution_config.json`; unset fields keep the user's value */
+export interface ProjectExecutionConfig {
+  output_format?: ClaudeExecutionConfig["output_format"];
+  timeout_seconds?: number;
+  max_tokens?: number;
+  verbose?: boolean;
+  /** Replaces the user's permissions as a whole */
+  permissions?: ClaudePermissionConfig;
+}
+
+export interface ProjectExecutionConfigState {
+  project_path: string;
+  config_file: string;
+  /** null when the project has no config of its own */
+  project: ProjectExecutionConfig | null;
+  /** The user's config with the project's overrides applied */
+  effective: ClaudeExecutionConfig;
+}
+
+/** Faults to simulate; only builds with the fault-injection feature accept one */
+export interface FaultInjectionConfig {
+  /** Answer Claude runs' API requests with 429 (rate limited) or 529 (overloaded) */
+  provider_status?: 429 | 529 | null;
+  /** Claude runs find no API endpoint, as when the ccr router has crashed */
+  router_down?: boolean;
+  /** Taking a database connection fails as if the database stayed locked */
+  db_locked?: boolean;
+  db_delay_ms?: number;
+  /** Delay added to each checkpoint blob and transcript read or write */
+  disk_delay_ms?: number;
+  /** Inject on every n-th opportunity of each kind; 0 and 1 mean every time */
+  every?: number;
+  /** Stop injecting a kind after this many faults */
+  limit?: number | null;
+}
+
+export interface FaultInjectionStatus {
+  /** This build was made with the fault-injection feature */
+  available: boolean;
+  /** null while no faults are injected */
+  config: FaultInjectionConfig | null;
+  injected: { provider: number; router: number; db: number; disk: number };
+}
+
+/** A workbench file in a project that git may pick up */
+export interface GitignoreFinding {
+  /** Relative to the project root */
+  path: string;
+  /** The file only makes sense on this machine and belongs in .gitignore */
+  local_only: boolean;
+  ignored: boolean;
+  /** Already committed; ignoring it no longer keeps it out of the repository */
+  tracked: boolean;
+  /** JSON key paths or kinds of keys that look like credentials, never the values */
+  secrets: string[];
+}
+
+export interface GitignoreAudit {
+  project_path: string;
+  /** Nothing is at risk outside a git repository */
+  is_git_repo: boolean;
+  /** Local-only files are added to the project's .gitignore when written */
+  auto_ignore: boolean;
+  findings: GitignoreFinding[];
+}
+
+/** How a provider's keys take turns serving executions */
+export type RotationStrategy = "round_robin" | "failover_on_429" | "weighted";
+
+/** One rotated key of a provider; the value itself is never returned */
+export interface ProviderKeyInfo {
+  alias: string;
+  weight: number;
+  /** Last characters of the key, to tell keys apart */
+  masked: string;
+  last_used_at: string | null;
+  rate_limit_count: number;
+  /** The key is passed over until then after hitting a rate limit */
+  cooldown_until: string | null;
+}
+
+export interface ProviderKeyRotation {
+  provider_id: string;
+  strategy: RotationStrategy;
+  /** In the order they were added; with no keys the preset's own key is used */
+  keys: ProviderKeyInfo[];
+}
+
+/** What importWorkbenchSettings does with an item that already exists */
+export type ConflictResolution = "skip" | "overwrite" | "rename";
+
+export interface SettingsExportSummary {
+  path: string;
+  exported_at: string;
+  includes_secrets: boolean;
+  /** Items exported per section */
+  sections: Record<string, number>;
+}
+
+export interface SectionImport {
+  imported: number;
+  replaced: number;
+  renamed: number;
+  skipped: number;
+}
+
+export interface SettingsImportSummary {
+  /** Version of the app that created the file */
+  app_version: string;
+  exported_at: string;
+  sections: Record<string, SectionImport>;
+  /** Items that could not be imported; the rest of the file still is */
+  warnings: string[];
+}
+
+export interface PromptContextPart {
+  source: "prompt" | "overhead" | "claude_md" | "attachment";
+  /** File the part was read from */
+  path?: string | null;
+  tokens: number;
+}
+
+export interface PromptCostEstimate {
+  /** The model the prompt would run on, after alias resolution */
+  model: string;
+  input_tokens: number;
+  parts: PromptContextPart[];
+  /** Memory files and overhead read from the prompt cache */
+  min_cost_usd: number;
+  /** Memory files and overhead written to the prompt cache */
+  max_cost_usd: number;
+  /** False when there is no price for the model; the costs are then zero */
+  priced: boolean;
+}
+
+export interface SessionTemplateConfig {
+  model: string;
+  /** "development", "safe" or "interactive"; null keeps the configured permissions */
+  permission_preset?: string | null;
+  /** Directories passed to `--add-dir` */
+  extra_dirs: string[];
+  /** MCP servers added for the session, in `.mcp.json` format */
+  mcp_servers: Record<string, { command: string; args: string[]; env: Record<string, string> }>;
+  /** CLAUDE.md-style instructions appended to the system prompt */
+  claude_md?: string | null;
+  initial_prompt: string;
+  provider_id?: string | null;
+}
+
+export type ExportFormat = "html" | "markdown";
+
+/** "auto" bundles both variants and follows the viewer's color scheme */
+export type ExportTheme = "light" | "dark" | "auto";
+
+export interface ExportPreview {
+  format: ExportFormat;
+  theme: ExportTheme;
+  /** Rendered document covering the first messages only */
+  content: string;
+  total_messages: number;
+  truncated: boolean;
+}
+
+export interface SessionTemplate {
+  id: number;
+  name: string;
+  config: SessionTemplateConfig;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface SessionLockInfo {
+  session_id: string;
+  /** Process writing the session */
+  pid: number;
+  /** Workbench process that took the lock */
+  owner_pid: number;
+  acquired_at: string;
+}
+
+export interface SessionLockStatus {
+  session_id: string;
+  lock?: SessionLockInfo | null;
+  held_by_us: boolean;
+  /** The locking process is gone */
+  stale: boolean;
+  /** Written very recently without a lock, e.g. by the CLI */
+  recently_modified: boolean;
+}
+
+export interface DangerousSkipAuditEntry {
+  id: number;
+  /** "execute", "continue", "resume" or "agent" */
+  source: string;
+  project_path: string;
+  session_id?: string | null;
+  expires_at?: string | null;
+  created_at: string;
+}
+
+export interface SafetyStatus {
+  /** Dangerous-skip permission mode is on and not yet expired */
+  dangerous_skip_active: boolean;
+  dangerous_skip_expires_at?: string | null;
+  dangerous_skip_remaining_minutes?: number | null;
+  dangerous_skip_projects: string[];
+  hooks_disabled: boolean;
+  recent_audit: DangerousSkipAuditEntry[];
+}
+
+export interface TeamUsageRecord {
+  /** Stable id for deduplicating merged exports */
+  record_id: string;
+  operator_id: string;
+  session_id: string;
+  project: string;
+  date: string;
+  timestamp: string;
+  model: string;
+  api_base_url: string;
+  provider_source: string;
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_tokens: number;
+  cache_read_tokens: number;
+  cost_usd: number;
+}
+
+export interface TeamUsageExport {
+  schema_version: number;
+  operator_id: string;
+  exported_at: string;
+  start_date: string;
+  end_date: string;
+  records: TeamUsageRecord[];
+  output_path?: string | null;
+}
+
+export interface UsageStats {
+  total_cost: number;
+  total_cost_local: number;
+  total_tokens: number;
+  total_input_tokens: number;
+  total_output_tokens: number;
+  total_cache_creation_tokens: number;
+  total_cache_read_tokens: number;
+  total_sessions: number;
+  by_model: ModelUsage[];
+  by_date: DailyUsage[];
+  by_project: ProjectUsage[];
+  by_api_base_url?: ApiBaseUrlUsage[];
+}
+
+export interface UsageOverview {
+  total_cost: number;
+  total_cost_local: number;
+  total_sessions: number;
+  total_tokens: number;
+  today_cost: number;
+  today_cost_local: number;
+  week_cost: number;
+  week_cost_local: number;
+  top_model?: string;
+  top_project?: string;
+}
+
+export interface SessionCacheTokens {
+  session_id: string;
+  total_cache_creation_tokens: number;
+  total_cache_read_tokens: number;
+}
+
+/**
+ * Represents a checkpoint in the session timeline
+ */
+export interface Checkpoint {
+  id: string;
+  sessionId: string;
+  projectId: string;
+  messageIndex: number;
+  timestamp: string;
+  description?: string;
+  parentCheckpointId?: string;
+  metadata: CheckpointMetadata;
+}
+
+/**
+ * Metadata associated with a checkpoint
+ */
+export interface CheckpointMetadata {
+  totalTokens: number;
+  modelUsed: string;
+  userPrompt: string;
+  fileChanges: number;
+  snapshotSize: number;
+}
+
+/**
+ * Represents a file snapshot at a checkpoint
+ */
+export interface FileSnapshot {
+  checkpointId: string;
+  filePath: string;
+  content: string;
+  hash: string;
+  isDeleted: boolean;
+  permissions?: number;
+  size: number;
+}
+
+/**
+ * Represents a node in the timeline tree
+ */
+export interface TimelineNode {
+  checkpoint: Checkpoint;
+  children: TimelineNode[];
+  fileSnapshotIds: string[];
+}
+
+/**
+ * The complete timeline for a session
+ */
+export interface SessionTimeline {
+  sessionId: string;
+  rootNode?: TimelineNode;
+  currentCheckpointId?: string;
+  autoCheckpointEnabled: boolean;
+  checkpointStrategy: CheckpointStrategy;
+  totalCheckpoints: number;
+}
+
+/**
+ * Strategy for automatic checkpoint creation
+ */
+export type CheckpointStrategy = 'manual' | 'per_prompt' | 'per_tool_use' | 'smart';
+
+/**
+ * Result of a checkpoint operation
+ */
+export interface CheckpointResult {
+  checkpoint: Checkpoint;
+  filesProcessed: number;
+  warnings: string[];
+  /** Files a restore left untouched because they had uncheckpointed changes */
+  conflicts?: RestoreConflict[];
+}
+
+/**
+ * A file whose current content differs from both its last checkpointed version
+ * (base) and the restored checkpoint; the inputs of a three-way merge
+ */
+export interface RestoreConflict {
+  file_path: string;
+  /** null when no checkpoint has the file */
+  base_content: string | null;
+  /** null when the file was deleted */
+  mine_content: string | null;
+  /** null when the restored checkpoint doesn't have the file */
+  checkpoint_content: string | null;
+}
+
+export type ConflictResolution =
+  | { kind: 'keep_mine' }
+  | { kind: 'take_checkpoint' }
+  | { kind: 'merged'; content: string };
+
+/**
+ * Diff between two checkpoints
+ */
+export interface CheckpointDiff {
+  fromCheckpointId: string;
+  toCheckpointId: string;
+  modifiedFiles: FileDiff[];
+  addedFiles: string[];
+  deletedFiles: string[];
+  tokenDelta: number;
+}
+
+/**
+ * Diff for a single file
+ */
+export interface FileDiff {
+  path: string;
+  additions: number;
+  deletions: number;
+  diffContent?: string;
+}
+
+/**
+ * A unified-diff hunk; lines are prefixed with ' ', '-' or '+'
+ */
+export interface DiffHunk {
+  old_start: number;
+  old_lines: number;
+  new_start: number;
+  new_lines: number;
+  lines: string[];
+}
+
+export interface WorktreeFileDiff {
+  path: string;
+  status: 'modified' | 'added' | 'deleted';
+  additions: number;
+  deletions: number;
+  hunks: DiffHunk[];
+}
+
+/**
+ * Changes in the working tree since a checkpoint
+ */
+export interface WorktreeDiff {
+  checkpoint_id: string;
+  files: WorktreeFileDiff[];
+  unchanged_files: number;
+}
+
+/**
+ * Disk usage of a project's checkpoints
+ */
+export interface CheckpointStorageStats {
+  project_id: string;
+  sessions: number;
+  checkpoints: number;
+  blob_count: number;
+  /** Compressed size of the blobs on disk */
+  blob_bytes: number;
+  /** Uncompressed size of everything the checkpoints reference */
+  logical_bytes: number;
+  unreferenced_blobs: number;
+  unreferenced_bytes: number;
+  /** Data in the old per-session layout, which compaction migrates */
+  legacy_bytes: number;
+  total_bytes: number;
+}
+
+/**
+ * What a checkpoint storage compaction did
+ */
+export interface CheckpointCompaction {
+  projects: number;
+  migrated_blobs: number;
+  migrated_transcripts: number;
+  removed_blobs: number;
+  bytes_before: number;
+  bytes_after: number;
+  warnings: string[];
+}
+
+/**
+ * Which project files checkpoints capture
+ */
+export interface CheckpointScope {
+  /** Globs a file must match; empty captures every file */
+  include: string[];
+  /** Globs for files and directories to skip */
+  exclude: string[];
+  respectGitignore: boolean;
+}
+
+export interface CheckpointScopeInfo {
+  scope: CheckpointScope;
+  fileCount: number;
+  /** Sample of captured files, sorted */
+  files: string[];
+}
+
+/**
+ * Provider configuration for API switching
+ */
+export interface ProviderConfig {
+  id: string;
+  name: string;
+  description: string;
+  base_url: string;
+  auth_token?: string;
+  api_key?: string;
+  api_key_helper?: string;
+  model?: string;
+  enable_auto_api_key_helper?: boolean;
+}
+
+/** Auth probe result of one claude-code-router provider */
+export interface RouterProviderValidation {
+  provider: string;
+  api_base_url: string;
+  status: "ok" | "missing_key" | "auth_failed" | "unreachable" | "wrong_base_url" | "unverified";
+  /** HTTP status or error of the probe */
+  detail?: string | null;
+  /** Configured or routed models the provider does not list */
+  missing_models: string[];
+  works: boolean;
+}
+
+export interface RouterValidation {
+  providers: RouterProviderValidation[];
+  /** `provider,model` of the default route */
+  default_route?: string | null;
+  /** Whether switching to a provider that points at the router is allowed */
+  routing_allowed: boolean;
+  blocked_reason?: string | null;
+}
+
+/**
+ * Current provider configuration from environment variables
+ */
+export interface CurrentProviderConfig {
+  anthropic_base_url?: string;
+  anthropic_auth_token?: string;
+  anthropic_api_key?: string;
+  anthropic_api_key_helper?: string;
+  anthropic_model?: string;
+}
+
+/**
+ * Represents an MCP server configuration
+ */
+export interface MCPServer {
+  /** Server name/identifier */
+  name: string;
+  /** Transport type: "stdio" or "sse" */
+  transport: string;
+  /** Command to execute (for stdio) */
+  command?: string;
+  /** Command arguments (for stdio) */
+  args: string[];
+  /** Environment variables */
+  env: Record<string, string>;
+  /** URL endpoint (for SSE) */
+  url?: string;
+  /** Configuration scope: "local", "project", or "user" */
+  scope: string;
+  /** Whether the server is currently active */
+  is_active: boolean;
+  /** Server status */
+  status: ServerStatus;
+}
+
+/**
+ * Server status information
+ */
+export interface ServerStatus {
+  /** Whether the server is running */
+  running: boolean;
+  /** Last error message if any */
+  error?: string;
+  /** Last checked timestamp */
+  last_checked?: number;
+}
+
+/**
+ * MCP configuration for project scope (.mcp.json)
+ */
+export interface MCPProjectConfig {
+  mcpServers: Record<string, MCPServerConfig>;
+}
+
+/**
+ * Individual server configuration in .mcp.json
+ */
+export interface MCPServerConfig {
+  command: string;
+  args: string[];
+  env: Record<string, string>;
+}
+
+/**
+ * Represents a custom slash command
+ */
+export interface SlashCommand {
+  /** Unique identifier for the command */
+  id: string;
+  /** Command name (without prefix) */
+  name: string;
+  /** Full command with prefix (e.g., "/project:optimize") */
+  full_command: string;
+  /** Command scope: "project" or "user" */
+  scope: string;
+  /** Optional namespace (e.g., "frontend" in "/project:frontend:component") */
+  namespace?: string;
+  /** Path to the markdown file */
+  file_path: string;
+  /** Command content (markdown body) */
+  content: string;
+  /** Optional description from frontmatter */
+  description?: string;
+  /** Allowed tools from frontmatter */
+  allowed_tools: string[];
+  /** Whether the command has bash commands (!) */
+  has_bash_commands: boolean;
+  /** Whether the command has file references (@) */
+  has_file_references: boolean;
+  /** Whether the command uses $ARGUMENTS placeholder */
+  accepts_arguments: boolean;
+}
+
+
+/**
+ * Result of saving clipboard image
+ */
+export interface SavedImageResult {
+  success: boolean;
+  file_path?: string;
+  error?: string;
+}
+
+/**
+ * Result of adding a server
+ */
+export interface AddServerResult {
+  success: boolean;
+  message: string;
+  server_name?: string;
+}
+
+/**
+ * Translation configuration interface
+ */
+export interface TranslationConfig {
+  enabled: boolean;
+  api_base_url: string;
+  api_key: string;
+  model: string;
+  timeout_seconds: number;
+  cache_ttl_seconds: number;
+}
+
+/**
+ * Translation cache statistics
+ */
+export interface TranslationCacheStats {
+  total_entries: number;
+  expired_entries: number;
+  active_entries: number;
+}
+
+/**
+ * Auto-compact configuration
+ */
+export interface AutoCompactConfig {
+  /** Enable automatic compaction */
+  enabled: boolean;
+  /** Maximum context tokens before triggering compaction */
+  max_context_tokens: number;
+  /** Threshold percentage to trigger compaction (0.0-1.0) */
+  compaction_threshold: number;
+  /** Minimum time between compactions in seconds */
+  min_compaction_interval: number;
+  /** Strategy for compaction */
+  compaction_strategy: CompactionStrategy;
+  /** Whether to preserve recent messages */
+  preserve_recent_messages: boolean;
+  /** Number of recent messages to preserve */
+  preserve_message_count: number;
+  /** Custom compaction instructions */
+  custom_instructions?: string;
+}
+
+/**
+ * Compaction strategies
+ */
+export type CompactionStrategy =
+  | 'Smart'
+  | 'Aggressive'
+  | 'Conservative'
+  | { Custom: string };
+
+/**
+ * Session context information
+ */
+export interface SessionContext {
+  session_id: string;
+  project_path: string;
+  current_tokens: number;
+  message_count: number;
+  last_compaction?: string; // ISO timestamp
+  compaction_count: number;
+  model: string;
+  status: SessionStatus;
+}
+
+/**
+ * Session status
+ */
+export type SessionStatus =
+  | 'Active'
+  | 'Idle'
+  | 'Compacting'
+  | { CompactionFailed: string };
+
+/**
+ * Auto-compact status information
+ */
+export interface AutoCompactStatus {
+  enabled: boolean;
+  is_monitoring: boolean;
+  sessions_count: number;
+  total_compactions: number;
+  max_context_tokens: number;
+  compaction_threshold: number;
+}
+
+/**
+ * Import result for multiple servers
+ */
+export interface ImportResult {
+  imported_count: number;
+  failed_count: number;
+  servers: ImportServerResult[];
+}
+
+/**
+ * Result for individual server import
+ */
+export interface ImportServerResult {
+  name: string;
+  success: boolean;
+  error?: string;
+}
+
+/**
+ * API client for interacting with the Rust backend
+ */
+export const api = {
+  /**
+   * Lists all projects in the ~/.claude/projects directory
+   * @param kind - Optional session kind; only projects and sessions of that kind are returned
+   * @returns Promise resolving to an array of projects
+   */
+  async listProjects(kind?: SessionKind): Promise<Project[]> {
+    try {
+      return await invoke<Project[]>("list_projects", { kind });
+    } catch (error) {
+      console.error("Failed to list projects:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Starts a background scan of ~/.claude/projects
+   * @returns Promise resolving to the scan token and the previous scan's projects;
+   * partial results arrive as `projects-scan-progress` events
+   */
+  async startProjectScan(): Promise<import('@/types/events').ProjectScanSnapshot> {
+    try {
+      return await invoke<import('@/types/events').ProjectScanSnapshot>("start_project_scan");
+    } catch (error) {
+      console.error("Failed to start project scan:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the current state of a project scan
+   * @param token - The token returned by startProjectScan
+   * @returns Promise resolving to the projects scanned so far
+   */
+  async getProjectScan(token: string): Promise<import('@/types/events').ProjectScanProgress> {
+    try {
+      return await invoke<import('@/types/events').ProjectScanProgress>("get_project_scan", { token });
+    } catch (error) {
+      console.error("Failed to get project scan:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Retrieves sessions for a specific project
+   * @param projectId - The ID of the project to retrieve sessions for
+   * @param kind - Optional session kind to filter by
+   * @returns Promise resolving to an array of sessions
+   */
+  async getProjectSessions(projectId: string, kind?: SessionKind): Promise<Session[]> {
+    try {
+      return await invoke<Session[]>('get_project_sessions', { projectId, kind });
+    } catch (error) {
+      console.error("Failed to get project sessions:", error);
+      throw error;
+    }
+  },
+
+  /**
    * Removes a project from the project list (without deleting files)
    * @param projectId - The ID of the project to remove from list
    * @returns Promise resolving to success message
    */
-  async deleteProject(projectId: string): Promise<string> {
+  async deleteProject(projectId: string): Promise<string> {
+    try {
+      return await invoke<string>('delete_project', { projectId });
+    } catch (error) {
+      console.error("Failed to remove project from list:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Restores a hidden project back to the project list
+   * @param projectId - The ID of the project to restore
+   * @returns Promise resolving to success message
+   */
+  async restoreProject(projectId: string): Promise<string> {
+    try {
+      return await invoke<string>('restore_project', { projectId });
+    } catch (error) {
+      console.error("Failed to restore project:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists all hidden projects
+   * @returns Promise resolving to array of hidden project IDs
+   */
+  async listHiddenProjects(): Promise<string[]> {
+    try {
+      return await invoke<string[]>('list_hidden_projects');
+    } catch (error) {
+      console.error("Failed to list hidden projects:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Permanently delete a project and all its files
+   * @param projectId - The project ID to permanently delete
+   * @returns Promise resolving to success message
+   */
+  async deleteProjectPermanently(projectId: string): Promise<string> {
+    try {
+      return await invoke<string>('delete_project_permanently', { projectId });
+    } catch (error) {
+      console.error("Failed to permanently delete project:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Fetch list of agents from GitHub repository (falls back to the cached index offline)
+   * @returns Promise resolving to list of available agents on GitHub
+   */
+  async fetchGitHubAgents(): Promise<GitHubAgentFile[]> {
+    try {
+      return await invoke<GitHubAgentFile[]>('fetch_github_agents');
+    } catch (error) {
+      console.error("Failed to fetch GitHub agents:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Fetch and preview a specific agent from GitHub.
+   * Progress is reported as `agent-download-progress` events; previously fetched
+   * agents are served from the offline cache when GitHub is unreachable.
+   * @param downloadUrl - The download URL for the agent file
+   * @returns Promise resolving to the agent export data
+   */
+  async fetchGitHubAgentContent(downloadUrl: string): Promise<AgentExport> {
+    try {
+      return await invoke<AgentExport>('fetch_github_agent_content', { downloadUrl });
+    } catch (error) {
+      console.error("Failed to fetch GitHub agent content:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Import an agent directly from GitHub
+   * @param downloadUrl - The download URL for the agent file
+   * @returns Promise resolving to the imported agent
+   */
+  async importAgentFromGitHub(downloadUrl: string): Promise<Agent> {
+    try {
+      return await invoke<Agent>('import_agent_from_github', { downloadUrl });
+    } catch (error) {
+      console.error("Failed to import agent from GitHub:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets whether a GitHub token is configured and the remaining API quota
+   * @returns Promise resolving to the GitHub API status
+   */
+  async getGitHubApiStatus(): Promise<GitHubApiStatus> {
+    try {
+      return await invoke<GitHubApiStatus>('get_github_api_status');
+    } catch (error) {
+      console.error("Failed to get GitHub API status:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Stores the GitHub token used for API requests, raising the rate limit
+   * @param token - Personal access token, or undefined to remove it
+   * @returns Promise resolving to the updated GitHub API status
+   */
+  async setGitHubToken(token?: string): Promise<GitHubApiStatus> {
+    try {
+      return await invoke<GitHubApiStatus>('set_github_token', { token });
+    } catch (error) {
+      console.error("Failed to set GitHub token:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads the Claude settings file
+   * @returns Promise resolving to the settings object
+   */
+  async getClaudeSettings(): Promise<ClaudeSettings> {
+    try {
+      const result = await invoke<ClaudeSettings>("get_claude_settings");
+      console.log("Raw result from get_claude_settings:", result);
+      
+      // Due to #[serde(flatten)] in Rust, the result is directly the settings object
+      return result;
+    } catch (error) {
+      console.error("Failed to get Claude settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Opens a new Claude Code session
+   * @param path - Optional path to open the session in
+   * @returns Promise resolving when the session is opened
+   */
+  async openNewSession(path?: string): Promise<string> {
+    try {
+      return await invoke<string>("open_new_session", { path });
+    } catch (error) {
+      console.error("Failed to open new session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads the CLAUDE.md system prompt file
+   * @returns Promise resolving to the system prompt content
+   */
+  async getSystemPrompt(): Promise<string> {
+    try {
+      return await invoke<string>("get_system_prompt");
+    } catch (error) {
+      console.error("Failed to get system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Checks if Claude Code is installed and gets its version
+   * @returns Promise resolving to the version status
+   */
+  async checkClaudeVersion(): Promise<ClaudeVersionStatus> {
+    try {
+      return await invoke<ClaudeVersionStatus>("check_claude_version");
+    } catch (error) {
+      console.error("Failed to check Claude version:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the CLAUDE.md system prompt file
+   * @param content - The new content for the system prompt
+   * @returns Promise resolving when the file is saved
+   */
+  async saveSystemPrompt(content: string): Promise<string> {
+    try {
+      return await invoke<string>("save_system_prompt", { content });
+    } catch (error) {
+      console.error("Failed to save system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the Claude settings file
+   * @param settings - The settings object to save
+   * @returns Promise resolving when the settings are saved
+   */
+  async saveClaudeSettings(settings: ClaudeSettings): Promise<string> {
+    try {
+      console.log("Saving Claude settings:", settings);
+      return await invoke<string>("save_claude_settings", { settings });
+    } catch (error) {
+      console.error("Failed to save Claude settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Finds all CLAUDE.md files in a project directory
+   * @param projectPath - The absolute path to the project
+   * @returns Promise resolving to an array of CLAUDE.md files
+   */
+  async findClaudeMdFiles(projectPath: string): Promise<ClaudeMdFile[]> {
+    try {
+      return await invoke<ClaudeMdFile[]>("find_claude_md_files", { projectPath });
+    } catch (error) {
+      console.error("Failed to find CLAUDE.md files:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads a specific CLAUDE.md file
+   * @param filePath - The absolute path to the file
+   * @returns Promise resolving to the file content
+   */
+  async readClaudeMdFile(filePath: string): Promise<string> {
+    try {
+      return await invoke<string>("read_claude_md_file", { filePath });
+    } catch (error) {
+      console.error("Failed to read CLAUDE.md file:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves a specific CLAUDE.md file
+   * @param filePath - The absolute path to the file
+   * @param content - The new content for the file
+   * @returns Promise resolving when the file is saved
+   */
+  async saveClaudeMdFile(filePath: string, content: string): Promise<string> {
+    try {
+      return await invoke<string>("save_claude_md_file", { filePath, content });
+    } catch (error) {
+      console.error("Failed to save CLAUDE.md file:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Analyzes a CLAUDE.md file for size, duplicate or conflicting instructions,
+   * stale file references and sections unrelated to recent sessions
+   * @param filePath - The absolute path to the file
+   * @param projectPath - Project root for reference and relevance checks; defaults to the file's directory
+   * @param content - Unsaved editor content to analyze instead of the file
+   */
+  async analyzeClaudeMd(filePath: string, projectPath?: string, content?: string): Promise<ClaudeMdAnalysis> {
+    try {
+      return await invoke<ClaudeMdAnalysis>("analyze_claude_md", { path: filePath, projectPath, content });
+    } catch (error) {
+      console.error("Failed to analyze CLAUDE.md file:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Applies CLAUDE.md fixes to content
+   * @returns Promise resolving to the new content, not yet saved
+   */
+  async applyClaudeMdFixes(content: string, fixes: ClaudeMdFix[]): Promise<string> {
+    try {
+      return await invoke<string>("apply_claude_md_fixes", { content, fixes });
+    } catch (error) {
+      console.error("Failed to apply CLAUDE.md fixes:", error);
+      throw error;
+    }
+  },
+
+  // Agent API methods
+  
+  /**
+   * Lists all CC agents
+   * @returns Promise resolving to an array of agents
+   */
+  async listAgents(): Promise<Agent[]> {
+    try {
+      return await invoke<Agent[]>('list_agents');
+    } catch (error) {
+      console.error("Failed to list agents:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Creates a new agent
+   * @param name - The agent name
+   * @param icon - The icon identifier
+   * @param system_prompt - The system prompt for the agent
+   * @param default_task - Optional default task
+   * @param model - Optional model (defaults to 'sonnet')
+   * @param hooks - Optional hooks configuration as JSON string
+   * @returns Promise resolving to the created agent
+   */
+  async createAgent(
+    name: string, 
+    icon: string, 
+    system_prompt: string, 
+    default_task?: string, 
+    model?: string,
+    hooks?: string
+  ): Promise<Agent> {
+    try {
+      return await invoke<Agent>('create_agent', { 
+        name, 
+        icon, 
+        systemPrompt: system_prompt,
+        defaultTask: default_task,
+        model,
+        hooks
+      });
+    } catch (error) {
+      console.error("Failed to create agent:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Updates an existing agent
+   * @param id - The agent ID
+   * @param name - The updated name
+   * @param icon - The updated icon
+   * @param system_prompt - The updated system prompt
+   * @param default_task - Optional default task
+   * @param model - Optional model
+   * @param hooks - Optional hooks configuration as JSON string
+   * @returns Promise resolving to the updated agent
+   */
+  async updateAgent(
+    id: number, 
+    name: string, 
+    icon: string, 
+    system_prompt: string, 
+    default_task?: string, 
+    model?: string,
+    hooks?: string
+  ): Promise<Agent> {
+    try {
+      return await invoke<Agent>('update_agent', { 
+        id, 
+        name, 
+        icon, 
+        systemPrompt: system_prompt,
+        defaultTask: default_task,
+        model,
+        hooks
+      });
+    } catch (error) {
+      console.error("Failed to update agent:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes an agent
+   * @param id - The agent ID to delete
+   * @returns Promise resolving when the agent is deleted
+   */
+  async deleteAgent(id: number): Promise<void> {
+    try {
+      return await invoke('delete_agent', { id });
+    } catch (error) {
+      console.error("Failed to delete agent:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets a single agent by ID
+   * @param id - The agent ID
+   * @returns Promise resolving to the agent
+   */
+  async getAgent(id: number): Promise<Agent> {
+    try {
+      return await invoke<Agent>('get_agent', { id });
+    } catch (error) {
+      console.error("Failed to get agent:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Exports a single agent to JSON format
+   * @param id - The agent ID to export
+   * @returns Promise resolving to the JSON string
+   */
+  async exportAgent(id: number): Promise<string> {
+    try {
+      return await invoke<string>('export_agent', { id });
+    } catch (error) {
+      console.error("Failed to export agent:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Imports an agent from JSON data
+   * @param jsonData - The JSON string containing the agent export
+   * @returns Promise resolving to the imported agent
+   */
+  async importAgent(jsonData: string): Promise<Agent> {
+    try {
+      return await invoke<Agent>('import_agent', { jsonData });
+    } catch (error) {
+      console.error("Failed to import agent:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Imports an agent from a file
+   * @param filePath - The path to the JSON file
+   * @returns Promise resolving to the imported agent
+   */
+  async importAgentFromFile(filePath: string): Promise<Agent> {
+    try {
+      return await invoke<Agent>('import_agent_from_file', { filePath });
+    } catch (error) {
+      console.error("Failed to import agent from file:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Executes an agent
+   * @param agentId - The agent ID to execute
+   * @param projectPath - The project path to run the agent in
+   * @param task - The task description
+   * @param model - Optional model override
+   * @param maxCostUsd - Optional cost cap; the run is checkpointed and stopped once it exceeds it
+   * @param envProfile - Optional environment profile whose variables are set on this run only
+   * @returns Promise resolving to the run ID when execution starts
+   */
+  async executeAgent(
+    agentId: number,
+    projectPath: string,
+    task: string,
+    model?: string,
+    maxCostUsd?: number,
+    envProfile?: string
+  ): Promise<number> {
+    try {
+      return await invoke<number>('execute_agent', {
+        agentId,
+        projectPath,
+        task,
+        model,
+        maxCostUsd,
+        envProfile,
+      });
+    } catch (error) {
+      console.error("Failed to execute agent:", error);
+      // Return a sentinel value to indicate error
+      throw new Error(`Failed to execute agent: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Sets the JSON Schema an agent's final result must match, or removes it
+   * @param schema - A JSON Schema object, or undefined to remove it
+   */
+  async setAgentOutputSchema(agentId: number, schema?: Record<string, unknown>): Promise<void> {
+    try {
+      await invoke("set_agent_output_schema", { agentId, schema });
+    } catch (error) {
+      console.error("Failed to set agent output schema:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the output schema of an agent, or null when it has none
+   */
+  async getAgentOutputSchema(agentId: number): Promise<Record<string, unknown> | null> {
+    try {
+      return await invoke<Record<string, unknown> | null>("get_agent_output_schema", { agentId });
+    } catch (error) {
+      console.error("Failed to get agent output schema:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the structured result of an agent run, validated against the agent's output schema
+   * @returns null while the run is going and for agents without an output schema
+   */
+  async getAgentRunResult(runId: number): Promise<AgentRunResult | null> {
+    try {
+      return await invoke<AgentRunResult | null>("get_agent_run_result", { runId });
+    } catch (error) {
+      console.error("Failed to get agent run result:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists agent runs with metrics
+   * @param agentId - Optional agent ID to filter runs
+   * @returns Promise resolving to an array of agent runs with metrics
+   */
+  async listAgentRuns(agentId?: number): Promise<AgentRunWithMetrics[]> {
+    try {
+      return await invoke<AgentRunWithMetrics[]>('list_agent_runs', { agentId });
+    } catch (error) {
+      console.error("Failed to list agent runs:", error);
+      // Return empty array instead of throwing to prevent UI crashes
+      return [];
+    }
+  },
+
+  /**
+   * Gets a single agent run by ID with metrics
+   * @param id - The run ID
+   * @returns Promise resolving to the agent run with metrics
+   */
+  async getAgentRun(id: number): Promise<AgentRunWithMetrics> {
+    try {
+      return await invoke<AgentRunWithMetrics>('get_agent_run', { id });
+    } catch (error) {
+      console.error("Failed to get agent run:", error);
+      throw new Error(`Failed to get agent run: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Gets a single agent run by ID with real-time metrics from JSONL
+   * @param id - The run ID
+   * @returns Promise resolving to the agent run with metrics
+   */
+  async getAgentRunWithRealTimeMetrics(id: number): Promise<AgentRunWithMetrics> {
+    try {
+      return await invoke<AgentRunWithMetrics>('get_agent_run_with_real_time_metrics', { id });
+    } catch (error) {
+      console.error("Failed to get agent run with real-time metrics:", error);
+      throw new Error(`Failed to get agent run with real-time metrics: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Lists all currently running agent sessions
+   * @returns Promise resolving to list of running agent sessions
+   */
+  async listRunningAgentSessions(): Promise<AgentRun[]> {
+    try {
+      return await invoke<AgentRun[]>('list_running_sessions');
+    } catch (error) {
+      console.error("Failed to list running agent sessions:", error);
+      throw new Error(`Failed to list running agent sessions: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Kills a running agent session
+   * @param runId - The run ID to kill
+   * @returns Promise resolving to whether the session was successfully killed
+   */
+  async killAgentSession(runId: number): Promise<boolean> {
+    try {
+      return await invoke<boolean>('kill_agent_session', { runId });
+    } catch (error) {
+      console.error("Failed to kill agent session:", error);
+      throw new Error(`Failed to kill agent session: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Gets the status of a specific agent session
+   * @param runId - The run ID to check
+   * @returns Promise resolving to the session status or null if not found
+   */
+  async getSessionStatus(runId: number): Promise<string | null> {
+    try {
+      return await invoke<string | null>('get_session_status', { runId });
+    } catch (error) {
+      console.error("Failed to get session status:", error);
+      throw new Error(`Failed to get session status: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Cleanup finished processes and update their status
+   * @returns Promise resolving to list of run IDs that were cleaned up
+   */
+  async cleanupFinishedProcesses(): Promise<number[]> {
+    try {
+      return await invoke<number[]>('cleanup_finished_processes');
+    } catch (error) {
+      console.error("Failed to cleanup finished processes:", error);
+      throw new Error(`Failed to cleanup finished processes: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Get real-time output for a running session (with live output fallback)
+   * @param runId - The run ID to get output for
+   * @returns Promise resolving to the current session output (JSONL format)
+   */
+  async getSessionOutput(runId: number): Promise<string> {
+    try {
+      return await invoke<string>('get_session_output', { runId });
+    } catch (error) {
+      console.error("Failed to get session output:", error);
+      throw new Error(`Failed to get session output: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Get live output directly from process stdout buffer, or from the run's
+   * on-disk journal once it is no longer in memory (e.g. after a restart)
+   * @param runId - The run ID to get live output for
+   * @returns Promise resolving to the current live output
+   */
+  async getLiveSessionOutput(runId: number): Promise<string> {
+    try {
+      return await invoke<string>('get_live_session_output', { runId });
+    } catch (error) {
+      console.error("Failed to get live session output:", error);
+      throw new Error(`Failed to get live session output: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Read a byte window of a running process' live output, including output spilled to disk
+   * @param runId - The run ID to read output for
+   * @param offset - Absolute byte offset to start at
+   * @param len - Maximum number of bytes to return
+   * @returns Promise resolving to the window, or null if the process is no longer registered
+   */
+  async getLiveOutputRange(runId: number, offset: number, len: number): Promise<LiveOutputRange | null> {
+    try {
+      return await invoke<LiveOutputRange | null>('get_live_output_range', { runId, offset, len });
+    } catch (error) {
+      console.error("Failed to get live output range:", error);
+      throw new Error(`Failed to get live output range: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Lists every backend event with its payload type and schema version
+   * @returns Promise resolving to the event catalog
+   */
+  async getEventCatalog(): Promise<import('@/types/events').EventDescriptor[]> {
+    try {
+      return await invoke<import('@/types/events').EventDescriptor[]>('get_event_catalog');
+    } catch (error) {
+      console.error("Failed to get event catalog:", error);
+      throw new Error(`Failed to get event catalog: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Subscribes a window to event patterns so the backend skips events no window listens to.
+   * Patterns are event names (matching their scoped forms too), `prefix*` or `*`.
+   * @returns Promise resolving to the window's patterns
+   */
+  async subscribeEvents(windowLabel: string, patterns: string[]): Promise<string[]> {
+    try {
+      return await invoke<string[]>("subscribe_events", { windowLabel, patterns });
+    } catch (error) {
+      console.error("Failed to subscribe to events:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Removes event patterns from a window; without patterns the window receives every event again
+   * @returns Promise resolving to the remaining patterns
+   */
+  async unsubscribeEvents(windowLabel: string, patterns?: string[]): Promise<string[]> {
+    try {
+      return await invoke<string[]>("unsubscribe_events", { windowLabel, patterns });
+    } catch (error) {
+      console.error("Failed to unsubscribe from events:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Negotiates the API version with the backend
+   * @param clientVersion - `major.minor` the caller was built against; defaults to this frontend's
+   * @returns Promise resolving to the backend version, feature flags and compatibility
+   */
+  async getApiVersion(
+    clientVersion: string = CLIENT_API_VERSION
+  ): Promise<import('@/types/api-manifest').ApiVersionInfo> {
+    try {
+      return await invoke<import('@/types/api-manifest').ApiVersionInfo>('get_api_version', { clientVersion });
+    } catch (error) {
+      console.error("Failed to get API version:", error);
+      throw new Error(`Failed to get API version: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Lists every backend command with its capability flags
+   * @returns Promise resolving to the command manifest
+   */
+  async getCommandManifest(): Promise<import('@/types/api-manifest').CommandManifest> {
+    try {
+      return await invoke<import('@/types/api-manifest').CommandManifest>('get_command_manifest');
+    } catch (error) {
+      console.error("Failed to get command manifest:", error);
+      throw new Error(`Failed to get command manifest: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Start streaming real-time output for a running session; after a restart the
+   * persisted output is replayed first
+   * @param runId - The run ID to stream output for
+   * @returns Promise that resolves when streaming starts
+   */
+  async streamSessionOutput(runId: number): Promise<void> {
+    try {
+      return await invoke<void>('stream_session_output', { runId });
+    } catch (error) {
+      console.error("Failed to start streaming session output:", error);
+      throw new Error(`Failed to start streaming session output: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Loads the JSONL history for a specific session
+   * @param filter - Trims tool chatter out of the history
+   */
+  async loadSessionHistory(
+    sessionId: string,
+    projectId: string,
+    filter?: HistoryFilter
+  ): Promise<any[]> {
+    return invoke("load_session_history", { sessionId, projectId, filter });
+  },
+
+  /**
+   * Loads part of a session's history without reading the whole transcript
+   * @param offset - Index of the first message; omit to get the last `limit` messages
+   * @param limit - Messages per page (default 200, at most 2000)
+   * @param filter - Trims the page's messages; offsets still count every message
+   */
+  async loadSessionHistoryPage(
+    sessionId: string,
+    projectId: string,
+    offset?: number,
+    limit?: number,
+    filter?: HistoryFilter
+  ): Promise<SessionHistoryPage> {
+    try {
+      return await invoke<SessionHistoryPage>("load_session_history_page", {
+        sessionId,
+        projectId,
+        offset,
+        limit,
+        filter,
+      });
+    } catch (error) {
+      console.error("Failed to load session history page:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the messages appended to a session's transcript since a cursor from a page or
+   * an earlier delta, to refresh a long session without reloading its history
+   * @param sinceCursor - Omit to start at the first message
+   * @param projectId - Omit to look the session up in every project, as for agent runs
+   */
+  async getSessionHistoryDelta(
+    sessionId: string,
+    sinceCursor?: string,
+    projectId?: string,
+    filter?: HistoryFilter
+  ): Promise<SessionHistoryDelta> {
+    try {
+      return await invoke<SessionHistoryDelta>("get_session_history_delta", {
+        sessionId,
+        sinceCursor,
+        projectId,
+        filter,
+      });
+    } catch (error) {
+      console.error("Failed to get session history delta:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the number of messages in a session, as loadSessionHistory would return them
+   */
+  async getSessionMessageCount(sessionId: string, projectId: string): Promise<number> {
+    try {
+      return await invoke<number>("get_session_message_count", { sessionId, projectId });
+    } catch (error) {
+      console.error("Failed to get session message count:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Loads the JSONL history for a specific agent session
+   * Similar to loadSessionHistory but searches across all project directories
+   * @param sessionId - The session ID (UUID)
+   * @param filter - Trims tool chatter out of the history
+   * @returns Promise resolving to array of session messages
+   */
+  async loadAgentSessionHistory(sessionId: string, filter?: HistoryFilter): Promise<any[]> {
+    try {
+      return await invoke<any[]>('load_agent_session_history', { sessionId, filter });
+    } catch (error) {
+      console.error("Failed to load agent session history:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Executes a new interactive Claude Code session with streaming output
+   */
+  async executeClaudeCode(
+    projectPath: string,
+    prompt: string,
+    model: string,
+    providerId?: string,
+    options?: ExecuteOptions
+  ): Promise<RunHandle> {
+    return invoke<RunHandle>("execute_claude_code", { projectPath, prompt, model, providerId, ...options });
+  },
+
+  /**
+   * Continues an existing Claude Code conversation with streaming output
+   */
+  async continueClaudeCode(
+    projectPath: string,
+    prompt: string,
+    model: string,
+    providerId?: string,
+    options?: RunStartOptions
+  ): Promise<RunHandle> {
+    return invoke<RunHandle>("continue_claude_code", { projectPath, prompt, model, providerId, ...options });
+  },
+
+  /**
+   * Resumes an existing Claude Code session by ID with streaming output.
+   * Without a providerId the provider bound to the session is used.
+   */
+  async resumeClaudeCode(
+    projectPath: string,
+    sessionId: string,
+    prompt: string,
+    model: string,
+    providerId?: string,
+    options?: RunStartOptions
+  ): Promise<RunHandle> {
+    return invoke<RunHandle>("resume_claude_code", { projectPath, sessionId, prompt, model, providerId, ...options });
+  },
+
+  /**
+   * Estimates the input tokens and cost of sending a prompt, including the CLI's overhead,
+   * the CLAUDE.md files it loads and attachments; output tokens are not included
+   * @param attachments - File paths relative to the project
+   */
+  async estimatePromptCost(
+    projectPath: string,
+    prompt: string,
+    model: string,
+    attachments?: string[]
+  ): Promise<PromptCostEstimate> {
+    try {
+      return await invoke<PromptCostEstimate>("estimate_prompt_cost", {
+        projectPath,
+        prompt,
+        model,
+        attachments,
+      });
+    } catch (error) {
+      console.error("Failed to estimate prompt cost:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Cancels the currently running Claude Code execution
+   * @param sessionId - Optional session ID to cancel a specific session
+   */
+  async cancelClaudeExecution(sessionId?: string): Promise<void> {
+    return invoke("cancel_claude_execution", { sessionId });
+  },
+
+  /**
+   * Suspends a running session's process and the tools it started, without ending it.
+   * A `claude-session-state` event with status "paused" is emitted.
+   */
+  async pauseExecution(sessionId: string): Promise<ProcessInfo> {
+    try {
+      return await invoke<ProcessInfo>("pause_execution", { sessionId });
+    } catch (error) {
+      console.error("Failed to pause execution:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Resumes a session suspended with pauseExecution
+   */
+  async resumeExecution(sessionId: string): Promise<ProcessInfo> {
+    try {
+      return await invoke<ProcessInfo>("resume_execution", { sessionId });
+    } catch (error) {
+      console.error("Failed to resume execution:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Queues a prompt to run via resume once the session's current run completes successfully.
+   * A `queued-prompt-started:<sessionId>` event is emitted when it starts.
+   */
+  async queueFollowupPrompt(
+    sessionId: string,
+    projectPath: string,
+    prompt: string,
+    model: string,
+    providerId?: string
+  ): Promise<QueuedPrompt> {
+    try {
+      return await invoke<QueuedPrompt>("queue_followup_prompt", { sessionId, projectPath, prompt, model, providerId });
+    } catch (error) {
+      console.error("Failed to queue follow-up prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists a session's queued prompts in dispatch order
+   */
+  async listQueuedPrompts(sessionId: string): Promise<QueuedPrompt[]> {
+    try {
+      return await invoke<QueuedPrompt[]>("list_queued_prompts", { sessionId });
+    } catch (error) {
+      console.error("Failed to list queued prompts:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Removes a queued prompt
+   * @returns false if it was no longer queued
+   */
+  async removeQueuedPrompt(sessionId: string, promptId: string): Promise<boolean> {
+    try {
+      return await invoke<boolean>("remove_queued_prompt", { sessionId, promptId });
+    } catch (error) {
+      console.error("Failed to remove queued prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reorders a session's queued prompts
+   * @param promptIds - Prompt ids in the new order
+   */
+  async reorderQueuedPrompts(sessionId: string, promptIds: string[]): Promise<QueuedPrompt[]> {
+    try {
+      return await invoke<QueuedPrompt[]>("reorder_queued_prompts", { sessionId, promptIds });
+    } catch (error) {
+      console.error("Failed to reorder queued prompts:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Drops every queued prompt of a session
+   */
+  async clearPromptQueue(sessionId: string): Promise<void> {
+    try {
+      return await invoke<void>("clear_prompt_queue", { sessionId });
+    } catch (error) {
+      console.error("Failed to clear prompt queue:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Suggests next prompts from the project's state (failing tests, uncommitted changes,
+   * TODOs, README tasks, latest commit). Pass a model to have the suggestions refined.
+   */
+  async suggestPrompts(projectPath: string, limit?: number, refineModel?: string): Promise<PromptSuggestion[]> {
+    try {
+      return await invoke<PromptSuggestion[]>("suggest_prompts", { projectPath, limit, refineModel });
+    } catch (error) {
+      console.error("Failed to suggest prompts:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves a session template bundling model, permissions, extra dirs, MCP servers,
+   * instructions and a first prompt
+   */
+  async createSessionTemplate(name: string, config: SessionTemplateConfig): Promise<SessionTemplate> {
+    try {
+      return await invoke<SessionTemplate>("create_session_template", { name, config });
+    } catch (error) {
+      console.error("Failed to create session template:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists session templates
+   */
+  async listSessionTemplates(): Promise<SessionTemplate[]> {
+    try {
+      return await invoke<SessionTemplate[]>("list_session_templates");
+    } catch (error) {
+      console.error("Failed to list session templates:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes a session template
+   */
+  async deleteSessionTemplate(templateId: number): Promise<void> {
+    try {
+      return await invoke<void>("delete_session_template", { templateId });
+    } catch (error) {
+      console.error("Failed to delete session template:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Starts a new session in a project from a template; output streams like executeClaudeCode
+   */
+  async startSessionFromTemplate(templateId: number, projectPath: string): Promise<RunHandle> {
+    try {
+      return await invoke<RunHandle>("start_session_from_template", { templateId, projectPath });
+    } catch (error) {
+      console.error("Failed to start session from template:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Exports a session transcript, with tool calls, edit diffs and a token usage summary,
+   * as HTML (in the given theme) or Markdown; without outputPath a save dialog asks for one
+   * @returns Promise resolving to the written path
+   */
+  async exportSession(sessionId: string, format: ExportFormat, theme: ExportTheme, outputPath?: string): Promise<string> {
+    try {
+      return await invoke<string>("export_session", { sessionId, format, theme, outputPath });
+    } catch (error) {
+      console.error("Failed to export session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Renders the first messages of a session export to check format and theme before exporting
+   */
+  async getExportPreview(sessionId: string, format: ExportFormat, theme: ExportTheme): Promise<ExportPreview> {
+    try {
+      return await invoke<ExportPreview>("get_export_preview", { sessionId, format, theme });
+    } catch (error) {
+      console.error("Failed to get export preview:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the lock state of a session, to warn before starting a conflicting run
+   */
+  async getSessionLock(sessionId: string): Promise<SessionLockStatus> {
+    try {
+      return await invoke<SessionLockStatus>("get_session_lock", { sessionId });
+    } catch (error) {
+      console.error("Failed to get session lock:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Removes a session's lock regardless of its owner, e.g. one left behind by a crash
+   * @returns false if the session was not locked
+   */
+  async forceUnlockSession(sessionId: string): Promise<boolean> {
+    try {
+      return await invoke<boolean>("force_unlock_session", { sessionId });
+    } catch (error) {
+      console.error("Failed to force unlock session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists all currently running Claude sessions
+   * @returns Promise resolving to list of running Claude sessions
+   */
+  async listRunningClaudeSessions(): Promise<any[]> {
+    return invoke("list_running_claude_sessions");
+  },
+
+  /**
+   * Gets live output from a Claude session
+   * @param sessionId - The session ID to get output for
+   * @returns Promise resolving to the current live output
+   */
+  async getClaudeSessionOutput(sessionId: string): Promise<string> {
+    return invoke("get_claude_session_output", { sessionId });
+  },
+
+  /**
+   * Lists files and directories in a given path
+   */
+  async listDirectoryContents(directoryPath: string): Promise<FileEntry[]> {
+    return invoke("list_directory_contents", { directoryPath });
+  },
+
+  /**
+   * Searches for files and directories matching a pattern
+   */
+  async searchFiles(basePath: string, query: string): Promise<FileEntry[]> {
+    return invoke("search_files", { basePath, query });
+  },
+
+  /**
+   * Gets overall usage statistics
+   * @returns Promise resolving to usage statistics
+   */
+  async getUsageStats(): Promise<UsageStats> {
+    try {
+      return await invoke<UsageStats>("get_usage_stats");
+    } catch (error) {
+      console.error("Failed to get usage stats:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Get usage overview statistics - fast loading with essential metrics only
+   */
+  async getUsageOverview(): Promise<UsageOverview> {
+    try {
+      return await invoke<UsageOverview>("get_usage_overview");
+    } catch (error) {
+      console.error("Failed to get usage overview:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets usage statistics filtered by date range
+   * @param startDate - Start date (ISO format)
+   * @param endDate - End date (ISO format)
+   * @returns Promise resolving to usage statistics
+   */
+  async getUsageByDateRange(startDate: string, endDate: string): Promise<UsageStats> {
+    try {
+      return await invoke<UsageStats>("get_usage_by_date_range", { startDate, endDate });
+    } catch (error) {
+      console.error("Failed to get usage by date range:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Compares cost, latency, cache hit rate and tokens per model over a date range
+   * @param startDate - Start date (ISO format)
+   * @param endDate - End date (ISO format)
+   * @returns Promise resolving to the per-model report, with router savings
+   */
+  async getModelComparisonReport(
+    startDate: string,
+    endDate: string
+  ): Promise<ModelComparisonReport> {
+    try {
+      return await invoke<ModelComparisonReport>("get_model_comparison_report", {
+        startDate,
+        endDate,
+      });
+    } catch (error) {
+      console.error("Failed to get model comparison report:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets usage statistics grouped by session
+   * @param since - Optional start date (YYYYMMDD)
+   * @param until - Optional end date (YYYYMMDD)
+   * @param order - Optional sort order ('asc' or 'desc')
+   * @returns Promise resolving to an array of session usage data
+   */
+  async getSessionStats(
+    since?: string,
+    until?: string,
+    order?: "asc" | "desc"
+  ): Promise<ProjectUsage[]> {
+    try {
+      return await invoke<ProjectUsage[]>("get_session_stats", {
+        since,
+        until,
+        order,
+      });
+    } catch (error) {
+      console.error("Failed to get session stats:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets usage statistics for today only
+   * @returns Promise resolving to today's usage statistics
+   */
+  async getTodayUsageStats(): Promise<UsageStats> {
+    try {
+      return await invoke<UsageStats>("get_today_usage_stats");
+    } catch (error) {
+      console.error("Failed to get today's usage stats:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets usage statistics grouped by API Base URL; usage proxied by
+   * claude-code-router is attributed to the provider that served it
+   * @returns Promise resolving to array of API Base URL usage statistics
+   */
+  async getUsageByApiBaseUrl(): Promise<ApiBaseUrlUsage[]> {
+    try {
+      return await invoke<ApiBaseUrlUsage[]>("get_usage_by_api_base_url");
+    } catch (error) {
+      console.error("Failed to get usage by API Base URL:", error);
+      throw error;
+    }
+  },
+  /**
+   * Gets the display currency settings and the exchange rate in effect
+   */
+  async getCurrencyConfig(): Promise<CurrencyStatus> {
+    try {
+      return await invoke<CurrencyStatus>("get_currency_config");
+    } catch (error) {
+      console.error("Failed to get currency config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Updates the display currency and how its exchange rate is obtained
+   */
+  async updateCurrencyConfig(config: CurrencyConfig): Promise<CurrencyStatus> {
+    try {
+      return await invoke<CurrencyStatus>("update_currency_config", { config });
+    } catch (error) {
+      console.error("Failed to update currency config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the dangerous-skip permission state and the latest runs that skipped permission checks
+   */
+  async getSafetyStatus(): Promise<SafetyStatus> {
+    try {
+      return await invoke<SafetyStatus>("get_safety_status");
+    } catch (error) {
+      console.error("Failed to get safety status:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Turns dangerous-skip permission mode off immediately
+   */
+  async disableDangerousSkip(): Promise<void> {
+    try {
+      return await invoke<void>("disable_dangerous_skip");
+    } catch (error) {
+      console.error("Failed to disable dangerous-skip mode:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the operator id identifying this user in team usage exports
+   */
+  async getOperatorId(): Promise<string | null> {
+    try {
+      return await invoke<string | null>("get_operator_id");
+    } catch (error) {
+      console.error("Failed to get operator id:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Sets the operator id; pass null to clear it
+   */
+  async setOperatorId(operatorId: string | null): Promise<string | null> {
+    try {
+      return await invoke<string | null>("set_operator_id", { operatorId });
+    } catch (error) {
+      console.error("Failed to set operator id:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Exports usage between two dates (YYYY-MM-DD, inclusive) in the normalized team format
+   * @param outputPath - Optional file to write the export to as JSON
+   */
+  async exportTeamUsage(startDate: string, endDate: string, outputPath?: string): Promise<TeamUsageExport> {
+    try {
+      return await invoke<TeamUsageExport>("export_team_usage", { startDate, endDate, outputPath });
+    } catch (error) {
+      console.error("Failed to export team usage:", error);
+      throw error;
+    }
+  },
+
+
+  /**
+   * Gets detailed usage entries with optional filtering
+   * @param limit - Optional limit for number of entries
+   * @returns Promise resolving to array of usage entries
+   */
+  async getUsageDetails(limit?: number): Promise<UsageEntry[]> {
+    try {
+      return await invoke<UsageEntry[]>("get_usage_details", { limit });
+    } catch (error) {
+      console.error("Failed to get usage details:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets cache tokens for a specific session
+   * @param sessionId - The session ID to get cache tokens for
+   * @returns Promise resolving to session cache tokens
+   */
+  async getSessionCacheTokens(sessionId: string): Promise<SessionCacheTokens> {
+    try {
+      return await invoke<SessionCacheTokens>("get_session_cache_tokens", { sessionId });
+    } catch (error) {
+      console.error("Failed to get session cache tokens:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Creates a checkpoint for the current session state
+   */
+  async createCheckpoint(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    messageIndex?: number,
+    description?: string
+  ): Promise<CheckpointResult> {
+    return invoke("create_checkpoint", {
+      sessionId,
+      projectId,
+      projectPath,
+      messageIndex,
+      description
+    });
+  },
+
+  /**
+   * Starts creating a checkpoint in the background and returns its job id.
+   * Progress arrives as `checkpoint-progress:<sessionId>` events.
+   */
+  async createCheckpointAsync(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    messageIndex?: number,
+    description?: string
+  ): Promise<string> {
+    try {
+      return await invoke<string>("create_checkpoint_async", {
+        sessionId,
+        projectId,
+        projectPath,
+        messageIndex,
+        description
+      });
+    } catch (error) {
+      console.error("Failed to start checkpoint creation:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Cancels a background checkpoint; resolves to false if it already finished
+   */
+  async cancelCheckpointJob(jobId: string): Promise<boolean> {
+    try {
+      return await invoke<boolean>("cancel_checkpoint_job", { jobId });
+    } catch (error) {
+      console.error("Failed to cancel checkpoint job:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Restores a session to a specific checkpoint with specified restore mode
+   * @param restoreMode - 'conversation_only' | 'code_only' | 'both' (defaults to 'both')
+   */
+  async restoreCheckpoint(
+    checkpointId: string,
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    restoreMode?: 'conversation_only' | 'code_only' | 'both'
+  ): Promise<CheckpointResult> {
+    return invoke("restore_checkpoint", {
+      checkpointId,
+      sessionId,
+      projectId,
+      projectPath,
+      restoreMode: restoreMode || 'both'
+    });
+  },
+
+  /**
+   * Lists the conflicts left by the last restore of a session
+   */
+  async listRestoreConflicts(
+    sessionId: string,
+    projectId: string,
+    projectPath: string
+  ): Promise<RestoreConflict[]> {
+    return invoke("list_restore_conflicts", {
+      sessionId,
+      projectId,
+      projectPath
+    });
+  },
+
+  /**
+   * Settles one restore conflict; returns the conflicts still unresolved
+   */
+  async resolveRestoreConflict(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    filePath: string,
+    resolution: ConflictResolution
+  ): Promise<RestoreConflict[]> {
+    return invoke("resolve_restore_conflict", {
+      sessionId,
+      projectId,
+      projectPath,
+      filePath,
+      resolution
+    });
+  },
+
+  /**
+   * Lists all checkpoints for a session
+   */
+  async listCheckpoints(
+    sessionId: string,
+    projectId: string,
+    projectPath: string
+  ): Promise<Checkpoint[]> {
+    return invoke("list_checkpoints", {
+      sessionId,
+      projectId,
+      projectPath
+    });
+  },
+
+  /**
+   * Forks a new timeline branch from a checkpoint
+   */
+  async forkFromCheckpoint(
+    checkpointId: string,
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    newSessionId: string,
+    description?: string
+  ): Promise<CheckpointResult> {
+    return invoke("fork_from_checkpoint", {
+      checkpointId,
+      sessionId,
+      projectId,
+      projectPath,
+      newSessionId,
+      description
+    });
+  },
+
+  /**
+   * Gets the timeline for a session
+   */
+  async getSessionTimeline(
+    sessionId: string,
+    projectId: string,
+    projectPath: string
+  ): Promise<SessionTimeline> {
+    return invoke("get_session_timeline", {
+      sessionId,
+      projectId,
+      projectPath
+    });
+  },
+
+  /**
+   * Updates checkpoint settings for a session
+   */
+  async updateCheckpointSettings(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    autoCheckpointEnabled: boolean,
+    checkpointStrategy: CheckpointStrategy
+  ): Promise<void> {
+    return invoke("update_checkpoint_settings", {
+      sessionId,
+      projectId,
+      projectPath,
+      autoCheckpointEnabled,
+      checkpointStrategy
+    });
+  },
+
+  /**
+   * Gets diff between two checkpoints
+   */
+  async getCheckpointDiff(
+    fromCheckpointId: string,
+    toCheckpointId: string,
+    sessionId: string,
+    projectId: string
+  ): Promise<CheckpointDiff> {
+    try {
+      return await invoke<CheckpointDiff>("get_checkpoint_diff", {
+        fromCheckpointId,
+        toCheckpointId,
+        sessionId,
+        projectId
+      });
+    } catch (error) {
+      console.error("Failed to get checkpoint diff:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Diffs a checkpoint against the files currently on disk, previewing what restoreCheckpoint
+   * would overwrite (modified), delete (added) or recreate (deleted)
+   */
+  async diffCheckpointToWorktree(
+    checkpointId: string,
+    sessionId: string,
+    projectId: string,
+    projectPath: string
+  ): Promise<WorktreeDiff> {
+    try {
+      return await invoke<WorktreeDiff>("diff_checkpoint_to_worktree", {
+        checkpointId,
+        sessionId,
+        projectId,
+        projectPath
+      });
+    } catch (error) {
+      console.error("Failed to diff checkpoint against working tree:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists what restoreCheckpoint would overwrite (modified), delete (added, i.e. files that
+   * exist now but not in the checkpoint) or recreate (deleted), with diffs
+   */
+  async diffCheckpointAgainstWorkdir(
+    checkpointId: string,
+    sessionId: string,
+    projectId: string,
+    projectPath: string
+  ): Promise<WorktreeDiff> {
+    try {
+      return await invoke<WorktreeDiff>("diff_checkpoint_against_workdir", {
+        checkpointId,
+        sessionId,
+        projectId,
+        projectPath
+      });
+    } catch (error) {
+      console.error("Failed to diff checkpoint against working directory:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets a project's checkpoint scope and the files it captures
+   */
+  async getCheckpointScope(projectId: string, projectPath: string): Promise<CheckpointScopeInfo> {
+    try {
+      return await invoke<CheckpointScopeInfo>("get_checkpoint_scope", { projectId, projectPath });
+    } catch (error) {
+      console.error("Failed to get checkpoint scope:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Updates which files a project's checkpoints capture
+   */
+  async updateCheckpointScope(
+    projectId: string,
+    projectPath: string,
+    scope: CheckpointScope
+  ): Promise<CheckpointScopeInfo> {
+    try {
+      return await invoke<CheckpointScopeInfo>("update_checkpoint_scope", {
+        projectId,
+        projectPath,
+        scope
+      });
+    } catch (error) {
+      console.error("Failed to update checkpoint scope:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists the files snapshotted before destructive tool calls of a session, newest first
+   */
+  async listSafetyCheckpoints(sessionId: string, projectId: string): Promise<SafetyCheckpoint[]> {
+    try {
+      return await invoke<SafetyCheckpoint[]>("list_safety_checkpoints", { sessionId, projectId });
+    } catch (error) {
+      console.error("Failed to list safety checkpoints:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists the file edits parsed from a session's tool calls, in the order they were made
+   */
+  async listSessionFileEdits(sessionId: string): Promise<FileEdit[]> {
+    try {
+      return await invoke<FileEdit[]>("list_session_file_edits", { sessionId });
+    } catch (error) {
+      console.error("Failed to list session file edits:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Restores only the files of one safety checkpoint
+   */
+  async restoreSafetyCheckpoint(
+    sessionId: string,
+    projectId: string,
+    checkpointId: string
+  ): Promise<SafetyCheckpoint> {
+    try {
+      return await invoke<SafetyCheckpoint>("restore_safety_checkpoint", { sessionId, projectId, checkpointId });
+    } catch (error) {
+      console.error("Failed to restore safety checkpoint:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Restores the files touched by the session's most recent destructive action
+   */
+  async undoLastDestructiveAction(sessionId: string, projectId: string): Promise<SafetyCheckpoint> {
+    try {
+      return await invoke<SafetyCheckpoint>("undo_last_destructive_action", { sessionId, projectId });
+    } catch (error) {
+      console.error("Failed to undo last destructive action:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Tracks a message for checkpointing
+   */
+  async trackCheckpointMessage(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    message: string
+  ): Promise<void> {
+    try {
+      await invoke("track_checkpoint_message", {
+        sessionId,
+        projectId,
+        projectPath,
+        message
+      });
+    } catch (error) {
+      console.error("Failed to track checkpoint message:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Checks if auto-checkpoint should be triggered
+   */
+  async checkAutoCheckpoint(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    message: string
+  ): Promise<boolean> {
+    try {
+      return await invoke<boolean>("check_auto_checkpoint", {
+        sessionId,
+        projectId,
+        projectPath,
+        message
+      });
+    } catch (error) {
+      console.error("Failed to check auto checkpoint:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Triggers cleanup of old checkpoints
+   */
+  async cleanupOldCheckpoints(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    keepCount: number
+  ): Promise<number> {
+    try {
+      return await invoke<number>("cleanup_old_checkpoints", {
+        sessionId,
+        projectId,
+        projectPath,
+        keepCount
+      });
+    } catch (error) {
+      console.error("Failed to cleanup old checkpoints:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Cleanup checkpoints older than specified days (default: 30 days per Claude Code docs)
+   */
+  async cleanupOldCheckpointsByAge(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    days?: number
+  ): Promise<number> {
+    try {
+      return await invoke<number>("cleanup_old_checkpoints_by_age", {
+        sessionId,
+        projectId,
+        projectPath,
+        days: days ?? 30 // Default to 30 days
+      });
+    } catch (error) {
+      console.error("Failed to cleanup old checkpoints by age:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reports checkpoint disk usage of a project, or of every project when omitted
+   */
+  async getCheckpointStorageStats(projectId?: string): Promise<CheckpointStorageStats[]> {
+    try {
+      return await invoke<CheckpointStorageStats[]>("get_checkpoint_storage_stats", { projectId });
+    } catch (error) {
+      console.error("Failed to get checkpoint storage stats:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deduplicates checkpoint storage and deletes unreferenced blobs, for a project or
+   * every project when omitted
+   */
+  async compactCheckpointStorage(projectId?: string): Promise<CheckpointCompaction> {
+    try {
+      return await invoke<CheckpointCompaction>("compact_checkpoint_storage", { projectId });
+    } catch (error) {
+      console.error("Failed to compact checkpoint storage:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets checkpoint settings for a session
+   */
+  async getCheckpointSettings(
+    sessionId: string,
+    projectId: string,
+    projectPath: string
+  ): Promise<{
+    auto_checkpoint_enabled: boolean;
+    checkpoint_strategy: CheckpointStrategy;
+    total_checkpoints: number;
+    current_checkpoint_id?: string;
+  }> {
+    try {
+      return await invoke("get_checkpoint_settings", {
+        sessionId,
+        projectId,
+        projectPath
+      });
+    } catch (error) {
+      console.error("Failed to get checkpoint settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Clears checkpoint manager for a session (cleanup on session end)
+   */
+  async clearCheckpointManager(sessionId: string): Promise<void> {
+    try {
+      await invoke("clear_checkpoint_manager", { sessionId });
+    } catch (error) {
+      console.error("Failed to clear checkpoint manager:", error);
+      throw error;
+    }
+  },
+
+  // ============================================================================
+  // MESSAGE-LEVEL OPERATIONS (Fine-grained Undo/Redo)
+  // ============================================================================
+
+  /**
+   * Undo the last N messages (default 1)
+   * Creates a safety checkpoint before undoing
+   */
+  async messageUndo(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    count?: number
+  ): Promise<CheckpointResult> {
+    return invoke("message_undo", {
+      sessionId,
+      projectId,
+      projectPath,
+      count
+    });
+  },
+
+  /**
+   * Truncate messages to a specific index
+   * All messages after this index will be removed
+   */
+  async messageTruncateToIndex(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    messageIndex: number
+  ): Promise<CheckpointResult> {
+    return invoke("message_truncate_to_index", {
+      sessionId,
+      projectId,
+      projectPath,
+      messageIndex
+    });
+  },
+
+  /**
+   * Edit a specific message and regenerate from that point
+   */
+  async messageEdit(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    messageIndex: number,
+    newContent: string
+  ): Promise<CheckpointResult> {
+    return invoke("message_edit", {
+      sessionId,
+      projectId,
+      projectPath,
+      messageIndex,
+      newContent
+    });
+  },
+
+  /**
+   * Delete a specific message by index
+   */
+  async messageDelete(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    messageIndex: number
+  ): Promise<CheckpointResult> {
+    return invoke("message_delete", {
+      sessionId,
+      projectId,
+      projectPath,
+      messageIndex
+    });
+  },
+
+  /**
+   * Get the current number of messages in a session
+   */
+  async messageGetCount(
+    sessionId: string,
+    projectId: string,
+    projectPath: string
+  ): Promise<number> {
+    return invoke("message_get_count", {
+      sessionId,
+      projectId,
+      projectPath
+    });
+  },
+
+  /**
+   * Get a specific message by index
+   */
+  async messageGetByIndex(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    messageIndex: number
+  ): Promise<string> {
+    return invoke("message_get_by_index", {
+      sessionId,
+      projectId,
+      projectPath,
+      messageIndex
+    });
+  },
+
+  /**
+   * Get all messages in a session
+   */
+  async messageGetAll(
+    sessionId: string,
+    projectId: string,
+    projectPath: string
+  ): Promise<string[]> {
+    return invoke("message_get_all", {
+      sessionId,
+      projectId,
+      projectPath
+    });
+  },
+
+  /**
+   * Tracks a batch of messages for a session for checkpointing
+   */
+  trackSessionMessages: (
+    sessionId: string, 
+    projectId: string, 
+    projectPath: string, 
+    messages: string[]
+  ): Promise<void> =>
+    invoke("track_session_messages", { sessionId, projectId, projectPath, messages }),
+
+  /**
+   * Adds a new MCP server
+   */
+  async mcpAdd(
+    name: string,
+    transport: string,
+    command?: string,
+    args: string[] = [],
+    env: Record<string, string> = {},
+    url?: string,
+    scope: string = "local"
+  ): Promise<AddServerResult> {
+    try {
+      return await invoke<AddServerResult>("mcp_add", {
+        name,
+        transport,
+        command,
+        args,
+        env,
+        url,
+        scope
+      });
+    } catch (error) {
+      console.error("Failed to add MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists all configured MCP servers
+   */
+  async mcpList(): Promise<MCPServer[]> {
+    try {
+      console.log("API: Calling mcp_list...");
+      const result = await invoke<MCPServer[]>("mcp_list");
+      console.log("API: mcp_list returned:", result);
+      return result;
+    } catch (error) {
+      console.error("API: Failed to list MCP servers:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets details for a specific MCP server
+   */
+  async mcpGet(name: string): Promise<MCPServer> {
+    try {
+      return await invoke<MCPServer>("mcp_get", { name });
+    } catch (error) {
+      console.error("Failed to get MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Removes an MCP server
+   */
+  async mcpRemove(name: string): Promise<string> {
+    try {
+      return await invoke<string>("mcp_remove", { name });
+    } catch (error) {
+      console.error("Failed to remove MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Adds an MCP server from JSON configuration
+   */
+  async mcpAddJson(name: string, jsonConfig: string, scope: string = "local"): Promise<AddServerResult> {
+    try {
+      return await invoke<AddServerResult>("mcp_add_json", { name, jsonConfig, scope });
+    } catch (error) {
+      console.error("Failed to add MCP server from JSON:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Imports MCP servers from Claude Desktop
+   */
+  async mcpAddFromClaudeDesktop(scope: string = "local"): Promise<ImportResult> {
+    try {
+      return await invoke<ImportResult>("mcp_add_from_claude_desktop", { scope });
+    } catch (error) {
+      console.error("Failed to import from Claude Desktop:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Starts Claude Code as an MCP server
+   */
+  async mcpServe(): Promise<string> {
+    try {
+      return await invoke<string>("mcp_serve");
+    } catch (error) {
+      console.error("Failed to start MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Registers the workbench memory as a user-scoped MCP server so sessions can use it
+   * @returns Promise resolving to the registered server name
+   */
+  async mcpRegisterMemoryServer(): Promise<string> {
+    try {
+      return await invoke<string>("mcp_register_memory_server");
+    } catch (error) {
+      console.error("Failed to register memory MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Tests connection to an MCP server
+   */
+  async mcpTestConnection(name: string): Promise<string> {
+    try {
+      return await invoke<string>("mcp_test_connection", { name });
+    } catch (error) {
+      console.error("Failed to test MCP connection:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Exports MCP server configuration from .claude.json
+   */
+  async mcpExportConfig(): Promise<string> {
+    try {
+      return await invoke<string>("mcp_export_config");
+    } catch (error) {
+      console.error("Failed to export MCP configuration:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Resets project-scoped server approval choices
+   */
+  async mcpResetProjectChoices(): Promise<string> {
+    try {
+      return await invoke<string>("mcp_reset_project_choices");
+    } catch (error) {
+      console.error("Failed to reset project choices:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the status of MCP servers
+   */
+  async mcpGetServerStatus(): Promise<Record<string, ServerStatus>> {
+    try {
+      return await invoke<Record<string, ServerStatus>>("mcp_get_server_status");
+    } catch (error) {
+      console.error("Failed to get server status:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads .mcp.json from the current project
+   */
+  async mcpReadProjectConfig(projectPath: string): Promise<MCPProjectConfig> {
+    try {
+      return await invoke<MCPProjectConfig>("mcp_read_project_config", { projectPath });
+    } catch (error) {
+      console.error("Failed to read project MCP config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves .mcp.json to the current project
+   */
+  async mcpSaveProjectConfig(projectPath: string, config: MCPProjectConfig): Promise<string> {
+    try {
+      return await invoke<string>("mcp_save_project_config", { projectPath, config });
+    } catch (error) {
+      console.error("Failed to save project MCP config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Get the stored Claude binary path from settings
+   * @returns Promise resolving to the path if set, null otherwise
+   */
+  async getClaudeBinaryPath(): Promise<string | null> {
+    try {
+      return await invoke<string | null>("get_claude_binary_path");
+    } catch (error) {
+      console.error("Failed to get Claude binary path:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Set the Claude binary path in settings
+   * @param path - The absolute path to the Claude binary
+   * @returns Promise resolving when the path is saved
+   */
+  async setClaudeBinaryPath(path: string): Promise<void> {
+    try {
+      return await invoke<void>("set_claude_binary_path", { path });
+    } catch (error) {
+      console.error("Failed to set Claude binary path:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * List all available Claude installations on the system
+   * @returns Promise resolving to an array of Claude installations
+   */
+  async listClaudeInstallations(): Promise<ClaudeInstallation[]> {
+    try {
+      return await invoke<ClaudeInstallation[]>("list_claude_installations");
+    } catch (error) {
+      console.error("Failed to list Claude installations:", error);
+      throw error;
+    }
+  },
+
+  // Storage API methods
+
+  /**
+   * Lists all tables in the SQLite database
+   * @returns Promise resolving to an array of table information
+   */
+  async storageListTables(): Promise<any[]> {
     try {
-      return await invoke<string>('delete_project', { projectId });
+      return await invoke<any[]>("storage_list_tables");
     } catch (error) {
-      console.error("Failed to remove project from list:", error);
+      console.error("Failed to list tables:", error);
       throw error;
     }
   },
 
   /**
-   * Restores a hidden project back to the project list
-   * @param projectId - The ID of the project to restore
+   * Reads table data with pagination
+   * @param tableName - Name of the table to read
+   * @param page - Page number (1-indexed)
+   * @param pageSize - Number of rows per page
+   * @param searchQuery - Optional search query
+   * @returns Promise resolving to table data with pagination info
+   */
+  async storageReadTable(
+    tableName: string,
+    page: number,
+    pageSize: number,
+    searchQuery?: string
+  ): Promise<any> {
+    try {
+      return await invoke<any>("storage_read_table", {
+        tableName,
+        page,
+        pageSize,
+        searchQuery,
+      });
+    } catch (error) {
+      console.error("Failed to read table:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Updates a row in a table
+   * @param tableName - Name of the table
+   * @param primaryKeyValues - Map of primary key column names to values
+   * @param updates - Map of column names to new values
+   * @returns Promise resolving when the row is updated
+   */
+  async storageUpdateRow(
+    tableName: string,
+    primaryKeyValues: Record<string, any>,
+    updates: Record<string, any>
+  ): Promise<void> {
+    try {
+      return await invoke<void>("storage_update_row", {
+        tableName,
+        primaryKeyValues,
+        updates,
+      });
+    } catch (error) {
+      console.error("Failed to update row:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes a row from a table
+   * @param tableName - Name of the table
+   * @param primaryKeyValues - Map of primary key column names to values
+   * @returns Promise resolving when the row is deleted
+   */
+  async storageDeleteRow(
+    tableName: string,
+    primaryKeyValues: Record<string, any>
+  ): Promise<void> {
+    try {
+      return await invoke<void>("storage_delete_row", {
+        tableName,
+        primaryKeyValues,
+      });
+    } catch (error) {
+      console.error("Failed to delete row:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Inserts a new row into a table
+   * @param tableName - Name of the table
+   * @param values - Map of column names to values
+   * @returns Promise resolving to the last insert row ID
+   */
+  async storageInsertRow(
+    tableName: string,
+    values: Record<string, any>
+  ): Promise<number> {
+    try {
+      return await invoke<number>("storage_insert_row", {
+        tableName,
+        values,
+      });
+    } catch (error) {
+      console.error("Failed to insert row:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Executes a raw SQL query
+   * @param query - SQL query string
+   * @returns Promise resolving to query result
+   */
+  async storageExecuteSql(query: string): Promise<any> {
+    try {
+      return await invoke<any>("storage_execute_sql", { query });
+    } catch (error) {
+      console.error("Failed to execute SQL:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Resets the entire database
+   * @returns Promise resolving when the database is reset
+   */
+  async storageResetDatabase(): Promise<void> {
+    try {
+      return await invoke<void>("storage_reset_database");
+    } catch (error) {
+      console.error("Failed to reset database:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Get hooks configuration for a specific scope
+   * @param scope - The configuration scope: 'user', 'project', or 'local'
+   * @param projectPath - Project path (required for project and local scopes)
+   * @returns Promise resolving to the hooks configuration
+   */
+  async getHooksConfig(scope: 'user' | 'project' | 'local', projectPath?: string): Promise<HooksConfiguration> {
+    try {
+      return await invoke<HooksConfiguration>("get_hooks_config", { scope, projectPath });
+    } catch (error) {
+      console.error("Failed to get hooks config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Update hooks configuration for a specific scope
+   * @param scope - The configuration scope: 'user', 'project', or 'local'
+   * @param hooks - The hooks configuration to save
+   * @param projectPath - Project path (required for project and local scopes)
    * @returns Promise resolving to success message
    */
-  async restoreProject(projectId: string): Promise<string> {
+  async updateHooksConfig(
+    scope: 'user' | 'project' | 'local',
+    hooks: HooksConfiguration,
+    projectPath?: string
+  ): Promise<string> {
     try {
-      return await invoke<string>('restore_project', { projectId });
+      return await invoke<string>("update_hooks_config", { scope, projectPath, hooks });
     } catch (error) {
-      console.error("Failed to restore project:", error);
+      console.error("Failed to update hooks config:", error);
       throw error;
     }
   },
 
   /**
-   * Lists all hidden projects
-   * @returns Promise resolving to array of hidden project IDs
+   * Lists the built-in hook packs and the user's pack bundles from ~/.claude/hook-packs
    */
-  async listHiddenProjects(): Promise<string[]> {
+  async listHookPacks(): Promise<HookPack[]> {
     try {
-      return await invoke<string[]>('list_hidden_projects');
+      return await invoke<HookPack[]>("list_hook_packs");
+    } catch (error) {
+      console.error("Failed to list hook packs:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Previews a hook pack; with a scope, also compares it to the hooks configured there
+   */
+  async previewHookPack(
+    id: string,
+    scope?: 'user' | 'project' | 'local',
+    projectPath?: string
+  ): Promise<HookPackPreview> {
+    try {
+      return await invoke<HookPackPreview>("preview_hook_pack", { id, scope, projectPath });
+    } catch (error) {
+      console.error("Failed to preview hook pack:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Merges a hook pack into the hooks of a scope, skipping hooks that are already present
+   * @param onConflict - How to handle matcher groups running other hooks; aborts by default
+   */
+  async installHookPack(
+    id: string,
+    scope: 'user' | 'project' | 'local',
+    projectPath?: string,
+    onConflict?: HookPackConflictStrategy
+  ): Promise<HookPackInstallResult> {
+    try {
+      return await invoke<HookPackInstallResult>("install_hook_pack", { id, scope, projectPath, onConflict });
+    } catch (error) {
+      console.error("Failed to install hook pack:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists the built-in hook templates and the user's templates in ~/.claude/hook_templates
+   */
+  async listHookTemplates(): Promise<HookTemplate[]> {
+    try {
+      return await invoke<HookTemplate[]>("list_hook_templates");
+    } catch (error) {
+      console.error("Failed to list hook templates:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Fills in a hook template's parameters and merges its hooks into a settings scope
+   * @param scope - Defaults to the template's default scope
+   * @param params - Parameter values; omitted ones take their defaults
+   */
+  async applyHookTemplate(
+    id: string,
+    scope?: 'user' | 'project' | 'local',
+    projectPath?: string,
+    params?: Record<string, string>,
+    onConflict?: HookPackConflictStrategy
+  ): Promise<HookPackInstallResult> {
+    try {
+      return await invoke<HookPackInstallResult>("apply_hook_template", {
+        id,
+        scope,
+        projectPath,
+        params,
+        onConflict,
+      });
+    } catch (error) {
+      console.error("Failed to apply hook template:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists the environment profiles, by name
+   */
+  async listEnvProfiles(): Promise<EnvProfile[]> {
+    try {
+      return await invoke<EnvProfile[]>("list_env_profiles");
+    } catch (error) {
+      console.error("Failed to list environment profiles:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Creates an environment profile, or replaces the one with the same name
+   */
+  async saveEnvProfile(
+    name: string,
+    vars: Record<string, string>,
+    description?: string
+  ): Promise<EnvProfile> {
+    try {
+      return await invoke<EnvProfile>("save_env_profile", { name, description, vars });
+    } catch (error) {
+      console.error("Failed to save environment profile:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes an environment profile
+   */
+  async deleteEnvProfile(name: string): Promise<void> {
+    try {
+      return await invoke<void>("delete_env_profile", { name });
+    } catch (error) {
+      console.error("Failed to delete environment profile:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Detects a project's stack and proposes a starter CLAUDE.md, permission preset and
+   * hooks; nothing is written until the proposal is applied
+   */
+  async analyzeProjectStack(projectPath: string): Promise<BootstrapProposal> {
+    try {
+      return await invoke<BootstrapProposal>("analyze_project_stack", { projectPath });
+    } catch (error) {
+      console.error("Failed to analyze project stack:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Writes a proposal's CLAUDE.md, sets its permission preset for the project and
+   * adds its hooks to the project settings
+   * @param overwriteClaudeMd - Replace an existing CLAUDE.md
+   */
+  async applyProjectBootstrap(
+    proposal: BootstrapProposal,
+    overwriteClaudeMd?: boolean
+  ): Promise<BootstrapResult> {
+    try {
+      return await invoke<BootstrapResult>("apply_project_bootstrap", {
+        proposal,
+        overwriteClaudeMd,
+      });
+    } catch (error) {
+      console.error("Failed to apply project bootstrap:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reports the agents database schema version, its migrations and tables
+   */
+  async getDbSchemaInfo(): Promise<DbSchemaInfo> {
+    try {
+      return await invoke<DbSchemaInfo>("get_db_schema_info");
     } catch (error) {
-      console.error("Failed to list hidden projects:", error);
+      console.error("Failed to get database schema info:", error);
       throw error;
     }
   },
 
   /**
-   * Permanently delete a project and all its files
-   * @param projectId - The project ID to permanently delete
-   * @returns Promise resolving to success message
+   * Reverts the agents database schema to a version, dropping the data of the
+   * reverted migrations; a copy of the database is written first
    */
-  async deleteProjectPermanently(projectId: string): Promise<string> {
+  async rollbackDbSchema(targetVersion: number): Promise<SchemaRollback> {
     try {
-      return await invoke<string>('delete_project_permanently', { projectId });
+      return await invoke<SchemaRollback>("rollback_db_schema", { targetVersion });
     } catch (error) {
-      console.error("Failed to permanently delete project:", error);
+      console.error("Failed to roll back database schema:", error);
       throw error;
     }
   },
 
   /**
-   * Fetch list of agents from GitHub repository
-   * @returns Promise resolving to list of available agents on GitHub
+   * Lists prompt templates by name
    */
-  async fetchGitHubAgents(): Promise<GitHubAgentFile[]> {
+  async listPromptTemplates(): Promise<PromptTemplate[]> {
     try {
-      return await invoke<GitHubAgentFile[]>('fetch_github_agents');
+      return await invoke<PromptTemplate[]>("list_prompt_templates");
     } catch (error) {
-      console.error("Failed to fetch GitHub agents:", error);
+      console.error("Failed to list prompt templates:", error);
       throw error;
     }
   },
 
   /**
-   * Fetch and preview a specific agent from GitHub
-   * @param downloadUrl - The download URL for the agent file
-   * @returns Promise resolving to the agent export data
+   * Saves a new prompt template
    */
-  async fetchGitHubAgentContent(downloadUrl: string): Promise<AgentExport> {
+  async createPromptTemplate(
+    name: string,
+    content: string,
+    description?: string
+  ): Promise<PromptTemplate> {
     try {
-      return await invoke<AgentExport>('fetch_github_agent_content', { downloadUrl });
+      return await invoke<PromptTemplate>("create_prompt_template", { name, description, content });
     } catch (error) {
-      console.error("Failed to fetch GitHub agent content:", error);
+      console.error("Failed to create prompt template:", error);
       throw error;
     }
   },
 
   /**
-   * Import an agent directly from GitHub
-   * @param downloadUrl - The download URL for the agent file
-   * @returns Promise resolving to the imported agent
+   * Replaces a prompt template's name, description and content
    */
-  async importAgentFromGitHub(downloadUrl: string): Promise<Agent> {
+  async updatePromptTemplate(
+    templateId: number,
+    name: string,
+    content: string,
+    description?: string
+  ): Promise<PromptTemplate> {
     try {
-      return await invoke<Agent>('import_agent_from_github', { downloadUrl });
+      return await invoke<PromptTemplate>("update_prompt_template", {
+        templateId,
+        name,
+        description,
+        content,
+      });
     } catch (error) {
-      console.error("Failed to import agent from GitHub:", error);
+      console.error("Failed to update prompt template:", error);
       throw error;
     }
   },
 
   /**
-   * Reads the Claude settings file
-   * @returns Promise resolving to the settings object
+   * Deletes a prompt template
    */
-  async getClaudeSettings(): Promise<ClaudeSettings> {
+  async deletePromptTemplate(templateId: number): Promise<void> {
     try {
-      const result = await invoke<ClaudeSettings>("get_claude_settings");
-      console.log("Raw result from get_claude_settings:", result);
-      
-      // Due to #[serde(flatten)] in Rust, the result is directly the settings object
-      return result;
+      return await invoke<void>("delete_prompt_template", { templateId });
     } catch (error) {
-      console.error("Failed to get Claude settings:", error);
+      console.error("Failed to delete prompt template:", error);
       throw error;
     }
   },
 
   /**
-   * Opens a new Claude Code session
-   * @param path - Optional path to open the session in
-   * @returns Promise resolving when the session is opened
+   * Fills in a prompt template's variables, e.g. to preview the prompt
    */
-  async openNewSession(path?: string): Promise<string> {
+  async expandPromptTemplate(
+    templateId: number,
+    variables: Record<string, string>
+  ): Promise<string> {
     try {
-      return await invoke<string>("open_new_session", { path });
+      return await invoke<string>("expand_prompt_template", { templateId, variables });
     } catch (error) {
-      console.error("Failed to open new session:", error);
+      console.error("Failed to expand prompt template:", error);
       throw error;
     }
   },
 
   /**
-   * Reads the CLAUDE.md system prompt file
-   * @returns Promise resolving to the system prompt content
+   * Lists a project's memory entries, keyed values first
+   * @param query - Optional text to filter keys and content by
    */
-  async getSystemPrompt(): Promise<string> {
+  async listMemoryEntries(projectPath: string, query?: string): Promise<MemoryEntry[]> {
     try {
-      return await invoke<string>("get_system_prompt");
+      return await invoke<MemoryEntry[]>("list_memory_entries", { projectPath, query });
     } catch (error) {
-      console.error("Failed to get system prompt:", error);
+      console.error("Failed to list memory entries:", error);
       throw error;
     }
   },
 
   /**
-   * Checks if Claude Code is installed and gets its version
-   * @returns Promise resolving to the version status
+   * Stores a value under a key in a project's memory, replacing the previous one
    */
-  async checkClaudeVersion(): Promise<ClaudeVersionStatus> {
+  async setMemoryValue(projectPath: string, key: string, content: string): Promise<MemoryEntry> {
     try {
-      return await invoke<ClaudeVersionStatus>("check_claude_version");
+      return await invoke<MemoryEntry>("set_memory_value", { projectPath, key, content });
     } catch (error) {
-      console.error("Failed to check Claude version:", error);
+      console.error("Failed to set memory value:", error);
       throw error;
     }
   },
 
   /**
-   * Saves the CLAUDE.md system prompt file
-   * @param content - The new content for the system prompt
-   * @returns Promise resolving when the file is saved
+   * Adds a free-form note to a project's memory
    */
-  async saveSystemPrompt(content: string): Promise<string> {
+  async addMemoryNote(projectPath: string, content: string): Promise<MemoryEntry> {
     try {
-      return await invoke<string>("save_system_prompt", { content });
+      return await invoke<MemoryEntry>("add_memory_note", { projectPath, content });
     } catch (error) {
-      console.error("Failed to save system prompt:", error);
+      console.error("Failed to add memory note:", error);
       throw error;
     }
   },
 
   /**
-   * Saves the Claude settings file
-   * @param settings - The settings object to save
-   * @returns Promise resolving when the settings are saved
+   * Edits the content of a memory entry
    */
-  async saveClaudeSettings(settings: ClaudeSettings): Promise<string> {
+  async updateMemoryEntry(id: number, content: string): Promise<MemoryEntry> {
     try {
-      console.log("Saving Claude settings:", settings);
-      return await invoke<string>("save_claude_settings", { settings });
+      return await invoke<MemoryEntry>("update_memory_entry", { id, content });
     } catch (error) {
-      console.error("Failed to save Claude settings:", error);
+      console.error("Failed to update memory entry:", error);
       throw error;
     }
   },
 
   /**
-   * Finds all CLAUDE.md files in a project directory
-   * @param projectPath - The absolute path to the project
-   * @returns Promise resolving to an array of CLAUDE.md files
+   * Deletes a memory entry
+   * @returns Promise resolving to whether the entry existed
    */
-  async findClaudeMdFiles(projectPath: string): Promise<ClaudeMdFile[]> {
+  async deleteMemoryEntry(id: number): Promise<boolean> {
     try {
-      return await invoke<ClaudeMdFile[]>("find_claude_md_files", { projectPath });
+      return await invoke<boolean>("delete_memory_entry", { id });
     } catch (error) {
-      console.error("Failed to find CLAUDE.md files:", error);
+      console.error("Failed to delete memory entry:", error);
       throw error;
     }
   },
 
   /**
-   * Reads a specific CLAUDE.md file
-   * @param filePath - The absolute path to the file
-   * @returns Promise resolving to the file content
+   * Chunks and embeds a project's docs folder into its knowledge base
+   * @param docsDir - Folder to ingest, relative to the project; defaults to "docs"
+   * @param force - Rebuild instead of re-reading only changed files
    */
-  async readClaudeMdFile(filePath: string): Promise<string> {
+  async ingestProjectKnowledge(
+    projectPath: string,
+    docsDir?: string,
+    force?: boolean
+  ): Promise<KnowledgeIngestStats> {
     try {
-      return await invoke<string>("read_claude_md_file", { filePath });
+      return await invoke<KnowledgeIngestStats>("ingest_project_knowledge", { projectPath, docsDir, force });
     } catch (error) {
-      console.error("Failed to read CLAUDE.md file:", error);
+      console.error("Failed to ingest project knowledge:", error);
       throw error;
     }
   },
 
   /**
-   * Saves a specific CLAUDE.md file
-   * @param filePath - The absolute path to the file
-   * @param content - The new content for the file
-   * @returns Promise resolving when the file is saved
+   * Finds the passages of a project's ingested docs most relevant to a question
+   * @param k - Number of passages to return, 5 by default
    */
-  async saveClaudeMdFile(filePath: string, content: string): Promise<string> {
+  async queryProjectKnowledge(projectPath: string, question: string, k?: number): Promise<KnowledgeQueryResult> {
     try {
-      return await invoke<string>("save_claude_md_file", { filePath, content });
+      return await invoke<KnowledgeQueryResult>("query_project_knowledge", { projectPath, question, k });
     } catch (error) {
-      console.error("Failed to save CLAUDE.md file:", error);
+      console.error("Failed to query project knowledge:", error);
       throw error;
     }
   },
 
-  // Agent API methods
-  
   /**
-   * Lists all CC agents
-   * @returns Promise resolving to an array of agents
+   * Lists execution profiles, built-in ones (local-dev, ci, demo) first
    */
-  async listAgents(): Promise<Agent[]> {
+  async listExecutionProfiles(): Promise<ExecutionProfile[]> {
     try {
-      return await invoke<Agent[]>('list_agents');
+      return await invoke<ExecutionProfile[]>("list_execution_profiles");
     } catch (error) {
-      console.error("Failed to list agents:", error);
+      console.error("Failed to list execution profiles:", error);
       throw error;
     }
   },
 
   /**
-   * Creates a new agent
-   * @param name - The agent name
-   * @param icon - The icon identifier
-   * @param system_prompt - The system prompt for the agent
-   * @param default_task - Optional default task
-   * @param model - Optional model (defaults to 'sonnet')
-   * @param hooks - Optional hooks configuration as JSON string
-   * @returns Promise resolving to the created agent
+   * Creates a profile or replaces the settings of the one with the same name
    */
-  async createAgent(
-    name: string, 
-    icon: string, 
-    system_prompt: string, 
-    default_task?: string, 
-    model?: string,
-    hooks?: string
-  ): Promise<Agent> {
+  async saveExecutionProfile(
+    name: string,
+    config: ExecutionProfileConfig,
+    description?: string
+  ): Promise<ExecutionProfile> {
     try {
-      return await invoke<Agent>('create_agent', { 
-        name, 
-        icon, 
-        systemPrompt: system_prompt,
-        defaultTask: default_task,
-        model,
-        hooks
-      });
+      return await invoke<ExecutionProfile>("save_execution_profile", { name, description, config });
     } catch (error) {
-      console.error("Failed to create agent:", error);
+      console.error("Failed to save execution profile:", error);
       throw error;
     }
   },
 
   /**
-   * Updates an existing agent
-   * @param id - The agent ID
-   * @param name - The updated name
-   * @param icon - The updated icon
-   * @param system_prompt - The updated system prompt
-   * @param default_task - Optional default task
-   * @param model - Optional model
-   * @param hooks - Optional hooks configuration as JSON string
-   * @returns Promise resolving to the updated agent
+   * Deletes a user-defined execution profile
    */
-  async updateAgent(
-    id: number, 
-    name: string, 
-    icon: string, 
-    system_prompt: string, 
-    default_task?: string, 
-    model?: string,
-    hooks?: string
-  ): Promise<Agent> {
+  async deleteExecutionProfile(name: string): Promise<void> {
     try {
-      return await invoke<Agent>('update_agent', { 
-        id, 
-        name, 
-        icon, 
-        systemPrompt: system_prompt,
-        defaultTask: default_task,
-        model,
-        hooks
-      });
+      return await invoke<void>("delete_execution_profile", { name });
     } catch (error) {
-      console.error("Failed to update agent:", error);
+      console.error("Failed to delete execution profile:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes an agent
-   * @param id - The agent ID to delete
-   * @returns Promise resolving when the agent is deleted
+   * Starts a new session with an execution profile's permissions, limits and notifications
    */
-  async deleteAgent(id: number): Promise<void> {
+  async applyExecutionProfile(profile: string, run: ProfiledRun): Promise<RunHandle> {
     try {
-      return await invoke('delete_agent', { id });
+      return await invoke<RunHandle>("apply_execution_profile", { profile, run });
     } catch (error) {
-      console.error("Failed to delete agent:", error);
+      console.error("Failed to apply execution profile:", error);
       throw error;
     }
   },
 
   /**
-   * Gets a single agent by ID
-   * @param id - The agent ID
-   * @returns Promise resolving to the agent
+   * Returns the name of the execution profile a session ran with, if any
    */
-  async getAgent(id: number): Promise<Agent> {
+  async getSessionProfile(sessionId: string): Promise<string | null> {
     try {
-      return await invoke<Agent>('get_agent', { id });
+      return await invoke<string | null>("get_session_profile", { sessionId });
     } catch (error) {
-      console.error("Failed to get agent:", error);
+      console.error("Failed to get session profile:", error);
       throw error;
     }
   },
 
   /**
-   * Exports a single agent to JSON format
-   * @param id - The agent ID to export
-   * @returns Promise resolving to the JSON string
+   * Classifies a Claude launch error and lists recovery actions. Failed
+   * execute/continue/resume calls also emit `claude-spawn-failed` with the same payload.
+   * @param claudePath - The binary that was started, used for concrete fix commands
    */
-  async exportAgent(id: number): Promise<string> {
+  async diagnoseSpawnFailure(error: string, claudePath?: string): Promise<SpawnFailure> {
     try {
-      return await invoke<string>('export_agent', { id });
+      return await invoke<SpawnFailure>("diagnose_spawn_failure", { error, claudePath });
     } catch (error) {
-      console.error("Failed to export agent:", error);
+      console.error("Failed to diagnose spawn failure:", error);
       throw error;
     }
   },
 
   /**
-   * Imports an agent from JSON data
-   * @param jsonData - The JSON string containing the agent export
-   * @returns Promise resolving to the imported agent
+   * Returns how project paths are compared for case
    */
-  async importAgent(jsonData: string): Promise<Agent> {
+  async getPathCaseSensitivity(): Promise<PathCaseStatus> {
     try {
-      return await invoke<Agent>('import_agent', { jsonData });
+      return await invoke<PathCaseStatus>("get_path_case_sensitivity");
     } catch (error) {
-      console.error("Failed to import agent:", error);
+      console.error("Failed to get path case sensitivity:", error);
       throw error;
     }
   },
 
   /**
-   * Imports an agent from a file
-   * @param filePath - The path to the JSON file
-   * @returns Promise resolving to the imported agent
+   * Overrides case sensitivity detection for project paths, or restores "auto"
    */
-  async importAgentFromFile(filePath: string): Promise<Agent> {
+  async setPathCaseSensitivity(mode: CaseSensitivity): Promise<PathCaseStatus> {
     try {
-      return await invoke<Agent>('import_agent_from_file', { filePath });
+      return await invoke<PathCaseStatus>("set_path_case_sensitivity", { mode });
     } catch (error) {
-      console.error("Failed to import agent from file:", error);
+      console.error("Failed to set path case sensitivity:", error);
       throw error;
     }
   },
 
   /**
-   * Executes an agent
-   * @param agentId - The agent ID to execute
-   * @param projectPath - The project path to run the agent in
-   * @param task - The task description
-   * @param model - Optional model override
-   * @returns Promise resolving to the run ID when execution starts
+   * Moves memory, knowledge and trust data that case-insensitive matching attached
+   * to the wrong project
+   * @param dryRun - Report what would change without writing
    */
-  async executeAgent(agentId: number, projectPath: string, task: string, model?: string): Promise<number> {
+  async repairMergedProjects(dryRun?: boolean): Promise<ProjectRepairReport> {
     try {
-      return await invoke<number>('execute_agent', { agentId, projectPath, task, model });
+      return await invoke<ProjectRepairReport>("repair_merged_projects", { dryRun });
     } catch (error) {
-      console.error("Failed to execute agent:", error);
-      // Return a sentinel value to indicate error
-      throw new Error(`Failed to execute agent: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to repair merged projects:", error);
+      throw error;
     }
   },
 
   /**
-   * Lists agent runs with metrics
-   * @param agentId - Optional agent ID to filter runs
-   * @returns Promise resolving to an array of agent runs with metrics
+   * Lists the paths and project IDs excluded from the duplicate-project merge
    */
-  async listAgentRuns(agentId?: number): Promise<AgentRunWithMetrics[]> {
+  async listProjectMergeExclusions(): Promise<MergeExclusions> {
     try {
-      return await invoke<AgentRunWithMetrics[]>('list_agent_runs', { agentId });
+      return await invoke<MergeExclusions>("list_project_merge_exclusions");
     } catch (error) {
-      console.error("Failed to list agent runs:", error);
-      // Return empty array instead of throwing to prevent UI crashes
-      return [];
+      console.error("Failed to list project merge exclusions:", error);
+      throw error;
     }
   },
 
   /**
-   * Gets a single agent run by ID with metrics
-   * @param id - The run ID
-   * @returns Promise resolving to the agent run with metrics
+   * Lists every project directory for a path separately instead of merging them
    */
-  async getAgentRun(id: number): Promise<AgentRunWithMetrics> {
+  async excludeFromProjectMerge(path: string): Promise<MergeExclusions> {
     try {
-      return await invoke<AgentRunWithMetrics>('get_agent_run', { id });
+      return await invoke<MergeExclusions>("exclude_from_project_merge", { path });
     } catch (error) {
-      console.error("Failed to get agent run:", error);
-      throw new Error(`Failed to get agent run: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to exclude project from merge:", error);
+      throw error;
     }
   },
 
   /**
-   * Gets a single agent run by ID with real-time metrics from JSONL
-   * @param id - The run ID
-   * @returns Promise resolving to the agent run with metrics
+   * Lets an excluded path or project ID be merged again
    */
-  async getAgentRunWithRealTimeMetrics(id: number): Promise<AgentRunWithMetrics> {
+  async removeProjectMergeExclusion(entry: string): Promise<MergeExclusions> {
     try {
-      return await invoke<AgentRunWithMetrics>('get_agent_run_with_real_time_metrics', { id });
+      return await invoke<MergeExclusions>("remove_project_merge_exclusion", { entry });
     } catch (error) {
-      console.error("Failed to get agent run with real-time metrics:", error);
-      throw new Error(`Failed to get agent run with real-time metrics: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to remove project merge exclusion:", error);
+      throw error;
     }
   },
 
   /**
-   * Lists all currently running agent sessions
-   * @returns Promise resolving to list of running agent sessions
+   * Undoes the merge behind a listed project
+   * @returns The projects now listed separately
    */
-  async listRunningAgentSessions(): Promise<AgentRun[]> {
+  async splitMergedProject(projectId: string): Promise<Project[]> {
     try {
-      return await invoke<AgentRun[]>('list_running_sessions');
+      return await invoke<Project[]>("split_merged_project", { projectId });
     } catch (error) {
-      console.error("Failed to list running agent sessions:", error);
-      throw new Error(`Failed to list running agent sessions: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to split merged project:", error);
+      throw error;
     }
   },
 
   /**
-   * Kills a running agent session
-   * @param runId - The run ID to kill
-   * @returns Promise resolving to whether the session was successfully killed
+   * Copies a Claude CLI transcript from any location into ~/.claude/projects
+   * @param jsonlPath - The transcript to import
+   * @param projectPath - Project to file it under; defaults to the directory the transcript records
    */
-  async killAgentSession(runId: number): Promise<boolean> {
+  async importSessionFile(jsonlPath: string, projectPath?: string): Promise<ImportedSession> {
     try {
-      return await invoke<boolean>('kill_agent_session', { runId });
+      return await invoke<ImportedSession>("import_session_file", { jsonlPath, projectPath });
     } catch (error) {
-      console.error("Failed to kill agent session:", error);
-      throw new Error(`Failed to kill agent session: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to import session file:", error);
+      throw error;
     }
   },
 
   /**
-   * Gets the status of a specific agent session
-   * @param runId - The run ID to check
-   * @returns Promise resolving to the session status or null if not found
+   * Backs up the database, Claude settings, sessions and checkpoints to one file.
+   * Progress arrives as `backup-progress` events.
+   * @param passphrase - Encrypts the backup; stored secrets are only included when set
    */
-  async getSessionStatus(runId: number): Promise<string | null> {
+  async createFullBackup(dest: string, passphrase?: string): Promise<BackupSummary> {
     try {
-      return await invoke<string | null>('get_session_status', { runId });
+      return await invoke<BackupSummary>("create_full_backup", { dest, passphrase });
     } catch (error) {
-      console.error("Failed to get session status:", error);
-      throw new Error(`Failed to get session status: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to create full backup:", error);
+      throw error;
     }
   },
 
   /**
-   * Cleanup finished processes and update their status
-   * @returns Promise resolving to list of run IDs that were cleaned up
+   * Restores a full backup after verifying its integrity and versions
    */
-  async cleanupFinishedProcesses(): Promise<number[]> {
+  async restoreFullBackup(path: string, passphrase?: string): Promise<RestoreSummary> {
     try {
-      return await invoke<number[]>('cleanup_finished_processes');
+      return await invoke<RestoreSummary>("restore_full_backup", { path, passphrase });
     } catch (error) {
-      console.error("Failed to cleanup finished processes:", error);
-      throw new Error(`Failed to cleanup finished processes: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to restore full backup:", error);
+      throw error;
     }
   },
 
   /**
-   * Get real-time output for a running session (with live output fallback)
-   * @param runId - The run ID to get output for
-   * @returns Promise resolving to the current session output (JSONL format)
+   * Exports agents, provider presets, hooks, user slash commands, translation, router and
+   * execution config and execution profiles to one JSON file, for setting up another machine
+   * @param includeSecrets - Keep API keys and tokens in the file; blanked otherwise
    */
-  async getSessionOutput(runId: number): Promise<string> {
+  async exportWorkbenchSettings(dest: string, includeSecrets?: boolean): Promise<SettingsExportSummary> {
     try {
-      return await invoke<string>('get_session_output', { runId });
-    } catch (error) {
-      console.error("Failed to get session output:", error);
-      throw new Error(`Failed to get session output: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      return await invoke<SettingsExportSummary>("export_workbench_settings", { dest, includeSecrets });
+    } catch (error) {
+      console.error("Failed to export workbench settings:", error);
+      throw error;
     }
   },
 
   /**
-   * Get live output directly from process stdout buffer
-   * @param runId - The run ID to get live output for
-   * @returns Promise resolving to the current live output
+   * Imports a file written by exportWorkbenchSettings
+   * @param onConflict - What to do with items that already exist; "skip" by default
    */
-  async getLiveSessionOutput(runId: number): Promise<string> {
+  async importWorkbenchSettings(
+    path: string,
+    onConflict?: ConflictResolution
+  ): Promise<SettingsImportSummary> {
     try {
-      return await invoke<string>('get_live_session_output', { runId });
+      return await invoke<SettingsImportSummary>("import_workbench_settings", { path, onConflict });
     } catch (error) {
-      console.error("Failed to get live session output:", error);
-      throw new Error(`Failed to get live session output: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to import workbench settings:", error);
+      throw error;
     }
   },
 
   /**
-   * Start streaming real-time output for a running session
-   * @param runId - The run ID to stream output for
-   * @returns Promise that resolves when streaming starts
+   * Creates a capability token for the local API
+   * @param name - Label shown in the token list and audit trail
+   * @param scopes - Commands the token may invoke
+   * @param expiresInDays - Days until the token expires; omit for no expiry
+   * @returns The token and its secret, which is shown only this once
    */
-  async streamSessionOutput(runId: number): Promise<void> {
+  async createCapabilityToken(
+    name: string,
+    scopes: CapabilityScope[],
+    expiresInDays?: number
+  ): Promise<CreatedCapabilityToken> {
     try {
-      return await invoke<void>('stream_session_output', { runId });
+      return await invoke<CreatedCapabilityToken>("create_capability_token", { name, scopes, expiresInDays });
     } catch (error) {
-      console.error("Failed to start streaming session output:", error);
-      throw new Error(`Failed to start streaming session output: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to create capability token:", error);
+      throw error;
     }
   },
 
   /**
-   * Loads the JSONL history for a specific session
+   * Lists capability tokens, newest first
    */
-  async loadSessionHistory(sessionId: string, projectId: string): Promise<any[]> {
-    return invoke("load_session_history", { sessionId, projectId });
+  async listCapabilityTokens(): Promise<CapabilityToken[]> {
+    try {
+      return await invoke<CapabilityToken[]>("list_capability_tokens");
+    } catch (error) {
+      console.error("Failed to list capability tokens:", error);
+      throw error;
+    }
   },
 
   /**
-   * Loads the JSONL history for a specific agent session
-   * Similar to loadSessionHistory but searches across all project directories
-   * @param sessionId - The session ID (UUID)
-   * @returns Promise resolving to array of session messages
+   * Revokes a capability token
+   * @param tokenId - ID of the token
    */
-  async loadAgentSessionHistory(sessionId: string): Promise<any[]> {
+  async revokeCapabilityToken(tokenId: number): Promise<void> {
     try {
-      return await invoke<any[]>('load_agent_session_history', { sessionId });
+      return await invoke<void>("revoke_capability_token", { tokenId });
     } catch (error) {
-      console.error("Failed to load agent session history:", error);
+      console.error("Failed to revoke capability token:", error);
       throw error;
     }
   },
 
   /**
-   * Executes a new interactive Claude Code session with streaming output
+   * Lists recent local API invocations, allowed and refused
+   * @param limit - Maximum number of entries (default 100)
    */
-  async executeClaudeCode(projectPath: string, prompt: string, model: string): Promise<void> {
-    return invoke("execute_claude_code", { projectPath, prompt, model });
+  async listRemoteInvocationAudit(limit?: number): Promise<RemoteInvocationAudit[]> {
+    try {
+      return await invoke<RemoteInvocationAudit[]>("list_remote_invocation_audit", { limit });
+    } catch (error) {
+      console.error("Failed to list remote invocation audit:", error);
+      throw error;
+    }
   },
 
   /**
-   * Continues an existing Claude Code conversation with streaming output
+   * Gets the local API configuration and whether it is running
    */
-  async continueClaudeCode(projectPath: string, prompt: string, model: string): Promise<void> {
-    return invoke("continue_claude_code", { projectPath, prompt, model });
+  async getLocalApiStatus(): Promise<LocalApiStatus> {
+    try {
+      return await invoke<LocalApiStatus>("get_local_api_status");
+    } catch (error) {
+      console.error("Failed to get local API status:", error);
+      throw error;
+    }
   },
 
   /**
-   * Resumes an existing Claude Code session by ID with streaming output
+   * Enables or disables the loopback-only local API
+   * @param enabled - Whether the API should run
+   * @param port - Port to listen on (default 3460); without one, a configured port
+   * another app holds is swapped for a free one
    */
-  async resumeClaudeCode(projectPath: string, sessionId: string, prompt: string, model: string): Promise<void> {
-    return invoke("resume_claude_code", { projectPath, sessionId, prompt, model });
+  async setLocalApiEnabled(enabled: boolean, port?: number): Promise<LocalApiStatus> {
+    try {
+      return await invoke<LocalApiStatus>("set_local_api_enabled", { enabled, port });
+    } catch (error) {
+      console.error("Failed to set local API state:", error);
+      throw error;
+    }
   },
 
   /**
-   * Cancels the currently running Claude Code execution
-   * @param sessionId - Optional session ID to cancel a specific session
+   * Serves Prometheus metrics at `/metrics` on the local API. Scrapers authenticate
+   * with a capability token that has the `metrics:read` scope.
    */
-  async cancelClaudeExecution(sessionId?: string): Promise<void> {
-    return invoke("cancel_claude_execution", { sessionId });
+  async setMetricsExporterEnabled(enabled: boolean): Promise<LocalApiStatus> {
+    try {
+      return await invoke<LocalApiStatus>("set_metrics_exporter_enabled", { enabled });
+    } catch (error) {
+      console.error("Failed to set metrics exporter state:", error);
+      throw error;
+    }
   },
 
   /**
-   * Lists all currently running Claude sessions
-   * @returns Promise resolving to list of running Claude sessions
+   * Where claude-code-router and the local API are bound, and whether another app
+   * holds either port
    */
-  async listRunningClaudeSessions(): Promise<any[]> {
-    return invoke("list_running_claude_sessions");
+  async getServicePorts(): Promise<ServicePort[]> {
+    try {
+      return await invoke<ServicePort[]>("get_service_ports");
+    } catch (error) {
+      console.error("Failed to get service ports:", error);
+      throw error;
+    }
   },
 
   /**
-   * Gets live output from a Claude session
-   * @param sessionId - The session ID to get output for
-   * @returns Promise resolving to the current live output
+   * Watches a project for file changes. Debounced batches arrive as `files-changed`
+   * events and run the project's OnFileChange hooks.
+   * @param projectPath - Project directory to watch
+   * @param sessionId - Session passed to the hooks, if any
    */
-  async getClaudeSessionOutput(sessionId: string): Promise<string> {
-    return invoke("get_claude_session_output", { sessionId });
+  async watchProjectPath(projectPath: string, sessionId?: string): Promise<void> {
+    try {
+      await invoke("watch_project_path", { projectPath, sessionId });
+    } catch (error) {
+      console.error("Failed to watch project path:", error);
+      throw error;
+    }
   },
 
   /**
-   * Lists files and directories in a given path
+   * Stops watching a project
+   * @returns false if the project was not watched
    */
-  async listDirectoryContents(directoryPath: string): Promise<FileEntry[]> {
-    return invoke("list_directory_contents", { directoryPath });
+  async unwatchProjectPath(projectPath: string): Promise<boolean> {
+    try {
+      return await invoke<boolean>("unwatch_project_path", { projectPath });
+    } catch (error) {
+      console.error("Failed to unwatch project path:", error);
+      throw error;
+    }
   },
 
   /**
-   * Searches for files and directories matching a pattern
+   * Token and cost burn rates of running sessions, fastest-spending first.
+   * Updates arrive as `burn-rate:<session_id>` events every few seconds.
    */
-  async searchFiles(basePath: string, query: string): Promise<FileEntry[]> {
-    return invoke("search_files", { basePath, query });
+  async getActiveBurnRates(): Promise<BurnRate[]> {
+    try {
+      return await invoke<BurnRate[]>("get_active_burn_rates");
+    } catch (error) {
+      console.error("Failed to get active burn rates:", error);
+      throw error;
+    }
   },
 
   /**
-   * Gets overall usage statistics
-   * @returns Promise resolving to usage statistics
+   * How long execution idempotency keys are remembered, in seconds
    */
-  async getUsageStats(): Promise<UsageStats> {
+  async getRunDedupeWindow(): Promise<number> {
     try {
-      return await invoke<UsageStats>("get_usage_stats");
+      return await invoke<number>("get_run_dedupe_window");
     } catch (error) {
-      console.error("Failed to get usage stats:", error);
+      console.error("Failed to get run dedupe window:", error);
       throw error;
     }
   },
 
   /**
-   * Get usage overview statistics - fast loading with essential metrics only
+   * Sets how long execution idempotency keys are remembered; 0 turns deduplication off
    */
-  async getUsageOverview(): Promise<UsageOverview> {
+  async setRunDedupeWindow(seconds: number): Promise<number> {
     try {
-      return await invoke<UsageOverview>("get_usage_overview");
+      return await invoke<number>("set_run_dedupe_window", { seconds });
     } catch (error) {
-      console.error("Failed to get usage overview:", error);
+      console.error("Failed to set run dedupe window:", error);
       throw error;
     }
   },
 
   /**
-   * Gets usage statistics filtered by date range
-   * @param startDate - Start date (ISO format)
-   * @param endDate - End date (ISO format)
-   * @returns Promise resolving to usage statistics
+   * Status of a run started by executeClaudeCode, continueClaudeCode or resumeClaudeCode
+   * @param runId - run_id of the RunHandle
    */
-  async getUsageByDateRange(startDate: string, endDate: string): Promise<UsageStats> {
+  async getRunStatus(runId: number): Promise<RunStatus> {
     try {
-      return await invoke<UsageStats>("get_usage_by_date_range", { startDate, endDate });
+      return await invoke<RunStatus>("get_run_status", { runId });
     } catch (error) {
-      console.error("Failed to get usage by date range:", error);
+      console.error("Failed to get run status:", error);
       throw error;
     }
   },
 
   /**
-   * Gets usage statistics grouped by session
-   * @param since - Optional start date (YYYYMMDD)
-   * @param until - Optional end date (YYYYMMDD)
-   * @param order - Optional sort order ('asc' or 'desc')
-   * @returns Promise resolving to an array of session usage data
+   * Creates a task grouping the sessions and agent runs of one piece of work
    */
-  async getSessionStats(
-    since?: string,
-    until?: string,
-    order?: "asc" | "desc"
-  ): Promise<ProjectUsage[]> {
+  async createTask(name: string, projectPath: string, description?: string): Promise<Task> {
     try {
-      return await invoke<ProjectUsage[]>("get_session_stats", {
-        since,
-        until,
-        order,
-      });
+      return await invoke<Task>("create_task", { name, projectPath, description });
     } catch (error) {
-      console.error("Failed to get session stats:", error);
+      console.error("Failed to create task:", error);
       throw error;
     }
   },
 
   /**
-   * Gets usage statistics for today only
-   * @returns Promise resolving to today's usage statistics
+   * Lists tasks, newest first
+   * @param projectPath - Only tasks of this project
    */
-  async getTodayUsageStats(): Promise<UsageStats> {
+  async listTasks(projectPath?: string): Promise<Task[]> {
     try {
-      return await invoke<UsageStats>("get_today_usage_stats");
+      return await invoke<Task[]>("list_tasks", { projectPath });
     } catch (error) {
-      console.error("Failed to get today's usage stats:", error);
+      console.error("Failed to list tasks:", error);
       throw error;
     }
   },
 
   /**
-   * Gets usage statistics grouped by API Base URL
-   * @returns Promise resolving to array of API Base URL usage statistics
+   * Deletes a task; its sessions and agent runs are kept
    */
-  async getUsageByApiBaseUrl(): Promise<ApiBaseUrlUsage[]> {
+  async deleteTask(taskId: number): Promise<void> {
     try {
-      return await invoke<ApiBaseUrlUsage[]>("get_usage_by_api_base_url");
+      return await invoke<void>("delete_task", { taskId });
     } catch (error) {
-      console.error("Failed to get usage by API Base URL:", error);
+      console.error("Failed to delete task:", error);
       throw error;
     }
   },
 
   /**
-   * Gets detailed usage entries with optional filtering
-   * @param limit - Optional limit for number of entries
-   * @returns Promise resolving to array of usage entries
+   * Assigns a session (by session id) or agent run (by run id) to a task,
+   * moving it out of any task it was in
    */
-  async getUsageDetails(limit?: number): Promise<UsageEntry[]> {
+  async assignToTask(taskId: number, kind: TaskMemberKind, memberId: string): Promise<Task> {
     try {
-      return await invoke<UsageEntry[]>("get_usage_details", { limit });
+      return await invoke<Task>("assign_to_task", { taskId, kind, memberId });
     } catch (error) {
-      console.error("Failed to get usage details:", error);
+      console.error("Failed to assign to task:", error);
       throw error;
     }
   },
 
   /**
-   * Gets cache tokens for a specific session
-   * @param sessionId - The session ID to get cache tokens for
-   * @returns Promise resolving to session cache tokens
+   * Takes a session or agent run out of its task
    */
-  async getSessionCacheTokens(sessionId: string): Promise<SessionCacheTokens> {
+  async removeFromTask(kind: TaskMemberKind, memberId: string): Promise<void> {
     try {
-      return await invoke<SessionCacheTokens>("get_session_cache_tokens", { sessionId });
+      return await invoke<void>("remove_from_task", { kind, memberId });
     } catch (error) {
-      console.error("Failed to get session cache tokens:", error);
+      console.error("Failed to remove from task:", error);
       throw error;
     }
   },
 
   /**
-   * Creates a checkpoint for the current session state
+   * Transcripts, cost, file changes and checkpoints across all of a task's sessions
    */
-  async createCheckpoint(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    messageIndex?: number,
-    description?: string
-  ): Promise<CheckpointResult> {
-    return invoke("create_checkpoint", {
-      sessionId,
-      projectId,
-      projectPath,
-      messageIndex,
-      description
-    });
+  async getTaskOverview(taskId: number): Promise<TaskOverview> {
+    try {
+      return await invoke<TaskOverview>("get_task_overview", { taskId });
+    } catch (error) {
+      console.error("Failed to get task overview:", error);
+      throw error;
+    }
   },
 
   /**
-   * Restores a session to a specific checkpoint with specified restore mode
-   * @param restoreMode - 'conversation_only' | 'code_only' | 'both' (defaults to 'both')
+   * Crash reports on disk, newest first
    */
-  async restoreCheckpoint(
-    checkpointId: string,
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    restoreMode?: 'conversation_only' | 'code_only' | 'both'
-  ): Promise<CheckpointResult> {
-    return invoke("restore_checkpoint", {
-      checkpointId,
-      sessionId,
-      projectId,
-      projectPath,
-      restoreMode: restoreMode || 'both'
-    });
+  async listCrashReports(): Promise<CrashReportSummary[]> {
+    try {
+      return await invoke<CrashReportSummary[]>("list_crash_reports");
+    } catch (error) {
+      console.error("Failed to list crash reports:", error);
+      throw error;
+    }
   },
 
   /**
-   * Lists all checkpoints for a session
+   * Render a crash report as Markdown for a bug report
+   * @param reportId - The report to export
+   * @param dest - Optional file to also write the Markdown to
    */
-  async listCheckpoints(
-    sessionId: string,
-    projectId: string,
-    projectPath: string
-  ): Promise<Checkpoint[]> {
-    return invoke("list_checkpoints", {
-      sessionId,
-      projectId,
-      projectPath
-    });
+  async exportCrashReport(reportId: string, dest?: string): Promise<string> {
+    try {
+      return await invoke<string>("export_crash_report", { reportId, dest });
+    } catch (error) {
+      console.error("Failed to export crash report:", error);
+      throw error;
+    }
   },
 
   /**
-   * Forks a new timeline branch from a checkpoint
+   * How long each startup step took, in the order they started
    */
-  async forkFromCheckpoint(
-    checkpointId: string,
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    newSessionId: string,
-    description?: string
-  ): Promise<CheckpointResult> {
-    return invoke("fork_from_checkpoint", {
-      checkpointId,
-      sessionId,
-      projectId,
-      projectPath,
-      newSessionId,
-      description
-    });
+  async getStartupTimings(): Promise<StartupTimings> {
+    try {
+      return await invoke<StartupTimings>("get_startup_timings");
+    } catch (error) {
+      console.error("Failed to get startup timings:", error);
+      throw error;
+    }
   },
 
   /**
-   * Gets the timeline for a session
+   * Which optional subsystems are enabled
    */
-  async getSessionTimeline(
-    sessionId: string,
-    projectId: string,
-    projectPath: string
-  ): Promise<SessionTimeline> {
-    return invoke("get_session_timeline", {
-      sessionId,
-      projectId,
-      projectPath
-    });
+  async getStartupConfig(): Promise<StartupConfig> {
+    try {
+      return await invoke<StartupConfig>("get_startup_config");
+    } catch (error) {
+      console.error("Failed to get startup config:", error);
+      throw error;
+    }
   },
 
   /**
-   * Updates checkpoint settings for a session
+   * Turn optional subsystems on or off; takes effect right away
+   * @param config - The subsystems to enable
    */
-  async updateCheckpointSettings(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    autoCheckpointEnabled: boolean,
-    checkpointStrategy: CheckpointStrategy
-  ): Promise<void> {
-    return invoke("update_checkpoint_settings", {
-      sessionId,
-      projectId,
-      projectPath,
-      autoCheckpointEnabled,
-      checkpointStrategy
-    });
+  async setStartupConfig(config: StartupConfig): Promise<StartupConfig> {
+    try {
+      return await invoke<StartupConfig>("set_startup_config", { config });
+    } catch (error) {
+      console.error("Failed to set startup config:", error);
+      throw error;
+    }
   },
 
   /**
-   * Gets diff between two checkpoints
+   * Installed plugins with their manifest, state and load errors
    */
-  async getCheckpointDiff(
-    fromCheckpointId: string,
-    toCheckpointId: string,
-    sessionId: string,
-    projectId: string
-  ): Promise<CheckpointDiff> {
+  async listPlugins(): Promise<PluginInfo[]> {
     try {
-      return await invoke<CheckpointDiff>("get_checkpoint_diff", {
-        fromCheckpointId,
-        toCheckpointId,
-        sessionId,
-        projectId
-      });
+      return await invoke<PluginInfo[]>("list_plugins");
     } catch (error) {
-      console.error("Failed to get checkpoint diff:", error);
+      console.error("Failed to list plugins:", error);
       throw error;
     }
   },
 
   /**
-   * Tracks a message for checkpointing
+   * Directory plugins are installed into
    */
-  async trackCheckpointMessage(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    message: string
-  ): Promise<void> {
+  async getPluginsDir(): Promise<string> {
     try {
-      await invoke("track_checkpoint_message", {
-        sessionId,
-        projectId,
-        projectPath,
-        message
-      });
+      return await invoke<string>("get_plugins_dir");
     } catch (error) {
-      console.error("Failed to track checkpoint message:", error);
+      console.error("Failed to get plugins directory:", error);
       throw error;
     }
   },
 
   /**
-   * Checks if auto-checkpoint should be triggered
+   * Enable or disable a plugin; enabling also clears its failure count
    */
-  async checkAutoCheckpoint(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    message: string
-  ): Promise<boolean> {
+  async setPluginEnabled(pluginId: string, enabled: boolean): Promise<void> {
     try {
-      return await invoke<boolean>("check_auto_checkpoint", {
-        sessionId,
-        projectId,
-        projectPath,
-        message
-      });
+      return await invoke<void>("set_plugin_enabled", { pluginId, enabled });
     } catch (error) {
-      console.error("Failed to check auto checkpoint:", error);
+      console.error("Failed to set plugin enabled:", error);
       throw error;
     }
   },
 
   /**
-   * Triggers cleanup of old checkpoints
+   * Run a command a plugin declares in its manifest
    */
-  async cleanupOldCheckpoints(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    keepCount: number
-  ): Promise<number> {
+  async invokePluginCommand(
+    pluginId: string,
+    command: string,
+    input?: any,
+    projectPath?: string
+  ): Promise<PluginCallResult> {
     try {
-      return await invoke<number>("cleanup_old_checkpoints", {
-        sessionId,
-        projectId,
-        projectPath,
-        keepCount
-      });
+      return await invoke<PluginCallResult>("invoke_plugin_command", { pluginId, command, input, projectPath });
     } catch (error) {
-      console.error("Failed to cleanup old checkpoints:", error);
+      console.error("Failed to invoke plugin command:", error);
       throw error;
     }
   },
 
   /**
-   * Cleanup checkpoints older than specified days (default: 30 days per Claude Code docs)
+   * Run a plugin's analyzer against a project
    */
-  async cleanupOldCheckpointsByAge(
-    sessionId: string,
-    projectId: string,
+  async runPluginAnalyzer(
+    pluginId: string,
+    analyzer: string,
     projectPath: string,
-    days?: number
-  ): Promise<number> {
+    input?: any
+  ): Promise<PluginCallResult> {
     try {
-      return await invoke<number>("cleanup_old_checkpoints_by_age", {
-        sessionId,
-        projectId,
-        projectPath,
-        days: days ?? 30 // Default to 30 days
-      });
+      return await invoke<PluginCallResult>("run_plugin_analyzer", { pluginId, analyzer, projectPath, input });
     } catch (error) {
-      console.error("Failed to cleanup old checkpoints by age:", error);
+      console.error("Failed to run plugin analyzer:", error);
       throw error;
     }
   },
 
   /**
-   * Gets checkpoint settings for a session
+   * Create a git worktree on a new branch for a session to run in
+   * @param projectPath - A directory inside the repository
+   * @param name - Optional name used for the branch and directory
+   * @param baseBranch - Branch to start from; defaults to the checked-out branch
    */
-  async getCheckpointSettings(
-    sessionId: string,
-    projectId: string,
-    projectPath: string
-  ): Promise<{
-    auto_checkpoint_enabled: boolean;
-    checkpoint_strategy: CheckpointStrategy;
-    total_checkpoints: number;
-    current_checkpoint_id?: string;
-  }> {
+  async createSessionWorktree(projectPath: string, name?: string, baseBranch?: string): Promise<SessionWorktree> {
     try {
-      return await invoke("get_checkpoint_settings", {
-        sessionId,
-        projectId,
-        projectPath
-      });
+      return await invoke<SessionWorktree>("create_session_worktree", { projectPath, name, baseBranch });
     } catch (error) {
-      console.error("Failed to get checkpoint settings:", error);
+      console.error("Failed to create session worktree:", error);
       throw error;
     }
   },
 
   /**
-   * Clears checkpoint manager for a session (cleanup on session end)
+   * Session worktrees that were not removed, optionally of one repository
    */
-  async clearCheckpointManager(sessionId: string): Promise<void> {
+  async listSessionWorktrees(projectPath?: string): Promise<SessionWorktree[]> {
     try {
-      await invoke("clear_checkpoint_manager", { sessionId });
+      return await invoke<SessionWorktree[]>("list_session_worktrees", { projectPath });
     } catch (error) {
-      console.error("Failed to clear checkpoint manager:", error);
+      console.error("Failed to list session worktrees:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // MESSAGE-LEVEL OPERATIONS (Fine-grained Undo/Redo)
-  // ============================================================================
-
   /**
-   * Undo the last N messages (default 1)
-   * Creates a safety checkpoint before undoing
+   * Commit the session's changes and merge its branch into the base branch.
+   * A conflicting merge is aborted and rejected.
    */
-  async messageUndo(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    count?: number
-  ): Promise<CheckpointResult> {
-    return invoke("message_undo", {
-      sessionId,
-      projectId,
-      projectPath,
-      count
-    });
+  async mergeSessionWorktree(
+    worktreeId: number,
+    commitMessage?: string,
+    removeAfter?: boolean
+  ): Promise<WorktreeMergeResult> {
+    try {
+      return await invoke<WorktreeMergeResult>("merge_session_worktree", { worktreeId, commitMessage, removeAfter });
+    } catch (error) {
+      console.error("Failed to merge session worktree:", error);
+      throw error;
+    }
   },
 
   /**
-   * Truncate messages to a specific index
-   * All messages after this index will be removed
+   * Remove a session worktree and its branch; unmerged work needs force
    */
-  async messageTruncateToIndex(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    messageIndex: number
-  ): Promise<CheckpointResult> {
-    return invoke("message_truncate_to_index", {
-      sessionId,
-      projectId,
-      projectPath,
-      messageIndex
-    });
+  async removeSessionWorktree(worktreeId: number, force?: boolean): Promise<void> {
+    try {
+      return await invoke<void>("remove_session_worktree", { worktreeId, force });
+    } catch (error) {
+      console.error("Failed to remove session worktree:", error);
+      throw error;
+    }
   },
 
   /**
-   * Edit a specific message and regenerate from that point
+   * Find lock and state files left behind by the Claude CLI or a crashed workbench
+   * (dead-process locks, long-untouched lock files, empty session files) and remove them
+   * unless dryRun is set; with projectId only that project's sessions are checked
    */
-  async messageEdit(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    messageIndex: number,
-    newContent: string
-  ): Promise<CheckpointResult> {
-    return invoke("message_edit", {
-      sessionId,
-      projectId,
-      projectPath,
-      messageIndex,
-      newContent
-    });
+  async cleanStaleClaudeState(projectId?: string, dryRun?: boolean): Promise<StaleStateReport> {
+    try {
+      return await invoke<StaleStateReport>("clean_stale_claude_state", { projectId, dryRun });
+    } catch (error) {
+      console.error("Failed to clean stale Claude state:", error);
+      throw error;
+    }
   },
 
   /**
-   * Delete a specific message by index
+   * Exclude a message from, or include it back in, the context of later resumes; index is
+   * the message's position as returned by loadSessionHistory. Resuming a session with
+   * exclusions continues from a filtered copy of its transcript.
    */
-  async messageDelete(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    messageIndex: number
-  ): Promise<CheckpointResult> {
-    return invoke("message_delete", {
-      sessionId,
-      projectId,
-      projectPath,
-      messageIndex
-    });
+  async setMessageContextInclusion(sessionId: string, index: number, included: boolean): Promise<ContextExclusion[]> {
+    try {
+      return await invoke<ContextExclusion[]>("set_message_context_inclusion", { sessionId, index, included });
+    } catch (error) {
+      console.error("Failed to set message context inclusion:", error);
+      throw error;
+    }
   },
 
   /**
-   * Get the current number of messages in a session
+   * List the messages of a session excluded from the context of later resumes
    */
-  async messageGetCount(
-    sessionId: string,
-    projectId: string,
-    projectPath: string
-  ): Promise<number> {
-    return invoke("message_get_count", {
-      sessionId,
-      projectId,
-      projectPath
-    });
+  async listContextExclusions(sessionId: string): Promise<ContextExclusion[]> {
+    try {
+      return await invoke<ContextExclusion[]>("list_context_exclusions", { sessionId });
+    } catch (error) {
+      console.error("Failed to list context exclusions:", error);
+      throw error;
+    }
   },
 
   /**
-   * Get a specific message by index
+   * Get the response language of a project and, if given, of one of its sessions
    */
-  async messageGetByIndex(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    messageIndex: number
-  ): Promise<string> {
-    return invoke("message_get_by_index", {
-      sessionId,
-      projectId,
-      projectPath,
-      messageIndex
-    });
+  async getResponseLanguage(projectPath: string, sessionId?: string): Promise<ResponseLanguageSetting> {
+    try {
+      return await invoke<ResponseLanguageSetting>("get_response_language", { projectPath, sessionId });
+    } catch (error) {
+      console.error("Failed to get response language:", error);
+      throw error;
+    }
   },
 
   /**
-   * Get all messages in a session
+   * Set the language runs in a project answer in (e.g. "zh" or "Japanese"); null clears it
    */
-  async messageGetAll(
-    sessionId: string,
-    projectId: string,
-    projectPath: string
-  ): Promise<string[]> {
-    return invoke("message_get_all", {
-      sessionId,
-      projectId,
-      projectPath
-    });
+  async setProjectResponseLanguage(projectPath: string, language: string | null): Promise<ResponseLanguageSetting> {
+    try {
+      return await invoke<ResponseLanguageSetting>("set_project_response_language", { projectPath, language });
+    } catch (error) {
+      console.error("Failed to set project response language:", error);
+      throw error;
+    }
   },
 
   /**
-   * Tracks a batch of messages for a session for checkpointing
-   */
-  trackSessionMessages: (
-    sessionId: string, 
-    projectId: string, 
-    projectPath: string, 
-    messages: string[]
-  ): Promise<void> =>
-    invoke("track_session_messages", { sessionId, projectId, projectPath, messages }),
-
-  /**
-   * Adds a new MCP server
+   * Override the project's response language for one session; null means no preference,
+   * inherit drops the override
    */
-  async mcpAdd(
-    name: string,
-    transport: string,
-    command?: string,
-    args: string[] = [],
-    env: Record<string, string> = {},
-    url?: string,
-    scope: string = "local"
-  ): Promise<AddServerResult> {
+  async setSessionResponseLanguage(
+    projectPath: string,
+    sessionId: string,
+    language: string | null,
+    inherit?: boolean
+  ): Promise<ResponseLanguageSetting> {
     try {
-      return await invoke<AddServerResult>("mcp_add", {
-        name,
-        transport,
-        command,
-        args,
-        env,
-        url,
-        scope
+      return await invoke<ResponseLanguageSetting>("set_session_response_language", {
+        projectPath,
+        sessionId,
+        language,
+        inherit,
       });
     } catch (error) {
-      console.error("Failed to add MCP server:", error);
+      console.error("Failed to set session response language:", error);
       throw error;
     }
   },
 
   /**
-   * Lists all configured MCP servers
+   * Suggest a response language from a session's first prompt
    */
-  async mcpList(): Promise<MCPServer[]> {
+  async suggestResponseLanguage(
+    projectPath: string,
+    prompt: string,
+    sessionId?: string
+  ): Promise<ResponseLanguageSuggestion> {
     try {
-      console.log("API: Calling mcp_list...");
-      const result = await invoke<MCPServer[]>("mcp_list");
-      console.log("API: mcp_list returned:", result);
-      return result;
+      return await invoke<ResponseLanguageSuggestion>("suggest_response_language", { projectPath, sessionId, prompt });
     } catch (error) {
-      console.error("API: Failed to list MCP servers:", error);
+      console.error("Failed to suggest response language:", error);
       throw error;
     }
   },
 
   /**
-   * Gets details for a specific MCP server
+   * Schedule recurring runs of an agent
    */
-  async mcpGet(name: string): Promise<MCPServer> {
+  async createAgentSchedule(schedule: AgentScheduleInput): Promise<AgentSchedule> {
     try {
-      return await invoke<MCPServer>("mcp_get", { name });
+      return await invoke<AgentSchedule>("create_agent_schedule", { schedule });
     } catch (error) {
-      console.error("Failed to get MCP server:", error);
+      console.error("Failed to create agent schedule:", error);
       throw error;
     }
   },
 
   /**
-   * Removes an MCP server
+   * Replace a schedule's settings; its next run is recomputed from now
    */
-  async mcpRemove(name: string): Promise<string> {
+  async updateAgentSchedule(id: number, schedule: AgentScheduleInput): Promise<AgentSchedule> {
     try {
-      return await invoke<string>("mcp_remove", { name });
+      return await invoke<AgentSchedule>("update_agent_schedule", { id, schedule });
     } catch (error) {
-      console.error("Failed to remove MCP server:", error);
+      console.error("Failed to update agent schedule:", error);
       throw error;
     }
   },
 
-  /**
-   * Adds an MCP server from JSON configuration
-   */
-  async mcpAddJson(name: string, jsonConfig: string, scope: string = "local"): Promise<AddServerResult> {
+  async deleteAgentSchedule(id: number): Promise<void> {
     try {
-      return await invoke<AddServerResult>("mcp_add_json", { name, jsonConfig, scope });
+      return await invoke("delete_agent_schedule", { id });
     } catch (error) {
-      console.error("Failed to add MCP server from JSON:", error);
+      console.error("Failed to delete agent schedule:", error);
       throw error;
     }
   },
 
   /**
-   * Imports MCP servers from Claude Desktop
+   * List schedules, optionally of one agent
    */
-  async mcpAddFromClaudeDesktop(scope: string = "local"): Promise<ImportResult> {
+  async listAgentSchedules(agentId?: number): Promise<AgentSchedule[]> {
     try {
-      return await invoke<ImportResult>("mcp_add_from_claude_desktop", { scope });
+      return await invoke<AgentSchedule[]>("list_agent_schedules", { agentId });
     } catch (error) {
-      console.error("Failed to import from Claude Desktop:", error);
+      console.error("Failed to list agent schedules:", error);
       throw error;
     }
   },
 
   /**
-   * Starts Claude Code as an MCP server
+   * Recent launches of a schedule, newest first
    */
-  async mcpServe(): Promise<string> {
+  async listAgentScheduleRuns(scheduleId: number): Promise<AgentScheduleRun[]> {
     try {
-      return await invoke<string>("mcp_serve");
+      return await invoke<AgentScheduleRun[]>("list_agent_schedule_runs", { scheduleId });
     } catch (error) {
-      console.error("Failed to start MCP server:", error);
+      console.error("Failed to list agent schedule runs:", error);
       throw error;
     }
   },
 
   /**
-   * Tests connection to an MCP server
+   * Runs a prompt once, in a new session, at `runAt` (ISO 8601). With `maxCostUsd`
+   * the prompt is skipped if its estimated cost is already over the cap, and the run
+   * is stopped once it spends more.
    */
-  async mcpTestConnection(name: string): Promise<string> {
+  async schedulePrompt(
+    projectPath: string,
+    prompt: string,
+    runAt: string,
+    model: string,
+    maxCostUsd?: number
+  ): Promise<ScheduledPrompt> {
     try {
-      return await invoke<string>("mcp_test_connection", { name });
+      return await invoke<ScheduledPrompt>("schedule_prompt", {
+        projectPath,
+        prompt,
+        runAt,
+        model,
+        maxCostUsd,
+      });
     } catch (error) {
-      console.error("Failed to test MCP connection:", error);
+      console.error("Failed to schedule prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Exports MCP server configuration from .claude.json
+   * Replaces the settings of a prompt that has not started yet
    */
-  async mcpExportConfig(): Promise<string> {
-    try {
-      return await invoke<string>("mcp_export_config");
+  async updateScheduledPrompt(
+    id: number,
+    projectPath: string,
+    prompt: string,
+    runAt: string,
+    model: string,
+    maxCostUsd?: number
+  ): Promise<ScheduledPrompt> {
+    try {
+      return await invoke<ScheduledPrompt>("update_scheduled_prompt", {
+        id,
+        projectPath,
+        prompt,
+        runAt,
+        model,
+        maxCostUsd,
+      });
     } catch (error) {
-      console.error("Failed to export MCP configuration:", error);
+      console.error("Failed to update scheduled prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Resets project-scoped server approval choices
+   * Cancels a prompt that has not started yet
    */
-  async mcpResetProjectChoices(): Promise<string> {
+  async cancelScheduledPrompt(id: number): Promise<ScheduledPrompt> {
     try {
-      return await invoke<string>("mcp_reset_project_choices");
+      return await invoke<ScheduledPrompt>("cancel_scheduled_prompt", { id });
     } catch (error) {
-      console.error("Failed to reset project choices:", error);
+      console.error("Failed to cancel scheduled prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Gets the status of MCP servers
+   * Lists pending and running scheduled prompts, plus finished ones when asked
    */
-  async mcpGetServerStatus(): Promise<Record<string, ServerStatus>> {
+  async listScheduledPrompts(includeFinished?: boolean): Promise<ScheduledPrompt[]> {
     try {
-      return await invoke<Record<string, ServerStatus>>("mcp_get_server_status");
+      return await invoke<ScheduledPrompt[]>("list_scheduled_prompts", { includeFinished });
     } catch (error) {
-      console.error("Failed to get server status:", error);
+      console.error("Failed to list scheduled prompts:", error);
       throw error;
     }
   },
 
   /**
-   * Reads .mcp.json from the current project
+   * Runs that finished while the window was hidden or showing another project,
+   * newest first
    */
-  async mcpReadProjectConfig(projectPath: string): Promise<MCPProjectConfig> {
+  async listUnseenCompletions(): Promise<UnseenCompletion[]> {
     try {
-      return await invoke<MCPProjectConfig>("mcp_read_project_config", { projectPath });
+      return await invoke<UnseenCompletion[]>("list_unseen_completions");
     } catch (error) {
-      console.error("Failed to read project MCP config:", error);
+      console.error("Failed to list unseen completions:", error);
       throw error;
     }
   },
 
   /**
-   * Saves .mcp.json to the current project
+   * Clears an unseen completion (by session ID, or `agent-run:<id>`)
+   * @returns The number of completions still unseen
    */
-  async mcpSaveProjectConfig(projectPath: string, config: MCPProjectConfig): Promise<string> {
+  async markSessionSeen(id: string): Promise<number> {
     try {
-      return await invoke<string>("mcp_save_project_config", { projectPath, config });
+      return await invoke<number>("mark_session_seen", { id });
     } catch (error) {
-      console.error("Failed to save project MCP config:", error);
+      console.error("Failed to mark session seen:", error);
       throw error;
     }
   },
 
   /**
-   * Get the stored Claude binary path from settings
-   * @returns Promise resolving to the path if set, null otherwise
+   * Tells the backend which project the window shows, so runs finishing in other
+   * projects are counted as unseen
    */
-  async getClaudeBinaryPath(): Promise<string | null> {
+  async setActiveProject(projectPath: string | null): Promise<void> {
     try {
-      return await invoke<string | null>("get_claude_binary_path");
+      return await invoke<void>("set_active_project", { projectPath });
     } catch (error) {
-      console.error("Failed to get Claude binary path:", error);
+      console.error("Failed to set active project:", error);
       throw error;
     }
   },
 
   /**
-   * Set the Claude binary path in settings
-   * @param path - The absolute path to the Claude binary
-   * @returns Promise resolving when the path is saved
+   * How much the `accessibility` event channel announces
    */
-  async setClaudeBinaryPath(path: string): Promise<void> {
+  async getAccessibilityVerbosity(): Promise<AccessibilityVerbosity> {
     try {
-      return await invoke<void>("set_claude_binary_path", { path });
+      return await invoke<AccessibilityVerbosity>("get_accessibility_verbosity");
     } catch (error) {
-      console.error("Failed to set Claude binary path:", error);
+      console.error("Failed to get accessibility verbosity:", error);
       throw error;
     }
   },
 
   /**
-   * List all available Claude installations on the system
-   * @returns Promise resolving to an array of Claude installations
+   * Set how much the `accessibility` event channel announces.
+   * Announcements arrive as `accessibility` events.
    */
-  async listClaudeInstallations(): Promise<ClaudeInstallation[]> {
+  async setAccessibilityVerbosity(verbosity: AccessibilityVerbosity): Promise<AccessibilityVerbosity> {
     try {
-      return await invoke<ClaudeInstallation[]>("list_claude_installations");
+      return await invoke<AccessibilityVerbosity>("set_accessibility_verbosity", { verbosity });
     } catch (error) {
-      console.error("Failed to list Claude installations:", error);
+      console.error("Failed to set accessibility verbosity:", error);
       throw error;
     }
   },
 
-  // Storage API methods
-
   /**
-   * Lists all tables in the SQLite database
-   * @returns Promise resolving to an array of table information
+   * Gets when system sleep is blocked during runs, and whether it is blocked now
    */
-  async storageListTables(): Promise<any[]> {
+  async getPowerPolicy(): Promise<PowerStatus> {
     try {
-      return await invoke<any[]>("storage_list_tables");
+      return await invoke<PowerStatus>("get_power_policy");
     } catch (error) {
-      console.error("Failed to list tables:", error);
+      console.error("Failed to get power policy:", error);
       throw error;
     }
   },
 
   /**
-   * Reads table data with pagination
-   * @param tableName - Name of the table to read
-   * @param page - Page number (1-indexed)
-   * @param pageSize - Number of rows per page
-   * @param searchQuery - Optional search query
-   * @returns Promise resolving to table data with pagination info
+   * Changes when system sleep is blocked; applies to runs already active
    */
-  async storageReadTable(
-    tableName: string,
-    page: number,
-    pageSize: number,
-    searchQuery?: string
-  ): Promise<any> {
+  async updatePowerPolicy(policy: PowerPolicy): Promise<PowerStatus> {
     try {
-      return await invoke<any>("storage_read_table", {
-        tableName,
-        page,
-        pageSize,
-        searchQuery,
-      });
+      return await invoke<PowerStatus>("update_power_policy", { policy });
     } catch (error) {
-      console.error("Failed to read table:", error);
+      console.error("Failed to update power policy:", error);
       throw error;
     }
   },
 
   /**
-   * Updates a row in a table
-   * @param tableName - Name of the table
-   * @param primaryKeyValues - Map of primary key column names to values
-   * @param updates - Map of column names to new values
-   * @returns Promise resolving when the row is updated
+   * Gets a project's own execution config and the merged config its runs use
    */
-  async storageUpdateRow(
-    tableName: string,
-    primaryKeyValues: Record<string, any>,
-    updates: Record<string, any>
-  ): Promise<void> {
+  async getProjectExecutionConfig(projectPath: string): Promise<ProjectExecutionConfigState> {
     try {
-      return await invoke<void>("storage_update_row", {
-        tableName,
-        primaryKeyValues,
-        updates,
-      });
+      return await invoke<ProjectExecutionConfigState>("get_project_execution_config", { projectPath });
     } catch (error) {
-      console.error("Failed to update row:", error);
+      console.error("Failed to get project execution config:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a row from a table
-   * @param tableName - Name of the table
-   * @param primaryKeyValues - Map of primary key column names to values
-   * @returns Promise resolving when the row is deleted
+   * Saves a project's execution config; null removes it so the user's config applies
    */
-  async storageDeleteRow(
-    tableName: string,
-    primaryKeyValues: Record<string, any>
-  ): Promise<void> {
+  async updateProjectExecutionConfig(
+    projectPath: string,
+    config: ProjectExecutionConfig | null
+  ): Promise<ProjectExecutionConfigState> {
     try {
-      return await invoke<void>("storage_delete_row", {
-        tableName,
-        primaryKeyValues,
+      return await invoke<ProjectExecutionConfigState>("update_project_execution_config", {
+        projectPath,
+        config,
       });
     } catch (error) {
-      console.error("Failed to delete row:", error);
+      console.error("Failed to update project execution config:", error);
       throw error;
     }
   },
 
   /**
-   * Inserts a new row into a table
-   * @param tableName - Name of the table
-   * @param values - Map of column names to values
-   * @returns Promise resolving to the last insert row ID
+   * Gets the fault injection config and how many faults were injected
    */
-  async storageInsertRow(
-    tableName: string,
-    values: Record<string, any>
-  ): Promise<number> {
+  async getFaultInjection(): Promise<FaultInjectionStatus> {
     try {
-      return await invoke<number>("storage_insert_row", {
-        tableName,
-        values,
-      });
+      return await invoke<FaultInjectionStatus>("get_fault_injection");
     } catch (error) {
-      console.error("Failed to insert row:", error);
+      console.error("Failed to get fault injection:", error);
       throw error;
     }
   },
 
   /**
-   * Executes a raw SQL query
-   * @param query - SQL query string
-   * @returns Promise resolving to query result
+   * Starts injecting faults, or stops with null. Fault counters restart on every call.
    */
-  async storageExecuteSql(query: string): Promise<any> {
+  async setFaultInjection(config: FaultInjectionConfig | null): Promise<FaultInjectionStatus> {
     try {
-      return await invoke<any>("storage_execute_sql", { query });
+      return await invoke<FaultInjectionStatus>("set_fault_injection", { config });
     } catch (error) {
-      console.error("Failed to execute SQL:", error);
+      console.error("Failed to set fault injection:", error);
       throw error;
     }
   },
 
   /**
-   * Resets the entire database
-   * @returns Promise resolving when the database is reset
+   * Reports workbench files in a project that could be committed: local-only files git
+   * does not ignore, and files that appear to contain credentials
    */
-  async storageResetDatabase(): Promise<void> {
+  async auditProjectGitignore(projectPath: string): Promise<GitignoreAudit> {
     try {
-      return await invoke<void>("storage_reset_database");
+      return await invoke<GitignoreAudit>("audit_project_gitignore", { projectPath });
     } catch (error) {
-      console.error("Failed to reset database:", error);
+      console.error("Failed to audit project gitignore:", error);
       throw error;
     }
   },
 
   /**
-   * Get hooks configuration for a specific scope
-   * @param scope - The configuration scope: 'user', 'project', or 'local'
-   * @param projectPath - Project path (required for project and local scopes)
-   * @returns Promise resolving to the hooks configuration
+   * Adds the given files, or every local-only file the audit found, to the project's .gitignore
    */
-  async getHooksConfig(scope: 'user' | 'project' | 'local', projectPath?: string): Promise<HooksConfiguration> {
+  async ignoreLocalFiles(projectPath: string, paths?: string[]): Promise<GitignoreAudit> {
     try {
-      return await invoke<HooksConfiguration>("get_hooks_config", { scope, projectPath });
+      return await invoke<GitignoreAudit>("ignore_local_files", { projectPath, paths });
     } catch (error) {
-      console.error("Failed to get hooks config:", error);
+      console.error("Failed to ignore local files:", error);
       throw error;
     }
   },
 
   /**
-   * Update hooks configuration for a specific scope
-   * @param scope - The configuration scope: 'user', 'project', or 'local'
-   * @param hooks - The hooks configuration to save
-   * @param projectPath - Project path (required for project and local scopes)
-   * @returns Promise resolving to success message
+   * Allows or stops adding local-only files to project .gitignore files as they are written
    */
-  async updateHooksConfig(
-    scope: 'user' | 'project' | 'local',
-    hooks: HooksConfiguration,
-    projectPath?: string
-  ): Promise<string> {
+  async setAutoGitignore(enabled: boolean): Promise<void> {
     try {
-      return await invoke<string>("update_hooks_config", { scope, projectPath, hooks });
+      await invoke("set_auto_gitignore", { enabled });
     } catch (error) {
-      console.error("Failed to update hooks config:", error);
+      console.error("Failed to set automatic gitignore:", error);
       throw error;
     }
   },
@@ -2136,11 +5803,15 @@ export const api = {
   /**
    * Validate a hook command syntax
    * @param command - The shell command to validate
+   * @param shell - The shell the hook runs in; defaults to the platform's
    * @returns Promise resolving to validation result
    */
-  async validateHookCommand(command: string): Promise<{ valid: boolean; message: string }> {
+  async validateHookCommand(
+    command: string,
+    shell?: HookShell
+  ): Promise<{ valid: boolean; message: string }> {
     try {
-      return await invoke<{ valid: boolean; message: string }>("validate_hook_command", { command });
+      return await invoke<{ valid: boolean; message: string }>("validate_hook_command", { command, shell });
     } catch (error) {
       console.error("Failed to validate hook command:", error);
       throw error;
@@ -2338,7 +6009,8 @@ export const api = {
   },
 
   /**
-   * Switches to a new provider configuration
+   * Switches to a new provider configuration. Fails for a provider that points at
+   * claude-code-router while the router's default route fails validation.
    * @param config - The provider configuration to switch to
    * @returns Promise resolving to success message
    */
@@ -2351,6 +6023,19 @@ export const api = {
     }
   },
 
+  /**
+   * Probes every claude-code-router provider with its key and reports which will
+   * work, and whether the default route can serve requests
+   */
+  async routerValidateProviders(): Promise<RouterValidation> {
+    try {
+      return await invoke<RouterValidation>("router_validate_providers");
+    } catch (error) {
+      console.error("Failed to validate router providers:", error);
+      throw error;
+    }
+  },
+
   /**
    * Clears all provider-related environment variables
    * @returns Promise resolving to success message
@@ -2378,6 +6063,122 @@ export const api = {
     }
   },
 
+  /**
+   * Binds a session to a provider preset for all later executions, without touching global settings
+   * @param sessionId - The session to bind
+   * @param providerId - The provider preset ID, or undefined to remove the binding
+   */
+  async bindSessionProvider(sessionId: string, providerId?: string): Promise<void> {
+    try {
+      await invoke("bind_session_provider", { sessionId, providerId });
+    } catch (error) {
+      console.error("Failed to bind session provider:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the provider preset bound to a session
+   * @param sessionId - The session ID
+   * @returns Promise resolving to the provider preset ID, or null if unbound
+   */
+  async getSessionProvider(sessionId: string): Promise<string | null> {
+    try {
+      return await invoke<string | null>("get_session_provider", { sessionId });
+    } catch (error) {
+      console.error("Failed to get session provider:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Sets environment variables on every later turn of a session, on top of its provider's
+   * @param sessionId - The session ID
+   * @param vars - Variables such as API_TIMEOUT_MS or ANTHROPIC_BASE_URL; empty to remove them
+   */
+  async setSessionEnv(sessionId: string, vars: Record<string, string>): Promise<void> {
+    try {
+      await invoke("set_session_env", { sessionId, vars });
+    } catch (error) {
+      console.error("Failed to set session environment:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the environment overrides of a session
+   */
+  async getSessionEnv(sessionId: string): Promise<Record<string, string>> {
+    try {
+      return await invoke<Record<string, string>>("get_session_env", { sessionId });
+    } catch (error) {
+      console.error("Failed to get session environment:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets a provider's rotated API keys (aliases and masked values) and rotation strategy
+   */
+  async getProviderKeys(providerId: string): Promise<ProviderKeyRotation> {
+    try {
+      return await invoke<ProviderKeyRotation>("get_provider_keys", { providerId });
+    } catch (error) {
+      console.error("Failed to get provider keys:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Adds an API key to a provider's rotation, or replaces the key stored under the alias
+   * @param weight - Share of executions under the weighted strategy, 1-100
+   */
+  async addProviderKey(
+    providerId: string,
+    alias: string,
+    key: string,
+    weight?: number
+  ): Promise<ProviderKeyRotation> {
+    try {
+      return await invoke<ProviderKeyRotation>("add_provider_key", {
+        providerId,
+        alias,
+        key,
+        weight,
+      });
+    } catch (error) {
+      console.error("Failed to add provider key:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Removes an API key from a provider's rotation
+   */
+  async removeProviderKey(providerId: string, alias: string): Promise<ProviderKeyRotation> {
+    try {
+      return await invoke<ProviderKeyRotation>("remove_provider_key", { providerId, alias });
+    } catch (error) {
+      console.error("Failed to remove provider key:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Chooses how a provider's API keys take turns serving executions
+   */
+  async setRotationStrategy(
+    providerId: string,
+    strategy: RotationStrategy
+  ): Promise<ProviderKeyRotation> {
+    try {
+      return await invoke<ProviderKeyRotation>("set_rotation_strategy", { providerId, strategy });
+    } catch (error) {
+      console.error("Failed to set rotation strategy:", error);
+      throw error;
+    }
+  },
+
   /**
    * Adds a new provider configuration
    * @param config - The provider configuration to add
@@ -2896,11 +6697,12 @@ export const api = {
    * Triggers a hook event with context
    * @param event - The hook event name
    * @param context - The hook execution context
+   * @param shell - Shell for the hooks that do not set one
    * @returns Promise resolving to hook chain execution result
    */
-  async triggerHookEvent(event: string, context: any): Promise<any> {
+  async triggerHookEvent(event: string, context: any, shell?: HookShell): Promise<any> {
     try {
-      return await invoke<any>("trigger_hook_event", { event, context });
+      return await invoke<any>("trigger_hook_event", { event, context, shell });
     } catch (error) {
       console.error("Failed to trigger hook event:", error);
       throw error;
@@ -2943,4 +6745,46 @@ export const api = {
     }
   },
 
+  /**
+   * Lists the model alias table
+   * @returns Promise resolving to all model aliases
+   */
+  async listModelAliases(): Promise<ModelAlias[]> {
+    try {
+      return await invoke<ModelAlias[]>("list_model_aliases");
+    } catch (error) {
+      console.error("Failed to list model aliases:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Adds a model alias or changes its target
+   * @param alias - The model ID used by the frontend
+   * @param target - The model passed to `claude --model`
+   * @param description - Optional description
+   * @returns Promise resolving to the saved alias
+   */
+  async upsertModelAlias(alias: string, target: string, description?: string): Promise<ModelAlias> {
+    try {
+      return await invoke<ModelAlias>("upsert_model_alias", { alias, target, description });
+    } catch (error) {
+      console.error("Failed to save model alias:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes a model alias (built-in aliases are reset to their default)
+   * @param alias - The alias to delete
+   */
+  async deleteModelAlias(alias: string): Promise<void> {
+    try {
+      await invoke("delete_model_alias", { alias });
+    } catch (error) {
+      console.error("Failed to delete model alias:", error);
+      throw error;
+    }
+  },
+
 };
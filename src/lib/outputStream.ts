@@ -0,0 +1,55 @@
+/**
+ * Listener helpers for Claude / agent output streams.
+ *
+ * The backend coalesces high-volume output into `<event>-batch` events
+ * (e.g. `claude-output-batch:<sessionId>`) unless a session is in raw mode,
+ * in which case every line still arrives on the original event.
+ */
+
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { OutputBatch } from '@/types/events';
+
+export interface OutputLineEvent {
+  event: string;
+  payload: string;
+}
+
+/**
+ * Name of the batched counterpart of an output event:
+ * `claude-output:<id>` -> `claude-output-batch:<id>`, `agent-output` -> `agent-output-batch`
+ */
+export function batchEventName(event: string): string {
+  const separator = event.indexOf(':');
+  if (separator === -1) {
+    return `${event}-batch`;
+  }
+  return `${event.slice(0, separator)}-batch${event.slice(separator)}`;
+}
+
+/**
+ * Listen to an output stream line by line, regardless of whether the backend
+ * delivers single lines or batches. Lines are passed to the handler in emission order.
+ */
+export async function listenOutputLines(
+  event: string,
+  handler: (event: OutputLineEvent) => void | Promise<void>
+): Promise<UnlistenFn> {
+  // Chain handler calls so async handlers never interleave
+  let queue: Promise<void> = Promise.resolve();
+  const dispatch = (payload: string) => {
+    queue = queue
+      .then(() => handler({ event, payload }))
+      .catch((err) => console.error(`[outputStream] Handler for ${event} failed:`, err));
+  };
+
+  const unlistenLine = await listen<string>(event, (evt) => dispatch(evt.payload));
+  const unlistenBatch = await listen<OutputBatch>(batchEventName(event), (evt) => {
+    evt.payload.lines.forEach(dispatch);
+  });
+
+  return () => {
+    unlistenLine();
+    unlistenBatch();
+  };
+}
+
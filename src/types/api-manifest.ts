@@ -0,0 +1,14 @@
+/**
+ * Versioned command surface.
+ *
+ * Types in ./generated are produced from the Rust definitions by ts-rs
+ * (`cargo test` in src-tauri).
+ */
+
+export type { ApiVersionInfo } from './generated/ApiVersionInfo';
+export type { CommandCapability } from './generated/CommandCapability';
+export type { CommandDescriptor } from './generated/CommandDescriptor';
+export type { CommandManifest } from './generated/CommandManifest';
+
+/** `major.minor` of the command surface this frontend was built against */
+export const CLIENT_API_VERSION = '1.64';
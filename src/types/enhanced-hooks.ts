@@ -65,11 +65,17 @@ export interface ConditionalTrigger {
   priority?: number;      // 执行优先级
 }
 
+/**
+ * 执行hook命令的shell
+ */
+export type HookShell = 'powershell' | 'cmd' | 'bash' | 'sh';
+
 /**
  * 增强型Hook定义
  */
 export interface EnhancedHook {
   command: string;
+  shell?: HookShell;        // 未设置时Windows用PowerShell，其他系统用bash（没有时用sh）
   timeout?: number;
   retry?: number;
   condition?: ConditionalTrigger;
@@ -106,7 +112,7 @@ export interface EnhancedHooksAPI {
   /**
    * 触发Hook事件
    */
-  triggerHookEvent(event: string, context: HookContext): Promise<HookChainResult>;
+  triggerHookEvent(event: string, context: HookContext, shell?: HookShell): Promise<HookChainResult>;
 
   /**
    * 测试Hook条件
@@ -0,0 +1,123 @@
+/**
+ * Typed backend events.
+ *
+ * Payload types in ./generated are produced from the Rust definitions by ts-rs
+ * (`cargo test` in src-tauri); this file maps event names to those payloads.
+ */
+
+import { listen, type EventCallback, type UnlistenFn } from '@tauri-apps/api/event';
+import type { AccessibilityAnnouncement } from './generated/AccessibilityAnnouncement';
+import type { AgentDelegation } from './generated/AgentDelegation';
+import type { AgentDownloadProgress } from './generated/AgentDownloadProgress';
+import type { BackupProgress } from './generated/BackupProgress';
+import type { BurnRate } from './generated/BurnRate';
+import type { CheckpointProgress } from './generated/CheckpointProgress';
+import type { CostCapReached } from './generated/CostCapReached';
+import type { EventKind } from './generated/EventKind';
+import type { FileEdit } from './generated/FileEdit';
+import type { FilesChanged } from './generated/FilesChanged';
+import type { HookExecutionResult } from './generated/HookExecutionResult';
+import type { HooksKillSwitchStatus } from './generated/HooksKillSwitchStatus';
+import type { OutputBatch } from './generated/OutputBatch';
+import type { PausedRun } from './generated/PausedRun';
+import type { ProjectScanProgress } from './generated/ProjectScanProgress';
+import type { QueuedPromptStarted } from './generated/QueuedPromptStarted';
+import type { SafetyCheckpoint } from './generated/SafetyCheckpoint';
+import type { ScheduledPrompt } from './generated/ScheduledPrompt';
+import type { SessionStateEvent } from './generated/SessionStateEvent';
+import type { SettingChanged } from './generated/SettingChanged';
+import type { SpawnFailure } from './generated/SpawnFailure';
+import type { UnseenCompletionsChanged } from './generated/UnseenCompletionsChanged';
+import type { WeeklyDigest } from './generated/WeeklyDigest';
+
+export type { AccessibilityAnnouncement } from './generated/AccessibilityAnnouncement';
+export type { AccessibilityVerbosity } from './generated/AccessibilityVerbosity';
+export type { AgentDelegation } from './generated/AgentDelegation';
+export type { AgentDownloadProgress } from './generated/AgentDownloadProgress';
+export type { AnnouncementKind } from './generated/AnnouncementKind';
+export type { AnnouncementPriority } from './generated/AnnouncementPriority';
+export type { BackupOperation } from './generated/BackupOperation';
+export type { BackupProgress } from './generated/BackupProgress';
+export type { BurnRate } from './generated/BurnRate';
+export type { CheckpointProgress } from './generated/CheckpointProgress';
+export type { CostCapReached } from './generated/CostCapReached';
+export type { DestructiveKind } from './generated/DestructiveKind';
+export type { EventDescriptor } from './generated/EventDescriptor';
+export type { EventKind } from './generated/EventKind';
+export type { FileChange } from './generated/FileChange';
+export type { FileChangeKind } from './generated/FileChangeKind';
+export type { FileEdit } from './generated/FileEdit';
+export type { FilesChanged } from './generated/FilesChanged';
+export type { HookExecutionResult } from './generated/HookExecutionResult';
+export type { HooksKillSwitchStatus } from './generated/HooksKillSwitchStatus';
+export type { OutputBatch } from './generated/OutputBatch';
+export type { PausedRun } from './generated/PausedRun';
+export type { ProjectScanProgress } from './generated/ProjectScanProgress';
+export type { ProjectScanSnapshot } from './generated/ProjectScanSnapshot';
+export type { QueuedPromptStarted } from './generated/QueuedPromptStarted';
+export type { RecoveryAction } from './generated/RecoveryAction';
+export type { SafetyCheckpoint } from './generated/SafetyCheckpoint';
+export type { ScheduledPrompt } from './generated/ScheduledPrompt';
+export type { ScheduledPromptStatus } from './generated/ScheduledPromptStatus';
+export type { SessionStateEvent } from './generated/SessionStateEvent';
+export type { SessionStatus } from './generated/SessionStatus';
+export type { SettingChanged } from './generated/SettingChanged';
+export type { SpawnFailure } from './generated/SpawnFailure';
+export type { SpawnFailureCause } from './generated/SpawnFailureCause';
+export type { UnseenCompletion } from './generated/UnseenCompletion';
+export type { UnseenCompletionsChanged } from './generated/UnseenCompletionsChanged';
+export type { WeeklyDigest } from './generated/WeeklyDigest';
+
+/** Payload type of each event; must match `EventKind::descriptor` in src-tauri/src/commands/events.rs */
+export interface EventPayloads {
+  'claude-output': string;
+  'claude-output-batch': OutputBatch;
+  'claude-error': string;
+  'claude-complete': boolean;
+  'claude-cancelled': boolean;
+  'claude-session-state': SessionStateEvent;
+  'agent-output': string;
+  'agent-output-batch': OutputBatch;
+  'agent-error': string;
+  'agent-complete': boolean;
+  'agent-cancelled': boolean;
+  'agent-delegation': AgentDelegation;
+  'session-output-update': string;
+  'hook-chain-complete': HookExecutionResult[];
+  'hooks-kill-switch': HooksKillSwitchStatus;
+  'weekly-digest-ready': WeeklyDigest;
+  'projects-scan-progress': ProjectScanProgress;
+  'agent-download-progress': AgentDownloadProgress;
+  'checkpoint-progress': CheckpointProgress;
+  'queued-prompt-started': QueuedPromptStarted;
+  'safety-checkpoint-created': SafetyCheckpoint;
+  'claude-spawn-failed': SpawnFailure;
+  'backup-progress': BackupProgress;
+  'burn-rate': BurnRate;
+  'accessibility': AccessibilityAnnouncement;
+  'power-run-paused': PausedRun;
+  'file-edited': FileEdit;
+  'cost-cap-reached': CostCapReached;
+  'scheduled-prompt-started': ScheduledPrompt;
+  'scheduled-prompt-finished': ScheduledPrompt;
+  'unseen-completions': UnseenCompletionsChanged;
+  'setting-changed': SettingChanged;
+  'files-changed': FilesChanged;
+}
+
+/** Schema version the frontend was built against; compare with the event catalog */
+export const EVENT_SCHEMA_VERSION = 1;
+
+/** Event name, optionally scoped to a session or run ID */
+export function eventName(kind: EventKind, scope?: string | number): string {
+  return scope === undefined ? kind : `${kind}:${scope}`;
+}
+
+/** `listen` with the payload type derived from the event kind */
+export function listenEvent<K extends EventKind>(
+  kind: K,
+  handler: EventCallback<EventPayloads[K]>,
+  scope?: string | number
+): Promise<UnlistenFn> {
+  return listen<EventPayloads[K]>(eventName(kind, scope), handler);
+}
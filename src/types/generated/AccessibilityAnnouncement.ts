@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AnnouncementKind } from "./AnnouncementKind";
+import type { AnnouncementPriority } from "./AnnouncementPriority";
+
+/**
+ * Payload of `accessibility`
+ */
+export type AccessibilityAnnouncement = { kind: AnnouncementKind, 
+/**
+ * One sentence to read out
+ */
+message: string, priority: AnnouncementPriority, session_id?: string, run_id?: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How much the `accessibility` channel announces
+ */
+export type AccessibilityVerbosity = "off" | "minimal" | "normal" | "verbose";
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A recorded delegation between two agent runs
+ */
+export type AgentDelegation = { id: number, parent_run_id: number, child_run_id: number | null, root_run_id: number, depth: number, specialty: string, task: string, delegate_agent_id: number | null, 
+/**
+ * pending, running, completed, failed, rejected
+ */
+status: string, result: string | null, error: string | null, created_at: string, completed_at: string | null, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Payload of `agent-download-progress`
+ */
+export type AgentDownloadProgress = { url: string, downloaded: number, 
+/**
+ * Total size, when the server reports it
+ */
+total: number | null, attempt: number, done: boolean, 
+/**
+ * Served from the offline cache because the download failed
+ */
+from_cache: boolean, };
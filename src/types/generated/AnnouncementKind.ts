@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What an announcement reports
+ */
+export type AnnouncementKind = "run_started" | "files_edited" | "tool_started" | "run_completed" | "run_failed" | "run_cancelled";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How urgently a screen reader should speak an announcement, as in `aria-live`
+ */
+export type AnnouncementPriority = "polite" | "assertive";
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Result of `get_api_version`
+ */
+export type ApiVersionInfo = { 
+/**
+ * `major.minor` of the command surface
+ */
+api_version: string, app_version: string, event_schema_version: number, 
+/**
+ * Whether a client built against the requested version can use this backend
+ */
+compatible: boolean, 
+/**
+ * Enabled feature flags
+ */
+features: Array<string>, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BackupOperation } from "./BackupOperation";
+
+/**
+ * Payload of `backup-progress`
+ */
+export type BackupProgress = { operation: BackupOperation, files_done: number, files_total: number, bytes_done: number, bytes_total: number, 
+/**
+ * Archive path of the file being processed
+ */
+current: string | null, done: boolean, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Payload of `burn-rate:<session_id>`
+ */
+export type BurnRate = { session_id: string, 
+/**
+ * Model of the most recent message
+ */
+model: string, 
+/**
+ * Input and output tokens per minute over the last minute
+ */
+tokens_per_minute: number, 
+/**
+ * Estimated USD per minute over the last minute
+ */
+cost_per_minute_usd: number, 
+/**
+ * Input and output tokens since the session started streaming
+ */
+total_tokens: number, total_cost_usd: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Stage a checkpoint creation job is in
+ */
+export type CheckpointPhase = "scanning" | "snapshotting" | "saving" | "completed" | "failed" | "cancelled";
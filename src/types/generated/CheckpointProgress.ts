@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CheckpointPhase } from "./CheckpointPhase";
+
+/**
+ * Payload of `checkpoint-progress:<session_id>`
+ */
+export type CheckpointProgress = { job_id: string, session_id: string, phase: CheckpointPhase, 
+/**
+ * Files processed in the current phase
+ */
+files_processed: number, total_files: number, bytes_processed: number, 
+/**
+ * Set once the checkpoint is saved
+ */
+checkpoint_id: string | null, error: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How a command behaves beyond a plain request/response
+ */
+export type CommandCapability = "streaming" | "job" | "cancellable" | "network";
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CommandCapability } from "./CommandCapability";
+
+/**
+ * Manifest entry describing one command
+ */
+export type CommandDescriptor = { name: string, capabilities: Array<CommandCapability>, 
+/**
+ * Set on compatibility shims: the command this name was renamed to
+ */
+replaced_by: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CommandDescriptor } from "./CommandDescriptor";
+
+/**
+ * Result of `get_command_manifest`
+ */
+export type CommandManifest = { api_version: string, app_version: string, commands: Array<CommandDescriptor>, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Payload of `cost-cap-reached`, also kept on the run's status
+ */
+export type CostCapReached = { 
+/**
+ * The Claude run, or the agent run when `agent_run` is set
+ */
+run_id: number, agent_run: boolean, session_id: string | null, project_path: string, limit_usd: number, 
+/**
+ * Estimated spend when the run was stopped
+ */
+spent_usd: number, 
+/**
+ * Checkpoint taken before stopping; None if there was no session yet or it failed
+ */
+checkpoint_id: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Kind of destructive action a safety checkpoint guards against
+ */
+export type DestructiveKind = "delete" | "git-discard" | "db-migration";
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type DigestProject = { project_path: string, cost: number, tokens: number, sessions: number, active_hours: number, };
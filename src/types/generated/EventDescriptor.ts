@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EventKind } from "./EventKind";
+
+/**
+ * Catalog entry describing one event
+ */
+export type EventDescriptor = { kind: EventKind, name: string, 
+/**
+ * What the `:<scope>` suffix holds, if the event is also emitted scoped
+ */
+scope: string | null, 
+/**
+ * TypeScript type of the payload
+ */
+payload: string, version: number, description: string, };
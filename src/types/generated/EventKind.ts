@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Every event the backend emits to the frontend.
+ *
+ * Event names are the kebab-case variant names; scoped events append `:<scope>`
+ * (session ID or run ID), e.g. `claude-output:<session_id>`.
+ */
+export type EventKind = "claude-output" | "claude-output-batch" | "claude-error" | "claude-complete" | "claude-cancelled" | "claude-session-state" | "agent-output" | "agent-output-batch" | "agent-error" | "agent-complete" | "agent-cancelled" | "agent-delegation" | "session-output-update" | "hook-chain-complete" | "hooks-kill-switch" | "weekly-digest-ready" | "projects-scan-progress" | "agent-download-progress" | "checkpoint-progress" | "queued-prompt-started" | "safety-checkpoint-created" | "claude-spawn-failed" | "backup-progress" | "burn-rate" | "accessibility" | "power-run-paused" | "file-edited" | "cost-cap-reached" | "scheduled-prompt-started" | "scheduled-prompt-finished" | "unseen-completions" | "setting-changed" | "files-changed";
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FileChangeKind } from "./FileChangeKind";
+
+/**
+ * A single file change passed to OnFileChange hooks
+ */
+export type FileChange = { 
+/**
+ * Path relative to the project root, using forward slashes
+ */
+path: string, change_type: FileChangeKind, };
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One file edit made by a tool call, as announced by `file-edited`.
+ *
+ * Line counts are estimated from the tool input when the call starts, before the tool
+ * has run; checkpoint diffs remain the exact record.
+ */
+export type FileEdit = { session_id: string, 
+/**
+ * Edit, MultiEdit or Write
+ */
+tool: string, file_path: string, additions: number, deletions: number, tool_use_id: string | null, created_at: string, };
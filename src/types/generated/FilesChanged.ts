@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FileChange } from "./FileChange";
+
+/**
+ * Payload of `files-changed`: one debounced batch of changes in a watched project
+ */
+export type FilesChanged = { project_path: string, changes: Array<FileChange>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Hook执行结果
+ */
+export type HookExecutionResult = { success: boolean, output: string, error: string | null, execution_time_ms: number, hook_command: string, output_truncated: boolean, };
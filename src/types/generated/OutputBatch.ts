@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Payload of a batched output event
+ */
+export type OutputBatch = { 
+/**
+ * Lines in emission order
+ */
+lines: Array<string>, 
+/**
+ * Per-event sequence number, lets listeners detect gaps
+ */
+seq: number, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Payload of `power-run-paused`
+ */
+export type PausedRun = { session_id: string, project_path: string, 
+/**
+ * Checkpoint the session can be restored from; None if creating it failed
+ */
+checkpoint_id: string | null, };
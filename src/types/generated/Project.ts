@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Represents a project in the ~/.claude/projects directory
+ */
+export type Project = { 
+/**
+ * The project ID (derived from the directory name)
+ */
+id: string, 
+/**
+ * The original project path (decoded from the directory name)
+ */
+path: string, 
+/**
+ * List of session IDs (JSONL file names without extension)
+ */
+sessions: Array<string>, 
+/**
+ * Unix timestamp of the latest activity (session modification or project creation)
+ */
+created_at: number, 
+/**
+ * IDs of other directories for the same path whose sessions were merged into this project
+ */
+merged_from: Array<string>, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Project } from "./Project";
+
+/**
+ * Payload of `projects-scan-progress` and `get_project_scan`
+ */
+export type ProjectScanProgress = { token: string, scanned: number, total: number, done: boolean, 
+/**
+ * Progress events carry only projects scanned since the previous event;
+ * `get_project_scan` and the final event carry the merged, sorted list
+ */
+projects: Array<Project>, };
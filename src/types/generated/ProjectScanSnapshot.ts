@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Project } from "./Project";
+
+/**
+ * Returned by `start_project_scan` before the scan finishes
+ */
+export type ProjectScanSnapshot = { 
+/**
+ * Identifies the scan in progress events and `get_project_scan`
+ */
+token: string, 
+/**
+ * Number of project directories being scanned
+ */
+total: number, 
+/**
+ * Projects from the previous completed scan, to show while this one runs
+ */
+projects: Array<Project>, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Payload of `queued-prompt-started:<session_id>`
+ */
+export type QueuedPromptStarted = { session_id: string, prompt_id: string, prompt: string, 
+/**
+ * Prompts still queued after this one
+ */
+remaining: number, };
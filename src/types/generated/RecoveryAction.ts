@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Something the user can do to fix a failed launch
+ */
+export type RecoveryAction = { "type": "run-command", label: string, command: string, } | { "type": "open-settings", label: string, section: string, } | { "type": "run-doctor", label: string, } | { "type": "open-url", label: string, url: string, };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DestructiveKind } from "./DestructiveKind";
+
+/**
+ * Copies of the files a destructive tool call was about to touch, taken when the
+ * call appeared in the stream. Payload of `safety-checkpoint-created:<session_id>`.
+ */
+export type SafetyCheckpoint = { id: string, session_id: string, project_path: string, 
+/**
+ * The `tool_use` block that triggered the snapshot
+ */
+tool_use_id: string | null, command: string, kind: DestructiveKind, created_at: string, 
+/**
+ * Snapshotted files, relative to the project
+ */
+files: Array<string>, 
+/**
+ * Some files were left out because of the size limits
+ */
+truncated: boolean, restored_at: string | null, };
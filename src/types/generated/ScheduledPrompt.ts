@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ScheduledPromptStatus } from "./ScheduledPromptStatus";
+
+/**
+ * A prompt run once at a set time in a new session; payload of
+ * `scheduled-prompt-started` and `scheduled-prompt-finished`
+ */
+export type ScheduledPrompt = { id: number, project_path: string, prompt: string, model: string, 
+/**
+ * RFC 3339, UTC
+ */
+run_at: string, 
+/**
+ * Skip the prompt if its estimated input cost exceeds this, and stop the run
+ * once its spend does
+ */
+max_cost_usd: number | null, status: ScheduledPromptStatus, 
+/**
+ * The Claude run, once started
+ */
+run_id: number | null, session_id: string | null, 
+/**
+ * Why the prompt failed or was skipped
+ */
+error: string | null, created_at: string, started_at: string | null, finished_at: string | null, };
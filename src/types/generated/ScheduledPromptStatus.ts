@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where a scheduled prompt is in its lifecycle
+ */
+export type ScheduledPromptStatus = "pending" | "running" | "completed" | "failed" | "cancelled" | "skipped" | "cost_cap_reached";
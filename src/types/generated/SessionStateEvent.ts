@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SessionStatus } from "./SessionStatus";
+
+/**
+ * Payload of `claude-session-state`
+ */
+export type SessionStateEvent = { session_id: string, status: SessionStatus, project_path?: string, model?: string, pid?: number, run_id?: number, success?: boolean, error?: string, };
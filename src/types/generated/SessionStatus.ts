@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Lifecycle status reported by `claude-session-state`
+ */
+export type SessionStatus = "started" | "stopped" | "paused" | "resumed";
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RecoveryAction } from "./RecoveryAction";
+import type { SpawnFailureCause } from "./SpawnFailureCause";
+
+/**
+ * A classified launch failure. Payload of `claude-spawn-failed`.
+ */
+export type SpawnFailure = { cause: SpawnFailureCause, 
+/**
+ * One sentence explaining the cause
+ */
+summary: string, 
+/**
+ * The original error or stderr output
+ */
+detail: string, 
+/**
+ * Most useful first
+ */
+actions: Array<RecoveryAction>, };
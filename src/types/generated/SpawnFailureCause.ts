@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Why the Claude CLI could not be started
+ */
+export type SpawnFailureCause = "binary-missing" | "node-version" | "path-issue" | "permission-denied" | "antivirus-blocked" | "unknown";
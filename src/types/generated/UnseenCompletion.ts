@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A run that finished while nobody was looking
+ */
+export type UnseenCompletion = { 
+/**
+ * The session ID, or `agent-run:<id>` for an agent run that never reported one
+ */
+id: string, session_id: string | null, agent_run_id: number | null, project_path: string, success: boolean, completed_at: string, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UnseenCompletion } from "./UnseenCompletion";
+
+/**
+ * Payload of `unseen-completions`
+ */
+export type UnseenCompletionsChanged = { count: number, 
+/**
+ * The completion just recorded; None when one was marked seen
+ */
+completion: UnseenCompletion | null, };
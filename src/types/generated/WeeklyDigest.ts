@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DigestAgentStats } from "./DigestAgentStats";
+import type { DigestProject } from "./DigestProject";
+import type { DigestSession } from "./DigestSession";
+
+/**
+ * Compiled digest data plus the paths of the rendered reports
+ */
+export type WeeklyDigest = { start_date: string, end_date: string, generated_at: string, total_cost: number, 
+/**
+ * `total_cost` in `currency`
+ */
+total_cost_local: number, 
+/**
+ * Display currency when the digest was generated
+ */
+currency: string, total_tokens: number, total_sessions: number, active_hours: number, cost_by_model: Array<[string, number]>, top_projects: Array<DigestProject>, costliest_sessions: Array<DigestSession>, longest_sessions: Array<DigestSession>, agents: Array<DigestAgentStats>, anomalies: Array<string>, markdown_path: string | null, html_path: string | null, };